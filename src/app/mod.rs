@@ -1,210 +1,1169 @@
 pub mod state;
 
-use anyhow::Result;
-use crossterm::event::{Event, EventStream};
+use anyhow::{Context, Result};
+use crossterm::event::{Event, KeyCode};
 use rspotify::prelude::Id;
-use futures::StreamExt;
-use std::{sync::Arc, time::Duration};
-use tokio::{sync::Mutex, sync::mpsc, time};
+use futures::{Stream, StreamExt};
+use chrono::{DateTime, Utc};
+use std::{collections::HashSet, future::Future, io, sync::Arc, time::Duration};
+use tokio::{sync::Mutex, sync::mpsc, sync::watch, time};
 use tracing::{info, warn};
 
+use rspotify::model::{AudioFeatures, Device, FullTrack, Offset, PlayHistory, PlaylistItem, SavedAlbum, SavedTrack, SimplifiedPlaylist};
+
 use crate::{
-    app::state::{ActiveScreen, AppState, CurrentTrack, Notification},
-    cache::Cache,
-    config::Config,
-    events::{map_key_to_action, UserAction},
+    artwork::{fetch_art, Cover, Rgb},
+    clipboard::{self, TrackInfo},
+    app::state::{
+        capped_bpm, parse_decade, playlist_entry_key, replay_gain_delta, restore_removed, screen_order, step_filtered, take_matching, ActiveScreen, AppState, ArchiveView, BrowseState, CurrentTrack, HistoryEntry, DedupeView, DevicesView, FeatureProfile, LogView, LibraryTab, MoodView, Notification, PendingWrite, PlaylistPicker, PrefetchedPage, Prompt,
+        ListFilter, LoadStatus, PromptKind, QueueRow, RadarTrack, RadarView, ResumePoint, RetryTarget, SearchState, SearchTab, Startup, StartupLoad, StatsView, VibesState, VisualSelect,
+    },
+    cache::{
+        library::Cached,
+        snapshots::{self, PlaylistDiff, PlaylistSnapshot},
+        Cache,
+    },
+    capture,
+    config::{Config, OpenIn, QuoteSource, SidebarPanel, VibesMood},
+    connect::{self, LocalDevice},
+    daemon::Screen,
+    lyrics::{self, LyricLine},
+    quotes::{self, QuoteCache},
+    mpd,
+    remote::{self, Pairing, RemoteStatus},
+    events::{map_key_to_action, Keymap, SheetFormat, UserAction},
+    features::{Feature, Features},
+    hooks::{self, HookEvent, HookTrack},
+    index::{
+        features::{self, FeatureIndex, IndexEntry, TrackFeatures},
+        filter::MoodFilter,
+        LibraryIndex,
+    },
+    ipc,
+    jobs::{JobId, JobKind},
+    secrets,
+    session::{
+        archive::{self, PlaylistArchive},
+        inbox,
+        plays::{self, DayRecap, ExportFormat, Play},
+        searches,
+        trace::{self, TraceEvent, Tracer},
+        write_atomic, NowPlaying, QueueEntry, SessionSummary,
+    },
+    ui::{anim::Animations, text::format_duration, theme::Theme},
     spotify::{
-        build_spotify_client, complete_auth,
-        auth::wait_for_auth_code,
+        authorize_url, build_spotify_client, capabilities::{self, Capabilities}, complete_auth, is_unauthorized, retry, TokenStore,
+        analysis::Analysis,
+        auth::{bind_redirect, wait_for_auth_code, PkceChallenge},
         library::Library,
-        player::Player,
+        player::{find_device, PlayableBatch, Player},
         queue::Queue,
         search::Search,
+        stats::Stats,
         vibes::Vibes,
     },
+    widget,
 };
 
-const TICK_MS: u64 = 80;         // UI tick (animations, EQ bars) — slightly faster
-const SLOW_TICK_MS: u64 = 2000;  // Playback polling — less aggressive
+/// Gap between the calls when `Q` queues a whole Vibes list
+const ENQUEUE_PACE: Duration = Duration::from_millis(250);
+
+/// Gap between `audio-features` batches while the library is indexed
+const INDEX_PACE: Duration = Duration::from_secs(3);
+/// Longest wait between retries after an indexing batch fails
+const INDEX_MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// Failed batches in a row before the indexer gives up until next time
+const INDEX_RETRIES: u32 = 4;
+
+/// Liked Songs pages between re-sorts while the rest streams in; sorting and
+/// reindexing the whole list on every page would make a big library slow
+const LIKED_RESORT_PAGES: usize = 20;
+
+/// What a background playback poll found, and how long Spotify took to say
+enum PlaybackPoll {
+    Playing { track: Box<CurrentTrack>, took_ms: u64 },
+    /// No playback (or the request failed), with the device list if that could be read
+    Missing { devices: Option<Vec<String>>, took_ms: u64 },
+    /// Spotify no longer accepts the login (token expired or revoked)
+    Unauthorized { took_ms: u64 },
+}
+
+/// The on-disk audio-features index, when `feature_index` is on
+fn load_feature_index(config: &Config) -> FeatureIndex {
+    let Some(path) = features::default_path().filter(|_| config.feature_index) else {
+        return FeatureIndex::default();
+    };
+    FeatureIndex::load(&path).unwrap_or_else(|e| {
+        warn!("Feature index load error: {e}");
+        FeatureIndex::default()
+    })
+}
+
+/// Sidebar quotes at startup. The API source starts from the last cached
+/// response, even a stale one, until `refresh_quotes` has today's
+fn load_quotes(config: &Config) -> Vec<String> {
+    match config.quote_source {
+        QuoteSource::Builtin => config.quotes.clone(),
+        QuoteSource::File => {
+            let Some(ref path) = config.quote_file else {
+                warn!("quote_source = \"file\" needs quote_file");
+                return Vec::new();
+            };
+            quotes::load_file(path).unwrap_or_else(|e| {
+                warn!("Quote file error: {e}");
+                Vec::new()
+            })
+        }
+        QuoteSource::Api => quotes::cache_path().and_then(|p| quotes::load_cache(&p)).map(|c| c.quotes).unwrap_or_default(),
+        QuoteSource::History => quotes::history_facts(chrono::Local::now().date_naive()),
+    }
+}
+
+fn load_search_history() -> Vec<String> {
+    let Some(path) = searches::default_path() else { return Vec::new() };
+    searches::load(&path).unwrap_or_else(|e| {
+        warn!("Search history load error: {e}");
+        Vec::new()
+    })
+}
+
+/// Fire-and-forget: poll playback in the background and feed the result back
+fn spawn_playback_poll(spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, tx: mpsc::Sender<PlaybackPoll>) {
+    tokio::spawn(async move {
+        let player = Player::new(spotify);
+        let asked = std::time::Instant::now();
+        let playback = player.get_current_playback().await;
+        let took_ms = asked.elapsed().as_millis() as u64;
+        let poll = match playback {
+            Ok(Some(ct)) => PlaybackPoll::Playing { track: Box::new(ct), took_ms },
+            Err(ref e) if is_unauthorized(e) => PlaybackPoll::Unauthorized { took_ms },
+            Ok(None) | Err(_) => PlaybackPoll::Missing { devices: player.device_ids().await.ok(), took_ms },
+        };
+        let _ = tx.send(poll).await;
+    });
+}
+
+/// Where a list put on screen came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListSource {
+    Spotify,
+    /// The cache, while it's fresh
+    Cache,
+    /// The cache's last copy, Spotify being unreachable
+    OfflineCopy,
+}
+
+/// A list as fetched, with how many items Spotify has in all
+struct FetchedList<T> {
+    list: Cached<T>,
+    total: usize,
+    source: ListSource,
+}
+
+/// Liked Songs from the cache while it's fresh (unless `fresh`), else their
+/// first page from Spotify, else the cache's offline copy
+async fn fetch_liked_songs(cache: Arc<Cache>, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, ttl_secs: u64, fresh: bool) -> Result<FetchedList<SavedTrack>> {
+    let cached = if fresh { None } else { cache.get_liked_songs(ttl_secs).await };
+    if let Some(list) = cached {
+        return Ok(FetchedList { total: list.items.len(), list, source: ListSource::Cache });
+    }
+    match Library::new(spotify).liked_songs_page(0).await {
+        Ok((items, total)) => Ok(FetchedList { list: Cached { fetched_at: Utc::now(), items }, total, source: ListSource::Spotify }),
+        Err(e) => {
+            let Some(list) = cache.offline_liked_songs().await else { return Err(e) };
+            warn!("Library load error: {e}");
+            Ok(FetchedList { total: list.items.len(), list, source: ListSource::OfflineCopy })
+        }
+    }
+}
+
+/// Every playlist, from the same places as `fetch_liked_songs`, with the
+/// ids of those changed since they were last opened
+async fn fetch_playlists(
+    cache: Arc<Cache>,
+    spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>,
+    ttl_secs: u64,
+    fresh: bool,
+) -> Result<(FetchedList<SimplifiedPlaylist>, Vec<String>)> {
+    let cached = if fresh { None } else { cache.get_playlists(ttl_secs).await };
+    let (list, source) = match cached {
+        Some(list) => (list, ListSource::Cache),
+        None => match Library::new(spotify).get_user_playlists().await {
+            Ok(items) => (Cached { fetched_at: Utc::now(), items }, ListSource::Spotify),
+            Err(e) => {
+                let Some(list) = cache.offline_playlists().await else { return Err(e) };
+                warn!("Playlists load error: {e}");
+                (list, ListSource::OfflineCopy)
+            }
+        },
+    };
+    let mut updated = Vec::new();
+    for pl in &list.items {
+        let id = pl.id.id();
+        if let Some(seen) = cache.get_playlist_snapshot(id).await {
+            if seen.snapshot_id != pl.snapshot_id {
+                updated.push(id.to_string());
+            }
+        }
+    }
+    Ok((FetchedList { total: list.items.len(), list, source }, updated))
+}
+
+/// Results of fire-and-forget background tasks, routed back into the event loop
+enum BgEvent {
+    AudioFeatures { track_id: String, features: AudioFeatures },
+    AlbumArt { track_id: Option<String>, accent: Option<Rgb>, cover: Option<Cover> },
+    QueuePreview(Vec<FullTrack>),
+    /// Radar popup data; `None` when Spotify wouldn't say
+    RadarFeatures { track_id: String, profile: Option<FeatureProfile> },
+    Capabilities(Capabilities),
+    Lyrics { track_id: Option<String>, lines: Option<Vec<LyricLine>> },
+    /// Today's response from the quotes API
+    Quotes(Vec<String>),
+    /// Outcome of an optimistic playlist removal; `removed` is put back on error
+    PlaylistRemoval {
+        playlist_id: String,
+        name: String,
+        removed: Vec<(usize, PlaylistItem)>,
+        result: Result<String>,
+    },
+    /// Outcome of appending the Vibes recommendations to the queue
+    Enqueued { added: Vec<String>, total: usize, result: Result<()> },
+    /// Outcome of resuming on another device; `point` is kept for a retry on error
+    Resumed { point: ResumePoint, result: Result<()> },
+    /// Devices for the `gd` popup
+    Devices(Result<Vec<Device>>),
+    /// Outcome of moving playback to the device called `name`
+    Transferred { name: String, play: bool, result: Result<()> },
+    /// The built-in Connect device registered, or why it didn't
+    LocalDevice(Result<LocalDevice>),
+    /// Audio capture is listening, or why it isn't
+    Capture(Result<watch::Receiver<[u8; capture::BARS]>>),
+    /// First page of a highlighted playlist, and how many tracks it has in all
+    PlaylistPrefetched { playlist_id: String, snapshot_id: String, result: Result<(Vec<PlaylistItem>, usize)> },
+    /// Every track of a playlist opened from its prefetched first page
+    PlaylistTracks { playlist_id: String, result: Result<Vec<PlaylistItem>> },
+    /// What changed in an opened playlist since the last visit
+    PlaylistDiffed { playlist_id: String, name: String, diff: PlaylistDiff },
+    /// A further page of Liked Songs, and how many there are in all; `page`
+    /// counts from 1 within one paging run
+    LikedPage { songs: Vec<SavedTrack>, total: usize, page: usize },
+    /// Liked Songs paging stopped: everything arrived, or why not
+    LikedPagesDone(Result<()>),
+    /// Liked Songs membership of listed tracks, for the row hearts
+    LikedStatus(Vec<(String, bool)>),
+    /// Outcome of sending the offline outbox; `liked_songs` is the reloaded
+    /// list when it held likes, and `writes` go back in the outbox on error
+    OutboxFlushed { writes: Vec<PendingWrite>, liked_songs: Option<Vec<SavedTrack>>, result: Result<()> },
+    /// Playlists archived and unfollowed so far; `result` is why the rest weren't
+    Archived { job: JobId, archived: Vec<String>, result: Result<()> },
+    /// Outcome of restoring `entry`; `playlists` is the reloaded list on success
+    Restored { job: JobId, entry: Box<PlaylistArchive>, playlists: Option<Vec<SimplifiedPlaylist>>, result: Result<()> },
+    /// Outcome of `:export`: the file written and how many plays it holds
+    Exported { job: JobId, result: Result<(std::path::PathBuf, usize)> },
+    /// Liked Songs and saved albums loaded for `:dedupe`; `None` for what was
+    /// loaded already or failed to
+    DedupeLoaded { job: JobId, liked: Option<Vec<SavedTrack>>, albums: Option<Vec<SavedAlbum>>, result: Result<()> },
+    /// Details of inbox tracks, for the popup rows
+    InboxTracks(Vec<FullTrack>),
+    /// Spotify's recently played, to merge into the history and play log
+    RecentlyPlayed(Result<Vec<PlayHistory>>),
+    /// The signed-in user's Spotify id
+    Profile(Result<String>),
+    /// The library's playlists, with the ids of those changed since last opened
+    Playlists(Result<(FetchedList<SimplifiedPlaylist>, Vec<String>)>),
+    /// The first page of Liked Songs
+    LikedSongs(Result<FetchedList<SavedTrack>>),
+    /// One batch fetched by the library indexer
+    FeaturesIndexed(Vec<IndexEntry>),
+    /// The indexer stopped: everything was fetched, it was cancelled, or why not
+    FeatureIndexDone { job: JobId, result: Result<()> },
+}
 
 pub struct App {
     pub state: AppState,
     config: Config,
     cache: Arc<Cache>,
+    keymap: Keymap,
+    bg_tx: mpsc::UnboundedSender<BgEvent>,
+    bg_rx: Option<mpsc::UnboundedReceiver<BgEvent>>,
+    /// Last summary written to `config.session_file`
+    last_session: Option<SessionSummary>,
+    /// Last line written to `config.status_file`
+    last_status_line: Option<String>,
+    /// Feeds now-playing to the web remote and the MPD server while they're serving
+    remote_status: Option<watch::Sender<RemoteStatus>>,
+    /// Modification time of the inbox file when last loaded
+    inbox_seen: Option<std::time::SystemTime>,
+    /// The audio-features indexer was cancelled from the Jobs panel; it stays
+    /// off until the next launch
+    index_cancelled: bool,
+    /// Last mood filter applied, to start the `M` prompt from
+    mood_expr: String,
+    /// `:trace on`: playback polls, track changes and skips go to a file
+    tracer: Option<Tracer>,
+    /// Set by `:profile <name>`: once this session has wound down, start
+    /// another logged in as that profile
+    pub switch_profile: Option<String>,
+    /// Set under `vibes daemon`: quitting only lets the attached terminal go
+    pub daemon: Option<Screen>,
+    /// `vibes ctl quit`: stop even as a daemon
+    shutdown: bool,
+    /// Spotify stopped accepting the login; the loop opens the login screen
+    needs_login: bool,
+    /// The login screen was quit mid-session; it stays shut until `:login`
+    login_declined: bool,
+    /// Spotify is back after lists were shown from the offline copy; the
+    /// loop fetches them again
+    reload_library: bool,
+    /// 429s already toasted, against `retry::rate_limit_hits`
+    rate_limits_seen: u64,
+    /// Vibes itself as a Connect device, while it's registered
+    local_device: Option<LocalDevice>,
+    /// Bar levels from audio capture, while it's listening
+    capture: Option<watch::Receiver<[u8; capture::BARS]>>,
+    /// When the playlists and Liked Songs on screen came from Spotify, so
+    /// writing them back to the cache doesn't extend their TTL
+    playlists_fetched: Option<DateTime<Utc>>,
+    liked_fetched: Option<DateTime<Utc>>,
 }
 
 impl App {
     pub async fn new(config: Config, cache: Arc<Cache>) -> Result<Self> {
+        let (bg_tx, bg_rx) = mpsc::unbounded_channel();
+        let keymap = Keymap::from_config(&config.keybinds, &config.chords);
+        let screens = screen_order(&config.screens);
+        let state = AppState {
+            active_screen: screens[0].clone(),
+            screens,
+            replay_gain: config.replay_gain,
+            mini: config.mini,
+            visualizer: config.visualizer,
+            sidebar_panel: config.sidebar_panel,
+            quotes: load_quotes(&config),
+            vibes: VibesState { moods: VibesMood::all(&config.moods), ..Default::default() },
+            theme: Theme::from_config(&config.theme, &config.colors),
+            color_depth: config.color_depth.resolve(),
+            features: Features::from_config(&config.features),
+            toast_settings: config.notifications,
+            toast_ticks: config.notifications.ticks(config.tick_ms),
+            anims: Animations { reduce_motion: config.reduce_motion, ..Default::default() },
+            feature_index: load_feature_index(&config),
+            search: SearchState { history: load_search_history(), ..Default::default() },
+            ..AppState::default()
+        };
         Ok(App {
-            state: AppState::default(),
+            state,
             config,
             cache,
+            keymap,
+            bg_tx,
+            bg_rx: Some(bg_rx),
+            last_session: None,
+            last_status_line: None,
+            remote_status: None,
+            inbox_seen: None,
+            index_cancelled: false,
+            mood_expr: String::new(),
+            tracer: None,
+            switch_profile: None,
+            daemon: None,
+            shutdown: false,
+            needs_login: false,
+            login_declined: false,
+            reload_library: false,
+            rate_limits_seen: 0,
+            local_device: None,
+            capture: None,
+            playlists_fetched: None,
+            liked_fetched: None,
         })
     }
 
-    pub async fn run<B: ratatui::backend::Backend>(
-        &mut self,
-        terminal: &mut ratatui::Terminal<B>,
-    ) -> Result<()> {
-        // ── Spotify Auth ─────────────────────────────────────────────────────
-        let (spotify_arc, auth_url) = build_spotify_client(&self.config, &self.cache).await?;
-
-        if let Some(ref url) = auth_url {
-            self.state.auth_url = Some(url.clone());
-            // Open browser
-            if let Err(e) = open::that(url) {
-                warn!("Could not open browser: {e}");
-            }
+    /// Runs until quit, drawing to `terminal` and taking keys from `events`
+    pub async fn run<B, E>(&mut self, terminal: &mut ratatui::Terminal<B>, events: &mut E) -> Result<()>
+    where
+        B: ratatui::backend::Backend,
+        E: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        self.refresh_quotes();
 
-            // Draw auth screen first
-            terminal.draw(|f| crate::ui::render(f, &self.state))?;
+        // ── Spotify Auth ─────────────────────────────────────────────────────
+        let keyring = self.state.features.enabled(Feature::Keyring).then(|| secrets::token_entry(&self.config));
+        let cache = self.cache.clone();
+        let tokens = TokenStore { cache: &cache, keyring };
+        let (spotify_arc, logged_in) = build_spotify_client(&self.config, &tokens).await?;
 
-            // Generate PKCE verifier (we need to store it)
-            let pkce = crate::spotify::auth::PkceChallenge::new();
-            // Wait for the redirect
-            let auth_result = wait_for_auth_code().await?;
-            complete_auth(spotify_arc.clone(), &auth_result.code, &pkce.verifier, &self.cache).await?;
+        if !logged_in && !self.log_in(terminal, events, &spotify_arc, &tokens).await? {
+            return Ok(());
         }
 
         self.state.is_authenticated = true;
         self.state.auth_url = None;
+        self.state.auth_redirect = None;
+        self.state.auth_error = None;
         self.state.spotify = Some(spotify_arc.clone());
-        self.state.set_notification(Notification::info("Connected to Spotify ✓"));
+        let connected = match self.config.profile {
+            Some(ref profile) => format!("Connected to Spotify as {profile} ✓"),
+            None => "Connected to Spotify ✓".to_string(),
+        };
+        self.state.set_notification(Notification::info(connected));
         info!("Authenticated successfully");
 
-        // ── Load initial data (in background) ────────────────────────────────
-        self.load_playlists(spotify_arc.clone()).await;
-        self.load_library(spotify_arc.clone()).await;
+        // ── Load initial data in the background, the panel showing progress ──
+        self.state.startup = Some(Startup::default());
+        let (tx, sp) = (self.bg_tx.clone(), spotify_arc.clone());
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::Profile(Library::new(sp).current_user_id().await));
+        });
+        self.spawn_load_playlists(spotify_arc.clone());
+        self.spawn_load_library(spotify_arc.clone());
+
+        let tx = self.bg_tx.clone();
+        let sp = spotify_arc.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::Capabilities(capabilities::probe(sp).await));
+        });
+        let (tx, sp) = (self.bg_tx.clone(), spotify_arc.clone());
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::RecentlyPlayed(Stats::new(sp).recently_played().await));
+        });
+
+        self.start_local_device(spotify_arc.clone()).await;
+        self.start_capture();
+
+        if self.config.resume_on_launch {
+            self.resume_last_session(spotify_arc.clone()).await;
+        }
+
+        self.check_inbox(spotify_arc.clone());
+
+        // ── Web remote and MPD server: their commands arrive as ordinary actions
+        let (remote_tx, mut remote_rx) = mpsc::unbounded_channel::<UserAction>();
+        self.start_remote(remote_tx.clone()).await;
+        self.start_mpd(remote_tx.clone()).await;
+        self.start_control_socket(remote_tx).await;
+
+        if self.config.trace {
+            if let Err(e) = self.start_tracing() {
+                self.state.set_notification(Notification::failure(format!("Trace not started: {e}"), &e));
+            }
+        }
 
         // ── Background playback channel ──────────────────────────────────────
-        let (pb_tx, mut pb_rx) = mpsc::channel::<CurrentTrack>(4);
+        let (pb_tx, mut pb_rx) = mpsc::channel::<PlaybackPoll>(4);
+        let mut bg_rx = self.bg_rx.take().expect("App::run called twice");
 
         // ── Main event loop ───────────────────────────────────────────────────
-        let mut tick_interval = time::interval(Duration::from_millis(TICK_MS));
-        let mut slow_interval = time::interval(Duration::from_millis(SLOW_TICK_MS));
-        let mut event_stream = EventStream::new();
+        let tick_ms = self.config.tick_ms;
+        let mut tick_interval = time::interval(Duration::from_millis(tick_ms));
+        let mut slow_interval = time::interval(Duration::from_millis(self.config.poll_ms));
 
         loop {
-            // Draw
-            terminal.draw(|f| crate::ui::render(f, &self.state))?;
+            if std::mem::take(&mut self.needs_login) {
+                self.log_in_again(terminal, events, &spotify_arc, &tokens).await?;
+            }
+            if std::mem::take(&mut self.reload_library) {
+                self.reload_library(spotify_arc.clone()).await;
+            }
+            self.request_liked_status(spotify_arc.clone());
+            self.prefetch_playlist(spotify_arc.clone());
+            // Draw, unless it's a daemon nobody is attached to
+            if self.daemon.as_ref().is_none_or(Screen::is_attached) {
+                let frame = terminal.draw(|f| crate::ui::render(f, &self.state))?;
+                self.state.page_size = crate::ui::page_size(frame.area, &self.state);
+            }
 
             // Wait for next event
             tokio::select! {
                 _ = tick_interval.tick() => {
+                    self.state.tick_tempo(tick_ms);
+                    self.state.tick_startup(tick_ms);
+                    self.state.pending_key = self.keymap.pending();
+                    if let Some(n) = self.state.tick_row_jump(tick_ms) {
+                        self.handle_action(UserAction::SwitchScreen(n), spotify_arc.clone()).await;
+                    }
+                    self.poll_capture();
+                    if let Some(view) = self.state.log_view.as_mut() {
+                        view.follow();
+                    }
+                    self.note_rate_limit();
                     self.state.update_eq_bars();
                     self.state.tick_ticker();
                     self.state.tick_notification();
+                    self.state.tick_accent();
+                    self.state.anims.tick();
+                    self.write_status_line();
                     // Auto-increment progress for smooth bar movement
-                    if self.state.current_track.is_playing {
-                        self.state.current_track.progress_ms =
-                            (self.state.current_track.progress_ms + TICK_MS as u32)
-                                .min(self.state.current_track.duration_ms);
+                    if self.state.tick_progress(tick_ms as u32) {
+                        let ct = &self.state.current_track;
+                        self.trace(TraceEvent::TrackEnded { track: ct.id.clone(), progress_ms: ct.progress_ms });
+                        // Track just ran out: pick up the next one now, not in up to 2s
+                        spawn_playback_poll(spotify_arc.clone(), pb_tx.clone());
+                        slow_interval.reset();
                     }
                 }
                 _ = slow_interval.tick() => {
-                    // Fire-and-forget: spawn background task to poll playback
-                    let sp = spotify_arc.clone();
-                    let tx = pb_tx.clone();
-                    tokio::spawn(async move {
-                        let player = Player::new(sp);
-                        if let Ok(Some(ct)) = player.get_current_playback().await {
-                            let _ = tx.send(ct).await;
-                        }
-                    });
+                    spawn_playback_poll(spotify_arc.clone(), pb_tx.clone());
+                    self.check_inbox(spotify_arc.clone());
                 }
-                Some(ct) = pb_rx.recv() => {
-                    // Sync volume from Spotify device
-                    if let Some(vol) = ct.device_volume {
-                        self.state.volume = vol;
+                Some(poll) = pb_rx.recv() => {
+                    self.trace_poll(&poll);
+                    match poll {
+                        PlaybackPoll::Playing { track, .. } => self.apply_playback(*track, spotify_arc.clone()),
+                        PlaybackPoll::Missing { devices, .. } => self.note_playback_missing(devices, spotify_arc.clone()),
+                        PlaybackPoll::Unauthorized { .. } => self.needs_login = !self.login_declined,
                     }
-                    self.state.current_track = ct;
                 }
-                maybe_event = event_stream.next() => {
+                Some(action) = remote_rx.recv() => {
+                    self.handle_action(action, spotify_arc.clone()).await;
+                    self.publish_remote_status();
+                }
+                Some(ev) = bg_rx.recv() => {
+                    self.handle_bg_event(ev, spotify_arc.clone());
+                    self.write_session();
+                    self.publish_remote_status();
+                }
+                maybe_event = events.next() => {
                     if let Some(Ok(Event::Key(key))) = maybe_event {
-                        let search_active = self.state.search.is_searching;
-                        if let Some(action) = map_key_to_action(key, search_active) {
-                            self.handle_action(action, spotify_arc.clone()).await;
+                        let search_active = self.state.is_typing();
+                        let action = if self.state.error_details.is_some() {
+                            Some(UserAction::Back) // any key dismisses the details popup
+                        } else if search_active {
+                            map_key_to_action(key, search_active, &mut self.keymap)
+                        } else {
+                            match self.toast_action(key.code) {
+                                Some(action) => action,
+                                None => map_key_to_action(key, search_active, &mut self.keymap),
+                            }
+                        };
+                        self.state.pending_key = self.keymap.pending();
+                        if let Some(action) = action {
+                            let seq = self.state.notification_seq;
+                            let target = self.retry_target();
+                            self.handle_action(action.clone(), spotify_arc.clone()).await;
+                            // Remember what failed, and on what, so Enter on the toast can retry it
+                            if self.state.notification_seq != seq {
+                                if let Some(n) = self.state.latest_notification_mut().filter(|n| n.is_error()) {
+                                    n.retry.get_or_insert((action, target));
+                                }
+                            }
                         }
+                    } else if let (Some(Ok(Event::FocusGained)), Some(_)) = (maybe_event, &self.daemon) {
+                        // A terminal just attached to the daemon: it starts out blank
+                        terminal.clear()?;
+                        self.state.set_notification(Notification::info("Attached — q detaches, playback carries on"));
                     }
                 }
             }
 
             if self.state.should_quit {
-                break;
+                match self.daemon {
+                    Some(ref screen) if !self.shutdown && self.switch_profile.is_none() => {
+                        screen.detach();
+                        self.state.should_quit = false;
+                    }
+                    _ => break,
+                }
             }
         }
 
+        // Likes, follows and the like made this session go into the cache too
+        self.cache_playlists().await;
+        self.cache_liked_songs().await;
         Ok(())
     }
 
+    /// A poll found playback: sync it into state
+    fn apply_playback(&mut self, mut ct: CurrentTrack, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.connection.note_ok() {
+            self.reconnected(spotify.clone());
+        }
+        self.state.note_playback_seen(ct.progress_ms, ct.is_playing);
+        ct.is_liked = ct.id.as_deref().is_some_and(|uri| self.state.liked.contains(uri));
+        // Sync volume from Spotify device
+        if let Some(vol) = ct.device_volume {
+            self.state.sync_device_volume(vol);
+        }
+        let track_changed = ct.id != self.state.current_track.id;
+        // Same track from the top right after it ended: repeat-one
+        let restarted = !track_changed && self.state.track_ended && ct.progress_ms < 5_000;
+        let previous = std::mem::replace(&mut self.state.current_track, ct);
+        self.state.note_play_state();
+        if track_changed || restarted {
+            self.trace(TraceEvent::TrackChange {
+                from: previous.id.clone(),
+                to: self.state.current_track.id.clone(),
+                from_progress_ms: previous.progress_ms,
+                from_duration_ms: previous.duration_ms,
+                restarted,
+            });
+            let play = self.state.push_history(&previous)
+                .filter(|entry| entry.scrobble && !previous.is_episode)
+                .map(Play::from);
+            if let Some(play) = play {
+                self.log_play(&play);
+            }
+            self.run_hook(HookEvent::TrackChange, HookTrack::from(&self.state.current_track));
+        } else if previous.is_playing && !self.state.current_track.is_playing {
+            // Paused somewhere else; a pause from here already ran the hook
+            self.run_hook(HookEvent::Pause, HookTrack::from(&self.state.current_track));
+        }
+        if track_changed {
+            if let Some(ref uri) = self.state.current_track.id {
+                self.state.queue.manual.remove(uri);
+            }
+            self.on_track_change(spotify);
+        }
+        self.write_session();
+        self.publish_remote_status();
+    }
+
+    /// A poll found no playback; warn once if the playing device vanished.
+    /// No device list either means Spotify couldn't be reached at all.
+    fn note_playback_missing(&mut self, devices: Option<Vec<String>>, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if devices.is_some() {
+            if self.state.connection.note_ok() {
+                self.reconnected(spotify);
+            }
+        } else if self.state.connection.note_failure() {
+            self.state.set_notification(Notification::warning(
+                "Offline — Spotify can't be reached. Browsing what's loaded; likes are saved for later",
+            ));
+        }
+        let offline = self.state.connection.offline;
+        let point = self.state.note_playback_missing(devices.as_deref());
+        // Offline, the outage is the news, not the device
+        if let Some(point) = point.filter(|_| !offline) {
+            let msg = format!(
+                "{} disconnected — paused at {}. Press Space to continue on another device",
+                point.device_name,
+                format_duration(point.position_ms),
+            );
+            self.state.set_notification(Notification::warning(msg));
+            self.publish_remote_status();
+        }
+    }
+
+    /// Spotify answered again after an outage: recheck what was left
+    /// unanswered and send the likes made while offline
+    fn reconnected(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.liked.forget_pending();
+        self.reload_library = std::mem::take(&mut self.state.connection.offline_copy);
+        let writes = std::mem::take(&mut self.state.connection.outbox);
+        if writes.is_empty() {
+            self.state.set_notification(Notification::info("Back online"));
+            return;
+        }
+        self.state.set_notification(Notification::info(format!("Back online — syncing {} like(s)", writes.len())));
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let (likes, unlikes): (Vec<&PendingWrite>, Vec<&PendingWrite>) =
+                writes.iter().partition(|w| matches!(w, PendingWrite::Like(_)));
+            let uris = |ws: Vec<&PendingWrite>| ws.into_iter().map(|w| w.uri().to_string()).collect::<Vec<_>>();
+            let lib = Library::new(spotify);
+            let mut result = lib.like_tracks(&uris(likes.clone())).await;
+            if result.is_ok() {
+                result = lib.unlike_tracks(&uris(unlikes)).await;
+            }
+            // Reload so songs liked offline show up with their dates
+            let liked_songs = match result {
+                Ok(()) if !likes.is_empty() => lib.liked_songs_page(0).await.ok().map(|(songs, _)| songs),
+                _ => None,
+            };
+            let _ = tx.send(BgEvent::OutboxFlushed { writes, liked_songs, result });
+        });
+    }
+
+    /// Enter retries and `d` opens details while an error toast is showing.
+    /// `Some` when the toast took the key, with the action to run if any.
+    fn toast_action(&mut self, key: KeyCode) -> Option<Option<UserAction>> {
+        let n = self.state.notifications.iter().rev().find(|n| n.is_error())?;
+        match key {
+            KeyCode::Enter if n.retry.is_some() => {
+                let (action, target) = self.state.take_error_notification()?.retry?;
+                if self.aim_at(&target) {
+                    return Some(Some(action));
+                }
+                self.state.set_notification(Notification::warning("What that was aimed at isn't listed any more"));
+                Some(None)
+            }
+            KeyCode::Char('d') if n.details.is_some() => Some(Some(UserAction::ShowErrorDetails)),
+            _ => None,
+        }
+    }
+
+    /// Where an action would land right now, see `RetryTarget`
+    fn retry_target(&self) -> RetryTarget {
+        RetryTarget {
+            screen: self.state.active_screen.clone(),
+            playlist_id: self.open_playlist_id(),
+            uri: self.selected_item().map(|(uri, _)| uri),
+        }
+    }
+
+    /// Goes back to `target`'s screen and row for a retry; false when its
+    /// playlist was closed or the row is gone
+    fn aim_at(&mut self, target: &RetryTarget) -> bool {
+        if self.retry_target() == *target {
+            return true;
+        }
+        // Play/pause and the like aren't aimed at a row
+        let Some(ref uri) = target.uri else { return true };
+        self.state.navigate_to(target.screen.clone());
+        let len = self.state.list_len();
+        if self.open_playlist_id() != target.playlist_id || len == usize::MAX {
+            return false;
+        }
+        self.navigate_up(usize::MAX);
+        for _ in 0..len {
+            if self.selected_item().is_some_and(|(u, _)| u == *uri) {
+                return true;
+            }
+            self.navigate_down(1);
+        }
+        false
+    }
+
     // ── Action handler ────────────────────────────────────────────────────────
     async fn handle_action(&mut self, action: UserAction, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        // A typed row number ends with Enter (jump), Esc (cancel) or any other key (dropped)
+        if !matches!(action, UserAction::RowDigit(_)) {
+            if let Some(jump) = self.state.row_jump.take() {
+                match action {
+                    UserAction::Select => return self.jump_to_row(jump.row()),
+                    UserAction::Back => return,
+                    _ => {}
+                }
+            }
+        }
+        if matches!(
+            action,
+            UserAction::TogglePlay
+                | UserAction::NextTrack
+                | UserAction::PrevTrack
+                | UserAction::SeekForward
+                | UserAction::SeekBackward
+                | UserAction::SeekForwardLarge
+                | UserAction::SeekBackwardLarge
+                | UserAction::SeekPercent(_)
+        ) {
+            let ct = &self.state.current_track;
+            self.trace(TraceEvent::Command { action: format!("{action:?}"), track: ct.id.clone(), progress_ms: ct.progress_ms });
+        }
         match action {
+            UserAction::TogglePlay if self.state.visual.is_some() => self.mark_selected(),
+            // Ahead of the offline check: ending the `/` filter needs no network
+            UserAction::SearchSubmit if self.state.filter.typing => {
+                self.state.filter.typing = false;
+                if self.state.filter.query.trim().is_empty() {
+                    self.state.filter = ListFilter::default();
+                }
+            }
+            // Also ahead of it: cancelling a job is local
+            UserAction::RemoveFromPlaylist if self.state.show_jobs => self.cancel_job(),
+            action if self.state.connection.offline && action.needs_network() => {
+                self.state.set_notification(Notification::warning("Offline — that needs Spotify. It'll work again once reconnected"));
+            }
             UserAction::Quit => {
                 self.state.should_quit = true;
             }
+            UserAction::Shutdown => {
+                self.shutdown = true;
+                self.state.should_quit = true;
+            }
+            UserAction::ToggleVisualSelect => {
+                if self.state.visual.take().is_some() {
+                    self.state.set_notification(Notification::info("Visual select off"));
+                } else {
+                    self.state.visual = Some(VisualSelect::default());
+                    self.state.set_notification(Notification::info(
+                        "Visual select: Space marks rows, then l likes, a queues, P adds them to a playlist",
+                    ));
+                }
+            }
+            UserAction::LikeTrack if !self.state.marked_tracks().is_empty() => self.like_marked(spotify.clone()).await,
+            UserAction::AddToQueue if !self.state.marked_tracks().is_empty() => self.enqueue_marked(spotify.clone()),
+            UserAction::AddToPlaylist if !self.state.marked_tracks().is_empty() => {
+                if self.state.editable_playlists().is_empty() {
+                    self.state.set_notification(Notification::info("No playlists you can add to — press c to create one"));
+                } else {
+                    let track_uris = self.state.marked_tracks();
+                    let track_name = format!("{} tracks", track_uris.len());
+                    self.state.visual = None;
+                    self.state.picker = Some(PlaylistPicker { track_uris, track_name, selected: 0 });
+                }
+            }
+            UserAction::ArchivePlaylists => self.archive_playlists(spotify.clone()),
+            UserAction::AddToQueue if self.state.inbox.open => self.queue_from_inbox(spotify.clone()).await,
+            UserAction::AddToPlaylist if self.state.inbox.open => {
+                let inbox = &self.state.inbox;
+                let selected = inbox.selected_item().map(|item| {
+                    let name = inbox.tracks.get(&item.uri).map_or_else(|| item.uri.clone(), |t| t.name.clone());
+                    (item.uri.clone(), name)
+                });
+                if let Some((track_uri, track_name)) = selected {
+                    if self.state.editable_playlists().is_empty() {
+                        self.state.set_notification(Notification::info("No playlists you can add to — press c to create one"));
+                    } else {
+                        self.state.picker = Some(PlaylistPicker { track_uris: vec![track_uri], track_name, selected: 0 });
+                    }
+                }
+            }
+            UserAction::RemoveFromPlaylist if self.state.inbox.open => {
+                if let Some(uri) = self.state.inbox.selected_item().map(|i| i.uri.clone()) {
+                    self.drop_from_inbox(&[uri]);
+                }
+            }
             UserAction::ToggleHelp => {
                 self.state.show_help = !self.state.show_help;
             }
-            UserAction::SwitchScreen(n) => {
-                self.state.show_help = false;
-                match n {
-                    1 => { self.state.navigate_to(ActiveScreen::Search); self.state.search.is_searching = false; }
-                    2 => { self.state.navigate_to(ActiveScreen::Library); self.load_library(spotify.clone()).await; }
-                    3 => { self.state.navigate_to(ActiveScreen::Playlists); self.load_playlists(spotify.clone()).await; }
-                    4 => { self.state.navigate_to(ActiveScreen::Queue); self.load_queue(spotify.clone()).await; }
-                    5 => { self.state.navigate_to(ActiveScreen::Vibes); }
-                    _ => {}
+            UserAction::SwitchScreen(n) => self.switch_screen(n, spotify.clone()).await,
+            UserAction::GoTo(screen) => self.go_to_screen(screen, spotify.clone()).await,
+            UserAction::GoToPlayingContext => self.go_to_playing_context(spotify.clone()).await,
+            UserAction::RowDigit(d) => {
+                if let Some(n) = self.state.push_row_digit(d) {
+                    self.switch_screen(n, spotify.clone()).await;
                 }
             }
             UserAction::OpenSearch => {
                 self.state.navigate_to(ActiveScreen::Search);
                 self.state.search.is_searching = true;
             }
+            UserAction::CycleSidebarPanel => {
+                let panel = self.state.sidebar_panel.next();
+                self.state.sidebar_panel = panel;
+                if panel == SidebarPanel::Lyrics && self.state.lyrics.is_none() {
+                    self.fetch_lyrics();
+                }
+                self.state.set_notification(Notification::info(format!("Sidebar: {panel:?}")));
+                self.config.sidebar_panel = panel;
+                if let Err(e) = Config::save_setting("sidebar_panel", &panel) {
+                    warn!("Could not save config: {e}");
+                }
+            }
+            UserAction::CycleVisualizer => {
+                let visualizer = self.state.visualizer.next();
+                self.state.visualizer = visualizer;
+                self.state.set_notification(Notification::info(format!("Visualizer: {visualizer:?}")));
+                self.config.visualizer = visualizer;
+                if let Err(e) = Config::save_setting("visualizer", &visualizer) {
+                    warn!("Could not save config: {e}");
+                }
+            }
+            UserAction::TogglePlaylistDiff => {
+                if self.state.playlists.diff.is_some() {
+                    self.state.playlists.show_diff = !self.state.playlists.show_diff;
+                } else if self.state.active_screen == ActiveScreen::Playlists {
+                    self.state.set_notification(Notification::info("No changes since your last visit"));
+                }
+            }
+            UserAction::ShowErrorDetails => {
+                if let Some(n) = self.state.take_error_notification() {
+                    self.state.error_details = n.details;
+                }
+            }
             UserAction::Back => {
-                if self.state.search.is_searching {
+                if self.state.error_details.is_some() {
+                    self.state.error_details = None;
+                } else if self.state.prompt.is_some() {
+                    self.state.prompt = None;
+                } else if self.state.picker.is_some() {
+                    self.state.picker = None;
+                } else if self.state.dedupe.is_some() {
+                    self.state.dedupe = None;
+                } else if self.state.archived.is_some() {
+                    self.state.archived = None;
+                } else if self.state.devices.is_some() {
+                    self.state.devices = None;
+                } else if self.state.log_view.is_some() {
+                    self.state.log_view = None;
+                } else if self.state.mood.is_some() {
+                    self.state.mood = None;
+                } else if self.state.inbox.open {
+                    self.state.inbox.open = false;
+                } else if self.state.search.show_history {
+                    self.state.search.show_history = false;
+                } else if self.state.show_jobs {
+                    self.state.show_jobs = false;
+                } else if self.state.radar.is_some() {
+                    self.state.radar = None;
+                } else if self.state.show_features {
+                    self.state.show_features = false;
+                } else if self.state.show_messages {
+                    self.state.show_messages = false;
+                } else if self.state.playlists.show_diff {
+                    self.state.playlists.show_diff = false;
+                } else if self.state.focus {
+                    self.state.focus = false;
+                } else if self.state.visual.is_some() {
+                    self.state.visual = None;
+                } else if self.state.shown_filter().is_some() {
+                    self.state.filter = ListFilter::default();
+                } else if self.state.search.is_searching {
                     self.state.search.is_searching = false;
                 } else if self.state.playlists.viewing_tracks {
                     self.state.playlists.viewing_tracks = false;
+                } else if self.state.active_screen == ActiveScreen::Library && self.state.library.viewing_album {
+                    self.state.library.viewing_album = false;
+                } else if self.state.active_screen == ActiveScreen::Library && self.state.library.artist_top_tracks.is_some() {
+                    self.state.library.artist_top_tracks = None;
+                } else if self.state.active_screen == ActiveScreen::Browse && self.state.browse.viewing_playlists {
+                    self.state.browse.viewing_playlists = false;
                 } else if self.state.show_help {
                     self.state.show_help = false;
                 }
             }
-            UserAction::SearchInput(c) => {
-                self.state.search.query.push(c);
-            }
+            UserAction::SearchInput(c) => match self.state.prompt.as_mut() {
+                Some(prompt) => prompt.input.push(c),
+                None if self.state.filter.typing => {
+                    self.state.filter.query.push(c);
+                    self.state.snap_to_filter();
+                }
+                None => {
+                    self.state.search.recall = None;
+                    self.state.search.query.push(c);
+                }
+            },
             UserAction::SearchBackspace => {
-                self.state.search.query.pop();
+                match self.state.prompt.as_mut() {
+                    Some(prompt) => prompt.input.pop(),
+                    None if self.state.filter.typing => {
+                        let popped = self.state.filter.query.pop();
+                        self.state.snap_to_filter();
+                        popped
+                    }
+                    None => {
+                        self.state.search.recall = None;
+                        self.state.search.query.pop()
+                    }
+                };
+            }
+            // ↑ / ↓ still move through the rows while typing a filter
+            UserAction::RecallOlderSearch if self.state.filter.typing => self.navigate_up(1),
+            UserAction::RecallNewerSearch if self.state.filter.typing => self.navigate_down(1),
+            UserAction::RecallOlderSearch | UserAction::RecallNewerSearch if self.state.prompt.is_none() => {
+                self.state.search.recall(action == UserAction::RecallOlderSearch);
+            }
+            UserAction::RecallOlderSearch | UserAction::RecallNewerSearch => {}
+            UserAction::FilterList => match self.state.filterable_list() {
+                Some(list) => {
+                    if self.state.filter.list != Some(list) {
+                        self.state.filter = ListFilter { list: Some(list), ..Default::default() };
+                    }
+                    self.state.filter.typing = true;
+                }
+                None => self.state.set_notification(Notification::info("/ filters Liked Songs, Playlists and the Queue")),
+            },
+            UserAction::CycleSort => self.cycle_sort(),
+            UserAction::RefreshLibrary => self.refresh_library(spotify.clone()).await,
+            UserAction::OpenJobs => self.open_jobs(),
+            UserAction::OpenDevices => self.open_devices(spotify.clone()),
+            UserAction::OpenLog => self.state.log_view = Some(LogView::open(self.config.log_path())),
+            UserAction::TransferPlayback { device, play } => self.transfer_to_named(device, play, spotify.clone()),
+            UserAction::OpenSearchHistory => {
+                if self.state.search.history.is_empty() {
+                    self.state.set_notification(Notification::info("No past searches yet"));
+                } else {
+                    self.state.search.show_history = true;
+                    self.state.search.selected_history = 0;
+                }
+            }
+            UserAction::SearchSubmit if self.state.prompt.is_some() => {
+                if let Some(prompt) = self.state.prompt.take() {
+                    self.submit_prompt(prompt, spotify.clone()).await;
+                }
+            }
+            UserAction::CreatePlaylist if self.state.mood.is_some() => self.save_mood_playlist(spotify.clone()).await,
+            UserAction::CreatePlaylist => {
+                self.state.prompt = Some(Prompt::new(PromptKind::NewPlaylist));
+            }
+            UserAction::MoodFilter => {
+                self.state.prompt = Some(Prompt { kind: PromptKind::MoodFilter, input: self.mood_expr.clone() });
+            }
+            UserAction::SwitchTab => match self.state.active_screen {
+                ActiveScreen::Search => self.state.search.tab = self.state.search.tab.next(),
+                ActiveScreen::Stats => {
+                    let stats = &mut self.state.stats;
+                    stats.view = match stats.view {
+                        StatsView::Tracks => StatsView::Artists,
+                        StatsView::Artists => StatsView::Tracks,
+                    };
+                    stats.selected = 0;
+                }
+                ActiveScreen::Library => {
+                    self.state.library.tab = self.state.library.tab.next();
+                    match self.state.library.tab {
+                        LibraryTab::Albums => self.load_saved_albums(spotify.clone()).await,
+                        LibraryTab::Artists => self.load_followed_artists(spotify.clone()).await,
+                        LibraryTab::Liked => {}
+                    }
+                }
+                _ => {}
+            },
+            UserAction::FollowPlaylist => self.follow_playlist(spotify.clone()).await,
+            UserAction::CopyTrackInfo => self.copy_track_info(),
+            UserAction::CopyTrackLink => self.copy_track_link(false),
+            UserAction::CopyTrackUri => self.copy_track_link(true),
+            UserAction::OpenInSpotify => self.open_in_spotify(),
+            UserAction::OpenInbox => self.open_inbox(spotify.clone()),
+            UserAction::ShowTrackFeatures => self.open_radar(spotify.clone()),
+            UserAction::StartRadio => self.start_radio(spotify.clone()).await,
+            UserAction::EnqueueAll => self.enqueue_recommendations(spotify.clone()),
+            UserAction::ReplayOnThisDay => self.replay_on_this_day(spotify.clone()).await,
+            UserAction::OpenCommand => {
+                self.state.prompt = Some(Prompt::new(PromptKind::Command));
+            }
+            UserAction::AddToPlaylist => {
+                if let Some((track_uri, track_name)) = self.selected_track() {
+                    if self.state.editable_playlists().is_empty() {
+                        self.state.set_notification(Notification::info("No playlists you can add to — press c to create one"));
+                    } else {
+                        self.state.picker = Some(PlaylistPicker { track_uris: vec![track_uri], track_name, selected: 0 });
+                    }
+                }
             }
+            UserAction::RemoveFromPlaylist if self.state.search.show_history => self.forget_search(),
+            UserAction::RemoveFromPlaylist if self.state.dedupe.is_some() => {
+                self.unlike_album_duplicates(spotify.clone()).await;
+            }
+            UserAction::RemoveFromPlaylist
+                if self.state.active_screen == ActiveScreen::Library && self.state.library.tab == LibraryTab::Artists =>
+            {
+                self.unfollow_artist(spotify.clone()).await;
+            }
+            UserAction::RemoveFromPlaylist if self.state.active_screen == ActiveScreen::Queue => {
+                self.clear_from_queue(spotify.clone()).await;
+            }
+            UserAction::RemoveFromPlaylist => self.remove_from_playlist(spotify.clone()),
+            UserAction::Select if self.state.dedupe.is_some() => {
+                self.like_whole_album(spotify.clone()).await;
+            }
+            UserAction::Select if self.state.archived.is_some() => self.restore_archived(spotify.clone()),
+            UserAction::Select if self.state.devices.is_some() => self.transfer_selected(true, spotify.clone()),
+            UserAction::Select if self.state.picker.is_some() => {
+                if let Some(picker) = self.state.picker.take() {
+                    self.submit_picker(picker, spotify.clone()).await;
+                }
+            }
+            UserAction::Select if self.state.mood.is_some() => self.play_mood_matches(spotify.clone()).await,
+            UserAction::Select if self.state.inbox.open => self.play_from_inbox(spotify.clone()).await,
+            UserAction::Select if self.state.search.show_history => self.rerun_search(spotify.clone()).await,
+            UserAction::Search(query) => {
+                self.state.search.query = query;
+                self.state.search.show_history = false;
+                self.state.navigate_to(ActiveScreen::Search);
+                self.do_search(spotify.clone()).await;
+            }
+            UserAction::QueueUri(uri) => match Queue::new(spotify.clone()).add_to_queue(&uri).await {
+                Ok(_) => {
+                    self.state.queue.manual.insert(uri.clone());
+                    self.state.set_notification(Notification::info(format!("Queued {uri} ✓")));
+                }
+                Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+            },
             UserAction::SearchSubmit => {
                 self.state.search.is_searching = false;
                 if !self.state.search.query.is_empty() {
                     self.do_search(spotify.clone()).await;
                 }
             }
-            UserAction::NavigateUp => self.navigate_up(),
-            UserAction::NavigateDown => self.navigate_down(),
+            UserAction::NavigateUp => self.navigate_up(1),
+            UserAction::NavigateDown => self.navigate_down(1),
+            UserAction::PageUp => self.navigate_up(self.state.page_size),
+            UserAction::PageDown => self.navigate_down(self.state.page_size),
+            UserAction::JumpTop => self.navigate_up(usize::MAX),
+            UserAction::JumpBottom => self.navigate_down(usize::MAX),
+            UserAction::JumpToPlaying => {
+                if self.state.current_track.id.is_none() {
+                    self.state.set_notification(Notification::info("Nothing is playing"));
+                } else if !self.state.select_playing() {
+                    self.state.set_notification(Notification::info("Now playing track isn't in this list"));
+                }
+            }
+            UserAction::NavigateLeft if self.state.active_screen == ActiveScreen::Stats => {
+                self.state.stats.range = self.state.stats.range.prev();
+                self.load_stats(spotify.clone()).await;
+            }
+            UserAction::NavigateRight if self.state.active_screen == ActiveScreen::Stats => {
+                self.state.stats.range = self.state.stats.range.next();
+                self.load_stats(spotify.clone()).await;
+            }
             UserAction::NavigateLeft => {
                 if self.state.active_screen == ActiveScreen::Playlists && self.state.playlists.viewing_tracks {
                     self.state.playlists.viewing_tracks = false;
                 }
+                if self.state.active_screen == ActiveScreen::Browse {
+                    self.state.browse.viewing_playlists = false;
+                }
+                if self.state.active_screen == ActiveScreen::Library {
+                    self.state.library.viewing_album = false;
+                    self.state.library.artist_top_tracks = None;
+                }
             }
             UserAction::NavigateRight => {
                 if self.state.active_screen == ActiveScreen::Playlists && !self.state.playlists.viewing_tracks {
                     self.state.playlists.viewing_tracks = true;
                 }
+                if self.state.active_screen == ActiveScreen::Browse && !self.state.browse.viewing_playlists {
+                    self.open_category(spotify.clone()).await;
+                }
+                if self.state.active_screen == ActiveScreen::Library
+                    && self.state.library.tab == LibraryTab::Artists
+                    && self.state.library.artist_top_tracks.is_none()
+                {
+                    self.load_artist_top_tracks(spotify.clone()).await;
+                }
+                let library = &mut self.state.library;
+                if self.state.active_screen == ActiveScreen::Library
+                    && library.tab == LibraryTab::Albums
+                    && !library.viewing_album
+                    && library.open_album().is_some()
+                {
+                    library.viewing_album = true;
+                    library.selected_album_track = 0;
+                }
             }
             UserAction::Select => self.handle_select(spotify.clone()).await,
+            UserAction::TogglePlay if self.state.devices.is_some() => self.transfer_selected(false, spotify.clone()),
+            UserAction::TogglePlay if self.state.resume_point.is_some() => {
+                let Some(point) = self.state.resume_point.take() else { return };
+                self.state.current_track.is_playing = true;
+                // The old device is known gone; the next poll reports the new one
+                self.state.current_track.device_id = None;
+                self.state.note_play_state();
+                self.state.set_notification(Notification::info(format!("Resuming at {}", format_duration(point.position_ms))));
+                let tx = self.bg_tx.clone();
+                tokio::spawn(async move {
+                    let result = Player::new(spotify).play_at(&point.uri, point.context_uri.as_deref(), point.position_ms).await;
+                    let _ = tx.send(BgEvent::Resumed { point, result });
+                });
+            }
             UserAction::TogglePlay => {
                 let is_playing = self.state.current_track.is_playing;
+                let paused_ms = self.state.paused_at.map_or(0, |t| t.elapsed().as_millis() as u64);
+                let track = &self.state.current_track;
+                let rewind = if is_playing { 0 } else { self.config.smart_resume.rewind_ms(paused_ms, track.is_episode) };
+                let rewind_to = (rewind > 0).then(|| track.progress_ms.saturating_sub(rewind));
                 self.state.current_track.is_playing = !is_playing; // Optimistic UI update
-                let msg = if is_playing { "Paused" } else { "Resumed" };
+                self.state.note_play_state();
+                if is_playing {
+                    self.run_hook(HookEvent::Pause, HookTrack::from(&self.state.current_track));
+                }
+                let msg = match rewind_to {
+                    _ if is_playing => "Paused".to_string(),
+                    Some(pos) => {
+                        self.state.current_track.progress_ms = pos;
+                        format!("Resumed {}s back", rewind / 1000)
+                    }
+                    None => "Resumed".to_string(),
+                };
                 self.state.set_notification(Notification::info(msg));
-                
+
                 let sp = spotify.clone();
                 tokio::spawn(async move {
                     let player = Player::new(sp);
-                    let _ = player.toggle_playback(is_playing).await;
+                    let _ = player.toggle_playback(is_playing, rewind_to).await;
                 });
             }
             UserAction::NextTrack => {
@@ -234,165 +1193,1388 @@ impl App {
                     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
                 });
             }
-            UserAction::VolumeUp => {
-                let new_vol = (self.state.volume as u16 + 5).min(100) as u8;
-                self.state.volume = new_vol;
-                self.state.set_notification(Notification::info(format!("Volume: {new_vol}%")));
-                let sp = spotify.clone();
-                tokio::spawn(async move {
-                    let player = Player::new(sp);
-                    let _ = player.set_volume(new_vol).await;
-                });
-            }
-            UserAction::VolumeDown => {
-                let new_vol = self.state.volume.saturating_sub(5);
-                self.state.volume = new_vol;
-                self.state.set_notification(Notification::info(format!("Volume: {new_vol}%")));
+            UserAction::VolumeUp => self.change_volume(self.config.steps.volume as i16, spotify.clone()),
+            UserAction::VolumeDown => self.change_volume(-(self.config.steps.volume as i16), spotify.clone()),
+            UserAction::VolumeUpLarge => self.change_volume(self.config.steps.volume_large as i16, spotify.clone()),
+            UserAction::VolumeDownLarge => self.change_volume(-(self.config.steps.volume_large as i16), spotify.clone()),
+            UserAction::ToggleMute => {
+                let new_vol = self.state.toggle_mute();
+                let msg = if self.state.muted_volume.is_some() { "Muted 🔇".to_string() } else { format!("Unmuted: {new_vol}%") };
+                self.state.set_notification(Notification::info(msg));
                 let sp = spotify.clone();
                 tokio::spawn(async move {
                     let player = Player::new(sp);
                     let _ = player.set_volume(new_vol).await;
                 });
             }
-            UserAction::LikeTrack => {
-                if let Some(ref id) = self.state.current_track.id.clone() {
-                    let player = Player::new(spotify.clone());
-                    if self.state.current_track.is_liked {
-                        if player.remove_track(id).await.is_ok() {
-                            self.state.current_track.is_liked = false;
-                            self.state.set_notification(Notification::info("Removed from Liked Songs"));
-                        }
-                    } else if player.save_track(id).await.is_ok() {
-                        self.state.current_track.is_liked = true;
-                        self.state.set_notification(Notification::info("❤ Added to Liked Songs"));
-                    }
-                }
-            }
+            UserAction::LikeTrack => self.toggle_like(spotify.clone()).await,
             UserAction::AddToQueue => {
                 self.handle_add_to_queue(spotify.clone()).await;
             }
-            UserAction::SeekForward => {
-                let new_pos = (self.state.current_track.progress_ms + 10_000)
-                    .min(self.state.current_track.duration_ms);
-                self.state.current_track.progress_ms = new_pos;
-                let sp = spotify.clone();
-                tokio::spawn(async move {
-                    let player = Player::new(sp);
-                    let _ = player.seek(new_pos).await;
-                });
+            UserAction::PlayNext => {
+                self.handle_play_next(spotify.clone()).await;
             }
-            UserAction::SeekBackward => {
-                let new_pos = self.state.current_track.progress_ms.saturating_sub(10_000);
-                self.state.current_track.progress_ms = new_pos;
+            UserAction::SeekForward => self.seek_by(self.config.steps.seek_secs as i64 * 1000, spotify.clone()),
+            UserAction::SeekBackward => self.seek_by(-(self.config.steps.seek_secs as i64) * 1000, spotify.clone()),
+            UserAction::SeekForwardLarge => self.seek_by(self.config.steps.seek_large_secs as i64 * 1000, spotify.clone()),
+            UserAction::SeekBackwardLarge => self.seek_by(-(self.config.steps.seek_large_secs as i64) * 1000, spotify.clone()),
+            UserAction::SeekPercent(percent) if self.state.current_track.duration_ms > 0 => {
+                let track = &mut self.state.current_track;
+                let new_pos = (track.duration_ms as u64 * percent as u64 / 100) as u32;
+                track.progress_ms = new_pos;
                 let sp = spotify.clone();
                 tokio::spawn(async move {
                     let player = Player::new(sp);
                     let _ = player.seek(new_pos).await;
                 });
             }
+            UserAction::SeekPercent(_) => {}
             UserAction::ToggleEQ => {
                 self.state.eq_expanded = !self.state.eq_expanded;
                 let mode = if self.state.eq_expanded { "Expanded" } else { "Compact" };
                 self.state.set_notification(Notification::info(format!("EQ: {mode}")));
             }
-
-        }
-    }
-
-    // ── Navigation helpers ────────────────────────────────────────────────────
-    fn navigate_up(&mut self) {
-        match self.state.active_screen {
-            ActiveScreen::Search => {
-                if self.state.search.selected_track > 0 {
-                    self.state.search.selected_track -= 1;
+            UserAction::ToggleFocus => {
+                self.state.focus = !self.state.focus;
+                if self.state.focus && self.state.lyrics.is_none() {
+                    self.fetch_lyrics();
                 }
-            }
-            ActiveScreen::Library => {
-                if self.state.library.selected > 0 {
-                    self.state.library.selected -= 1;
+                if self.state.focus && self.state.cover.is_none() {
+                    self.fetch_art();
                 }
             }
-            ActiveScreen::Playlists => {
-                if self.state.playlists.viewing_tracks {
-                    if self.state.playlists.selected_track > 0 {
-                        self.state.playlists.selected_track -= 1;
-                    }
-                } else if self.state.playlists.selected_playlist > 0 {
-                    self.state.playlists.selected_playlist -= 1;
-                }
+            UserAction::ToggleMini => {
+                self.state.mini = !self.state.mini;
+                self.state.focus = false;
             }
-            ActiveScreen::Queue => {
-                if self.state.queue.selected > 0 {
-                    self.state.queue.selected -= 1;
+            UserAction::ToggleReplayGain => {
+                self.state.replay_gain = !self.state.replay_gain;
+                self.config.replay_gain = self.state.replay_gain;
+                if let Err(e) = Config::save_setting("replay_gain", &self.config.replay_gain) {
+                    warn!("Could not save config: {e}");
                 }
-            }
-            ActiveScreen::Vibes => {
-                if self.state.vibes.selected_mood > 0 && !self.state.vibes.recommendations.is_empty() {
-                    // In track list
-                    if self.state.vibes.selected_track > 0 {
-                        self.state.vibes.selected_track -= 1;
-                    }
-                } else if self.state.vibes.selected_mood > 0 {
-                    self.state.vibes.selected_mood -= 1;
+                if self.state.replay_gain {
+                    self.state.set_notification(Notification::info("Volume leveling: On"));
+                    self.fetch_audio_features(spotify.clone());
+                } else {
+                    // Drop the current nudge so the user's own volume comes back
+                    let new_vol = self.state.apply_replay_gain(0);
+                    self.state.set_notification(Notification::info("Volume leveling: Off"));
+                    let sp = spotify.clone();
+                    tokio::spawn(async move {
+                        let player = Player::new(sp);
+                        let _ = player.set_volume(new_vol).await;
+                    });
                 }
             }
 
         }
     }
 
-    fn navigate_down(&mut self) {
-        match self.state.active_screen {
-            ActiveScreen::Search => {
-                let max = self.state.search.tracks.len().saturating_sub(1);
-                if self.state.search.selected_track < max {
-                    self.state.search.selected_track += 1;
-                }
-            }
-            ActiveScreen::Library => {
-                let max = self.state.library.liked_songs.len().saturating_sub(1);
-                if self.state.library.selected < max {
-                    self.state.library.selected += 1;
-                }
-            }
-            ActiveScreen::Playlists => {
-                if self.state.playlists.viewing_tracks {
-                    let max = self.state.playlists.playlist_tracks.len().saturating_sub(1);
-                    if self.state.playlists.selected_track < max {
-                        self.state.playlists.selected_track += 1;
-                    }
-                } else {
-                    let max = self.state.playlists.playlists.len().saturating_sub(1);
-                    if self.state.playlists.selected_playlist < max {
-                        self.state.playlists.selected_playlist += 1;
-                    }
-                }
-            }
-            ActiveScreen::Queue => {
-                let max = self.state.queue.tracks.len().saturating_sub(1);
-                if self.state.queue.selected < max {
-                    self.state.queue.selected += 1;
-                }
-            }
-            ActiveScreen::Vibes => {
-                if !self.state.vibes.recommendations.is_empty() {
-                    let max = self.state.vibes.recommendations.len().saturating_sub(1);
-                    if self.state.vibes.selected_track < max {
-                        self.state.vibes.selected_track += 1;
-                    }
-                } else {
-                    let max = 4; // 5 moods
-                    if self.state.vibes.selected_mood < max {
-                        self.state.vibes.selected_mood += 1;
-                    }
-                }
-            }
+    // ── Background events ─────────────────────────────────────────────────────
+    fn on_track_change(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.tempo_bpm = None;
+        self.state.energy = None;
+        self.state.beat_phase = 0.0;
+        self.state.anims.track_changed();
+        // Episodes have no audio features to fetch
+        if (self.state.replay_gain || self.config.tempo_sync) && !self.state.current_track.is_episode {
+            self.fetch_audio_features(spotify.clone());
+        }
+        self.state.cover = None;
+        if self.config.dynamic_theme || self.state.focus {
+            self.fetch_art();
+        }
+        self.state.lyrics = None;
+        if self.state.sidebar_panel == SidebarPanel::Lyrics || self.state.focus {
+            self.fetch_lyrics();
+        }
+        if self.config.session_file.is_some()
+            && self.state.features.enabled(Feature::SessionFile)
+            && self.state.capabilities.queue.usable()
+        {
+            self.refresh_queue_preview(spotify);
+        }
+    }
 
+    /// Starts the `[hooks]` command for `event`, if one is set
+    fn run_hook(&self, event: HookEvent, track: HookTrack) {
+        if let Some(hook) = self.config.hooks.command(event) {
+            hooks::spawn(hook, event, &track);
         }
     }
 
-    // ── Select handler ────────────────────────────────────────────────────────
-    async fn handle_select(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+    /// Re-reads the queue in the background
+    fn refresh_queue_preview(&self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            match Queue::new(spotify).get_queue().await {
+                Ok(tracks) => {
+                    let _ = tx.send(BgEvent::QueuePreview(tracks));
+                }
+                Err(e) => warn!("Queue preview failed: {e}"),
+            }
+        });
+    }
+
+    /// Refreshes the session summary file if anything in it changed
+    fn write_session(&mut self) {
+        if !self.state.features.enabled(Feature::SessionFile) {
+            return;
+        }
+        let Some(path) = self.config.session_file.as_ref() else { return };
+        let summary = SessionSummary::from_state(&self.state);
+        if self.last_session.as_ref() == Some(&summary) {
+            return;
+        }
+        match serde_json::to_string_pretty(&summary).map_err(Into::into).and_then(|raw| write_atomic(path, raw)) {
+            Ok(()) => self.last_session = Some(summary),
+            Err(e) => warn!("Session file not written: {e}"),
+        }
+    }
+
+    /// Keeps `status_file` on the current line; progress moves it every second
+    fn write_status_line(&mut self) {
+        let Some(path) = self.config.status_file.as_ref() else { return };
+        let now = NowPlaying::from_track(&self.state.current_track);
+        let line = widget::status_line(&self.config.status_format, now.as_ref(), self.state.volume);
+        if self.last_status_line.as_ref() == Some(&line) {
+            return;
+        }
+        // Not retried until the line changes, so a bad path doesn't flood the log
+        if let Err(e) = widget::write_status(path, &line) {
+            warn!("Status file not written: {e}");
+        }
+        self.last_status_line = Some(line);
+    }
+
+    fn play_log_path(&self) -> Option<std::path::PathBuf> {
+        self.config.play_log.clone().or_else(plays::default_path)
+    }
+
+    fn log_play(&self, play: &Play) {
+        if !self.state.features.enabled(Feature::PlayLog) {
+            return;
+        }
+        let Some(path) = self.play_log_path() else { return };
+        if let Err(e) = plays::append(&path, play) {
+            warn!("Play not logged: {e}");
+        }
+    }
+
+    /// Folds Spotify's recently played into the history and the play log, so
+    /// what was heard while vibes wasn't running shows up in both
+    fn merge_recently_played(&mut self, items: &[PlayHistory]) {
+        let entries: Vec<HistoryEntry> = items.iter().map(HistoryEntry::from).filter(|e| !e.id.is_empty()).collect();
+        if let Some(path) = self.play_log_path().filter(|_| self.state.features.enabled(Feature::PlayLog)) {
+            let recent: Vec<Play> = entries.iter().filter(|e| e.scrobble).map(Play::from).collect();
+            match plays::merge(&path, &recent) {
+                Ok(added) if !added.is_empty() => {
+                    info!("Logged {} plays from Spotify's recently played", added.len());
+                    self.state.stats.on_this_day = self.on_this_day();
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Play log merge error: {e}"),
+            }
+        }
+        self.state.merge_history(entries);
+    }
+
+    /// Today's date in earlier years, from the play log; empty without one
+    fn on_this_day(&self) -> Vec<DayRecap> {
+        let Some(path) = self.play_log_path().filter(|p| p.exists()) else { return Vec::new() };
+        if !self.state.features.enabled(Feature::PlayLog) {
+            return Vec::new();
+        }
+        match plays::load(&path) {
+            Ok(log) => {
+                let now = chrono::Local::now();
+                plays::on_this_day(&log, now.date_naive(), *now.offset())
+            }
+            Err(e) => {
+                warn!("Play log not read: {e}");
+                Vec::new()
+            }
+        }
+    }
+
+    /// o: plays the top track from the most recent year with plays on this date
+    async fn replay_on_this_day(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.stats.on_this_day = self.on_this_day();
+        let Some(recap) = self.state.stats.on_this_day.first().cloned() else {
+            self.state.set_notification(Notification::info("Nothing in your play log for this date yet"));
+            return;
+        };
+        match Player::new(spotify).play_tracks(vec![recap.top.id.as_str()]).await {
+            Ok(_) => {
+                self.state.set_notification(Notification::info(format!(
+                    "📅 {} — your top track on this day in {}",
+                    recap.top.track, recap.year
+                )));
+                self.state.queue.set_origin(format!("on this day {}", recap.year), [recap.top.id]);
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Replay failed: {e}"), &e)),
+        }
+    }
+
+    /// `:export [csv|listenbrainz] [YYYY-MM-DD]` — the play log (optionally
+    /// from a date on) in a format backfill tools can import
+    fn export_plays(&mut self, args: &[&str]) {
+        let mut format = ExportFormat::Csv;
+        let mut since = None;
+        for arg in args {
+            if let Some(f) = ExportFormat::from_name(arg) {
+                format = f;
+            } else if let Ok(date) = chrono::NaiveDate::parse_from_str(arg, "%Y-%m-%d") {
+                since = date.and_hms_opt(0, 0, 0).and_then(|t| t.and_local_timezone(chrono::Local).single());
+            } else {
+                self.state.set_notification(Notification::warning(format!("Usage: :export [csv|listenbrainz] [YYYY-MM-DD], not “{arg}”")));
+                return;
+            }
+        }
+        let Some(log) = self.play_log_path() else {
+            self.state.set_notification(Notification::warning("No play log path ($HOME unset)"));
+            return;
+        };
+        let (job, handle) = self.state.jobs.start(JobKind::Export, format!("{format:?} export"));
+        let tx = self.bg_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = plays::export(&log, format, since.map(|t| t.to_utc()), &handle);
+            let _ = tx.send(BgEvent::Exported { job, result });
+        });
+    }
+
+    /// `:cheatsheet [path]`: the current keymap as markdown, or plain text
+    /// for a `.txt` path; by default `cheatsheet.md` next to the play log
+    fn write_cheat_sheet(&mut self, path: &str) {
+        let path = match path {
+            "" => match plays::default_path() {
+                Some(log) => log.with_file_name("cheatsheet.md"),
+                None => {
+                    self.state.set_notification(Notification::warning("Usage: :cheatsheet <path> ($HOME unset)"));
+                    return;
+                }
+            },
+            path => std::path::PathBuf::from(path),
+        };
+        let sheet = self.keymap.cheat_sheet(SheetFormat::for_path(&path));
+        let result = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, sheet));
+        match result {
+            Ok(()) => self.state.set_notification(Notification::info(format!("Wrote the key cheat sheet to {}", path.display()))),
+            Err(e) => {
+                let e = anyhow::Error::from(e);
+                self.state.set_notification(Notification::failure(format!("Could not write {}: {e}", path.display()), &e));
+            }
+        }
+    }
+
+    /// Moves the volume by `delta` points (clamped to 0–100); any volume key ends a mute
+    fn change_volume(&mut self, delta: i16, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.muted_volume = None;
+        let new_vol = (self.state.volume as i16 + delta).clamp(0, 100) as u8;
+        self.state.volume = new_vol;
+        self.state.set_notification(Notification::info(format!("Volume: {new_vol}%")));
+        tokio::spawn(async move {
+            let player = Player::new(spotify);
+            let _ = player.set_volume(new_vol).await;
+        });
+    }
+
+    /// Seeks `delta_ms` from the current position, within the track
+    fn seek_by(&mut self, delta_ms: i64, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let track = &mut self.state.current_track;
+        let new_pos = (track.progress_ms as i64 + delta_ms).clamp(0, track.duration_ms as i64) as u32;
+        track.progress_ms = new_pos;
+        tokio::spawn(async move {
+            let player = Player::new(spotify);
+            let _ = player.seek(new_pos).await;
+        });
+    }
+
+    /// Serves the web remote when `remote_addr` is set; the pairing code only
+    /// lives for this run and is shown in a toast and the `:features` popup
+    async fn start_remote(&mut self, actions: mpsc::UnboundedSender<UserAction>) {
+        let Some(addr) = self.config.remote_addr else { return };
+        let code = remote::pairing_code();
+        match remote::serve(addr, code.clone(), self.subscribe_remote_status(), actions).await {
+            Ok(()) => {
+                let url = remote::lan_url(addr);
+                self.state.set_notification(Notification::info(format!("Web remote on {url} · pairing code {code}")));
+                self.state.remote = Some(Pairing { url, code });
+                self.publish_remote_status();
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Web remote not started: {e}"), &e)),
+        }
+    }
+
+    /// Registers Vibes as a Connect device in librespot builds, unless
+    /// `local_device` is switched off in `[features]`. It logs in with the
+    /// current token in the background, so startup doesn't wait for it
+    async fn start_local_device(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.features.enabled(Feature::LocalDevice) {
+            return;
+        }
+        let token = spotify.lock().await.token.lock().await.unwrap().as_ref().map(|t| t.access_token.clone());
+        let Some(token) = token else { return };
+        let (tx, settings) = (self.bg_tx.clone(), self.config.device.clone());
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::LocalDevice(connect::start(&token, &settings).await));
+        });
+    }
+
+    /// Listens to `capture_device` for the EQ bars in builds with audio
+    /// capture, unless `audio_capture` is switched off in `[features]`.
+    /// Without it, or when it fails, the bars stay simulated
+    fn start_capture(&mut self) {
+        if !self.state.features.enabled(Feature::AudioCapture) {
+            return;
+        }
+        let (tx, device) = (self.bg_tx.clone(), self.config.capture_device.clone());
+        tokio::task::spawn_blocking(move || {
+            let _ = tx.send(BgEvent::Capture(capture::start(device.as_deref())));
+        });
+    }
+
+    /// Logs in through the browser. Esc or `q` stops waiting for it, as does
+    /// `auth_timeout_secs` going by; after that `r` tries again, `c` copies
+    /// the login link and `q` quits, which is `Ok(false)`.
+    async fn log_in<B, E>(&mut self, terminal: &mut ratatui::Terminal<B>, events: &mut E, spotify: &Arc<Mutex<rspotify::AuthCodePkceSpotify>>, tokens: &TokenStore<'_>) -> Result<bool>
+    where
+        B: ratatui::backend::Backend,
+        E: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        loop {
+            self.state.auth_error = match self.try_log_in(terminal, events, spotify, tokens).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => Some("Login cancelled".to_string()),
+                Err(e) => {
+                    warn!("Login failed: {e:#}");
+                    Some(format!("{e:#}"))
+                }
+            };
+            loop {
+                terminal.draw(|f| crate::ui::render(f, &self.state))?;
+                let Some(event) = events.next().await else { return Ok(false) };
+                let Ok(Event::Key(key)) = event else { continue };
+                match key.code {
+                    KeyCode::Char('r') => break,
+                    KeyCode::Char('c') => self.copy_auth_url(),
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Toasts a new 429 once, with how long until requests go again
+    fn note_rate_limit(&mut self) {
+        let hits = retry::rate_limit_hits();
+        if hits == self.rate_limits_seen {
+            return;
+        }
+        self.rate_limits_seen = hits;
+        if let Some(wait) = retry::rate_limited_for() {
+            let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            self.state.set_notification(Notification::warning(format!("Rate limited by Spotify, retrying in {secs}s")));
+        }
+    }
+
+    /// The token stopped working mid-session: forgets it and shows the login
+    /// screen over everything, carrying on as before once logged in. Quitting
+    /// the screen leaves Vibes logged out until `:login`.
+    async fn log_in_again<B, E>(&mut self, terminal: &mut ratatui::Terminal<B>, events: &mut E, spotify: &Arc<Mutex<rspotify::AuthCodePkceSpotify>>, tokens: &TokenStore<'_>) -> Result<()>
+    where
+        B: ratatui::backend::Backend,
+        E: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        warn!("Spotify turned the login down; logging in again");
+        tokens.forget().await;
+        *spotify.lock().await.token.lock().await.unwrap() = None;
+        self.state.is_authenticated = false;
+        self.state.auth_expired = true;
+        let logged_in = self.log_in(terminal, events, spotify, tokens).await?;
+        self.state.is_authenticated = true;
+        self.state.auth_expired = false;
+        self.state.auth_url = None;
+        self.state.auth_redirect = None;
+        self.state.auth_error = None;
+        self.login_declined = !logged_in;
+        if logged_in {
+            info!("Logged in again");
+            self.state.set_notification(Notification::info("Logged in again ✓"));
+        } else {
+            self.state.set_notification(Notification::warning("Logged out of Spotify — :login to log in again"));
+        }
+        Ok(())
+    }
+
+    /// One go at logging in: `Ok(false)` when the user gave up waiting
+    async fn try_log_in<B, E>(&mut self, terminal: &mut ratatui::Terminal<B>, events: &mut E, spotify: &Arc<Mutex<rspotify::AuthCodePkceSpotify>>, tokens: &TokenStore<'_>) -> Result<bool>
+    where
+        B: ratatui::backend::Backend,
+        E: Stream<Item = io::Result<Event>> + Unpin,
+    {
+        self.state.auth_error = None;
+        let redirect = bind_redirect(&self.config.redirect_uri, &self.config.redirect_fallback_ports).await?;
+        let pkce = PkceChallenge::new();
+        let url = authorize_url(spotify, &redirect.uri, &pkce).await;
+        self.state.auth_url = Some(url.clone());
+        self.state.auth_redirect = Some(redirect.uri.clone());
+        // Open browser
+        if let Err(e) = open::that(&url) {
+            warn!("Could not open browser: {e}");
+        }
+
+        // Wait for the redirect; dropping it closes the port again
+        let timeout = Duration::from_secs(self.config.auth_timeout_secs.max(1));
+        let redirected = wait_for_auth_code(redirect, &pkce.state);
+        let deadline = time::sleep(timeout);
+        tokio::pin!(redirected, deadline);
+        let auth_result = loop {
+            terminal.draw(|f| crate::ui::render(f, &self.state))?;
+            tokio::select! {
+                result = &mut redirected => break result?,
+                _ = &mut deadline => anyhow::bail!("No answer from the browser within {} seconds", timeout.as_secs()),
+                event = events.next() => match event {
+                    Some(Ok(Event::Key(key))) if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) => return Ok(false),
+                    Some(Ok(Event::Key(key))) if key.code == KeyCode::Char('c') => self.copy_auth_url(),
+                    Some(_) => {}
+                    None => return Ok(false),
+                },
+            }
+        };
+        complete_auth(spotify.clone(), &auth_result.code, &pkce.verifier, tokens).await?;
+        Ok(true)
+    }
+
+    /// `c` on the login screen, for a browser on another machine
+    fn copy_auth_url(&mut self) {
+        let Some(url) = self.state.auth_url.clone() else { return };
+        match clipboard::copy(&url) {
+            Ok(()) => self.state.set_notification(Notification::info("Copied the login link")),
+            Err(e) => self.state.set_notification(Notification::failure(format!("Copy failed: {e}"), &e)),
+        }
+    }
+
+    /// Hands the latest captured levels to the bars; when capture has
+    /// stopped (the device went away), they go back to animating
+    fn poll_capture(&mut self) {
+        let Some(ref mut levels) = self.capture else { return };
+        match levels.has_changed() {
+            Ok(true) => self.state.eq_capture = Some(*levels.borrow_and_update()),
+            Ok(false) => {}
+            Err(_) => {
+                warn!("Audio capture stopped; the EQ bars are simulated again");
+                self.capture = None;
+                self.state.eq_capture = None;
+            }
+        }
+    }
+
+    /// Answers MPD clients when `mpd_addr` is set
+    async fn start_mpd(&mut self, actions: mpsc::UnboundedSender<UserAction>) {
+        let Some(addr) = self.config.mpd_addr else { return };
+        match mpd::serve(addr, self.subscribe_remote_status(), actions).await {
+            Ok(()) => {
+                self.state.mpd = Some(addr);
+                self.publish_remote_status();
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("MPD server not started: {e}"), &e)),
+        }
+    }
+
+    /// Takes commands from `vibes ctl` and scripts, unless `control_socket`
+    /// is switched off in `[features]`
+    async fn start_control_socket(&mut self, actions: mpsc::UnboundedSender<UserAction>) {
+        if !self.state.features.enabled(Feature::ControlSocket) {
+            return;
+        }
+        let path = self.config.control_socket_path();
+        match ipc::serve(&path, self.subscribe_remote_status(), actions).await {
+            Ok(()) => {
+                self.state.control_socket = Some(path);
+                self.publish_remote_status();
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Control socket not started: {e}"), &e)),
+        }
+    }
+
+    /// A receiver of the status the servers answer from, created with the first one
+    fn subscribe_remote_status(&mut self) -> watch::Receiver<RemoteStatus> {
+        self.remote_status.get_or_insert_with(|| watch::channel(RemoteStatus::default()).0).subscribe()
+    }
+
+    fn publish_remote_status(&self) {
+        let Some(ref tx) = self.remote_status else { return };
+        let now_playing = NowPlaying::from_track(&self.state.current_track);
+        let queue = self.state.queue.tracks.iter().map(QueueEntry::from_track).collect();
+        let status = RemoteStatus { now_playing, volume: self.state.volume, queue };
+        tx.send_if_modified(|current| {
+            let modified = *current != status;
+            *current = status;
+            modified
+        });
+    }
+
+    fn fetch_audio_features(&self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.features.enabled(Feature::AudioFeatures) || !self.state.capabilities.audio_features.usable() {
+            return;
+        }
+        if let Some(track_id) = self.state.current_track.id.clone() {
+            let tx = self.bg_tx.clone();
+            tokio::spawn(async move {
+                let analysis = Analysis::new(spotify);
+                match analysis.get_audio_features(&track_id).await {
+                    Ok(features) => {
+                        let _ = tx.send(BgEvent::AudioFeatures { track_id, features });
+                    }
+                    Err(e) => warn!("Audio features unavailable: {e}"),
+                }
+            });
+        }
+    }
+
+    /// Asks the quotes API for today's batch unless the cache already has it.
+    /// On failure the cached (or built-in) quotes stay up
+    fn refresh_quotes(&self) {
+        if self.config.quote_source != QuoteSource::Api {
+            return;
+        }
+        let url = self.config.quote_api.clone();
+        let today = chrono::Local::now().date_naive();
+        let path = quotes::cache_path();
+        if path.as_deref().and_then(quotes::load_cache).is_some_and(|c| c.is_fresh(&url, today)) {
+            return;
+        }
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let quotes = match quotes::fetch(&url).await {
+                Ok(quotes) if !quotes.is_empty() => quotes,
+                Ok(_) => {
+                    warn!("Quotes API returned no quotes");
+                    return;
+                }
+                Err(e) => {
+                    warn!("Quotes API failed: {e}");
+                    return;
+                }
+            };
+            if let Some(path) = path {
+                let cache = QuoteCache { date: today, url, quotes: quotes.clone() };
+                if let Err(e) = quotes::save_cache(&path, &cache) {
+                    warn!("Quote cache not saved: {e}");
+                }
+            }
+            let _ = tx.send(BgEvent::Quotes(quotes));
+        });
+    }
+
+    fn fetch_lyrics(&self) {
+        let track = &self.state.current_track;
+        if track.name.is_empty() || !self.state.features.enabled(Feature::Lyrics) {
+            return;
+        }
+        let track_id = track.id.clone();
+        let artist = track.artists.first().cloned().unwrap_or_default();
+        let (title, album, duration_ms) = (track.name.clone(), track.album.clone(), track.duration_ms);
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let lines = lyrics::fetch_synced(&artist, &title, &album, duration_ms)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Lyrics lookup failed: {e}");
+                    None
+                });
+            let _ = tx.send(BgEvent::Lyrics { track_id, lines });
+        });
+    }
+
+    /// The accent for `dynamic_theme` and the cover for focus mode
+    fn fetch_art(&self) {
+        if !self.state.features.enabled(Feature::AlbumArt) {
+            return;
+        }
+        let track_id = self.state.current_track.id.clone();
+        let url = self.state.current_track.album_art_thumb_url.clone();
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let (accent, cover) = match url {
+                Some(url) => fetch_art(&url).await.unwrap_or_else(|e| {
+                    warn!("Album art failed: {e}");
+                    (None, None)
+                }),
+                None => (None, None),
+            };
+            let _ = tx.send(BgEvent::AlbumArt { track_id, accent, cover });
+        });
+    }
+
+    fn handle_bg_event(&mut self, ev: BgEvent, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        match ev {
+            BgEvent::AudioFeatures { track_id, features } => {
+                // Ignore stale results for a track that has already been skipped
+                if self.state.current_track.id.as_deref() != Some(track_id.as_str()) {
+                    return;
+                }
+                if self.config.tempo_sync && features.tempo > 0.0 {
+                    self.state.tempo_bpm = Some(capped_bpm(features.tempo, self.config.max_bpm));
+                    self.state.energy = Some(features.energy);
+                }
+                // Leveling would unmute by setting a volume
+                if !self.state.replay_gain || self.state.muted_volume.is_some() {
+                    return;
+                }
+                let before = self.state.volume;
+                let new_vol = self.state.apply_replay_gain(replay_gain_delta(features.loudness));
+                if new_vol != before {
+                    tokio::spawn(async move {
+                        let player = Player::new(spotify);
+                        let _ = player.set_volume(new_vol).await;
+                    });
+                }
+            }
+            BgEvent::LikedStatus(status) => {
+                for (uri, liked) in status {
+                    self.state.note_liked(&uri, liked);
+                }
+            }
+            BgEvent::RadarFeatures { track_id, profile } => {
+                if let Some(ref mut radar) = self.state.radar {
+                    radar.set_profile(&track_id, profile);
+                }
+            }
+            BgEvent::Quotes(quotes) => {
+                if !quotes.is_empty() {
+                    self.state.quotes = quotes;
+                }
+            }
+            BgEvent::Lyrics { track_id, lines } => {
+                if self.state.current_track.id == track_id {
+                    self.state.lyrics = lines;
+                }
+            }
+            BgEvent::Capabilities(caps) => self.state.set_capabilities(caps),
+            BgEvent::QueuePreview(tracks) => {
+                self.state.queue.tracks = tracks;
+                self.clamp_queue_selection();
+            }
+            BgEvent::PlaylistRemoval { playlist_id, name, removed, result } => {
+                let open = self.open_playlist_id().as_deref() == Some(playlist_id.as_str());
+                match result {
+                    Ok(snapshot_id) => {
+                        if let Some(pl) = self.state.playlists.playlists.iter_mut().find(|p| p.id.id() == playlist_id) {
+                            pl.tracks.total = pl.tracks.total.saturating_sub(removed.len() as u32);
+                            pl.snapshot_id = snapshot_id;
+                        }
+                        // Our own edit shouldn't show up as "changed since last visit"
+                        if open {
+                            if let Some((id, _, snapshot)) = self.open_playlist_snapshot() {
+                                let cache = self.cache.clone();
+                                tokio::spawn(async move {
+                                    if let Err(e) = cache.set_playlist_snapshot(&id, &snapshot).await {
+                                        warn!("Playlist snapshot not saved: {e}");
+                                    }
+                                });
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        if open {
+                            restore_removed(&mut self.state.playlists.playlist_tracks, removed);
+                        }
+                        self.state.set_notification(Notification::failure(
+                            format!("Could not remove from {name}: {e}"),
+                            &e,
+                        ));
+                    }
+                }
+            }
+            BgEvent::AlbumArt { track_id, accent, cover } => {
+                if self.state.current_track.id == track_id {
+                    if self.config.dynamic_theme {
+                        // `None` fades back to the stock palette
+                        self.state.accent_target = accent;
+                    }
+                    self.state.cover = cover;
+                }
+            }
+            BgEvent::Enqueued { added, total, result } => {
+                self.state.vibes.enqueueing = false;
+                let n = added.len();
+                self.state.queue.manual.extend(added);
+                match result {
+                    Ok(()) => self.state.set_notification(Notification::info(format!("Queued all {n} tracks ✓"))),
+                    Err(e) => self.state.set_notification(Notification::failure(
+                        format!("Queued {n} of {total}, then: {e}"),
+                        &e,
+                    )),
+                }
+                if self.state.active_screen == ActiveScreen::Queue {
+                    self.refresh_queue_preview(spotify);
+                }
+            }
+            BgEvent::OutboxFlushed { writes, liked_songs, result } => match result {
+                Ok(()) => {
+                    if let Some(songs) = liked_songs {
+                        self.state.library.add_liked(songs);
+                        self.state.sort_liked();
+                        self.refresh_library_index();
+                    }
+                    self.state.set_notification(Notification::info(format!("Synced {} like(s) made offline", writes.len())));
+                }
+                Err(e) => {
+                    // Keep them, ahead of anything queued since, for the next reconnect
+                    let newer = std::mem::replace(&mut self.state.connection.outbox, writes);
+                    for write in newer {
+                        self.state.connection.queue(write);
+                    }
+                    let msg = format!("Could not sync {} like(s) made offline: {e}", self.state.connection.outbox.len());
+                    self.state.set_notification(Notification::failure(msg, &e));
+                }
+            },
+            BgEvent::Archived { job, archived, result } => {
+                let open = self.open_playlist_id();
+                let playlists = &mut self.state.playlists;
+                playlists.playlists.retain(|pl| !archived.iter().any(|id| id == pl.id.id()));
+                match open.and_then(|id| playlists.playlists.iter().position(|pl| pl.id.id() == id)) {
+                    Some(i) => playlists.selected_playlist = i,
+                    None => {
+                        playlists.viewing_tracks = false;
+                        playlists.selected_playlist = playlists.selected_playlist.min(playlists.playlists.len().saturating_sub(1));
+                    }
+                }
+                let n = archived.len();
+                if !self.finish_job(job) {
+                    return;
+                }
+                match result {
+                    Ok(()) => self.state.set_notification(Notification::info(format!(
+                        "Archived {n} playlist(s) — :archived to restore"
+                    ))),
+                    Err(e) => self.state.set_notification(Notification::failure(format!("Archived {n}, then: {e:#}"), &e)),
+                }
+            }
+            BgEvent::Restored { job, entry, playlists, result } => {
+                if let Some(pls) = playlists {
+                    self.state.playlists.playlists = pls;
+                    self.playlists_fetched = Some(Utc::now());
+                }
+                if result.is_ok() {
+                    if let Some(view) = self.state.archived.as_mut() {
+                        view.entries.retain(|a| a.id != entry.id);
+                        view.selected = view.selected.min(view.entries.len().saturating_sub(1));
+                        if view.entries.is_empty() {
+                            self.state.archived = None;
+                        }
+                    }
+                }
+                if !self.finish_job(job) {
+                    return;
+                }
+                match result {
+                    Ok(()) => self.state.set_notification(Notification::info(format!("Restored “{}”", entry.name))),
+                    Err(e) => self.state.set_notification(Notification::failure(format!("Could not restore {}: {e}", entry.name), &e)),
+                }
+            }
+            BgEvent::Exported { job, result } => {
+                if !self.finish_job(job) {
+                    return;
+                }
+                match result {
+                    Ok((path, n)) => self.state.set_notification(Notification::info(format!("Exported {n} plays to {}", path.display()))),
+                    Err(e) => self.state.set_notification(Notification::failure(format!("Export failed: {e}"), &e)),
+                }
+            }
+            BgEvent::DedupeLoaded { job, liked, albums, result } => {
+                if let Some(songs) = liked {
+                    for uri in songs.iter().filter_map(|s| s.track.id.as_ref()).map(|id| id.uri()) {
+                        self.state.note_liked(&uri, true);
+                    }
+                    self.state.library.add_liked(songs);
+                    self.state.sort_liked();
+                }
+                if let Some(albums) = albums {
+                    self.state.library.saved_albums = albums;
+                }
+                self.refresh_library_index();
+                if !self.finish_job(job) {
+                    return;
+                }
+                if let Err(e) = result {
+                    self.state.set_notification(Notification::failure(format!("Could not scan the library: {e}"), &e));
+                    return;
+                }
+                let overlaps = LibraryIndex::from_library(&self.state.library).overlaps();
+                if overlaps.is_empty() {
+                    self.state.set_notification(Notification::info("No liked songs overlap with your saved albums"));
+                    return;
+                }
+                self.state.dedupe = Some(DedupeView { overlaps, selected: 0 });
+            }
+            BgEvent::RecentlyPlayed(Ok(items)) => {
+                self.state.note_startup(StartupLoad::RecentlyPlayed, LoadStatus::Done, format!("{} plays", items.len()));
+                self.merge_recently_played(&items);
+            }
+            BgEvent::RecentlyPlayed(Err(e)) => {
+                warn!("Recently played unavailable: {e}");
+                self.state.note_startup(StartupLoad::RecentlyPlayed, LoadStatus::Failed, e.to_string());
+            }
+            BgEvent::Profile(Ok(id)) => {
+                self.state.note_startup(StartupLoad::Profile, LoadStatus::Done, id.clone());
+                self.state.user_id = Some(id);
+            }
+            BgEvent::Profile(Err(e)) => {
+                warn!("Could not fetch user profile: {e}");
+                self.state.note_startup(StartupLoad::Profile, LoadStatus::Failed, e.to_string());
+            }
+            BgEvent::Playlists(fetched) => self.playlists_loaded(fetched),
+            BgEvent::LikedSongs(fetched) => self.liked_songs_loaded(fetched, spotify),
+            BgEvent::PlaylistPrefetched { playlist_id, snapshot_id, result } => {
+                let prefetched = &mut self.state.playlists.prefetched;
+                prefetched.in_flight = None;
+                match result {
+                    Ok((items, total)) => prefetched.insert(PrefetchedPage { playlist_id, snapshot_id, items, total }),
+                    Err(e) => {
+                        warn!("Prefetch of playlist {playlist_id} failed: {e}");
+                        prefetched.fail(playlist_id, snapshot_id);
+                    }
+                }
+            }
+            BgEvent::PlaylistTracks { playlist_id, result } => {
+                if self.open_playlist_id().as_deref() != Some(playlist_id.as_str()) {
+                    return;
+                }
+                match result {
+                    Ok(tracks) => {
+                        self.set_playlist_tracks(tracks);
+                        self.cache_playlist_tracks();
+                        self.playlist_tracks_loaded();
+                    }
+                    Err(e) => {
+                        self.state.playlists.paging_tracks = false;
+                        warn!("Playlist tracks load error: {e}");
+                    }
+                }
+            }
+            BgEvent::PlaylistDiffed { playlist_id, name, diff } => {
+                if self.open_playlist_id().as_deref() != Some(playlist_id.as_str()) {
+                    return;
+                }
+                self.state.set_notification(Notification::info(format!(
+                    "Changed since last visit: +{} −{}  (u to view)",
+                    diff.added.len(),
+                    diff.removed.len()
+                )));
+                self.state.playlists.diff = Some((name, diff));
+            }
+            BgEvent::LikedPage { songs, total, page } => {
+                for uri in songs.iter().filter_map(|s| s.track.id.as_ref()).map(|id| id.uri()) {
+                    self.state.note_liked(&uri, true);
+                }
+                self.state.library.liked_total = total;
+                self.state.library.add_liked(songs);
+                let loaded = format!("{} of {total}", self.state.library.liked_songs.len());
+                self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Loading, loaded);
+                // Pages come newest first, so unsorted they're still in place
+                // unless a column sort is on; that catches up every so often
+                if page % LIKED_RESORT_PAGES == 0 {
+                    self.state.sort_liked();
+                    self.refresh_library_index();
+                }
+            }
+            BgEvent::LikedPagesDone(result) => {
+                self.state.sort_liked();
+                self.refresh_library_index();
+                let library = &mut self.state.library;
+                library.paging_liked = false;
+                match result {
+                    Ok(()) => {
+                        let loaded = format!("{} songs", library.liked_songs.len());
+                        self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Done, loaded);
+                        tokio::spawn(self.cache_liked_songs());
+                    }
+                    Err(e) => {
+                        // Only part of it is here, which mustn't be cached as the whole
+                        self.liked_fetched = None;
+                        self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Failed, e.to_string());
+                        let library = &self.state.library;
+                        let msg = format!("Liked Songs stopped loading at {} of {}: {e}", library.liked_songs.len(), library.liked_total);
+                        self.state.set_notification(Notification::failure(msg, &e));
+                    }
+                }
+            }
+            BgEvent::FeaturesIndexed(entries) => {
+                let index = &mut self.state.feature_index;
+                match features::default_path() {
+                    Some(path) => {
+                        if let Err(e) = index.append(&path, entries) {
+                            warn!("Feature index write error: {e}");
+                        }
+                    }
+                    None => index.insert(entries),
+                }
+            }
+            BgEvent::FeatureIndexDone { job, result } => {
+                if !self.finish_job(job) {
+                    self.index_cancelled = true;
+                    return;
+                }
+                match result {
+                    Ok(()) => {
+                        info!("Audio features indexed for {} tracks", self.state.feature_index.len());
+                        // Liked Songs pages that came in meanwhile weren't part of this run
+                        self.index_features(&LibraryIndex::from_library(&self.state.library));
+                    }
+                    Err(e) => warn!("Feature indexing stopped: {e}"),
+                }
+            }
+            BgEvent::InboxTracks(tracks) => {
+                for track in tracks {
+                    if let Some(ref id) = track.id {
+                        self.state.inbox.tracks.insert(id.uri(), track);
+                    }
+                }
+            }
+            BgEvent::Devices(Ok(devices)) if devices.is_empty() => {
+                self.state.set_notification(Notification::warning("No devices — open Spotify on your phone, desktop or the web player"));
+            }
+            BgEvent::Devices(Ok(devices)) => {
+                let selected = devices.iter().position(|d| d.is_active).unwrap_or(0);
+                self.state.devices = Some(DevicesView { devices, selected });
+            }
+            BgEvent::Devices(Err(e)) => {
+                self.state.set_notification(Notification::failure(format!("Could not list devices: {e}"), &e));
+            }
+            BgEvent::Transferred { name, play, result } => match result {
+                Ok(()) if play => self.state.set_notification(Notification::info(format!("▶ Playing on {name}"))),
+                Ok(()) => self.state.set_notification(Notification::info(format!("Playback moved to {name}"))),
+                Err(e) => self.state.set_notification(Notification::failure(format!("Could not move playback to {name}: {e}"), &e)),
+            },
+            BgEvent::LocalDevice(Ok(device)) => {
+                self.state.set_notification(Notification::info(format!("🔈 Device “{}” ready to play here", device.name())));
+                self.local_device = Some(device);
+            }
+            BgEvent::Capture(Ok(levels)) => {
+                info!("Audio capture started; the EQ bars follow what's playing");
+                self.capture = Some(levels);
+            }
+            BgEvent::Capture(Err(e)) => warn!("Audio capture unavailable, EQ bars simulated: {e}"),
+            BgEvent::LocalDevice(Err(e)) => {
+                self.state.set_notification(Notification::failure(format!("Built-in device not started: {e}"), &e));
+            }
+            BgEvent::Resumed { point, result } => {
+                if let Err(e) = result {
+                    self.state.current_track.is_playing = false;
+                    self.state.note_play_state();
+                    self.state.resume_point = Some(point);
+                    self.state.set_notification(Notification::failure(format!("Could not resume: {e}"), &e));
+                }
+            }
+        }
+    }
+
+    // ── Navigation helpers ────────────────────────────────────────────────────
+    /// Shows `screen`, loading its data if it hasn't been yet
+    async fn go_to_screen(&mut self, screen: ActiveScreen, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if screen == ActiveScreen::Browse && !self.state.capabilities.browse.usable() {
+            self.state.set_notification(Notification::info("Spotify doesn't let this app browse categories"));
+            return;
+        }
+        self.state.show_help = false;
+        self.state.navigate_to(screen.clone());
+        match screen {
+            ActiveScreen::Search => self.state.search.is_searching = false,
+            ActiveScreen::Library => self.load_library(spotify, false).await,
+            ActiveScreen::Playlists => self.load_playlists(spotify, false).await,
+            ActiveScreen::Queue => self.load_queue(spotify).await,
+            ActiveScreen::Vibes => {}
+            ActiveScreen::Stats => self.load_stats(spotify).await,
+            ActiveScreen::Browse => self.load_categories(spotify).await,
+        }
+    }
+
+    /// The `n`th sidebar screen, counting from 1
+    async fn switch_screen(&mut self, n: u8, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let screen = (n as usize).checked_sub(1).and_then(|i| self.state.screens.get(i)).cloned();
+        if let Some(screen) = screen {
+            self.go_to_screen(screen, spotify).await;
+        }
+    }
+
+    /// gn: opens the playlist, saved album or followed artist the playing
+    /// track was started from, with the track highlighted. Plain track lists
+    /// have no context, so Liked Songs is tried for those.
+    async fn go_to_playing_context(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        use rspotify::prelude::Id;
+        if self.state.current_track.id.is_none() {
+            self.state.set_notification(Notification::info("Nothing is playing"));
+            return;
+        }
+        let context = self.state.current_track.context_uri.clone().unwrap_or_default();
+        match context.split(':').nth(1) {
+            Some("playlist") => {
+                self.go_to_screen(ActiveScreen::Playlists, spotify.clone()).await;
+                let playlists = &self.state.playlists.playlists;
+                let Some(i) = playlists.iter().position(|p| p.id.uri() == context) else {
+                    self.state.set_notification(Notification::info("Playing from a playlist that isn't in your library"));
+                    return;
+                };
+                self.state.playlists.selected_playlist = i;
+                self.state.playlists.viewing_tracks = true;
+                self.state.playlists.selected_track = 0;
+                self.load_playlist_tracks(spotify, playlists[i].id.to_string()).await;
+            }
+            Some("album") => {
+                self.go_to_screen(ActiveScreen::Library, spotify.clone()).await;
+                self.load_saved_albums(spotify).await;
+                let library = &mut self.state.library;
+                let Some(i) = library.saved_albums.iter().position(|a| a.album.id.uri() == context) else {
+                    self.state.set_notification(Notification::info("Playing from an album that isn't saved"));
+                    return;
+                };
+                library.tab = LibraryTab::Albums;
+                library.selected_album = i;
+            }
+            Some("artist") => {
+                self.go_to_screen(ActiveScreen::Library, spotify.clone()).await;
+                self.load_followed_artists(spotify.clone()).await;
+                let library = &mut self.state.library;
+                let Some(i) = library.followed_artists.iter().position(|a| a.id.uri() == context) else {
+                    self.state.set_notification(Notification::info("Playing from an artist you don't follow"));
+                    return;
+                };
+                library.tab = LibraryTab::Artists;
+                library.selected_artist = i;
+                library.artist_top_tracks = None;
+                self.load_artist_top_tracks(spotify).await;
+            }
+            _ => {
+                self.go_to_screen(ActiveScreen::Library, spotify).await;
+                self.state.library.tab = LibraryTab::Liked;
+                if !self.state.select_playing() {
+                    self.state.set_notification(Notification::info("Playing from a list with no playlist or album to go to"));
+                }
+                return;
+            }
+        }
+        if !self.state.select_playing() {
+            let msg = format!("{} isn't listed there any more", self.state.current_track.name);
+            self.state.set_notification(Notification::info(msg));
+        }
+    }
+
+    /// Moves the selection up by `step` rows (1 for k/↑, a page, or to the top)
+    fn navigate_up(&mut self, step: usize) {
+        if let Some(picker) = self.state.picker.as_mut() {
+            picker.selected = picker.selected.saturating_sub(step);
+            return;
+        }
+        if let Some(dedupe) = self.state.dedupe.as_mut() {
+            dedupe.selected = dedupe.selected.saturating_sub(step);
+            return;
+        }
+        if let Some(archived) = self.state.archived.as_mut() {
+            archived.selected = archived.selected.saturating_sub(step);
+            return;
+        }
+        if let Some(view) = self.state.devices.as_mut() {
+            view.selected = view.selected.saturating_sub(step);
+            return;
+        }
+        if let Some(view) = self.state.log_view.as_mut() {
+            view.scroll = view.scroll.saturating_add(step).min(view.lines.len().saturating_sub(1));
+            return;
+        }
+        if let Some(mood) = self.state.mood.as_mut() {
+            mood.selected = mood.selected.saturating_sub(step);
+            return;
+        }
+        if self.state.inbox.open {
+            self.state.inbox.selected = self.state.inbox.selected.saturating_sub(step);
+            return;
+        }
+        if self.state.search.show_history {
+            self.state.search.selected_history = self.state.search.selected_history.saturating_sub(step);
+            return;
+        }
+        if self.state.show_jobs {
+            self.state.selected_job = self.state.selected_job.saturating_sub(step);
+            return;
+        }
+        if let Some(rows) = self.state.filtered_rows() {
+            if let Some(selected) = self.state.filter_selection() {
+                *selected = step_filtered(&rows, *selected, step, true).unwrap_or(*selected);
+            }
+            return;
+        }
+        match self.state.active_screen {
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Playlists => {
+                self.state.search.selected_playlist = self.state.search.selected_playlist.saturating_sub(step);
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Albums => {
+                self.state.search.selected_album = self.state.search.selected_album.saturating_sub(step);
+            }
+            ActiveScreen::Search => {
+                self.state.search.selected_track = self.state.search.selected_track.saturating_sub(step);
+            }
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Albums => {
+                let library = &mut self.state.library;
+                if library.viewing_album {
+                    library.selected_album_track = library.selected_album_track.saturating_sub(step);
+                } else {
+                    library.selected_album = library.selected_album.saturating_sub(step);
+                }
+            }
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Artists => {
+                let library = &mut self.state.library;
+                if library.artist_top_tracks.is_some() {
+                    library.selected_artist_track = library.selected_artist_track.saturating_sub(step);
+                } else {
+                    library.selected_artist = library.selected_artist.saturating_sub(step);
+                }
+            }
+            ActiveScreen::Library => {
+                self.state.library.selected = self.state.library.selected.saturating_sub(step);
+            }
+            ActiveScreen::Playlists => {
+                if self.state.playlists.viewing_tracks {
+                    self.state.playlists.selected_track = self.state.playlists.selected_track.saturating_sub(step);
+                } else {
+                    self.state.playlists.selected_playlist = self.state.playlists.selected_playlist.saturating_sub(step);
+                }
+            }
+            ActiveScreen::Queue => {
+                self.state.queue.selected = self.state.queue.selected.saturating_sub(step);
+            }
+            ActiveScreen::Vibes => {
+                if self.state.vibes.selected_mood > 0 && !self.state.vibes.recommendations.is_empty() {
+                    // In track list
+                    self.state.vibes.selected_track = self.state.vibes.selected_track.saturating_sub(step);
+                } else {
+                    self.state.vibes.selected_mood = self.state.vibes.selected_mood.saturating_sub(step);
+                }
+            }
+            ActiveScreen::Stats => {
+                self.state.stats.selected = self.state.stats.selected.saturating_sub(step);
+            }
+            ActiveScreen::Browse if self.state.browse.viewing_playlists => {
+                self.state.browse.selected_playlist = self.state.browse.selected_playlist.saturating_sub(step);
+            }
+            ActiveScreen::Browse => {
+                self.state.browse.selected_category = self.state.browse.selected_category.saturating_sub(step);
+            }
+
+        }
+    }
+
+    /// Selects row `row` (1-based, as the tables number them) of whatever
+    /// list has focus, or its last row when the list is shorter
+    fn jump_to_row(&mut self, row: Option<usize>) {
+        let Some(row) = row else { return };
+        let no_popup = self.state.picker.is_none() && self.state.dedupe.is_none() && self.state.archived.is_none()
+            && self.state.devices.is_none() && self.state.log_view.is_none() && self.state.mood.is_none() && !self.state.inbox.open && !self.state.search.show_history
+            && !self.state.show_jobs;
+        if no_popup && self.state.active_screen == ActiveScreen::Queue {
+            // Rows are numbered by track; group headers aren't counted
+            let last = self.state.queue.tracks.len().saturating_sub(1);
+            self.state.queue.select_track((row - 1).min(last));
+            return;
+        }
+        self.navigate_up(usize::MAX);
+        self.navigate_down(row - 1);
+    }
+
+    /// Moves the selection down by `step` rows, stopping at the last one
+    fn navigate_down(&mut self, step: usize) {
+        fn down(selected: &mut usize, len: usize, step: usize) {
+            *selected = selected.saturating_add(step).min(len.saturating_sub(1));
+        }
+        if self.state.picker.is_some() {
+            let len = self.state.editable_playlists().len();
+            if let Some(picker) = self.state.picker.as_mut() {
+                down(&mut picker.selected, len, step);
+            }
+            return;
+        }
+        if let Some(dedupe) = self.state.dedupe.as_mut() {
+            down(&mut dedupe.selected, dedupe.overlaps.len(), step);
+            return;
+        }
+        if let Some(archived) = self.state.archived.as_mut() {
+            down(&mut archived.selected, archived.entries.len(), step);
+            return;
+        }
+        if let Some(view) = self.state.devices.as_mut() {
+            down(&mut view.selected, view.devices.len(), step);
+            return;
+        }
+        if let Some(view) = self.state.log_view.as_mut() {
+            view.scroll = view.scroll.saturating_sub(step);
+            return;
+        }
+        if let Some(mood) = self.state.mood.as_mut() {
+            down(&mut mood.selected, mood.tracks.len(), step);
+            return;
+        }
+        if self.state.inbox.open {
+            down(&mut self.state.inbox.selected, self.state.inbox.items.len(), step);
+            return;
+        }
+        if self.state.search.show_history {
+            let search = &mut self.state.search;
+            down(&mut search.selected_history, search.history.len(), step);
+            return;
+        }
+        if self.state.show_jobs {
+            down(&mut self.state.selected_job, self.state.jobs.running().len(), step);
+            return;
+        }
+        if let Some(rows) = self.state.filtered_rows() {
+            if let Some(selected) = self.state.filter_selection() {
+                *selected = step_filtered(&rows, *selected, step, false).unwrap_or(*selected);
+            }
+            return;
+        }
+        match self.state.active_screen {
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Playlists => {
+                down(&mut self.state.search.selected_playlist, self.state.search.playlists.len(), step);
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Albums => {
+                let listed = self.state.search.album_order().len();
+                down(&mut self.state.search.selected_album, listed, step);
+            }
+            ActiveScreen::Search => {
+                down(&mut self.state.search.selected_track, self.state.search.tracks.len(), step);
+            }
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Albums => {
+                let library = &mut self.state.library;
+                if library.viewing_album {
+                    let len = library.open_album().map_or(0, |a| a.album.tracks.items.len());
+                    down(&mut library.selected_album_track, len, step);
+                } else {
+                    down(&mut library.selected_album, library.saved_albums.len(), step);
+                }
+            }
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Artists => {
+                let library = &mut self.state.library;
+                match library.artist_top_tracks {
+                    Some(ref tracks) => down(&mut library.selected_artist_track, tracks.len(), step),
+                    None => down(&mut library.selected_artist, library.followed_artists.len(), step),
+                }
+            }
+            ActiveScreen::Library => {
+                down(&mut self.state.library.selected, self.state.library.liked_songs.len(), step);
+            }
+            ActiveScreen::Playlists => {
+                if self.state.playlists.viewing_tracks {
+                    down(&mut self.state.playlists.selected_track, self.state.playlists.playlist_tracks.len(), step);
+                } else {
+                    down(&mut self.state.playlists.selected_playlist, self.state.playlists.playlists.len(), step);
+                }
+            }
+            ActiveScreen::Queue => {
+                let len = self.state.queue.rows().len();
+                down(&mut self.state.queue.selected, len, step);
+            }
+            ActiveScreen::Vibes => {
+                if !self.state.vibes.recommendations.is_empty() {
+                    down(&mut self.state.vibes.selected_track, self.state.vibes.recommendations.len(), step);
+                } else {
+                    down(&mut self.state.vibes.selected_mood, self.state.vibes.moods.len(), step);
+                }
+            }
+            ActiveScreen::Stats => {
+                let len = self.state.stats.row_count();
+                down(&mut self.state.stats.selected, len, step);
+            }
+            ActiveScreen::Browse if self.state.browse.viewing_playlists => {
+                down(&mut self.state.browse.selected_playlist, self.state.browse.playlists.len(), step);
+            }
+            ActiveScreen::Browse => {
+                down(&mut self.state.browse.selected_category, self.state.browse.categories.len(), step);
+            }
+
+        }
+    }
+
+    // ── Select handler ────────────────────────────────────────────────────────
+    async fn handle_select(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
         match self.state.active_screen.clone() {
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Artists => {
+                let Some(ref tracks) = self.state.library.artist_top_tracks else {
+                    self.load_artist_top_tracks(spotify.clone()).await;
+                    return;
+                };
+                let current_idx = self.state.library.selected_artist_track;
+                let uris: Vec<String> = tracks.iter()
+                    .skip(current_idx)
+                    .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
+                    .collect();
+                let name = tracks.get(current_idx).map(|t| t.name.clone()).unwrap_or_default();
+                let artist = self.state.library.followed_artists
+                    .get(self.state.library.selected_artist)
+                    .map_or_else(|| "artist".to_string(), |a| a.name.clone());
+                if let Some(batch) = self.play_checked(spotify.clone(), uris).await {
+                    let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+                    self.state.set_notification(Notification::info(msg));
+                    self.state.queue.set_origin(format!("{artist}'s top tracks"), batch.uris);
+                }
+            }
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Albums => {
+                let library = &self.state.library;
+                let Some(saved) = library.open_album() else { return };
+                // From the highlighted track when drilled in, else the whole album
+                let offset = if library.viewing_album {
+                    saved.album.tracks.items.get(library.selected_album_track).and_then(|t| t.id.as_ref()).map(|id| Offset::Uri(id.uri()))
+                } else {
+                    None
+                };
+                let context = saved.album.id.uri();
+                let name = saved.album.name.clone();
+                let uris: Vec<String> = saved.album.tracks.items.iter().filter_map(|t| t.id.as_ref().map(|id| id.uri())).collect();
+                let player = Player::new(spotify.clone());
+                match player.play_context(&context, offset).await {
+                    Ok(_) => {
+                        self.state.queue.set_origin(format!("album {name}"), uris);
+                        self.state.set_notification(Notification::info(format!("Playing album: {name}")));
+                    }
+                    Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+                }
+            }
+            ActiveScreen::Stats => self.play_from_stats(spotify.clone()).await,
+            ActiveScreen::Browse if self.state.browse.viewing_playlists => {
+                let Some(pl) = self.state.browse.selected_playlist().cloned() else { return };
+                match Player::new(spotify.clone()).play_context(&pl.id.uri(), None).await {
+                    Ok(_) => {
+                        // Its tracks aren't loaded here; clearing the old origin keeps the
+                        // Queue from crediting them to whatever was playing before
+                        self.state.queue.set_origin(format!("playlist {}", pl.name), Vec::new());
+                        self.state.set_notification(Notification::info(format!("Playing playlist: {}", pl.name)));
+                    }
+                    Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+                }
+            }
+            ActiveScreen::Browse => self.open_category(spotify.clone()).await,
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Playlists && !self.state.search.playlists.is_empty() => {
+                self.state.set_notification(Notification::info("Press F to follow this playlist"));
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Playlists => {}
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Albums => {
+                let Some(album) = self.state.search.selected_album().cloned() else { return };
+                let Some(id) = album.id else { return };
+                match Player::new(spotify.clone()).play_context(&id.uri(), None).await {
+                    Ok(_) => {
+                        // Search results carry no track list, so there's nothing to credit yet
+                        self.state.queue.set_origin(format!("album {}", album.name), Vec::new());
+                        self.state.set_notification(Notification::info(format!("Playing album: {}", album.name)));
+                    }
+                    Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+                }
+            }
             ActiveScreen::Search => {
                 let current_idx = self.state.search.selected_track;
                 let uris: Vec<String> = self.state.search.tracks.iter()
@@ -401,15 +2583,11 @@ impl App {
                     .take(50)
                     .collect();
                 
-                if let Some(track) = self.state.search.tracks.get(current_idx) {
-                    if !uris.is_empty() {
-                        let player = Player::new(spotify.clone());
-                        let uri_refs: Vec<&str> = uris.iter().map(|s| s.as_str()).collect();
-                        match player.play_tracks(uri_refs).await {
-                            Ok(_) => self.state.set_notification(Notification::info(format!("Playing: {}", track.name))),
-                            Err(e) => self.state.set_notification(Notification::error(format!("{e}"))),
-                        }
-                    }
+                let Some(name) = self.state.search.tracks.get(current_idx).map(|t| t.name.clone()) else { return };
+                if let Some(batch) = self.play_checked(spotify.clone(), uris).await {
+                    let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+                    self.state.set_notification(Notification::info(msg));
+                    self.state.queue.set_origin(format!("search “{}”", self.state.search.query), batch.uris);
                 }
             }
             ActiveScreen::Library => {
@@ -420,15 +2598,11 @@ impl App {
                     .take(50)
                     .collect();
 
-                if let Some(saved) = self.state.library.liked_songs.get(current_idx) {
-                    if !uris.is_empty() {
-                        let player = Player::new(spotify.clone());
-                        let uri_refs: Vec<&str> = uris.iter().map(|s| s.as_str()).collect();
-                        match player.play_tracks(uri_refs).await {
-                            Ok(_) => self.state.set_notification(Notification::info(format!("Playing: {}", saved.track.name))),
-                            Err(e) => self.state.set_notification(Notification::error(format!("{e}"))),
-                        }
-                    }
+                let Some(name) = self.state.library.liked_songs.get(current_idx).map(|s| s.track.name.clone()) else { return };
+                if let Some(batch) = self.play_checked(spotify.clone(), uris).await {
+                    let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+                    self.state.set_notification(Notification::info(msg));
+                    self.state.queue.set_origin("Liked Songs", batch.uris);
                 }
             }
             ActiveScreen::Playlists => {
@@ -442,92 +2616,1229 @@ impl App {
                     if let Some(pid) = playlist_id {
                         self.load_playlist_tracks(spotify.clone(), pid).await;
                     }
+                } else if self.state.playlists.sort.column.is_some() {
+                    // Spotify only plays a playlist in its own order, so a sorted
+                    // one plays as shown, the next 50 from the selected track
+                    use rspotify::model::PlayableItem;
+                    let current_idx = self.state.playlists.selected_track;
+                    let uris: Vec<String> = self.state.playlists.playlist_tracks.iter()
+                        .skip(current_idx)
+                        .filter_map(|item| match item.track {
+                            Some(PlayableItem::Track(ref t)) => t.id.as_ref().map(|id| id.uri()),
+                            _ => None,
+                        })
+                        .take(50)
+                        .collect();
+                    let Some(Some(PlayableItem::Track(track))) =
+                        self.state.playlists.playlist_tracks.get(current_idx).map(|item| &item.track)
+                    else {
+                        return;
+                    };
+                    let name = track.name.clone();
+                    let playlist = self.state.playlists.playlists
+                        .get(self.state.playlists.selected_playlist)
+                        .map_or_else(|| "playlist".to_string(), |p| format!("playlist {} (sorted)", p.name));
+                    if let Some(batch) = self.play_checked(spotify.clone(), uris).await {
+                        let msg = batch.toast(format!("Playing as sorted: {}", batch.first.as_deref().unwrap_or(&name)));
+                        self.state.set_notification(Notification::info(msg));
+                        self.state.queue.set_origin(playlist, batch.uris);
+                    }
                 } else {
-                    // Play selected track
+                    // Play the playlist itself from the selected track, so the
+                    // whole thing plays on rather than just the next 50
                     use rspotify::model::PlayableItem;
+                    let Some(playlist_id) = self.open_playlist_id() else { return };
                     let current_idx = self.state.playlists.selected_track;
+                    let Some(Some(PlayableItem::Track(track))) =
+                        self.state.playlists.playlist_tracks.get(current_idx).map(|item| &item.track)
+                    else {
+                        return;
+                    };
+                    let name = track.name.clone();
+                    // By position, so a track that's in twice starts at this copy
+                    let position = self.state.playlists.playlist_tracks.get(current_idx)
+                        .and_then(|item| self.state.playlists.positions.get(&playlist_entry_key(item)).copied())
+                        .unwrap_or(current_idx);
+                    let playlist = self.state.playlists.playlists
+                        .get(self.state.playlists.selected_playlist)
+                        .map_or_else(|| "playlist".to_string(), |p| format!("playlist {}", p.name));
                     let uris: Vec<String> = self.state.playlists.playlist_tracks.iter()
-                        .skip(current_idx)
-                        .filter_map(|item| {
-                            if let Some(PlayableItem::Track(ref track)) = item.track {
-                                track.id.as_ref().map(|id| id.uri())
-                            } else {
-                                None
-                            }
+                        .filter_map(|item| match item.track {
+                            Some(PlayableItem::Track(ref t)) => t.id.as_ref().map(|id| id.uri()),
+                            _ => None,
                         })
+                        .collect();
+                    let player = Player::new(spotify.clone());
+                    match player.play_context(&format!("spotify:playlist:{playlist_id}"), Some(Player::row_offset(position))).await {
+                        Ok(_) => {
+                            self.state.queue.set_origin(playlist, uris);
+                            self.state.set_notification(Notification::info(format!("Playing: {name}")));
+                        }
+                        Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+                    }
+                }
+            }
+            ActiveScreen::Queue => {
+                self.state.queue.toggle_selected_group();
+                self.clamp_queue_selection();
+            }
+            ActiveScreen::Vibes => {
+                if self.state.vibes.recommendations.is_empty() {
+                    // Load recommendations for selected mood
+                    if let Some(mood) = self.state.vibes.moods.get(self.state.vibes.selected_mood).cloned() {
+                        self.load_vibes(spotify.clone(), mood).await;
+                    }
+                } else {
+                    // Play selected recommendation
+                    let current_idx = self.state.vibes.selected_track;
+                    let uris: Vec<String> = self.state.vibes.recommendations.iter()
+                        .skip(current_idx)
+                        .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
                         .take(50)
                         .collect();
 
-                    if let Some(item) = self.state.playlists.playlist_tracks.get(current_idx) {
-                        if !uris.is_empty() {
-                            let player = Player::new(spotify.clone());
-                            let uri_refs: Vec<&str> = uris.iter().map(|s| s.as_str()).collect();
-                            match player.play_tracks(uri_refs).await {
-                                Ok(_) => {
-                                    if let Some(PlayableItem::Track(ref track)) = item.track {
-                                        self.state.set_notification(Notification::info(format!("Playing: {}", track.name)));
-                                    }
-                                }
-                                Err(e) => self.state.set_notification(Notification::error(format!("{e}"))),
-                            }
+                    let Some(name) = self.state.vibes.recommendations.get(current_idx).map(|t| t.name.clone()) else { return };
+                    if let Some(batch) = self.play_checked(spotify.clone(), uris).await {
+                        let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+                        self.state.set_notification(Notification::info(msg));
+                        let mood = self.state.vibes.moods.get(self.state.vibes.selected_mood).map(|m| m.name.clone());
+                        self.state.queue.set_origin(format!("Vibes · {}", mood.unwrap_or_default()), batch.uris);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn submit_prompt(&mut self, prompt: Prompt, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let input = prompt.input.trim().to_string();
+        if input.is_empty() {
+            return;
+        }
+        match prompt.kind {
+            PromptKind::NewPlaylist => {
+                match Library::new(spotify).create_playlist(&input).await {
+                    Ok(pl) => {
+                        self.state.playlists.playlists.insert(0, pl);
+                        self.state.playlists.selected_playlist = 0;
+                        self.state.playlists.viewing_tracks = false;
+                        self.state.navigate_to(ActiveScreen::Playlists);
+                        self.state.set_notification(Notification::info(format!("Created playlist “{input}”")));
+                    }
+                    Err(e) => {
+                        // Keep the popup open with the name so Enter tries again
+                        self.state.prompt = Some(prompt);
+                        self.state.set_notification(Notification::failure(format!("Could not create playlist: {e}"), &e));
+                    }
+                }
+            }
+            PromptKind::MoodFilter => self.open_mood(&input, spotify).await,
+            PromptKind::Command => match input.trim_start_matches(':') {
+                cmd if cmd.split_whitespace().next() == Some("export") => {
+                    let args: Vec<&str> = cmd.split_whitespace().skip(1).collect();
+                    self.export_plays(&args);
+                }
+                cmd if cmd.split_whitespace().next() == Some("cheatsheet") => {
+                    let path = cmd.trim_start_matches("cheatsheet").trim().to_string();
+                    self.write_cheat_sheet(&path);
+                }
+                cmd if cmd.split_whitespace().next() == Some("mood") => {
+                    let expr = cmd.trim_start_matches("mood").trim().to_string();
+                    self.open_mood(&expr, spotify).await;
+                }
+                cmd if cmd.split_whitespace().next() == Some("profile") => {
+                    let arg = cmd.trim_start_matches("profile").trim().to_string();
+                    self.switch_to_profile(&arg);
+                }
+                cmd if cmd.split_whitespace().next() == Some("trace") => {
+                    let arg = cmd.trim_start_matches("trace").trim().to_string();
+                    self.set_tracing(&arg);
+                }
+                cmd if cmd.split_whitespace().next() == Some("decade") => {
+                    let arg = cmd.trim_start_matches("decade").trim().to_string();
+                    self.filter_decade(&arg);
+                }
+                "features" => self.state.show_features = true,
+                "messages" => self.state.show_messages = true,
+                "dedupe" => self.open_dedupe(spotify),
+                "archived" => self.open_archived(),
+                "jobs" => self.open_jobs(),
+                "devices" => self.open_devices(spotify),
+                "login" => {
+                    self.login_declined = false;
+                    self.needs_login = true;
+                }
+                "log" => self.state.log_view = Some(LogView::open(self.config.log_path())),
+                "inbox" => self.open_inbox(spotify),
+                "help" => self.state.show_help = true,
+                "q" | "quit" => self.state.should_quit = true,
+                other => self.state.set_notification(Notification::warning(format!("Unknown command: {other}"))),
+            },
+        }
+    }
+
+    /// Drops the highlighted track from the open playlist right away and
+    /// removes it on Spotify in the background, rolling back if that fails
+    fn remove_from_playlist(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.active_screen != ActiveScreen::Playlists {
+            return;
+        }
+        let Some(playlist_id) = self.open_playlist_id() else { return };
+        if self.state.playlists.paging_tracks {
+            // The full list replaces what's shown once it lands, which would undo this
+            self.state.set_notification(Notification::info("Still loading the rest of this playlist"));
+            return;
+        }
+        let Some(pl) = self.state.playlists.playlists.get(self.state.playlists.selected_playlist) else { return };
+        if let Some(ref me) = self.state.user_id {
+            if pl.owner.id.id() != me {
+                self.state.set_notification(Notification::info("You can only remove tracks from your own playlists"));
+                return;
+            }
+        }
+        let name = pl.name.clone();
+        let Some((uri, track_name)) = self.selected_track() else { return };
+
+        // Spotify removes every occurrence, so mirror that locally
+        let removed = take_matching(&mut self.state.playlists.playlist_tracks, |item| {
+            matches!(item.track, Some(rspotify::model::PlayableItem::Track(ref t))
+                if t.id.as_ref().is_some_and(|id| id.uri() == uri))
+        });
+        let len = self.state.playlists.playlist_tracks.len();
+        self.state.playlists.selected_track = self.state.playlists.selected_track.min(len.saturating_sub(1));
+        self.state.set_notification(Notification::info(format!("Removed “{track_name}” from {name}")));
+
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let result = Library::new(spotify).remove_track_from_playlist(&playlist_id, &uri).await;
+            let _ = tx.send(BgEvent::PlaylistRemoval { playlist_id, name, removed, result });
+        });
+    }
+
+    // ── Devices ───────────────────────────────────────────────────────────────
+    /// gd: fetches the devices, and the popup opens once they're in
+    fn open_devices(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::Devices(Player::new(spotify).devices().await));
+        });
+    }
+
+    /// Enter (`play`) or Space in the devices popup: moves playback to the
+    /// highlighted device and closes the popup
+    fn transfer_selected(&mut self, play: bool, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(view) = self.state.devices.take() else { return };
+        let Some(device) = view.devices.get(view.selected) else { return };
+        let Some(id) = device.id.clone().filter(|_| !device.is_restricted) else {
+            self.state.set_notification(Notification::warning(format!("{} can't be controlled from here", device.name)));
+            self.state.devices = Some(view);
+            return;
+        };
+        let (tx, name) = (self.bg_tx.clone(), device.name.clone());
+        tokio::spawn(async move {
+            let result = Player::new(spotify).transfer(&id, play).await;
+            let _ = tx.send(BgEvent::Transferred { name, play, result });
+        });
+    }
+
+    /// `vibes ctl transfer`: the device is named by ID or name, so the list
+    /// is fetched first to find it
+    fn transfer_to_named(&mut self, wanted: String, play: bool, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let player = Player::new(spotify);
+            let found = async {
+                let devices = player.devices().await?;
+                let device = find_device(&devices, &wanted).with_context(|| format!("no device called {wanted}"))?;
+                let id = device.id.clone().filter(|_| !device.is_restricted).context("it can't be controlled from here")?;
+                anyhow::Ok((id, device.name.clone()))
+            };
+            let (name, result) = match found.await {
+                Ok((id, name)) => (name, player.transfer(&id, play).await),
+                Err(e) => (wanted, Err(e)),
+            };
+            let _ = tx.send(BgEvent::Transferred { name, play, result });
+        });
+    }
+
+    // ── Jobs ──────────────────────────────────────────────────────────────────
+    fn open_jobs(&mut self) {
+        if self.state.jobs.is_empty() {
+            self.state.set_notification(Notification::info("No jobs running"));
+            return;
+        }
+        self.state.show_jobs = true;
+        self.state.selected_job = 0;
+    }
+
+    /// x in the Jobs panel: asks the highlighted job to stop after its current step
+    fn cancel_job(&mut self) {
+        let Some(job) = self.state.jobs.running().get(self.state.selected_job).cloned() else { return };
+        if self.state.jobs.cancel(job.id) {
+            self.state.set_notification(Notification::info(format!("Cancelling {} — {}…", job.kind.name(), job.label)));
+        }
+    }
+
+    /// Takes a job whose task reported back off the panel. A cancelled job
+    /// gets its toast here and `false` back, so callers only announce results
+    fn finish_job(&mut self, id: JobId) -> bool {
+        let Some(job) = self.state.jobs.finish(id) else { return true };
+        let left = self.state.jobs.running().len();
+        self.state.selected_job = self.state.selected_job.min(left.saturating_sub(1));
+        if left == 0 {
+            self.state.show_jobs = false;
+        }
+        if !job.handle.is_cancelled() {
+            return true;
+        }
+        self.state.set_notification(Notification::info(format!(
+            "{} cancelled after {} — {}",
+            job.kind.name(),
+            job.progress(),
+            job.label
+        )));
+        false
+    }
+
+    // ── Playlist archive ──────────────────────────────────────────────────────
+    /// X on the playlist list: saves the marked playlists (or the highlighted
+    /// one) to the archive folder, then unfollows them, in the background.
+    /// Stops at the first failure so nothing is unfollowed without a backup.
+    fn archive_playlists(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let playlists = &self.state.playlists;
+        if self.state.active_screen != ActiveScreen::Playlists || playlists.viewing_tracks {
+            return;
+        }
+        let Some(dir) = archive::default_dir() else {
+            self.state.set_notification(Notification::warning("Nowhere to archive to — set XDG_DATA_HOME or HOME"));
+            return;
+        };
+        let marked = self.state.marked_playlists();
+        let targets: Vec<_> = if marked.is_empty() {
+            playlists.playlists.get(playlists.selected_playlist).cloned().into_iter().collect()
+        } else {
+            playlists.playlists.iter().filter(|pl| marked.contains(&pl.id.uri())).cloned().collect()
+        };
+        if targets.is_empty() {
+            return;
+        }
+        if self.state.jobs.is_running(JobKind::Archive) {
+            self.state.set_notification(Notification::info("Still archiving — gb shows how far along"));
+            return;
+        }
+        self.state.visual = None;
+        self.state.set_notification(Notification::info(format!("Archiving {} playlist(s)… gb shows progress", targets.len())));
+        let label = match &targets[..] {
+            [pl] => pl.name.clone(),
+            _ => format!("{} playlists", targets.len()),
+        };
+        let (job, handle) = self.state.jobs.start(JobKind::Archive, label);
+        handle.set_total(targets.len());
+        let user_id = self.state.user_id.clone();
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let lib = Library::new(spotify);
+            let mut archived = Vec::new();
+            let mut result = Ok(());
+            for pl in &targets {
+                if handle.is_cancelled() {
+                    break;
+                }
+                let id = pl.id.id();
+                let step = async {
+                    let items = lib.get_playlist_tracks(id).await?;
+                    archive::save(&dir, &PlaylistArchive::new(pl, user_id.as_deref(), &items))?;
+                    lib.unfollow_playlist(id).await
+                };
+                if let Err(e) = step.await {
+                    result = Err(e.context(format!("could not archive {}", pl.name)));
+                    break;
+                }
+                archived.push(id.to_string());
+                handle.advance(1);
+            }
+            let _ = tx.send(BgEvent::Archived { job, archived, result });
+        });
+    }
+
+    fn open_archived(&mut self) {
+        let Some(dir) = archive::default_dir() else { return };
+        match archive::load_all(&dir) {
+            Ok(entries) if entries.is_empty() => {
+                self.state.set_notification(Notification::info("Nothing archived yet — X on a playlist archives it"));
+            }
+            Ok(entries) => self.state.archived = Some(ArchiveView { entries, selected: 0 }),
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not read the archive: {e}"), &e)),
+        }
+    }
+
+    /// Enter in `:archived`: follows the playlist again, or for the user's own
+    /// playlists that Spotify no longer has, recreates it from the archived
+    /// tracks, 100 at a time so a cancel can land in between. A recreation
+    /// that's cancelled or fails is unfollowed again, leaving the archive
+    /// entry to retry from scratch.
+    fn restore_archived(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(entry) = self.state.archived.as_ref().and_then(|a| a.entries.get(a.selected)).cloned() else { return };
+        let Some(dir) = archive::default_dir() else { return };
+        if self.state.jobs.is_running(JobKind::Restore) {
+            self.state.set_notification(Notification::info("Still restoring — gb shows how far along"));
+            return;
+        }
+        let (job, handle) = self.state.jobs.start(JobKind::Restore, entry.name.clone());
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let lib = Library::new(spotify);
+            // Spotify's API can't add local files, so a recreation leaves them out
+            let uris: Vec<String> =
+                entry.snapshot.tracks.iter().map(|(uri, _)| uri.clone()).filter(|uri| !snapshots::is_local(uri)).collect();
+            handle.set_total(uris.len());
+            let mut result = lib.follow_playlist(&entry.id).await;
+            if result.is_err() && entry.owned {
+                result = async {
+                    let pl = lib.create_playlist(&entry.name).await?;
+                    let filled = async {
+                        for chunk in uris.chunks(100) {
+                            handle.check()?;
+                            lib.add_tracks_to_playlist(pl.id.id(), chunk).await?;
+                            handle.advance(chunk.len());
+                        }
+                        Ok(())
+                    }
+                    .await;
+                    // A half-filled copy would sit next to the next attempt's
+                    if filled.is_err() {
+                        if let Err(e) = lib.unfollow_playlist(pl.id.id()).await {
+                            warn!("Could not remove the partly restored “{}”: {e:#}", entry.name);
+                        }
+                    }
+                    filled
+                }
+                .await;
+            } else {
+                handle.advance(uris.len());
+            }
+            let result = result.and_then(|()| archive::remove(&dir, &entry.id));
+            let playlists = match result {
+                Ok(()) => lib.get_user_playlists().await.ok(),
+                Err(_) => None,
+            };
+            let _ = tx.send(BgEvent::Restored { job, entry: Box::new(entry), playlists, result });
+        });
+    }
+
+    // ── Inbox ─────────────────────────────────────────────────────────────────
+    fn inbox_path(&self) -> Option<std::path::PathBuf> {
+        self.config.inbox_file.clone().or_else(inbox::default_path)
+    }
+
+    /// Reloads the inbox when its file changed (`vibes inbox add`, or a
+    /// script appending links) and says how many new links came in
+    fn check_inbox(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(path) = self.inbox_path() else { return };
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if modified == self.inbox_seen {
+            return;
+        }
+        self.inbox_seen = modified;
+        let items = match inbox::load(&path) {
+            Ok(items) => items,
+            Err(e) => {
+                warn!("Inbox load error: {e}");
+                return;
+            }
+        };
+        let new = items.iter().filter(|i| !self.state.inbox.contains(&i.uri)).count();
+        self.set_inbox(items);
+        if new > 0 {
+            self.state.set_notification(Notification::info(format!("📥 {new} new in the inbox — gi to listen")));
+        }
+        if self.state.inbox.open {
+            self.fetch_inbox_tracks(spotify);
+        }
+    }
+
+    fn set_inbox(&mut self, items: Vec<inbox::InboxItem>) {
+        let inbox = &mut self.state.inbox;
+        inbox.items = items;
+        inbox.selected = inbox.selected.min(inbox.items.len().saturating_sub(1));
+        if inbox.items.is_empty() {
+            inbox.open = false;
+        }
+    }
+
+    fn open_inbox(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.inbox.items.is_empty() {
+            self.state.set_notification(Notification::info("The inbox is empty — send links with `vibes inbox add <link>`"));
+            return;
+        }
+        self.state.inbox.open = true;
+        self.state.inbox.selected = 0;
+        self.fetch_inbox_tracks(spotify);
+    }
+
+    /// Looks up names for inbox rows that don't have one yet
+    fn fetch_inbox_tracks(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let inbox = &self.state.inbox;
+        let uris: Vec<String> = inbox.items.iter().map(|i| i.uri.clone()).filter(|uri| !inbox.tracks.contains_key(uri)).collect();
+        if uris.is_empty() || self.state.connection.offline {
+            return;
+        }
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            match Library::new(spotify).get_tracks(&uris).await {
+                Ok(tracks) => {
+                    let _ = tx.send(BgEvent::InboxTracks(tracks));
+                }
+                Err(e) => warn!("Inbox tracks load error: {e}"),
+            }
+        });
+    }
+
+    /// Takes accepted or dismissed links out of the inbox, on disk too
+    fn drop_from_inbox(&mut self, uris: &[String]) {
+        if !uris.iter().any(|uri| self.state.inbox.contains(uri)) {
+            return;
+        }
+        let Some(path) = self.inbox_path() else { return };
+        match inbox::remove(&path, uris) {
+            Ok(kept) => {
+                // Our own write; don't reload it as news
+                self.inbox_seen = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                self.set_inbox(kept);
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not update the inbox: {e}"), &e)),
+        }
+    }
+
+    /// Enter in the inbox: plays the link to hear it before deciding
+    async fn play_from_inbox(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(item) = self.state.inbox.selected_item() else { return };
+        let uri = item.uri.clone();
+        let name = self.state.inbox.tracks.get(&uri).map_or_else(|| uri.clone(), |t| t.name.clone());
+        match Player::new(spotify).play_tracks(vec![&uri]).await {
+            Ok(()) => self.state.set_notification(Notification::info(format!("Playing: {name}"))),
+            Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+        }
+    }
+
+    /// a in the inbox: queues the link and takes it out of the inbox
+    async fn queue_from_inbox(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(item) = self.state.inbox.selected_item() else { return };
+        let uri = item.uri.clone();
+        let name = self.state.inbox.tracks.get(&uri).map_or_else(|| uri.clone(), |t| t.name.clone());
+        match Queue::new(spotify).add_to_queue(&uri).await {
+            Ok(_) => {
+                self.state.queue.manual.insert(uri.clone());
+                self.state.set_notification(Notification::info(format!("Queued “{name}” ✓")));
+                self.drop_from_inbox(&[uri]);
+            }
+            Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+        }
+    }
+
+    // ── Mood filter ───────────────────────────────────────────────────────────
+    /// Lists the liked songs whose indexed audio features pass `expr`; a
+    /// filter that doesn't parse goes back into the prompt to be fixed
+    async fn open_mood(&mut self, expr: &str, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let filter = match MoodFilter::parse(expr) {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.state.prompt = Some(Prompt { kind: PromptKind::MoodFilter, input: expr.to_string() });
+                self.state.set_notification(Notification::warning(e.to_string()));
+                return;
+            }
+        };
+        self.mood_expr = expr.to_string();
+        if self.state.feature_index.is_empty() {
+            self.state.set_notification(Notification::info("No audio features indexed yet — set feature_index = true in config.toml"));
+            return;
+        }
+        self.load_library(spotify, false).await;
+        let mut unindexed = 0;
+        let tracks: Vec<(FullTrack, TrackFeatures)> = self.state.library.liked_songs.iter()
+            .filter_map(|s| {
+                let uri = s.track.id.as_ref()?.uri();
+                let Some(features) = self.state.feature_index.get(&uri) else {
+                    unindexed += 1;
+                    return None;
+                };
+                filter.matches(features).then(|| (s.track.clone(), *features))
+            })
+            .collect();
+        if tracks.is_empty() {
+            let msg = match unindexed {
+                0 => format!("No liked songs match {filter}"),
+                n => format!("No liked songs match {filter} ({n} not indexed yet)"),
+            };
+            self.state.set_notification(Notification::info(msg));
+            return;
+        }
+        self.state.mood = Some(MoodView { filter, tracks, unindexed, selected: 0 });
+    }
+
+    /// Enter in the mood popup: plays the matches from the highlighted one on
+    async fn play_mood_matches(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(mood) = self.state.mood.as_ref() else { return };
+        let uris: Vec<String> = mood.tracks.iter()
+            .skip(mood.selected)
+            .filter_map(|(t, _)| t.id.as_ref().map(|id| id.uri()))
+            .take(50)
+            .collect();
+        let Some((first, _)) = mood.tracks.get(mood.selected) else { return };
+        let (name, label) = (first.name.clone(), format!("Mood: {}", mood.filter));
+        if let Some(batch) = self.play_checked(spotify, uris).await {
+            let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+            self.state.set_notification(Notification::info(msg));
+            self.state.queue.set_origin(label, batch.uris);
+            self.state.mood = None;
+        }
+    }
+
+    /// c in the mood popup: saves every match as a new playlist named after the filter
+    async fn save_mood_playlist(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(mood) = self.state.mood.as_ref() else { return };
+        let name = format!("Vibes: {}", mood.filter);
+        let uris: Vec<String> = mood.tracks.iter().filter_map(|(t, _)| t.id.as_ref().map(|id| id.uri())).collect();
+        let lib = Library::new(spotify);
+        let result = async {
+            let pl = lib.create_playlist(&name).await?;
+            lib.add_tracks_to_playlist(pl.id.id(), &uris).await?;
+            Ok::<_, anyhow::Error>(pl)
+        }
+        .await;
+        match result {
+            Ok(pl) => {
+                // Keep the highlighted playlist where it was
+                let playlists = &mut self.state.playlists;
+                if !playlists.playlists.is_empty() {
+                    playlists.selected_playlist += 1;
+                }
+                playlists.playlists.insert(0, pl);
+                self.state.mood = None;
+                self.state.set_notification(Notification::info(format!("Saved {} tracks to “{name}”", uris.len())));
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not save “{name}”: {e}"), &e)),
+        }
+    }
+
+    // ── Library dedupe ────────────────────────────────────────────────────────
+    /// `:dedupe`: loads whatever of Liked Songs and saved albums isn't loaded
+    /// yet in the background, then opens the popup from `BgEvent::DedupeLoaded`
+    fn open_dedupe(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.jobs.is_running(JobKind::Dedupe) {
+            return;
+        }
+        let library = &self.state.library;
+        let need_liked = library.liked_songs.is_empty() && !library.paging_liked;
+        let need_albums = library.saved_albums.is_empty();
+        let (job, handle) = self.state.jobs.start(JobKind::Dedupe, "Liked Songs vs saved albums");
+        handle.set_total(usize::from(need_liked) + usize::from(need_albums));
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let lib = Library::new(spotify);
+            let (mut liked, mut albums) = (None, None);
+            let result = async {
+                if need_liked {
+                    liked = Some(lib.get_liked_songs().await?);
+                    handle.advance(1);
+                }
+                handle.check()?;
+                if need_albums {
+                    albums = Some(lib.get_saved_albums().await?);
+                    handle.advance(1);
+                }
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(BgEvent::DedupeLoaded { job, liked, albums, result });
+        });
+    }
+
+    /// Rebuilds the index after Liked Songs or saved albums change
+    fn refresh_library_index(&mut self) {
+        let index = LibraryIndex::from_library(&self.state.library);
+        self.state.library.on_saved_album = index.on_saved_albums();
+        if let Some(dedupe) = self.state.dedupe.as_mut() {
+            dedupe.overlaps = index.overlaps();
+            dedupe.selected = dedupe.selected.min(dedupe.overlaps.len().saturating_sub(1));
+        }
+        self.index_features(&index);
+    }
+
+    /// `feature_index`: fetches audio features for library tracks that aren't
+    /// indexed yet, a batch every few seconds, backing off when Spotify
+    /// refuses. Runs again whenever the library changes, so a stopped run
+    /// picks up where it left off.
+    fn index_features(&mut self, library: &LibraryIndex) {
+        if !self.config.feature_index
+            || self.index_cancelled
+            || self.state.jobs.is_running(JobKind::Index)
+            || !self.state.features.enabled(Feature::AudioFeatures)
+            || !self.state.capabilities.audio_features.usable()
+        {
+            return;
+        }
+        let Some(spotify) = self.state.spotify.clone() else { return };
+        let missing = self.state.feature_index.missing(library.track_uris());
+        if missing.is_empty() {
+            return;
+        }
+        info!("Indexing audio features for {} tracks", missing.len());
+        let (job, handle) = self.state.jobs.start(JobKind::Index, "Audio features of the library");
+        handle.set_total(missing.len());
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let analysis = Analysis::new(spotify);
+            let mut batches = missing.chunks(features::BATCH_SIZE);
+            let mut batch = batches.next();
+            let (mut pause, mut failures) = (INDEX_PACE, 0);
+            while let Some(uris) = batch.filter(|_| !handle.is_cancelled()) {
+                match analysis.get_audio_features_batch(uris).await {
+                    Ok(found) => {
+                        let entries = uris
+                            .iter()
+                            .map(|uri| IndexEntry {
+                                uri: uri.clone(),
+                                features: found.iter().find(|f| f.id.uri() == *uri).map(TrackFeatures::from),
+                            })
+                            .collect();
+                        if tx.send(BgEvent::FeaturesIndexed(entries)).is_err() {
+                            return;
+                        }
+                        handle.advance(uris.len());
+                        (pause, failures) = (INDEX_PACE, 0);
+                        batch = batches.next();
+                    }
+                    Err(e) => {
+                        failures += 1;
+                        if failures == INDEX_RETRIES {
+                            let _ = tx.send(BgEvent::FeatureIndexDone { job, result: Err(e) });
+                            return;
                         }
+                        pause = (pause * 2).min(INDEX_MAX_BACKOFF);
                     }
                 }
+                if batch.is_some() {
+                    time::sleep(pause).await;
+                }
+            }
+            let _ = tx.send(BgEvent::FeatureIndexDone { job, result: Ok(()) });
+        });
+    }
+
+    /// Likes every track of the highlighted album that isn't liked yet
+    async fn like_whole_album(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(album) = self.state.dedupe.as_ref().and_then(|d| d.overlaps.get(d.selected)).cloned() else { return };
+        if album.missing.is_empty() {
+            self.state.set_notification(Notification::info(format!("Every track of {} is already liked", album.name)));
+            return;
+        }
+        match Library::new(spotify.clone()).like_tracks(&album.missing).await {
+            Ok(()) => {
+                for uri in &album.missing {
+                    self.state.note_liked(uri, true);
+                }
+                self.state.set_notification(Notification::info(format!(
+                    "❤ Liked {} more from {}", album.missing.len(), album.name
+                )));
+                // Fetch so the new SavedTracks (with dates) show up
+                self.refresh_recent_likes(spotify).await;
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not like {}: {e}", album.name), &e)),
+        }
+    }
+
+    /// Unlikes liked songs that the highlighted saved album already covers
+    async fn unlike_album_duplicates(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(album) = self.state.dedupe.as_ref().and_then(|d| d.overlaps.get(d.selected)).cloned() else { return };
+        match Library::new(spotify).unlike_tracks(&album.liked).await {
+            Ok(()) => {
+                for uri in &album.liked {
+                    self.state.note_liked(uri, false);
+                }
+                self.state.library.liked_songs.retain(|s| {
+                    !s.track.id.as_ref().is_some_and(|id| album.liked.contains(&id.uri()))
+                });
+                let len = self.state.library.liked_songs.len();
+                self.state.library.selected = self.state.library.selected.min(len.saturating_sub(1));
+                self.state.set_notification(Notification::info(format!(
+                    "Removed {} duplicate(s) of {} from Liked Songs", album.liked.len(), album.name
+                )));
+                self.refresh_library_index();
+                if self.state.dedupe.as_ref().is_some_and(|d| d.overlaps.is_empty()) {
+                    self.state.dedupe = None;
+                }
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not unlike tracks: {e}"), &e)),
+        }
+    }
+
+    async fn follow_playlist(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let search = &self.state.search;
+        let pl = match self.state.active_screen {
+            ActiveScreen::Search if search.tab == SearchTab::Playlists => search.playlists.get(search.selected_playlist),
+            ActiveScreen::Browse => self.state.browse.selected_playlist(),
+            _ => None,
+        };
+        let Some(pl) = pl.cloned() else { return };
+        if self.state.playlists.playlists.iter().any(|p| p.id == pl.id) {
+            self.state.set_notification(Notification::info(format!("{} is already in your library", pl.name)));
+            return;
+        }
+        match Library::new(spotify.clone()).follow_playlist(pl.id.id()).await {
+            Ok(()) => {
+                self.state.set_notification(Notification::info(format!("Following {} ✓", pl.name)));
+                // Reload so the new playlist shows up in screen 3
+                self.state.playlists.playlists.clear();
+                self.state.playlists.selected_playlist = 0;
+                self.state.playlists.viewing_tracks = false;
+                self.load_playlists(spotify, true).await;
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not follow {}: {e}", pl.name), &e)),
+        }
+    }
+
+    async fn submit_picker(&mut self, picker: PlaylistPicker, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some(pl) = self.state.editable_playlists().get(picker.selected).map(|pl| (*pl).clone()) else { return };
+        match Library::new(spotify).add_tracks_to_playlist(pl.id.id(), &picker.track_uris).await {
+            Ok(_) => {
+                if let Some(p) = self.state.playlists.playlists.iter_mut().find(|p| p.id == pl.id) {
+                    p.tracks.total += picker.track_uris.len() as u32;
+                }
+                self.state.set_notification(Notification::info(format!(
+                    "Added “{}” to {} ✓", picker.track_name, pl.name
+                )));
+                self.drop_from_inbox(&picker.track_uris);
+            }
+            Err(e) => {
+                // Reopen on the same row so Enter tries again
+                self.state.picker = Some(picker);
+                self.state.set_notification(Notification::failure(format!("Could not add to {}: {e}", pl.name), &e));
+            }
+        }
+    }
+
+    /// URI and name of the highlighted track on screens that list tracks
+    fn selected_track(&self) -> Option<(String, String)> {
+        let library = &self.state.library;
+        if self.state.active_screen == ActiveScreen::Library && library.tab == LibraryTab::Albums {
+            if !library.viewing_album {
+                return None;
+            }
+            let track = library.open_album()?.album.tracks.items.get(library.selected_album_track)?;
+            return track.id.as_ref().map(|id| (id.uri(), track.name.clone()));
+        }
+        let track = self.selected_full_track()?;
+        track.id.as_ref().map(|id| (id.uri(), track.name.clone()))
+    }
+
+    /// (URI, name) of the highlighted row on any list: a track, or an album,
+    /// artist or playlist where those are listed
+    fn selected_item(&self) -> Option<(String, String)> {
+        let library = &self.state.library;
+        match self.state.active_screen {
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Playlists => {
+                let pl = self.state.search.playlists.get(self.state.search.selected_playlist)?;
+                Some((pl.id.uri(), pl.name.clone()))
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Albums => {
+                let album = self.state.search.selected_album()?;
+                Some((album.id.as_ref()?.uri(), album.name.clone()))
+            }
+            ActiveScreen::Library if library.tab == LibraryTab::Albums && !library.viewing_album => {
+                let album = &library.open_album()?.album;
+                Some((album.id.uri(), album.name.clone()))
+            }
+            ActiveScreen::Library if library.tab == LibraryTab::Artists && library.artist_top_tracks.is_none() => {
+                let artist = library.followed_artists.get(library.selected_artist)?;
+                Some((artist.id.uri(), artist.name.clone()))
+            }
+            ActiveScreen::Playlists if !self.state.playlists.viewing_tracks => {
+                let pl = self.state.playlists.playlists.get(self.state.playlists.selected_playlist)?;
+                Some((pl.id.uri(), pl.name.clone()))
+            }
+            ActiveScreen::Stats if self.state.stats.view == StatsView::Artists => {
+                let artist = self.state.stats.top_artists.get(self.state.stats.selected)?;
+                Some((artist.id.uri(), artist.name.clone()))
+            }
+            ActiveScreen::Browse => {
+                let pl = self.state.browse.selected_playlist()?;
+                Some((pl.id.uri(), pl.name.clone()))
+            }
+            _ => self.selected_track(),
+        }
+    }
+
+    /// The highlighted row on screens that list full tracks (album tracks are simplified)
+    fn selected_full_track(&self) -> Option<&FullTrack> {
+        use rspotify::model::PlayableItem;
+        let library = &self.state.library;
+        if self.state.active_screen == ActiveScreen::Library && library.tab == LibraryTab::Artists {
+            return library.artist_top_tracks.as_ref()?.get(library.selected_artist_track);
+        }
+        if self.state.active_screen == ActiveScreen::Library && library.tab == LibraryTab::Albums {
+            return None;
+        }
+        match self.state.active_screen {
+            ActiveScreen::Search if self.state.search.tab != SearchTab::Tracks => None,
+            ActiveScreen::Search => self.state.search.tracks.get(self.state.search.selected_track),
+            ActiveScreen::Library => self.state.library.liked_songs
+                .get(self.state.library.selected)
+                .map(|s| &s.track),
+            ActiveScreen::Playlists if self.state.playlists.viewing_tracks => {
+                match self.state.playlists.playlist_tracks.get(self.state.playlists.selected_track)?.track {
+                    Some(PlayableItem::Track(ref t)) => Some(t),
+                    _ => None,
+                }
+            }
+            ActiveScreen::Queue => self.state.queue.selected_track(),
+            ActiveScreen::Vibes => self.state.vibes.recommendations.get(self.state.vibes.selected_track),
+            ActiveScreen::Stats if self.state.stats.view == StatsView::Tracks => {
+                self.state.stats.top_tracks.get(self.state.stats.selected)
+            }
+            _ => None,
+        }
+    }
+
+    /// Every track row on the current screen; Liked Songs is left out as it's liked by definition
+    fn listed_tracks(&self) -> Vec<&FullTrack> {
+        use rspotify::model::PlayableItem;
+        let library = &self.state.library;
+        match self.state.active_screen {
+            ActiveScreen::Library if library.tab == LibraryTab::Artists => {
+                library.artist_top_tracks.iter().flatten().collect()
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Tracks => self.state.search.tracks.iter().collect(),
+            ActiveScreen::Playlists if self.state.playlists.viewing_tracks => self.state.playlists.playlist_tracks
+                .iter()
+                .filter_map(|item| match item.track {
+                    Some(PlayableItem::Track(ref t)) => Some(t),
+                    _ => None,
+                })
+                .collect(),
+            ActiveScreen::Queue => self.state.queue.tracks.iter().collect(),
+            ActiveScreen::Vibes => self.state.vibes.recommendations.iter().collect(),
+            ActiveScreen::Stats if self.state.stats.view == StatsView::Tracks => self.state.stats.top_tracks.iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Looks up Liked Songs membership for listed tracks and what's playing
+    /// that haven't been checked yet, so their hearts can be drawn
+    fn request_liked_status(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.connection.offline {
+            return;
+        }
+        let listed = self.listed_tracks().into_iter().filter_map(|t| t.id.as_ref()).map(|id| id.uri());
+        let uris: Vec<String> = listed.chain(self.state.current_track.id.clone()).collect();
+        let uris = self.state.liked.claim_unknown(uris);
+        if uris.is_empty() {
+            return;
+        }
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            match Library::new(spotify).liked_status(&uris).await {
+                Ok(liked) => {
+                    let _ = tx.send(BgEvent::LikedStatus(uris.into_iter().zip(liked).collect()));
+                }
+                Err(e) => warn!("Liked status check failed: {e}"),
+            }
+        });
+    }
+
+    /// l: likes or unlikes the highlighted track, or what's playing when no row is highlighted
+    async fn toggle_like(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let target = match self.selected_full_track() {
+            Some(track) => track.id.as_ref().map(|id| (id.uri(), track.name.clone(), HookTrack::from(track))),
+            None if !self.state.current_track.is_episode => {
+                let ct = &self.state.current_track;
+                ct.id.clone().map(|uri| (uri, ct.name.clone(), HookTrack::from(ct)))
+            }
+            None => None,
+        };
+        let Some((uri, name, hook_track)) = target else { return };
+        let offline = self.state.connection.offline;
+        let player = Player::new(spotify.clone());
+        let liked = match self.state.liked.get(&uri) {
+            Some(liked) => liked,
+            None if offline => false,
+            None => player.is_track_saved(&uri).await.unwrap_or(false),
+        };
+        if offline {
+            let write = if liked { PendingWrite::Unlike(uri.clone()) } else { PendingWrite::Like(uri.clone()) };
+            self.state.connection.queue(write);
+        } else {
+            let result = if liked { player.remove_track(&uri).await } else { player.save_track(&uri).await };
+            if let Err(e) = result {
+                self.state.set_notification(Notification::failure(format!("Could not update Liked Songs: {e}"), &e));
+                return;
+            }
+        }
+        self.state.note_liked(&uri, !liked);
+        if !liked {
+            self.run_hook(HookEvent::Like, hook_track);
+        }
+        let later = if offline { " once back online" } else { "" };
+        if liked {
+            let library = &mut self.state.library;
+            library.liked_songs.retain(|s| s.track.id.as_ref().is_none_or(|id| id.uri() != uri));
+            library.selected = library.selected.min(library.liked_songs.len().saturating_sub(1));
+            self.refresh_library_index();
+            self.state.set_notification(Notification::info(format!("Removed {name} from Liked Songs{later}")));
+        } else if offline {
+            self.state.set_notification(Notification::info(format!("❤ Adding {name} to Liked Songs{later}")));
+        } else {
+            self.state.set_notification(Notification::info(format!("❤ Added {name} to Liked Songs")));
+            // Fetch so the new SavedTrack (with its date) shows up
+            self.refresh_recent_likes(spotify).await;
+        }
+    }
+
+    /// Y: the highlighted track if there is one, else what's playing
+    fn copy_track_info(&mut self) {
+        let info = match self.selected_full_track() {
+            Some(track) => TrackInfo::from(track),
+            None if self.state.current_track.id.is_some() => TrackInfo::from(&self.state.current_track),
+            None => return,
+        };
+        let text = clipboard::format_track(&self.config.copy_template, &info);
+        match clipboard::copy(&text) {
+            Ok(()) => self.state.set_notification(Notification::info(format!("Copied: {text}"))),
+            Err(e) => self.state.set_notification(Notification::failure(format!("Copy failed: {e}"), &e)),
+        }
+    }
+
+    /// y / Y: copies the highlighted (or playing) track's open.spotify.com
+    /// link, or its `spotify:` URI
+    fn copy_track_link(&mut self, as_uri: bool) {
+        let uri = match self.selected_full_track() {
+            Some(track) => track.id.as_ref().map(|id| id.uri()),
+            None if self.state.current_track.name.is_empty() => return,
+            None => self.state.current_track.id.clone(),
+        };
+        let Some(uri) = uri else {
+            self.state.set_notification(Notification::info("Local files have no Spotify link"));
+            return;
+        };
+        let (what, text) = if as_uri { ("URI", uri) } else { ("link", clipboard::web_link(&uri)) };
+        match clipboard::copy(&text) {
+            Ok(()) => self.state.set_notification(Notification::info(format!("Copied {what}: {text}"))),
+            Err(e) => self.state.set_notification(Notification::failure(format!("Copy failed: {e}"), &e)),
+        }
+    }
+
+    /// O: opens the highlighted item (or what's playing) in the Spotify app,
+    /// or the web player when `open_in = "web"` or the app can't be launched
+    fn open_in_spotify(&mut self) {
+        let playing = &self.state.current_track;
+        let Some((uri, name)) = self
+            .selected_item()
+            .or_else(|| Some((playing.id.clone()?, playing.name.clone())))
+        else {
+            return;
+        };
+        let link = clipboard::web_link(&uri);
+        let opened = match self.config.open_in {
+            OpenIn::App => open::that(&uri).map(|()| "Spotify").or_else(|_| open::that(&link).map(|()| "the browser")),
+            OpenIn::Web => open::that(&link).map(|()| "the browser"),
+        };
+        match opened {
+            Ok(target) => self.state.set_notification(Notification::info(format!("Opened “{name}” in {target}"))),
+            Err(e) => {
+                let e = anyhow::Error::from(e);
+                self.state.set_notification(Notification::failure(format!("Could not open {link}: {e}"), &e));
+            }
+        }
+    }
+
+    /// Opens the radar popup for the highlighted track (or what's playing),
+    /// adding the playing track alongside for comparison
+    fn open_radar(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.features.enabled(Feature::AudioFeatures) || !self.state.capabilities.audio_features.usable() {
+            self.state.set_notification(Notification::warning("Audio features aren't available"));
+            return;
+        }
+        let playing = &self.state.current_track;
+        let playing = playing.id.clone().filter(|_| !playing.is_episode).map(|id| (id, playing.name.clone()));
+        let selected = self
+            .selected_full_track()
+            .and_then(|t| Some((t.id.as_ref()?.uri(), t.name.clone())));
+        let mut charts: Vec<(String, String)> = selected.into_iter().chain(playing).collect();
+        charts.dedup_by(|a, b| a.0 == b.0);
+        if charts.is_empty() {
+            self.state.set_notification(Notification::info("Nothing to chart"));
+            return;
+        }
+        for (track_id, _) in &charts {
+            // Indexed tracks chart straight away
+            if let Some(f) = self.state.feature_index.get(track_id) {
+                let _ = self.bg_tx.send(BgEvent::RadarFeatures { track_id: track_id.clone(), profile: Some(FeatureProfile::from(f)) });
+                continue;
             }
-            ActiveScreen::Vibes => {
-                if self.state.vibes.recommendations.is_empty() {
-                    // Load recommendations for selected mood
-                    use strum::IntoEnumIterator;
-                    use crate::app::state::VibesMood;
-                    let moods: Vec<VibesMood> = VibesMood::iter().collect();
-                    if let Some(mood) = moods.get(self.state.vibes.selected_mood) {
-                        self.load_vibes(spotify.clone(), mood.clone()).await;
-                    }
-                } else {
-                    // Play selected recommendation
-                    let current_idx = self.state.vibes.selected_track;
-                    let uris: Vec<String> = self.state.vibes.recommendations.iter()
-                        .skip(current_idx)
-                        .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
-                        .take(50)
-                        .collect();
+            let (tx, sp, track_id) = (self.bg_tx.clone(), spotify.clone(), track_id.clone());
+            tokio::spawn(async move {
+                let profile = Analysis::new(sp).get_audio_features(&track_id).await.ok().map(|f| FeatureProfile::from(&f));
+                let _ = tx.send(BgEvent::RadarFeatures { track_id, profile });
+            });
+        }
+        let tracks = charts
+            .into_iter()
+            .map(|(id, name)| RadarTrack { id, name, profile: None, loaded: false })
+            .collect();
+        self.state.radar = Some(RadarView { tracks });
+    }
 
-                    if let Some(track) = self.state.vibes.recommendations.get(current_idx) {
-                        if !uris.is_empty() {
-                            let player = Player::new(spotify.clone());
-                            let uri_refs: Vec<&str> = uris.iter().map(|s| s.as_str()).collect();
-                            match player.play_tracks(uri_refs).await {
-                                Ok(_) => self.state.set_notification(Notification::info(format!("Playing: {}", track.name))),
-                                Err(e) => self.state.set_notification(Notification::error(format!("{e}"))),
-                            }
-                        }
-                    }
-                }
+    /// R: replaces playback with a radio set seeded by the highlighted track,
+    /// or by what's playing when nothing is highlighted
+    async fn start_radio(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let playing = &self.state.current_track;
+        let playing = playing.id.clone().filter(|_| !playing.is_episode).map(|id| (id, playing.name.clone()));
+        let Some((seed, name)) = self.selected_track().or(playing) else {
+            self.state.set_notification(Notification::info("Highlight or play a track to start its radio"));
+            return;
+        };
+        self.state.set_notification(Notification::info(format!("Tuning radio to “{name}”…")));
+        let uris = match Vibes::new(spotify.clone()).track_radio(&seed).await {
+            Ok(uris) => uris,
+            Err(e) => {
+                self.state.set_notification(Notification::failure(format!("Radio failed: {e}"), &e));
+                return;
             }
+        };
+        if let Some(batch) = self.play_checked(spotify, uris).await {
+            let msg = batch.toast(format!("📻 {name} radio — {} tracks", batch.uris.len()));
+            self.state.set_notification(Notification::info(msg));
+            self.state.queue.set_origin(format!("{name} radio"), batch.uris);
+        }
+    }
 
-            _ => {}
+    /// Q: appends every Vibes recommendation to the queue in the background,
+    /// paced so Spotify doesn't rate-limit a 30-track burst
+    fn enqueue_recommendations(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.active_screen != ActiveScreen::Vibes || self.state.vibes.recommendations.is_empty() {
+            self.state.set_notification(Notification::info("Generate some Vibes first, then Q queues them all"));
+            return;
         }
+        if self.state.vibes.enqueueing {
+            return;
+        }
+        let uris: Vec<String> = self.state.vibes.recommendations.iter()
+            .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
+            .collect();
+        self.state.vibes.enqueueing = true;
+        self.state.set_notification(Notification::info(format!("Queueing {} tracks…", uris.len())));
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let (n, result) = Queue::new(spotify).add_all(&uris, ENQUEUE_PACE).await;
+            let total = uris.len();
+            let added = uris.into_iter().take(n).collect();
+            let _ = tx.send(BgEvent::Enqueued { added, total, result });
+        });
     }
 
-    async fn handle_add_to_queue(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
-        let uri = match self.state.active_screen {
-            ActiveScreen::Search => self.state.search.tracks
-                .get(self.state.search.selected_track)
-                .and_then(|t| t.id.as_ref().map(|id| id.uri())),
-            ActiveScreen::Library => self.state.library.liked_songs
-                .get(self.state.library.selected)
-                .and_then(|s| s.track.id.as_ref().map(|id| id.uri())),
-            ActiveScreen::Vibes => self.state.vibes.recommendations
-                .get(self.state.vibes.selected_track)
-                .and_then(|t| t.id.as_ref().map(|id| id.uri())),
-            _ => None,
+    /// Space in visual select: marks or unmarks the highlighted track (or
+    /// playlist, on the playlist list), then moves down
+    fn mark_selected(&mut self) {
+        let playlists = &self.state.playlists;
+        let on_playlist_list = self.state.active_screen == ActiveScreen::Playlists && !playlists.viewing_tracks;
+        let uri = if on_playlist_list {
+            playlists.playlists.get(playlists.selected_playlist).map(|pl| pl.id.uri())
+        } else {
+            self.selected_track().map(|(uri, _)| uri)
         };
-        if let Some(uri) = uri {
+        let Some(uri) = uri else { return };
+        if let Some(visual) = self.state.visual.as_mut() {
+            visual.toggle(&uri);
+        }
+        self.navigate_down(1);
+    }
+
+    /// l with rows marked: likes them in one batch, or unlikes them all when
+    /// every one is liked already
+    async fn like_marked(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let marked = self.state.marked_tracks();
+        let unlike = marked.iter().all(|uri| self.state.liked.contains(uri));
+        let targets: Vec<String> = marked.into_iter().filter(|uri| unlike || !self.state.liked.contains(uri)).collect();
+        let offline = self.state.connection.offline;
+        if offline {
+            for uri in &targets {
+                let write = if unlike { PendingWrite::Unlike(uri.clone()) } else { PendingWrite::Like(uri.clone()) };
+                self.state.connection.queue(write);
+            }
+        } else {
+            let library = Library::new(spotify.clone());
+            let result = if unlike { library.unlike_tracks(&targets).await } else { library.like_tracks(&targets).await };
+            if let Err(e) = result {
+                self.state.set_notification(Notification::failure(format!("Could not update Liked Songs: {e}"), &e));
+                return;
+            }
+        }
+        for uri in &targets {
+            self.state.note_liked(uri, !unlike);
+        }
+        self.state.visual = None;
+        let later = if offline { " once back online" } else { "" };
+        let n = targets.len();
+        if unlike {
+            let library = &mut self.state.library;
+            library.liked_songs.retain(|s| s.track.id.as_ref().is_none_or(|id| !targets.contains(&id.uri())));
+            library.selected = library.selected.min(library.liked_songs.len().saturating_sub(1));
+            self.refresh_library_index();
+            self.state.set_notification(Notification::info(format!("Removed {n} tracks from Liked Songs{later}")));
+        } else {
+            self.state.set_notification(Notification::info(format!("❤ Liked {n} tracks{later}")));
+            if !offline {
+                self.refresh_recent_likes(spotify).await;
+            }
+        }
+    }
+
+    /// a with rows marked: appends them to the queue in the background, paced like Q
+    fn enqueue_marked(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let uris = self.state.marked_tracks();
+        self.state.visual = None;
+        self.state.set_notification(Notification::info(format!("Queueing {} tracks…", uris.len())));
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let (n, result) = Queue::new(spotify).add_all(&uris, ENQUEUE_PACE).await;
+            let total = uris.len();
+            let added = uris.into_iter().take(n).collect();
+            let _ = tx.send(BgEvent::Enqueued { added, total, result });
+        });
+    }
+
+    async fn handle_add_to_queue(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if let Some((uri, _)) = self.selected_track() {
             let queue = Queue::new(spotify.clone());
             match queue.add_to_queue(&uri).await {
-                Ok(_) => self.state.set_notification(Notification::info("Added to queue ✓")),
-                Err(e) => self.state.set_notification(Notification::error(format!("{e}"))),
+                Ok(_) => {
+                    self.state.queue.manual.insert(uri);
+                    self.state.set_notification(Notification::info("Added to queue ✓"));
+                }
+                Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+            }
+        }
+    }
+
+    /// A: Spotify plays what was queued by hand before going on with the album
+    /// or playlist, so an append is as close to "next" as its API allows. A
+    /// restart could only put it behind the hand-queued tracks too, as
+    /// Spotify keeps those through one.
+    async fn handle_play_next(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let Some((uri, name)) = self.selected_track() else { return };
+        let ahead = self.state.queue.manual.iter().filter(|u| **u != uri).count();
+        match Queue::new(spotify.clone()).add_to_queue(&uri).await {
+            Ok(()) => {
+                self.state.queue.manual.insert(uri);
+                let msg = match ahead {
+                    0 => format!("⏭ Playing next: {name}"),
+                    n => format!("⏭ Playing after the {n} you queued: {name}"),
+                };
+                self.state.set_notification(Notification::info(msg));
             }
+            Err(e) => self.state.set_notification(Notification::failure(e.to_string(), &e)),
+        }
+        if self.state.active_screen == ActiveScreen::Queue {
+            self.load_queue(spotify).await;
         }
     }
 
@@ -551,8 +3862,46 @@ impl App {
         }
     }
 
+    /// Puts `query` at the top of the search history, on disk too
+    fn remember_search(&mut self, query: &str) {
+        let search = &mut self.state.search;
+        search.recall = None;
+        searches::push(&mut search.history, query);
+        self.save_search_history();
+    }
+
+    fn save_search_history(&self) {
+        let Some(path) = searches::default_path() else { return };
+        if let Err(e) = searches::save(&path, &self.state.search.history) {
+            warn!("Search history not saved: {e}");
+        }
+    }
+
+    /// Enter in the `gs` popup: searches for the highlighted query again
+    async fn rerun_search(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let search = &mut self.state.search;
+        let Some(query) = search.history.get(search.selected_history).cloned() else { return };
+        search.show_history = false;
+        search.query = query;
+        self.state.navigate_to(ActiveScreen::Search);
+        self.do_search(spotify).await;
+    }
+
+    /// x in the `gs` popup: drops the highlighted query from the history
+    fn forget_search(&mut self) {
+        let search = &mut self.state.search;
+        if search.selected_history >= search.history.len() {
+            return;
+        }
+        search.history.remove(search.selected_history);
+        search.selected_history = search.selected_history.min(search.history.len().saturating_sub(1));
+        search.show_history = !search.history.is_empty();
+        self.save_search_history();
+    }
+
     async fn do_search(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
         let query = self.state.search.query.clone();
+        self.remember_search(&query);
         self.state.search.is_searching = true;
         let searcher = Search::new(spotify.clone());
         match searcher.search_tracks(&query, 50).await {
@@ -560,71 +3909,828 @@ impl App {
                 self.state.search.tracks = tracks;
                 self.state.search.selected_track = 0;
                 self.state.search.is_searching = false;
+                // Playlists are a bonus; a failure here shouldn't hide the tracks
+                match searcher.search_playlists(&query, 20).await {
+                    Ok(playlists) => self.state.search.playlists = playlists,
+                    Err(e) => {
+                        warn!("Playlist search failed: {e}");
+                        self.state.search.playlists.clear();
+                    }
+                }
+                self.state.search.selected_playlist = 0;
+                match searcher.search_albums(&query, 50).await {
+                    Ok(albums) => self.state.search.albums = albums,
+                    Err(e) => {
+                        warn!("Album search failed: {e}");
+                        self.state.search.albums.clear();
+                    }
+                }
+                self.state.search.selected_album = 0;
                 self.state.set_notification(Notification::info(format!(
-                    "Found {} tracks", self.state.search.tracks.len()
+                    "Found {} tracks, {} playlists, {} albums (Tab)",
+                    self.state.search.tracks.len(),
+                    self.state.search.playlists.len(),
+                    self.state.search.albums.len()
                 )));
             }
             Err(e) => {
                 self.state.search.is_searching = false;
-                self.state.set_notification(Notification::error(format!("Search failed: {e}")));
+                self.state.set_notification(Notification::failure(format!("Search failed: {e}"), &e));
             }
         }
     }
 
-    async fn load_library(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
-        if !self.state.library.liked_songs.is_empty() { return; }
+    /// The first page of Liked Songs right away; the rest streams in behind
+    /// it from `page_liked_songs`
+    /// Served from the cache while it's fresh, unless `fresh` asks for
+    /// Spotify's copy
+    async fn load_library(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, fresh: bool) {
+        if !self.state.library.liked_songs.is_empty() || self.state.library.paging_liked || self.state.library.is_loading { return; }
         self.state.library.is_loading = true;
-        let lib = Library::new(spotify.clone());
-        match lib.get_liked_songs(200).await {
-            Ok(songs) => {
-                self.state.library.liked_songs = songs;
-                self.state.library.is_loading = false;
-            }
+        let fetched = fetch_liked_songs(self.cache.clone(), spotify.clone(), self.config.cache_ttl.liked_songs, fresh).await;
+        self.liked_songs_loaded(fetched, spotify);
+    }
+
+    /// `load_library` off the event loop; the songs land as a `BgEvent::LikedSongs`
+    fn spawn_load_library(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.library.liked_songs.is_empty() || self.state.library.paging_liked || self.state.library.is_loading { return; }
+        self.state.library.is_loading = true;
+        let (tx, cache, ttl) = (self.bg_tx.clone(), self.cache.clone(), self.config.cache_ttl.liked_songs);
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::LikedSongs(fetch_liked_songs(cache, spotify, ttl, false).await));
+        });
+    }
+
+    /// Puts the first page of Liked Songs on screen and pages in the rest
+    fn liked_songs_loaded(&mut self, fetched: Result<FetchedList<SavedTrack>>, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.library.is_loading = false;
+        let FetchedList { list: Cached { fetched_at, items: songs }, total, source } = match fetched {
+            Ok(fetched) => fetched,
             Err(e) => {
-                self.state.library.is_loading = false;
                 warn!("Library load error: {e}");
+                self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Failed, e.to_string());
+                return;
+            }
+        };
+        if source == ListSource::OfflineCopy {
+            let msg = self.state.connection.use_offline_copy("Liked Songs", fetched_at);
+            self.state.set_notification(Notification::warning(msg));
+        }
+        for uri in songs.iter().filter_map(|s| s.track.id.as_ref()).map(|id| id.uri()) {
+            self.state.note_liked(&uri, true);
+        }
+        let loaded = songs.len();
+        self.liked_fetched = Some(fetched_at);
+        self.state.library.liked_total = total;
+        self.state.library.add_liked(songs);
+        self.state.sort_liked();
+        self.refresh_library_index();
+        if loaded < total {
+            self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Loading, format!("{loaded} of {total}"));
+            self.page_liked_songs(spotify, loaded, total);
+        } else {
+            self.state.note_startup(StartupLoad::LikedSongs, LoadStatus::Done, format!("{total} songs"));
+            if source == ListSource::Spotify {
+                tokio::spawn(self.cache_liked_songs());
             }
         }
     }
 
-    async fn load_playlists(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
-        if !self.state.playlists.playlists.is_empty() { return; }
-        self.state.playlists.is_loading = true;
-        let lib = Library::new(spotify.clone());
-        match lib.get_user_playlists().await {
-            Ok(pls) => {
-                self.state.playlists.playlists = pls;
-                self.state.playlists.is_loading = false;
+    /// Fetches Liked Songs from `offset` on, a page at a time, in the
+    /// background; each page lands as a `BgEvent::LikedPage`
+    fn page_liked_songs(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, offset: usize, total: usize) {
+        self.state.library.paging_liked = true;
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            let lib = Library::new(spotify);
+            let (mut offset, mut total, mut page) = (offset, total, 0);
+            let result = async {
+                while offset < total {
+                    let (songs, now) = lib.liked_songs_page(offset).await?;
+                    if songs.is_empty() {
+                        break;
+                    }
+                    (offset, total, page) = (offset + songs.len(), now, page + 1);
+                    if tx.send(BgEvent::LikedPage { songs, total, page }).is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            let _ = tx.send(BgEvent::LikedPagesDone(result));
+        });
+    }
+
+    /// After a like: fetches the newest page only and adds what's new, rather
+    /// than paging through the whole collection again
+    async fn refresh_recent_likes(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.library.liked_songs.is_empty() && !self.state.library.paging_liked {
+            return self.load_library(spotify, false).await;
+        }
+        match Library::new(spotify).liked_songs_page(0).await {
+            Ok((songs, total)) => {
+                let library = &mut self.state.library;
+                library.liked_total = total;
+                library.add_liked(songs);
+                self.state.sort_liked();
+                self.refresh_library_index();
+            }
+            Err(e) => warn!("Liked Songs refresh error: {e}"),
+        }
+    }
+
+    async fn load_saved_albums(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.library.saved_albums.is_empty() { return; }
+        match Library::new(spotify).get_saved_albums().await {
+            Ok(albums) => {
+                self.state.library.saved_albums = albums;
+                self.refresh_library_index();
+            }
+            Err(e) => warn!("Saved albums load error: {e}"),
+        }
+    }
+
+    /// `resume_on_launch`: continue the last session unless something's already playing
+    async fn resume_last_session(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let player = Player::new(spotify);
+        if let Ok(Some(ct)) = player.get_current_playback().await {
+            if ct.is_playing {
+                return;
+            }
+        }
+        match player.resume_session().await {
+            Ok(()) => self.state.set_notification(Notification::info("Resumed where you left off")),
+            Err(e) => self.state.set_notification(Notification::failure(format!("Couldn't resume: {e}"), &e)),
+        }
+    }
+
+    /// Spotify's browse categories, loaded the first time Browse is shown;
+    /// the featured playlists go first when the app may fetch them
+    async fn load_categories(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.browse.categories.is_empty() {
+            return;
+        }
+        self.state.browse.is_loading = true;
+        match Library::new(spotify).get_categories().await {
+            Ok(categories) => {
+                let featured = self.state.capabilities.featured.usable().then(BrowseState::featured_category);
+                self.state.browse.categories = featured.into_iter().chain(categories).collect();
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not load categories: {e}"), &e)),
+        }
+        self.state.browse.is_loading = false;
+    }
+
+    /// Enter or → on a category: shows its playlists, loading them unless
+    /// they're the ones already shown
+    async fn open_category(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let browse = &mut self.state.browse;
+        let Some(category) = browse.selected_category().cloned() else { return };
+        browse.viewing_playlists = true;
+        if browse.playlists_of.as_deref() == Some(category.id.as_str()) {
+            return;
+        }
+        browse.is_loading = true;
+        browse.playlists.clear();
+        browse.selected_playlist = 0;
+        browse.playlists_of = Some(category.id.clone());
+        let lib = Library::new(spotify);
+        let result = if category.id == BrowseState::FEATURED {
+            lib.get_featured_playlists().await.map(|(message, playlists)| {
+                self.state.browse.featured_message = Some(message).filter(|m| !m.is_empty());
+                playlists
+            })
+        } else {
+            lib.get_category_playlists(&category.id).await
+        };
+        match result {
+            Ok(playlists) => self.state.browse.playlists = playlists,
+            Err(e) => {
+                self.state.browse.playlists_of = None;
+                self.state.browse.viewing_playlists = false;
+                self.state.set_notification(Notification::failure(format!("Could not load {}: {e}", category.name), &e));
+            }
+        }
+        self.state.browse.is_loading = false;
+    }
+
+    /// Top tracks and artists for the selected range (skipped if already loaded)
+    async fn load_stats(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        self.state.stats.on_this_day = self.on_this_day();
+        let range = self.state.stats.range;
+        if self.state.stats.loaded == Some(range) {
+            return;
+        }
+        self.state.stats.is_loading = true;
+        let stats = Stats::new(spotify);
+        match tokio::try_join!(stats.top_tracks(range), stats.top_artists(range)) {
+            Ok((tracks, artists)) => {
+                self.state.stats.top_tracks = tracks;
+                self.state.stats.top_artists = artists;
+                self.state.stats.selected = 0;
+                self.state.stats.loaded = Some(range);
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Stats load failed: {e}"), &e)),
+        }
+        self.state.stats.is_loading = false;
+    }
+
+    /// Enter on Stats: play the top tracks from the highlighted one, or the
+    /// highlighted artist's own top tracks
+    async fn play_from_stats(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let stats = &self.state.stats;
+        let (uris, name) = match stats.view {
+            StatsView::Tracks => {
+                let Some(track) = stats.top_tracks.get(stats.selected) else { return };
+                let uris: Vec<String> = stats.top_tracks.iter()
+                    .skip(stats.selected)
+                    .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
+                    .collect();
+                (uris, track.name.clone())
+            }
+            StatsView::Artists => {
+                let Some(artist) = stats.top_artists.get(stats.selected).cloned() else { return };
+                match Library::new(spotify.clone()).get_artist_top_tracks(artist.id.id()).await {
+                    Ok(tracks) => (tracks.iter().filter_map(|t| t.id.as_ref().map(|id| id.uri())).collect(), artist.name),
+                    Err(e) => {
+                        self.state.set_notification(Notification::failure(format!("Top tracks for {} failed: {e}", artist.name), &e));
+                        return;
+                    }
+                }
+            }
+        };
+        if let Some(batch) = self.play_checked(spotify, uris).await {
+            let msg = batch.toast(format!("Playing: {}", batch.first.as_deref().unwrap_or(&name)));
+            self.state.set_notification(Notification::info(msg));
+            self.state.queue.set_origin("your top tracks", batch.uris);
+        }
+    }
+
+    /// Plays a hand-built batch (search results, Liked Songs, Vibes, …) after
+    /// dropping what can't play in the user's market, so playback starts on
+    /// the picked track or the next playable one. `None` when nothing played;
+    /// the toast is already up then
+    async fn play_checked(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, uris: Vec<String>) -> Option<PlayableBatch> {
+        if uris.is_empty() {
+            return None;
+        }
+        let player = Player::new(spotify);
+        let batch = match player.playable_batch(&uris).await {
+            Ok(batch) => batch,
+            Err(e) => {
+                // Better to play unchecked than not at all
+                warn!("Availability check failed: {e}");
+                PlayableBatch { uris, first: None, excluded: 0 }
             }
+        };
+        if batch.uris.is_empty() {
+            let msg = match batch.excluded {
+                1 => "That track isn't available in your country".to_string(),
+                n => format!("None of these {n} tracks are available in your country"),
+            };
+            self.state.set_notification(Notification::warning(msg));
+            return None;
+        }
+        match player.play_tracks(batch.uris.iter().map(String::as_str).collect()).await {
+            Ok(()) => Some(batch),
+            Err(e) => {
+                self.state.set_notification(Notification::failure(e.to_string(), &e));
+                None
+            }
+        }
+    }
+
+    async fn load_followed_artists(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.library.followed_artists.is_empty() { return; }
+        self.state.library.is_loading = true;
+        match Library::new(spotify).get_followed_artists().await {
+            Ok(artists) => self.state.library.followed_artists = artists,
+            Err(e) => warn!("Followed artists load error: {e}"),
+        }
+        self.state.library.is_loading = false;
+    }
+
+    async fn load_artist_top_tracks(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let library = &self.state.library;
+        let Some(artist) = library.followed_artists.get(library.selected_artist) else { return };
+        let name = artist.name.clone();
+        match Library::new(spotify).get_artist_top_tracks(artist.id.id()).await {
+            Ok(tracks) => {
+                self.state.library.artist_top_tracks = Some(tracks);
+                self.state.library.selected_artist_track = 0;
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Top tracks for {name} failed: {e}"), &e)),
+        }
+    }
+
+    async fn unfollow_artist(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let library = &self.state.library;
+        if library.artist_top_tracks.is_some() {
+            return; // only from the artist list, not while browsing top tracks
+        }
+        let Some(artist) = library.followed_artists.get(library.selected_artist).cloned() else { return };
+        match Library::new(spotify).unfollow_artist(artist.id.id()).await {
+            Ok(()) => {
+                let library = &mut self.state.library;
+                library.followed_artists.retain(|a| a.id != artist.id);
+                library.selected_artist = library.selected_artist.min(library.followed_artists.len().saturating_sub(1));
+                self.state.set_notification(Notification::info(format!("Unfollowed {}", artist.name)));
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not unfollow {}: {e}", artist.name), &e)),
+        }
+    }
+
+    /// Served from the cache while it's fresh, unless `fresh` asks for
+    /// Spotify's copy
+    async fn load_playlists(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, fresh: bool) {
+        if !self.state.playlists.playlists.is_empty() || self.state.playlists.is_loading { return; }
+        self.state.playlists.is_loading = true;
+        let fetched = fetch_playlists(self.cache.clone(), spotify, self.config.cache_ttl.playlists, fresh).await;
+        self.playlists_loaded(fetched);
+    }
+
+    /// `load_playlists` off the event loop; they land as a `BgEvent::Playlists`
+    fn spawn_load_playlists(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.playlists.playlists.is_empty() || self.state.playlists.is_loading { return; }
+        self.state.playlists.is_loading = true;
+        let (tx, cache, ttl) = (self.bg_tx.clone(), self.cache.clone(), self.config.cache_ttl.playlists);
+        tokio::spawn(async move {
+            let _ = tx.send(BgEvent::Playlists(fetch_playlists(cache, spotify, ttl, false).await));
+        });
+    }
+
+    fn playlists_loaded(&mut self, fetched: Result<(FetchedList<SimplifiedPlaylist>, Vec<String>)>) {
+        self.state.playlists.is_loading = false;
+        let (FetchedList { list: Cached { fetched_at, items: pls }, source, .. }, updated) = match fetched {
+            Ok(fetched) => fetched,
             Err(e) => {
-                self.state.playlists.is_loading = false;
                 warn!("Playlists load error: {e}");
+                self.state.note_startup(StartupLoad::Playlists, LoadStatus::Failed, e.to_string());
+                return;
+            }
+        };
+        if source == ListSource::OfflineCopy {
+            let msg = self.state.connection.use_offline_copy("playlists", fetched_at);
+            self.state.set_notification(Notification::warning(msg));
+        }
+        self.state.note_startup(StartupLoad::Playlists, LoadStatus::Done, format!("{} playlists", pls.len()));
+        self.state.playlists.updated.extend(updated);
+        self.state.playlists.playlists = pls;
+        self.playlists_fetched = Some(fetched_at);
+        if source == ListSource::Spotify {
+            tokio::spawn(self.cache_playlists());
+        }
+    }
+
+    /// Writes the playlists on screen to the cache, for what's left of the
+    /// TTL they were fetched under
+    fn cache_playlists(&self) -> impl Future<Output = ()> + Send + 'static {
+        let (cache, ttl) = (self.cache.clone(), self.config.cache_ttl.playlists);
+        let list = self.playlists_fetched
+            .filter(|_| !self.state.playlists.playlists.is_empty())
+            .map(|fetched_at| Cached { fetched_at, items: self.state.playlists.playlists.clone() });
+        async move {
+            if let Some(list) = list {
+                if let Err(e) = cache.set_playlists(&list, ttl).await {
+                    warn!("Playlists not cached: {e}");
+                }
+            }
+        }
+    }
+
+    /// Liked Songs' counterpart of `cache_playlists`; skipped while pages
+    /// are still coming in
+    fn cache_liked_songs(&self) -> impl Future<Output = ()> + Send + 'static {
+        let (cache, ttl) = (self.cache.clone(), self.config.cache_ttl.liked_songs);
+        let library = &self.state.library;
+        let list = self.liked_fetched
+            .filter(|_| !library.paging_liked && !library.liked_songs.is_empty())
+            .map(|fetched_at| Cached { fetched_at, items: library.liked_songs.clone() });
+        async move {
+            if let Some(list) = list {
+                if let Err(e) = cache.set_liked_songs(&list, ttl).await {
+                    warn!("Liked Songs not cached: {e}");
+                }
+            }
+        }
+    }
+
+    /// The open playlist's tracks, just fetched, into the cache under its snapshot
+    fn cache_playlist_tracks(&self) {
+        let playlists = &self.state.playlists;
+        let Some(pl) = playlists.playlists.get(playlists.selected_playlist) else { return };
+        let (id, snapshot_id) = (pl.id.id().to_string(), pl.snapshot_id.clone());
+        let list = Cached { fetched_at: Utc::now(), items: playlists.playlist_tracks.clone() };
+        let (cache, ttl) = (self.cache.clone(), self.config.cache_ttl.playlist_tracks);
+        tokio::spawn(async move {
+            if let Err(e) = cache.set_playlist_tracks(&id, &snapshot_id, &list, ttl).await {
+                warn!("Playlist tracks not cached: {e}");
             }
+        });
+    }
+
+    /// U: drops the cached playlists and Liked Songs and fetches both again
+    async fn refresh_library(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if self.state.library.paging_liked {
+            self.state.set_notification(Notification::info("Liked Songs are still loading"));
+            return;
+        }
+        if let Err(e) = self.cache.forget_library().await {
+            warn!("Library cache not cleared: {e}");
+        }
+        self.reload_library(spotify).await;
+        self.state.set_notification(Notification::info(format!(
+            "Reloaded {} playlists and {} liked songs from Spotify",
+            self.state.playlists.playlists.len(),
+            self.state.library.liked_total
+        )));
+    }
+
+    /// Fetches playlists and Liked Songs from Spotify again, keeping the
+    /// open playlist highlighted. Without a connection they fall back to
+    /// the offline copy again
+    async fn reload_library(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let open = self.open_playlist_id();
+        let playlists = &mut self.state.playlists;
+        playlists.playlists.clear();
+        playlists.viewing_tracks = false;
+        self.state.library.liked_songs.clear();
+        self.state.library.liked_total = 0;
+        self.load_playlists(spotify.clone(), true).await;
+        self.load_library(spotify, true).await;
+
+        let playlists = &mut self.state.playlists;
+        match open.and_then(|id| playlists.playlists.iter().position(|pl| pl.id.id() == id)) {
+            Some(i) => playlists.selected_playlist = i,
+            None => playlists.selected_playlist = playlists.selected_playlist.min(playlists.playlists.len().saturating_sub(1)),
         }
+        let library = &mut self.state.library;
+        library.selected = library.selected.min(library.liked_songs.len().saturating_sub(1));
     }
 
+    /// Loads the highlighted playlist's tracks: from the cache when this
+    /// snapshot is in it, else from Spotify. A prefetched first page is
+    /// shown right away; the rest of a longer playlist follows in the
+    /// background as a `BgEvent::PlaylistTracks`.
     async fn load_playlist_tracks(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, playlist_id: String) {
+        let playlists = &mut self.state.playlists;
+        playlists.playlist_tracks.clear();
+        playlists.paging_tracks = false;
+        let open = playlists.playlists.get(playlists.selected_playlist).map(|pl| (pl.id.id().to_string(), pl.snapshot_id.clone()));
+        let page = open.as_ref().and_then(|(id, snapshot_id)| playlists.prefetched.get(id, snapshot_id)).cloned();
+        if page.as_ref().is_none_or(|p| !p.is_complete()) {
+            if let Some((id, snapshot_id)) = &open {
+                if let Some(list) = self.cache.get_playlist_tracks(id, snapshot_id, self.config.cache_ttl.playlist_tracks).await {
+                    self.set_playlist_tracks(list.items);
+                    return self.playlist_tracks_loaded();
+                }
+            }
+        }
+        if let Some(page) = page {
+            let complete = page.is_complete();
+            self.set_playlist_tracks(page.items.clone());
+            if complete {
+                self.cache_playlist_tracks();
+                return self.playlist_tracks_loaded();
+            }
+            self.state.playlists.paging_tracks = true;
+            let tx = self.bg_tx.clone();
+            tokio::spawn(async move {
+                let lib = Library::new(spotify);
+                let result = async {
+                    let mut items = page.items;
+                    let mut total = page.total;
+                    while items.len() < total {
+                        let (more, now) = lib.playlist_page(&page.playlist_id, items.len()).await?;
+                        if more.is_empty() {
+                            break;
+                        }
+                        items.extend(more);
+                        total = now;
+                    }
+                    Ok(items)
+                }
+                .await;
+                let _ = tx.send(BgEvent::PlaylistTracks { playlist_id: page.playlist_id, result });
+            });
+            return;
+        }
+
         self.state.playlists.is_loading = true;
-        self.state.playlists.playlist_tracks.clear();
         let lib = Library::new(spotify.clone());
         match lib.get_playlist_tracks(&playlist_id).await {
             Ok(tracks) => {
-                self.state.playlists.playlist_tracks = tracks;
-                self.state.playlists.is_loading = false;
+                self.set_playlist_tracks(tracks);
+                self.cache_playlist_tracks();
+                self.playlist_tracks_loaded();
             }
             Err(e) => {
-                self.state.playlists.is_loading = false;
                 warn!("Playlist tracks load error: {e}");
+                let copy = match open {
+                    Some((id, snapshot_id)) => self.cache.offline_playlist_tracks(&id, &snapshot_id).await,
+                    None => None,
+                };
+                match copy {
+                    Some(list) => {
+                        let msg = self.state.connection.use_offline_copy("this playlist", list.fetched_at);
+                        self.state.set_notification(Notification::warning(msg));
+                        self.set_playlist_tracks(list.items);
+                        self.playlist_tracks_loaded();
+                    }
+                    None => self.state.playlists.is_loading = false,
+                }
+            }
+        }
+    }
+
+    /// Puts `tracks`, in the playlist's own order, on screen
+    fn set_playlist_tracks(&mut self, tracks: Vec<PlaylistItem>) {
+        let playlists = &mut self.state.playlists;
+        playlists.positions.clear();
+        for (i, item) in tracks.iter().enumerate() {
+            playlists.positions.entry(playlist_entry_key(item)).or_insert(i);
+        }
+        playlists.playlist_tracks = tracks;
+    }
+
+    /// The open playlist's tracks are all in: diff and sort them
+    fn playlist_tracks_loaded(&mut self) {
+        if let Some(id) = self.open_playlist_id() {
+            self.state.playlists.prefetched.loaded(&id);
+        }
+        self.state.playlists.is_loading = false;
+        self.state.playlists.paging_tracks = false;
+        self.record_playlist_snapshot();
+        self.state.sort_playlist_tracks();
+    }
+
+    /// Prefetches the first page of the highlighted playlist, so Enter
+    /// doesn't have to wait for it
+    fn prefetch_playlist(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        let playlists = &mut self.state.playlists;
+        if self.state.connection.offline || self.state.active_screen != ActiveScreen::Playlists || playlists.viewing_tracks {
+            return;
+        }
+        let Some(pl) = playlists.playlists.get(playlists.selected_playlist) else { return };
+        let (playlist_id, snapshot_id) = (pl.id.id().to_string(), pl.snapshot_id.clone());
+        if !playlists.prefetched.wants(&playlist_id, &snapshot_id) {
+            return;
+        }
+        playlists.prefetched.in_flight = Some(playlist_id.clone());
+        let (tx, cache, ttl) = (self.bg_tx.clone(), self.cache.clone(), self.config.cache_ttl.playlist_tracks);
+        tokio::spawn(async move {
+            // A cached snapshot is the whole playlist, so it makes a complete page
+            let result = match cache.get_playlist_tracks(&playlist_id, &snapshot_id, ttl).await {
+                Some(list) => Ok((list.items.clone(), list.items.len())),
+                None => Library::new(spotify).playlist_page(&playlist_id, 0).await,
+            };
+            let _ = tx.send(BgEvent::PlaylistPrefetched { playlist_id, snapshot_id, result });
+        });
+    }
+
+    /// Compares the opened playlist with the snapshot from the last visit in
+    /// the background, then remembers the current contents; changes come
+    /// back as a `BgEvent::PlaylistDiffed`
+    fn record_playlist_snapshot(&mut self) {
+        let Some((id, name, current)) = self.open_playlist_snapshot() else { return };
+
+        self.state.playlists.updated.remove(&id);
+        self.state.playlists.diff = None;
+        self.state.playlists.show_diff = false;
+        let cache = self.cache.clone();
+        let tx = self.bg_tx.clone();
+        tokio::spawn(async move {
+            if let Some(seen) = cache.get_playlist_snapshot(&id).await {
+                if seen.snapshot_id != current.snapshot_id {
+                    let diff = PlaylistDiff::between(&seen, &current);
+                    if !diff.is_empty() {
+                        let _ = tx.send(BgEvent::PlaylistDiffed { playlist_id: id.clone(), name, diff });
+                    }
+                }
+            }
+            if let Err(e) = cache.set_playlist_snapshot(&id, &current).await {
+                warn!("Playlist snapshot not saved: {e}");
+            }
+        });
+    }
+
+    /// Id of the playlist whose tracks are on screen
+    fn open_playlist_id(&self) -> Option<String> {
+        if !self.state.playlists.viewing_tracks {
+            return None;
+        }
+        let pl = self.state.playlists.playlists.get(self.state.playlists.selected_playlist)?;
+        Some(pl.id.id().to_string())
+    }
+
+    /// (id, name, snapshot) of the highlighted playlist's loaded tracks
+    fn open_playlist_snapshot(&self) -> Option<(String, String, PlaylistSnapshot)> {
+        let pl = self.state.playlists.playlists.get(self.state.playlists.selected_playlist)?;
+        let snapshot = PlaylistSnapshot::from_items(&pl.snapshot_id, &self.state.playlists.playlist_tracks);
+        Some((pl.id.id().to_string(), pl.name.clone(), snapshot))
+    }
+
+    /// S: the next column / direction for Liked Songs or the open playlist
+    fn cycle_sort(&mut self) {
+        let order = match self.state.active_screen {
+            ActiveScreen::Library if self.state.library.tab == LibraryTab::Liked => {
+                self.state.library.sort = self.state.library.sort.next();
+                self.state.sort_liked();
+                self.state.library.sort
+            }
+            ActiveScreen::Playlists if self.state.playlists.viewing_tracks => {
+                self.state.playlists.sort = self.state.playlists.sort.next();
+                self.state.sort_playlist_tracks();
+                self.state.playlists.sort
+            }
+            ActiveScreen::Search if self.state.search.tab == SearchTab::Albums => {
+                self.state.search.reorder_albums(|search| search.album_sort = search.album_sort.next());
+                let label = self.state.search.album_sort.label();
+                let msg = label.map_or_else(|| "Best match first".to_string(), |label| format!("Sorted by {label}"));
+                self.state.set_notification(Notification::info(msg));
+                return;
+            }
+            _ => {
+                self.state.set_notification(Notification::info("S sorts Liked Songs, open playlists and album results"));
+                return;
+            }
+        };
+        let msg = order.label().map_or_else(|| "Spotify's order".to_string(), |label| format!("Sorted by {label}"));
+        self.state.set_notification(Notification::info(msg));
+    }
+
+    // ── Profiles ──────────────────────────────────────────────────────────────
+
+    /// `:profile <name>` ends this session and logs in as `name`; on its own,
+    /// lists the profiles
+    fn switch_to_profile(&mut self, name: &str) {
+        let current = self.config.profile.as_deref().unwrap_or("default");
+        let names: Vec<&str> = std::iter::once("default").chain(self.config.profiles.iter().map(String::as_str)).collect();
+        if name.is_empty() {
+            let others: Vec<&str> = names.iter().copied().filter(|&n| n != current).collect();
+            let msg = match others.as_slice() {
+                [] => format!("Profile: {current} — add others to `profiles` in config.toml"),
+                _ => format!("Profile: {current} · others: {} — :profile <name> to switch", others.join(", ")),
+            };
+            return self.state.set_notification(Notification::info(msg));
+        }
+        if name == current {
+            return self.state.set_notification(Notification::info(format!("Already on {current}")));
+        }
+        if !names.contains(&name) {
+            let msg = format!("No profile {name} — there's {}", names.join(", "));
+            return self.state.set_notification(Notification::warning(msg));
+        }
+        self.switch_profile = Some(name.to_string());
+        self.state.should_quit = true;
+    }
+
+    // ── Trace ─────────────────────────────────────────────────────────────────
+
+    /// `:trace on` / `:trace off`; on its own, says whether it's on
+    fn set_tracing(&mut self, arg: &str) {
+        let msg = match (arg, &self.tracer) {
+            ("on", Some(tracer)) => format!("Already tracing to {}", tracer.path().display()),
+            ("on", None) => match self.start_tracing() {
+                Ok(path) => format!("Tracing playback to {}", path.display()),
+                Err(e) => return self.state.set_notification(Notification::failure(format!("Trace not started: {e}"), &e)),
+            },
+            ("off", Some(_)) => {
+                let path = self.tracer.take().map(|t| t.path().to_path_buf()).unwrap_or_default();
+                self.state.trace = None;
+                format!("Trace off — kept in {}", path.display())
+            }
+            ("off", None) => "Trace is already off".to_string(),
+            ("", Some(tracer)) => format!("Tracing to {} — :trace off to stop", tracer.path().display()),
+            ("", None) => "Trace is off — :trace on to start".to_string(),
+            _ => return self.state.set_notification(Notification::warning("Use :trace on or :trace off")),
+        };
+        self.state.set_notification(Notification::info(msg));
+    }
+
+    fn start_tracing(&mut self) -> Result<std::path::PathBuf> {
+        let Some(path) = trace::default_path() else { anyhow::bail!("no data directory for the trace file") };
+        let tracer = Tracer::open(&path)?;
+        self.state.trace = Some(path.clone());
+        self.tracer = Some(tracer);
+        Ok(path)
+    }
+
+    /// Writes `event` to the trace while it's on; a failed write stops it
+    fn trace(&mut self, event: TraceEvent) {
+        let Some(tracer) = self.tracer.as_mut() else { return };
+        if let Err(e) = tracer.record(&event) {
+            self.tracer = None;
+            self.state.trace = None;
+            self.state.set_notification(Notification::failure(format!("Trace stopped: {e}"), &e));
+        }
+    }
+
+    /// A poll result for the trace, with how far the progress bar had drifted
+    /// from it; traced before it's applied, so there's something to compare
+    fn trace_poll(&mut self, poll: &PlaybackPoll) {
+        if self.tracer.is_none() {
+            return;
+        }
+        let local = &self.state.current_track;
+        let event = match poll {
+            PlaybackPoll::Playing { track, took_ms } => TraceEvent::Poll {
+                track: track.id.clone(),
+                progress_ms: track.progress_ms,
+                duration_ms: track.duration_ms,
+                is_playing: track.is_playing,
+                took_ms: *took_ms,
+                drift_ms: (track.id == local.id).then(|| track.progress_ms as i64 - local.progress_ms as i64),
+            },
+            PlaybackPoll::Missing { devices, took_ms } => {
+                TraceEvent::PollMissing { took_ms: *took_ms, devices: devices.as_ref().map(Vec::len) }
             }
+            PlaybackPoll::Unauthorized { took_ms } => TraceEvent::PollMissing { took_ms: *took_ms, devices: None },
+        };
+        self.trace(event);
+    }
+
+    /// `:decade 90s` keeps album results from that decade; `:decade` alone
+    /// lists them all again
+    fn filter_decade(&mut self, arg: &str) {
+        let decade = match arg {
+            "" | "all" => None,
+            arg => match parse_decade(arg) {
+                Some(decade) => Some(decade),
+                None => {
+                    self.state.set_notification(Notification::warning(format!("Not a decade: {arg} (try 90s or 1990s)")));
+                    return;
+                }
+            },
+        };
+        let search = &mut self.state.search;
+        search.reorder_albums(|search| search.decade = decade);
+        search.tab = SearchTab::Albums;
+        let shown = search.album_order().len();
+        self.state.navigate_to(ActiveScreen::Search);
+        let msg = match decade {
+            Some(d) => format!("{shown} albums from the {d}s"),
+            None => format!("All {shown} albums"),
+        };
+        self.state.set_notification(Notification::info(msg));
+    }
+
+    fn clamp_queue_selection(&mut self) {
+        let rows = self.state.queue.rows().len();
+        self.state.queue.selected = self.state.queue.selected.min(rows.saturating_sub(1));
+    }
+
+    /// x on the Queue: drops the selected track, or its whole group on a
+    /// header. Spotify can't remove from the queue, so what's left is
+    /// restarted after the current track at the same position; tracks queued
+    /// by hand stay put through that, so they can't be dropped.
+    async fn clear_from_queue(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        use rspotify::prelude::Id;
+        let queue = &self.state.queue;
+        let (drop, what) = match queue.selected_row() {
+            Some(QueueRow::Group(g)) => {
+                let Some((source, range)) = queue.groups().into_iter().nth(g) else { return };
+                (range, source.label())
+            }
+            Some(QueueRow::Track(i)) => (i..i + 1, format!("“{}”", queue.tracks[i].name)),
+            None => return,
+        };
+        let Some(current) = self.state.current_track.id.clone() else {
+            self.state.set_notification(Notification::info("Nothing is playing"));
+            return;
+        };
+        let drop: HashSet<String> = queue.tracks[drop].iter().filter_map(|t| t.id.as_ref().map(|id| id.uri())).collect();
+        if drop.iter().any(|uri| queue.manual.contains(uri)) {
+            self.state.set_notification(Notification::info("Spotify can't take tracks queued by hand off the queue"));
+            return;
+        }
+        let track = &self.state.current_track;
+        // Shuffled, the album or playlist order isn't what plays next
+        let context = track.context_uri.as_deref().filter(|_| !track.shuffle);
+        let result = Queue::new(spotify.clone())
+            .replace_upcoming(&current, context, &drop, &queue.manual, track.progress_ms)
+            .await;
+        match result {
+            Ok(()) => {
+                self.state.set_notification(Notification::info(format!("Cleared {what} from the queue")));
+                self.load_queue(spotify).await;
+            }
+            Err(e) => self.state.set_notification(Notification::failure(format!("Could not clear the queue: {e}"), &e)),
         }
     }
 
     async fn load_queue(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>) {
+        if !self.state.capabilities.queue.usable() {
+            self.state.set_notification(Notification::info("Spotify doesn't let this app read the queue"));
+            return;
+        }
         self.state.queue.is_loading = true;
         let q = Queue::new(spotify.clone());
         match q.get_queue().await {
             Ok(tracks) => {
                 self.state.queue.tracks = tracks;
+                self.clamp_queue_selection();
                 self.state.queue.is_loading = false;
             }
             Err(e) => {
@@ -634,7 +4740,7 @@ impl App {
         }
     }
 
-    async fn load_vibes(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, mood: crate::app::state::VibesMood) {
+    async fn load_vibes(&mut self, spotify: Arc<Mutex<rspotify::AuthCodePkceSpotify>>, mood: VibesMood) {
         self.state.vibes.is_loading = true;
         self.state.vibes.recommendations.clear();
         self.state.vibes.selected_track = 0;
@@ -647,7 +4753,7 @@ impl App {
             }
             Err(e) => {
                 self.state.vibes.is_loading = false;
-                self.state.set_notification(Notification::error(format!("Vibes error: {e}")));
+                self.state.set_notification(Notification::failure(format!("Vibes error: {e}"), &e));
             }
         }
     }
@@ -1,152 +1,1562 @@
 use rspotify::{
-    model::{FullTrack, SimplifiedPlaylist, SavedTrack, PlaylistItem},
+    model::{AudioFeatures, Category, Device, FullArtist, FullTrack, PlayHistory, RepeatState, SimplifiedAlbum, SimplifiedPlaylist, SavedAlbum, SavedTrack, PlaylistItem},
+};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ops::Range,
+    sync::Arc,
+    time::{Duration, Instant},
 };
-use std::sync::Arc;
 use tokio::sync::Mutex;
 use rspotify::AuthCodePkceSpotify;
+use chrono::{DateTime, Local, Utc};
+use serde::Serialize;
+
+use crate::artwork::{blend, Cover, Rgb};
+use crate::cache::snapshots::PlaylistDiff;
+use crate::collate::{collator, fuzzy_match};
+use crate::config::{NotificationSettings, SidebarPanel, ToastTicks, VibesMood, Visualizer};
+use crate::logs::{self, LogLine};
+use crate::lyrics::LyricLine;
+use crate::events::UserAction;
+use crate::features::Features;
+use crate::index::{features::{FeatureIndex, TrackFeatures}, filter::MoodFilter, AlbumOverlap};
+use crate::jobs::Jobs;
+use crate::remote::Pairing;
+use crate::session::{archive::PlaylistArchive, inbox::InboxItem, plays::{same_listen_slack, DayRecap}};
+use crate::spotify::capabilities::Capabilities;
+use crate::ui::{anim::Animations, text::format_duration, theme::{ColorDepth, Theme}};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActiveScreen {
+    Search,
+    Library,
+    Playlists,
+    Queue,
+    Vibes,
+    Stats,
+    Browse,
+}
+
+impl Default for ActiveScreen {
+    fn default() -> Self {
+        ActiveScreen::Search
+    }
+}
+
+impl ActiveScreen {
+    /// Sidebar order out of the box
+    pub const ALL: [ActiveScreen; 7] = [
+        ActiveScreen::Search,
+        ActiveScreen::Library,
+        ActiveScreen::Playlists,
+        ActiveScreen::Queue,
+        ActiveScreen::Vibes,
+        ActiveScreen::Stats,
+        ActiveScreen::Browse,
+    ];
+
+    /// Name used in the `screens` config list
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActiveScreen::Search => "search",
+            ActiveScreen::Library => "library",
+            ActiveScreen::Playlists => "playlists",
+            ActiveScreen::Queue => "queue",
+            ActiveScreen::Vibes => "vibes",
+            ActiveScreen::Stats => "stats",
+            ActiveScreen::Browse => "browse",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ActiveScreen> {
+        ActiveScreen::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActiveScreen::Search => "Search",
+            ActiveScreen::Library => "Liked Songs",
+            ActiveScreen::Playlists => "Playlists",
+            ActiveScreen::Queue => "Queue",
+            ActiveScreen::Vibes => "Vibes",
+            ActiveScreen::Stats => "Stats",
+            ActiveScreen::Browse => "Browse",
+        }
+    }
+}
+
+/// Sidebar order from the `screens` config list; screens left out are hidden.
+/// Unknown and repeated names are skipped, and an empty list means all of them.
+pub fn screen_order(names: &[String]) -> Vec<ActiveScreen> {
+    let mut order: Vec<ActiveScreen> = Vec::new();
+    for name in names {
+        match ActiveScreen::from_name(name.trim()) {
+            Some(screen) if !order.contains(&screen) => order.push(screen),
+            Some(_) => {}
+            None => tracing::warn!("Unknown screen in `screens`: {name}"),
+        }
+    }
+    if order.is_empty() {
+        order = ActiveScreen::ALL.to_vec();
+    }
+    order
+}
+
+#[derive(Debug, Clone, Default)]
+#[allow(dead_code)]
+pub struct CurrentTrack {
+    pub id: Option<String>,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    /// Release year of the album, when Spotify reports one
+    pub album_year: Option<String>,
+    pub duration_ms: u32,
+    pub progress_ms: u32,
+    pub is_playing: bool,
+    pub is_liked: bool,
+    pub album_art_url: Option<String>,
+    /// Smallest cover variant (64px), used for palette extraction
+    pub album_art_thumb_url: Option<String>,
+    pub device_volume: Option<u8>,
+    /// The device playing it, to notice when that device drops off
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+    /// Playlist, album or artist URI it's playing from; `None` for a plain list of tracks
+    pub context_uri: Option<String>,
+    /// A podcast episode rather than a song (artists holds the publisher)
+    pub is_episode: bool,
+    pub shuffle: bool,
+    /// `None` when repeat is off
+    pub repeat: Option<RepeatState>,
+}
+
+/// Consecutive polls without playback (and no device list to check against)
+/// after which the playing device is taken to be gone
+pub const DEVICE_LOST_MISSES: u8 = 3;
+
+/// Where playback stopped when its device vanished; the next play resumes there
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumePoint {
+    pub uri: String,
+    /// The album or playlist it was playing from, to carry on through
+    pub context_uri: Option<String>,
+    pub position_ms: u32,
+    pub device_name: String,
+}
+
+/// Tracks what the last good poll saw, to tell a vanished device from a blip
+#[derive(Debug, Clone, Default)]
+pub struct DeviceWatch {
+    pub misses: u8,
+    /// Position at the last poll that returned playback
+    pub last_progress_ms: u32,
+}
+
+/// Failed polls in a row before the app counts as offline
+pub const OFFLINE_AFTER_FAILURES: u8 = 3;
+
+/// A write made while offline, sent once Spotify is reachable again
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingWrite {
+    Like(String),
+    Unlike(String),
+}
+
+impl PendingWrite {
+    pub fn uri(&self) -> &str {
+        match self {
+            PendingWrite::Like(uri) | PendingWrite::Unlike(uri) => uri,
+        }
+    }
+}
+
+/// Whether the Spotify API is reachable, judged from the playback poll
+#[derive(Debug, Clone, Default)]
+pub struct Connection {
+    failures: u8,
+    pub offline: bool,
+    /// Writes waiting for the connection to come back, oldest first
+    pub outbox: Vec<PendingWrite>,
+    /// Something on screen came from the cache's offline copy, to be
+    /// reloaded from Spotify once it answers
+    pub offline_copy: bool,
+}
+
+impl Connection {
+    /// A call got through; true when that ends an outage
+    pub fn note_ok(&mut self) -> bool {
+        self.failures = 0;
+        std::mem::take(&mut self.offline)
+    }
+
+    /// A call failed; true when that starts an outage
+    pub fn note_failure(&mut self) -> bool {
+        self.failures = self.failures.saturating_add(1);
+        let went_offline = !self.offline && self.failures >= OFFLINE_AFTER_FAILURES;
+        self.offline |= went_offline;
+        went_offline
+    }
+
+    /// `what` couldn't be loaded and is shown as Spotify last sent it, at
+    /// `fetched_at`; counts as a failed call. The toast to show
+    pub fn use_offline_copy(&mut self, what: &str, fetched_at: DateTime<Utc>) -> String {
+        self.offline_copy = true;
+        self.note_failure();
+        let saved = fetched_at.with_timezone(&Local).format("%b %-d %H:%M");
+        format!("Spotify can't be reached — showing {what} as of {saved}")
+    }
+
+    /// Queues a write; one for a track that already has a pending write
+    /// undoes it, so both are dropped
+    pub fn queue(&mut self, write: PendingWrite) {
+        let before = self.outbox.len();
+        self.outbox.retain(|w| w.uri() != write.uri());
+        if self.outbox.len() == before {
+            self.outbox.push(write);
+        }
+    }
+}
+
+/// How long the startup panel stays up once everything has loaded
+pub const STARTUP_LINGER_MS: u64 = 2000;
+
+/// What's fetched in the background right after logging in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartupLoad {
+    Profile,
+    Playlists,
+    LikedSongs,
+    RecentlyPlayed,
+}
+
+impl StartupLoad {
+    pub const ALL: [StartupLoad; 4] = [StartupLoad::Profile, StartupLoad::Playlists, StartupLoad::LikedSongs, StartupLoad::RecentlyPlayed];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StartupLoad::Profile => "Profile",
+            StartupLoad::Playlists => "Playlists",
+            StartupLoad::LikedSongs => "Liked Songs",
+            StartupLoad::RecentlyPlayed => "Recently played",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadStatus {
+    Loading,
+    Done,
+    Failed,
+}
+
+/// The startup panel: each load, how it's going and a word on it
+/// (`120 of 1,204`, `38 playlists`, the error)
+#[derive(Debug, Clone)]
+pub struct Startup {
+    pub loads: Vec<(StartupLoad, LoadStatus, String)>,
+    /// Time since the last load finished
+    done_ms: u64,
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Startup { loads: StartupLoad::ALL.iter().map(|&l| (l, LoadStatus::Loading, String::new())).collect(), done_ms: 0 }
+    }
+}
+
+impl Startup {
+    pub fn set(&mut self, load: StartupLoad, status: LoadStatus, detail: impl Into<String>) {
+        if let Some(entry) = self.loads.iter_mut().find(|(l, ..)| *l == load) {
+            (entry.1, entry.2) = (status, detail.into());
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.loads.iter().all(|(_, status, _)| *status != LoadStatus::Loading)
+    }
+}
+
+/// Pause after a lone digit before it counts as a screen switch instead of
+/// the start of a row number
+pub const ROW_JUMP_TIMEOUT_MS: u64 = 500;
+
+/// Row number being typed, e.g. the `23` of `23<Enter>`
+#[derive(Debug, Clone, Default)]
+pub struct RowJump {
+    pub digits: String,
+    idle_ms: u64,
+}
+
+impl RowJump {
+    /// 1-based row, as numbered in the tables
+    pub fn row(&self) -> Option<usize> {
+        self.digits.parse().ok().filter(|&n| n > 0)
+    }
+}
+
+/// What's known of Liked Songs membership, by track URI, for the row hearts
+#[derive(Debug, Clone, Default)]
+pub struct LikedTracks {
+    known: HashMap<String, bool>,
+    /// Asked about but not answered yet; a failed check stays here so it isn't retried every frame
+    pending: HashSet<String>,
+}
+
+impl LikedTracks {
+    pub fn get(&self, uri: &str) -> Option<bool> {
+        self.known.get(uri).copied()
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.get(uri).unwrap_or(false)
+    }
+
+    pub fn set(&mut self, uri: &str, liked: bool) {
+        self.pending.remove(uri);
+        self.known.insert(uri.to_string(), liked);
+    }
+
+    /// Lets checks that never got an answer be asked again
+    pub fn forget_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// The track URIs among `uris` not known or asked about yet, now marked as asked
+    pub fn claim_unknown(&mut self, uris: impl IntoIterator<Item = String>) -> Vec<String> {
+        let mut out = Vec::new();
+        for uri in uris {
+            if uri.starts_with("spotify:track:") && !self.known.contains_key(&uri) && self.pending.insert(uri.clone()) {
+                out.push(uri);
+            }
+        }
+        out
+    }
+}
+
+/// `V` mode: Space marks rows, on any list screen, for `l`, `a` and `P` to
+/// act on together (or `X` on the playlist list)
+#[derive(Debug, Clone, Default)]
+pub struct VisualSelect {
+    /// Track and playlist URIs in the order they were marked
+    pub marked: Vec<String>,
+}
+
+impl VisualSelect {
+    /// Marks or unmarks `uri`; true when it's now marked
+    pub fn toggle(&mut self, uri: &str) -> bool {
+        match self.marked.iter().position(|m| m == uri) {
+            Some(i) => {
+                self.marked.remove(i);
+                false
+            }
+            None => {
+                self.marked.push(uri.to_string());
+                true
+            }
+        }
+    }
+
+    pub fn contains(&self, uri: &str) -> bool {
+        self.marked.iter().any(|m| m == uri)
+    }
+}
+
+/// Tracks kept in the in-memory listening history
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A track that finished (or was skipped) during this session
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    pub id: String,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub duration_ms: u32,
+    /// When listening began: `played_at` minus the time actually listened
+    pub started_at: DateTime<Utc>,
+    pub played_at: DateTime<Utc>,
+    /// Played through to the end rather than skipped
+    pub completed: bool,
+    /// Listened long enough to count as a scrobble (see `counts_as_scrobble`)
+    pub scrobble: bool,
+}
+
+impl From<&PlayHistory> for HistoryEntry {
+    /// Spotify stamps a recently-played track when it ended, so it started
+    /// about a track length earlier
+    fn from(item: &PlayHistory) -> Self {
+        use rspotify::prelude::Id;
+        let track = &item.track;
+        let duration_ms = track.duration.num_milliseconds() as u32;
+        HistoryEntry {
+            id: track.id.as_ref().map(|id| id.uri()).unwrap_or_default(),
+            name: track.name.clone(),
+            artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+            album: track.album.name.clone(),
+            duration_ms,
+            started_at: item.played_at - chrono::Duration::milliseconds(duration_ms as i64),
+            played_at: item.played_at,
+            completed: true,
+            scrobble: counts_as_scrobble(duration_ms, duration_ms),
+        }
+    }
+}
+
+/// Last.fm's rule: tracks over 30s count once played for half their length
+/// or 4 minutes, whichever comes first. `listened_ms` excludes seeks.
+pub fn counts_as_scrobble(duration_ms: u32, listened_ms: u32) -> bool {
+    duration_ms > 30_000 && (listened_ms >= duration_ms / 2 || listened_ms >= 240_000)
+}
+
+impl CurrentTrack {
+    pub fn progress_percent(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        (self.progress_ms as f64 / self.duration_ms as f64).clamp(0.0, 1.0)
+    }
+
+    pub fn progress_formatted(&self) -> String {
+        format!("{} / {}", format_duration(self.progress_ms), format_duration(self.duration_ms))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchState {
+    pub query: String,
+    pub tracks: Vec<FullTrack>,
+    pub selected_track: usize,
+    pub playlists: Vec<SimplifiedPlaylist>,
+    pub selected_playlist: usize,
+    /// In Spotify's order; `album_order` is what's listed
+    pub albums: Vec<SimplifiedAlbum>,
+    /// Position in `album_order`, not in `albums`
+    pub selected_album: usize,
+    pub album_sort: AlbumSort,
+    /// `:decade 90s` keeps albums released 1990–1999; holds the first year
+    pub decade: Option<i32>,
+    /// Which results are listed (Tab)
+    pub tab: SearchTab,
+    pub is_searching: bool,
+    /// Past queries, newest first
+    pub history: Vec<String>,
+    /// Which past query ↑/↓ have put in the input, if any
+    pub recall: Option<usize>,
+    /// What was typed before ↑ started recalling, restored by ↓ past the newest
+    pub draft: String,
+    /// `gs` popup listing `history`
+    pub show_history: bool,
+    pub selected_history: usize,
+}
+
+impl SearchState {
+    /// ↑ (`older`) or ↓ in the search input: steps through past queries
+    pub fn recall(&mut self, older: bool) {
+        let Some(last) = self.history.len().checked_sub(1) else { return };
+        let pos = match (self.recall, older) {
+            (None, true) => {
+                self.draft = self.query.clone();
+                Some(0)
+            }
+            (None, false) => return,
+            (Some(i), true) => Some((i + 1).min(last)),
+            (Some(i), false) => i.checked_sub(1),
+        };
+        self.recall = pos;
+        self.query = pos.map_or_else(|| self.draft.clone(), |i| self.history[i].clone());
+    }
+
+    /// Indexes into `albums` that pass the decade filter, in `album_sort`
+    /// order; albums without a release date go last either way
+    pub fn album_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.albums.len())
+            .filter(|&i| self.decade.is_none_or(|d| release_year(&self.albums[i]).map(decade_of) == Some(d)))
+            .collect();
+        let date = |i: &usize| self.albums[*i].release_date.clone();
+        match self.album_sort {
+            AlbumSort::Relevance => {}
+            AlbumSort::Oldest => order.sort_by_key(|i| (date(i).is_none(), date(i))),
+            AlbumSort::Newest => order.sort_by_key(|i| (date(i).is_none(), std::cmp::Reverse(date(i)))),
+        }
+        order
+    }
+
+    /// `album_order` as listed: while sorted by date, each decade starts
+    /// with a header row
+    pub fn album_rows(&self) -> Vec<AlbumRow> {
+        let mut rows = Vec::new();
+        let mut last = None;
+        for i in self.album_order() {
+            let decade = release_year(&self.albums[i]).map(decade_of);
+            if self.album_sort != AlbumSort::Relevance && last != Some(decade) {
+                rows.push(AlbumRow::Decade(decade));
+                last = Some(decade);
+            }
+            rows.push(AlbumRow::Album(i));
+        }
+        rows
+    }
+
+    pub fn selected_album(&self) -> Option<&SimplifiedAlbum> {
+        self.albums.get(*self.album_order().get(self.selected_album)?)
+    }
+
+    /// Re-sorts or re-filters the albums, staying on the highlighted one
+    /// while it's still listed
+    pub fn reorder_albums(&mut self, change: impl FnOnce(&mut SearchState)) {
+        let album = self.album_order().get(self.selected_album).copied();
+        change(self);
+        self.selected_album = album.and_then(|a| self.album_order().iter().position(|&i| i == a)).unwrap_or(0);
+    }
+}
+
+/// What the Search screen lists, in Tab order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchTab {
+    #[default]
+    Tracks,
+    Playlists,
+    Albums,
+}
+
+impl SearchTab {
+    pub fn next(self) -> SearchTab {
+        match self {
+            SearchTab::Tracks => SearchTab::Playlists,
+            SearchTab::Playlists => SearchTab::Albums,
+            SearchTab::Albums => SearchTab::Tracks,
+        }
+    }
+}
+
+/// How album results are ordered; `S` on the Albums tab cycles them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AlbumSort {
+    /// Spotify's order, best match first
+    #[default]
+    Relevance,
+    Oldest,
+    Newest,
+}
+
+impl AlbumSort {
+    pub fn next(self) -> AlbumSort {
+        match self {
+            AlbumSort::Relevance => AlbumSort::Oldest,
+            AlbumSort::Oldest => AlbumSort::Newest,
+            AlbumSort::Newest => AlbumSort::Relevance,
+        }
+    }
+
+    /// `release date ↑`, or `None` in Spotify's order
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            AlbumSort::Relevance => None,
+            AlbumSort::Oldest => Some("release date ↑"),
+            AlbumSort::Newest => Some("release date ↓"),
+        }
+    }
+}
+
+/// A line of the album results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlbumRow {
+    /// Header for albums from this decade on; `None` for ones without a date
+    Decade(Option<i32>),
+    /// Index into `SearchState::albums`
+    Album(usize),
+}
+
+/// The year out of a `release_date`, which Spotify gives as `1997`,
+/// `1997-05` or `1997-05-21`
+pub fn release_year(album: &SimplifiedAlbum) -> Option<i32> {
+    album.release_date.as_deref()?.get(..4)?.parse().ok()
+}
+
+pub fn decade_of(year: i32) -> i32 {
+    year - year.rem_euclid(10)
+}
+
+/// `90s`, `'90s`, `1990s` or `1990` → 1990; two digits below 30 mean the
+/// 2000s, so `10s` is 2010
+pub fn parse_decade(input: &str) -> Option<i32> {
+    let digits = input.trim().trim_start_matches('\'').trim_end_matches('s');
+    let year: i32 = digits.parse().ok()?;
+    let year = match digits.len() {
+        2 if year < 30 => 2000 + year,
+        2 => 1900 + year,
+        4 => year,
+        _ => return None,
+    };
+    Some(decade_of(year))
+}
+
+/// Lists `/` can narrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterList {
+    Liked,
+    Playlists,
+    PlaylistTracks,
+    Queue,
+}
+
+/// `/` on Library, Playlists or the Queue: hides rows that don't fuzzy-match
+/// `query`. Rows keep their indices, so every action works as unfiltered
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListFilter {
+    /// The list it was typed on; another list on screen isn't narrowed
+    pub list: Option<FilterList>,
+    pub query: String,
+    /// Keys go into `query` until Enter
+    pub typing: bool,
+}
+
+/// Where `step` rows up or down lands among the `rows` left by a filter,
+/// from `selected` (which may itself be hidden)
+pub fn step_filtered(rows: &[usize], selected: usize, step: usize, up: bool) -> Option<usize> {
+    let last = rows.len().checked_sub(1)?;
+    let at = rows.partition_point(|&r| r < selected);
+    let pos = if up {
+        at.saturating_sub(step)
+    } else if rows.get(at) == Some(&selected) {
+        at.saturating_add(step)
+    } else {
+        at.saturating_add(step - 1)
+    };
+    Some(rows[pos.min(last)])
+}
+
+/// What `/` matches a track on
+fn track_haystack(track: &FullTrack) -> String {
+    let artists: Vec<&str> = track.artists.iter().map(|a| a.name.as_str()).collect();
+    format!("{} {} {}", track.name, artists.join(" "), track.album.name)
+}
+
+/// Columns `S` sorts Liked Songs and playlist tracks by, in cycle order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Title,
+    Artist,
+    Album,
+    Duration,
+    Added,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 5] = [SortColumn::Title, SortColumn::Artist, SortColumn::Album, SortColumn::Duration, SortColumn::Added];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortColumn::Title => "title",
+            SortColumn::Artist => "artist",
+            SortColumn::Album => "album",
+            SortColumn::Duration => "duration",
+            SortColumn::Added => "date added",
+        }
+    }
+}
+
+/// How a track table is sorted; no column means Spotify's own order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    pub column: Option<SortColumn>,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    /// Each column ascending, then descending, then back to Spotify's order
+    pub fn next(self) -> SortOrder {
+        let Some(column) = self.column else {
+            return SortOrder { column: Some(SortColumn::ALL[0]), descending: false };
+        };
+        if !self.descending {
+            return SortOrder { column: Some(column), descending: true };
+        }
+        let at = SortColumn::ALL.iter().position(|&c| c == column).unwrap_or_default();
+        match SortColumn::ALL.get(at + 1) {
+            Some(&next) => SortOrder { column: Some(next), descending: false },
+            None => SortOrder::default(),
+        }
+    }
+
+    /// `artist ↓`, or `None` in Spotify's order
+    pub fn label(self) -> Option<String> {
+        let column = self.column?;
+        Some(format!("{} {}", column.name(), if self.descending { "↓" } else { "↑" }))
+    }
+}
+
+/// What a track row sorts on for one column
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SortKey {
+    /// Collation key, so "The Beatles" sorts under B
+    Text(String),
+    Number(i64),
+    /// Rows without a date (old playlist entries) sort first
+    Date(Option<DateTime<Utc>>),
+}
+
+impl SortKey {
+    /// `track` is `None` for podcast episodes in a playlist, which sort as blanks
+    pub fn of(column: SortColumn, track: Option<&FullTrack>, added: Option<DateTime<Utc>>) -> SortKey {
+        let text = |f: fn(&FullTrack) -> &str| SortKey::Text(track.map(|t| collator().key(f(t))).unwrap_or_default());
+        match column {
+            SortColumn::Title => text(|t| &t.name),
+            SortColumn::Artist => text(|t| t.artists.first().map_or("", |a| a.name.as_str())),
+            SortColumn::Album => text(|t| &t.album.name),
+            SortColumn::Duration => SortKey::Number(track.map_or(0, |t| t.duration.num_milliseconds())),
+            SortColumn::Added => SortKey::Date(added),
+        }
+    }
+}
+
+/// Stable-sorts `items` by `key`, keeping `selected` on the same row
+pub fn sort_rows<T, K: Ord>(items: &mut Vec<T>, selected: &mut usize, descending: bool, key: impl Fn(&T) -> K) {
+    let mut order: Vec<(K, usize)> = items.iter().enumerate().map(|(i, item)| (key(item), i)).collect();
+    order.sort_by(|a, b| if descending { b.0.cmp(&a.0) } else { a.0.cmp(&b.0) });
+    if let Some(at) = order.iter().position(|&(_, i)| i == *selected) {
+        *selected = at;
+    }
+    let mut slots: Vec<Option<T>> = items.drain(..).map(Some).collect();
+    items.extend(order.into_iter().filter_map(|(_, i)| slots[i].take()));
+}
+
+/// Identifies a playlist entry for `PlaylistsState::positions`
+pub fn playlist_entry_key(item: &PlaylistItem) -> (Option<String>, Option<DateTime<Utc>>) {
+    use rspotify::prelude::Id;
+    let uri = match item.track {
+        Some(rspotify::model::PlayableItem::Track(ref t)) => t.id.as_ref().map(|id| id.uri()),
+        Some(rspotify::model::PlayableItem::Episode(ref e)) => Some(e.id.uri()),
+        None => None,
+    };
+    (uri, item.added_at)
+}
+
+/// Sub-tabs of the Library screen
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LibraryTab {
+    #[default]
+    Liked,
+    Albums,
+    Artists,
+}
+
+impl LibraryTab {
+    pub fn next(self) -> Self {
+        match self {
+            LibraryTab::Liked => LibraryTab::Albums,
+            LibraryTab::Albums => LibraryTab::Artists,
+            LibraryTab::Artists => LibraryTab::Liked,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LibraryState {
+    pub tab: LibraryTab,
+    pub liked_songs: Vec<SavedTrack>,
+    pub selected: usize,
+    pub is_loading: bool,
+    /// How many liked songs Spotify has; more than are listed while later
+    /// pages are still coming in
+    pub liked_total: usize,
+    /// Liked Songs pages are still being fetched in the background
+    pub paging_liked: bool,
+    pub saved_albums: Vec<SavedAlbum>,
+    pub selected_album: usize,
+    /// Showing the tracks of the selected album instead of the album list
+    pub viewing_album: bool,
+    pub selected_album_track: usize,
+    pub followed_artists: Vec<FullArtist>,
+    pub selected_artist: usize,
+    /// Top tracks of the selected artist, shown instead of the artist list when set
+    pub artist_top_tracks: Option<Vec<FullTrack>>,
+    pub selected_artist_track: usize,
+    /// Liked track URIs that are also on a saved album (flagged in the table)
+    pub on_saved_album: HashSet<String>,
+    pub sort: SortOrder,
+}
+
+/// `:dedupe` popup: saved albums sharing tracks with Liked Songs
+#[derive(Debug, Clone, Default)]
+pub struct DedupeView {
+    pub overlaps: Vec<AlbumOverlap>,
+    pub selected: usize,
+}
+
+/// `:archived` popup: playlists archived with `X`, newest first
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveView {
+    pub entries: Vec<PlaylistArchive>,
+    pub selected: usize,
+}
+
+/// `gd` popup: the Spotify Connect devices playback can move to
+#[derive(Debug, Clone, Default)]
+pub struct DevicesView {
+    pub devices: Vec<Device>,
+    pub selected: usize,
+}
+
+/// `gl` popup: the tail of the log, following new lines
+#[derive(Debug, Clone, Default)]
+pub struct LogView {
+    /// The log file, named in the popup
+    pub path: Option<std::path::PathBuf>,
+    pub lines: Vec<LogLine>,
+    /// `logs::written()` when `lines` was taken
+    pub written: u64,
+    /// Lines scrolled up from the newest; 0 follows the log
+    pub scroll: usize,
+}
+
+impl LogView {
+    pub fn open(path: Option<std::path::PathBuf>) -> Self {
+        let (written, lines) = logs::recent();
+        LogView { path, lines, written, scroll: 0 }
+    }
+
+    /// Takes in what's been logged since, unless scrolled up to read
+    pub fn follow(&mut self) {
+        if self.scroll == 0 && logs::written() != self.written {
+            (self.written, self.lines) = logs::recent();
+        }
+    }
+}
+
+/// `M` popup: liked songs whose indexed audio features pass a mood filter
+#[derive(Debug, Clone)]
+pub struct MoodView {
+    pub filter: MoodFilter,
+    pub tracks: Vec<(FullTrack, TrackFeatures)>,
+    /// Liked songs the filter couldn't judge because they aren't indexed yet
+    pub unindexed: usize,
+    pub selected: usize,
+}
+
+/// Tracks shared with `vibes inbox add` (or appended to the inbox file),
+/// oldest first; the `gi` popup lists them when `open`
+#[derive(Debug, Clone, Default)]
+pub struct Inbox {
+    pub items: Vec<InboxItem>,
+    /// Names and artists for the rows, fetched when the popup opens
+    pub tracks: HashMap<String, FullTrack>,
+    pub open: bool,
+    pub selected: usize,
+}
+
+impl Inbox {
+    pub fn contains(&self, uri: &str) -> bool {
+        self.items.iter().any(|i| i.uri == uri)
+    }
+
+    pub fn selected_item(&self) -> Option<&InboxItem> {
+        self.items.get(self.selected)
+    }
+}
+
+/// Radar chart axes, in drawing order (clockwise from the top)
+pub const RADAR_AXES: [&str; 5] = ["energy", "dance", "valence", "acoustic", "instrumental"];
+
+/// A track's audio features on the `RADAR_AXES`, each 0.0–1.0
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FeatureProfile(pub [f64; 5]);
+
+impl From<&AudioFeatures> for FeatureProfile {
+    fn from(f: &AudioFeatures) -> Self {
+        FeatureProfile::from(&TrackFeatures::from(f))
+    }
+}
+
+impl From<&TrackFeatures> for FeatureProfile {
+    fn from(f: &TrackFeatures) -> Self {
+        let values = [f.energy, f.danceability, f.valence, f.acousticness, f.instrumentalness];
+        FeatureProfile(values.map(|v| (v as f64).clamp(0.0, 1.0)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadarTrack {
+    pub id: String,
+    pub name: String,
+    /// Filled in when the fetch comes back; `None` after that means unavailable
+    pub profile: Option<FeatureProfile>,
+    pub loaded: bool,
+}
+
+/// `i` popup: the highlighted track's audio features, side by side with the
+/// playing track's when that's a different one
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RadarView {
+    pub tracks: Vec<RadarTrack>,
+}
+
+impl RadarView {
+    /// Stores a fetch result for whichever chart shows `track_id`
+    pub fn set_profile(&mut self, track_id: &str, profile: Option<FeatureProfile>) {
+        for track in self.tracks.iter_mut().filter(|t| t.id == track_id) {
+            track.profile = profile;
+            track.loaded = true;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PlaylistsState {
+    pub playlists: Vec<SimplifiedPlaylist>,
+    pub selected_playlist: usize,
+    pub playlist_tracks: Vec<PlaylistItem>,
+    pub selected_track: usize,
+    pub viewing_tracks: bool,
+    pub is_loading: bool,
+    /// Playlist ids whose snapshot changed since they were last opened
+    pub updated: HashSet<String>,
+    /// Changes found when the open playlist was last loaded (name, diff)
+    pub diff: Option<(String, PlaylistDiff)>,
+    pub show_diff: bool,
+    pub sort: SortOrder,
+    /// Where each loaded track sits in the playlist itself, to go back to
+    /// that order after sorting
+    pub positions: HashMap<(Option<String>, Option<DateTime<Utc>>), usize>,
+    pub prefetched: Prefetched,
+    /// The open playlist shows its prefetched first page while the rest loads
+    pub paging_tracks: bool,
+}
+
+/// The first page of a playlist, fetched while it was highlighted
+#[derive(Debug, Clone)]
+pub struct PrefetchedPage {
+    pub playlist_id: String,
+    /// The playlist's snapshot when fetched; a newer one makes the page stale
+    pub snapshot_id: String,
+    pub items: Vec<PlaylistItem>,
+    /// Tracks in the whole playlist
+    pub total: usize,
+}
+
+impl PrefetchedPage {
+    pub fn is_complete(&self) -> bool {
+        self.items.len() >= self.total
+    }
+}
+
+/// First pages of recently highlighted playlists, so Enter can show them
+/// without waiting. One is fetched at a time, which keeps scrolling through
+/// the list from firing a request per row.
+#[derive(Debug, Clone, Default)]
+pub struct Prefetched {
+    /// Oldest first
+    pages: VecDeque<PrefetchedPage>,
+    /// Playlist id being fetched
+    pub in_flight: Option<String>,
+    /// Playlists whose prefetch failed, with the snapshot tried and when;
+    /// Enter loads them the usual way meanwhile
+    failed: HashMap<String, (String, Instant)>,
+}
+
+impl Prefetched {
+    /// Pages kept; the least recently fetched goes first
+    pub const KEEP: usize = 20;
+    /// How long a failed prefetch rests before it's tried again
+    pub const RETRY_AFTER: Duration = Duration::from_secs(60);
+
+    /// The page for `playlist_id` if it's still `snapshot_id`
+    pub fn get(&self, playlist_id: &str, snapshot_id: &str) -> Option<&PrefetchedPage> {
+        self.pages.iter().find(|p| p.playlist_id == playlist_id && p.snapshot_id == snapshot_id)
+    }
+
+    /// Whether to fetch `playlist_id` now. A failure holds off only for the
+    /// same snapshot, and only for `RETRY_AFTER`.
+    pub fn wants(&self, playlist_id: &str, snapshot_id: &str) -> bool {
+        let resting = self.failed.get(playlist_id)
+            .is_some_and(|(snapshot, at)| snapshot == snapshot_id && at.elapsed() < Self::RETRY_AFTER);
+        self.in_flight.is_none() && !resting && self.get(playlist_id, snapshot_id).is_none()
+    }
+
+    pub fn fail(&mut self, playlist_id: String, snapshot_id: String) {
+        self.failed.insert(playlist_id, (snapshot_id, Instant::now()));
+    }
+
+    /// The playlist loaded after all, so its next prefetch goes ahead
+    pub fn loaded(&mut self, playlist_id: &str) {
+        self.failed.remove(playlist_id);
+    }
+
+    pub fn insert(&mut self, page: PrefetchedPage) {
+        self.failed.remove(&page.playlist_id);
+        self.pages.retain(|p| p.playlist_id != page.playlist_id);
+        self.pages.push_back(page);
+        while self.pages.len() > Self::KEEP {
+            self.pages.pop_front();
+        }
+    }
+}
+
+/// Spotify's browse categories (genres, moods, charts…) and the playlists of
+/// the opened one
+#[derive(Debug, Clone, Default)]
+pub struct BrowseState {
+    pub categories: Vec<Category>,
+    pub selected_category: usize,
+    /// Playlists of the category they were loaded for (its id)
+    pub playlists: Vec<SimplifiedPlaylist>,
+    pub playlists_of: Option<String>,
+    pub selected_playlist: usize,
+    pub viewing_playlists: bool,
+    pub is_loading: bool,
+    /// The header Spotify sends with its featured playlists
+    pub featured_message: Option<String>,
+}
+
+impl BrowseState {
+    /// Id of the Featured entry put at the top of the categories
+    pub const FEATURED: &'static str = "featured";
+
+    pub fn featured_category() -> Category {
+        Category { href: String::new(), icons: Vec::new(), id: Self::FEATURED.to_string(), name: "Featured".to_string() }
+    }
+
+    pub fn selected_category(&self) -> Option<&Category> {
+        self.categories.get(self.selected_category)
+    }
+
+    /// The highlighted playlist, once a category is open
+    pub fn selected_playlist(&self) -> Option<&SimplifiedPlaylist> {
+        self.playlists.get(self.selected_playlist).filter(|_| self.viewing_playlists)
+    }
+}
+
+/// Where an upcoming queue entry came from
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueueSource {
+    /// Added with `a` / `A`
+    Manual,
+    /// Part of what was last started from vibes, e.g. `playlist Road trip`
+    From(String),
+    /// Anything else: Spotify's autoplay, or something started on another client
+    Autoplay,
+}
+
+impl QueueSource {
+    pub fn label(&self) -> String {
+        match self {
+            QueueSource::Manual => "Manually queued".to_string(),
+            QueueSource::From(name) => format!("From {name}"),
+            QueueSource::Autoplay => "Autoplay".to_string(),
+        }
+    }
+}
+
+/// A row of the grouped Queue table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueueRow {
+    /// Index into `QueueState::groups`
+    Group(usize),
+    /// Index into `QueueState::tracks`
+    Track(usize),
+}
+
+/// Runs of consecutive tracks sharing a source, as `(source, track range)`
+pub fn queue_groups(sources: &[QueueSource]) -> Vec<(QueueSource, Range<usize>)> {
+    let mut groups: Vec<(QueueSource, Range<usize>)> = Vec::new();
+    for (i, source) in sources.iter().enumerate() {
+        match groups.last_mut() {
+            Some((last, range)) if last == source => range.end = i + 1,
+            _ => groups.push((source.clone(), i..i + 1)),
+        }
+    }
+    groups
+}
+
+/// A header per group, followed by its tracks unless the group is collapsed
+pub fn queue_rows(groups: &[(QueueSource, Range<usize>)], collapsed: &HashSet<QueueSource>) -> Vec<QueueRow> {
+    let mut rows = Vec::new();
+    for (g, (source, range)) in groups.iter().enumerate() {
+        rows.push(QueueRow::Group(g));
+        if !collapsed.contains(source) {
+            rows.extend(range.clone().map(QueueRow::Track));
+        }
+    }
+    rows
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QueueState {
+    pub tracks: Vec<FullTrack>,
+    /// Index into `rows()`
+    pub selected: usize,
+    pub is_loading: bool,
+    /// Tracks queued by hand that haven't played yet
+    pub manual: HashSet<String>,
+    /// Label and tracks of what was last started from vibes
+    pub origin: Option<(String, HashSet<String>)>,
+    pub collapsed: HashSet<QueueSource>,
+}
+
+impl QueueState {
+    pub fn set_origin(&mut self, label: impl Into<String>, uris: impl IntoIterator<Item = String>) {
+        self.origin = Some((label.into(), uris.into_iter().collect()));
+    }
+
+    pub fn source_of(&self, uri: &str) -> QueueSource {
+        if self.manual.contains(uri) {
+            return QueueSource::Manual;
+        }
+        match self.origin {
+            Some((ref label, ref uris)) if uris.contains(uri) => QueueSource::From(label.clone()),
+            _ => QueueSource::Autoplay,
+        }
+    }
+
+    pub fn groups(&self) -> Vec<(QueueSource, Range<usize>)> {
+        use rspotify::prelude::Id;
+        let sources: Vec<QueueSource> = self
+            .tracks
+            .iter()
+            .map(|t| t.id.as_ref().map_or(QueueSource::Autoplay, |id| self.source_of(&id.uri())))
+            .collect();
+        queue_groups(&sources)
+    }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ActiveScreen {
-    Search,
-    Library,
-    Playlists,
-    Queue,
-    Vibes,
-}
+    pub fn rows(&self) -> Vec<QueueRow> {
+        queue_rows(&self.groups(), &self.collapsed)
+    }
 
-impl Default for ActiveScreen {
-    fn default() -> Self {
-        ActiveScreen::Search
+    pub fn selected_row(&self) -> Option<QueueRow> {
+        self.rows().get(self.selected).copied()
     }
-}
 
-#[derive(Debug, Clone, Default)]
-#[allow(dead_code)]
-pub struct CurrentTrack {
-    pub id: Option<String>,
-    pub name: String,
-    pub artists: Vec<String>,
-    pub album: String,
-    pub duration_ms: u32,
-    pub progress_ms: u32,
-    pub is_playing: bool,
-    pub is_liked: bool,
-    pub album_art_url: Option<String>,
-    pub device_volume: Option<u8>,
-}
+    pub fn selected_track(&self) -> Option<&FullTrack> {
+        match self.selected_row()? {
+            QueueRow::Track(i) => self.tracks.get(i),
+            QueueRow::Group(_) => None,
+        }
+    }
 
-impl CurrentTrack {
-    pub fn progress_percent(&self) -> f64 {
-        if self.duration_ms == 0 {
-            return 0.0;
+    /// Row showing track `i`, expanding its group if it was collapsed
+    pub fn select_track(&mut self, i: usize) -> bool {
+        if let Some((source, _)) = self.groups().into_iter().find(|(_, range)| range.contains(&i)) {
+            self.collapsed.remove(&source);
+        }
+        match self.rows().iter().position(|r| *r == QueueRow::Track(i)) {
+            Some(row) => {
+                self.selected = row;
+                true
+            }
+            None => false,
         }
-        (self.progress_ms as f64 / self.duration_ms as f64).clamp(0.0, 1.0)
     }
 
-    pub fn progress_formatted(&self) -> String {
-        let secs = self.progress_ms / 1000;
-        let dur_secs = self.duration_ms / 1000;
-        format!(
-            "{}:{:02} / {}:{:02}",
-            secs / 60,
-            secs % 60,
-            dur_secs / 60,
-            dur_secs % 60
-        )
+    /// Collapses or expands the group of the selected header
+    pub fn toggle_selected_group(&mut self) -> bool {
+        let Some(QueueRow::Group(g)) = self.selected_row() else { return false };
+        let Some((source, _)) = self.groups().into_iter().nth(g) else { return false };
+        if !self.collapsed.remove(&source) {
+            self.collapsed.insert(source);
+        }
+        true
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct SearchState {
-    pub query: String,
-    pub tracks: Vec<FullTrack>,
-    pub selected_track: usize,
-    pub is_searching: bool,
+/// Time windows of Spotify's personalization endpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatsRange {
+    /// About 4 weeks
+    Short,
+    /// About 6 months
+    #[default]
+    Medium,
+    /// Several years
+    Long,
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct LibraryState {
-    pub liked_songs: Vec<SavedTrack>,
-    pub selected: usize,
-    pub is_loading: bool,
+impl StatsRange {
+    pub fn next(self) -> Self {
+        match self {
+            StatsRange::Short => StatsRange::Medium,
+            StatsRange::Medium => StatsRange::Long,
+            StatsRange::Long => StatsRange::Short,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        self.next().next()
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StatsRange::Short => "last 4 weeks",
+            StatsRange::Medium => "last 6 months",
+            StatsRange::Long => "all time",
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct PlaylistsState {
-    pub playlists: Vec<SimplifiedPlaylist>,
-    pub selected_playlist: usize,
-    pub playlist_tracks: Vec<PlaylistItem>,
-    pub selected_track: usize,
-    pub viewing_tracks: bool,
-    pub is_loading: bool,
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum StatsView {
+    #[default]
+    Tracks,
+    Artists,
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct QueueState {
-    pub tracks: Vec<FullTrack>,
+pub struct StatsState {
+    pub range: StatsRange,
+    pub view: StatsView,
+    pub top_tracks: Vec<FullTrack>,
+    pub top_artists: Vec<FullArtist>,
     pub selected: usize,
     pub is_loading: bool,
+    /// Range the lists currently hold, so switching back doesn't refetch
+    pub loaded: Option<StatsRange>,
+    /// Today's date in earlier years, from the local play log
+    pub on_this_day: Vec<DayRecap>,
 }
 
-#[derive(Debug, Clone, PartialEq, strum_macros::Display, strum_macros::EnumIter)]
-pub enum VibesMood {
-    #[strum(to_string = "🌊 Chill")]
-    Chill,
-    #[strum(to_string = "⚡ Hype")]
-    Hype,
-    #[strum(to_string = "🎯 Focus")]
-    Focus,
-    #[strum(to_string = "✨ Happy")]
-    Happy,
-    #[strum(to_string = "🌑 Dark")]
-    Dark,
+impl StatsState {
+    pub fn row_count(&self) -> usize {
+        match self.view {
+            StatsView::Tracks => self.top_tracks.len(),
+            StatsView::Artists => self.top_artists.len(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct VibesState {
+    /// Built-ins plus the user's, see `VibesMood::all`
+    pub moods: Vec<VibesMood>,
     pub selected_mood: usize,
     pub recommendations: Vec<FullTrack>,
     pub selected_track: usize,
     pub is_loading: bool,
+    /// Set while `Q` is appending the recommendations to the queue
+    pub enqueueing: bool,
+}
+
+/// What a text prompt popup is asking for
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromptKind {
+    NewPlaylist,
+    /// `:` command line, e.g. `:features`
+    Command,
+    /// `M`: a mood filter over Liked Songs, e.g. `energy > 0.7 and tempo 120–130`
+    MoodFilter,
+}
+
+impl LibraryState {
+    pub fn open_album(&self) -> Option<&SavedAlbum> {
+        self.saved_albums.get(self.selected_album)
+    }
+
+    /// Appends liked songs that aren't listed yet. Offsets shift when
+    /// something is liked while pages are coming in, so neighbouring pages
+    /// can overlap.
+    pub fn add_liked(&mut self, songs: Vec<SavedTrack>) {
+        use rspotify::prelude::Id;
+        let key = |s: &SavedTrack| (s.track.id.as_ref().map(|id| id.uri()), s.track.name.clone(), s.added_at);
+        let listed: HashSet<_> = self.liked_songs.iter().map(key).collect();
+        self.liked_songs.extend(songs.into_iter().filter(|s| !listed.contains(&key(s))));
+        self.liked_total = self.liked_total.max(self.liked_songs.len());
+    }
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::NewPlaylist => " ＋ New playlist name ",
+            PromptKind::Command => " : command ",
+            PromptKind::MoodFilter => " ≈ Mood filter, e.g. energy > 0.7 and tempo 120–130 ",
+        }
+    }
+}
+
+/// Single-line text input popup; while open it captures typing like search does
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind) -> Self {
+        Prompt { kind, input: String::new() }
+    }
+}
+
+/// "Add to playlist" popup opened on a track
+#[derive(Debug, Clone)]
+pub struct PlaylistPicker {
+    pub track_uris: Vec<String>,
+    /// The track's name, or how many were marked
+    pub track_name: String,
+    /// Index into `AppState::editable_playlists`
+    pub selected: usize,
+}
+
+/// How much a toast matters; sets its icon, color and how long it stays up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Info,
+    /// Something didn't happen, but nothing failed (unavailable, bad input)
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "✔ ",
+            Severity::Warning => "⚠ ",
+            Severity::Error => "✖ ",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default)]
 pub struct Notification {
     pub message: String,
-    pub remaining_ticks: u8,
-    pub is_error: bool,
+    pub remaining_ticks: u16,
+    pub severity: Severity,
+    /// Full error chain, shown with `d` while the toast is up
+    pub details: Option<String>,
+    /// Action that failed and what it was aimed at, re-run with Enter while
+    /// the toast is up
+    pub retry: Option<(UserAction, RetryTarget)>,
+}
+
+/// Where an action was aimed when it ran, so a retry hits the same row even
+/// if the selection has moved on since
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryTarget {
+    pub screen: ActiveScreen,
+    /// Id of the playlist open at the time
+    pub playlist_id: Option<String>,
+    /// URI of the highlighted track, album, artist or playlist
+    pub uri: Option<String>,
 }
 
 impl Notification {
     pub fn info(msg: impl Into<String>) -> Self {
-        Notification { message: msg.into(), remaining_ticks: 30, is_error: false }
+        Notification { message: msg.into(), remaining_ticks: 30, ..Default::default() }
+    }
+    pub fn warning(msg: impl Into<String>) -> Self {
+        Notification { message: msg.into(), remaining_ticks: 45, severity: Severity::Warning, ..Default::default() }
+    }
+    /// Error toast carrying the error chain; stays up longer so there's time to act on it
+    pub fn failure(msg: impl Into<String>, err: &anyhow::Error) -> Self {
+        Notification {
+            message: msg.into(),
+            remaining_ticks: 75,
+            severity: Severity::Error,
+            details: Some(format!("{err:?}")),
+            retry: None,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Past toasts kept for `:messages`
+pub const NOTIFICATION_HISTORY: usize = 50;
+
+/// Loudness (dB, as reported by audio features) that volume leveling aims for
+pub const REPLAY_GAIN_TARGET_DB: f32 = -9.0;
+/// Largest volume nudge, in percentage points, leveling may apply
+pub const REPLAY_GAIN_MAX_DELTA: f32 = 8.0;
+
+/// Takes every item matching `pred` out of `items`, remembering where each
+/// was so `restore_removed` can put them back (optimistic edits)
+pub fn take_matching<T>(items: &mut Vec<T>, pred: impl Fn(&T) -> bool) -> Vec<(usize, T)> {
+    let mut removed = Vec::new();
+    let mut i = 0;
+    let mut original = 0;
+    while i < items.len() {
+        if pred(&items[i]) {
+            removed.push((original, items.remove(i)));
+        } else {
+            i += 1;
+        }
+        original += 1;
+    }
+    removed
+}
+
+/// Undoes `take_matching`
+pub fn restore_removed<T>(items: &mut Vec<T>, removed: Vec<(usize, T)>) {
+    for (i, item) in removed {
+        items.insert(i.min(items.len()), item);
     }
-    pub fn error(msg: impl Into<String>) -> Self {
-        Notification { message: msg.into(), remaining_ticks: 40, is_error: true }
+}
+
+/// Halves a tempo until it's at most `max_bpm`, so very fast tracks pulse in
+/// half/quarter time instead of strobing while staying on the beat
+pub fn capped_bpm(bpm: f32, max_bpm: f32) -> f32 {
+    let mut bpm = bpm;
+    while bpm > max_bpm.max(30.0) {
+        bpm /= 2.0;
     }
+    bpm
+}
+
+/// Volume delta (percentage points, 1 per dB) nudging a track of the given
+/// loudness towards `REPLAY_GAIN_TARGET_DB` — a poor man's ReplayGain.
+pub fn replay_gain_delta(loudness_db: f32) -> i8 {
+    (REPLAY_GAIN_TARGET_DB - loudness_db)
+        .clamp(-REPLAY_GAIN_MAX_DELTA, REPLAY_GAIN_MAX_DELTA)
+        .round() as i8
+}
+
+/// Running start times for tracks of the given lengths, beginning `first_ms` from now
+pub fn start_offsets(first_ms: u64, durations: impl IntoIterator<Item = u64>) -> Vec<u64> {
+    let mut at = first_ms;
+    durations
+        .into_iter()
+        .map(|d| {
+            let start = at;
+            at += d;
+            start
+        })
+        .collect()
 }
 
 pub struct AppState {
     pub active_screen: ActiveScreen,
     pub previous_screen: Option<ActiveScreen>,
+    /// Sidebar screens in order; number keys pick from this
+    pub screens: Vec<ActiveScreen>,
     pub current_track: CurrentTrack,
     pub volume: u8,
+    pub device_watch: DeviceWatch,
+    /// Set when the playing device disappeared mid-track
+    pub resume_point: Option<ResumePoint>,
+    pub liked: LikedTracks,
+    pub connection: Connection,
+    /// What's still loading after login; `None` once the panel has gone
+    pub startup: Option<Startup>,
+    /// Volume to go back to on unmute; `Some` while muted
+    pub muted_volume: Option<u8>,
+    pub replay_gain: bool,
+    pub replay_gain_offset: i8,
     pub search: SearchState,
+    pub filter: ListFilter,
     pub library: LibraryState,
+    /// Audio features for the whole library, filled in by the `feature_index` job
+    pub feature_index: FeatureIndex,
     pub playlists: PlaylistsState,
     pub queue: QueueState,
     pub vibes: VibesState,
-    pub notification: Option<Notification>,
+    pub stats: StatsState,
+    pub browse: BrowseState,
+    /// Most recent first, capped at `HISTORY_LIMIT`
+    pub history: VecDeque<HistoryEntry>,
+    /// Time actually spent playing the current track (seeks don't count)
+    pub listened_ms: u32,
+    /// Progress reached the end while playing — a natural completion
+    pub track_ended: bool,
+    /// When playback was last seen going from playing to paused (for smart resume)
+    pub paused_at: Option<Instant>,
+    /// Rows in the visible list, refreshed every frame (PageUp/PageDown step)
+    pub page_size: usize,
+    /// Toasts on screen, oldest first, at most `toast_settings.max_stacked`
+    pub notifications: VecDeque<Notification>,
+    /// Every toast raised this session, most recent first (`:messages`)
+    pub notification_history: VecDeque<(DateTime<Local>, Notification)>,
+    pub show_messages: bool,
+    /// Long operations still running (`gb` lists them)
+    pub jobs: Jobs,
+    pub show_jobs: bool,
+    pub selected_job: usize,
+    pub toast_settings: NotificationSettings,
+    /// Configured toast lifetimes in UI ticks, overriding the built-in ones
+    pub toast_ticks: ToastTicks,
+    /// Bumped on every `set_notification`, to tell which action raised a toast
+    pub notification_seq: u64,
+    /// Error chain popup opened from an error toast
+    pub error_details: Option<String>,
+    pub prompt: Option<Prompt>,
+    pub picker: Option<PlaylistPicker>,
+    pub dedupe: Option<DedupeView>,
+    pub archived: Option<ArchiveView>,
+    pub devices: Option<DevicesView>,
+    pub log_view: Option<LogView>,
+    pub mood: Option<MoodView>,
+    pub inbox: Inbox,
+    pub radar: Option<RadarView>,
+    pub row_jump: Option<RowJump>,
+    /// First key of a two-key sequence (`g` of `gp`) waiting for the second
+    pub pending_key: Option<char>,
+    pub visual: Option<VisualSelect>,
+    /// Spotify id of the signed-in user, to tell which playlists we can edit
+    pub user_id: Option<String>,
     pub show_help: bool,
+    pub show_features: bool,
+    pub features: Features,
+    /// Endpoints the app/account may use, probed after login
+    pub capabilities: Capabilities,
+    /// Web remote address and pairing code, when `remote_addr` is configured
+    pub remote: Option<Pairing>,
+    /// Where the MPD server listens, when `mpd_addr` is configured
+    pub mpd: Option<std::net::SocketAddr>,
+    /// Where the control socket listens, once it's up
+    pub control_socket: Option<std::path::PathBuf>,
+    /// The trace file while `:trace` is on
+    pub trace: Option<std::path::PathBuf>,
     pub should_quit: bool,
     pub eq_bars: [u8; 24],
+    /// Latest levels heard by audio capture; the bars follow these instead
+    /// of animating while it runs
+    pub eq_capture: Option<[u8; 24]>,
     pub eq_tick: u64,
     pub eq_expanded: bool,
+    /// `z`: Now Playing fills the screen instead of the sidebar and lists
+    pub focus: bool,
+    /// `Z` or `--mini`: no sidebar, just the list and a compact player bar,
+    /// for a small tmux pane
+    pub mini: bool,
     pub ticker_offset: usize,
     pub ticker_tick: u64,
+    /// Tempo of the current track when tempo sync is on (already capped)
+    pub tempo_bpm: Option<f32>,
+    /// Progress through the current beat, 0.0..1.0
+    pub beat_phase: f32,
+    /// Set on the tick a beat lands
+    pub beat: bool,
+    /// Energy (0–1) of the current track when tempo sync is on; how high
+    /// the bars kick on the beat
+    pub energy: Option<f32>,
+    /// Visualizer animation frame (animal dance moves)
+    pub anim_frame: u64,
+    pub visualizer: Visualizer,
+    pub sidebar_panel: SidebarPanel,
+    /// From the configured quote source; empty means the built-in ones
+    pub quotes: Vec<String>,
+    /// Synced lyrics for the current track, when the lyrics panel is on
+    pub lyrics: Option<Vec<LyricLine>>,
+    pub theme: Theme,
+    /// What the terminal can show; `theme` is mapped down to it when drawn
+    pub color_depth: ColorDepth,
+    /// Album-art accent currently drawn, fading towards `accent_target`
+    pub accent_tint: Option<Rgb>,
+    pub accent_target: Option<Rgb>,
+    /// The playing track's album art for focus mode, with `album_art` on
+    pub cover: Option<Cover>,
+    /// Track-change and screen-switch transitions
+    pub anims: Animations,
     pub spotify: Option<Arc<Mutex<AuthCodePkceSpotify>>>,
     pub is_authenticated: bool,
     pub auth_url: Option<String>,
+    /// Where the login redirect is awaited, as the auth screen shows it
+    pub auth_redirect: Option<String>,
+    /// Why the last login attempt ended, while the auth screen waits for `r`
+    pub auth_error: Option<String>,
+    /// The login screen is back mid-session because Spotify dropped the login
+    pub auth_expired: bool,
     #[allow(dead_code)]
     pub cached_device_id: Option<String>,
 }
@@ -156,24 +1566,92 @@ impl Default for AppState {
         AppState {
             active_screen: ActiveScreen::Search,
             previous_screen: None,
+            screens: ActiveScreen::ALL.to_vec(),
             current_track: CurrentTrack::default(),
             volume: 50,
+            replay_gain: false,
+            device_watch: DeviceWatch::default(),
+            resume_point: None,
+            liked: LikedTracks::default(),
+            connection: Connection::default(),
+            startup: None,
+            muted_volume: None,
+            replay_gain_offset: 0,
             search: SearchState::default(),
+            filter: ListFilter::default(),
             library: LibraryState::default(),
+            feature_index: FeatureIndex::default(),
             playlists: PlaylistsState::default(),
             queue: QueueState::default(),
             vibes: VibesState::default(),
-            notification: None,
+            stats: StatsState::default(),
+            browse: BrowseState::default(),
+            history: VecDeque::new(),
+            listened_ms: 0,
+            track_ended: false,
+            paused_at: None,
+            page_size: 10,
+            notifications: VecDeque::new(),
+            notification_history: VecDeque::new(),
+            show_messages: false,
+            jobs: Jobs::default(),
+            show_jobs: false,
+            selected_job: 0,
+            toast_settings: NotificationSettings::default(),
+            toast_ticks: ToastTicks::default(),
+            notification_seq: 0,
+            error_details: None,
+            prompt: None,
+            picker: None,
+            dedupe: None,
+            archived: None,
+            devices: None,
+            log_view: None,
+            mood: None,
+            inbox: Inbox::default(),
+            radar: None,
+            row_jump: None,
+            pending_key: None,
+            visual: None,
+            user_id: None,
             show_help: false,
+            show_features: false,
+            features: Features::default(),
+            capabilities: Capabilities::default(),
+            remote: None,
+            mpd: None,
+            control_socket: None,
+            trace: None,
             should_quit: false,
             eq_bars: [4, 6, 8, 5, 7, 9, 4, 6, 8, 5, 7, 6, 4, 8, 5, 7, 9, 3, 6, 8, 5, 7, 4, 6],
+            eq_capture: None,
             eq_tick: 0,
             eq_expanded: false,
+            focus: false,
+            mini: false,
             ticker_offset: 0,
             ticker_tick: 0,
+            tempo_bpm: None,
+            beat_phase: 0.0,
+            beat: false,
+            energy: None,
+            anim_frame: 0,
+            visualizer: Visualizer::default(),
+            sidebar_panel: SidebarPanel::default(),
+            quotes: Vec::new(),
+            lyrics: None,
+            theme: Theme::default(),
+            color_depth: ColorDepth::TrueColor,
+            accent_tint: None,
+            accent_target: None,
+            cover: None,
+            anims: Animations::default(),
             spotify: None,
             is_authenticated: false,
             auth_url: None,
+            auth_redirect: None,
+            auth_error: None,
+            auth_expired: false,
             cached_device_id: None,
         }
     }
@@ -184,20 +1662,572 @@ impl AppState {
         if self.active_screen != screen {
             self.previous_screen = Some(self.active_screen.clone());
             self.active_screen = screen;
+            self.anims.screen_switched();
+        }
+        self.focus = false;
+    }
+
+    /// Takes in the startup probe: a Browse screen Spotify won't serve this
+    /// app leaves the sidebar, and so does Browse's featured row
+    pub fn set_capabilities(&mut self, caps: Capabilities) {
+        self.capabilities = caps;
+        if !caps.browse.usable() && self.screens.iter().any(|s| *s != ActiveScreen::Browse) {
+            self.screens.retain(|s| *s != ActiveScreen::Browse);
+            if self.active_screen == ActiveScreen::Browse {
+                self.navigate_to(self.screens[0].clone());
+            }
+        }
+        if !caps.featured.usable() {
+            let browse = &mut self.browse;
+            browse.categories.retain(|c| c.id != BrowseState::FEATURED);
+            browse.selected_category = browse.selected_category.min(browse.categories.len().saturating_sub(1));
+        }
+    }
+
+    /// Whether keys should be treated as text (search box, a prompt popup or
+    /// the `/` filter)
+    pub fn is_typing(&self) -> bool {
+        self.search.is_searching || self.prompt.is_some() || self.filter.typing
+    }
+
+    /// Puts Liked Songs in `library.sort` order. Spotify's own order is
+    /// newest first, so that's restored from the dates
+    pub fn sort_liked(&mut self) {
+        let library = &mut self.library;
+        sort_rows(&mut library.liked_songs, &mut library.selected, true, |s| s.added_at);
+        if let Some(column) = library.sort.column {
+            sort_rows(&mut library.liked_songs, &mut library.selected, library.sort.descending, |s| {
+                SortKey::of(column, Some(&s.track), Some(s.added_at))
+            });
+        }
+    }
+
+    /// Puts the open playlist in `playlists.sort` order, going back to the
+    /// playlist's own order first so ties keep it
+    pub fn sort_playlist_tracks(&mut self) {
+        let playlists = &mut self.playlists;
+        let positions = &playlists.positions;
+        sort_rows(&mut playlists.playlist_tracks, &mut playlists.selected_track, false, |item| {
+            positions.get(&playlist_entry_key(item)).copied().unwrap_or(usize::MAX)
+        });
+        if let Some(column) = playlists.sort.column {
+            sort_rows(&mut playlists.playlist_tracks, &mut playlists.selected_track, playlists.sort.descending, |item| {
+                let track = match item.track {
+                    Some(rspotify::model::PlayableItem::Track(ref t)) => Some(t),
+                    _ => None,
+                };
+                SortKey::of(column, track, item.added_at)
+            });
+        }
+    }
+
+    /// The list on screen that `/` can narrow
+    pub fn filterable_list(&self) -> Option<FilterList> {
+        match self.active_screen {
+            ActiveScreen::Library if self.library.tab == LibraryTab::Liked => Some(FilterList::Liked),
+            ActiveScreen::Playlists if self.playlists.viewing_tracks => Some(FilterList::PlaylistTracks),
+            ActiveScreen::Playlists => Some(FilterList::Playlists),
+            ActiveScreen::Queue => Some(FilterList::Queue),
+            _ => None,
+        }
+    }
+
+    /// The filter on the list on screen, while it's typed or narrowing
+    pub fn shown_filter(&self) -> Option<&ListFilter> {
+        let filter = &self.filter;
+        Some(filter).filter(|f| f.list.is_some() && f.list == self.filterable_list() && (f.typing || !f.query.is_empty()))
+    }
+
+    /// Indices of the rows the filter leaves on the list on screen, in order
+    /// (`rows()` positions on the Queue); `None` when nothing is filtered
+    pub fn filtered_rows(&self) -> Option<Vec<usize>> {
+        let query = self.shown_filter().map(|f| f.query.as_str()).filter(|q| !q.trim().is_empty())?;
+        let keep = |i: usize, haystack: String| fuzzy_match(query, &haystack).then_some(i);
+        Some(match self.filter.list? {
+            FilterList::Liked => self.library.liked_songs.iter().enumerate()
+                .filter_map(|(i, saved)| keep(i, track_haystack(&saved.track)))
+                .collect(),
+            FilterList::Playlists => self.playlists.playlists.iter().enumerate()
+                .filter_map(|(i, pl)| keep(i, format!("{} {}", pl.name, pl.owner.display_name.as_deref().unwrap_or_default())))
+                .collect(),
+            FilterList::PlaylistTracks => self.playlists.playlist_tracks.iter().enumerate()
+                .filter_map(|(i, item)| match item.track {
+                    Some(rspotify::model::PlayableItem::Track(ref track)) => keep(i, track_haystack(track)),
+                    _ => None,
+                })
+                .collect(),
+            FilterList::Queue => {
+                let groups = self.queue.groups();
+                let hits: Vec<bool> = self.queue.tracks.iter().map(|t| fuzzy_match(query, &track_haystack(t))).collect();
+                // A group header stays while any of its tracks match, folded or not
+                queue_rows(&groups, &self.queue.collapsed).iter().enumerate()
+                    .filter(|(_, row)| match **row {
+                        QueueRow::Track(i) => hits[i],
+                        QueueRow::Group(g) => hits[groups[g].1.clone()].contains(&true),
+                    })
+                    .map(|(row, _)| row)
+                    .collect()
+            }
+        })
+    }
+
+    /// The selection of the list `/` narrows
+    pub fn filter_selection(&mut self) -> Option<&mut usize> {
+        Some(match self.filter.list? {
+            FilterList::Liked => &mut self.library.selected,
+            FilterList::Playlists => &mut self.playlists.selected_playlist,
+            FilterList::PlaylistTracks => &mut self.playlists.selected_track,
+            FilterList::Queue => &mut self.queue.selected,
+        })
+    }
+
+    /// Moves the selection onto the first row left when the filter hides it
+    pub fn snap_to_filter(&mut self) {
+        let Some(rows) = self.filtered_rows() else { return };
+        if let Some(selected) = self.filter_selection() {
+            if rows.binary_search(selected).is_err() {
+                *selected = rows.first().copied().unwrap_or(*selected);
+            }
+        }
+    }
+
+    /// Playlists tracks can be added to: our own plus collaborative ones.
+    /// Until the user id is known every playlist is offered.
+    pub fn editable_playlists(&self) -> Vec<&SimplifiedPlaylist> {
+        use rspotify::prelude::Id;
+        self.playlists.playlists.iter()
+            .filter(|pl| {
+                pl.collaborative || match self.user_id {
+                    Some(ref me) => pl.owner.id.id() == me,
+                    None => true,
+                }
+            })
+            .collect()
+    }
+
+    /// Stacks a toast on top of the ones showing, dropping the oldest past
+    /// `max_stacked`, and records it in the history
+    pub fn set_notification(&mut self, mut n: Notification) {
+        let ticks = match n.severity {
+            Severity::Info => self.toast_ticks.info,
+            Severity::Warning => self.toast_ticks.warning,
+            Severity::Error => self.toast_ticks.error,
+        };
+        if let Some(ticks) = ticks {
+            n.remaining_ticks = ticks;
+        }
+        self.notification_history.push_front((Local::now(), n.clone()));
+        self.notification_history.truncate(NOTIFICATION_HISTORY);
+        self.notifications.push_back(n);
+        while self.notifications.len() > self.toast_settings.max_stacked.max(1) {
+            self.notifications.pop_front();
         }
+        self.notification_seq += 1;
+    }
+
+    /// The newest toast, which Enter/`d` and retry bookkeeping act on
+    pub fn latest_notification_mut(&mut self) -> Option<&mut Notification> {
+        self.notifications.back_mut()
     }
 
-    pub fn set_notification(&mut self, n: Notification) {
-        self.notification = Some(n);
+    /// Takes the newest error toast off the stack
+    pub fn take_error_notification(&mut self) -> Option<Notification> {
+        let i = self.notifications.iter().rposition(Notification::is_error)?;
+        self.notifications.remove(i)
     }
 
     pub fn tick_notification(&mut self) {
-        if let Some(ref mut n) = self.notification {
+        self.notifications.retain_mut(|n| {
             if n.remaining_ticks > 0 {
                 n.remaining_ticks -= 1;
+                true
             } else {
-                self.notification = None;
+                false
+            }
+        });
+    }
+
+    /// Selects the currently playing track in the active list, if it's there.
+    /// Returns whether it was found.
+    pub fn select_playing(&mut self) -> bool {
+        use rspotify::model::PlayableItem;
+        use rspotify::prelude::Id;
+        let Some(playing) = self.current_track.id.as_deref() else { return false };
+        let is_playing = |t: &FullTrack| t.id.as_ref().is_some_and(|id| id.uri() == playing);
+
+        let (found, selected) = match self.active_screen {
+            ActiveScreen::Search => (
+                self.search.tracks.iter().position(is_playing),
+                &mut self.search.selected_track,
+            ),
+            ActiveScreen::Library if self.library.tab == LibraryTab::Albums => (
+                self.library.open_album().and_then(|a| {
+                    a.album.tracks.items.iter()
+                        .position(|t| t.id.as_ref().is_some_and(|id| id.uri() == playing))
+                }),
+                &mut self.library.selected_album_track,
+            ),
+            ActiveScreen::Library if self.library.tab == LibraryTab::Artists => (
+                self.library.artist_top_tracks.as_ref().and_then(|t| t.iter().position(is_playing)),
+                &mut self.library.selected_artist_track,
+            ),
+            ActiveScreen::Library => (
+                self.library.liked_songs.iter().position(|s| is_playing(&s.track)),
+                &mut self.library.selected,
+            ),
+            // The loaded tracks may be an earlier playlist's while the list is shown
+            ActiveScreen::Playlists if !self.playlists.viewing_tracks => (None, &mut self.playlists.selected_track),
+            ActiveScreen::Playlists => (
+                self.playlists.playlist_tracks.iter().position(|item| {
+                    matches!(item.track, Some(PlayableItem::Track(ref t)) if is_playing(t))
+                }),
+                &mut self.playlists.selected_track,
+            ),
+            ActiveScreen::Queue => {
+                let i = self.queue.tracks.iter().position(is_playing);
+                return i.is_some_and(|i| self.queue.select_track(i));
+            }
+            ActiveScreen::Vibes => (
+                self.vibes.recommendations.iter().position(is_playing),
+                &mut self.vibes.selected_track,
+            ),
+            ActiveScreen::Stats if self.stats.view == StatsView::Tracks => (
+                self.stats.top_tracks.iter().position(is_playing),
+                &mut self.stats.selected,
+            ),
+            ActiveScreen::Stats => (None, &mut self.stats.selected),
+            ActiveScreen::Browse => (None, &mut self.browse.selected_playlist),
+        };
+        match found {
+            Some(i) => {
+                *selected = i;
+                match self.active_screen {
+                    ActiveScreen::Search => self.search.tab = SearchTab::Tracks,
+                    ActiveScreen::Library if self.library.tab == LibraryTab::Albums => self.library.viewing_album = true,
+                    _ => {}
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// How far from now each queued track should start, in ms: what's left of
+    /// the current track plus everything queued ahead of it
+    pub fn queue_start_offsets(&self) -> Vec<u64> {
+        let track = &self.current_track;
+        start_offsets(
+            track.duration_ms.saturating_sub(track.progress_ms) as u64,
+            self.queue.tracks.iter().map(|t| t.duration.num_milliseconds().max(0) as u64),
+        )
+    }
+
+    /// Keeps `paused_at` in step with the play state, wherever the change came from
+    pub fn note_play_state(&mut self) {
+        if self.current_track.is_playing {
+            self.paused_at = None;
+        } else {
+            self.paused_at.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Advances local progress by one UI tick. Returns true on the tick the
+    /// track reaches its end while playing, so the caller can poll right away
+    /// instead of waiting for the next slow poll.
+    pub fn tick_progress(&mut self, dt_ms: u32) -> bool {
+        let track = &mut self.current_track;
+        if !track.is_playing {
+            return false;
+        }
+        track.progress_ms = (track.progress_ms + dt_ms).min(track.duration_ms);
+        self.listened_ms = self.listened_ms.saturating_add(dt_ms);
+        if track.duration_ms > 0 && track.progress_ms >= track.duration_ms && !self.track_ended {
+            self.track_ended = true;
+            return true;
+        }
+        false
+    }
+
+    /// Records the outgoing track when playback moves on (or the same track
+    /// starts over) and resets the per-track listening counters. Returns the
+    /// new entry, if anything was playing.
+    pub fn push_history(&mut self, track: &CurrentTrack) -> Option<&HistoryEntry> {
+        // A poll landing in the last couple of seconds also means it finished
+        let completed = self.track_ended || track.progress_ms + 2_000 >= track.duration_ms;
+        let scrobble = counts_as_scrobble(track.duration_ms, self.listened_ms);
+        let listened = chrono::Duration::milliseconds(self.listened_ms as i64);
+        self.listened_ms = 0;
+        self.track_ended = false;
+        let id = track.id.clone()?;
+        let now = Utc::now();
+        self.history.push_front(HistoryEntry {
+            id,
+            name: track.name.clone(),
+            artists: track.artists.clone(),
+            album: track.album.clone(),
+            duration_ms: track.duration_ms,
+            started_at: now - listened,
+            played_at: now,
+            completed: completed && track.duration_ms > 0,
+            scrobble,
+        });
+        self.history.truncate(HISTORY_LIMIT);
+        self.history.front()
+    }
+
+    /// Adds entries from Spotify's recently played that aren't in the
+    /// history yet, keeping it newest first
+    pub fn merge_history(&mut self, entries: impl IntoIterator<Item = HistoryEntry>) {
+        for entry in entries {
+            let known = self.history.iter().any(|h| {
+                h.id == entry.id && (h.played_at - entry.played_at).num_seconds().abs() <= same_listen_slack(h.duration_ms)
+            });
+            if !known {
+                self.history.push_back(entry);
+            }
+        }
+        self.history.make_contiguous().sort_by_key(|h| std::cmp::Reverse(h.played_at));
+        self.history.truncate(HISTORY_LIMIT);
+    }
+
+    pub fn is_liked(&self, track: &FullTrack) -> bool {
+        use rspotify::prelude::Id;
+        track.id.as_ref().is_some_and(|id| self.liked.contains(&id.uri()))
+    }
+
+    /// Track URIs marked in visual select, oldest first
+    pub fn marked_tracks(&self) -> Vec<String> {
+        self.marked_with_prefix("spotify:track:")
+    }
+
+    /// Playlist URIs marked in visual select, oldest first
+    pub fn marked_playlists(&self) -> Vec<String> {
+        self.marked_with_prefix("spotify:playlist:")
+    }
+
+    fn marked_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let marked = self.visual.as_ref().map_or(&[][..], |v| &v.marked);
+        marked.iter().filter(|uri| uri.starts_with(prefix)).cloned().collect()
+    }
+
+    pub fn is_marked(&self, id: Option<&rspotify::model::TrackId<'_>>) -> bool {
+        use rspotify::prelude::Id;
+        let Some(visual) = self.visual.as_ref() else { return false };
+        id.is_some_and(|id| visual.contains(&id.uri()))
+    }
+
+    /// Records whether `uri` is in Liked Songs, keeping the player bar's heart in step
+    pub fn note_liked(&mut self, uri: &str, liked: bool) {
+        self.liked.set(uri, liked);
+        if self.current_track.id.as_deref() == Some(uri) {
+            self.current_track.is_liked = liked;
+        }
+    }
+
+    /// A poll returned playback: the device is alive. Playback starting again
+    /// anywhere drops a pending resume point.
+    pub fn note_playback_seen(&mut self, progress_ms: u32, is_playing: bool) {
+        self.device_watch = DeviceWatch { misses: 0, last_progress_ms: progress_ms };
+        if is_playing {
+            self.resume_point = None;
+        }
+    }
+
+    /// A poll came back without playback (or failed). `devices` is the current
+    /// device list when it could be fetched. Returns the resume point when this
+    /// looks like the playing device vanished mid-track; playback is then shown
+    /// as paused at the last known position.
+    pub fn note_playback_missing(&mut self, devices: Option<&[String]>) -> Option<&ResumePoint> {
+        let track = &self.current_track;
+        let (Some(uri), Some(device)) = (track.id.clone(), track.device_id.clone()) else { return None };
+        if !track.is_playing {
+            return None;
+        }
+        self.device_watch.misses = self.device_watch.misses.saturating_add(1);
+        let gone = match devices {
+            Some(ids) => !ids.contains(&device),
+            None => self.device_watch.misses >= DEVICE_LOST_MISSES,
+        };
+        if !gone {
+            return None;
+        }
+        let position_ms = self.device_watch.last_progress_ms;
+        self.current_track.is_playing = false;
+        self.current_track.progress_ms = position_ms;
+        self.note_play_state();
+        self.resume_point = Some(ResumePoint {
+            uri,
+            context_uri: self.current_track.context_uri.clone(),
+            position_ms,
+            device_name: self.current_track.device_name.clone().unwrap_or_else(|| "Your device".to_string()),
+        });
+        self.resume_point.as_ref()
+    }
+
+    /// Mutes, or restores the volume from before muting; returns the new volume
+    pub fn toggle_mute(&mut self) -> u8 {
+        match self.muted_volume.take() {
+            Some(before) => self.volume = before,
+            None => {
+                self.muted_volume = Some(self.volume);
+                self.volume = 0;
+            }
+        }
+        self.volume
+    }
+
+    /// Takes the volume the device reports; turning it up elsewhere ends a mute
+    pub fn sync_device_volume(&mut self, vol: u8) {
+        if vol > 0 {
+            self.muted_volume = None;
+        }
+        self.volume = vol;
+    }
+
+    /// Re-applies volume leveling with a new delta on top of the user's base
+    /// volume (current volume minus the previously applied offset).
+    /// Returns the resulting device volume.
+    pub fn apply_replay_gain(&mut self, delta: i8) -> u8 {
+        let base = self.volume as i16 - self.replay_gain_offset as i16;
+        let new_vol = (base + delta as i16).clamp(0, 100);
+        self.replay_gain_offset = (new_vol - base) as i8;
+        self.volume = new_vol as u8;
+        self.volume
+    }
+
+    /// How many rows a typed row number can pick from: the popup's list when
+    /// one is open, else the screen's (as far as the filter leaves it)
+    pub fn list_len(&self) -> usize {
+        if self.picker.is_some() {
+            return self.editable_playlists().len();
+        }
+        if let Some(dedupe) = self.dedupe.as_ref() {
+            return dedupe.overlaps.len();
+        }
+        if let Some(archived) = self.archived.as_ref() {
+            return archived.entries.len();
+        }
+        if let Some(view) = self.devices.as_ref() {
+            return view.devices.len();
+        }
+        if self.log_view.is_some() {
+            // Scrolled by line, any number goes
+            return usize::MAX;
+        }
+        if let Some(mood) = self.mood.as_ref() {
+            return mood.tracks.len();
+        }
+        if self.inbox.open {
+            return self.inbox.items.len();
+        }
+        if self.search.show_history {
+            return self.search.history.len();
+        }
+        if self.show_jobs {
+            return self.jobs.running().len();
+        }
+        if let Some(rows) = self.filtered_rows() {
+            return rows.len();
+        }
+        match self.active_screen {
+            ActiveScreen::Search => match self.search.tab {
+                SearchTab::Playlists => self.search.playlists.len(),
+                SearchTab::Albums => self.search.album_order().len(),
+                SearchTab::Tracks => self.search.tracks.len(),
+            },
+            ActiveScreen::Library => {
+                let library = &self.library;
+                match library.tab {
+                    LibraryTab::Albums if library.viewing_album => library.open_album().map_or(0, |a| a.album.tracks.items.len()),
+                    LibraryTab::Albums => library.saved_albums.len(),
+                    LibraryTab::Artists => library.artist_top_tracks.as_ref().map_or(library.followed_artists.len(), Vec::len),
+                    LibraryTab::Liked => library.liked_songs.len(),
+                }
             }
+            ActiveScreen::Playlists if self.playlists.viewing_tracks => self.playlists.playlist_tracks.len(),
+            ActiveScreen::Playlists => self.playlists.playlists.len(),
+            // Numbered by track; group headers aren't counted
+            ActiveScreen::Queue => self.queue.tracks.len(),
+            ActiveScreen::Vibes if !self.vibes.recommendations.is_empty() => self.vibes.recommendations.len(),
+            ActiveScreen::Vibes => self.vibes.moods.len(),
+            ActiveScreen::Stats => self.stats.row_count(),
+            ActiveScreen::Browse if self.browse.viewing_playlists => self.browse.playlists.len(),
+            ActiveScreen::Browse => self.browse.categories.len(),
+        }
+    }
+
+    /// Adds a digit to the row number being typed. A first digit no row of
+    /// `list_len` starts with (`4` on a three-row list) can only be a screen
+    /// switch, so that's returned at once rather than after
+    /// `ROW_JUMP_TIMEOUT_MS`.
+    pub fn push_row_digit(&mut self, digit: u8) -> Option<u8> {
+        let rows = self.list_len();
+        let jump = self.row_jump.get_or_insert_with(RowJump::default);
+        // Five digits is already past any list Spotify hands out
+        if jump.digits.len() < 5 {
+            jump.digits.push(char::from(b'0' + digit));
+        }
+        jump.idle_ms = 0;
+        // Rows starting with a digit are all at least that digit
+        if jump.digits.len() == 1 && jump.row().is_none_or(|n| n > rows) {
+            self.row_jump = None;
+            return (1..=9).contains(&digit).then_some(digit);
+        }
+        None
+    }
+
+    /// Ages a typed row number. A lone digit left alone for
+    /// `ROW_JUMP_TIMEOUT_MS` is returned as the screen to switch to; longer
+    /// numbers that never got their Enter are dropped.
+    pub fn tick_row_jump(&mut self, dt_ms: u64) -> Option<u8> {
+        let jump = self.row_jump.as_mut()?;
+        jump.idle_ms += dt_ms;
+        if jump.idle_ms < ROW_JUMP_TIMEOUT_MS {
+            return None;
+        }
+        let jump = self.row_jump.take()?;
+        match jump.digits.as_bytes() {
+            [d @ b'1'..=b'9'] => Some(d - b'0'),
+            _ => None,
+        }
+    }
+
+    /// Startup load news for the panel, while it's up
+    pub fn note_startup(&mut self, load: StartupLoad, status: LoadStatus, detail: impl Into<String>) {
+        if let Some(startup) = self.startup.as_mut() {
+            startup.set(load, status, detail);
+        }
+    }
+
+    /// Takes the startup panel down a moment after the last load finished
+    pub fn tick_startup(&mut self, dt_ms: u64) {
+        let Some(startup) = self.startup.as_mut().filter(|s| s.is_done()) else { return };
+        startup.done_ms += dt_ms;
+        if startup.done_ms >= STARTUP_LINGER_MS {
+            self.startup = None;
+        }
+    }
+
+    /// Advances the beat clock by one UI tick; sets `beat` when one lands
+    pub fn tick_tempo(&mut self, dt_ms: u64) {
+        self.beat = false;
+        let Some(bpm) = self.tempo_bpm else { return };
+        if !self.current_track.is_playing {
+            return;
+        }
+        self.beat_phase += dt_ms as f32 * bpm / 60_000.0;
+        if self.beat_phase >= 1.0 {
+            self.beat_phase = self.beat_phase.fract();
+            self.beat = true;
+        }
+    }
+
+    /// Tallest a beat kicks the bars: 6 for the calmest tracks up to 12 for
+    /// the most energetic, and 12 when the energy isn't known
+    pub fn kick_height(&self) -> u8 {
+        match self.energy {
+            Some(energy) => 6 + (energy.clamp(0.0, 1.0) * 6.0).round() as u8,
+            None => 12,
         }
     }
 
@@ -205,7 +2235,26 @@ impl AppState {
         use rand::Rng;
         let mut rng = rand::thread_rng();
         self.eq_tick += 1;
-        if self.current_track.is_playing {
+        let synced = self.tempo_bpm.is_some();
+        if (synced && self.beat) || (!synced && self.eq_tick.is_multiple_of(3)) {
+            self.anim_frame += 1;
+        }
+        if let Some(levels) = self.eq_capture {
+            // Jump up to what's heard, fall back a step a tick
+            for (bar, level) in self.eq_bars.iter_mut().zip(levels) {
+                *bar = level.max(bar.saturating_sub(1)).max(1);
+            }
+        } else if self.current_track.is_playing && synced {
+            // Kick on the beat, fall off in between
+            let top = self.kick_height();
+            for bar in self.eq_bars.iter_mut() {
+                *bar = if self.beat {
+                    rng.gen_range(top.saturating_sub(5).max(2)..=top)
+                } else {
+                    bar.saturating_sub(rng.gen_range(0..=2)).max(1)
+                };
+            }
+        } else if self.current_track.is_playing {
             for bar in self.eq_bars.iter_mut() {
                 let delta: i8 = rng.gen_range(-3..=3);
                 *bar = (*bar as i8 + delta).clamp(1, 12) as u8;
@@ -221,25 +2270,42 @@ impl AppState {
 
     pub fn tick_ticker(&mut self) {
         self.ticker_tick += 1;
-        if self.ticker_tick % 5 == 0 {
+        // 5 ticks per step by default, scaled so 120 BPM keeps that pace
+        let every = match self.tempo_bpm {
+            Some(bpm) => ((600.0 / bpm).round() as u64).max(1),
+            None => 5,
+        };
+        if self.ticker_tick % every == 0 {
             let len = self.current_track.name.len().max(1);
             self.ticker_offset = (self.ticker_offset + 1) % len;
         }
     }
 
+    /// Fades the dynamic accent one step towards its target. With no target
+    /// (art unavailable) it fades back to the stock primary, then clears.
+    pub fn tick_accent(&mut self) {
+        let stock = match self.theme.primary {
+            ratatui::style::Color::Rgb(r, g, b) => (r, g, b),
+            _ => return,
+        };
+        let current = match (self.accent_tint, self.accent_target) {
+            (None, None) => return,
+            (Some(c), _) => c,
+            (None, Some(_)) => stock,
+        };
+        let target = self.accent_target.unwrap_or(stock);
+        let mut next = blend(current, target, 0.15);
+        if next == current {
+            next = target; // rounding stalled — snap the last step
+        }
+        self.accent_tint = if self.accent_target.is_none() && next == stock {
+            None
+        } else {
+            Some(next)
+        };
+    }
+
     pub fn get_display_title(&self, max_width: usize) -> String {
-        let title = &self.current_track.name;
-        if title.len() <= max_width {
-            return title.clone();
-        }
-        let padded = format!("{title}   ");
-        let chars: Vec<char> = padded.chars().collect();
-        let offset = self.ticker_offset % chars.len();
-        let visible: String = chars[offset..]
-            .iter()
-            .chain(chars[..offset].iter())
-            .take(max_width)
-            .collect();
-        visible
+        crate::ui::text::marquee(&self.current_track.name, self.ticker_offset, max_width)
     }
 }
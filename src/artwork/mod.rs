@@ -0,0 +1,87 @@
+// The histogram code is only reachable through the image decoder
+#![cfg_attr(not(feature = "album-art"), allow(dead_code))]
+
+use anyhow::Result;
+use std::collections::HashMap;
+
+pub type Rgb = (u8, u8, u8);
+
+/// Running channel sums plus pixel count for one histogram bucket
+type Bucket = (u32, u32, u32, u32);
+
+/// Side of a `Cover` in pixels; the focus-mode tile is at most 24 cells
+/// across and 24 half-block pixels down
+pub const COVER_PX: u32 = 24;
+
+/// Album art scaled to `COVER_PX` square, row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cover(pub Vec<Rgb>);
+
+impl Cover {
+    /// The pixel under (`x`, `y`) when the cover is stretched to `width` × `height`
+    pub fn sample(&self, x: u32, y: u32, width: u32, height: u32) -> Rgb {
+        let scale = |v: u32, len: u32| (v * COVER_PX / len.max(1)).min(COVER_PX - 1);
+        let at = scale(y, height) * COVER_PX + scale(x, width);
+        self.0.get(at as usize).copied().unwrap_or_default()
+    }
+}
+
+/// Downloads album art: its dominant, reasonably saturated color and the
+/// cover scaled down for drawing. Pass the smallest image Spotify offers
+/// (64px) — that's plenty for both.
+#[cfg(feature = "album-art")]
+pub async fn fetch_art(url: &str) -> Result<(Option<Rgb>, Option<Cover>)> {
+    let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    let img = image::load_from_memory(&bytes)?;
+    let accent = dominant_color(img.thumbnail(48, 48).to_rgb8().pixels().map(|p| p.0));
+    let cover = img.resize_exact(COVER_PX, COVER_PX, image::imageops::FilterType::Triangle).to_rgb8();
+    Ok((accent, Some(Cover(cover.pixels().map(|&image::Rgb([r, g, b])| (r, g, b)).collect()))))
+}
+
+/// Built without the image decoder: no accent, stock palette, no cover
+#[cfg(not(feature = "album-art"))]
+pub async fn fetch_art(_url: &str) -> Result<(Option<Rgb>, Option<Cover>)> {
+    Ok((None, None))
+}
+
+/// Buckets pixels into a coarse 4-bit-per-channel histogram, ignoring
+/// near-grey/black/white pixels, and averages the most populated bucket.
+/// The result is lifted so it stays readable on the dark background.
+pub fn dominant_color(pixels: impl Iterator<Item = [u8; 3]>) -> Option<Rgb> {
+    let mut buckets: HashMap<Rgb, Bucket> = HashMap::new();
+    for [r, g, b] in pixels {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        if max < 40 || min > 220 || max - min < 30 {
+            continue;
+        }
+        let entry = buckets.entry((r >> 4, g >> 4, b >> 4)).or_insert((0, 0, 0, 0));
+        entry.0 += r as u32;
+        entry.1 += g as u32;
+        entry.2 += b as u32;
+        entry.3 += 1;
+    }
+    let (r, g, b, n) = buckets.into_values().max_by_key(|(_, _, _, n)| *n)?;
+    Some(lift((
+        (r / n) as u8,
+        (g / n) as u8,
+        (b / n) as u8,
+    )))
+}
+
+/// Scales a color up so its brightest channel is at least 180
+fn lift((r, g, b): Rgb) -> Rgb {
+    let max = r.max(g).max(b).max(1) as f32;
+    if max >= 180.0 {
+        return (r, g, b);
+    }
+    let k = 180.0 / max;
+    let scale = |c: u8| (c as f32 * k).round().min(255.0) as u8;
+    (scale(r), scale(g), scale(b))
+}
+
+/// Moves `from` a fraction `t` (0..=1) of the way towards `to`
+pub fn blend(from: Rgb, to: Rgb, t: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+}
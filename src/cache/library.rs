@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rspotify::model::{PlaylistItem, SavedTrack, SimplifiedPlaylist};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::Cache;
+
+const PLAYLISTS: &str = "vibes:library:playlists";
+const LIKED_SONGS: &str = "vibes:library:liked_songs";
+/// How long a list stays in Redis past its TTL, to be shown while Spotify
+/// can't be reached
+const OFFLINE_COPY_SECS: u64 = 30 * 24 * 3600;
+
+/// Playlist contents only change with the snapshot, so each snapshot gets its own key
+fn tracks_key(playlist_id: &str, snapshot_id: &str) -> String {
+    format!("vibes:playlist_tracks:{playlist_id}:{snapshot_id}")
+}
+
+/// A list as Spotify returned it at `fetched_at`, along with any edits made
+/// in Vibes since
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cached<T> {
+    pub fetched_at: DateTime<Utc>,
+    pub items: Vec<T>,
+}
+
+impl<T> Cached<T> {
+    /// Seconds it may still be served for under `ttl_secs`; `None` once
+    /// it's due for a refetch, or when `ttl_secs` is 0 (not cached)
+    pub fn remaining(&self, ttl_secs: u64, now: DateTime<Utc>) -> Option<u64> {
+        let age = (now - self.fetched_at).num_seconds().max(0) as u64;
+        ttl_secs.checked_sub(age).filter(|&left| left > 0)
+    }
+}
+
+impl Cache {
+    /// `key`'s list if it's younger than `ttl_secs`. Checked here as well as
+    /// by Redis, so a shorter TTL in the config applies to what's stored
+    async fn get_list<T: DeserializeOwned>(&self, key: &str, ttl_secs: u64) -> Option<Cached<T>> {
+        let list: Cached<T> = self.get_offline_copy(key).await?;
+        list.remaining(ttl_secs, Utc::now())?;
+        Some(list)
+    }
+
+    /// `key`'s list however old, for when Spotify can't be asked
+    async fn get_offline_copy<T: DeserializeOwned>(&self, key: &str) -> Option<Cached<T>> {
+        let raw = self.get(key).await.ok()??;
+        serde_json::from_str(&raw).ok()
+    }
+
+    /// Stores `list` if it's younger than `ttl_secs`, and keeps it for
+    /// `OFFLINE_COPY_SECS` after that; writing it back later never extends
+    /// either
+    async fn set_list<T: Serialize>(&self, key: &str, list: &Cached<T>, ttl_secs: u64) -> Result<()> {
+        let now = Utc::now();
+        let Some(left) = list.remaining(ttl_secs, now).and(list.remaining(ttl_secs.saturating_add(OFFLINE_COPY_SECS), now)) else {
+            return Ok(());
+        };
+        self.set(key, &serde_json::to_string(list)?, Some(left)).await
+    }
+
+    pub async fn get_playlists(&self, ttl_secs: u64) -> Option<Cached<SimplifiedPlaylist>> {
+        self.get_list(PLAYLISTS, ttl_secs).await
+    }
+
+    pub async fn set_playlists(&self, list: &Cached<SimplifiedPlaylist>, ttl_secs: u64) -> Result<()> {
+        self.set_list(PLAYLISTS, list, ttl_secs).await
+    }
+
+    pub async fn offline_playlists(&self) -> Option<Cached<SimplifiedPlaylist>> {
+        self.get_offline_copy(PLAYLISTS).await
+    }
+
+    pub async fn get_liked_songs(&self, ttl_secs: u64) -> Option<Cached<SavedTrack>> {
+        self.get_list(LIKED_SONGS, ttl_secs).await
+    }
+
+    pub async fn set_liked_songs(&self, list: &Cached<SavedTrack>, ttl_secs: u64) -> Result<()> {
+        self.set_list(LIKED_SONGS, list, ttl_secs).await
+    }
+
+    pub async fn offline_liked_songs(&self) -> Option<Cached<SavedTrack>> {
+        self.get_offline_copy(LIKED_SONGS).await
+    }
+
+    pub async fn get_playlist_tracks(&self, playlist_id: &str, snapshot_id: &str, ttl_secs: u64) -> Option<Cached<PlaylistItem>> {
+        self.get_list(&tracks_key(playlist_id, snapshot_id), ttl_secs).await
+    }
+
+    pub async fn set_playlist_tracks(
+        &self,
+        playlist_id: &str,
+        snapshot_id: &str,
+        list: &Cached<PlaylistItem>,
+        ttl_secs: u64,
+    ) -> Result<()> {
+        self.set_list(&tracks_key(playlist_id, snapshot_id), list, ttl_secs).await
+    }
+
+    pub async fn offline_playlist_tracks(&self, playlist_id: &str, snapshot_id: &str) -> Option<Cached<PlaylistItem>> {
+        self.get_offline_copy(&tracks_key(playlist_id, snapshot_id)).await
+    }
+
+    /// Drops the cached playlists and Liked Songs, for a refresh
+    pub async fn forget_library(&self) -> Result<()> {
+        self.delete(PLAYLISTS).await?;
+        self.delete(LIKED_SONGS).await
+    }
+}
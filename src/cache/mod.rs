@@ -1,20 +1,39 @@
+pub mod library;
+pub mod snapshots;
+
 use anyhow::Result;
 use redis::AsyncCommands;
+use std::borrow::Cow;
 
 pub struct Cache {
     client: redis::Client,
+    /// The profile whose keys these are; `None` for the default one
+    profile: Option<String>,
 }
 
 impl Cache {
     pub fn new(redis_url: &str) -> Result<Self> {
         let client = redis::Client::open(redis_url)?;
-        Ok(Cache { client })
+        Ok(Cache { client, profile: None })
+    }
+
+    /// Keeps this profile's token and library apart from the other accounts'
+    pub fn for_profile(self, profile: Option<&str>) -> Self {
+        Cache { profile: profile.map(str::to_string), ..self }
+    }
+
+    /// `vibes:x` as stored: `vibes:profile:<name>:x` under a profile
+    pub fn key<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        match (&self.profile, key.strip_prefix("vibes:")) {
+            (Some(profile), Some(rest)) => Cow::Owned(format!("vibes:profile:{profile}:{rest}")),
+            _ => Cow::Borrowed(key),
+        }
     }
 
     pub async fn get(&self, key: &str) -> Result<Option<String>> {
         match self.client.get_multiplexed_async_connection().await {
             Ok(mut conn) => {
-                let val: Option<String> = conn.get(key).await.unwrap_or(None);
+                let val: Option<String> = conn.get(self.key(key).as_ref()).await.unwrap_or(None);
                 Ok(val)
             }
             Err(_) => Ok(None),
@@ -24,18 +43,17 @@ impl Cache {
     pub async fn set(&self, key: &str, value: &str, ttl_secs: Option<u64>) -> Result<()> {
         if let Ok(mut conn) = self.client.get_multiplexed_async_connection().await {
             if let Some(ttl) = ttl_secs {
-                let _: std::result::Result<(), _> = conn.set_ex(key, value, ttl).await;
+                let _: std::result::Result<(), _> = conn.set_ex(self.key(key).as_ref(), value, ttl).await;
             } else {
-                let _: std::result::Result<(), _> = conn.set(key, value).await;
+                let _: std::result::Result<(), _> = conn.set(self.key(key).as_ref(), value).await;
             }
         }
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn delete(&self, key: &str) -> Result<()> {
         if let Ok(mut conn) = self.client.get_multiplexed_async_connection().await {
-            let _: std::result::Result<(), _> = conn.del(key).await;
+            let _: std::result::Result<(), _> = conn.del(self.key(key).as_ref()).await;
         }
         Ok(())
     }
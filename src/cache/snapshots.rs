@@ -0,0 +1,101 @@
+use anyhow::Result;
+use rspotify::{
+    model::{FullTrack, PlayableItem, PlaylistItem},
+    prelude::Id,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use super::Cache;
+
+/// Playlist contents as of the last time the user opened it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistSnapshot {
+    pub snapshot_id: String,
+    /// (track or episode URI, "Title — Artist") in playlist order
+    pub tracks: Vec<(String, String)>,
+}
+
+impl PlaylistSnapshot {
+    /// Every item, episodes and local files included. Local files have no
+    /// id, so they get the `spotify:local:` URI Spotify's own apps use.
+    pub fn from_items(snapshot_id: &str, items: &[PlaylistItem]) -> Self {
+        PlaylistSnapshot {
+            snapshot_id: snapshot_id.to_string(),
+            tracks: items
+                .iter()
+                .filter_map(|item| match item.track {
+                    Some(PlayableItem::Track(ref t)) => {
+                        let artists: Vec<&str> = t.artists.iter().map(|a| a.name.as_str()).collect();
+                        let uri = t.id.as_ref().map_or_else(|| local_uri(t), |tid| tid.uri());
+                        Some((uri, format!("{} — {}", t.name, artists.join(", "))))
+                    }
+                    Some(PlayableItem::Episode(ref e)) => Some((e.id.uri(), format!("{} — {}", e.name, e.show.name))),
+                    None => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `spotify:local:artist:album:title:seconds`, each part form-encoded
+fn local_uri(track: &FullTrack) -> String {
+    let encode = |part: &str| urlencoding::encode(part).replace("%20", "+");
+    let artist = track.artists.first().map_or("", |a| a.name.as_str());
+    format!(
+        "spotify:local:{}:{}:{}:{}",
+        encode(artist),
+        encode(&track.album.name),
+        encode(&track.name),
+        track.duration.num_seconds()
+    )
+}
+
+/// Whether `uri` is a local file, which Spotify's API can't add to a playlist
+pub fn is_local(uri: &str) -> bool {
+    uri.starts_with("spotify:local:")
+}
+
+/// Tracks added/removed between two snapshots, as display labels
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlaylistDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl PlaylistDiff {
+    pub fn between(old: &PlaylistSnapshot, new: &PlaylistSnapshot) -> Self {
+        let old_uris: HashSet<&str> = old.tracks.iter().map(|(uri, _)| uri.as_str()).collect();
+        let new_uris: HashSet<&str> = new.tracks.iter().map(|(uri, _)| uri.as_str()).collect();
+        PlaylistDiff {
+            added: new.tracks.iter()
+                .filter(|(uri, _)| !old_uris.contains(uri.as_str()))
+                .map(|(_, label)| label.clone())
+                .collect(),
+            removed: old.tracks.iter()
+                .filter(|(uri, _)| !new_uris.contains(uri.as_str()))
+                .map(|(_, label)| label.clone())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn key(playlist_id: &str) -> String {
+    format!("vibes:playlist_snapshot:{playlist_id}")
+}
+
+impl Cache {
+    pub async fn get_playlist_snapshot(&self, playlist_id: &str) -> Option<PlaylistSnapshot> {
+        let raw = self.get(&key(playlist_id)).await.ok()??;
+        serde_json::from_str(&raw).ok()
+    }
+
+    pub async fn set_playlist_snapshot(&self, playlist_id: &str, snapshot: &PlaylistSnapshot) -> Result<()> {
+        let raw = serde_json::to_string(snapshot)?;
+        self.set(&key(playlist_id), &raw, None).await
+    }
+}
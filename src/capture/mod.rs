@@ -0,0 +1,183 @@
+// Only the spectrum-to-bars mapping is reachable without cpal
+#![cfg_attr(not(feature = "audio-capture"), allow(dead_code))]
+
+use anyhow::Result;
+use tokio::sync::watch;
+
+/// One level per EQ bar, as `AppState::eq_bars` has them
+pub const BARS: usize = 24;
+/// Tallest a bar gets
+const MAX_LEVEL: u8 = 12;
+/// The bars span this range, spaced logarithmically like the ear hears it
+const LOW_HZ: f32 = 40.0;
+const HIGH_HZ: f32 = 16_000.0;
+/// Band peaks at or below this are a flat bar; `CEILING_DB` fills it
+const FLOOR_DB: f32 = -60.0;
+const CEILING_DB: f32 = -10.0;
+
+/// Bar levels (1–12) for one magnitude spectrum: `magnitudes[k]` is the
+/// amplitude at bin `k` of an FFT of `sample_rate` audio, 1.0 being full
+/// scale. Each bar shows the loudest bin in its band.
+pub fn bar_levels(magnitudes: &[f32], sample_rate: u32) -> [u8; BARS] {
+    let mut levels = [1; BARS];
+    if magnitudes.len() < 2 {
+        return levels;
+    }
+    let bin_hz = sample_rate as f32 / (2 * (magnitudes.len() - 1)) as f32;
+    let edge = |i: usize| LOW_HZ * (HIGH_HZ / LOW_HZ).powf(i as f32 / BARS as f32);
+    for (i, level) in levels.iter_mut().enumerate() {
+        let lo = (edge(i) / bin_hz) as usize;
+        let hi = ((edge(i + 1) / bin_hz) as usize).max(lo + 1).min(magnitudes.len());
+        let peak = magnitudes.get(lo..hi).unwrap_or_default().iter().copied().fold(0.0, f32::max);
+        let db = 20.0 * peak.max(1e-9).log10();
+        let fill = ((db - FLOOR_DB) / (CEILING_DB - FLOOR_DB)).clamp(0.0, 1.0);
+        *level = 1 + (fill * f32::from(MAX_LEVEL - 1)).round() as u8;
+    }
+    levels
+}
+
+/// Listens to `device` (the default input when `None`) on a thread of its
+/// own and keeps the returned channel at the latest bar levels, about 40
+/// times a second. The thread stops once the receiver is dropped, and drops
+/// the sender if the device goes away.
+#[cfg(feature = "audio-capture")]
+pub fn start(device: Option<&str>) -> Result<watch::Receiver<[u8; BARS]>> {
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+    let device = device.map(str::to_string);
+    std::thread::Builder::new().name("vibes-capture".into()).spawn(move || match listen(device.as_deref()) {
+        Ok(input) => {
+            let (tx, rx) = watch::channel([1; BARS]);
+            let _ = ready_tx.send(Ok(rx));
+            input.analyze(tx);
+        }
+        Err(e) => {
+            let _ = ready_tx.send(Err(e));
+        }
+    })?;
+    ready_rx.recv()?
+}
+
+/// Built without cpal: the visualizer stays simulated
+#[cfg(not(feature = "audio-capture"))]
+pub fn start(_device: Option<&str>) -> Result<watch::Receiver<[u8; BARS]>> {
+    anyhow::bail!("this build has no audio capture support")
+}
+
+#[cfg(feature = "audio-capture")]
+use input::listen;
+
+#[cfg(feature = "audio-capture")]
+mod input {
+    use anyhow::{bail, Context, Result};
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+    use std::{
+        collections::VecDeque,
+        f32::consts::TAU,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc,
+        },
+        time::Duration,
+    };
+    use tokio::sync::watch;
+    use tracing::warn;
+
+    use super::{bar_levels, BARS};
+
+    /// Samples per FFT: ~43 ms at 48 kHz, about 23 Hz per bin
+    const FFT_SIZE: usize = 2048;
+    /// New samples between two analyses
+    const HOP: usize = 1024;
+
+    /// An open input stream, delivering mono blocks
+    pub struct Input {
+        /// Kept for as long as the blocks should keep coming
+        _stream: cpal::Stream,
+        blocks: mpsc::Receiver<Vec<f32>>,
+        sample_rate: u32,
+        failed: Arc<AtomicBool>,
+    }
+
+    pub fn listen(name: Option<&str>) -> Result<Input> {
+        let host = cpal::default_host();
+        let device = match name {
+            Some(name) => host
+                .input_devices()?
+                .find(|d| d.name().is_ok_and(|n| n == name))
+                .with_context(|| format!("No audio input called {name}"))?,
+            None => host.default_input_device().context("No default audio input")?,
+        };
+        let config = device.default_input_config()?;
+        let channels = usize::from(config.channels());
+        let sample_rate = config.sample_rate().0;
+        let (tx, blocks) = mpsc::channel();
+        let failed = Arc::new(AtomicBool::new(false));
+        let on_error = {
+            let failed = failed.clone();
+            move |e: cpal::StreamError| {
+                warn!("Audio capture: {e}");
+                failed.store(true, Ordering::Relaxed);
+            }
+        };
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => device.build_input_stream(&config.into(), mono::<f32>(channels, tx), on_error, None)?,
+            cpal::SampleFormat::I16 => device.build_input_stream(&config.into(), mono::<i16>(channels, tx), on_error, None)?,
+            cpal::SampleFormat::U16 => device.build_input_stream(&config.into(), mono::<u16>(channels, tx), on_error, None)?,
+            format => bail!("Audio input has an unsupported sample format ({format})"),
+        };
+        stream.play()?;
+        Ok(Input { _stream: stream, blocks, sample_rate, failed })
+    }
+
+    /// Averages each frame's channels and passes the block on
+    fn mono<T>(channels: usize, tx: mpsc::Sender<Vec<f32>>) -> impl FnMut(&[T], &cpal::InputCallbackInfo) + Send + 'static
+    where
+        T: cpal::SizedSample,
+        f32: cpal::FromSample<T>,
+    {
+        move |data: &[T], _| {
+            let block = data
+                .chunks(channels.max(1))
+                .map(|frame| frame.iter().map(|&s| <f32 as cpal::FromSample<T>>::from_sample_(s)).sum::<f32>() / frame.len() as f32)
+                .collect();
+            let _ = tx.send(block);
+        }
+    }
+
+    impl Input {
+        /// FFTs the latest `FFT_SIZE` samples every `HOP` new ones until
+        /// `levels` has no receiver or the stream fails
+        pub fn analyze(self, levels: watch::Sender<[u8; BARS]>) {
+            let fft = realfft::RealFftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+            // Hann window; it halves a sine's peak, hence the N/4 below
+            let window: Vec<f32> = (0..FFT_SIZE).map(|i| 0.5 - 0.5 * (TAU * i as f32 / FFT_SIZE as f32).cos()).collect();
+            let (mut input, mut spectrum) = (fft.make_input_vec(), fft.make_output_vec());
+            let mut samples: VecDeque<f32> = VecDeque::with_capacity(FFT_SIZE + HOP);
+            let mut fresh = 0;
+            while !levels.is_closed() && !self.failed.load(Ordering::Relaxed) {
+                let block = match self.blocks.recv_timeout(Duration::from_millis(200)) {
+                    Ok(block) => block,
+                    Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+                fresh += block.len();
+                samples.extend(block);
+                if samples.len() > FFT_SIZE {
+                    samples.drain(..samples.len() - FFT_SIZE);
+                }
+                if samples.len() < FFT_SIZE || fresh < HOP {
+                    continue;
+                }
+                fresh = 0;
+                for ((x, s), w) in input.iter_mut().zip(&samples).zip(&window) {
+                    *x = s * w;
+                }
+                if fft.process(&mut input, &mut spectrum).is_err() {
+                    continue;
+                }
+                let magnitudes: Vec<f32> = spectrum.iter().map(|c| c.norm() / (FFT_SIZE as f32 / 4.0)).collect();
+                levels.send_replace(bar_levels(&magnitudes, self.sample_rate));
+            }
+        }
+    }
+}
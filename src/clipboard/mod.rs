@@ -0,0 +1,102 @@
+use anyhow::Result;
+use base64::Engine;
+use rspotify::{model::FullTrack, prelude::Id};
+use std::io::Write;
+
+use crate::app::state::CurrentTrack;
+
+pub const DEFAULT_TEMPLATE: &str = "{artist} – {title} ({album}, {year}) {link}";
+
+/// The fields a copy template can use: `{artist}`, `{title}`, `{album}`, `{year}`, `{link}`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackInfo {
+    pub artist: String,
+    pub title: String,
+    pub album: String,
+    pub year: String,
+    pub link: String,
+}
+
+impl From<&FullTrack> for TrackInfo {
+    fn from(track: &FullTrack) -> Self {
+        TrackInfo {
+            artist: track.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", "),
+            title: track.name.clone(),
+            album: track.album.name.clone(),
+            year: track.album.release_date.as_deref().and_then(|d| d.get(..4)).unwrap_or_default().to_string(),
+            link: track.id.as_ref().map(|id| web_link(&id.uri())).unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&CurrentTrack> for TrackInfo {
+    fn from(track: &CurrentTrack) -> Self {
+        TrackInfo {
+            artist: track.artists.join(", "),
+            title: track.name.clone(),
+            album: track.album.clone(),
+            year: track.album_year.clone().unwrap_or_default(),
+            link: track.id.as_deref().map(web_link).unwrap_or_default(),
+        }
+    }
+}
+
+/// `spotify:track:abc` → `https://open.spotify.com/track/abc`
+pub fn web_link(uri: &str) -> String {
+    match uri.strip_prefix("spotify:").and_then(|rest| rest.split_once(':')) {
+        Some((kind, id)) => format!("https://open.spotify.com/{kind}/{id}"),
+        None => String::new(),
+    }
+}
+
+/// Fills in `template`, then tidies up separators left behind by empty fields
+/// (e.g. no year gives "(Album)" rather than "(Album, )")
+pub fn format_track(template: &str, info: &TrackInfo) -> String {
+    let mut out = template
+        .replace("{artist}", &info.artist)
+        .replace("{title}", &info.title)
+        .replace("{album}", &info.album)
+        .replace("{year}", &info.year)
+        .replace("{link}", &info.link);
+    for (empty, with) in [(", )", ")"), ("(, ", "("), (" ()", ""), ("()", "")] {
+        out = out.replace(empty, with);
+    }
+    out.trim().to_string()
+}
+
+/// Puts `text` on the system clipboard. Built with `system-clipboard` that
+/// goes straight to the OS; without it, or with no clipboard to reach (over
+/// SSH), through the terminal with OSC 52
+pub fn copy(text: &str) -> Result<()> {
+    #[cfg(feature = "system-clipboard")]
+    match system_copy(text) {
+        Ok(()) => return Ok(()),
+        Err(e) => tracing::debug!("System clipboard unavailable ({e}), copying with OSC 52"),
+    }
+    osc52(text)
+}
+
+#[cfg(feature = "system-clipboard")]
+fn system_copy(text: &str) -> Result<()> {
+    use anyhow::Context;
+    use std::cell::RefCell;
+    thread_local! {
+        // Kept for the whole run: on X11 the copied text lives only as long
+        // as the handle that set it
+        static CLIPBOARD: RefCell<Option<arboard::Clipboard>> = RefCell::new(arboard::Clipboard::new().ok());
+    }
+    CLIPBOARD.with_borrow_mut(|clipboard| {
+        clipboard.as_mut().context("no system clipboard")?.set_text(text)?;
+        Ok(())
+    })
+}
+
+/// The OSC 52 escape sequence, which most terminals (and tmux with
+/// `set-clipboard on`) honour — over SSH too
+fn osc52(text: &str) -> Result<()> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    let mut out = std::io::stdout();
+    write!(out, "\x1b]52;c;{encoded}\x07")?;
+    out.flush()?;
+    Ok(())
+}
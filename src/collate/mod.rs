@@ -0,0 +1,128 @@
+use std::{cmp::Ordering, sync::OnceLock};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Leading articles dropped from sort keys. English ones always apply since
+/// most of the catalog is English; the locale adds its own.
+const EN: &[&str] = &["the", "a", "an"];
+const FR: &[&str] = &["le", "la", "les", "l'"];
+const DE: &[&str] = &["der", "die", "das"];
+const ES: &[&str] = &["el", "la", "los", "las"];
+const IT: &[&str] = &["il", "lo", "la", "gli", "le", "l'"];
+const PT: &[&str] = &["o", "os", "as"];
+const NL: &[&str] = &["de", "het", "een"];
+
+/// The locale for `category` (e.g. `LC_MESSAGES`) from `LC_ALL`, `category`
+/// or `LANG`, first one set, without its encoding: `de_DE` for
+/// `de_DE.UTF-8`. `None` for unset, `C` and `POSIX`.
+pub fn env_locale(category: &str) -> Option<String> {
+    let value = ["LC_ALL", category, "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))?;
+    let locale = value.split(['.', '@']).next()?;
+    (locale.len() >= 2 && locale != "C" && locale != "POSIX").then(|| locale.to_string())
+}
+
+/// Sort and match keys for artist, track and album names: case-, diacritic-
+/// and article-insensitive, so "The Beatles" sorts under B and "Bjork" finds Björk
+#[derive(Debug, Clone)]
+pub struct Collator {
+    articles: Vec<&'static str>,
+}
+
+impl Default for Collator {
+    fn default() -> Self {
+        Collator::for_locale("en")
+    }
+}
+
+impl Collator {
+    /// `locale` as in `LANG`, e.g. `fr_FR.UTF-8`
+    pub fn for_locale(locale: &str) -> Self {
+        let lang = locale.split(['_', '.', '-', '@']).next().unwrap_or_default().to_ascii_lowercase();
+        let extra = match lang.as_str() {
+            "fr" => FR,
+            "de" => DE,
+            "es" => ES,
+            "it" => IT,
+            "pt" => PT,
+            "nl" => NL,
+            _ => &[],
+        };
+        let mut articles: Vec<&'static str> = EN.to_vec();
+        articles.extend(extra.iter().filter(|a| !EN.contains(a)));
+        Collator { articles }
+    }
+
+    /// From `LC_ALL`, `LC_COLLATE` or `LANG`, first one set
+    pub fn from_env() -> Self {
+        Collator::for_locale(&env_locale("LC_COLLATE").unwrap_or_default())
+    }
+
+    /// `fold(s)` without a leading article, e.g. "The Beatles" → "beatles".
+    /// A name that is only an article keeps it.
+    pub fn key(&self, s: &str) -> String {
+        let folded = fold(s);
+        for article in &self.articles {
+            let rest = if article.ends_with('\'') {
+                folded.strip_prefix(article)
+            } else {
+                folded.strip_prefix(article).and_then(|r| r.strip_prefix(' '))
+            };
+            if let Some(rest) = rest.filter(|r| !r.is_empty()) {
+                return rest.to_string();
+            }
+        }
+        folded
+    }
+
+    /// Orders by key, falling back to the raw strings so distinct names never tie
+    pub fn cmp(&self, a: &str, b: &str) -> Ordering {
+        self.key(a).cmp(&self.key(b)).then_with(|| a.cmp(b))
+    }
+}
+
+/// The collator for the user's locale, read once
+pub fn collator() -> &'static Collator {
+    static COLLATOR: OnceLock<Collator> = OnceLock::new();
+    COLLATOR.get_or_init(Collator::from_env)
+}
+
+/// Lowercase with diacritics removed, letters like ß and æ spelled out, and
+/// runs of whitespace collapsed: "  Sigur  Rós" → "sigur ros"
+pub fn fold(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.nfkd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' => out.push_str("ss"),
+            'æ' | 'Æ' => out.push_str("ae"),
+            'œ' | 'Œ' => out.push_str("oe"),
+            'ø' | 'Ø' => out.push('o'),
+            'đ' | 'Đ' | 'ð' | 'Ð' => out.push('d'),
+            'ł' | 'Ł' => out.push('l'),
+            'þ' | 'Þ' => out.push_str("th"),
+            // Typographic apostrophes, so "L’Impératrice" loses its article too
+            '\u{2018}' | '\u{2019}' | '`' => out.push('\''),
+            c if c.is_whitespace() => {
+                if !out.is_empty() && !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            c => out.extend(c.to_lowercase()),
+        }
+    }
+    if out.ends_with(' ') {
+        out.pop();
+    }
+    out
+}
+
+/// Whether each word of `query` turns up in `haystack` with its letters in
+/// order, gaps allowed, so "drk sde" finds "Dark Side of the Moon". Both
+/// sides are folded first
+pub fn fuzzy_match(query: &str, haystack: &str) -> bool {
+    let haystack = fold(haystack);
+    fold(query).split(' ').filter(|w| !w.is_empty()).all(|word| {
+        let mut rest = haystack.chars();
+        word.chars().all(|c| rest.any(|h| h == c))
+    })
+}
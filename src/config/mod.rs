@@ -1,27 +1,597 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dotenvy::dotenv;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf};
 
-#[derive(Debug, Clone)]
+use crate::collate::fold;
+use crate::connect::DeviceSettings;
+use crate::hooks::Hooks;
+use crate::paths;
+use crate::ui::theme::ColorDepth;
+
+pub const DEFAULT_TICK_MS: u64 = 80;   // UI tick (animations, EQ bars)
+pub const DEFAULT_POLL_MS: u64 = 2000; // Playback polling
+pub const DEFAULT_MAX_BPM: f32 = 180.0;
+/// 50 random quotes per call, no key needed
+pub const DEFAULT_QUOTE_API: &str = "https://zenquotes.io/api/quotes";
+
+/// What the bottom of the sidebar shows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidebarPanel {
+    #[default]
+    Quotes,
+    /// Time-synced lyrics for the current track (from lrclib.net)
+    Lyrics,
+    Off,
+}
+
+impl SidebarPanel {
+    pub fn next(self) -> Self {
+        match self {
+            SidebarPanel::Quotes => SidebarPanel::Lyrics,
+            SidebarPanel::Lyrics => SidebarPanel::Off,
+            SidebarPanel::Off => SidebarPanel::Quotes,
+        }
+    }
+}
+
+/// Where the sidebar quotes come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteSource {
+    /// The `quotes` list, or the built-in quotes when that's empty
+    #[default]
+    Builtin,
+    /// One quote per line of `quote_file`
+    File,
+    /// `quote_api`, asked at most once a day
+    Api,
+    /// Music-history facts for today's date
+    History,
+}
+
+/// The dancing critter in the sidebar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visualizer {
+    /// Alternate between the cat and the monkey
+    #[default]
+    Auto,
+    Cat,
+    Monkey,
+    Off,
+}
+
+impl Visualizer {
+    pub fn next(self) -> Self {
+        match self {
+            Visualizer::Auto => Visualizer::Cat,
+            Visualizer::Cat => Visualizer::Monkey,
+            Visualizer::Monkey => Visualizer::Off,
+            Visualizer::Off => Visualizer::Auto,
+        }
+    }
+}
+
+/// Where `O` opens tracks, albums, artists and playlists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenIn {
+    /// The desktop client, through its `spotify:` URI; the web player when
+    /// nothing handles those
+    #[default]
+    App,
+    /// The web player (open.spotify.com)
+    Web,
+}
+
+/// Screen corner toasts stack up from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToastCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub position: ToastCorner,
+    /// Widest a toast may get, in columns
+    pub width: u16,
+    /// How long info toasts stay up (errors stay 2.5× as long); unset keeps the built-in timings
+    pub duration_ms: Option<u64>,
+    /// Overrides for warning and error toasts
+    pub warning_ms: Option<u64>,
+    pub error_ms: Option<u64>,
+    /// Toasts shown at once; the oldest goes first when another arrives
+    pub max_stacked: usize,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            position: ToastCorner::default(),
+            width: 80,
+            duration_ms: None,
+            warning_ms: None,
+            error_ms: None,
+            max_stacked: 3,
+        }
+    }
+}
+
+/// Toast lifetimes per severity in UI ticks; `None` keeps the built-in one
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ToastTicks {
+    pub info: Option<u16>,
+    pub warning: Option<u16>,
+    pub error: Option<u16>,
+}
+
+impl NotificationSettings {
+    /// The configured durations in UI ticks (at least one). Warnings and errors
+    /// without their own setting last 1.5× and 2.5× as long as info toasts.
+    pub fn ticks(&self, tick_ms: u64) -> ToastTicks {
+        let to_ticks = |ms: u64| (ms / tick_ms.max(1)).clamp(1, u16::MAX as u64) as u16;
+        let scaled = |factor: u64| self.duration_ms.map(|ms| ms * factor / 2);
+        ToastTicks {
+            info: self.duration_ms.map(to_ticks),
+            warning: self.warning_ms.or(scaled(3)).map(to_ticks),
+            error: self.error_ms.or(scaled(5)).map(to_ticks),
+        }
+    }
+}
+
+/// Rewind a little when resuming after a long pause, separately for songs and podcasts.
+/// A rewind of 0 seconds turns it off for that kind of content.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmartResume {
+    /// Pauses shorter than this resume exactly where they left off
+    pub after_secs: u64,
+    pub music_secs: u32,
+    pub episode_secs: u32,
+}
+
+impl Default for SmartResume {
+    fn default() -> Self {
+        SmartResume { after_secs: 300, music_secs: 0, episode_secs: 0 }
+    }
+}
+
+impl SmartResume {
+    /// How far back to jump on resume, in ms (0 = resume in place)
+    pub fn rewind_ms(&self, paused_ms: u64, is_episode: bool) -> u32 {
+        if paused_ms < self.after_secs * 1000 {
+            return 0;
+        }
+        let secs = if is_episode { self.episode_secs } else { self.music_secs };
+        secs.saturating_mul(1000)
+    }
+}
+
+/// How far the volume and seek keys move; the `_large` steps are the Shift variants
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Steps {
+    /// Percentage points
+    pub volume: u8,
+    pub volume_large: u8,
+    pub seek_secs: u32,
+    pub seek_large_secs: u32,
+}
+
+impl Default for Steps {
+    fn default() -> Self {
+        Steps { volume: 5, volume_large: 20, seek_secs: 10, seek_large_secs: 60 }
+    }
+}
+
+/// How long, in seconds, lists stay in the Redis cache before they're
+/// fetched from Spotify again; 0 turns caching off for that list. `U`
+/// refetches playlists and Liked Songs sooner.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheTtl {
+    pub playlists: u64,
+    pub liked_songs: u64,
+    /// Kept per playlist snapshot, as the playlist list reports it: an edit
+    /// made elsewhere shows once that list is refetched (`playlists` or `U`)
+    pub playlist_tracks: u64,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        CacheTtl { playlists: 6 * 3600, liked_songs: 6 * 3600, playlist_tracks: 7 * 24 * 3600 }
+    }
+}
+
+/// A mood on the Vibes screen: the built-ins plus any `[[moods]]` from the config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VibesMood {
+    pub name: String,
+    #[serde(default)]
+    pub emoji: String,
+    #[serde(default)]
+    pub description: String,
+    /// Each becomes a `genre:` filter on the search
+    #[serde(default)]
+    pub genres: Vec<String>,
+    /// Free-text keywords added to the search
+    #[serde(default)]
+    pub query: String,
+}
+
+impl VibesMood {
+    fn builtin(emoji: &str, name: &str, description: &str, genre: &str, query: &str) -> Self {
+        VibesMood {
+            name: name.to_string(),
+            emoji: emoji.to_string(),
+            description: description.to_string(),
+            genres: vec![genre.to_string()],
+            query: query.to_string(),
+        }
+    }
+
+    pub fn defaults() -> Vec<VibesMood> {
+        vec![
+            Self::builtin("🌊", "Chill", "Lo-fi beats, ambient sounds, slow tempo", "chill", "lo-fi relaxing"),
+            Self::builtin("⚡", "Hype", "High energy, bass drops, dance tracks", "edm", "hype energy bass"),
+            Self::builtin("🎯", "Focus", "Instrumental, minimal vocals, concentration", "classical", "focus study ambient"),
+            Self::builtin("✨", "Happy", "Uplifting, positive vibes, sing-along", "pop", "happy upbeat feel good"),
+            Self::builtin("🌑", "Dark", "Deep, moody, atmospheric sounds", "metal", "dark heavy intense"),
+        ]
+    }
+
+    /// The built-ins followed by the user's moods; a user mood with a
+    /// built-in's name replaces it in place
+    pub fn all(custom: &[VibesMood]) -> Vec<VibesMood> {
+        let mut moods = Self::defaults();
+        for mood in custom {
+            match moods.iter_mut().find(|m| fold(&m.name) == fold(&mood.name)) {
+                Some(existing) => *existing = mood.clone(),
+                None => moods.push(mood.clone()),
+            }
+        }
+        moods
+    }
+
+    /// Search string for the mood, e.g. `genre:chill lo-fi relaxing`
+    pub fn search_query(&self) -> String {
+        self.genres
+            .iter()
+            .map(|g| if g.contains(' ') { format!("genre:\"{g}\"") } else { format!("genre:{g}") })
+            .chain((!self.query.trim().is_empty()).then(|| self.query.trim().to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl std::fmt::Display for VibesMood {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.emoji.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} {}", self.emoji, self.name)
+        }
+    }
+}
+
+/// Settings layered as: built-in defaults < `~/.config/vibes/config.toml` < env vars.
+/// Credentials only ever come from the environment (or `.env`) and are never saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
+    #[serde(skip)]
     pub client_id: String,
+    #[serde(skip)]
     pub client_secret: String,
+    /// The profile this run is logged in as, from `--profile` or `VIBES_PROFILE`;
+    /// `None` for the default one
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Whether that profile has its own Spotify app (`SPOTIFY_CLIENT_ID_<PROFILE>`)
+    /// rather than sharing the default one
+    #[serde(skip)]
+    pub profile_app: bool,
+    /// `--mini`: start in the compact layout, for a small terminal pane
+    #[serde(skip)]
+    pub mini: bool,
     pub redirect_uri: String,
+    /// Ports to log in through when `redirect_uri`'s is taken, e.g.
+    /// `"8990-8999"`; each needs its redirect URI registered with the app
+    pub redirect_fallback_ports: String,
+    /// How long the login screen waits for the browser before giving up
+    pub auth_timeout_secs: u64,
     pub redis_url: String,
+    pub cache_ttl: CacheTtl,
+    /// Preset name, see `ui::theme::PRESETS`
+    pub theme: String,
+    /// Per-color overrides on top of the preset, e.g. `primary = "#ff8800"`
+    pub colors: BTreeMap<String, String>,
+    /// `auto`, `truecolor`, `256`, `16` or `none`; the palette is mapped down
+    /// to the nearest colors the terminal has
+    pub color_depth: ColorDepth,
+    /// Action name → key, e.g. `next_track = "N"`
+    pub keybinds: BTreeMap<String, String>,
+    /// Action name → two-key sequence, e.g. `go_queue = "gq"`, on top of `gg`, `gp` and `gn`
+    pub chords: BTreeMap<String, String>,
+    pub tick_ms: u64,
+    pub poll_ms: u64,
+    pub replay_gain: bool,
+    /// Pulse the visualizer and ticker to the track's BPM (needs audio features)
+    pub tempo_sync: bool,
+    /// Faster tempos are halved until they fit under this
+    pub max_bpm: f32,
+    /// Tint accents with the dominant color of the current album art
+    pub dynamic_theme: bool,
+    pub sidebar_panel: SidebarPanel,
+    pub visualizer: Visualizer,
+    /// Audio input the EQ bars listen to, in builds with audio capture; the
+    /// default input when unset
+    pub capture_device: Option<String>,
+    /// Replaces the built-in sidebar quotes when non-empty
+    pub quotes: Vec<String>,
+    pub quote_source: QuoteSource,
+    /// Plain text, one quote per line, for `quote_source = "file"`
+    pub quote_file: Option<PathBuf>,
+    /// JSON quotes endpoint for `quote_source = "api"`
+    pub quote_api: String,
+    /// Extra Vibes moods (`[[moods]]` tables), listed after the built-ins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub moods: Vec<VibesMood>,
+    /// When set, a JSON summary of the session (now playing, history, queue
+    /// preview) is kept up to date at this path for overlays and scripts
+    pub session_file: Option<PathBuf>,
+    /// When set, one line about what's playing (`status_format`) is kept
+    /// here for status bars; a FIFO gets each new line written down it
+    pub status_file: Option<PathBuf>,
+    /// Template for `status_file` and `vibes status`, see `widget::status_line`
+    pub status_format: String,
+    /// Where plays are logged for `:export`; defaults to `~/.local/share/vibes/plays.jsonl`
+    pub play_log: Option<PathBuf>,
+    /// Where shared links wait for review (`vibes inbox add`); defaults to
+    /// `~/.local/share/vibes/inbox.jsonl`
+    pub inbox_file: Option<PathBuf>,
+    /// Slowly fetch audio features for every library track in the background,
+    /// into `~/.local/share/vibes/features.jsonl` (needs audio features)
+    pub feature_index: bool,
+    /// Optional subsystems switched on/off by name, see `features::Feature`
+    pub features: BTreeMap<String, bool>,
+    pub smart_resume: SmartResume,
+    pub steps: Steps,
+    pub notifications: NotificationSettings,
+    /// After login, resume whatever was last paused on the account
+    pub resume_on_launch: bool,
+    /// Skip the track-change and screen-switch transitions
+    pub reduce_motion: bool,
+    /// What `gy` copies; placeholders `{artist}`, `{title}`, `{album}`, `{year}`, `{link}`
+    pub copy_template: String,
+    pub open_in: OpenIn,
+    /// When set, serve a web remote for phones on this address, e.g. `"0.0.0.0:8990"`
+    pub remote_addr: Option<SocketAddr>,
+    /// When set, answer MPD clients (mpc, ncmpcpp…) on this address, e.g.
+    /// `"127.0.0.1:6600"`. There's no password, so keep it on localhost
+    pub mpd_addr: Option<SocketAddr>,
+    /// Where the control socket listens; `$XDG_RUNTIME_DIR/vibes.sock` when unset
+    pub control_socket: Option<PathBuf>,
+    /// Where the log is written; `vibes.log` in the platform's state
+    /// directory when unset (`~/.local/state/vibes` on Linux)
+    pub log_file: Option<PathBuf>,
+    /// Start with `:trace on`: playback polls, track changes and skips are
+    /// logged to `trace.jsonl` next to the play log
+    pub trace: bool,
+    /// Other accounts to log in as, e.g. `["family"]`, for `--profile` and `:profile`
+    pub profiles: Vec<String>,
+    /// Shell commands run on track change, pause and like
+    pub hooks: Hooks,
+    /// The built-in Connect device, in builds with librespot
+    pub device: DeviceSettings,
+    /// Sidebar order by name (`search`, `library`, `playlists`, `queue`, `vibes`,
+    /// `stats`, `browse`); screens left out are hidden. Empty shows all of them.
+    pub screens: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            client_id: String::new(),
+            client_secret: String::new(),
+            profile: None,
+            profile_app: false,
+            mini: false,
+            redirect_uri: "http://127.0.0.1:8989/login".to_string(),
+            redirect_fallback_ports: String::new(),
+            auth_timeout_secs: 300,
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            cache_ttl: CacheTtl::default(),
+            theme: "default".to_string(),
+            colors: BTreeMap::new(),
+            color_depth: ColorDepth::Auto,
+            keybinds: BTreeMap::new(),
+            chords: BTreeMap::new(),
+            tick_ms: DEFAULT_TICK_MS,
+            poll_ms: DEFAULT_POLL_MS,
+            replay_gain: false,
+            tempo_sync: false,
+            max_bpm: DEFAULT_MAX_BPM,
+            dynamic_theme: false,
+            sidebar_panel: SidebarPanel::default(),
+            visualizer: Visualizer::default(),
+            capture_device: None,
+            quotes: Vec::new(),
+            quote_source: QuoteSource::default(),
+            quote_file: None,
+            quote_api: DEFAULT_QUOTE_API.to_string(),
+            moods: Vec::new(),
+            session_file: None,
+            status_file: None,
+            status_format: crate::widget::DEFAULT_STATUS_FORMAT.to_string(),
+            play_log: None,
+            inbox_file: None,
+            feature_index: false,
+            features: BTreeMap::new(),
+            smart_resume: SmartResume::default(),
+            steps: Steps::default(),
+            notifications: NotificationSettings::default(),
+            resume_on_launch: false,
+            reduce_motion: false,
+            copy_template: crate::clipboard::DEFAULT_TEMPLATE.to_string(),
+            open_in: OpenIn::default(),
+            remote_addr: None,
+            mpd_addr: None,
+            control_socket: None,
+            log_file: None,
+            trace: false,
+            profiles: Vec::new(),
+            hooks: Hooks::default(),
+            device: DeviceSettings::default(),
+            screens: Vec::new(),
+        }
+    }
+}
+
+/// `family-2` → `FAMILY_2`, for the profile's environment variables
+pub fn profile_env_suffix(profile: &str) -> String {
+    profile.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect()
+}
+
+/// Takes `--profile <name>` (or `--profile=<name>`) out of the command line
+pub fn take_profile_flag(args: &mut Vec<String>) -> Result<Option<String>> {
+    let Some(i) = args.iter().position(|a| a == "--profile" || a.starts_with("--profile=")) else { return Ok(None) };
+    let flag = args.remove(i);
+    match flag.strip_prefix("--profile=") {
+        Some(name) => Ok(Some(name.to_string())),
+        None if i < args.len() => Ok(Some(args.remove(i))),
+        None => anyhow::bail!("--profile needs a name"),
+    }
+}
+
+/// Takes a switch like `--json` out of the command line; whether it was there
+pub fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let before = args.len();
+    args.retain(|a| a != flag);
+    args.len() != before
 }
 
 impl Config {
+    /// `config.toml` in `paths::config_dir`: `$XDG_CONFIG_HOME/vibes/config.toml`,
+    /// falling back to `~/.config/vibes/config.toml` on Linux
+    pub fn path() -> Option<PathBuf> {
+        Some(paths::config_dir()?.join("config.toml"))
+    }
+
     pub fn load() -> Result<Self> {
         dotenv().ok(); // Try loading .env file, ignore if it doesn't exist (e.g. env vars set manually)
 
-        Ok(Config {
-            client_id: std::env::var("SPOTIFY_CLIENT_ID")
-                .expect("SPOTIFY_CLIENT_ID is missing from .env or environment!"),
-            client_secret: std::env::var("SPOTIFY_CLIENT_SECRET")
-                .expect("SPOTIFY_CLIENT_SECRET is missing from .env or environment!"),
-            redirect_uri: std::env::var("SPOTIFY_REDIRECT_URI")
-                .unwrap_or_else(|_| "http://127.0.0.1:8989/login".to_string()),
-            redis_url: std::env::var("REDIS_URL")
-                .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
-        })
+        let mut config = match Self::path() {
+            Some(path) if path.exists() => {
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                toml::from_str::<Config>(&raw)
+                    .with_context(|| format!("Invalid config file {}", path.display()))?
+            }
+            _ => Config::default(),
+        };
+
+        config.client_id = std::env::var("SPOTIFY_CLIENT_ID")
+            .expect("SPOTIFY_CLIENT_ID is missing from .env or environment!");
+        // May also come from the keyring, see `spotify::resolve_client_secret`
+        config.client_secret = std::env::var("SPOTIFY_CLIENT_SECRET").unwrap_or_default();
+        if let Ok(uri) = std::env::var("SPOTIFY_REDIRECT_URI") {
+            config.redirect_uri = uri;
+        }
+        if let Ok(url) = std::env::var("REDIS_URL") {
+            config.redis_url = url;
+        }
+        if let Ok(theme) = std::env::var("VIBES_THEME") {
+            config.theme = theme;
+        }
+        if let Ok(list) = std::env::var("VIBES_FEATURES") {
+            crate::features::apply_env_list(&mut config.features, &list);
+        }
+        if let Some(path) = std::env::var_os("VIBES_SESSION_FILE").filter(|v| !v.is_empty()) {
+            config.session_file = Some(PathBuf::from(path));
+        }
+        if let Ok(addr) = std::env::var("VIBES_REMOTE") {
+            config.remote_addr = Some(addr.parse().with_context(|| format!("Invalid VIBES_REMOTE address {addr}"))?);
+        }
+        if let Ok(addr) = std::env::var("VIBES_MPD") {
+            config.mpd_addr = Some(addr.parse().with_context(|| format!("Invalid VIBES_MPD address {addr}"))?);
+        }
+
+        // Guard against a zero interval, which tokio would panic on
+        config.tick_ms = config.tick_ms.max(10);
+        config.poll_ms = config.poll_ms.max(250);
+
+        Ok(config)
+    }
+
+    /// [`Config::load`] logged in as `profile` (or `VIBES_PROFILE`). Its
+    /// credentials come from `SPOTIFY_CLIENT_ID_<PROFILE>` and
+    /// `SPOTIFY_CLIENT_SECRET_<PROFILE>`; without those it shares the default
+    /// app and only logs in as another account. `default` is the default profile.
+    pub fn load_profile(profile: Option<&str>) -> Result<Self> {
+        let mut config = Self::load()?;
+        let profile = profile.map(str::to_string).or_else(|| std::env::var("VIBES_PROFILE").ok());
+        let Some(name) = profile.filter(|p| !p.is_empty() && p != "default") else { return Ok(config) };
+        if !config.profiles.contains(&name) {
+            anyhow::bail!("Unknown profile {name:?}; add it to `profiles` in config.toml");
+        }
+        let suffix = profile_env_suffix(&name);
+        if let Ok(id) = std::env::var(format!("SPOTIFY_CLIENT_ID_{suffix}")) {
+            config.client_id = id;
+            config.client_secret = std::env::var(format!("SPOTIFY_CLIENT_SECRET_{suffix}")).unwrap_or_default();
+            config.profile_app = true;
+        }
+        config.profile = Some(name);
+        Ok(config)
+    }
+
+    /// The control socket for this profile
+    pub fn control_socket_path(&self) -> PathBuf {
+        self.control_socket.clone().unwrap_or_else(|| crate::ipc::default_path(self.profile.as_deref()))
+    }
+
+    /// `log_file`, or the platform's default; `None` only when no home
+    /// directory can be found
+    pub fn log_path(&self) -> Option<PathBuf> {
+        self.log_file.clone().or_else(paths::log_file)
+    }
+
+    /// Persists one in-app change (`sidebar_panel`, `visualizer`…) to the config
+    /// file. Only that key is written: the rest of the file, comments included,
+    /// stays as the user left it, and nothing from the environment gets saved.
+    pub fn save_setting<T: Serialize>(key: &str, value: &T) -> Result<()> {
+        let path = Self::path().context("Cannot resolve config directory ($HOME unset)")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let raw = with_setting(&raw, key, value).with_context(|| format!("Invalid config file {}", path.display()))?;
+        std::fs::write(&path, raw).with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+}
+
+/// `raw` with the top-level `key` set to `value`, keeping the old value's
+/// comments and spacing when it was already there
+pub fn with_setting<T: Serialize>(raw: &str, key: &str, value: &T) -> Result<String> {
+    let mut doc = raw.parse::<toml_edit::DocumentMut>()?;
+    let mut value = value.serialize(toml_edit::ser::ValueSerializer::new())?;
+    if let Some(old) = doc.get(key).and_then(toml_edit::Item::as_value) {
+        *value.decor_mut() = old.decor().clone();
     }
+    doc[key] = toml_edit::Item::Value(value);
+    Ok(doc.to_string())
 }
@@ -0,0 +1,118 @@
+// Only the settings are reachable without librespot
+#![cfg_attr(not(feature = "librespot"), allow(dead_code))]
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// The built-in Spotify Connect device (`[device]`); it only exists in
+/// builds with the `librespot` cargo feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceSettings {
+    /// What the device is called in Spotify's device lists
+    pub name: String,
+    /// Stream quality in kbit/s: 96, 160 or 320
+    pub bitrate: u16,
+    /// Volume it registers with, in percent
+    pub volume: u8,
+}
+
+impl Default for DeviceSettings {
+    fn default() -> Self {
+        DeviceSettings { name: "Vibes".to_string(), bitrate: 320, volume: 70 }
+    }
+}
+
+impl DeviceSettings {
+    /// `bitrate`, rounded down to a quality Spotify streams at
+    pub fn bitrate(&self) -> u16 {
+        match self.bitrate {
+            0..=159 => 96,
+            160..=319 => 160,
+            _ => 320,
+        }
+    }
+
+    /// `volume` on librespot's 0–65535 scale
+    pub fn initial_volume(&self) -> u16 {
+        (u32::from(self.volume.min(100)) * u32::from(u16::MAX) / 100) as u16
+    }
+}
+
+/// Vibes playing audio itself, listed among the account's devices as `name`.
+/// Dropping it takes the device off the list.
+pub struct LocalDevice {
+    name: String,
+    #[cfg(feature = "librespot")]
+    spirc: librespot::connect::spirc::Spirc,
+}
+
+impl LocalDevice {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(feature = "librespot")]
+impl Drop for LocalDevice {
+    fn drop(&mut self) {
+        if let Err(e) = self.spirc.shutdown() {
+            tracing::warn!("Built-in device didn't shut down: {e}");
+        }
+    }
+}
+
+/// Logs in to Spotify's streaming service with the Web API token (it has
+/// the `streaming` scope) and registers as a Connect device that plays
+/// through the default audio output
+#[cfg(feature = "librespot")]
+pub async fn start(access_token: &str, settings: &DeviceSettings) -> Result<LocalDevice> {
+    use anyhow::Context;
+    use librespot::{
+        connect::{config::ConnectConfig, spirc::Spirc},
+        core::{
+            authentication::Credentials,
+            config::{DeviceType, SessionConfig},
+            session::Session,
+        },
+        playback::{
+            audio_backend,
+            config::{AudioFormat, Bitrate, PlayerConfig},
+            mixer::{self, MixerConfig},
+            player::Player,
+        },
+    };
+
+    let bitrate = match settings.bitrate() {
+        96 => Bitrate::Bitrate96,
+        160 => Bitrate::Bitrate160,
+        _ => Bitrate::Bitrate320,
+    };
+    let backend = audio_backend::find(None).context("librespot was built without an audio backend")?;
+    let mixer = mixer::find(None).context("librespot was built without a mixer")?(MixerConfig::default());
+    let session = Session::new(SessionConfig::default(), None);
+    let player = Player::new(
+        PlayerConfig { bitrate, ..Default::default() },
+        session.clone(),
+        mixer.get_soft_volume(),
+        move || backend(None, AudioFormat::default()),
+    );
+    let connect = ConnectConfig {
+        name: settings.name.clone(),
+        device_type: DeviceType::Computer,
+        initial_volume: Some(settings.initial_volume()),
+        ..Default::default()
+    };
+    let (spirc, task) = Spirc::new(connect, session, Credentials::with_access_token(access_token), player, mixer)
+        .await
+        .context("Spotify didn't accept the built-in device")?;
+    tokio::spawn(task);
+    tracing::info!("Registered as Connect device {} ({} kbit/s)", settings.name, settings.bitrate());
+    Ok(LocalDevice { name: settings.name.clone(), spirc })
+}
+
+/// Built without librespot: playback needs another Spotify app
+#[cfg(not(feature = "librespot"))]
+pub async fn start(_access_token: &str, _settings: &DeviceSettings) -> Result<LocalDevice> {
+    anyhow::bail!("this build has no librespot support")
+}
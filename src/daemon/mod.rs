@@ -0,0 +1,306 @@
+use anyhow::Result;
+use crossterm::event::Event;
+use ratatui::{
+    backend::{Backend, ClearType, CrosstermBackend, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size},
+};
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+};
+use tokio::sync::mpsc;
+
+/// Where `vibes attach` finds the daemon: next to the control socket, so
+/// `vibes.sock` gets `vibes-attach.sock` and each profile has its own
+pub fn attach_path(control_socket: &Path) -> PathBuf {
+    let stem = control_socket.file_stem().and_then(|s| s.to_str()).unwrap_or("vibes");
+    control_socket.with_file_name(format!("{stem}-attach.sock"))
+}
+
+/// What the daemon draws at before any terminal has attached
+const DETACHED_SIZE: Size = Size { width: 80, height: 24 };
+
+#[derive(Debug)]
+struct Viewer {
+    /// Frames for the attached terminal; `None` while detached
+    out: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    /// Bumped on every attach, so a terminal that was taken over can't
+    /// detach or resize the one that took over
+    generation: u64,
+    size: Size,
+}
+
+/// The daemon's side of whichever terminal is attached, shared by the
+/// backend the app draws to and the attach socket
+#[derive(Debug, Clone)]
+pub struct Screen(Arc<Mutex<Viewer>>);
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen(Arc::new(Mutex::new(Viewer { out: None, generation: 0, size: DETACHED_SIZE })))
+    }
+}
+
+impl Screen {
+    fn viewer(&self) -> MutexGuard<'_, Viewer> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hands the screen to a terminal of `size`, detaching the previous one.
+    /// Returns the attachment's generation and where its frames go
+    pub fn attach(&self, size: Size) -> (u64, mpsc::UnboundedReceiver<Vec<u8>>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut viewer = self.viewer();
+        viewer.generation += 1;
+        viewer.out = Some(tx);
+        viewer.size = size;
+        (viewer.generation, rx)
+    }
+
+    /// Lets the attached terminal go: its frames end and `vibes attach`
+    /// exits. False when nothing was attached
+    pub fn detach(&self) -> bool {
+        self.viewer().out.take().is_some()
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.viewer().out.is_some()
+    }
+
+    /// Whether `generation` is the terminal attached right now
+    pub fn is_current(&self, generation: u64) -> bool {
+        let viewer = self.viewer();
+        viewer.out.is_some() && viewer.generation == generation
+    }
+
+    pub fn size(&self) -> Size {
+        self.viewer().size
+    }
+
+    fn resize(&self, generation: u64, size: Size) {
+        let mut viewer = self.viewer();
+        if viewer.generation == generation {
+            viewer.size = size;
+        }
+    }
+
+    fn detach_if(&self, generation: u64) {
+        let mut viewer = self.viewer();
+        if viewer.generation == generation {
+            viewer.out = None;
+        }
+    }
+
+    /// Frames drawn while detached go nowhere
+    fn send(&self, frame: Vec<u8>) {
+        if let Some(out) = &self.viewer().out {
+            let _ = out.send(frame);
+        }
+    }
+}
+
+/// Collects the backend's escape codes and passes them on at each flush
+struct Pipe {
+    screen: Screen,
+    buf: Vec<u8>,
+}
+
+impl Write for Pipe {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.screen.send(std::mem::take(&mut self.buf));
+        }
+        Ok(())
+    }
+}
+
+/// What `vibes daemon` draws to: crossterm's output, sent to the attached
+/// terminal, at that terminal's size. There's no terminal to ask, so the
+/// size and cursor are whatever was last reported or set.
+pub struct AttachBackend {
+    inner: CrosstermBackend<Pipe>,
+    screen: Screen,
+    cursor: Position,
+}
+
+impl AttachBackend {
+    pub fn new(screen: Screen) -> Self {
+        let pipe = Pipe { screen: screen.clone(), buf: Vec::new() };
+        AttachBackend { inner: CrosstermBackend::new(pipe), screen, cursor: Position::ORIGIN }
+    }
+}
+
+impl Backend for AttachBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.inner.draw(content)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.inner.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.inner.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.cursor = position.into();
+        self.inner.set_cursor_position(self.cursor)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.inner.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        Ok(self.screen.size())
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        Ok(WindowSize { columns_rows: self.screen.size(), pixels: Size::default() })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Backend::flush(&mut self.inner)
+    }
+}
+
+/// One event as `vibes attach` sends it
+fn event_line(event: &Event) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Lets `vibes attach` take the screen. A terminal sends its size first,
+/// then its key presses and resizes, one JSON event per line, and gets the
+/// frames back. A second one takes over from the first, like `tmux attach -d`.
+/// The app hears of each attach as `Event::FocusGained`, its cue to redraw
+/// everything.
+#[cfg(unix)]
+pub async fn serve(path: &Path, screen: Screen, events: mpsc::UnboundedSender<Event>) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tracing::{info, warn};
+
+    let listener = crate::ipc::bind(path).await?;
+    info!("Attach socket at {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = events.closed() => break,
+            };
+            let Ok((stream, _)) = accepted else { continue };
+            let (screen, events) = (screen.clone(), events.clone());
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                let Ok(Some(first)) = lines.next_line().await else { return };
+                let Ok(Event::Resize(cols, rows)) = serde_json::from_str(&first) else {
+                    warn!("Attach client didn't start with its size");
+                    return;
+                };
+                let (generation, mut frames) = screen.attach(Size::new(cols, rows));
+                info!("Terminal attached ({cols}×{rows})");
+                let _ = events.send(Event::FocusGained);
+                tokio::spawn(async move {
+                    while let Some(frame) = frames.recv().await {
+                        if write.write_all(&frame).await.is_err() {
+                            break;
+                        }
+                    }
+                    // Dropping `write` ends the client's output, and it exits
+                });
+                while let Ok(Some(line)) = lines.next_line().await {
+                    match serde_json::from_str::<Event>(&line) {
+                        Ok(_) if !screen.is_current(generation) => break,
+                        Ok(Event::Resize(cols, rows)) => screen.resize(generation, Size::new(cols, rows)),
+                        Ok(event) => {
+                            let _ = events.send(event);
+                        }
+                        Err(e) => warn!("Bad event from attach client: {e}"),
+                    }
+                }
+                screen.detach_if(generation);
+                info!("Terminal detached");
+            });
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn serve(_path: &Path, _screen: Screen, _events: mpsc::UnboundedSender<Event>) -> Result<()> {
+    anyhow::bail!("the daemon needs a Unix system")
+}
+
+/// Shows the daemon listening on `path` in this terminal until it lets go:
+/// `q`, `vibes ctl quit`, or another terminal attaching. False, without
+/// touching the terminal, when no daemon is listening.
+#[cfg(unix)]
+pub async fn attach(path: &Path) -> Result<bool> {
+    use crossterm::{cursor, event::EventStream, execute, terminal};
+    use futures::StreamExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let Ok(stream) = tokio::net::UnixStream::connect(path).await else { return Ok(false) };
+    let (mut read, mut write) = stream.into_split();
+    let (cols, rows) = terminal::size()?;
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let input = async {
+        write.write_all(&event_line(&Event::Resize(cols, rows))?).await?;
+        let mut events = EventStream::new();
+        while let Some(event) = events.next().await {
+            let event = event?;
+            if matches!(event, Event::Key(_) | Event::Resize(..)) {
+                write.write_all(&event_line(&event)?).await?;
+            }
+        }
+        anyhow::Ok(())
+    };
+    let output = async {
+        let (mut buf, mut stdout) = (vec![0; 64 * 1024], io::stdout());
+        loop {
+            let n = read.read(&mut buf).await?;
+            if n == 0 {
+                return anyhow::Ok(());
+            }
+            stdout.write_all(&buf[..n])?;
+            stdout.flush()?;
+        }
+    };
+    let result = tokio::select! {
+        result = input => result,
+        result = output => result,
+    };
+
+    terminal::disable_raw_mode()?;
+    execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show)?;
+    result.map(|()| true)
+}
+
+#[cfg(not(unix))]
+pub async fn attach(_path: &Path) -> Result<bool> {
+    Ok(false)
+}
@@ -1,4 +1,15 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+use crate::app::state::ActiveScreen;
+
+/// How long the first key of a sequence (`g`, `%`) waits for the second
+pub const CHORD_TIMEOUT: Duration = Duration::from_millis(1000);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum UserAction {
@@ -8,6 +19,11 @@ pub enum UserAction {
     NavigateDown,
     NavigateLeft,
     NavigateRight,
+    PageUp,
+    PageDown,
+    JumpTop,
+    JumpBottom,
+    JumpToPlaying,
     Select,
     Back,
     TogglePlay,
@@ -15,55 +31,553 @@ pub enum UserAction {
     PrevTrack,
     VolumeUp,
     VolumeDown,
+    /// Shift variants: `+` / `_`, by `steps.volume_large`
+    VolumeUpLarge,
+    VolumeDownLarge,
+    /// `m`: volume to 0, and back to where it was on the next press
+    ToggleMute,
     LikeTrack,
     AddToQueue,
+    PlayNext,
     OpenSearch,
     SearchInput(char),
     SearchBackspace,
     SearchSubmit,
+    /// ↑ / ↓ while typing a search: older / newer past queries
+    RecallOlderSearch,
+    RecallNewerSearch,
+    /// gs: past searches, to run one again
+    OpenSearchHistory,
+    /// /: narrow the list on screen as you type, without asking Spotify
+    FilterList,
+    /// S: sort Liked Songs or the open playlist by the next column / direction
+    CycleSort,
+    /// U: refetch playlists and Liked Songs, skipping the cache
+    RefreshLibrary,
     SwitchScreen(u8),
+    /// A screen by name rather than sidebar position, for sequences like `gp`
+    GoTo(ActiveScreen),
+    /// `gn`: the playlist, album or artist the playing track was started from
+    GoToPlayingContext,
+    /// A digit typed outside `%`: builds a row number for Enter to jump to,
+    /// or switches screens when it's a lone digit followed by a pause
+    RowDigit(u8),
+    /// `%` then a digit (or Alt+digit): jump to that tenth of the track, like mpv
+    SeekPercent(u8),
     SeekForward,
     SeekBackward,
+    /// Shift+→ / Shift+←, by `steps.seek_large_secs`
+    SeekForwardLarge,
+    SeekBackwardLarge,
     ToggleEQ,
+    /// Full-screen Now Playing, and back
+    ToggleFocus,
+    /// The compact layout for a small pane, and back
+    ToggleMini,
+    ToggleReplayGain,
+    ShowErrorDetails,
+    TogglePlaylistDiff,
+    CycleSidebarPanel,
+    CycleVisualizer,
+    CreatePlaylist,
+    AddToPlaylist,
+    RemoveFromPlaylist,
+    OpenCommand,
+    /// Tab: search tracks ↔ playlists, Liked Songs ↔ Albums
+    SwitchTab,
+    FollowPlaylist,
+    /// gy: copy "Artist – Title (Album, Year) <link>" to the clipboard
+    CopyTrackInfo,
+    /// y: copy the track's open.spotify.com link
+    CopyTrackLink,
+    /// Y: copy the track's `spotify:track:` URI
+    CopyTrackUri,
+    /// O: open the highlighted track, album, artist or playlist in Spotify
+    OpenInSpotify,
+    /// gi: tracks shared with `vibes inbox add`, to queue, add or dismiss
+    OpenInbox,
+    /// gb: long operations still running, with their progress
+    OpenJobs,
+    /// gd: Spotify Connect devices, to move playback to
+    OpenDevices,
+    /// gl: the latest log lines, errors highlighted
+    OpenLog,
+    /// i: audio-features radar of the highlighted track vs. what's playing
+    ShowTrackFeatures,
+    /// M: filter Liked Songs by indexed audio features, e.g. `energy > 0.7`
+    MoodFilter,
+    /// R: play a song-radio style set built from the highlighted (or playing) track
+    StartRadio,
+    /// Q on Vibes: append every recommendation to the Spotify queue
+    EnqueueAll,
+    /// o: replay the top track from this date last time it has plays
+    ReplayOnThisDay,
+    /// V: Space marks rows instead of playing, and l / a / P act on the marks
+    ToggleVisualSelect,
+    /// X on the playlist list: save the marked (or highlighted) playlists to
+    /// the archive folder and unfollow them
+    ArchivePlaylists,
+    /// From the control socket: switch to Search and look this up
+    Search(String),
+    /// From the control socket: append this `spotify:track:` URI to the queue
+    QueueUri(String),
+    /// `vibes ctl quit`: stop, even as a daemon, where `q` only detaches
+    Shutdown,
+    /// From the control socket: move playback to the device with this ID or
+    /// name; `play` starts it there
+    TransferPlayback { device: String, play: bool },
+}
+
+/// Every bindable action: its config name (`[keybinds]`, `[chords]`), the
+/// action, and what it does for the cheat sheet
+const ACTIONS: &[(&str, UserAction, &str)] = &[
+    ("quit", UserAction::Quit, "Quit"),
+    ("toggle_help", UserAction::ToggleHelp, "Toggle the help overlay"),
+    ("navigate_up", UserAction::NavigateUp, "Move up"),
+    ("navigate_down", UserAction::NavigateDown, "Move down"),
+    ("navigate_left", UserAction::NavigateLeft, "Back out of a list, or the previous range"),
+    ("navigate_right", UserAction::NavigateRight, "Open the highlighted item, or the next range"),
+    ("page_up", UserAction::PageUp, "Page up"),
+    ("page_down", UserAction::PageDown, "Page down"),
+    ("jump_top", UserAction::JumpTop, "Jump to the top"),
+    ("jump_bottom", UserAction::JumpBottom, "Jump to the bottom"),
+    ("jump_to_playing", UserAction::JumpToPlaying, "Jump to the playing track"),
+    ("select", UserAction::Select, "Play or open the highlighted item"),
+    ("back", UserAction::Back, "Close a popup or go back"),
+    ("toggle_play", UserAction::TogglePlay, "Play / pause"),
+    ("next_track", UserAction::NextTrack, "Next track"),
+    ("prev_track", UserAction::PrevTrack, "Previous track"),
+    ("volume_up", UserAction::VolumeUp, "Volume up"),
+    ("volume_down", UserAction::VolumeDown, "Volume down"),
+    ("volume_up_large", UserAction::VolumeUpLarge, "Volume up a lot"),
+    ("volume_down_large", UserAction::VolumeDownLarge, "Volume down a lot"),
+    ("toggle_mute", UserAction::ToggleMute, "Mute / unmute"),
+    ("like_track", UserAction::LikeTrack, "Like / unlike the highlighted or playing track"),
+    ("add_to_queue", UserAction::AddToQueue, "Add to queue"),
+    ("play_next", UserAction::PlayNext, "Play next"),
+    ("open_search", UserAction::OpenSearch, "Search"),
+    ("search_history", UserAction::OpenSearchHistory, "Past searches"),
+    ("filter", UserAction::FilterList, "Filter the list on screen"),
+    ("cycle_sort", UserAction::CycleSort, "Sort the track table"),
+    ("refresh_library", UserAction::RefreshLibrary, "Reload playlists and Liked Songs from Spotify"),
+    ("seek_forward", UserAction::SeekForward, "Seek forward"),
+    ("seek_backward", UserAction::SeekBackward, "Seek backward"),
+    ("seek_forward_large", UserAction::SeekForwardLarge, "Seek forward a lot"),
+    ("seek_backward_large", UserAction::SeekBackwardLarge, "Seek backward a lot"),
+    ("toggle_eq", UserAction::ToggleEQ, "Toggle the equalizer bars"),
+    ("focus_mode", UserAction::ToggleFocus, "Full-screen Now Playing"),
+    ("mini_mode", UserAction::ToggleMini, "Compact layout for a small pane"),
+    ("toggle_replay_gain", UserAction::ToggleReplayGain, "Volume leveling"),
+    ("playlist_changes", UserAction::TogglePlaylistDiff, "Playlist changes since your last visit"),
+    ("cycle_sidebar_panel", UserAction::CycleSidebarPanel, "Cycle the sidebar panel"),
+    ("cycle_visualizer", UserAction::CycleVisualizer, "Cycle the visualizer critter"),
+    ("create_playlist", UserAction::CreatePlaylist, "New playlist"),
+    ("add_to_playlist", UserAction::AddToPlaylist, "Add the track to a playlist"),
+    ("remove_from_playlist", UserAction::RemoveFromPlaylist, "Remove from playlist / unfollow / clear"),
+    ("command", UserAction::OpenCommand, "Command line"),
+    ("switch_tab", UserAction::SwitchTab, "Switch tab"),
+    ("follow_playlist", UserAction::FollowPlaylist, "Follow the playlist"),
+    ("copy_track", UserAction::CopyTrackInfo, "Copy track info"),
+    ("copy_link", UserAction::CopyTrackLink, "Copy the track's web link"),
+    ("copy_uri", UserAction::CopyTrackUri, "Copy the track's Spotify URI"),
+    ("open_in_spotify", UserAction::OpenInSpotify, "Open in Spotify"),
+    ("open_inbox", UserAction::OpenInbox, "Inbox of shared links"),
+    ("jobs", UserAction::OpenJobs, "Running jobs"),
+    ("devices", UserAction::OpenDevices, "Devices, to move playback to"),
+    ("log", UserAction::OpenLog, "Recent log lines"),
+    ("track_features", UserAction::ShowTrackFeatures, "Audio features radar"),
+    ("mood_filter", UserAction::MoodFilter, "Mood filter over Liked Songs"),
+    ("track_radio", UserAction::StartRadio, "Track radio"),
+    ("on_this_day", UserAction::ReplayOnThisDay, "Replay on this day"),
+    ("enqueue_all", UserAction::EnqueueAll, "Queue all Vibes results"),
+    ("visual_select", UserAction::ToggleVisualSelect, "Visual select (Space marks)"),
+    ("archive_playlists", UserAction::ArchivePlaylists, "Archive playlists"),
+    ("go_search", UserAction::GoTo(ActiveScreen::Search), "Go to Search"),
+    ("go_library", UserAction::GoTo(ActiveScreen::Library), "Go to Library"),
+    ("go_playlists", UserAction::GoTo(ActiveScreen::Playlists), "Go to Playlists"),
+    ("go_queue", UserAction::GoTo(ActiveScreen::Queue), "Go to Queue"),
+    ("go_vibes", UserAction::GoTo(ActiveScreen::Vibes), "Go to Vibes"),
+    ("go_stats", UserAction::GoTo(ActiveScreen::Stats), "Go to Stats"),
+    ("go_browse", UserAction::GoTo(ActiveScreen::Browse), "Go to Browse"),
+    ("go_playing_context", UserAction::GoToPlayingContext, "Go to what the playing track was started from"),
+];
+
+impl UserAction {
+    /// Looks up a bindable action by its config name (`[keybinds]` table)
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTIONS.iter().find(|(n, _, _)| *n == name).map(|(_, action, _)| action.clone())
+    }
+
+    /// Actions that change playback or the user's Spotify data, which can't
+    /// be done offline. Likes are left out: they wait in the outbox instead.
+    pub fn needs_network(&self) -> bool {
+        matches!(
+            self,
+            UserAction::TogglePlay
+                | UserAction::NextTrack
+                | UserAction::PrevTrack
+                | UserAction::VolumeUp
+                | UserAction::VolumeDown
+                | UserAction::VolumeUpLarge
+                | UserAction::VolumeDownLarge
+                | UserAction::ToggleMute
+                | UserAction::AddToQueue
+                | UserAction::PlayNext
+                | UserAction::SearchSubmit
+                | UserAction::SeekPercent(_)
+                | UserAction::SeekForward
+                | UserAction::SeekBackward
+                | UserAction::SeekForwardLarge
+                | UserAction::SeekBackwardLarge
+                | UserAction::CreatePlaylist
+                | UserAction::AddToPlaylist
+                | UserAction::RemoveFromPlaylist
+                | UserAction::FollowPlaylist
+                | UserAction::StartRadio
+                | UserAction::EnqueueAll
+                | UserAction::ReplayOnThisDay
+                | UserAction::ArchivePlaylists
+                | UserAction::RefreshLibrary
+                | UserAction::Search(_)
+                | UserAction::QueueUri(_)
+                | UserAction::OpenDevices
+                | UserAction::TransferPlayback { .. }
+        )
+    }
+}
+
+/// Parses a key name from the config: a single character, or one of
+/// `space`, `enter`, `esc`, `tab`, `backspace`, `up`, `down`, `left`, `right`,
+/// `pageup`, `pagedown`, `home`, `end`.
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    match name.to_ascii_lowercase().as_str() {
+        "space" => Some(KeyCode::Char(' ')),
+        "enter" => Some(KeyCode::Enter),
+        "esc" => Some(KeyCode::Esc),
+        "tab" => Some(KeyCode::Tab),
+        "backspace" => Some(KeyCode::Backspace),
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "pageup" => Some(KeyCode::PageUp),
+        "pagedown" => Some(KeyCode::PageDown),
+        "home" => Some(KeyCode::Home),
+        "end" => Some(KeyCode::End),
+        _ => None,
+    }
+}
+
+/// Built-in two-key sequences; `[chords]` in the config adds to them
+fn default_chords() -> HashMap<(char, char), UserAction> {
+    HashMap::from([
+        (('g', 'g'), UserAction::JumpTop),
+        (('g', 'p'), UserAction::GoTo(ActiveScreen::Playlists)),
+        (('g', 'n'), UserAction::GoToPlayingContext),
+        (('g', 'y'), UserAction::CopyTrackInfo),
+        (('g', 'i'), UserAction::OpenInbox),
+        (('g', 'b'), UserAction::OpenJobs),
+        (('g', 'd'), UserAction::OpenDevices),
+        (('g', 'l'), UserAction::OpenLog),
+        (('g', 's'), UserAction::OpenSearchHistory),
+    ])
+}
+
+/// A sequence from the config: exactly two characters, e.g. `gq`
+pub fn parse_chord(keys: &str) -> Option<(char, char)> {
+    let mut chars = keys.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(a), Some(b), None) => Some((a, b)),
+        _ => None,
+    }
+}
+
+/// User key overrides from config. They take precedence over the defaults in
+/// `map_key_to_action`; the default key for an action keeps working too.
+/// Also holds the two-key sequences (`gg`, `gp`) and the pending first key
+/// of one (or of `%5`).
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    overrides: HashMap<KeyCode, UserAction>,
+    chords: HashMap<(char, char), UserAction>,
+    pending: Option<(char, Instant)>,
 }
 
-pub fn map_key_to_action(key: KeyEvent, search_active: bool) -> Option<UserAction> {
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::from_config(&BTreeMap::new(), &BTreeMap::new())
+    }
+}
+
+impl Keymap {
+    pub fn from_config(binds: &BTreeMap<String, String>, chord_binds: &BTreeMap<String, String>) -> Self {
+        let mut overrides = HashMap::new();
+        for (name, key) in binds {
+            match (UserAction::from_name(name), parse_key(key)) {
+                (Some(action), Some(code)) => {
+                    overrides.insert(code, action);
+                }
+                (None, _) => warn!("Unknown action in [keybinds]: {name}"),
+                (_, None) => warn!("Unknown key for {name} in [keybinds]: {key}"),
+            }
+        }
+        let mut chords = default_chords();
+        for (name, keys) in chord_binds {
+            match (UserAction::from_name(name), parse_chord(keys)) {
+                (Some(action), Some(chord)) => {
+                    chords.insert(chord, action);
+                }
+                (None, _) => warn!("Unknown action in [chords]: {name}"),
+                (_, None) => warn!("Chord for {name} must be two keys: {keys}"),
+            }
+        }
+        Keymap { overrides, chords, pending: None }
+    }
+
+    /// First key of a sequence still waiting for its second
+    pub fn pending(&self) -> Option<char> {
+        self.pending.filter(|(_, at)| at.elapsed() < CHORD_TIMEOUT).map(|(c, _)| c)
+    }
+
+    fn starts_chord(&self, c: char) -> bool {
+        self.chords.keys().any(|&(first, _)| first == c)
+    }
+
+    /// What each action is bound to right now, in `ACTIONS` order. Found by
+    /// feeding every key through `map_key_to_action`, so `[keybinds]` and
+    /// `[chords]` are always reflected; digits and `%` are listed as is.
+    pub fn bindings(&self) -> Vec<(&'static str, Vec<String>)> {
+        let mut bound: Vec<(UserAction, String)> = Vec::new();
+        for key in probe_keys() {
+            let mut probe = Keymap { pending: None, ..self.clone() };
+            match map_key_to_action(key, false, &mut probe) {
+                Some(UserAction::RowDigit(_)) | None => {}
+                Some(action) => bound.push((action, key_label(&key))),
+            }
+        }
+        let mut chords: Vec<_> = self.chords.iter().collect();
+        chords.sort_by_key(|(keys, _)| **keys);
+        bound.extend(chords.into_iter().map(|(&(a, b), action)| (action.clone(), format!("{a}{b}"))));
+
+        let mut rows: Vec<(&'static str, Vec<String>)> = ACTIONS
+            .iter()
+            .filter_map(|(_, action, what)| {
+                let keys: Vec<String> = bound.iter().filter(|(a, _)| a == action).map(|(_, key)| key.clone()).collect();
+                (!keys.is_empty()).then_some((*what, keys))
+            })
+            .collect();
+        rows.push(("Switch screen, or type a row number then Enter", vec!["0–9".to_string()]));
+        rows.push(("Seek to that tenth of the track", vec!["%0–%9".to_string(), "Alt+0–9".to_string()]));
+        rows
+    }
+
+    /// A printable sheet of `bindings`
+    pub fn cheat_sheet(&self, format: SheetFormat) -> String {
+        let rows = self.bindings();
+        let mut out = String::new();
+        match format {
+            SheetFormat::Markdown => {
+                out.push_str("# vibes keys\n\n| Keys | Action |\n|---|---|\n");
+                for (what, keys) in rows {
+                    let keys: Vec<String> = keys.iter().map(|k| markdown_code(k)).collect();
+                    out.push_str(&format!("| {} | {what} |\n", keys.join(" ")));
+                }
+            }
+            SheetFormat::Text => {
+                let rows: Vec<(&str, String)> = rows.into_iter().map(|(what, keys)| (what, keys.join("  "))).collect();
+                let width = rows.iter().map(|(_, keys)| keys.chars().count()).max().unwrap_or(0);
+                out.push_str("vibes keys\n\n");
+                for (what, keys) in rows {
+                    out.push_str(&format!("{keys:<width$}  {what}\n"));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// How `:cheatsheet` writes the keymap
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetFormat {
+    Markdown,
+    Text,
+}
+
+impl SheetFormat {
+    /// Plain text for `.txt` files, markdown for anything else
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == "txt" => SheetFormat::Text,
+            _ => SheetFormat::Markdown,
+        }
+    }
+}
+
+/// Every key the cheat sheet asks `map_key_to_action` about
+fn probe_keys() -> Vec<KeyEvent> {
+    let named = [
+        KeyCode::Enter,
+        KeyCode::Esc,
+        KeyCode::Tab,
+        KeyCode::Backspace,
+        KeyCode::Delete,
+        KeyCode::Up,
+        KeyCode::Down,
+        KeyCode::Left,
+        KeyCode::Right,
+        KeyCode::PageUp,
+        KeyCode::PageDown,
+        KeyCode::Home,
+        KeyCode::End,
+    ];
+    let shifted = [KeyCode::Left, KeyCode::Right].map(|code| KeyEvent::new(code, KeyModifiers::SHIFT));
+    (' '..='~')
+        .map(KeyCode::Char)
+        .chain(named)
+        .map(|code| KeyEvent::new(code, KeyModifiers::NONE))
+        .chain(shifted)
+        .collect()
+}
+
+fn key_label(key: &KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Delete => "Del".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PgUp".to_string(),
+        KeyCode::PageDown => "PgDn".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        other => format!("{other:?}"),
+    };
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        format!("Shift+{name}")
+    } else {
+        name
+    }
+}
+
+/// A key as inline code inside a markdown table cell
+fn markdown_code(key: &str) -> String {
+    let code = if key.contains('`') { format!("`` {key} ``") } else { format!("`{key}`") };
+    code.replace('|', "\\|")
+}
+
+pub fn map_key_to_action(key: KeyEvent, search_active: bool, keymap: &mut Keymap) -> Option<UserAction> {
+    // Any key completes or cancels a pending sequence
+    let pending = keymap.pending();
+    keymap.pending = None;
     if search_active {
         return match key.code {
             KeyCode::Esc => Some(UserAction::Back),
             KeyCode::Enter => Some(UserAction::SearchSubmit),
             KeyCode::Backspace => Some(UserAction::SearchBackspace),
+            KeyCode::Up => Some(UserAction::RecallOlderSearch),
+            KeyCode::Down => Some(UserAction::RecallNewerSearch),
             KeyCode::Char(c) => Some(UserAction::SearchInput(c)),
             _ => None,
         };
     }
 
+    if let (Some(first), KeyCode::Char(c)) = (pending, key.code) {
+        if let Some(action) = keymap.chords.get(&(first, c)) {
+            return Some(action.clone());
+        }
+    }
+    if pending.is_some() && key.code == KeyCode::Esc {
+        return None;
+    }
+
+    if let Some(action) = keymap.overrides.get(&key.code) {
+        return Some(action.clone());
+    }
+
+    // A key that starts a sequence does nothing on its own
+    if let KeyCode::Char(c) = key.code {
+        if keymap.starts_chord(c) {
+            keymap.pending = Some((c, Instant::now()));
+            return None;
+        }
+    }
+
     match key.code {
+        KeyCode::Char(c @ '0'..='9') if pending == Some('%') || key.modifiers.contains(KeyModifiers::ALT) => {
+            Some(UserAction::SeekPercent((c as u8 - b'0') * 10))
+        }
+        KeyCode::Char('%') => {
+            keymap.pending = Some(('%', Instant::now()));
+            None
+        }
         KeyCode::Char('q') => Some(UserAction::Quit),
         KeyCode::Char('?') => Some(UserAction::ToggleHelp),
         KeyCode::Up | KeyCode::Char('k') => Some(UserAction::NavigateUp),
         KeyCode::Down | KeyCode::Char('j') => Some(UserAction::NavigateDown),
+        KeyCode::Left if key.modifiers.contains(KeyModifiers::SHIFT) => Some(UserAction::SeekBackwardLarge),
+        KeyCode::Right if key.modifiers.contains(KeyModifiers::SHIFT) => Some(UserAction::SeekForwardLarge),
         KeyCode::Left => Some(UserAction::NavigateLeft),
         KeyCode::Char('h') => Some(UserAction::NavigateLeft),
         KeyCode::Right => Some(UserAction::NavigateRight),
+        KeyCode::PageUp => Some(UserAction::PageUp),
+        KeyCode::PageDown => Some(UserAction::PageDown),
+        KeyCode::Home => Some(UserAction::JumpTop),
+        KeyCode::End => Some(UserAction::JumpBottom),
+        KeyCode::Char('G') => Some(UserAction::JumpBottom),
+        KeyCode::Char('.') => Some(UserAction::JumpToPlaying),
         KeyCode::Enter => Some(UserAction::Select),
         KeyCode::Esc | KeyCode::Char('b') => Some(UserAction::Back),
         KeyCode::Char(' ') => Some(UserAction::TogglePlay),
         KeyCode::Char('n') => Some(UserAction::NextTrack),
         KeyCode::Char('p') => Some(UserAction::PrevTrack),
-        KeyCode::Char('+') | KeyCode::Char('=') => Some(UserAction::VolumeUp),
+        KeyCode::Char('=') => Some(UserAction::VolumeUp),
         KeyCode::Char('-') => Some(UserAction::VolumeDown),
+        KeyCode::Char('+') => Some(UserAction::VolumeUpLarge),
+        KeyCode::Char('_') => Some(UserAction::VolumeDownLarge),
+        KeyCode::Char('m') => Some(UserAction::ToggleMute),
         KeyCode::Char('l') => Some(UserAction::LikeTrack),
         KeyCode::Char('a') => Some(UserAction::AddToQueue),
+        KeyCode::Char('A') => Some(UserAction::PlayNext),
+        KeyCode::Char('y') => Some(UserAction::CopyTrackLink),
+        KeyCode::Char('Y') => Some(UserAction::CopyTrackUri),
+        KeyCode::Char('O') => Some(UserAction::OpenInSpotify),
+        KeyCode::Char('i') => Some(UserAction::ShowTrackFeatures),
+        KeyCode::Char('M') => Some(UserAction::MoodFilter),
+        KeyCode::Char('/') => Some(UserAction::FilterList),
+        KeyCode::Char('S') => Some(UserAction::CycleSort),
+        KeyCode::Char('U') => Some(UserAction::RefreshLibrary),
+        KeyCode::Char('R') => Some(UserAction::StartRadio),
+        KeyCode::Char('o') => Some(UserAction::ReplayOnThisDay),
+        KeyCode::Char('Q') => Some(UserAction::EnqueueAll),
+        KeyCode::Char('V') => Some(UserAction::ToggleVisualSelect),
+        KeyCode::Char('X') => Some(UserAction::ArchivePlaylists),
         KeyCode::Char('s') => Some(UserAction::OpenSearch),
-        KeyCode::Char('1') => Some(UserAction::SwitchScreen(1)),
-        KeyCode::Char('2') => Some(UserAction::SwitchScreen(2)),
-        KeyCode::Char('3') => Some(UserAction::SwitchScreen(3)),
-        KeyCode::Char('4') => Some(UserAction::SwitchScreen(4)),
-        KeyCode::Char('5') => Some(UserAction::SwitchScreen(5)),
+        KeyCode::Char(c @ '0'..='9') => Some(UserAction::RowDigit(c as u8 - b'0')),
         KeyCode::Char('f') => Some(UserAction::SeekForward),
         KeyCode::Char('r') => Some(UserAction::SeekBackward),
         KeyCode::Char('e') => Some(UserAction::ToggleEQ),
+        KeyCode::Char('z') => Some(UserAction::ToggleFocus),
+        KeyCode::Char('Z') => Some(UserAction::ToggleMini),
+        KeyCode::Char('v') => Some(UserAction::ToggleReplayGain),
+        KeyCode::Char('u') => Some(UserAction::TogglePlaylistDiff),
+        KeyCode::Char('L') => Some(UserAction::CycleSidebarPanel),
+        KeyCode::Char('C') => Some(UserAction::CycleVisualizer),
+        KeyCode::Char('c') => Some(UserAction::CreatePlaylist),
+        KeyCode::Char('P') => Some(UserAction::AddToPlaylist),
+        KeyCode::Delete | KeyCode::Char('x') => Some(UserAction::RemoveFromPlaylist),
+        KeyCode::Char(':') => Some(UserAction::OpenCommand),
+        KeyCode::Tab => Some(UserAction::SwitchTab),
+        KeyCode::Char('F') => Some(UserAction::FollowPlaylist),
         _ => None,
     }
 }
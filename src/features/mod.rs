@@ -0,0 +1,115 @@
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+
+/// Optional subsystems that can be switched off at runtime with the
+/// `[features]` config table or `VIBES_FEATURES`, e.g. `"-lyrics,album_art"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, strum_macros::EnumIter)]
+pub enum Feature {
+    /// Album-art download and accent extraction (needs the `album-art` cargo feature)
+    AlbumArt,
+    /// Spotify audio features, used by tempo sync and volume leveling
+    AudioFeatures,
+    /// Synced lyrics from lrclib.net
+    Lyrics,
+    /// Local log of scrobble-worthy plays, for backfilling Last.fm / ListenBrainz
+    PlayLog,
+    /// The JSON session summary for overlays
+    SessionFile,
+    /// Client secret and login token in the OS keyring rather than `.env` and
+    /// Redis (needs the `keyring` cargo feature)
+    Keyring,
+    /// The Unix control socket for `vibes ctl` and scripts
+    ControlSocket,
+    /// Vibes as a Spotify Connect device that plays audio itself (needs the
+    /// `librespot` cargo feature)
+    LocalDevice,
+    /// EQ bars from what's actually playing, heard through an audio input
+    /// (needs the `audio-capture` cargo feature)
+    AudioCapture,
+}
+
+impl Feature {
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::AlbumArt => "album_art",
+            Feature::AudioFeatures => "audio_features",
+            Feature::Lyrics => "lyrics",
+            Feature::PlayLog => "play_log",
+            Feature::SessionFile => "session_file",
+            Feature::Keyring => "keyring",
+            Feature::ControlSocket => "control_socket",
+            Feature::LocalDevice => "local_device",
+            Feature::AudioCapture => "audio_capture",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Feature> {
+        use strum::IntoEnumIterator;
+        Feature::iter().find(|f| f.name() == name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Feature::AlbumArt => "Album-art accent colors",
+            Feature::AudioFeatures => "Tempo sync & volume leveling data",
+            Feature::Lyrics => "Synced lyrics (lrclib.net)",
+            Feature::PlayLog => "Play log for scrobble backfill",
+            Feature::SessionFile => "Session summary for overlays",
+            Feature::Keyring => "Secrets in the OS keyring",
+            Feature::ControlSocket => "Control socket for scripts",
+            Feature::LocalDevice => "Built-in Connect device",
+            Feature::AudioCapture => "EQ bars from system audio",
+        }
+    }
+
+    /// Whether this build includes the subsystem at all
+    pub fn compiled(self) -> bool {
+        match self {
+            Feature::AlbumArt => cfg!(feature = "album-art"),
+            Feature::Keyring => cfg!(feature = "keyring"),
+            Feature::ControlSocket => cfg!(unix),
+            Feature::LocalDevice => cfg!(feature = "librespot"),
+            Feature::AudioCapture => cfg!(feature = "audio-capture"),
+            _ => true,
+        }
+    }
+}
+
+/// Runtime feature registry; everything compiled in is on unless disabled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Features {
+    disabled: BTreeSet<Feature>,
+}
+
+impl Features {
+    /// Builds the registry from the `[features]` table (name → on/off),
+    /// warning about names it doesn't know
+    pub fn from_config(table: &BTreeMap<String, bool>) -> Features {
+        let mut features = Features::default();
+        for (name, on) in table {
+            match Feature::from_name(name) {
+                Some(feature) if !on => {
+                    features.disabled.insert(feature);
+                }
+                Some(_) => {}
+                None => warn!("Unknown feature in [features]: {name}"),
+            }
+        }
+        features
+    }
+
+    pub fn enabled(&self, feature: Feature) -> bool {
+        feature.compiled() && !self.disabled.contains(&feature)
+    }
+}
+
+/// Applies a `VIBES_FEATURES` list (`name` turns on, `-name` turns off) on top
+/// of the config table
+pub fn apply_env_list(table: &mut BTreeMap<String, bool>, list: &str) {
+    for item in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match item.strip_prefix('-') {
+            Some(name) => table.insert(name.to_string(), false),
+            None => table.insert(item.to_string(), true),
+        };
+    }
+}
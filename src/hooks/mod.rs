@@ -0,0 +1,138 @@
+use rspotify::{model::FullTrack, prelude::Id};
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+use crate::{app::state::CurrentTrack, clipboard::web_link};
+
+/// Shell commands run on playback events (`[hooks]`). Each gets the track
+/// in `VIBES_*` environment variables, and nobody waits for it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    /// Another track started, or the same one from the top
+    pub on_track_change: Option<String>,
+    /// Playback paused, here or on another device
+    pub on_pause: Option<String>,
+    /// A track was added to Liked Songs
+    pub on_like: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    TrackChange,
+    Pause,
+    Like,
+}
+
+impl HookEvent {
+    /// `VIBES_EVENT`, so one script can serve several hooks
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::TrackChange => "track_change",
+            HookEvent::Pause => "pause",
+            HookEvent::Like => "like",
+        }
+    }
+}
+
+impl Hooks {
+    /// The command configured for `event`, if any
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::TrackChange => &self.on_track_change,
+            HookEvent::Pause => &self.on_pause,
+            HookEvent::Like => &self.on_like,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// The track a hook is about
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookTrack {
+    pub uri: String,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub duration_ms: u32,
+    pub progress_ms: u32,
+}
+
+impl From<&CurrentTrack> for HookTrack {
+    fn from(track: &CurrentTrack) -> Self {
+        HookTrack {
+            uri: track.id.clone().unwrap_or_default(),
+            title: track.name.clone(),
+            artist: track.artists.join(", "),
+            album: track.album.clone(),
+            duration_ms: track.duration_ms,
+            progress_ms: track.progress_ms,
+        }
+    }
+}
+
+impl From<&FullTrack> for HookTrack {
+    fn from(track: &FullTrack) -> Self {
+        HookTrack {
+            uri: track.id.as_ref().map(|id| id.uri()).unwrap_or_default(),
+            title: track.name.clone(),
+            artist: track.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", "),
+            album: track.album.name.clone(),
+            duration_ms: track.duration.num_milliseconds() as u32,
+            progress_ms: 0,
+        }
+    }
+}
+
+/// The environment a hook runs with; empty fields (a local file has no
+/// URI) are set to the empty string rather than left out
+pub fn env(event: HookEvent, track: &HookTrack) -> Vec<(&'static str, String)> {
+    vec![
+        ("VIBES_EVENT", event.name().to_string()),
+        ("VIBES_TRACK_URI", track.uri.clone()),
+        ("VIBES_TRACK_LINK", web_link(&track.uri)),
+        ("VIBES_TITLE", track.title.clone()),
+        ("VIBES_ARTIST", track.artist.clone()),
+        ("VIBES_ALBUM", track.album.clone()),
+        ("VIBES_DURATION_MS", track.duration_ms.to_string()),
+        ("VIBES_PROGRESS_MS", track.progress_ms.to_string()),
+    ]
+}
+
+/// `hook` under the platform's shell, with the track in its environment.
+/// Output is dropped, except stderr, which goes to the log on failure.
+pub fn command(hook: &str, event: HookEvent, track: &HookTrack) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.args(["/C", hook]);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.args(["-c", hook]);
+        c
+    };
+    command.envs(env(event, track)).stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped());
+    command
+}
+
+/// Starts `hook` and leaves it running; a hook that fails to start or
+/// exits non-zero is logged
+pub fn spawn(hook: &str, event: HookEvent, track: &HookTrack) {
+    let child = match tokio::process::Command::from(command(hook, event, track)).spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            warn!("on_{} hook didn't start: {e}", event.name());
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        match child.wait_with_output().await {
+            Ok(out) if !out.status.success() => {
+                warn!("on_{} hook failed ({}): {}", event.name(), out.status, String::from_utf8_lossy(&out.stderr).trim());
+            }
+            Ok(_) => {}
+            Err(e) => warn!("on_{} hook: {e}", event.name()),
+        }
+    });
+}
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use rspotify::model::AudioFeatures;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::session::plays;
+
+/// How many tracks one `audio-features` request asks for (Spotify's cap)
+pub const BATCH_SIZE: usize = 100;
+
+/// The audio features mood filters and stats work from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrackFeatures {
+    pub energy: f32,
+    pub danceability: f32,
+    pub valence: f32,
+    pub acousticness: f32,
+    pub instrumentalness: f32,
+    /// BPM
+    pub tempo: f32,
+    /// dB
+    pub loudness: f32,
+}
+
+impl From<&AudioFeatures> for TrackFeatures {
+    fn from(f: &AudioFeatures) -> Self {
+        TrackFeatures {
+            energy: f.energy,
+            danceability: f.danceability,
+            valence: f.valence,
+            acousticness: f.acousticness,
+            instrumentalness: f.instrumentalness,
+            tempo: f.tempo,
+            loudness: f.loudness,
+        }
+    }
+}
+
+/// One line of the index file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// `spotify:track:` URI
+    pub uri: String,
+    /// `None` when Spotify has no features for the track, so it isn't asked again
+    pub features: Option<TrackFeatures>,
+}
+
+/// `$XDG_DATA_HOME/vibes/features.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("features.jsonl"))
+}
+
+/// Audio features for the whole library, filled in slowly by the background
+/// indexer and kept on disk so each track is only fetched once
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureIndex {
+    tracks: HashMap<String, Option<TrackFeatures>>,
+}
+
+impl FeatureIndex {
+    /// Reads the index file; lines that don't parse are skipped, later lines
+    /// win, and a missing file is an empty index
+    pub fn load(path: &Path) -> Result<FeatureIndex> {
+        let raw = match std::fs::read_to_string(path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(FeatureIndex::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        let mut index = FeatureIndex::default();
+        index.insert(raw.lines().filter_map(|line| serde_json::from_str(line).ok()));
+        Ok(index)
+    }
+
+    pub fn insert(&mut self, entries: impl IntoIterator<Item = IndexEntry>) {
+        self.tracks.extend(entries.into_iter().map(|e| (e.uri, e.features)));
+    }
+
+    /// Appends a fetched batch to the index file and to the index
+    pub fn append(&mut self, path: &Path, entries: Vec<IndexEntry>) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut body = String::new();
+        for entry in &entries {
+            body.push_str(&serde_json::to_string(entry)?);
+            body.push('\n');
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.write_all(body.as_bytes())?;
+        self.insert(entries);
+        Ok(())
+    }
+
+    /// Features for a track, if it's indexed and Spotify had some
+    pub fn get(&self, uri: &str) -> Option<&TrackFeatures> {
+        self.tracks.get(uri)?.as_ref()
+    }
+
+    /// Tracks with features
+    pub fn len(&self) -> usize {
+        self.tracks.values().filter(|f| f.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `uris` that haven't been asked about yet, each once, in order
+    pub fn missing<'a>(&self, uris: impl IntoIterator<Item = &'a String>) -> Vec<String> {
+        let mut seen = HashSet::new();
+        uris.into_iter()
+            .filter(|uri| !self.tracks.contains_key(*uri) && seen.insert(uri.as_str()))
+            .cloned()
+            .collect()
+    }
+}
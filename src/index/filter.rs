@@ -0,0 +1,183 @@
+use anyhow::{bail, Context, Result};
+use std::fmt;
+
+use super::features::TrackFeatures;
+
+/// An audio feature a mood filter can test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Energy,
+    Danceability,
+    Valence,
+    Acousticness,
+    Instrumentalness,
+    Tempo,
+    Loudness,
+}
+
+impl Field {
+    /// Full names plus the short forms people actually type
+    pub fn from_name(name: &str) -> Option<Field> {
+        Some(match name.to_lowercase().as_str() {
+            "energy" => Field::Energy,
+            "danceability" | "dance" => Field::Danceability,
+            "valence" | "happy" => Field::Valence,
+            "acousticness" | "acoustic" => Field::Acousticness,
+            "instrumentalness" | "instrumental" => Field::Instrumentalness,
+            "tempo" | "bpm" => Field::Tempo,
+            "loudness" | "loud" => Field::Loudness,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Energy => "energy",
+            Field::Danceability => "danceability",
+            Field::Valence => "valence",
+            Field::Acousticness => "acousticness",
+            Field::Instrumentalness => "instrumentalness",
+            Field::Tempo => "tempo",
+            Field::Loudness => "loudness",
+        }
+    }
+
+    pub fn of(self, f: &TrackFeatures) -> f32 {
+        match self {
+            Field::Energy => f.energy,
+            Field::Danceability => f.danceability,
+            Field::Valence => f.valence,
+            Field::Acousticness => f.acousticness,
+            Field::Instrumentalness => f.instrumentalness,
+            Field::Tempo => f.tempo,
+            Field::Loudness => f.loudness,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Test {
+    Above(f32),
+    AtLeast(f32),
+    Below(f32),
+    AtMost(f32),
+    /// Both ends included
+    Between(f32, f32),
+}
+
+impl Test {
+    fn passes(self, value: f32) -> bool {
+        match self {
+            Test::Above(n) => value > n,
+            Test::AtLeast(n) => value >= n,
+            Test::Below(n) => value < n,
+            Test::AtMost(n) => value <= n,
+            Test::Between(lo, hi) => (lo..=hi).contains(&value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub test: Test,
+}
+
+/// Conditions that must all hold, e.g. `energy > 0.7 and tempo 120–130`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoodFilter {
+    pub conditions: Vec<Condition>,
+}
+
+impl MoodFilter {
+    /// Parses `field op number` or `field low–high` clauses joined by `and`
+    /// or commas. Ops are `>`, `>=`, `<` and `<=`; ranges take `–`, `-`, `..`
+    /// or `to` and include both ends.
+    pub fn parse(expr: &str) -> Result<MoodFilter> {
+        let conditions = split_clauses(expr)
+            .into_iter()
+            .map(parse_condition)
+            .collect::<Result<Vec<_>>>()?;
+        if conditions.is_empty() {
+            bail!("Empty filter — try e.g. “energy > 0.7 and tempo 120–130”");
+        }
+        Ok(MoodFilter { conditions })
+    }
+
+    pub fn matches(&self, features: &TrackFeatures) -> bool {
+        self.conditions.iter().all(|c| c.test.passes(c.field.of(features)))
+    }
+}
+
+impl fmt::Display for MoodFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.conditions.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" and ")?;
+            }
+            let name = c.field.name();
+            match c.test {
+                Test::Above(n) => write!(f, "{name} > {n}")?,
+                Test::AtLeast(n) => write!(f, "{name} >= {n}")?,
+                Test::Below(n) => write!(f, "{name} < {n}")?,
+                Test::AtMost(n) => write!(f, "{name} <= {n}")?,
+                Test::Between(lo, hi) => write!(f, "{name} {lo}–{hi}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn split_clauses(expr: &str) -> Vec<String> {
+    let mut clauses = vec![String::new()];
+    for word in expr.replace(',', " and ").split_whitespace() {
+        if word.eq_ignore_ascii_case("and") {
+            clauses.push(String::new());
+        } else if let Some(clause) = clauses.last_mut() {
+            clause.push_str(word);
+            clause.push(' ');
+        }
+    }
+    clauses.into_iter().map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect()
+}
+
+fn parse_condition(clause: String) -> Result<Condition> {
+    let split = clause.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(clause.len());
+    let (name, rest) = clause.split_at(split);
+    let Some(field) = Field::from_name(name) else {
+        bail!("Unknown feature “{name}” — use energy, danceability, valence, acousticness, instrumentalness, tempo or loudness");
+    };
+    let rest = rest.trim();
+    let test = if let Some(n) = rest.strip_prefix(">=") {
+        Test::AtLeast(number(n)?)
+    } else if let Some(n) = rest.strip_prefix("<=") {
+        Test::AtMost(number(n)?)
+    } else if let Some(n) = rest.strip_prefix('>') {
+        Test::Above(number(n)?)
+    } else if let Some(n) = rest.strip_prefix('<') {
+        Test::Below(number(n)?)
+    } else if let Some((lo, hi)) = split_range(rest) {
+        let (lo, hi) = (number(lo)?, number(hi)?);
+        Test::Between(lo.min(hi), lo.max(hi))
+    } else {
+        bail!("Expected >, <, >=, <= or a range like 120–130 after {name}");
+    };
+    Ok(Condition { field, test })
+}
+
+/// `120–130`, `120-130`, `120..130` or `120 to 130`; a leading minus (for
+/// loudness) isn't taken as the separator
+fn split_range(s: &str) -> Option<(&str, &str)> {
+    for sep in ["..", "–", "—", " to "] {
+        if let Some(pair) = s.split_once(sep) {
+            return Some(pair);
+        }
+    }
+    let at = s.char_indices().skip(1).find(|&(_, c)| c == '-')?.0;
+    Some((&s[..at], &s[at + 1..]))
+}
+
+fn number(s: &str) -> Result<f32> {
+    let s = s.trim();
+    s.parse().with_context(|| format!("“{s}” isn't a number"))
+}
@@ -0,0 +1,99 @@
+pub mod features;
+pub mod filter;
+
+use rspotify::{model::SavedAlbum, prelude::Id};
+use std::collections::HashSet;
+
+use crate::{app::state::LibraryState, collate::collator};
+
+/// A saved album reduced to what the index needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedAlbum {
+    pub id: String,
+    pub name: String,
+    pub artist: String,
+    pub track_uris: Vec<String>,
+}
+
+/// Takes the album's tracks as loaded; `Library::get_saved_albums` pages in
+/// all of them, past the 50 Spotify sends with the album
+impl From<&SavedAlbum> for IndexedAlbum {
+    fn from(saved: &SavedAlbum) -> Self {
+        let album = &saved.album;
+        IndexedAlbum {
+            id: album.id.id().to_string(),
+            name: album.name.clone(),
+            artist: album.artists.iter().map(|a| a.name.as_str()).collect::<Vec<_>>().join(", "),
+            track_uris: album.tracks.items.iter()
+                .filter_map(|t| t.id.as_ref().map(|id| id.uri()))
+                .collect(),
+        }
+    }
+}
+
+/// A saved album that shares tracks with Liked Songs
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlbumOverlap {
+    pub album_id: String,
+    pub name: String,
+    pub artist: String,
+    /// Liked tracks that are also on the album (the duplicates)
+    pub liked: Vec<String>,
+    /// Album tracks that aren't liked yet
+    pub missing: Vec<String>,
+}
+
+/// In-memory view of Liked Songs and saved albums, keyed by track URI
+#[derive(Debug, Clone, Default)]
+pub struct LibraryIndex {
+    liked: HashSet<String>,
+    albums: Vec<IndexedAlbum>,
+}
+
+impl LibraryIndex {
+    pub fn new(liked: impl IntoIterator<Item = String>, albums: Vec<IndexedAlbum>) -> Self {
+        LibraryIndex { liked: liked.into_iter().collect(), albums }
+    }
+
+    pub fn from_library(library: &LibraryState) -> Self {
+        let liked = library.liked_songs.iter()
+            .filter_map(|s| s.track.id.as_ref().map(|id| id.uri()));
+        let albums = library.saved_albums.iter().map(IndexedAlbum::from).collect();
+        LibraryIndex::new(liked, albums)
+    }
+
+    /// Saved albums with at least one liked track, most duplicates first
+    pub fn overlaps(&self) -> Vec<AlbumOverlap> {
+        let mut overlaps: Vec<AlbumOverlap> = self.albums.iter()
+            .filter_map(|album| {
+                let (liked, missing): (Vec<String>, Vec<String>) = album.track_uris.iter()
+                    .cloned()
+                    .partition(|uri| self.liked.contains(uri));
+                (!liked.is_empty()).then(|| AlbumOverlap {
+                    album_id: album.id.clone(),
+                    name: album.name.clone(),
+                    artist: album.artist.clone(),
+                    liked,
+                    missing,
+                })
+            })
+            .collect();
+        let collator = collator();
+        overlaps.sort_by(|a, b| b.liked.len().cmp(&a.liked.len()).then_with(|| collator.cmp(&a.name, &b.name)));
+        overlaps
+    }
+
+    /// Every track in the library: liked ones first, then saved albums'
+    pub fn track_uris(&self) -> impl Iterator<Item = &String> {
+        self.liked.iter().chain(self.albums.iter().flat_map(|album| album.track_uris.iter()))
+    }
+
+    /// Liked track URIs that are also on some saved album
+    pub fn on_saved_albums(&self) -> HashSet<String> {
+        self.albums.iter()
+            .flat_map(|album| album.track_uris.iter())
+            .filter(|uri| self.liked.contains(*uri))
+            .cloned()
+            .collect()
+    }
+}
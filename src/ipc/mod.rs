@@ -0,0 +1,236 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::{mpsc, watch};
+
+use crate::{events::UserAction, paths, remote::RemoteStatus, session::inbox};
+
+/// `$XDG_RUNTIME_DIR/vibes.sock`, or `vibes-<profile>.sock` so instances
+/// logged in as different profiles don't collide. Without `XDG_RUNTIME_DIR`
+/// it goes in the data directory, and only with no home directory either in
+/// a `vibes-<user>` directory of the temp directory that `bind` keeps private
+pub fn default_path(profile: Option<&str>) -> PathBuf {
+    let name = match profile {
+        Some(profile) => format!("vibes-{profile}.sock"),
+        None => "vibes.sock".to_string(),
+    };
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(paths::data_dir)
+        .unwrap_or_else(|| {
+            let user = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
+            std::env::temp_dir().join(format!("vibes-{user}"))
+        })
+        .join(name)
+}
+
+/// One line on the control socket, e.g. `{"command":"queue","uri":"spotify:track:…"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    Play,
+    Pause,
+    Toggle,
+    Next,
+    Prev,
+    /// Switches to Search and looks `query` up
+    Search { query: String },
+    /// Appends a track, by URI or open.spotify.com link, to the Spotify queue
+    Queue { uri: String },
+    /// Answers with what's playing, the volume and the queue
+    Status,
+    /// Stops vibes, a `vibes daemon` included
+    Quit,
+    /// Moves playback to a device, by ID or name. `play` (the default)
+    /// starts it there; `false` leaves it playing or paused as it was
+    Transfer {
+        device: String,
+        #[serde(default = "starts_playing")]
+        play: bool,
+    },
+}
+
+fn starts_playing() -> bool {
+    true
+}
+
+impl Command {
+    /// `vibes ctl` arguments: `play`, `next`, `search <words…>`, `queue <uri>`…
+    pub fn from_args(args: &[&str]) -> Result<Command> {
+        Ok(match args {
+            ["play"] => Command::Play,
+            ["pause"] => Command::Pause,
+            ["toggle"] => Command::Toggle,
+            ["next"] => Command::Next,
+            ["prev" | "previous"] => Command::Prev,
+            ["status"] => Command::Status,
+            ["quit"] => Command::Quit,
+            ["search", words @ ..] if !words.is_empty() => Command::Search { query: words.join(" ") },
+            ["queue", uri] => Command::Queue { uri: uri.to_string() },
+            ["transfer", "--no-play", name @ ..] if !name.is_empty() => Command::Transfer { device: name.join(" "), play: false },
+            ["transfer", name @ ..] if !name.is_empty() => Command::Transfer { device: name.join(" "), play: true },
+            _ => bail!("Usage: vibes ctl play|pause|toggle|next|prev|status|quit|search <query…>|queue <uri>|transfer [--no-play] <device>"),
+        })
+    }
+}
+
+/// The answer to each line: `{"ok":true}`, with the status for `status`,
+/// or `{"ok":false,"error":"…"}`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reply<'a> {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<&'a RemoteStatus>,
+}
+
+impl Reply<'_> {
+    fn failed(error: impl Into<String>) -> Self {
+        Reply { ok: false, error: Some(error.into()), status: None }
+    }
+}
+
+/// Answers one line, and what the app should do about it. `play` and
+/// `pause` only toggle when that changes something.
+pub fn run<'a>(line: &str, status: &'a RemoteStatus) -> (Reply<'a>, Option<UserAction>) {
+    let command = match serde_json::from_str::<Command>(line) {
+        Ok(command) => command,
+        Err(e) => return (Reply::failed(format!("Bad command: {e}")), None),
+    };
+    let now = status.now_playing.as_ref();
+    let playing = now.is_some_and(|np| np.is_playing);
+    let ok = Reply { ok: true, error: None, status: None };
+    let action = match command {
+        Command::Play => (!playing && now.is_some()).then_some(UserAction::TogglePlay),
+        Command::Pause => playing.then_some(UserAction::TogglePlay),
+        Command::Toggle => Some(UserAction::TogglePlay),
+        Command::Next => Some(UserAction::NextTrack),
+        Command::Prev => Some(UserAction::PrevTrack),
+        Command::Search { query } if query.trim().is_empty() => return (Reply::failed("Empty search"), None),
+        Command::Search { query } => Some(UserAction::Search(query.trim().to_string())),
+        Command::Queue { uri } => match inbox::track_uri(&uri) {
+            Some(uri) => Some(UserAction::QueueUri(uri)),
+            None => return (Reply::failed(format!("Not a Spotify track: {uri}")), None),
+        },
+        Command::Status => return (Reply { status: Some(status), ..ok }, None),
+        Command::Quit => Some(UserAction::Shutdown),
+        Command::Transfer { device, .. } if device.trim().is_empty() => return (Reply::failed("No device named"), None),
+        Command::Transfer { device, play } => Some(UserAction::TransferPlayback { device: device.trim().to_string(), play }),
+    };
+    (ok, action)
+}
+
+/// Binds a socket only this user can connect to. A socket file left behind
+/// by a crash is replaced; one another instance still answers on is an error.
+#[cfg(unix)]
+pub async fn bind(path: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            bail!("another vibes is listening on {}", path.display());
+        }
+        std::fs::remove_file(path).with_context(|| format!("Failed to remove stale {}", path.display()))?;
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        use std::os::unix::fs::DirBuilderExt;
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        // Anyone can make directories in the temp directory, so one there has
+        // to be ours alone before a socket goes in it
+        if dir != std::env::temp_dir() && dir.starts_with(std::env::temp_dir()) {
+            let mode = std::fs::metadata(dir)?.permissions().mode();
+            if mode & 0o077 != 0 {
+                bail!("{} can be opened by other users; remove it or set control_socket", dir.display());
+            }
+        }
+    }
+    let listener = UnixListener::bind(path).with_context(|| format!("Failed to bind {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+/// Listens on `path` until the app that started it goes away
+#[cfg(unix)]
+pub async fn serve(
+    path: &Path,
+    status: watch::Receiver<RemoteStatus>,
+    actions: mpsc::UnboundedSender<UserAction>,
+) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tracing::{info, warn};
+
+    let listener = bind(path).await?;
+    info!("Control socket at {}", path.display());
+
+    let path = path.to_path_buf();
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = actions.closed() => break,
+            };
+            let Ok((stream, _)) = accepted else { continue };
+            let (status, actions) = (status.clone(), actions.clone());
+            tokio::spawn(async move {
+                let (read, mut write) = stream.into_split();
+                let mut lines = BufReader::new(read).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let current = status.borrow().clone();
+                    let (reply, action) = run(&line, &current);
+                    if let Some(action) = action {
+                        let _ = actions.send(action);
+                    }
+                    let Ok(json) = serde_json::to_string(&reply) else { break };
+                    if let Err(e) = write.write_all(format!("{json}\n").as_bytes()).await {
+                        warn!("Control socket client dropped: {e}");
+                        break;
+                    }
+                }
+            });
+        }
+        let _ = std::fs::remove_file(&path);
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn serve(
+    _path: &Path,
+    _status: watch::Receiver<RemoteStatus>,
+    _actions: mpsc::UnboundedSender<UserAction>,
+) -> Result<()> {
+    bail!("the control socket needs a Unix system")
+}
+
+/// Sends `command` to the vibes listening on `path` and returns its reply,
+/// for `vibes ctl` and widget clicks
+#[cfg(unix)]
+pub fn send(path: &Path, command: &Command) -> Result<serde_json::Value> {
+    use std::io::{BufRead, Write};
+    let stream = std::os::unix::net::UnixStream::connect(path)
+        .with_context(|| format!("vibes isn't running (no control socket at {})", path.display()))?;
+    stream.set_read_timeout(Some(std::time::Duration::from_secs(5)))?;
+    (&stream).write_all(format!("{}\n", serde_json::to_string(command)?).as_bytes())?;
+    let mut line = String::new();
+    std::io::BufReader::new(&stream).read_line(&mut line)?;
+    let reply: serde_json::Value = serde_json::from_str(&line).context("No answer from vibes")?;
+    if reply["ok"] != true {
+        bail!("{}", reply["error"].as_str().unwrap_or("vibes refused the command"));
+    }
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _command: &Command) -> Result<serde_json::Value> {
+    bail!("the control socket needs a Unix system")
+}
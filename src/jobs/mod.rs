@@ -0,0 +1,170 @@
+use anyhow::{bail, Result};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+/// The long operations that run as jobs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// `:export` of the play log
+    Export,
+    /// Enter in `:archived`: following or recreating a playlist
+    Restore,
+    /// X on the playlist list: backing playlists up, then unfollowing them
+    Archive,
+    /// Fetching audio features for the library
+    Index,
+    /// `:dedupe`: loading Liked Songs and saved albums to compare
+    Dedupe,
+}
+
+impl JobKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            JobKind::Export => "Export",
+            JobKind::Restore => "Restore",
+            JobKind::Archive => "Archive",
+            JobKind::Index => "Index",
+            JobKind::Dedupe => "Dedupe scan",
+        }
+    }
+
+    /// What the progress counts, for “3 of 12 …”
+    pub fn unit(self) -> &'static str {
+        match self {
+            JobKind::Export => "plays",
+            JobKind::Restore | JobKind::Index => "tracks",
+            JobKind::Archive => "playlists",
+            JobKind::Dedupe => "steps",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+/// Shared by a job's task and the Jobs panel: progress flows out of the task,
+/// cancellation flows in. The task checks for it between steps, so a step
+/// already under way still finishes.
+#[derive(Debug, Clone, Default)]
+pub struct JobHandle(Arc<Progress>);
+
+impl JobHandle {
+    pub fn set_total(&self, total: usize) {
+        self.0.total.store(total, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, n: usize) {
+        self.0.done.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn done(&self) -> usize {
+        self.0.done.load(Ordering::Relaxed)
+    }
+
+    /// 0 until the task knows how much there is
+    pub fn total(&self) -> usize {
+        self.0.total.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// For `?` between steps: an error once the job was cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            bail!("cancelled");
+        }
+        Ok(())
+    }
+}
+
+pub type JobId = usize;
+
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    /// What it's working on, e.g. a playlist name
+    pub label: String,
+    pub started: Instant,
+    pub handle: JobHandle,
+}
+
+impl Job {
+    /// How far along it is, 0.0–1.0; `None` while the total isn't known
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.handle.total();
+        (total > 0).then(|| self.handle.done().min(total) as f64 / total as f64)
+    }
+
+    /// “3 of 12 playlists”, or just the count so far without a total
+    pub fn progress(&self) -> String {
+        let (done, total, unit) = (self.handle.done(), self.handle.total(), self.kind.unit());
+        if total > 0 {
+            format!("{} of {total} {unit}", done.min(total))
+        } else {
+            format!("{done} {unit}")
+        }
+    }
+}
+
+/// The jobs still running, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct Jobs {
+    running: Vec<Job>,
+    next_id: JobId,
+}
+
+impl Jobs {
+    /// Registers a job; its task gets the handle and reports through it
+    pub fn start(&mut self, kind: JobKind, label: impl Into<String>) -> (JobId, JobHandle) {
+        let id = self.next_id;
+        self.next_id += 1;
+        let handle = JobHandle::default();
+        self.running.push(Job { id, kind, label: label.into(), started: Instant::now(), handle: handle.clone() });
+        (id, handle)
+    }
+
+    /// Drops a job once its task reported back
+    pub fn finish(&mut self, id: JobId) -> Option<Job> {
+        let i = self.running.iter().position(|j| j.id == id)?;
+        Some(self.running.remove(i))
+    }
+
+    /// Asks a job to stop; false when it's already gone or asked
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        match self.running.iter().find(|j| j.id == id) {
+            Some(job) if !job.handle.is_cancelled() => {
+                job.handle.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn running(&self) -> &[Job] {
+        &self.running
+    }
+
+    pub fn is_running(&self, kind: JobKind) -> bool {
+        self.running.iter().any(|j| j.kind == kind)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+}
@@ -0,0 +1,109 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Write},
+    sync::{Mutex, OnceLock},
+};
+use tracing::Level;
+
+/// Log lines kept in memory for the `gl` viewer
+pub const KEPT_LINES: usize = 500;
+
+/// One line of the log; lines continuing a multi-line event carry its level
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub level: Option<Level>,
+    pub text: String,
+}
+
+/// The newest `KEPT_LINES` log lines
+#[derive(Debug, Default)]
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    /// Lines ever pushed, so a reader can tell whether there's anything new
+    written: u64,
+    /// The end of a write that didn't finish its line
+    partial: String,
+}
+
+impl LogBuffer {
+    /// Adds the complete lines in `text`, holding on to an unfinished one
+    /// until the rest of it arrives
+    pub fn push(&mut self, text: &str) {
+        self.partial.push_str(text);
+        while let Some(end) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=end).collect();
+            let text = line.trim_end().to_string();
+            let level = parse_level(&text).or_else(|| self.lines.back().and_then(|l| l.level));
+            if self.lines.len() == KEPT_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(LogLine { level, text });
+            self.written += 1;
+        }
+    }
+
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// The kept lines, oldest first
+    pub fn lines(&self) -> Vec<LogLine> {
+        self.lines.iter().cloned().collect()
+    }
+}
+
+/// The level of a line as the fmt subscriber writes it
+/// (`2024-05-01T12:00:00.000000Z  WARN vibes::app: …`), if it starts one
+pub fn parse_level(line: &str) -> Option<Level> {
+    let mut words = line.split_whitespace();
+    let timestamp = words.next()?;
+    if !timestamp.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    words.next()?.parse().ok()
+}
+
+/// What this process has logged
+fn buffer() -> &'static Mutex<LogBuffer> {
+    static BUFFER: OnceLock<Mutex<LogBuffer>> = OnceLock::new();
+    BUFFER.get_or_init(Default::default)
+}
+
+/// Lines logged so far; compare with a previous `written` to skip copying
+pub fn written() -> u64 {
+    buffer().lock().map(|b| b.written()).unwrap_or_default()
+}
+
+/// `written` and the kept lines, oldest first
+pub fn recent() -> (u64, Vec<LogLine>) {
+    buffer().lock().map(|b| (b.written(), b.lines())).unwrap_or_default()
+}
+
+/// The log writer: everything goes to the log file, when there is one,
+/// and to the in-memory buffer the viewer shows
+pub struct Tee {
+    file: Option<File>,
+}
+
+impl Tee {
+    pub fn new(file: Option<File>) -> Self {
+        Tee { file }
+    }
+}
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Ok(mut lines) = buffer().lock() {
+            lines.push(&String::from_utf8_lossy(buf));
+        }
+        match self.file {
+            Some(ref mut file) => file.write_all(buf).map(|_| buf.len()),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), File::flush)
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+/// One timed line of synced lyrics
+pub type LyricLine = (u32, String);
+
+const LRCLIB_URL: &str = "https://lrclib.net/api/get";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LrclibTrack {
+    synced_lyrics: Option<String>,
+}
+
+/// Looks up time-synced lyrics on lrclib.net (free, no API key).
+/// `Ok(None)` when the track isn't there or only has plain lyrics.
+pub async fn fetch_synced(artist: &str, title: &str, album: &str, duration_ms: u32) -> Result<Option<Vec<LyricLine>>> {
+    let duration = (duration_ms / 1000).to_string();
+    let resp = reqwest::Client::new()
+        .get(LRCLIB_URL)
+        .header("User-Agent", concat!("vibes/", env!("CARGO_PKG_VERSION")))
+        .query(&[
+            ("artist_name", artist),
+            ("track_name", title),
+            ("album_name", album),
+            ("duration", duration.as_str()),
+        ])
+        .send()
+        .await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = resp.error_for_status()?.text().await?;
+    let track: LrclibTrack = serde_json::from_str(&body)?;
+    Ok(track.synced_lyrics.map(|lrc| parse_lrc(&lrc)).filter(|l| !l.is_empty()))
+}
+
+/// Parses LRC text (`[mm:ss.xx] line`, possibly several tags per line)
+/// into lines sorted by timestamp. Metadata tags like `[ar:...]` are skipped.
+pub fn parse_lrc(lrc: &str) -> Vec<LyricLine> {
+    let mut lines = Vec::new();
+    for raw in lrc.lines() {
+        let mut rest = raw.trim();
+        let mut stamps = Vec::new();
+        while let Some(tag) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+            let (stamp, after) = tag;
+            match parse_stamp(stamp) {
+                Some(ms) => stamps.push(ms),
+                None => break,
+            }
+            rest = after;
+        }
+        let text = rest.trim();
+        lines.extend(stamps.into_iter().map(|ms| (ms, text.to_string())));
+    }
+    lines.sort_by_key(|(ms, _)| *ms);
+    lines
+}
+
+/// `mm:ss.xx` in ms; None for anything negative or past `u32::MAX`
+fn parse_stamp(stamp: &str) -> Option<u32> {
+    let (min, sec) = stamp.split_once(':')?;
+    let min: u32 = min.parse().ok()?;
+    let sec_ms = (sec.parse::<f64>().ok()? * 1000.0).round();
+    if !(0.0..=f64::from(u32::MAX)).contains(&sec_ms) {
+        return None;
+    }
+    min.checked_mul(60_000)?.checked_add(sec_ms as u32)
+}
+
+/// Index of the line being sung at `progress_ms`
+pub fn current_line(lines: &[LyricLine], progress_ms: u32) -> Option<usize> {
+    lines.partition_point(|(ms, _)| *ms <= progress_ms).checked_sub(1)
+}
@@ -1,19 +1,43 @@
 mod app;
+mod artwork;
 mod cache;
+mod capture;
+mod clipboard;
+mod collate;
 mod config;
+mod connect;
+mod daemon;
 mod events;
+mod features;
+mod hooks;
+mod index;
+mod ipc;
+mod jobs;
+mod logs;
+mod lyrics;
+mod mpd;
+mod paths;
+mod quotes;
+mod remote;
+mod secrets;
+mod session;
 mod spotify;
 mod ui;
+mod widget;
 #[cfg(test)]
 mod tests;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use crossterm::{
+    event::EventStream,
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use futures::StreamExt;
 use std::{io, sync::Arc};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::error;
 use tracing_subscriber::{fmt, EnvFilter};
 
@@ -21,43 +45,57 @@ use crate::{
     app::App,
     cache::Cache,
     config::Config,
+    daemon::{AttachBackend, Screen},
+    features::{Feature, Features},
+    remote::RemoteStatus,
+    session::{inbox, NowPlaying, QueueEntry},
+    spotify::{search::Search, TokenStore},
+    ipc::Command,
+    widget::{Click, WidgetFormat},
 };
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // ── Logging setup ────────────────────────────────────────────────────────
-    let filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new("vibes=info"));
-    // Write logs to file so they don't corrupt the TUI
-    let log_file = std::fs::File::create("/tmp/vibes.log").ok();
-    if let Some(file) = log_file {
-        fmt()
-            .with_env_filter(filter)
-            .with_writer(std::sync::Mutex::new(file))
-            .init();
+    // ── Subcommands run without the TUI (and leave its log alone) ────────────
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let profile = config::take_profile_flag(&mut args)?;
+    let json = config::take_flag(&mut args, "--json");
+    let mini = config::take_flag(&mut args, "--mini");
+    let daemon = args == ["daemon"];
+    if !args.is_empty() && !daemon {
+        return run_command(&Config::load_profile(profile.as_deref())?, &args, json).await;
     }
 
-    // ── Load config ──────────────────────────────────────────────────────────
-    let config = Config::load()?;
-    let redis_url = config.redis_url.clone();
-
-    // ── Try Redis (optional — app works without it) ──────────────────────────
-    let cache = Arc::new(match Cache::new(&redis_url) {
-        Ok(c) => {
-            if c.ping().await {
-                tracing::info!("Redis connected at {redis_url}");
-                c
-            } else {
-                tracing::warn!("Redis not reachable — token caching disabled");
-                // Use a no-op cache key prefix so it silently fails
-                Cache::new("redis://127.0.0.1:0").unwrap_or_else(|_| Cache::new("redis://127.0.0.1:6379").unwrap())
-            }
-        }
-        Err(_) => {
-            tracing::warn!("Redis unavailable — running without token cache");
-            Cache::new("redis://127.0.0.1:6379").unwrap()
+    // ── A `vibes daemon` is already running: show that instead ──────────────
+    if !daemon {
+        let attach = daemon::attach_path(&Config::load_profile(profile.as_deref())?.control_socket_path());
+        if daemon::attach(&attach).await? {
+            return Ok(());
         }
+    }
+
+    // ── Logging setup ────────────────────────────────────────────────────────
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("vibes=info"));
+    // Write logs to file so they don't corrupt the TUI, keeping the latest
+    // lines for the in-app viewer (`gl`)
+    let log_path = Config::load_profile(profile.as_deref())?.log_path();
+    let log_file = log_path.as_deref().and_then(|path| {
+        std::fs::create_dir_all(path.parent()?).ok()?;
+        std::fs::File::create(path).ok()
     });
+    fmt()
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .with_writer(std::sync::Mutex::new(logs::Tee::new(log_file)))
+        .init();
+
+    // ── Load config, connect Redis ───────────────────────────────────────────
+    let (mut config, cache) = connect(profile.as_deref()).await?;
+    config.mini = mini;
+    if daemon {
+        return run_daemon(config, cache).await;
+    }
 
     // ── Terminal setup ────────────────────────────────────────────────────────
     enable_raw_mode()?;
@@ -75,10 +113,7 @@ async fn main() -> Result<()> {
     }));
 
     // ── Run the app ──────────────────────────────────────────────────────────
-    let result = {
-        let mut app = App::new(config, cache).await?;
-        app.run(&mut terminal).await
-    };
+    let result = run_app(&mut terminal, &mut EventStream::new(), None, config, cache).await;
 
     // ── Restore terminal ─────────────────────────────────────────────────────
     disable_raw_mode()?;
@@ -88,8 +123,208 @@ async fn main() -> Result<()> {
     if let Err(e) = result {
         error!("App error: {e:?}");
         eprintln!("\n\x1b[31mvibes crashed:\x1b[0m {e}");
-        eprintln!("Check /tmp/vibes.log for details");
+        if let Some(path) = log_path {
+            eprintln!("Check {} for details", path.display());
+        }
     }
 
     Ok(())
 }
+
+/// Config for `profile`, with the client secret resolved, and the Redis
+/// cache scoped to it
+async fn connect(profile: Option<&str>) -> Result<(Config, Arc<Cache>)> {
+    let mut config = Config::load_profile(profile)?;
+    let keyring = Features::from_config(&config.features).enabled(Feature::Keyring);
+    spotify::resolve_client_secret(&mut config, keyring).await?;
+    let redis_url = config.redis_url.clone();
+
+    // ── Try Redis (optional — app works without it) ──────────────────────────
+    let cache = match Cache::new(&redis_url) {
+        Ok(c) => {
+            if c.ping().await {
+                tracing::info!("Redis connected at {redis_url}");
+                c
+            } else {
+                tracing::warn!("Redis not reachable — token caching disabled");
+                // Use a no-op cache key prefix so it silently fails
+                Cache::new("redis://127.0.0.1:0").unwrap_or_else(|_| Cache::new("redis://127.0.0.1:6379").unwrap())
+            }
+        }
+        Err(_) => {
+            tracing::warn!("Redis unavailable — running without token cache");
+            Cache::new("redis://127.0.0.1:6379").unwrap()
+        }
+    };
+    let cache = Arc::new(cache.for_profile(config.profile.as_deref()));
+    Ok((config, cache))
+}
+
+/// Runs the app, and runs it again logged in as another profile for as long
+/// as `:profile` asks for one
+async fn run_app<B, E>(terminal: &mut Terminal<B>, events: &mut E, daemon: Option<Screen>, config: Config, cache: Arc<Cache>) -> Result<()>
+where
+    B: ratatui::backend::Backend,
+    E: futures::Stream<Item = io::Result<crossterm::event::Event>> + Unpin,
+{
+    let (mut config, mut cache) = (config, cache);
+    loop {
+        let mut app = App::new(config, cache).await?;
+        app.daemon = daemon.clone();
+        app.run(terminal, events).await?;
+        let Some(profile) = app.switch_profile.take() else { return Ok(()) };
+        drop(app);
+        terminal.clear()?;
+        (config, cache) = connect(Some(&profile)).await?;
+    }
+}
+
+/// `vibes daemon`: the app without a terminal of its own, so playback
+/// tracking, scrobbling, the servers and the caches outlive any terminal.
+/// `vibes attach` (or plain `vibes`) shows it; `q` there only detaches.
+async fn run_daemon(config: Config, cache: Arc<Cache>) -> Result<()> {
+    let path = daemon::attach_path(&config.control_socket_path());
+    let screen = Screen::default();
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    daemon::serve(&path, screen.clone(), event_tx).await?;
+    println!("vibes daemon running; `vibes attach` opens the player, `vibes ctl quit` stops it");
+
+    let mut terminal = Terminal::new(AttachBackend::new(screen.clone()))?;
+    let mut events = UnboundedReceiverStream::new(event_rx).map(Ok);
+    let result = run_app(&mut terminal, &mut events, Some(screen), config, cache).await;
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// What the running vibes is playing, its volume and queue, as `ctl status`
+/// returns them; a stopped one when vibes isn't running
+fn running_status(config: &Config) -> serde_json::Value {
+    match ipc::send(&config.control_socket_path(), &Command::Status) {
+        Ok(mut reply) => reply["status"].take(),
+        Err(_) => serde_json::to_value(RemoteStatus::default()).unwrap_or_default(),
+    }
+}
+
+/// `status` as `template` fills it in; empty when nothing is playing, or
+/// vibes isn't running, so a bar just shows nothing
+fn status_line(status: &serde_json::Value, template: &str) -> String {
+    let now: Option<NowPlaying> = serde_json::from_value(status["now_playing"].clone()).ok().flatten();
+    let volume = status["volume"].as_u64().unwrap_or(0).min(100) as u8;
+    widget::status_line(template, now.as_ref(), volume)
+}
+
+/// `vibes search`: tracks matching `query`. Logs in with the saved token
+/// rather than a browser, so vibes has to have been logged in once.
+async fn search_tracks(config: &Config, query: &str) -> Result<Vec<QueueEntry>> {
+    let (config, cache) = connect(config.profile.as_deref()).await?;
+    let keyring = Features::from_config(&config.features).enabled(Feature::Keyring).then(|| secrets::token_entry(&config));
+    let tokens = TokenStore { cache: &cache, keyring };
+    let (spotify, logged_in) = spotify::build_spotify_client(&config, &tokens).await?;
+    if !logged_in {
+        bail!("Not logged in yet; start vibes once to log in");
+    }
+    let tracks = Search::new(spotify).search_tracks(query, 20).await?;
+    Ok(tracks.iter().map(QueueEntry::from_track).collect())
+}
+
+fn print_json(value: &impl serde::Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// `vibes inbox add <link> [note…]` files a shared track for later;
+/// `vibes inbox` lists what's waiting. `vibes widget --format waybar|polybar`
+/// feeds a status bar from the session file, and `vibes widget click
+/// toggle|next|prev` is what its clicks run. `vibes status [--format …]`
+/// prints one line about what's playing, for tmux and bars that poll;
+/// `vibes queue` lists what's coming up and `vibes search <query…>` finds
+/// tracks. With `--json`, those, `inbox` and `ctl` print JSON instead. `vibes ctl <command>` drives a
+/// running vibes through its control socket, and `vibes attach` shows a
+/// running `vibes daemon`. `vibes keyring set` stores the
+/// client secret read from stdin; `vibes keyring clear` removes it and the
+/// login token.
+async fn run_command(config: &Config, args: &[String], json: bool) -> Result<()> {
+    let path = || config.inbox_file.clone().or_else(inbox::default_path).context("No data directory for the inbox");
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["widget", "--format", format] => {
+            let format: WidgetFormat = format.parse()?;
+            let session = config.session_file.as_ref().context("The widget follows session_file; set it in config.toml")?;
+            widget::follow(session, format)?;
+        }
+        ["widget", "click", action] => {
+            let click: Click = action.parse()?;
+            ipc::send(&config.control_socket_path(), &click.command())?;
+        }
+        ["status"] | ["status", "--format", _] if json => print_json(&running_status(config))?,
+        ["status"] => println!("{}", status_line(&running_status(config), &config.status_format)),
+        ["status", "--format", format] => println!("{}", status_line(&running_status(config), format)),
+        ["queue"] => {
+            let reply = ipc::send(&config.control_socket_path(), &Command::Status)?;
+            let queue: Vec<QueueEntry> = serde_json::from_value(reply["status"]["queue"].clone())?;
+            if json {
+                print_json(&queue)?;
+            } else {
+                for entry in &queue {
+                    println!("{} – {}", entry.artists.join(", "), entry.name);
+                }
+            }
+        }
+        ["search", words @ ..] if !words.is_empty() => {
+            let tracks = search_tracks(config, &words.join(" ")).await?;
+            if json {
+                print_json(&tracks)?;
+            } else {
+                for track in &tracks {
+                    println!("{} – {}  {}", track.artists.join(", "), track.name, track.id.as_deref().unwrap_or_default());
+                }
+            }
+        }
+        ["ctl", rest @ ..] => {
+            let command = Command::from_args(rest)?;
+            let reply = ipc::send(&config.control_socket_path(), &command)?;
+            if json {
+                print_json(&reply)?;
+            } else if command == Command::Status {
+                print_json(&reply["status"])?;
+            }
+        }
+        ["attach"] => {
+            if !daemon::attach(&daemon::attach_path(&config.control_socket_path())).await? {
+                bail!("No vibes daemon is running; start one with `vibes daemon`");
+            }
+        }
+        ["keyring", "set"] => {
+            let mut secret = String::new();
+            io::stdin().read_line(&mut secret)?;
+            let secret = secret.trim();
+            if secret.is_empty() {
+                bail!("No client secret on stdin");
+            }
+            secrets::set(&secrets::client_secret_entry(config), secret.to_string()).await?;
+            println!("Client secret stored in the keyring");
+        }
+        ["keyring", "clear"] => {
+            secrets::delete(&secrets::client_secret_entry(config)).await?;
+            secrets::delete(&secrets::token_entry(config)).await?;
+            println!("Client secret and login token removed from the keyring");
+        }
+        ["inbox", "add", link, note @ ..] => {
+            let note = (!note.is_empty()).then(|| note.join(" "));
+            let item = inbox::add(&path()?, link, note)?;
+            if json {
+                print_json(&item)?;
+            } else {
+                println!("Added {} to the inbox", item.uri);
+            }
+        }
+        ["inbox"] | ["inbox", "list"] if json => print_json(&inbox::load(&path()?)?)?,
+        ["inbox"] | ["inbox", "list"] => {
+            for item in inbox::load(&path()?)? {
+                let note = item.note.map(|n| format!("  {n}")).unwrap_or_default();
+                println!("{}  {}{note}", item.added_at.format("%Y-%m-%d"), item.uri);
+            }
+        }
+        _ => bail!("Usage: vibes [inbox [list | add <link> [note…]] | widget (--format waybar|polybar | click toggle|next|prev) | status [--format <template>] | queue | search <query…> | ctl <command> | daemon | attach | keyring set|clear] [--profile <name>] [--json] [--mini]"),
+    }
+    Ok(())
+}
@@ -0,0 +1,410 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::Write as _,
+    hash::{Hash, Hasher},
+    net::SocketAddr,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, watch},
+    time,
+};
+use tracing::{info, warn};
+
+use crate::{events::UserAction, remote::RemoteStatus, session::QueueEntry};
+
+/// Announced in the greeting; recent enough that clients stick to the
+/// commands below
+pub const PROTOCOL_VERSION: &str = "0.23.5";
+
+/// Commands answered here, for `commands`
+const COMMANDS: &[&str] = &[
+    "close", "command_list_begin", "command_list_end", "command_list_ok_begin", "commands", "currentsong", "idle",
+    "listplaylists", "lsinfo", "next", "noidle", "notcommands", "outputs", "password", "pause", "ping", "play",
+    "playid", "playlistid", "playlistinfo", "plchanges", "previous", "seekcur", "status", "stop", "tagtypes",
+    "urlhandlers",
+];
+
+/// Longest command line taken; MPD's own commands are far shorter
+const MAX_LINE: usize = 4096;
+/// Most commands one command list may collect
+const MAX_LIST: usize = 1024;
+/// A client that sends nothing for this long is dropped, as MPD does; one
+/// in `idle` may wait as long as it likes
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Pause after a failed accept (out of file descriptors, say) before the next
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// `ACK` error codes from MPD's `ack.h`
+const ACK_ERROR_ARG: u32 = 2;
+const ACK_ERROR_UNKNOWN: u32 = 5;
+const ACK_ERROR_NO_EXIST: u32 = 50;
+
+/// A failed command, sent as `ACK [code@index] {command} message`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ack {
+    pub code: u32,
+    pub message: String,
+}
+
+impl Ack {
+    fn new(code: u32, message: impl Into<String>) -> Self {
+        Ack { code, message: message.into() }
+    }
+
+    /// `index` is the command's position in a command list, 0 outside one
+    pub fn line(&self, index: usize, command: &str) -> String {
+        format!("ACK [{}@{index}] {{{command}}} {}\n", self.code, self.message)
+    }
+}
+
+/// Splits a command line into words. Arguments can be double-quoted, with
+/// `\"` and `\\` escapes inside, as clients send anything with spaces.
+pub fn parse_line(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut word = String::new();
+        if c == '"' {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' => break,
+                    '\\' => word.extend(chars.next()),
+                    c => word.push(c),
+                }
+            }
+        } else {
+            while let Some(&c) = chars.peek().filter(|c| !c.is_whitespace()) {
+                word.push(c);
+                chars.next();
+            }
+        }
+        words.push(word);
+    }
+    words
+}
+
+/// MPD's playlist as vibes sees it: what's playing at position 0, then the
+/// upcoming Spotify queue
+fn songs(status: &RemoteStatus) -> Vec<QueueEntry> {
+    let current = status.now_playing.as_ref().map(|np| QueueEntry {
+        id: np.id.clone(),
+        name: np.name.clone(),
+        artists: np.artists.clone(),
+        duration_ms: np.duration_ms,
+    });
+    current.into_iter().chain(status.queue.iter().cloned()).collect()
+}
+
+/// Changes whenever the songs do, so clients know to fetch them again
+fn playlist_version(songs: &[QueueEntry]) -> u32 {
+    let mut hasher = DefaultHasher::new();
+    for song in songs {
+        song.id.hash(&mut hasher);
+    }
+    (hasher.finish() as u32).max(1)
+}
+
+fn song_block(out: &mut String, pos: usize, song: &QueueEntry, album: Option<&str>) {
+    let _ = writeln!(out, "file: {}", song.id.as_deref().unwrap_or("spotify:local"));
+    if !song.artists.is_empty() {
+        let _ = writeln!(out, "Artist: {}", song.artists.join(", "));
+    }
+    let _ = writeln!(out, "Title: {}", song.name);
+    if let Some(album) = album.filter(|a| !a.is_empty()) {
+        let _ = writeln!(out, "Album: {album}");
+    }
+    let _ = writeln!(out, "Time: {}", song.duration_ms / 1000);
+    let _ = writeln!(out, "duration: {:.3}", song.duration_ms as f64 / 1000.0);
+    // Ids are positions + 1: the list is rebuilt from Spotify on every change
+    let _ = writeln!(out, "Pos: {pos}");
+    let _ = writeln!(out, "Id: {}", pos + 1);
+}
+
+fn number(arg: Option<&String>) -> Result<Option<f64>, Ack> {
+    arg.map(|a| a.parse::<f64>().map_err(|_| Ack::new(ACK_ERROR_ARG, format!("Number expected: {a}")))).transpose()
+}
+
+/// Answers one command: the response lines (without the final `OK`) and
+/// the action for the app to run, if any. Session commands — `idle`,
+/// `close` and command lists — are the server's business, not this.
+pub fn run(args: &[String], status: &RemoteStatus) -> Result<(String, Option<UserAction>), Ack> {
+    let Some(command) = args.first() else { return Err(Ack::new(ACK_ERROR_UNKNOWN, "No command given")) };
+    let songs = songs(status);
+    let now = status.now_playing.as_ref();
+    let playing = now.is_some_and(|np| np.is_playing);
+    let album = now.map(|np| np.album.as_str());
+    let mut out = String::new();
+    let action = match command.as_str() {
+        "ping" | "password" | "notcommands" | "listplaylists" | "lsinfo" => None,
+        "commands" => {
+            for c in COMMANDS {
+                let _ = writeln!(out, "command: {c}");
+            }
+            None
+        }
+        "tagtypes" => {
+            out.push_str("tagtype: Artist\ntagtype: Album\ntagtype: Title\n");
+            None
+        }
+        "urlhandlers" => {
+            out.push_str("handler: spotify:\n");
+            None
+        }
+        "outputs" => {
+            out.push_str("outputid: 0\noutputname: Spotify Connect\nplugin: spotify\noutputenabled: 1\n");
+            None
+        }
+        "status" => {
+            let _ = writeln!(out, "volume: {}", status.volume);
+            out.push_str("repeat: 0\nrandom: 0\nsingle: 0\nconsume: 0\n");
+            let _ = writeln!(out, "playlist: {}", playlist_version(&songs));
+            let _ = writeln!(out, "playlistlength: {}", songs.len());
+            let state = match now {
+                None => "stop",
+                Some(np) if np.is_playing => "play",
+                Some(_) => "pause",
+            };
+            let _ = writeln!(out, "state: {state}");
+            if let Some(np) = now {
+                let (elapsed, duration) = (np.progress_ms as f64 / 1000.0, np.duration_ms as f64 / 1000.0);
+                out.push_str("song: 0\nsongid: 1\n");
+                let _ = writeln!(out, "time: {}:{}", np.progress_ms / 1000, np.duration_ms / 1000);
+                let _ = writeln!(out, "elapsed: {elapsed:.3}\nduration: {duration:.3}");
+                if songs.len() > 1 {
+                    out.push_str("nextsong: 1\nnextsongid: 2\n");
+                }
+            }
+            None
+        }
+        "currentsong" => {
+            if let Some(song) = songs.first().filter(|_| now.is_some()) {
+                song_block(&mut out, 0, song, album);
+            }
+            None
+        }
+        "playlistinfo" | "playlistid" | "plchanges" => {
+            // plchanges' version is ignored: everything counts as changed
+            let wanted = match command.as_str() {
+                "playlistinfo" => number(args.get(1))?.map(|pos| pos as usize),
+                "playlistid" => number(args.get(1))?.map(|id| (id as usize).saturating_sub(1)),
+                _ => None,
+            };
+            if wanted.is_some_and(|pos| pos >= songs.len()) {
+                return Err(Ack::new(ACK_ERROR_NO_EXIST, "No such song"));
+            }
+            for (pos, song) in songs.iter().enumerate().filter(|(pos, _)| wanted.is_none_or(|w| w == *pos)) {
+                song_block(&mut out, pos, song, album.filter(|_| pos == 0));
+            }
+            None
+        }
+        "play" | "playid" => {
+            // Only the current song can be started; skipping ahead isn't in Spotify's queue API
+            let first = if command == "play" { 0.0 } else { 1.0 };
+            if number(args.get(1))?.is_some_and(|n| n != first) {
+                return Err(Ack::new(ACK_ERROR_ARG, "vibes can only play the current song"));
+            }
+            (!playing && now.is_some()).then_some(UserAction::TogglePlay)
+        }
+        "pause" => match args.get(1).map(String::as_str) {
+            None => now.map(|_| UserAction::TogglePlay),
+            Some("1") => playing.then_some(UserAction::TogglePlay),
+            Some("0") => (!playing && now.is_some()).then_some(UserAction::TogglePlay),
+            Some(other) => return Err(Ack::new(ACK_ERROR_ARG, format!("Boolean (0/1) expected: {other}"))),
+        },
+        "stop" => playing.then_some(UserAction::TogglePlay),
+        "next" => Some(UserAction::NextTrack),
+        "previous" => Some(UserAction::PrevTrack),
+        "seekcur" => {
+            let Some(arg) = args.get(1) else { return Err(Ack::new(ACK_ERROR_ARG, "Missing argument")) };
+            if arg.starts_with(['+', '-']) {
+                return Err(Ack::new(ACK_ERROR_ARG, "Relative seeks aren't supported"));
+            }
+            let secs = number(Some(arg))?.unwrap_or_default();
+            let duration = now.map_or(0, |np| np.duration_ms) as f64 / 1000.0;
+            (duration > 0.0).then(|| UserAction::SeekPercent((secs / duration * 100.0).clamp(0.0, 100.0) as u8))
+        }
+        other => return Err(Ack::new(ACK_ERROR_UNKNOWN, format!("unknown command \"{other}\""))),
+    };
+    Ok((out, action))
+}
+
+/// The `idle` subsystems that differ between two snapshots. Playback
+/// progress ticking along doesn't count; a seek does.
+pub fn changed_subsystems(before: &RemoteStatus, after: &RemoteStatus) -> Vec<&'static str> {
+    let mut changed = Vec::new();
+    let track = |s: &RemoteStatus| s.now_playing.as_ref().map(|np| (np.id.clone(), np.is_playing));
+    let seeked = match (&before.now_playing, &after.now_playing) {
+        (Some(a), Some(b)) => b.progress_ms < a.progress_ms || b.progress_ms > a.progress_ms + 10_000,
+        _ => false,
+    };
+    if track(before) != track(after) || seeked {
+        changed.push("player");
+    }
+    if before.volume != after.volume {
+        changed.push("mixer");
+    }
+    if before.queue != after.queue || track(before).map(|t| t.0) != track(after).map(|t| t.0) {
+        changed.push("playlist");
+    }
+    changed
+}
+
+/// Binds `addr` and speaks MPD there until the app exits
+pub async fn serve(
+    addr: SocketAddr,
+    status: watch::Receiver<RemoteStatus>,
+    actions: mpsc::UnboundedSender<UserAction>,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr} for the MPD server"))?;
+    info!("MPD server listening on {addr}");
+
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                // The app went away (`:profile` starts another): free the port
+                _ = actions.closed() => break,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("MPD server accept failed: {e}");
+                    time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let (status, actions) = (status.clone(), actions.clone());
+            tokio::spawn(async move {
+                if let Err(e) = session(stream, status, &actions).await {
+                    warn!("MPD client {peer} dropped: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+/// A command list being collected; `ok` for `command_list_ok_begin`
+struct CommandList {
+    ok: bool,
+    commands: Vec<Vec<String>>,
+}
+
+/// The next line without its line ending, `None` once the client hangs up.
+/// Fails on a line longer than `MAX_LINE` instead of buffering it all. What
+/// was read so far stays in `buf`, so a `select!` may drop the future.
+async fn next_line<R: AsyncBufRead + Unpin>(reader: &mut R, buf: &mut Vec<u8>) -> Result<Option<String>> {
+    let limit = (MAX_LINE + 1).saturating_sub(buf.len()) as u64;
+    let read = (&mut *reader).take(limit).read_until(b'\n', buf).await?;
+    if buf.len() > MAX_LINE {
+        bail!("line longer than {MAX_LINE} bytes");
+    }
+    if read == 0 && buf.is_empty() {
+        return Ok(None);
+    }
+    let line = String::from_utf8(std::mem::take(buf)).context("line isn't UTF-8")?;
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+async fn session(stream: TcpStream, mut status: watch::Receiver<RemoteStatus>, actions: &mpsc::UnboundedSender<UserAction>) -> Result<()> {
+    let (read, mut write) = stream.into_split();
+    let (mut lines, mut buf) = (BufReader::new(read), Vec::new());
+    write.write_all(format!("OK MPD {PROTOCOL_VERSION}\n").as_bytes()).await?;
+
+    let mut list: Option<CommandList> = None;
+    loop {
+        let Ok(line) = time::timeout(READ_TIMEOUT, next_line(&mut lines, &mut buf)).await else {
+            bail!("nothing sent for {}s", READ_TIMEOUT.as_secs());
+        };
+        let Some(line) = line? else { break };
+        let args = parse_line(&line);
+        let command = args.first().map(String::as_str).unwrap_or("");
+        if let Some(collecting) = list.as_mut() {
+            if command != "command_list_end" {
+                if collecting.commands.len() == MAX_LIST {
+                    bail!("command list longer than {MAX_LIST} commands");
+                }
+                collecting.commands.push(args);
+                continue;
+            }
+        }
+        let reply = match command {
+            "close" => return Ok(()),
+            "command_list_begin" | "command_list_ok_begin" => {
+                list = Some(CommandList { ok: command == "command_list_ok_begin", commands: Vec::new() });
+                continue;
+            }
+            "command_list_end" => match list.take() {
+                Some(collected) => run_list(&collected, &status.borrow(), actions),
+                None => Ack::new(ACK_ERROR_ARG, "not in a command list").line(0, command),
+            },
+            "idle" => {
+                let wanted: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+                let before = status.borrow_and_update().clone();
+                loop {
+                    tokio::select! {
+                        changed = status.changed() => {
+                            changed?;
+                            let changes: Vec<_> = changed_subsystems(&before, &status.borrow_and_update())
+                                .into_iter()
+                                .filter(|s| wanted.is_empty() || wanted.contains(s))
+                                .collect();
+                            if !changes.is_empty() {
+                                break changes.iter().map(|s| format!("changed: {s}\n")).collect::<String>() + "OK\n";
+                            }
+                        }
+                        // Anything else while idling ends it (only `noidle` is allowed)
+                        line = next_line(&mut lines, &mut buf) => match line? {
+                            Some(_) => break "OK\n".to_string(),
+                            None => return Ok(()),
+                        },
+                    }
+                }
+            }
+            "noidle" => continue,
+            _ => match run(&args, &status.borrow()) {
+                Ok((out, action)) => {
+                    if let Some(action) = action {
+                        let _ = actions.send(action);
+                    }
+                    out + "OK\n"
+                }
+                Err(ack) => ack.line(0, command),
+            },
+        };
+        write.write_all(reply.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+/// Runs a command list: stops at the first failure, whose `ACK` carries its
+/// position; `list_OK` follows each command in an `ok` list
+fn run_list(list: &CommandList, status: &RemoteStatus, actions: &mpsc::UnboundedSender<UserAction>) -> String {
+    let mut out = String::new();
+    for (i, args) in list.commands.iter().enumerate() {
+        match run(args, status) {
+            Ok((lines, action)) => {
+                if let Some(action) = action {
+                    let _ = actions.send(action);
+                }
+                out.push_str(&lines);
+                if list.ok {
+                    out.push_str("list_OK\n");
+                }
+            }
+            Err(ack) => return out + &ack.line(i, args.first().map_or("", String::as_str)),
+        }
+    }
+    out + "OK\n"
+}
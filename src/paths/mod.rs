@@ -0,0 +1,56 @@
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+/// The platform's places for vibes: the XDG directories on Linux,
+/// `~/Library/…` on macOS, `%APPDATA%` and `%LOCALAPPDATA%` on Windows
+fn project() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "vibes")
+}
+
+/// `$<var>/vibes` when the XDG variable is set, whatever the platform
+fn xdg(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var).filter(|v| !v.is_empty()).map(|base| PathBuf::from(base).join("vibes"))
+}
+
+/// `~/<under>/vibes`, where earlier versions kept files on every platform,
+/// if it's there; on Linux it's the same place anyway
+fn legacy(under: &str) -> Option<PathBuf> {
+    let dir = PathBuf::from(std::env::var_os("HOME")?).join(under).join("vibes");
+    dir.is_dir().then_some(dir)
+}
+
+/// Holds `config.toml`: `$XDG_CONFIG_HOME/vibes`, else `~/.config/vibes` on
+/// Linux (and wherever that already exists), `~/Library/Application
+/// Support/vibes` on macOS, `%APPDATA%\vibes\config` on Windows
+pub fn config_dir() -> Option<PathBuf> {
+    xdg("XDG_CONFIG_HOME")
+        .or_else(|| legacy(".config"))
+        .or_else(|| Some(project()?.config_dir().to_path_buf()))
+}
+
+/// Holds the play log and the files kept next to it: `$XDG_DATA_HOME/vibes`,
+/// else `~/.local/share/vibes` on Linux (and wherever that already exists),
+/// `~/Library/Application Support/vibes` on macOS, `%APPDATA%\vibes\data` on
+/// Windows
+pub fn data_dir() -> Option<PathBuf> {
+    xdg("XDG_DATA_HOME")
+        .or_else(|| legacy(".local/share"))
+        .or_else(|| Some(project()?.data_dir().to_path_buf()))
+}
+
+/// Files that can be thrown away: `$XDG_CACHE_HOME/vibes`, else
+/// `~/.cache/vibes` on Linux, `~/Library/Caches/vibes` on macOS,
+/// `%LOCALAPPDATA%\vibes\cache` on Windows
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg("XDG_CACHE_HOME").or_else(|| Some(project()?.cache_dir().to_path_buf()))
+}
+
+/// Where the log goes unless `log_file` says otherwise: `vibes.log` in
+/// `$XDG_STATE_HOME/vibes` (`~/.local/state/vibes` on Linux), or in the
+/// cache directory on platforms without a state directory
+pub fn log_file() -> Option<PathBuf> {
+    let dir = xdg("XDG_STATE_HOME")
+        .or_else(|| project()?.state_dir().map(PathBuf::from))
+        .or_else(cache_dir)?;
+    Some(dir.join("vibes.log"))
+}
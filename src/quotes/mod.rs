@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::session::{plays, write_atomic};
+
+/// Shown when no other source has anything
+pub const BUILTIN: &[&str] = &[
+    "\"Music is the universal language of mankind.\"\n  – Henry Wadsworth Longfellow",
+    "\"Where words fail, music speaks.\"\n  – Hans Christian Andersen",
+    "\"Without music, life would be a mistake.\"\n  – Friedrich Nietzsche",
+    "\"Music gives a soul to the universe.\"\n  – Plato",
+    "\"One good thing about music, when it hits you, you feel no pain.\"\n  – Bob Marley",
+    "\"Music can change the world because it can change people.\"\n  – Bono",
+    "\"I don't sing because I'm happy; I'm happy because I sing.\"\n  – William James",
+    "\"Music is life itself.\"\n  – Louis Armstrong",
+    "\"Life is like a beautiful melody, only the lyrics are messed up.\"\n  – Hans Christian Andersen",
+    "\"No matter what you're going through, there's a song for that.\"",
+];
+
+/// Music-history facts as (month, day, year, what happened)
+const FACTS: &[(u32, u32, i32, &str)] = &[
+    (1, 1, 1962, "The Beatles audition for Decca Records, who turn them down"),
+    (1, 8, 1935, "Elvis Presley is born in Tupelo, Mississippi"),
+    (1, 30, 1969, "The Beatles play their last public show, on the Apple Corps rooftop in London"),
+    (2, 3, 1959, "Buddy Holly, Ritchie Valens and the Big Bopper die in a plane crash — “the day the music died”"),
+    (2, 9, 1964, "The Beatles appear on The Ed Sullivan Show in front of some 73 million viewers"),
+    (2, 20, 1967, "Kurt Cobain is born in Aberdeen, Washington"),
+    (3, 1, 1973, "Pink Floyd release The Dark Side of the Moon in the US"),
+    (3, 21, 1685, "Johann Sebastian Bach is born in Eisenach"),
+    (4, 10, 1970, "Paul McCartney announces he has left The Beatles"),
+    (4, 12, 1954, "Bill Haley & His Comets record “Rock Around the Clock”"),
+    (5, 7, 1824, "Beethoven's Ninth Symphony premieres in Vienna"),
+    (5, 24, 1941, "Bob Dylan is born in Duluth, Minnesota"),
+    (6, 4, 1984, "Bruce Springsteen releases Born in the U.S.A."),
+    (6, 25, 1967, "The Beatles play “All You Need Is Love” on Our World, the first live global TV broadcast"),
+    (7, 6, 1957, "John Lennon meets Paul McCartney at a church fête in Woolton"),
+    (7, 13, 1985, "Live Aid is staged in London and Philadelphia"),
+    (7, 25, 1965, "Bob Dylan goes electric at the Newport Folk Festival"),
+    (8, 1, 1981, "MTV launches, opening with “Video Killed the Radio Star”"),
+    (8, 15, 1969, "Woodstock opens in Bethel, New York"),
+    (8, 16, 1977, "Elvis Presley dies at Graceland"),
+    (9, 24, 1991, "Nirvana release Nevermind"),
+    (9, 26, 1969, "The Beatles release Abbey Road"),
+    (10, 5, 1962, "The Beatles release their first single, “Love Me Do”"),
+    (10, 7, 2008, "Spotify opens to the public"),
+    (10, 23, 2001, "Apple unveils the first iPod"),
+    (11, 27, 1942, "Jimi Hendrix is born in Seattle"),
+    (11, 30, 1982, "Michael Jackson releases Thriller"),
+    (12, 5, 1791, "Wolfgang Amadeus Mozart dies in Vienna"),
+    (12, 8, 1980, "John Lennon is shot outside the Dakota in New York"),
+    (12, 17, 1770, "Ludwig van Beethoven is baptised in Bonn"),
+];
+
+/// Facts for `today`: the ones from this very day, else the rest of the month
+pub fn history_facts(today: NaiveDate) -> Vec<String> {
+    let month: Vec<_> = FACTS.iter().filter(|(m, ..)| *m == today.month()).collect();
+    let on_the_day: Vec<String> = month
+        .iter()
+        .filter(|(_, d, ..)| *d == today.day())
+        .map(|(_, _, year, text)| format!("On this day in {year}: {text}."))
+        .collect();
+    if !on_the_day.is_empty() {
+        return on_the_day;
+    }
+    month
+        .iter()
+        .filter_map(|&&(m, d, year, text)| {
+            let date = NaiveDate::from_ymd_opt(year, m, d)?;
+            Some(format!("{}: {text}.", date.format("%b %-d, %Y")))
+        })
+        .collect()
+}
+
+/// One quote per line of a plain text file; blank lines and `#` comments are
+/// skipped
+pub fn load_file(path: &Path) -> Result<Vec<String>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Quotes out of a quotes API response. Takes a list of quote objects, a
+/// single one, or either wrapped in a `quotes`/`data`/`results` field; the
+/// text comes from `q`, `quote`, `content` or `text` and the author from `a`
+/// or `author`, which covers ZenQuotes, Quotable, DummyJSON and the like
+pub fn parse_api(body: &str) -> Result<Vec<String>> {
+    let value: Value = serde_json::from_str(body).context("Quotes API didn't return JSON")?;
+    let value = ["quotes", "data", "results"].iter().find_map(|k| value.get(k)).unwrap_or(&value);
+    let entries = match value {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    let field = |entry: &Value, keys: &[&str]| {
+        keys.iter().find_map(|k| entry.get(k)?.as_str()).map(str::trim).filter(|s| !s.is_empty()).map(String::from)
+    };
+    Ok(entries
+        .into_iter()
+        .filter_map(|entry| {
+            let text = field(entry, &["q", "quote", "content", "text"])?;
+            Some(match field(entry, &["a", "author"]) {
+                Some(author) => format!("\"{text}\"\n  – {author}"),
+                None => format!("\"{text}\""),
+            })
+        })
+        .collect())
+}
+
+pub async fn fetch(url: &str) -> Result<Vec<String>> {
+    let body = reqwest::Client::new()
+        .get(url)
+        .header("User-Agent", concat!("vibes/", env!("CARGO_PKG_VERSION")))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_api(&body)
+}
+
+/// The last API response, kept so the API is asked at most once a day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteCache {
+    pub date: NaiveDate,
+    /// The API it came from; another URL in the config means a refetch
+    pub url: String,
+    pub quotes: Vec<String>,
+}
+
+impl QuoteCache {
+    pub fn is_fresh(&self, url: &str, today: NaiveDate) -> bool {
+        self.url == url && self.date == today
+    }
+}
+
+/// `$XDG_DATA_HOME/vibes/quotes.json`
+pub fn cache_path() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("quotes.json"))
+}
+
+/// The cached response, if there is one that parses
+pub fn load_cache(path: &Path) -> Option<QuoteCache> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+pub fn save_cache(path: &Path, cache: &QuoteCache) -> Result<()> {
+    write_atomic(path, serde_json::to_string(cache)?)
+}
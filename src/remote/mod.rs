@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use rand::Rng;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr, UdpSocket},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, watch},
+    time,
+};
+use tracing::{info, warn};
+
+use crate::{events::UserAction, session::{NowPlaying, QueueEntry}};
+
+/// Wrong pairing codes tolerated from one address before the remote stops
+/// answering it until restart
+pub const MAX_FAILED_ATTEMPTS: u32 = 10;
+/// How long a connection gets to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Pause after a failed accept (out of file descriptors, say) before the next
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Wrong pairing codes per client address, so one device guessing can't
+/// lock everyone else out
+#[derive(Debug, Default)]
+pub struct FailedAttempts(HashMap<IpAddr, u32>);
+
+impl FailedAttempts {
+    pub fn locked(&self, peer: IpAddr) -> bool {
+        self.0.get(&peer).is_some_and(|n| *n >= MAX_FAILED_ATTEMPTS)
+    }
+
+    /// Counts a wrong code from `peer`; true when that locks it out
+    pub fn record(&mut self, peer: IpAddr) -> bool {
+        let n = self.0.entry(peer).or_default();
+        *n += 1;
+        *n == MAX_FAILED_ATTEMPTS
+    }
+}
+
+/// What the web remote shows (and the MPD server answers from), published
+/// by the app whenever playback changes
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RemoteStatus {
+    pub now_playing: Option<NowPlaying>,
+    pub volume: u8,
+    /// The upcoming Spotify queue
+    pub queue: Vec<QueueEntry>,
+}
+
+/// Where the remote listens and the code a phone has to enter, shown in the TUI
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pairing {
+    pub url: String,
+    pub code: String,
+}
+
+/// A fresh six-digit pairing code
+pub fn pairing_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+/// Compares without bailing on the first differing byte
+fn code_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected.bytes().zip(given.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// The URL to type on a phone: a wildcard bind address is swapped for this
+/// machine's LAN address (found by routing a UDP socket; nothing is sent)
+pub fn lan_url(addr: SocketAddr) -> String {
+    let ip = if addr.ip().is_unspecified() {
+        UdpSocket::bind("0.0.0.0:0")
+            .and_then(|s| s.connect("8.8.8.8:80").map(|_| s))
+            .and_then(|s| s.local_addr())
+            .map(|a| a.ip())
+            .unwrap_or(addr.ip())
+    } else {
+        addr.ip()
+    };
+    format!("http://{}", SocketAddr::new(ip, addr.port()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub params: HashMap<String, String>,
+}
+
+/// Parses the request line: `POST /api/next?code=123456 HTTP/1.1`
+pub fn parse_request(raw: &str) -> Option<Request> {
+    let mut parts = raw.lines().next()?.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter_map(|kv| {
+            let (k, v) = kv.split_once('=')?;
+            Some((k.to_string(), v.to_string()))
+        })
+        .collect();
+    Some(Request { method, path: path.to_string(), params })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reply {
+    pub status: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl Reply {
+    fn json(status: &'static str, body: String) -> Self {
+        Reply { status, content_type: "application/json", body }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Reply::json(status, serde_json::json!({ "error": message }).to_string())
+    }
+}
+
+/// Routes one request. API calls need the pairing code as `?code=`; the
+/// returned action (if any) is for the app to run.
+pub fn handle(req: &Request, code: &str, status: &RemoteStatus) -> (Reply, Option<UserAction>) {
+    if req.method == "GET" && req.path == "/" {
+        let page = Reply { status: "200 OK", content_type: "text/html; charset=utf-8", body: PAGE.to_string() };
+        return (page, None);
+    }
+    let Some(endpoint) = req.path.strip_prefix("/api/") else {
+        return (Reply::error("404 Not Found", "not found"), None);
+    };
+    let given = req.params.get("code").map(String::as_str).unwrap_or("");
+    if !code_matches(code, given) {
+        return (Reply::error("401 Unauthorized", "wrong pairing code"), None);
+    }
+    let action = match (req.method.as_str(), endpoint) {
+        ("GET", "now") => None,
+        ("POST", "toggle") => Some(UserAction::TogglePlay),
+        ("POST", "next") => Some(UserAction::NextTrack),
+        ("POST", "prev") => Some(UserAction::PrevTrack),
+        ("POST", "volume_up") => Some(UserAction::VolumeUp),
+        ("POST", "volume_down") => Some(UserAction::VolumeDown),
+        _ => return (Reply::error("404 Not Found", "not found"), None),
+    };
+    let body = serde_json::to_string(status).unwrap_or_default();
+    (Reply::json("200 OK", body), action)
+}
+
+/// Binds `addr` and serves the remote until the app exits
+pub async fn serve(
+    addr: SocketAddr,
+    code: String,
+    status: watch::Receiver<RemoteStatus>,
+    actions: mpsc::UnboundedSender<UserAction>,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr} for the web remote"))?;
+    info!("Web remote listening on http://{addr}");
+
+    let failures = Arc::new(Mutex::new(FailedAttempts::default()));
+    tokio::spawn(async move {
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                // The app went away (`:profile` starts another): free the port
+                _ = actions.closed() => break,
+            };
+            let (stream, peer) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Web remote accept failed: {e}");
+                    time::sleep(ACCEPT_BACKOFF).await;
+                    continue;
+                }
+            };
+            let (code, status, actions, failures) = (code.clone(), status.clone(), actions.clone(), failures.clone());
+            tokio::spawn(async move {
+                if let Err(e) = answer(stream, peer.ip(), &code, &status, &actions, &failures).await {
+                    warn!("Web remote request from {peer} failed: {e}");
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+async fn answer(
+    mut stream: TcpStream,
+    peer: IpAddr,
+    code: &str,
+    status: &watch::Receiver<RemoteStatus>,
+    actions: &mpsc::UnboundedSender<UserAction>,
+    failures: &Mutex<FailedAttempts>,
+) -> Result<()> {
+    let mut buf = vec![0u8; 4096];
+    let n = time::timeout(READ_TIMEOUT, stream.read(&mut buf))
+        .await
+        .with_context(|| format!("no request within {}s", READ_TIMEOUT.as_secs()))??;
+    let raw = String::from_utf8_lossy(&buf[..n]);
+
+    let reply = match parse_request(&raw) {
+        None => Reply::error("400 Bad Request", "bad request"),
+        Some(_) if failures.lock().unwrap().locked(peer) => {
+            Reply::error("403 Forbidden", "too many wrong codes; restart vibes to pair again")
+        }
+        Some(req) => {
+            let (reply, action) = handle(&req, code, &status.borrow());
+            if reply.status.starts_with("401") && failures.lock().unwrap().record(peer) {
+                warn!("Web remote locked for {peer} after {MAX_FAILED_ATTEMPTS} wrong pairing codes");
+            }
+            if let Some(action) = action {
+                let _ = actions.send(action);
+            }
+            reply
+        }
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        reply.status,
+        reply.content_type,
+        reply.body.len(),
+        reply.body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>vibes remote</title>
+  <style>
+    body { background: #0D0D0D; color: #00F5FF; font-family: monospace; margin: 0;
+           display: flex; align-items: center; justify-content: center; min-height: 100vh; }
+    .card { text-align: center; border: 1px solid #9B5DE5; padding: 24px; border-radius: 12px; width: 85vw; max-width: 420px; }
+    h1 { color: #9B5DE5; margin-top: 0; }
+    #track { font-size: 1.2em; } #artist { color: #aaa; margin-bottom: 20px; }
+    button { background: none; color: #00F5FF; border: 1px solid #9B5DE5; border-radius: 10px;
+             font-size: 1.6em; padding: 12px 0; width: 30%; margin: 4px 1%; }
+    input { background: none; color: #00F5FF; border: 1px solid #9B5DE5; border-radius: 10px;
+            font: inherit; font-size: 1.6em; text-align: center; width: 60%; padding: 8px; letter-spacing: 6px; }
+    .muted { color: #aaa; } .hidden { display: none; }
+  </style>
+</head>
+<body>
+  <div class="card">
+    <h1>🎵 vibes</h1>
+    <div id="pair">
+      <p class="muted">Enter the pairing code shown in the terminal</p>
+      <input id="code" inputmode="numeric" maxlength="6" autocomplete="off">
+      <p><button onclick="pair()">✓</button></p>
+      <p id="err" class="muted"></p>
+    </div>
+    <div id="remote" class="hidden">
+      <div id="track">—</div>
+      <div id="artist"></div>
+      <div><button onclick="send('prev')">⏮</button><button id="toggle" onclick="send('toggle')">⏯</button><button onclick="send('next')">⏭</button></div>
+      <div><button onclick="send('volume_down')">−</button><button disabled id="vol">–</button><button onclick="send('volume_up')">+</button></div>
+    </div>
+  </div>
+  <script>
+    let code = localStorage.getItem('vibes-code') || '';
+    function show(s) {
+      const np = s.now_playing;
+      document.getElementById('track').textContent = np ? np.name : 'Nothing playing';
+      document.getElementById('artist').textContent = np ? np.artists.join(', ') : '';
+      document.getElementById('toggle').textContent = np && np.is_playing ? '⏸' : '▶';
+      document.getElementById('vol').textContent = s.volume + '%';
+    }
+    async function call(method, endpoint) {
+      const r = await fetch('/api/' + endpoint + '?code=' + encodeURIComponent(code), { method });
+      if (r.status === 401 || r.status === 403) { unpair((await r.json()).error); return; }
+      show(await r.json());
+    }
+    function unpair(msg) {
+      localStorage.removeItem('vibes-code');
+      document.getElementById('err').textContent = msg;
+      document.getElementById('pair').classList.remove('hidden');
+      document.getElementById('remote').classList.add('hidden');
+    }
+    async function pair() {
+      code = document.getElementById('code').value.trim();
+      localStorage.setItem('vibes-code', code);
+      document.getElementById('pair').classList.add('hidden');
+      document.getElementById('remote').classList.remove('hidden');
+      await call('GET', 'now');
+    }
+    function send(endpoint) { call('POST', endpoint); }
+    if (code) { document.getElementById('code').value = code; pair(); }
+    setInterval(() => { if (localStorage.getItem('vibes-code')) call('GET', 'now'); }, 2000);
+  </script>
+</body>
+</html>"#;
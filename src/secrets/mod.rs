@@ -0,0 +1,83 @@
+use anyhow::Result;
+
+use crate::config::Config;
+
+/// Entry name for the Spotify app's client secret
+pub const CLIENT_SECRET: &str = "client_secret";
+/// Entry name for the OAuth token, refresh token included
+pub const TOKEN: &str = "spotify_token";
+
+/// The client secret's entry: a profile with its own app keeps its own,
+/// one sharing the default app shares its secret too
+pub fn client_secret_entry(config: &Config) -> String {
+    match config.profile.as_deref().filter(|_| config.profile_app) {
+        Some(profile) => format!("{CLIENT_SECRET}:{profile}"),
+        None => CLIENT_SECRET.to_string(),
+    }
+}
+
+/// The token's entry; every profile is a separate login
+pub fn token_entry(config: &Config) -> String {
+    match config.profile.as_deref() {
+        Some(profile) => format!("{TOKEN}:{profile}"),
+        None => TOKEN.to_string(),
+    }
+}
+
+/// Service the entries are filed under (the macOS Keychain, Windows Credential
+/// Manager or the Secret Service on Linux)
+#[cfg(feature = "keyring")]
+const SERVICE: &str = "vibes";
+
+/// Keyring backends block (D-Bus, Keychain prompts), so every call runs on
+/// the blocking pool
+#[cfg(feature = "keyring")]
+async fn with_entry<T: Send + 'static>(
+    name: &str,
+    f: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send + 'static,
+) -> Result<T> {
+    let name = name.to_string();
+    tokio::task::spawn_blocking(move || Ok(f(&keyring::Entry::new(SERVICE, &name)?)?)).await?
+}
+
+/// `name`'s value; `None` when there's no such entry
+#[cfg(feature = "keyring")]
+pub async fn get(name: &str) -> Result<Option<String>> {
+    with_entry(name, |entry| match entry.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e),
+    })
+    .await
+}
+
+#[cfg(feature = "keyring")]
+pub async fn set(name: &str, value: String) -> Result<()> {
+    with_entry(name, move |entry| entry.set_password(&value)).await
+}
+
+/// Removes `name`; fine when it was never stored
+#[cfg(feature = "keyring")]
+pub async fn delete(name: &str) -> Result<()> {
+    with_entry(name, |entry| match entry.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e),
+    })
+    .await
+}
+
+/// Built without the `keyring` feature: nothing to read or write
+#[cfg(not(feature = "keyring"))]
+pub async fn get(_name: &str) -> Result<Option<String>> {
+    anyhow::bail!("this build has no keyring support")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub async fn set(_name: &str, _value: String) -> Result<()> {
+    anyhow::bail!("this build has no keyring support")
+}
+
+#[cfg(not(feature = "keyring"))]
+pub async fn delete(_name: &str) -> Result<()> {
+    anyhow::bail!("this build has no keyring support")
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rspotify::{
+    model::{PlaylistItem, SimplifiedPlaylist},
+    prelude::Id,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::{plays, write_atomic};
+use crate::cache::snapshots::PlaylistSnapshot;
+
+/// A playlist as it was when archived, written before it's unfollowed so
+/// the Archived browser can bring it back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistArchive {
+    /// Spotify playlist id
+    pub id: String,
+    pub name: String,
+    /// Owner's display name
+    pub owner: String,
+    /// The user's own playlist, which is recreated from `snapshot` if
+    /// following it again fails
+    pub owned: bool,
+    pub archived_at: DateTime<Utc>,
+    pub snapshot: PlaylistSnapshot,
+}
+
+impl PlaylistArchive {
+    pub fn new(pl: &SimplifiedPlaylist, user_id: Option<&str>, items: &[PlaylistItem]) -> Self {
+        PlaylistArchive {
+            id: pl.id.id().to_string(),
+            name: pl.name.clone(),
+            owner: pl.owner.display_name.clone().unwrap_or_else(|| pl.owner.id.id().to_string()),
+            owned: user_id == Some(pl.owner.id.id()),
+            archived_at: Utc::now(),
+            snapshot: PlaylistSnapshot::from_items(&pl.snapshot_id, items),
+        }
+    }
+}
+
+/// `$XDG_DATA_HOME/vibes/archive/`, one `<playlist id>.json` per playlist
+pub fn default_dir() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("archive"))
+}
+
+fn file(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.json"))
+}
+
+/// Written whole or not at all, as the playlist is unfollowed once it's saved
+pub fn save(dir: &Path, archive: &PlaylistArchive) -> Result<()> {
+    write_atomic(&file(dir, &archive.id), serde_json::to_string_pretty(archive)?)
+}
+
+/// Every archived playlist, most recently archived first; files that don't
+/// parse are skipped, and a missing folder means nothing is archived yet
+pub fn load_all(dir: &Path) -> Result<Vec<PlaylistArchive>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+    };
+    let mut archives: Vec<PlaylistArchive> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .filter_map(|raw| serde_json::from_str(&raw).ok())
+        .collect();
+    archives.sort_by_key(|a| std::cmp::Reverse(a.archived_at));
+    Ok(archives)
+}
+
+pub fn remove(dir: &Path, id: &str) -> Result<()> {
+    let path = file(dir, id);
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+}
@@ -0,0 +1,99 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::{plays, write_atomic};
+
+/// A track someone shared, waiting in the inbox to be listened to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InboxItem {
+    /// `spotify:track:` URI
+    pub uri: String,
+    pub added_at: DateTime<Utc>,
+    /// Who sent it or why, as given to `vibes inbox add`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// `$XDG_DATA_HOME/vibes/inbox.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("inbox.jsonl"))
+}
+
+/// The track URI of a shared link: `https://open.spotify.com/track/<id>?si=…`
+/// (also with an `intl-xx/` segment) or `spotify:track:<id>`
+pub fn track_uri(link: &str) -> Option<String> {
+    let link = link.trim();
+    let id = match link.strip_prefix("spotify:track:") {
+        Some(id) => id,
+        None => {
+            let rest = link
+                .strip_prefix("https://")
+                .or_else(|| link.strip_prefix("http://"))?
+                .strip_prefix("open.spotify.com/")?;
+            let rest = match rest.split_once('/') {
+                Some((locale, path)) if locale.starts_with("intl-") => path,
+                _ => rest,
+            };
+            rest.strip_prefix("track/")?.split(['?', '#', '/']).next()?
+        }
+    };
+    (id.len() == 22 && id.chars().all(|c| c.is_ascii_alphanumeric())).then(|| format!("spotify:track:{id}"))
+}
+
+/// Adds a shared link to the inbox file, for `vibes inbox add`
+pub fn add(path: &Path, link: &str, note: Option<String>) -> Result<InboxItem> {
+    let Some(uri) = track_uri(link) else { bail!("Not a Spotify track link: {link}") };
+    let item = InboxItem { uri, added_at: Utc::now(), note };
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&item)?)?;
+    Ok(item)
+}
+
+/// Everything in the inbox, oldest first, each track once. A line can also
+/// be a bare link, so scripts can simply append one; those are dated by the
+/// file's modification time. A missing file is an empty inbox.
+pub fn load(path: &Path) -> Result<Vec<InboxItem>> {
+    let raw = match std::fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_or_else(|_| Utc::now(), DateTime::<Utc>::from);
+    let mut items: Vec<InboxItem> = Vec::new();
+    for line in raw.lines().filter(|l| !l.trim().is_empty()) {
+        let item = serde_json::from_str(line)
+            .ok()
+            .or_else(|| track_uri(line).map(|uri| InboxItem { uri, added_at: modified, note: None }));
+        if let Some(item) = item.filter(|i: &InboxItem| !items.iter().any(|seen| seen.uri == i.uri)) {
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+/// Takes `uris` out of the inbox file. The file is read again first so
+/// links added since the TUI last loaded it aren't lost.
+pub fn remove(path: &Path, uris: &[String]) -> Result<Vec<InboxItem>> {
+    let kept: Vec<InboxItem> = load(path)?.into_iter().filter(|i| !uris.contains(&i.uri)).collect();
+    let mut body = String::new();
+    for item in &kept {
+        body.push_str(&serde_json::to_string(item)?);
+        body.push('\n');
+    }
+    write_atomic(path, body)?;
+    Ok(kept)
+}
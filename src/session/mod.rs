@@ -0,0 +1,96 @@
+pub mod archive;
+pub mod inbox;
+pub mod plays;
+pub mod searches;
+pub mod trace;
+
+use anyhow::{Context, Result};
+use rspotify::model::FullTrack;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::app::state::{AppState, CurrentTrack, HistoryEntry};
+
+/// How many history / queue entries the summary file carries
+pub const PREVIEW_LEN: usize = 10;
+
+/// Snapshot of the listening session for overlays (OBS text sources, scripts).
+/// Compared against the last written one so the file only changes on change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SessionSummary {
+    pub now_playing: Option<NowPlaying>,
+    pub history: Vec<HistoryEntry>,
+    pub queue: Vec<QueueEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NowPlaying {
+    pub id: Option<String>,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub album_art_url: Option<String>,
+    pub is_playing: bool,
+    pub progress_ms: u32,
+    pub duration_ms: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueEntry {
+    pub id: Option<String>,
+    pub name: String,
+    pub artists: Vec<String>,
+    pub duration_ms: u32,
+}
+
+impl NowPlaying {
+    /// `None` until something has played
+    pub fn from_track(ct: &CurrentTrack) -> Option<Self> {
+        (!ct.name.is_empty()).then(|| NowPlaying {
+            id: ct.id.clone(),
+            name: ct.name.clone(),
+            artists: ct.artists.clone(),
+            album: ct.album.clone(),
+            album_art_url: ct.album_art_url.clone(),
+            is_playing: ct.is_playing,
+            progress_ms: ct.progress_ms,
+            duration_ms: ct.duration_ms,
+        })
+    }
+}
+
+impl QueueEntry {
+    pub fn from_track(t: &FullTrack) -> Self {
+        QueueEntry {
+            id: t.id.as_ref().map(|id| id.to_string()),
+            name: t.name.clone(),
+            artists: t.artists.iter().map(|a| a.name.clone()).collect(),
+            duration_ms: t.duration.num_milliseconds() as u32,
+        }
+    }
+}
+
+impl SessionSummary {
+    pub fn from_state(state: &AppState) -> Self {
+        let now_playing = NowPlaying::from_track(&state.current_track);
+        let queue = state.queue.tracks.iter().take(PREVIEW_LEN).map(QueueEntry::from_track).collect();
+        SessionSummary {
+            now_playing,
+            history: state.history.iter().take(PREVIEW_LEN).cloned().collect(),
+            queue,
+        }
+    }
+}
+
+/// Writes `contents` to a sibling temp file and renames it over `path`
+/// (creating its folder if need be), so readers never see a half-written
+/// document.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents).with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, path).with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
@@ -0,0 +1,237 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use super::write_atomic;
+use crate::{app::state::HistoryEntry, clipboard::web_link, jobs::JobHandle, paths};
+
+/// One play worth scrobbling, as kept in the local play log (one JSON object per line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Play {
+    /// When the track started, which is what scrobblers expect
+    pub listened_at: DateTime<Utc>,
+    pub artist: String,
+    pub track: String,
+    pub album: String,
+    pub duration_ms: u32,
+    /// Spotify URI
+    pub id: String,
+}
+
+impl From<&HistoryEntry> for Play {
+    fn from(entry: &HistoryEntry) -> Self {
+        Play {
+            listened_at: entry.started_at,
+            artist: entry.artists.join(", "),
+            track: entry.name.clone(),
+            album: entry.album.clone(),
+            duration_ms: entry.duration_ms,
+            id: entry.id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// `timestamp,artist,track,album,duration` with Unix seconds, for Last.fm backfill tools
+    Csv,
+    /// A JSON array of listens, as ListenBrainz's importer takes them
+    ListenBrainz,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(ExportFormat::Csv),
+            "listenbrainz" | "json" => Some(ExportFormat::ListenBrainz),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::ListenBrainz => "json",
+        }
+    }
+}
+
+/// `plays.jsonl` in `paths::data_dir`: `$XDG_DATA_HOME/vibes/plays.jsonl`,
+/// falling back to `~/.local/share/vibes/plays.jsonl` on Linux. Vibes' other
+/// data files (searches, inbox, trace, feature index, quotes, archive) are
+/// placed next to it, so they follow it wherever it lands.
+pub fn default_path() -> Option<PathBuf> {
+    Some(paths::data_dir()?.join("plays.jsonl"))
+}
+
+/// How far apart (in seconds) two records of the same track can be and still
+/// be one listen: half the track, since a skipped track is stamped early or
+/// late depending on who records it, plus a couple of minutes for polling
+pub fn same_listen_slack(duration_ms: u32) -> i64 {
+    duration_ms as i64 / 2000 + 120
+}
+
+/// Adds the plays from Spotify's recently played that the log doesn't have
+/// yet — those heard while vibes wasn't running — and rewrites the log in
+/// time order. Returns the plays it added.
+pub fn merge(path: &Path, recent: &[Play]) -> Result<Vec<Play>> {
+    let mut log = if path.exists() { load(path)? } else { Vec::new() };
+    // Each logged play stands for one listen, so a track on repeat isn't
+    // folded into a single play
+    let mut matched = vec![false; log.len()];
+    let added: Vec<Play> = recent
+        .iter()
+        .filter(|play| {
+            let closest = log
+                .iter()
+                .enumerate()
+                .filter(|(i, p)| !matched[*i] && p.id == play.id)
+                .map(|(i, p)| (i, (p.listened_at - play.listened_at).num_seconds().abs()))
+                .filter(|&(_, gap)| gap <= same_listen_slack(play.duration_ms))
+                .min_by_key(|&(_, gap)| gap);
+            match closest {
+                Some((i, _)) => {
+                    matched[i] = true;
+                    false
+                }
+                None => true,
+            }
+        })
+        .cloned()
+        .collect();
+    if added.is_empty() {
+        return Ok(added);
+    }
+    log.extend(added.iter().cloned());
+    log.sort_by_key(|p| p.listened_at);
+    let mut body = String::new();
+    for play in &log {
+        body.push_str(&serde_json::to_string(play)?);
+        body.push('\n');
+    }
+    write_atomic(path, body)?;
+    Ok(added)
+}
+
+pub fn append(path: &Path, play: &Play) -> Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(play)?)?;
+    Ok(())
+}
+
+/// Every play in the log, oldest first; lines that don't parse are skipped
+pub fn load(path: &Path) -> Result<Vec<Play>> {
+    let raw = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(raw.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn to_csv(plays: &[Play]) -> String {
+    let mut out = String::from("timestamp,artist,track,album,duration\n");
+    for p in plays {
+        out.push_str(&format!(
+            "{},{},{},{},{}\n",
+            p.listened_at.timestamp(),
+            csv_field(&p.artist),
+            csv_field(&p.track),
+            csv_field(&p.album),
+            p.duration_ms / 1000
+        ));
+    }
+    out
+}
+
+pub fn to_listenbrainz(plays: &[Play]) -> String {
+    let listens: Vec<serde_json::Value> = plays
+        .iter()
+        .map(|p| {
+            serde_json::json!({
+                "listened_at": p.listened_at.timestamp(),
+                "track_metadata": {
+                    "artist_name": p.artist,
+                    "track_name": p.track,
+                    "release_name": p.album,
+                    "additional_info": {
+                        "duration_ms": p.duration_ms,
+                        "spotify_id": web_link(&p.id),
+                        "media_player": "Spotify",
+                        "submission_client": "vibes",
+                    },
+                },
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&listens).unwrap_or_default()
+}
+
+/// One earlier year's listening on today's date
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayRecap {
+    pub year: i32,
+    pub plays: usize,
+    /// Most played track that day; the earlier one on a tie
+    pub top: Play,
+    pub top_count: usize,
+}
+
+/// Plays on `today`'s month and day in earlier years, newest year first.
+/// Plays are dated in `offset`, the local timezone, not UTC.
+pub fn on_this_day(plays: &[Play], today: NaiveDate, offset: FixedOffset) -> Vec<DayRecap> {
+    let mut years: BTreeMap<i32, Vec<&Play>> = BTreeMap::new();
+    for play in plays {
+        let date = play.listened_at.with_timezone(&offset).date_naive();
+        if (date.month(), date.day()) == (today.month(), today.day()) && date.year() < today.year() {
+            years.entry(date.year()).or_default().push(play);
+        }
+    }
+    years
+        .into_iter()
+        .rev()
+        .filter_map(|(year, day)| {
+            let count = |id: &str| day.iter().filter(|p| p.id == id).count();
+            let top = day.iter().max_by(|a, b| count(&a.id).cmp(&count(&b.id)).then(b.listened_at.cmp(&a.listened_at)))?;
+            Some(DayRecap { year, plays: day.len(), top: (*top).clone(), top_count: count(&top.id) })
+        })
+        .collect()
+}
+
+/// Writes the plays since `since` (all of them when `None`) next to the log
+/// and returns the export's path and how many plays it holds; `job` gets
+/// the progress and can stop it before the write
+pub fn export(log: &Path, format: ExportFormat, since: Option<DateTime<Utc>>, job: &JobHandle) -> Result<(PathBuf, usize)> {
+    let plays: Vec<Play> = load(log)?
+        .into_iter()
+        .filter(|p| since.is_none_or(|t| p.listened_at >= t))
+        .collect();
+    job.set_total(plays.len());
+    let body = match format {
+        ExportFormat::Csv => to_csv(&plays),
+        ExportFormat::ListenBrainz => to_listenbrainz(&plays),
+    };
+    // Last chance to back out before anything lands on disk
+    job.check()?;
+    let name = format!("plays-{}.{}", Utc::now().format("%Y%m%d-%H%M%S"), format.extension());
+    let out = log.with_file_name(name);
+    std::fs::write(&out, body).with_context(|| format!("Failed to write {}", out.display()))?;
+    job.advance(plays.len());
+    Ok((out, plays.len()))
+}
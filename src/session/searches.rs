@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use super::{plays, write_atomic};
+
+/// How many past searches are kept
+pub const LIMIT: usize = 100;
+
+/// `$XDG_DATA_HOME/vibes/searches.txt`: one query per line, newest first
+pub fn default_path() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("searches.txt"))
+}
+
+/// Past searches, newest first; a missing file means none yet
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(raw) => Ok(raw.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).take(LIMIT).collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Moves `query` to the front of `history`, so a repeated search isn't
+/// listed twice, and drops the oldest beyond `LIMIT`
+pub fn push(history: &mut Vec<String>, query: &str) {
+    let query = query.trim();
+    if query.is_empty() {
+        return;
+    }
+    history.retain(|q| q != query);
+    history.insert(0, query.to_string());
+    history.truncate(LIMIT);
+}
+
+pub fn save(path: &Path, history: &[String]) -> Result<()> {
+    let mut body = history.join("\n");
+    body.push('\n');
+    write_atomic(path, body)
+}
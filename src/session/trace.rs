@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::plays;
+
+/// `$XDG_DATA_HOME/vibes/trace.jsonl`
+pub fn default_path() -> Option<PathBuf> {
+    Some(plays::default_path()?.with_file_name("trace.jsonl"))
+}
+
+/// What `:trace on` records: enough to replay how a track change or a
+/// progress jump was (or wasn't) noticed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// Tracing was switched on, by `:trace on` or the `trace` setting
+    Started,
+    /// A playback poll came back with something playing
+    Poll {
+        track: Option<String>,
+        progress_ms: u32,
+        duration_ms: u32,
+        is_playing: bool,
+        /// How long Spotify took to answer
+        took_ms: u64,
+        /// Polled progress minus what the progress bar had counted up to;
+        /// `None` when the poll found another track
+        drift_ms: Option<i64>,
+    },
+    /// A poll found nothing playing, or failed
+    PollMissing { took_ms: u64, devices: Option<usize> },
+    /// The progress bar reached the end of the track, so a poll went out early
+    TrackEnded { track: Option<String>, progress_ms: u32 },
+    /// A poll showed another track, or the same one from the top again
+    TrackChange {
+        from: Option<String>,
+        to: Option<String>,
+        /// Where the old track had got to when the change was noticed
+        from_progress_ms: u32,
+        from_duration_ms: u32,
+        restarted: bool,
+    },
+    /// Play/pause, a skip or a seek, from a key, the remote or an MPD client
+    Command { action: String, track: Option<String>, progress_ms: u32 },
+}
+
+#[derive(Serialize)]
+struct TraceLine<'a> {
+    at: DateTime<Utc>,
+    /// Milliseconds since tracing started, on the monotonic clock, so a
+    /// wall-clock adjustment can't reorder lines
+    mono_ms: u64,
+    #[serde(flatten)]
+    event: &'a TraceEvent,
+}
+
+/// An open trace file; every event is a JSON line, flushed as it's written
+#[derive(Debug)]
+pub struct Tracer {
+    path: PathBuf,
+    file: File,
+    started: Instant,
+}
+
+impl Tracer {
+    /// Appends to `path`, so turning tracing off and on keeps one file
+    pub fn open(path: &Path) -> Result<Tracer> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut tracer = Tracer { path: path.to_path_buf(), file, started: Instant::now() };
+        tracer.record(&TraceEvent::Started)?;
+        Ok(tracer)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&mut self, event: &TraceEvent) -> Result<()> {
+        let line = TraceLine { at: Utc::now(), mono_ms: self.started.elapsed().as_millis() as u64, event };
+        writeln!(self.file, "{}", serde_json::to_string(&line)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
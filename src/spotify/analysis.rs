@@ -0,0 +1,38 @@
+use anyhow::Result;
+use rspotify::{
+    model::{AudioFeatures, TrackId},
+    prelude::*,
+    AuthCodePkceSpotify,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::retry::send;
+
+pub struct Analysis {
+    spotify: Arc<Mutex<AuthCodePkceSpotify>>,
+}
+
+impl Analysis {
+    pub fn new(spotify: Arc<Mutex<AuthCodePkceSpotify>>) -> Self {
+        Analysis { spotify }
+    }
+
+    /// Loudness, tempo, energy etc. for a single track.
+    /// Note: Spotify restricts this endpoint for apps created after Nov 2024.
+    pub async fn get_audio_features(&self, track_id: &str) -> Result<AudioFeatures> {
+        let id = &TrackId::from_id_or_uri(track_id)?;
+        let features = send(&self.spotify, |sp| async move { sp.track_features(id.clone()).await }).await?;
+        Ok(features)
+    }
+
+    /// Features for up to 100 tracks in one request; tracks Spotify has no
+    /// features for are left out
+    pub async fn get_audio_features_batch(&self, track_ids: &[String]) -> Result<Vec<AudioFeatures>> {
+        let ids = &track_ids
+            .iter()
+            .map(|id| TrackId::from_id_or_uri(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(send(&self.spotify, |sp| async move { sp.tracks_features(ids.iter().cloned()).await }).await?.unwrap_or_default())
+    }
+}
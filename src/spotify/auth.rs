@@ -1,11 +1,11 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use base64::Engine;
 use rand::Rng;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::RangeInclusive};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpListener;
-use tracing::info;
+use tracing::{info, warn};
 
 fn generate_code_verifier() -> String {
     let mut rng = rand::thread_rng();
@@ -20,64 +20,132 @@ fn generate_code_challenge(verifier: &str) -> String {
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(hash)
 }
 
-#[allow(dead_code)]
+/// Where Spotify's login page is
+const AUTHORIZE_URL: &str = "https://accounts.spotify.com/authorize";
+
+#[derive(Debug)]
 pub struct AuthResult {
     pub code: String,
     pub state: String,
 }
 
-/// Starts a local HTTP server on port 8989 waiting for the Spotify redirect
-pub async fn wait_for_auth_code() -> Result<AuthResult> {
-    let listener = TcpListener::bind("127.0.0.1:8989")
-        .await
-        .context("Failed to bind port 8989 for OAuth redirect")?;
+/// The pieces of a loopback redirect URI like `http://127.0.0.1:8989/login`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedirectUri {
+    pub host: String,
+    pub port: u16,
+    /// From the `/` on, empty for none
+    pub path: String,
+}
 
-    info!("Waiting for Spotify auth redirect on http://127.0.0.1:8989/login ...");
+impl RedirectUri {
+    pub fn parse(uri: &str) -> Result<Self> {
+        let rest = uri.strip_prefix("http://").with_context(|| format!("redirect_uri {uri:?} isn't an http:// address"))?;
+        let (authority, path) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().with_context(|| format!("redirect_uri {uri:?} has a bad port"))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            bail!("redirect_uri {uri:?} has no host");
+        }
+        Ok(RedirectUri { host: host.to_string(), port, path: path.to_string() })
+    }
 
-    let (mut stream, _) = listener.accept().await?;
-    let mut buf = vec![0u8; 4096];
-    let n = stream.read(&mut buf).await?;
-    let request = String::from_utf8_lossy(&buf[..n]);
+    /// The same address on `port`
+    pub fn with_port(&self, port: u16) -> String {
+        format!("http://{}:{port}{}", self.host, self.path)
+    }
+}
 
-    // Parse the GET line: GET /login?code=...&state=... HTTP/1.1
-    let query = request
-        .lines()
-        .next()
-        .and_then(|line| line.split_whitespace().nth(1))
-        .and_then(|path| path.split('?').nth(1))
-        .unwrap_or("");
+/// `redirect_fallback_ports`: `8990-8999`, a single `8990`, or empty for none
+pub fn parse_port_range(range: &str) -> Result<RangeInclusive<u16>> {
+    let range = range.trim();
+    let parse = |port: &str| port.trim().parse::<u16>().with_context(|| format!("redirect_fallback_ports {range:?} should look like 8990-8999"));
+    if range.is_empty() {
+        // Empty: start past the end
+        return Ok(RangeInclusive::new(1, 0));
+    }
+    match range.split_once('-') {
+        Some((first, last)) => Ok(parse(first)?..=parse(last)?),
+        None => parse(range).map(|port| port..=port),
+    }
+}
 
-    let params: HashMap<&str, &str> = query
-        .split('&')
-        .filter_map(|kv| {
-            let mut parts = kv.splitn(2, '=');
-            Some((parts.next()?, parts.next()?))
-        })
-        .collect();
+/// The listener the browser is sent back to, and the redirect URI that
+/// reaches it (which goes in the authorize URL)
+pub struct RedirectListener {
+    listener: TcpListener,
+    pub uri: String,
+}
 
-    let code = params.get("code").context("No code in redirect")?.to_string();
-    let state = params.get("state").unwrap_or(&"").to_string();
+/// Listens on the host and port of `redirect_uri`; when that port is taken,
+/// on the first free one of `fallback_ports`. Spotify only redirects to URIs
+/// registered for the app, so each fallback has to be added there too.
+pub async fn bind_redirect(redirect_uri: &str, fallback_ports: &str) -> Result<RedirectListener> {
+    let redirect = RedirectUri::parse(redirect_uri)?;
+    let fallbacks = parse_port_range(fallback_ports)?;
+    let mut busy = Vec::new();
+    for port in std::iter::once(redirect.port).chain(fallbacks.filter(|&p| p != redirect.port)) {
+        match TcpListener::bind((redirect.host.as_str(), port)).await {
+            Ok(listener) => {
+                if !busy.is_empty() {
+                    warn!("Port {} is taken; the login redirect uses {port}", redirect.port);
+                }
+                return Ok(RedirectListener { listener, uri: redirect.with_port(port) });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => busy.push(port.to_string()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to listen on {}:{port} for the OAuth redirect", redirect.host)),
+        }
+    }
+    bail!("Ports {} are all taken; free one or set redirect_fallback_ports (and register it with your Spotify app)", busy.join(", "))
+}
+
+/// Waits on `redirect` for the browser to come back from Spotify, and checks
+/// it brings back `expected_state`; an answer that doesn't is someone else's
+/// login and is turned down
+pub async fn wait_for_auth_code(redirect: RedirectListener, expected_state: &str) -> Result<AuthResult> {
+    info!("Waiting for Spotify auth redirect on {} ...", redirect.uri);
+
+    let (mut stream, _) = redirect.listener.accept().await?;
+    let mut buf = vec![0u8; 4096];
+    let n = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let result = parse_redirect(&request).and_then(|auth| {
+        if auth.state != expected_state {
+            bail!("The login came back with a different state; start it again from Vibes");
+        }
+        Ok(auth)
+    });
 
     // Send a nice response page
-    let body = r#"<!DOCTYPE html>
+    let message = match result {
+        Ok(_) => "<p>Authentication successful! You can close this tab.</p>\n    <p style=\"color:#00F5FF\">Return to your terminal ✨</p>".to_string(),
+        Err(ref e) => {
+            let e = e.to_string().replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+            format!("<p style=\"color:#F15BB5\">Not logged in: {e}</p>\n    <p>Return to your terminal to try again.</p>")
+        }
+    };
+    let body = format!(
+        r#"<!DOCTYPE html>
 <html>
 <head>
   <style>
-    body { background: #0D0D0D; color: #00F5FF; font-family: monospace; 
-           display: flex; align-items: center; justify-content: center; height: 100vh; margin: 0; }
-    .card { text-align: center; border: 1px solid #9B5DE5; padding: 40px; border-radius: 12px; }
-    h1 { color: #9B5DE5; }
-    p { color: #aaa; }
+    body {{ background: #0D0D0D; color: #00F5FF; font-family: monospace; 
+           display: flex; align-items: center; justify-content: center; height: 100vh; margin: 0; }}
+    .card {{ text-align: center; border: 1px solid #9B5DE5; padding: 40px; border-radius: 12px; }}
+    h1 {{ color: #9B5DE5; }}
+    p {{ color: #aaa; }}
   </style>
 </head>
 <body>
   <div class="card">
     <h1>🎵 vibes</h1>
-    <p>Authentication successful! You can close this tab.</p>
-    <p style="color:#00F5FF">Return to your terminal ✨</p>
+    {message}
   </div>
 </body>
-</html>"#;
+</html>"#
+    );
 
     let response = format!(
         "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
@@ -87,18 +155,66 @@ pub async fn wait_for_auth_code() -> Result<AuthResult> {
     stream.write_all(response.as_bytes()).await?;
     stream.flush().await?;
 
+    result
+}
+
+/// The code and state out of the redirect's request line
+/// (`GET /login?code=...&state=... HTTP/1.1`); a refused login has an
+/// `error` there instead
+pub fn parse_redirect(request: &str) -> Result<AuthResult> {
+    let query = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|path| path.split('?').nth(1))
+        .unwrap_or("");
+
+    let params: HashMap<&str, &str> = query
+        .split('&')
+        .filter_map(|kv| {
+            let mut parts = kv.splitn(2, '=');
+            Some((parts.next()?, parts.next()?))
+        })
+        .collect();
+
+    if let Some(error) = params.get("error") {
+        bail!("Spotify answered {error}");
+    }
+    let code = params.get("code").context("No code in redirect")?.to_string();
+    let state = urlencoding::decode(params.get("state").unwrap_or(&""))?.into_owned();
     Ok(AuthResult { code, state })
 }
 
+/// One login's secrets: the PKCE verifier whose challenge goes in the
+/// authorize URL and is checked against it when the code is exchanged, and
+/// the state the redirect has to bring back
 pub struct PkceChallenge {
     pub verifier: String,
     pub challenge: String,
+    pub state: String,
 }
 
 impl PkceChallenge {
     pub fn new() -> Self {
         let verifier = generate_code_verifier();
         let challenge = generate_code_challenge(&verifier);
-        PkceChallenge { verifier, challenge }
+        let state = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::thread_rng().gen::<[u8; 16]>());
+        PkceChallenge { verifier, challenge, state }
+    }
+
+    /// Spotify's login page for `client_id`, asking for `scopes` and sending
+    /// the browser back to `redirect_uri`
+    pub fn authorize_url(&self, client_id: &str, redirect_uri: &str, scopes: &[&str]) -> String {
+        let params = [
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("redirect_uri", redirect_uri),
+            ("code_challenge_method", "S256"),
+            ("code_challenge", &self.challenge),
+            ("state", &self.state),
+            ("scope", &scopes.join(" ")),
+        ];
+        let query: Vec<String> = params.iter().map(|(k, v)| format!("{k}={}", urlencoding::encode(v))).collect();
+        format!("{AUTHORIZE_URL}?{}", query.join("&"))
     }
 }
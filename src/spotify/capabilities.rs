@@ -0,0 +1,127 @@
+use rspotify::{
+    http::HttpError,
+    model::{ArtistId, RecommendationsAttribute, TrackId},
+    prelude::*,
+    AuthCodePkceSpotify, ClientError, ClientResult,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Any long-lived catalog track works; it's only used to poke endpoints
+const PROBE_TRACK: &str = "4uLU6hMCjMI75M1A2tKUQC";
+
+/// Spotify's own charts category, there in every market
+const PROBE_CATEGORY: &str = "toplists";
+
+/// What the startup probe learned about one endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Capability {
+    /// Not probed yet, or the probe failed for an unrelated reason (network)
+    #[default]
+    Unknown,
+    Available,
+    /// Refused for this app or account — don't bother calling it
+    Unavailable,
+}
+
+impl Capability {
+    /// Spotify answers 403/404 for endpoints an app can't use (e.g.
+    /// recommendations and audio features for apps created after Nov 2024)
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            200..=299 => Capability::Available,
+            403 | 404 | 410 => Capability::Unavailable,
+            _ => Capability::Unknown,
+        }
+    }
+
+    /// The queue endpoint answers 404 when no device is active, which says
+    /// nothing about whether the app may use it; only 403 and 501 do
+    pub fn from_queue_status(status: u16) -> Self {
+        match status {
+            200..=299 => Capability::Available,
+            403 | 501 => Capability::Unavailable,
+            _ => Capability::Unknown,
+        }
+    }
+
+    /// Unknown still gets a try; only a known refusal switches things off
+    pub fn usable(self) -> bool {
+        self != Capability::Unavailable
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Capability::Unknown => "unknown",
+            Capability::Available => "available",
+            Capability::Unavailable => "unavailable",
+        }
+    }
+
+    fn of<T>(result: ClientResult<T>, from_status: fn(u16) -> Self) -> Self {
+        match result {
+            Ok(_) => Capability::Available,
+            Err(ClientError::Http(e)) => match *e {
+                HttpError::StatusCode(resp) => from_status(resp.status().as_u16()),
+                _ => Capability::Unknown,
+            },
+            Err(_) => Capability::Unknown,
+        }
+    }
+}
+
+/// Endpoints whose availability depends on the app or account rather than
+/// on rspotify (which is pinned in Cargo.toml)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Capabilities {
+    pub recommendations: Capability,
+    pub audio_features: Capability,
+    pub queue: Capability,
+    /// Category playlists, which the Browse screen lists
+    pub browse: Capability,
+    pub featured: Capability,
+}
+
+impl Capabilities {
+    /// Name, what depends on it, result — for the `:features` popup
+    pub fn rows(&self) -> [(&'static str, &'static str, Capability); 5] {
+        [
+            ("recommendations", "Deprecated; Vibes uses search", self.recommendations),
+            ("audio-features", "Tempo sync & volume leveling", self.audio_features),
+            ("queue", "Queue screen & play next", self.queue),
+            ("browse", "Browse screen", self.browse),
+            ("featured-playlists", "Featured on Browse", self.featured),
+        ]
+    }
+}
+
+/// One cheap call per endpoint, run once after authentication
+pub async fn probe(spotify: Arc<Mutex<AuthCodePkceSpotify>>) -> Capabilities {
+    let sp = spotify.lock().await;
+    let track = TrackId::from_id(PROBE_TRACK).expect("valid probe track id");
+
+    let recommendations = Capability::of(
+        sp.recommendations(
+            Vec::<RecommendationsAttribute>::new(),
+            None::<Vec<ArtistId>>,
+            None::<Vec<&str>>,
+            Some([track.clone()]),
+            None,
+            Some(1),
+        )
+        .await,
+        Capability::from_status,
+    );
+    let audio_features = Capability::of(sp.track_features(track).await, Capability::from_status);
+    let queue = Capability::of(sp.current_user_queue().await, Capability::from_queue_status);
+    let browse = Capability::of(
+        sp.category_playlists_manual(PROBE_CATEGORY, None, Some(1), None).await,
+        Capability::from_status,
+    );
+    let featured = Capability::of(sp.featured_playlists(None, None, None, Some(1), None).await, Capability::from_status);
+
+    let caps = Capabilities { recommendations, audio_features, queue, browse, featured };
+    info!("API capabilities: {caps:?}");
+    caps
+}
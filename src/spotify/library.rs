@@ -1,13 +1,21 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use rspotify::{
-    model::{PlaylistId, SavedTrack, SimplifiedPlaylist, PlaylistItem},
+    model::{ArtistId, Category, FullArtist, FullTrack, Market, PlayableId, PlaylistId, PlaylistTracksRef, SavedAlbum, SavedTrack, SimplifiedPlaylist, PlaylistItem, TrackId},
     prelude::*,
     AuthCodePkceSpotify,
 };
-use futures::{StreamExt, TryStreamExt};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::collate::env_locale;
+use super::queue::playable_id;
+use super::retry::{send, send_pages};
+
+/// Liked songs per request, Spotify's maximum
+pub const LIKED_PAGE: usize = 50;
+/// Playlist tracks Spotify hands out per request, the most it allows
+pub const PLAYLIST_PAGE: usize = 100;
+
 pub struct Library {
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
 }
@@ -17,32 +25,233 @@ impl Library {
         Library { spotify }
     }
 
-    pub async fn get_liked_songs(&self, limit: u32) -> Result<Vec<SavedTrack>> {
-        let sp = self.spotify.lock().await;
-        let stream = sp.current_user_saved_tracks(None); // Removed Market::FromToken
-        let tracks: Vec<SavedTrack> = stream
-            .take(limit as usize)
-            .try_collect::<Vec<_>>()
-            .await?; // Proper error propagation
+    /// Every liked song in one go; the UI pages with `liked_songs_page` instead
+    pub async fn get_liked_songs(&self) -> Result<Vec<SavedTrack>> {
+        let tracks = send_pages(&self.spotify, LIKED_PAGE as u32, |sp, limit, offset| async move {
+            sp.current_user_saved_tracks_manual(None, Some(limit), Some(offset)).await
+        })
+        .await?; // Removed Market::FromToken
         Ok(tracks)
     }
 
-    pub async fn get_user_playlists(&self) -> Result<Vec<SimplifiedPlaylist>> {
-        let sp = self.spotify.lock().await;
-        let stream = sp.current_user_playlists();
-        let playlists: Vec<SimplifiedPlaylist> = stream
-            .try_collect()
+    /// `LIKED_PAGE` liked songs from `offset` on, newest first, with how many
+    /// there are in all
+    pub async fn liked_songs_page(&self, offset: usize) -> Result<(Vec<SavedTrack>, usize)> {
+        let page = send(&self.spotify, |sp| async move {
+            sp.current_user_saved_tracks_manual(None, Some(LIKED_PAGE as u32), Some(offset as u32)).await
+        })
+        .await?;
+        Ok((page.items, page.total as usize))
+    }
+
+    /// Every saved album with all its tracks. Spotify sends only the first 50
+    /// with each album, so the rest of a longer one is paged through
+    pub async fn get_saved_albums(&self) -> Result<Vec<SavedAlbum>> {
+        let mut albums = send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+            sp.current_user_saved_albums_manual(None, Some(limit), Some(offset)).await
+        })
+        .await?;
+        for saved in albums.iter_mut().filter(|s| s.album.tracks.next.is_some()) {
+            let id = &saved.album.id;
+            let tracks = send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+                sp.album_track_manual(id.clone(), None, Some(limit), Some(offset)).await
+            })
             .await?;
+            saved.album.tracks.items = tracks;
+            saved.album.tracks.next = None;
+        }
+        Ok(albums)
+    }
+
+    /// Every artist the user follows (cursor-paged, 50 at a time)
+    pub async fn get_followed_artists(&self) -> Result<Vec<FullArtist>> {
+        let mut artists = Vec::new();
+        let mut after: Option<String> = None;
+        loop {
+            let cursor = after.as_deref();
+            let page = send(&self.spotify, |sp| async move { sp.current_user_followed_artists(cursor, Some(50)).await }).await?;
+            artists.extend(page.items);
+            after = page.cursors.and_then(|c| c.after);
+            if page.next.is_none() || after.is_none() {
+                break;
+            }
+        }
+        Ok(artists)
+    }
+
+    pub async fn get_artist_top_tracks(&self, artist_id: &str) -> Result<Vec<FullTrack>> {
+        let id = &ArtistId::from_id_or_uri(artist_id)?;
+        send(&self.spotify, |sp| async move { sp.artist_top_tracks(id.clone(), Some(Market::FromToken)).await }).await
+    }
+
+    pub async fn unfollow_artist(&self, artist_id: &str) -> Result<()> {
+        let id = &ArtistId::from_id_or_uri(artist_id)?;
+        send(&self.spotify, |sp| async move { sp.user_unfollow_artists([id.clone()]).await }).await?;
+        Ok(())
+    }
+
+    pub async fn get_user_playlists(&self) -> Result<Vec<SimplifiedPlaylist>> {
+        let playlists = send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+            sp.current_user_playlists_manual(Some(limit), Some(offset)).await
+        })
+        .await?;
+        Ok(playlists)
+    }
+
+    /// Spotify's browse categories (genres, moods, charts…)
+    pub async fn get_categories(&self) -> Result<Vec<Category>> {
+        let categories = send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+            sp.categories_manual(None, None, Some(limit), Some(offset)).await
+        })
+        .await?;
+        Ok(categories)
+    }
+
+    /// Spotify's featured playlists and the message it shows above them
+    /// ("Monday morning picks"), in the system locale when `LANG` names one
+    pub async fn get_featured_playlists(&self) -> Result<(String, Vec<SimplifiedPlaylist>)> {
+        let locale = &env_locale("LC_MESSAGES");
+        let featured = send(&self.spotify, |sp| async move {
+            sp.featured_playlists(locale.as_deref(), None, None, Some(50), None).await
+        })
+        .await?;
+        Ok((featured.message, featured.playlists.items))
+    }
+
+    pub async fn get_category_playlists(&self, category_id: &str) -> Result<Vec<SimplifiedPlaylist>> {
+        let playlists = send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+            sp.category_playlists_manual(category_id, None, Some(limit), Some(offset)).await
+        })
+        .await?;
         Ok(playlists)
     }
 
     pub async fn get_playlist_tracks(&self, playlist_id: &str) -> Result<Vec<PlaylistItem>> {
-        let sp = self.spotify.lock().await;
-        let pid = PlaylistId::from_id(playlist_id)?;
-        let stream = sp.playlist_items(pid, None, None); // Removed Market::FromToken
-        let items: Vec<PlaylistItem> = stream
-            .try_collect()
-            .await?;
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        let items = send_pages(&self.spotify, PLAYLIST_PAGE as u32, |sp, limit, offset| async move {
+            sp.playlist_items_manual(pid.clone(), None, None, Some(limit), Some(offset)).await
+        })
+        .await?; // Removed Market::FromToken
         Ok(items)
     }
+
+    /// `PLAYLIST_PAGE` tracks of a playlist from `offset` on, with how many
+    /// it has in all
+    pub async fn playlist_page(&self, playlist_id: &str, offset: usize) -> Result<(Vec<PlaylistItem>, usize)> {
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        let page = send(&self.spotify, |sp| async move {
+            sp.playlist_items_manual(pid.clone(), None, None, Some(PLAYLIST_PAGE as u32), Some(offset as u32)).await
+        })
+        .await?;
+        Ok((page.items, page.total as usize))
+    }
+
+    /// Creates a private playlist owned by the current user
+    pub async fn create_playlist(&self, name: &str) -> Result<SimplifiedPlaylist> {
+        let user_id = &send(&self.spotify, |sp| async move { sp.current_user().await }).await?.id;
+        let pl = send(&self.spotify, |sp| async move {
+            sp.user_playlist_create(user_id.clone(), name, Some(false), None, None).await
+        })
+        .await?;
+        Ok(SimplifiedPlaylist {
+            collaborative: pl.collaborative,
+            external_urls: pl.external_urls,
+            href: pl.href,
+            id: pl.id,
+            images: pl.images,
+            name: pl.name,
+            owner: pl.owner,
+            public: pl.public,
+            snapshot_id: pl.snapshot_id,
+            tracks: PlaylistTracksRef { href: pl.tracks.href, total: pl.tracks.total },
+        })
+    }
+
+    /// Spotify id of the signed-in user
+    pub async fn current_user_id(&self) -> Result<String> {
+        let user = send(&self.spotify, |sp| async move { sp.current_user().await }).await?;
+        Ok(user.id.id().to_string())
+    }
+
+    /// Appends tracks or episodes, 100 per request (the API maximum); returns
+    /// the last snapshot id
+    pub async fn add_tracks_to_playlist(&self, playlist_id: &str, track_uris: &[String]) -> Result<String> {
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        let mut snapshot_id = String::new();
+        for chunk in track_uris.chunks(100) {
+            let ids = &chunk
+                .iter()
+                .map(|uri| playable_id(uri).ok_or_else(|| anyhow!("Not a track or episode: {uri}")))
+                .collect::<Result<Vec<_>>>()?;
+            snapshot_id = send(&self.spotify, |sp| async move {
+                sp.playlist_add_items(pid.as_ref(), ids.iter().cloned(), None).await
+            })
+            .await?.snapshot_id;
+        }
+        Ok(snapshot_id)
+    }
+
+    /// Removes every occurrence of a track; returns the new snapshot id
+    pub async fn remove_track_from_playlist(&self, playlist_id: &str, track_uri: &str) -> Result<String> {
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        let track_id = &TrackId::from_uri(track_uri)?;
+        let result = send(&self.spotify, |sp| async move {
+            sp.playlist_remove_all_occurrences_of_items(pid.clone(), [PlayableId::Track(track_id.clone())], None).await
+        })
+        .await?;
+        Ok(result.snapshot_id)
+    }
+
+    /// Adds someone else's playlist to the current user's library
+    pub async fn follow_playlist(&self, playlist_id: &str) -> Result<()> {
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        send(&self.spotify, |sp| async move { sp.playlist_follow(pid.clone(), None).await }).await?;
+        Ok(())
+    }
+
+    /// Removes a playlist from the library; for the user's own playlists
+    /// this is Spotify's delete
+    pub async fn unfollow_playlist(&self, playlist_id: &str) -> Result<()> {
+        let pid = &PlaylistId::from_id_or_uri(playlist_id)?;
+        send(&self.spotify, |sp| async move { sp.playlist_unfollow(pid.clone()).await }).await?;
+        Ok(())
+    }
+
+    /// Saves tracks to Liked Songs, 50 per request (the API maximum)
+    pub async fn like_tracks(&self, track_uris: &[String]) -> Result<()> {
+        for chunk in track_uris.chunks(50) {
+            let ids = &chunk.iter().map(|uri| TrackId::from_uri(uri)).collect::<Result<Vec<_>, _>>()?;
+            send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_add(ids.iter().cloned()).await }).await?;
+        }
+        Ok(())
+    }
+
+    /// Whether each track is in Liked Songs, in order, 50 per request
+    pub async fn liked_status(&self, track_uris: &[String]) -> Result<Vec<bool>> {
+        let mut liked = Vec::with_capacity(track_uris.len());
+        for chunk in track_uris.chunks(50) {
+            let ids = &chunk.iter().map(|uri| TrackId::from_uri(uri)).collect::<Result<Vec<_>, _>>()?;
+            liked.extend(send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_contains(ids.iter().cloned()).await }).await?);
+        }
+        Ok(liked)
+    }
+
+    /// Full details of tracks by URI, 50 per request
+    pub async fn get_tracks(&self, track_uris: &[String]) -> Result<Vec<FullTrack>> {
+        let mut tracks = Vec::with_capacity(track_uris.len());
+        for chunk in track_uris.chunks(50) {
+            let ids = &chunk.iter().map(|uri| TrackId::from_uri(uri)).collect::<Result<Vec<_>, _>>()?;
+            tracks.extend(send(&self.spotify, |sp| async move { sp.tracks(ids.iter().cloned(), Some(Market::FromToken)).await }).await?);
+        }
+        Ok(tracks)
+    }
+
+    /// Removes tracks from Liked Songs, 50 per request
+    pub async fn unlike_tracks(&self, track_uris: &[String]) -> Result<()> {
+        for chunk in track_uris.chunks(50) {
+            let ids = &chunk.iter().map(|uri| TrackId::from_uri(uri)).collect::<Result<Vec<_>, _>>()?;
+            send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_delete(ids.iter().cloned()).await }).await?;
+        }
+        Ok(())
+    }
 }
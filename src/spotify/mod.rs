@@ -1,43 +1,149 @@
 use anyhow::Result;
 use rspotify::{
     clients::{BaseClient, OAuthClient},
-    scopes, AuthCodePkceSpotify, Config as SpotifyConfig, Credentials, OAuth,
+    http::HttpError,
+    AuthCodePkceSpotify, ClientError, Config as SpotifyConfig, Credentials, OAuth,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::cache::Cache;
 use crate::config::Config;
+use crate::secrets;
 use self::auth::PkceChallenge;
 
+pub mod analysis;
 pub mod auth;
+pub mod capabilities;
 pub mod library;
 pub mod player;
 pub mod queue;
+pub mod retry;
 pub mod search;
+pub mod stats;
 pub mod vibes;
 
 const TOKEN_CACHE_KEY: &str = "vibes:spotify_token";
 
+/// What the login asks to be allowed
+const SCOPES: &[&str] = &[
+    "user-read-playback-state",
+    "user-modify-playback-state",
+    "user-read-currently-playing",
+    "user-library-read",
+    "user-library-modify",
+    "playlist-read-private",
+    "playlist-read-collaborative",
+    "playlist-modify-private",
+    "playlist-modify-public",
+    "user-follow-read",
+    "user-follow-modify",
+    "user-top-read",
+    "user-read-recently-played",
+    "user-read-private",
+    "user-read-email",
+    "streaming",
+];
+
+/// Where the login token is kept between runs: the keyring entry when the
+/// keyring is on, else Redis (the cache is already scoped to the profile)
+pub struct TokenStore<'a> {
+    pub cache: &'a Cache,
+    pub keyring: Option<String>,
+}
+
+impl TokenStore<'_> {
+    /// A token still in Redis moves to the keyring the first time it's read
+    async fn load(&self) -> Option<String> {
+        if let Some(ref entry) = self.keyring {
+            match secrets::get(entry).await {
+                Ok(Some(token)) => return Some(token),
+                Ok(None) => {
+                    let token = self.cache.get(TOKEN_CACHE_KEY).await.ok()??;
+                    match secrets::set(entry, token.clone()).await {
+                        Ok(()) => {
+                            self.cache.delete(TOKEN_CACHE_KEY).await.ok();
+                            info!("Moved the cached token from Redis to the keyring");
+                        }
+                        Err(e) => warn!("Token left in Redis, keyring not writable: {e}"),
+                    }
+                    return Some(token);
+                }
+                Err(e) => warn!("Keyring unavailable ({e}), reading the token from Redis"),
+            }
+        }
+        self.cache.get(TOKEN_CACHE_KEY).await.ok().flatten()
+    }
+
+    /// Redis is the fallback when the keyring is off or can't be written
+    async fn save(&self, token_json: &str) {
+        if let Some(ref entry) = self.keyring {
+            match secrets::set(entry, token_json.to_string()).await {
+                Ok(()) => {
+                    info!("Token saved to the keyring");
+                    return;
+                }
+                Err(e) => warn!("Keyring not writable ({e}), caching the token in Redis"),
+            }
+        }
+        self.cache.set(TOKEN_CACHE_KEY, token_json, Some(3600 * 24)).await.ok();
+        info!("Token saved to Redis cache");
+    }
+
+    /// Drops the token wherever it's kept, so the next run logs in afresh
+    pub async fn forget(&self) {
+        if let Some(ref entry) = self.keyring {
+            if let Err(e) = secrets::delete(entry).await {
+                warn!("Token not removed from the keyring: {e}");
+            }
+        }
+        self.cache.delete(TOKEN_CACHE_KEY).await.ok();
+    }
+}
+
+/// Fills in `config.client_secret` from the keyring when it isn't in the
+/// environment. One that is gets copied into the keyring, so it can be taken
+/// out of `.env`.
+pub async fn resolve_client_secret(config: &mut Config, keyring: bool) -> Result<()> {
+    let var = match config.profile.as_deref().filter(|_| config.profile_app) {
+        Some(profile) => format!("SPOTIFY_CLIENT_SECRET_{}", crate::config::profile_env_suffix(profile)),
+        None => "SPOTIFY_CLIENT_SECRET".to_string(),
+    };
+    if keyring {
+        let entry = secrets::client_secret_entry(config);
+        let stored = match secrets::get(&entry).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("Keyring unavailable: {e}");
+                None
+            }
+        };
+        match stored {
+            Some(secret) if config.client_secret.is_empty() => config.client_secret = secret,
+            Some(secret) if secret == config.client_secret => {}
+            _ if config.client_secret.is_empty() => {}
+            _ => match secrets::set(&entry, config.client_secret.clone()).await {
+                Ok(()) => info!("{var} copied to the keyring; it can be removed from .env"),
+                Err(e) => warn!("Client secret not stored in the keyring: {e}"),
+            },
+        }
+    }
+    if config.client_secret.is_empty() {
+        anyhow::bail!("{var} is missing from .env, the environment and the keyring");
+    }
+    Ok(())
+}
+
+/// The client, and whether the saved token still works; when it doesn't,
+/// log in through `authorize_url`
 pub async fn build_spotify_client(
     config: &Config,
-    cache: &Cache,
-) -> Result<(Arc<Mutex<AuthCodePkceSpotify>>, Option<String>)> {
+    tokens: &TokenStore<'_>,
+) -> Result<(Arc<Mutex<AuthCodePkceSpotify>>, bool)> {
     let creds = Credentials::new(&config.client_id, &config.client_secret);
 
-    let scopes = scopes!(
-        "user-read-playback-state",
-        "user-modify-playback-state",
-        "user-read-currently-playing",
-        "user-library-read",
-        "user-library-modify",
-        "playlist-read-private",
-        "playlist-read-collaborative",
-        "user-read-private",
-        "user-read-email",
-        "streaming"
-    );
+    let scopes = SCOPES.iter().map(|s| s.to_string()).collect();
 
     let oauth = OAuth {
         redirect_uri: config.redirect_uri.clone(),
@@ -50,45 +156,76 @@ pub async fn build_spotify_client(
         ..Default::default()
     };
 
-    let mut spotify = AuthCodePkceSpotify::with_config(creds, oauth, sp_config);
+    let spotify = AuthCodePkceSpotify::with_config(creds, oauth, sp_config);
 
-    // Try loading cached token from Redis
-    if let Ok(Some(token_json)) = cache.get(TOKEN_CACHE_KEY).await {
+    // Try loading the saved token
+    if let Some(token_json) = tokens.load().await {
         if let Ok(token) = serde_json::from_str::<rspotify::Token>(&token_json) {
-            info!("Loaded cached token from Redis");
+            info!("Loaded saved token");
             *spotify.token.lock().await.unwrap() = Some(token.clone());
             
             // Attempt to refresh the token to ensure it's still valid
-            match spotify.refetch_token().await {
+            match spotify.refetch_token().await.map_err(anyhow::Error::new) {
                 Ok(_) => {
                     let client = Arc::new(Mutex::new(spotify));
-                    return Ok((client, None));
+                    return Ok((client, true));
                 }
-                Err(e) => {
+                Err(e) if is_unauthorized(&e) => {
                     tracing::warn!("Failed to refresh cached token ({}), clearing cache and re-authenticating", e);
                     // Clear invalid token from memory and cache
                     *spotify.token.lock().await.unwrap() = None;
-                    cache.delete(TOKEN_CACHE_KEY).await.ok();
+                    tokens.forget().await;
+                }
+                Err(e) => {
+                    // Spotify or the network having a bad moment: the token is
+                    // still good, and requests refresh it once they get through
+                    tracing::warn!("Couldn't refresh cached token ({}), keeping it", e);
+                    return Ok((Arc::new(Mutex::new(spotify)), true));
                 }
             }
         }
     }
 
-    // Generate PKCE challenge and auth URL
-    let pkce = PkceChallenge::new();
-    let url = spotify.get_authorize_url(Some(pkce.challenge.len()))?;
+    Ok((Arc::new(Mutex::new(spotify)), false))
+}
+
+/// The Spotify login page for `pkce`, sending the browser back to
+/// `redirect_uri` (the configured one, or a fallback port when that was
+/// taken). The client remembers the address, as exchanging the code repeats it.
+pub async fn authorize_url(spotify: &Arc<Mutex<AuthCodePkceSpotify>>, redirect_uri: &str, pkce: &PkceChallenge) -> String {
+    let mut sp = spotify.lock().await;
+    sp.oauth.redirect_uri = redirect_uri.to_string();
+    sp.oauth.state = pkce.state.clone();
     info!("Auth URL generated, opening browser...");
+    pkce.authorize_url(&sp.creds.id, redirect_uri, SCOPES)
+}
 
-    Ok((Arc::new(Mutex::new(spotify)), Some(url)))
+/// Whether `error` is Spotify turning the login down: a 401, a refused
+/// token refresh, or no usable token at all. Logging in again fixes these.
+/// The token endpoint refuses a refresh (`invalid_grant`) with a 400; its
+/// 5xx and 429 answers are passing trouble, left to the retry layer.
+pub fn is_unauthorized(error: &anyhow::Error) -> bool {
+    error.chain().any(|e| match e.downcast_ref::<ClientError>() {
+        Some(ClientError::InvalidToken) => true,
+        Some(ClientError::Http(http)) => match **http {
+            HttpError::StatusCode(ref resp) => {
+                resp.status() == 401 || (resp.status() == 400 && resp.url().path() == "/api/token")
+            }
+            _ => false,
+        },
+        _ => false,
+    })
 }
 
 pub async fn complete_auth(
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
     code: &str,
-    _verifier: &str,
-    cache: &Cache,
+    verifier: &str,
+    tokens: &TokenStore<'_>,
 ) -> Result<()> {
-    let sp = spotify.lock().await;
+    let mut sp = spotify.lock().await;
+    // The verifier whose challenge the authorize URL carried
+    sp.verifier = Some(verifier.to_string());
     sp.request_token(code).await?;
 
     // Cache the token
@@ -96,8 +233,7 @@ pub async fn complete_auth(
     if let Some(ref token) = *token_guard {
         let token_json = serde_json::to_string(token)?;
         drop(token_guard);
-        cache.set(TOKEN_CACHE_KEY, &token_json, Some(3600 * 24)).await.ok();
-        info!("Token saved to Redis cache");
+        tokens.save(&token_json).await;
     }
 
     Ok(())
@@ -1,7 +1,7 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use rspotify::{
     model::{
-        AdditionalType, Market, PlayableItem, TrackId,
+        AdditionalType, AlbumId, ArtistId, Device, EpisodeId, FullTrack, Market, Offset, PlayContextId, PlayableItem, PlaylistId, RepeatState, TrackId,
     },
     prelude::*,
     AuthCodePkceSpotify,
@@ -11,6 +11,61 @@ use tokio::sync::Mutex;
 use tracing::info;
 
 use crate::app::state::CurrentTrack;
+use super::retry::send;
+
+/// A hand-built play batch after the market check
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayableBatch {
+    /// URIs that can play here, in order; relinked tracks are swapped for the
+    /// copy Spotify offers in the user's market
+    pub uris: Vec<String>,
+    /// Name of the track playback starts on
+    pub first: Option<String>,
+    /// Tracks left out because they can't play here
+    pub excluded: usize,
+}
+
+impl PlayableBatch {
+    /// Keeps the tracks a market-aware lookup says are playable. The ones
+    /// Spotify didn't return at all count as excluded too
+    pub fn from_tracks(requested: usize, tracks: &[FullTrack]) -> PlayableBatch {
+        let mut batch = PlayableBatch { excluded: requested.saturating_sub(tracks.len()), ..Default::default() };
+        for track in tracks {
+            match track.id.as_ref().filter(|_| !track.is_local && track.is_playable != Some(false)) {
+                Some(id) => {
+                    batch.first.get_or_insert_with(|| track.name.clone());
+                    batch.uris.push(id.uri());
+                }
+                None => batch.excluded += 1,
+            }
+        }
+        batch
+    }
+
+    /// `msg`, plus how many tracks were skipped when there were any
+    pub fn toast(&self, msg: impl Into<String>) -> String {
+        let msg = msg.into();
+        match self.excluded {
+            0 => msg,
+            1 => format!("{msg} · 1 unavailable track skipped"),
+            n => format!("{msg} · {n} unavailable tracks skipped"),
+        }
+    }
+}
+
+/// The device `wanted` names for `vibes ctl transfer`: its ID, its name in
+/// any case, or failing those the one device whose name contains it
+pub fn find_device<'a>(devices: &'a [Device], wanted: &str) -> Option<&'a Device> {
+    let wanted_lower = wanted.to_lowercase();
+    devices
+        .iter()
+        .find(|d| d.id.as_deref() == Some(wanted))
+        .or_else(|| devices.iter().find(|d| d.name.to_lowercase() == wanted_lower))
+        .or_else(|| {
+            let mut partial = devices.iter().filter(|d| d.name.to_lowercase().contains(&wanted_lower));
+            partial.next().filter(|_| partial.next().is_none())
+        })
+}
 
 pub struct Player {
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
@@ -22,14 +77,15 @@ impl Player {
     }
 
     pub async fn get_current_playback(&self) -> Result<Option<CurrentTrack>> {
-        let sp = self.spotify.lock().await;
-        let additional = [AdditionalType::Track];
-        let playback = sp
-            .current_playback(Some(Market::FromToken), Some(additional.iter()))
-            .await?;
+        let additional = [AdditionalType::Track, AdditionalType::Episode];
+        let playback = send(&self.spotify, |sp| async move {
+            sp.current_playback(Some(Market::FromToken), Some(additional.iter())).await
+        })
+        .await?;
 
         if let Some(ctx) = playback {
             let device_vol = ctx.device.volume_percent.map(|v| v.clamp(0, 100) as u8);
+            let context_uri = ctx.context.as_ref().map(|c| c.uri.clone());
             if let Some(PlayableItem::Track(track)) = ctx.item {
                 let track_id = track.id.as_ref().map(|id| id.to_string());
                 let is_playing = ctx.is_playing;
@@ -40,12 +96,43 @@ impl Player {
                     name: track.name.clone(),
                     artists: track.artists.iter().map(|a| a.name.clone()).collect(),
                     album: track.album.name.clone(),
+                    album_year: track.album.release_date.as_deref().and_then(|d| d.get(..4)).map(str::to_string),
                     duration_ms: track.duration.num_milliseconds() as u32,
                     progress_ms,
                     is_playing,
                     is_liked: false,
                     album_art_url: track.album.images.first().map(|i| i.url.clone()),
+                    album_art_thumb_url: track.album.images.last().map(|i| i.url.clone()),
                     device_volume: device_vol,
+                    device_id: ctx.device.id.clone(),
+                    device_name: Some(ctx.device.name.clone()),
+                    context_uri: context_uri.clone(),
+                    is_episode: false,
+                    shuffle: ctx.shuffle_state,
+                    repeat: (ctx.repeat_state != RepeatState::Off).then_some(ctx.repeat_state),
+                };
+                return Ok(Some(ct));
+            }
+            if let Some(PlayableItem::Episode(episode)) = ctx.item {
+                let ct = CurrentTrack {
+                    id: Some(episode.id.to_string()),
+                    name: episode.name.clone(),
+                    artists: vec![episode.show.publisher.clone()],
+                    album: episode.show.name.clone(),
+                    album_year: None,
+                    duration_ms: episode.duration.num_milliseconds() as u32,
+                    progress_ms: ctx.progress.map(|p| p.num_milliseconds() as u32).unwrap_or(0),
+                    is_playing: ctx.is_playing,
+                    is_liked: false,
+                    album_art_url: episode.images.first().map(|i| i.url.clone()),
+                    album_art_thumb_url: episode.images.last().map(|i| i.url.clone()),
+                    device_volume: device_vol,
+                    device_id: ctx.device.id.clone(),
+                    device_name: Some(ctx.device.name.clone()),
+                    context_uri: context_uri.clone(),
+                    is_episode: true,
+                    shuffle: ctx.shuffle_state,
+                    repeat: (ctx.repeat_state != RepeatState::Off).then_some(ctx.repeat_state),
                 };
                 return Ok(Some(ct));
             }
@@ -53,10 +140,27 @@ impl Player {
         Ok(None)
     }
 
+    /// Ids of the devices Spotify currently lists for the account
+    pub async fn device_ids(&self) -> Result<Vec<String>> {
+        Ok(send(&self.spotify, |sp| async move { sp.device().await }).await?.into_iter().filter_map(|d| d.id).collect())
+    }
+
+    /// Every device Spotify Connect can play on right now
+    pub async fn devices(&self) -> Result<Vec<Device>> {
+        send(&self.spotify, |sp| async move { sp.device().await }).await
+    }
+
+    /// Moves playback to `device_id`. `play` starts it there; otherwise it
+    /// keeps playing or stays paused, as it was
+    pub async fn transfer(&self, device_id: &str, play: bool) -> Result<()> {
+        send(&self.spotify, |sp| async move { sp.transfer_playback(device_id, Some(play)).await }).await?;
+        info!("Transferred playback to device {device_id} (play: {play})");
+        Ok(())
+    }
+
     /// Get the first available device ID, or return an error with helpful message
     async fn get_device_id(&self) -> Result<String> {
-        let sp = self.spotify.lock().await;
-        let devices = sp.device().await?;
+        let devices = send(&self.spotify, |sp| async move { sp.device().await }).await?;
 
         // Try to find an active device first, then any device
         if let Some(dev) = devices.iter().find(|d| d.is_active) {
@@ -66,101 +170,181 @@ impl Player {
             return Ok(dev.id.clone().unwrap_or_default());
         }
 
+        // Builds with librespot list themselves once they've logged in
+        if cfg!(feature = "librespot") {
+            bail!("No Spotify device found! The built-in one isn't up yet; give it a moment, or open Spotify elsewhere.");
+        }
         Err(anyhow!(
             "No Spotify device found! Open Spotify on your phone, desktop, or web browser first."
         ))
     }
 
-    // Replaced `play_track` with `play_tracks` to support Queue context
+    // Replaced `play_track` with `play_tracks` to support Queue context.
+    // Lists without a Spotify context (search results, Liked Songs, Vibes)
+    // still go through here; albums and playlists use `play_context`.
 
     pub async fn play_tracks(&self, uris: Vec<&str>) -> Result<()> {
-        let device_id = self.get_device_id().await?;
-        let sp = self.spotify.lock().await;
-
-        let mut playable_ids = Vec::new();
-        for uri in uris {
-            // Silently ignore invalid URIs
-            if let Ok(id) = TrackId::from_uri(uri) {
-                playable_ids.push(PlayableId::Track(id));
-            }
-        }
+        let device_id = &self.get_device_id().await?;
+        // Silently ignore invalid URIs
+        let playable_ids = &uris
+            .into_iter()
+            .filter_map(|uri| TrackId::from_uri(uri).ok())
+            .map(PlayableId::Track)
+            .collect::<Vec<_>>();
 
-        sp.start_uris_playback(
-            playable_ids,
-            Some(&device_id),
-            None,
-            None,
-        )
+        send(&self.spotify, |sp| async move {
+            sp.start_uris_playback(playable_ids.iter().cloned(), Some(device_id.as_str()), None, None).await
+        })
         .await?;
         info!("Playing multiple tracks on device {device_id}");
         Ok(())
     }
 
+    /// Looks `uris` up for the user's market before they go to `play_tracks`.
+    /// Spotify skips unplayable tracks on its own, so without this a batch
+    /// can quietly start further down than the track that was picked
+    pub async fn playable_batch(&self, uris: &[String]) -> Result<PlayableBatch> {
+        let mut tracks = Vec::with_capacity(uris.len());
+        for chunk in uris.chunks(50) {
+            let ids = &chunk.iter().map(|uri| TrackId::from_uri(uri)).collect::<Result<Vec<_>, _>>()?;
+            tracks.extend(send(&self.spotify, |sp| async move { sp.tracks(ids.iter().cloned(), Some(Market::FromToken)).await }).await?);
+        }
+        Ok(PlayableBatch::from_tracks(uris.len(), &tracks))
+    }
+
+    /// Offset of the `index`th entry of a context. Unlike a URI it's right
+    /// when the same track is in a playlist twice. rspotify sends the
+    /// position as the delta's milliseconds, hence the odd type.
+    pub fn row_offset(index: usize) -> Offset {
+        Offset::Position(chrono::TimeDelta::milliseconds(index as i64))
+    }
+
+    /// Plays a whole album/playlist/artist so Spotify carries on through the
+    /// entire context (with shuffle, repeat and autoplay radio working as in
+    /// the official apps), starting at `offset` when given.
+    pub async fn play_context(&self, context_uri: &str, offset: Option<Offset>) -> Result<()> {
+        let device_id = &self.get_device_id().await?;
+        let context = &match context_uri.split(':').nth(1) {
+            Some("album") => PlayContextId::from(AlbumId::from_uri(context_uri)?),
+            Some("playlist") => PlayContextId::from(PlaylistId::from_uri(context_uri)?),
+            Some("artist") => PlayContextId::from(ArtistId::from_uri(context_uri)?),
+            _ => return Err(anyhow!("Not a playable context: {context_uri}")),
+        };
+        let offset = &offset;
+        send(&self.spotify, |sp| async move {
+            sp.start_context_playback(context.clone(), Some(device_id.as_str()), offset.clone(), None).await
+        })
+        .await?;
+        info!("Playing context {context_uri} on device {device_id}");
+        Ok(())
+    }
+
+    /// Picks up the account's last session: hands it to the active (or first
+    /// available) device and starts playing. Errors when no device is open.
+    pub async fn resume_session(&self) -> Result<()> {
+        let device_id = &self.get_device_id().await?;
+        send(&self.spotify, |sp| async move { sp.transfer_playback(device_id, Some(true)).await }).await?;
+        info!("Resumed last session on device {device_id}");
+        Ok(())
+    }
+
+    /// Starts `uri` at `position_ms` on the active (or first available) device,
+    /// for picking up where a vanished device left off. Within its album or
+    /// playlist when `context_uri` is one, so playback carries on from there.
+    pub async fn play_at(&self, uri: &str, context_uri: Option<&str>, position_ms: u32) -> Result<()> {
+        use chrono::TimeDelta;
+        let device_id = &self.get_device_id().await?;
+        let pos = TimeDelta::milliseconds(position_ms as i64);
+        let context = &match context_uri {
+            Some(c) if c.starts_with("spotify:album:") => Some(PlayContextId::from(AlbumId::from_uri(c)?)),
+            Some(c) if c.starts_with("spotify:playlist:") => Some(PlayContextId::from(PlaylistId::from_uri(c)?)),
+            _ => None,
+        };
+        if let Some(context) = context {
+            let offset = &Offset::Uri(uri.to_string());
+            send(&self.spotify, |sp| async move {
+                sp.start_context_playback(context.clone(), Some(device_id.as_str()), Some(offset.clone()), Some(pos)).await
+            })
+            .await?;
+            info!("Resumed {uri} in its context at {position_ms}ms on device {device_id}");
+            return Ok(());
+        }
+        let id = &match uri.split(':').nth(1) {
+            Some("episode") => PlayableId::Episode(EpisodeId::from_uri(uri)?),
+            _ => PlayableId::Track(TrackId::from_uri(uri)?),
+        };
+        send(&self.spotify, |sp| async move {
+            sp.start_uris_playback([id.clone()], Some(device_id.as_str()), None, Some(pos)).await
+        })
+        .await?;
+        info!("Resumed {uri} at {position_ms}ms on device {device_id}");
+        Ok(())
+    }
+
     pub async fn pause(&self) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        sp.pause_playback(None).await?;
+        send(&self.spotify, |sp| async move { sp.pause_playback(None).await }).await?;
         Ok(())
     }
 
     pub async fn resume(&self) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        sp.resume_playback(None, None).await?;
+        send(&self.spotify, |sp| async move { sp.resume_playback(None, None).await }).await?;
+        Ok(())
+    }
+
+    /// Resume from `position_ms` instead of where playback stopped
+    pub async fn resume_at(&self, position_ms: u32) -> Result<()> {
+        use chrono::TimeDelta;
+        let pos = TimeDelta::milliseconds(position_ms as i64);
+        send(&self.spotify, |sp| async move { sp.resume_playback(None, Some(pos)).await }).await?;
         Ok(())
     }
 
-    pub async fn toggle_playback(&self, is_playing: bool) -> Result<()> {
-        if is_playing {
-            self.pause().await
-        } else {
-            self.resume().await
+    /// `rewind_to` is the smart-resume position, if the pause was long enough to rewind
+    pub async fn toggle_playback(&self, is_playing: bool, rewind_to: Option<u32>) -> Result<()> {
+        match (is_playing, rewind_to) {
+            (true, _) => self.pause().await,
+            (false, Some(pos)) => self.resume_at(pos).await,
+            (false, None) => self.resume().await,
         }
     }
 
     pub async fn next_track(&self) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        sp.next_track(None).await?;
+        send(&self.spotify, |sp| async move { sp.next_track(None).await }).await?;
         Ok(())
     }
 
     pub async fn previous_track(&self) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        sp.previous_track(None).await?;
+        send(&self.spotify, |sp| async move { sp.previous_track(None).await }).await?;
         Ok(())
     }
 
     pub async fn seek(&self, position_ms: u32) -> Result<()> {
         use chrono::TimeDelta;
         let pos = TimeDelta::milliseconds(position_ms as i64);
-        let sp = self.spotify.lock().await;
-        sp.seek_track(pos, None).await?;
+        send(&self.spotify, |sp| async move { sp.seek_track(pos, None).await }).await?;
         Ok(())
     }
 
     pub async fn set_volume(&self, volume: u8) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        sp.volume(volume, None).await?;
+        send(&self.spotify, |sp| async move { sp.volume(volume, None).await }).await?;
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn is_track_saved(&self, track_id: &str) -> Result<bool> {
-        let sp = self.spotify.lock().await;
-        let id = TrackId::from_id(track_id)?;
-        let results = sp.current_user_saved_tracks_contains([id]).await?;
+        let id = &TrackId::from_id_or_uri(track_id)?;
+        let results = send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_contains([id.clone()]).await }).await?;
         Ok(results.into_iter().next().unwrap_or(false))
     }
 
     pub async fn save_track(&self, track_id: &str) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        let id = TrackId::from_id(track_id)?;
-        sp.current_user_saved_tracks_add([id]).await?;
+        let id = &TrackId::from_id_or_uri(track_id)?;
+        send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_add([id.clone()]).await }).await?;
         Ok(())
     }
 
     pub async fn remove_track(&self, track_id: &str) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        let id = TrackId::from_id(track_id)?;
-        sp.current_user_saved_tracks_delete([id]).await?;
+        let id = &TrackId::from_id_or_uri(track_id)?;
+        send(&self.spotify, |sp| async move { sp.current_user_saved_tracks_delete([id.clone()]).await }).await?;
         Ok(())
     }
 }
@@ -1,12 +1,16 @@
 use anyhow::Result;
+use chrono::TimeDelta;
 use rspotify::{
-    model::{FullTrack, PlayableItem, TrackId},
+    model::{AlbumId, EpisodeId, FullTrack, PlayableItem, PlaylistId, TrackId},
     prelude::*,
     AuthCodePkceSpotify,
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::sync::Mutex;
 
+use super::library::PLAYLIST_PAGE;
+use super::retry::{send, send_pages};
+
 pub struct Queue {
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
 }
@@ -17,8 +21,7 @@ impl Queue {
     }
 
     pub async fn get_queue(&self) -> Result<Vec<FullTrack>> {
-        let sp = self.spotify.lock().await;
-        let queue = sp.current_user_queue().await?;
+        let queue = send(&self.spotify, |sp| async move { sp.current_user_queue().await }).await?;
         let tracks: Vec<FullTrack> = queue
             .queue
             .into_iter()
@@ -34,9 +37,110 @@ impl Queue {
     }
 
     pub async fn add_to_queue(&self, track_uri: &str) -> Result<()> {
-        let sp = self.spotify.lock().await;
-        let track_id = TrackId::from_uri(track_uri)?;
-        sp.add_item_to_queue(PlayableId::Track(track_id), None).await?;
+        let track_id = &TrackId::from_uri(track_uri)?;
+        send(&self.spotify, |sp| async move { sp.add_item_to_queue(PlayableId::Track(track_id.clone()), None).await }).await?;
+        Ok(())
+    }
+
+    /// Appends `uris` one at a time, `pace` apart so a long list doesn't trip
+    /// the rate limit. Stops at the first failure; returns how many were added
+    /// alongside it.
+    pub async fn add_all(&self, uris: &[String], pace: Duration) -> (usize, Result<()>) {
+        for (added, uri) in uris.iter().enumerate() {
+            if added > 0 {
+                tokio::time::sleep(pace).await;
+            }
+            // Locked per call so playback polls can get in between
+            if let Err(e) = self.add_to_queue(uri).await {
+                return (added, Err(e));
+            }
+        }
+        (uris.len(), Ok(()))
+    }
+
+    /// Takes the URIs in `drop` off what's coming up. Spotify can't remove
+    /// from the queue, so `current` is restarted at `position_ms` followed by
+    /// everything else still to come: the rest of the album or playlist when
+    /// `context` is one (the queue only lists the next 20 or so), else what
+    /// the queue lists. Tracks in `manual` are left out of the restart, as
+    /// Spotify keeps what was queued by hand through it.
+    pub async fn replace_upcoming(
+        &self,
+        current: &str,
+        context: Option<&str>,
+        drop: &HashSet<String>,
+        manual: &HashSet<String>,
+        position_ms: u32,
+    ) -> Result<()> {
+        let rest = match context {
+            Some(context) => self.context_after(context, current).await?,
+            None => None,
+        };
+        let rest = match rest {
+            Some(rest) => rest,
+            None => send(&self.spotify, |sp| async move { sp.current_user_queue().await })
+                .await?
+                .queue
+                .iter()
+                .filter_map(playable_uri)
+                .collect(),
+        };
+        let order = restart_order(current, &rest, drop, manual);
+        let ids = &order.iter().filter_map(|uri| playable_id(uri)).collect::<Vec<_>>();
+        let position = TimeDelta::milliseconds(position_ms as i64);
+        send(&self.spotify, |sp| async move { sp.start_uris_playback(ids.iter().cloned(), None, None, Some(position)).await }).await?;
         Ok(())
     }
+
+    /// What an album or playlist holds after `current`, in order; `None` for
+    /// other contexts, or when `current` isn't in it
+    async fn context_after(&self, context: &str, current: &str) -> Result<Option<Vec<String>>> {
+        let uris: Vec<String> = match context.split(':').nth(1) {
+            Some("playlist") => {
+                let pid = &PlaylistId::from_uri(context)?;
+                send_pages(&self.spotify, PLAYLIST_PAGE as u32, |sp, limit, offset| async move {
+                    sp.playlist_items_manual(pid.clone(), None, None, Some(limit), Some(offset)).await
+                })
+                .await?
+                .iter()
+                .filter_map(|item| item.track.as_ref().and_then(playable_uri))
+                .collect()
+            }
+            Some("album") => {
+                let aid = &AlbumId::from_uri(context)?;
+                send_pages(&self.spotify, 50, |sp, limit, offset| async move {
+                    sp.album_track_manual(aid.clone(), None, Some(limit), Some(offset)).await
+                })
+                .await?
+                .into_iter()
+                .filter_map(|t| t.id.map(|id| id.uri()))
+                .collect()
+            }
+            _ => return Ok(None),
+        };
+        Ok(uris.iter().position(|uri| uri == current).map(|i| uris[i + 1..].to_vec()))
+    }
+}
+
+/// `current`, then whatever in `rest` isn't dropped or queued by hand
+pub fn restart_order(current: &str, rest: &[String], drop: &HashSet<String>, manual: &HashSet<String>) -> Vec<String> {
+    std::iter::once(current.to_string())
+        .chain(rest.iter().filter(|uri| !drop.contains(*uri) && !manual.contains(*uri)).cloned())
+        .collect()
+}
+
+/// The URI of a queued track or episode
+fn playable_uri(item: &PlayableItem) -> Option<String> {
+    match item {
+        PlayableItem::Track(t) => t.id.as_ref().map(|id| id.uri()),
+        PlayableItem::Episode(e) => Some(e.id.uri()),
+    }
+}
+
+/// A track or episode URI as something Spotify can play
+pub(crate) fn playable_id(uri: &str) -> Option<PlayableId<'_>> {
+    match uri.split(':').nth(1) {
+        Some("episode") => EpisodeId::from_uri(uri).ok().map(PlayableId::Episode),
+        _ => TrackId::from_uri(uri).ok().map(PlayableId::Track),
+    }
 }
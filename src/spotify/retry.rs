@@ -0,0 +1,159 @@
+use anyhow::{bail, Result};
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use rspotify::{http::HttpError, model::Page, AuthCodePkceSpotify, ClientError, ClientResult};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tokio::sync::{Mutex as ClientLock, OwnedMutexGuard};
+use tracing::{debug, warn};
+
+/// Times one request is sent again before its error is kept
+const MAX_RETRIES: u32 = 3;
+/// Rate limits longer than this aren't sat out; the request fails straight away
+const MAX_WAIT: Duration = Duration::from_secs(30);
+/// The first pause after a network error; it doubles for each retry
+const BACKOFF_BASE: Duration = Duration::from_millis(250);
+/// The longest pause between network retries
+const BACKOFF_CAP: Duration = Duration::from_secs(4);
+
+/// When Spotify will take requests again after the last 429
+#[derive(Debug, Default)]
+pub struct RateLimit {
+    until: Option<Instant>,
+    /// 429s seen, so the app can tell a new one from the one it toasted
+    hits: u64,
+}
+
+impl RateLimit {
+    /// Notes a 429 asking for `wait`; a later `until` already noted stands
+    pub fn hit(&mut self, wait: Duration, now: Instant) {
+        let until = now + wait;
+        self.until = Some(self.until.map_or(until, |u| u.max(until)));
+        self.hits += 1;
+    }
+
+    /// How long requests still have to wait, if at all
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.until.filter(|&u| u > now).map(|u| u - now)
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+}
+
+/// The limit shared by every request this process makes
+fn limit() -> &'static Mutex<RateLimit> {
+    static LIMIT: OnceLock<Mutex<RateLimit>> = OnceLock::new();
+    LIMIT.get_or_init(Default::default)
+}
+
+/// 429s seen so far; compare with a previous value to spot a new one
+pub fn rate_limit_hits() -> u64 {
+    limit().lock().map(|l| l.hits()).unwrap_or_default()
+}
+
+/// How long until Spotify takes requests again, while it's rate limiting
+pub fn rate_limited_for() -> Option<Duration> {
+    limit().lock().ok()?.remaining(Instant::now())
+}
+
+/// A `Retry-After` header in seconds; one second when it's missing or
+/// unreadable, as Spotify always sends it and a bad one shouldn't mean a
+/// tight loop
+pub fn parse_retry_after(header: Option<&str>) -> Duration {
+    let secs = header.and_then(|h| h.trim().parse::<u64>().ok()).unwrap_or(1);
+    Duration::from_secs(secs.max(1))
+}
+
+/// The pause before network retry number `retry` (from 0): doubling from
+/// `BACKOFF_BASE` up to `BACKOFF_CAP`, then scaled down by up to half by
+/// `jitter` (0 to 1) so requests that failed together don't retry together
+pub fn backoff(retry: u32, jitter: f64) -> Duration {
+    let full = BACKOFF_BASE.saturating_mul(1 << retry.min(16)).min(BACKOFF_CAP);
+    full.mul_f64(1.0 - jitter.clamp(0.0, 1.0) / 2.0)
+}
+
+/// How long a 429 asks to wait; `None` for every other error
+fn retry_after(error: &ClientError) -> Option<Duration> {
+    let ClientError::Http(http) = error else { return None };
+    let HttpError::StatusCode(ref resp) = **http else { return None };
+    (resp.status() == 429).then(|| parse_retry_after(resp.headers().get(RETRY_AFTER).and_then(|v| v.to_str().ok())))
+}
+
+/// Errors worth another go: the connection couldn't be made (so the request
+/// never got to Spotify), or Spotify said it's briefly unavailable. Timeouts
+/// aren't, as the request may have gone through and skipping a track twice
+/// is worse than an error
+fn transient(error: &ClientError) -> bool {
+    let ClientError::Http(http) = error else { return false };
+    match **http {
+        HttpError::Client(ref e) => e.is_connect(),
+        HttpError::StatusCode(ref resp) => matches!(resp.status().as_u16(), 502 | 503),
+    }
+}
+
+/// Sends a Spotify request, waiting out any rate limit first (or failing
+/// straight away when it's longer than `MAX_WAIT`). A 429 makes every request
+/// wait its `Retry-After`, then this one goes again; a network error or a
+/// 502/503 is retried after a jittered backoff. Either way it's `MAX_RETRIES`
+/// times at most. `request` gets the client locked for that one attempt, so
+/// nobody's kept waiting on the lock while this one sleeps.
+pub async fn send<T, F, Fut>(spotify: &Arc<ClientLock<AuthCodePkceSpotify>>, mut request: F) -> Result<T>
+where
+    F: FnMut(OwnedMutexGuard<AuthCodePkceSpotify>) -> Fut,
+    Fut: Future<Output = ClientResult<T>>,
+{
+    let mut retries = 0;
+    loop {
+        if let Some(wait) = rate_limited_for() {
+            if wait > MAX_WAIT {
+                bail!("Rate limited by Spotify, try again in {}s", wait.as_secs());
+            }
+            tokio::time::sleep(wait).await;
+        }
+        let error = match request(spotify.clone().lock_owned().await).await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        if let Some(wait) = retry_after(&error) {
+            if let Ok(mut limit) = limit().lock() {
+                limit.hit(wait, Instant::now());
+            }
+            if retries == MAX_RETRIES || wait > MAX_WAIT {
+                return Err(anyhow::Error::new(error).context(format!("Rate limited by Spotify, try again in {}s", wait.as_secs())));
+            }
+            warn!("Rate limited by Spotify, retrying in {}s ({}/{MAX_RETRIES})", wait.as_secs(), retries + 1);
+        } else if transient(&error) && retries < MAX_RETRIES {
+            let pause = backoff(retries, rand::thread_rng().gen());
+            debug!("Spotify request failed ({error}), retrying in {}ms ({}/{MAX_RETRIES})", pause.as_millis(), retries + 1);
+            tokio::time::sleep(pause).await;
+        } else {
+            return Err(error.into());
+        }
+        retries += 1;
+    }
+}
+
+/// Every item of a paged list, `page_size` at a time, each page `send` on its
+/// own so a failure part way retries that page rather than the whole list.
+/// `request` gets the client, the page size and the offset to read from.
+pub async fn send_pages<T, F, Fut>(spotify: &Arc<ClientLock<AuthCodePkceSpotify>>, page_size: u32, mut request: F) -> Result<Vec<T>>
+where
+    F: FnMut(OwnedMutexGuard<AuthCodePkceSpotify>, u32, u32) -> Fut,
+    Fut: Future<Output = ClientResult<Page<T>>>,
+{
+    let mut items = Vec::new();
+    loop {
+        let offset = items.len() as u32;
+        let page = send(spotify, |sp| request(sp, page_size, offset)).await?;
+        let last = page.next.is_none() || page.items.is_empty();
+        items.extend(page.items);
+        if last {
+            return Ok(items);
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use anyhow::Result;
 use rspotify::{
-    model::{FullTrack, SearchResult, SearchType},
+    model::{FullTrack, SearchResult, SearchType, SimplifiedAlbum, SimplifiedPlaylist},
     prelude::*,
     AuthCodePkceSpotify,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use super::retry::send;
+
 pub struct Search {
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
 }
@@ -20,17 +22,10 @@ impl Search {
         if query.trim().is_empty() {
             return Ok(vec![]);
         }
-        let sp = self.spotify.lock().await;
-        let result = sp
-            .search(
-                query,
-                SearchType::Track,
-                None,
-                None,
-                Some(limit),
-                None,
-            )
-            .await?;
+        let result = send(&self.spotify, |sp| async move {
+            sp.search(query, SearchType::Track, None, None, Some(limit), None).await
+        })
+        .await?;
 
         let tracks = match result {
             SearchResult::Tracks(page) => page.items,
@@ -38,4 +33,36 @@ impl Search {
         };
         Ok(tracks)
     }
+
+    pub async fn search_playlists(&self, query: &str, limit: u32) -> Result<Vec<SimplifiedPlaylist>> {
+        if query.trim().is_empty() {
+            return Ok(vec![]);
+        }
+        let result = send(&self.spotify, |sp| async move {
+            sp.search(query, SearchType::Playlist, None, None, Some(limit), None).await
+        })
+        .await?;
+
+        let playlists = match result {
+            SearchResult::Playlists(page) => page.items,
+            _ => vec![],
+        };
+        Ok(playlists)
+    }
+
+    pub async fn search_albums(&self, query: &str, limit: u32) -> Result<Vec<SimplifiedAlbum>> {
+        if query.trim().is_empty() {
+            return Ok(vec![]);
+        }
+        let result = send(&self.spotify, |sp| async move {
+            sp.search(query, SearchType::Album, None, None, Some(limit), None).await
+        })
+        .await?;
+
+        let albums = match result {
+            SearchResult::Albums(page) => page.items,
+            _ => vec![],
+        };
+        Ok(albums)
+    }
 }
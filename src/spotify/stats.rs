@@ -0,0 +1,53 @@
+use anyhow::Result;
+use rspotify::{
+    model::{FullArtist, FullTrack, PlayHistory, TimeRange},
+    prelude::*,
+    AuthCodePkceSpotify,
+};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::app::state::StatsRange;
+use super::retry::send;
+
+/// Personalization endpoints (needs the `user-top-read` and
+/// `user-read-recently-played` scopes)
+pub struct Stats {
+    spotify: Arc<Mutex<AuthCodePkceSpotify>>,
+}
+
+impl Stats {
+    pub fn new(spotify: Arc<Mutex<AuthCodePkceSpotify>>) -> Self {
+        Stats { spotify }
+    }
+
+    pub async fn top_tracks(&self, range: StatsRange) -> Result<Vec<FullTrack>> {
+        let page = send(&self.spotify, |sp| async move {
+            sp.current_user_top_tracks_manual(Some(time_range(range)), Some(50), None).await
+        })
+        .await?;
+        Ok(page.items)
+    }
+
+    pub async fn top_artists(&self, range: StatsRange) -> Result<Vec<FullArtist>> {
+        let page = send(&self.spotify, |sp| async move {
+            sp.current_user_top_artists_manual(Some(time_range(range)), Some(50), None).await
+        })
+        .await?;
+        Ok(page.items)
+    }
+
+    /// The last 50 tracks played on the account, from any client, newest first
+    pub async fn recently_played(&self) -> Result<Vec<PlayHistory>> {
+        let page = send(&self.spotify, |sp| async move { sp.current_user_recently_played(Some(50), None).await }).await?;
+        Ok(page.items)
+    }
+}
+
+fn time_range(range: StatsRange) -> TimeRange {
+    match range {
+        StatsRange::Short => TimeRange::ShortTerm,
+        StatsRange::Medium => TimeRange::MediumTerm,
+        StatsRange::Long => TimeRange::LongTerm,
+    }
+}
@@ -1,13 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use rspotify::{
-    model::{Market, SearchType, SearchResult, FullTrack},
+    model::{Market, SearchType, SearchResult, FullTrack, TrackId},
     prelude::*,
     AuthCodePkceSpotify,
 };
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::app::state::VibesMood;
+use crate::config::VibesMood;
+use super::retry::send;
 
 pub struct Vibes {
     spotify: Arc<Mutex<AuthCodePkceSpotify>>,
@@ -21,27 +22,12 @@ impl Vibes {
     /// Since Spotify deprecated the Recommendations API (Nov 2024),
     /// we use search with mood-appropriate keywords + genres instead.
     pub async fn get_recommendations(&self, mood: &VibesMood) -> Result<Vec<FullTrack>> {
-        let sp = self.spotify.lock().await;
-
-        // Build a mood-based search query
-        let query = match mood {
-            VibesMood::Chill => "genre:chill lo-fi relaxing",
-            VibesMood::Hype  => "genre:edm hype energy bass",
-            VibesMood::Focus => "genre:classical focus study ambient",
-            VibesMood::Happy => "genre:pop happy upbeat feel good",
-            VibesMood::Dark  => "genre:metal dark heavy intense",
-        };
-
-        let result = sp
-            .search(
-                query,
-                SearchType::Track,
-                Some(Market::FromToken),
-                None,  // include_external
-                Some(30),
-                Some(0),
-            )
-            .await?;
+        let query = &mood.search_query();
+
+        let result = send(&self.spotify, |sp| async move {
+            sp.search(query, SearchType::Track, Some(Market::FromToken), None, Some(30), Some(0)).await
+        })
+        .await?;
 
         if let SearchResult::Tracks(page) = result {
             Ok(page.items)
@@ -49,4 +35,80 @@ impl Vibes {
             Ok(vec![])
         }
     }
+
+    /// A song-radio style set seeded by one track: the seed, then the artist's
+    /// top tracks interleaved with searches on the artist's genres, so the
+    /// artist doesn't take over. Spotify's radio is built on the retired
+    /// recommendations API, so this is an approximation.
+    pub async fn track_radio(&self, seed_uri: &str) -> Result<Vec<String>> {
+        let seed_id = &TrackId::from_uri(seed_uri)?;
+        let seed = send(&self.spotify, |sp| async move { sp.track(seed_id.clone(), Some(Market::FromToken)).await }).await?;
+        let artist = seed.artists.first().context("Track has no artist")?;
+        let artist_id = &artist.id.clone().context("Artist has no Spotify id")?;
+        let genres = send(&self.spotify, |sp| async move { sp.artist(artist_id.clone()).await }).await?.genres;
+
+        let mut pools = vec![send(&self.spotify, |sp| async move {
+            sp.artist_top_tracks(artist_id.clone(), Some(Market::FromToken)).await
+        })
+        .await?];
+        // Without genres, tracks the artist features on are the next best thing
+        let mut queries: Vec<String> = genres.iter().take(RADIO_GENRES).map(|g| format!("genre:\"{g}\"")).collect();
+        if queries.is_empty() {
+            queries.push(format!("artist:\"{}\"", artist.name));
+        }
+        for query in &queries {
+            let result = send(&self.spotify, |sp| async move {
+                sp.search(query, SearchType::Track, Some(Market::FromToken), None, Some(30), Some(0)).await
+            })
+            .await?;
+            if let SearchResult::Tracks(page) = result {
+                pools.push(page.items);
+            }
+        }
+
+        let pools: Vec<Vec<(String, String)>> = pools
+            .into_iter()
+            .map(|pool| {
+                pool.into_iter()
+                    .filter_map(|t| Some((t.id?.uri(), t.artists.first()?.name.clone())))
+                    .collect()
+            })
+            .collect();
+        Ok(radio_order(seed_uri, &pools, RADIO_LENGTH))
+    }
+}
+
+/// Tracks in a radio set, seed included
+pub const RADIO_LENGTH: usize = 50;
+/// Genre searches mixed into a radio set
+const RADIO_GENRES: usize = 2;
+/// Most tracks one artist gets in a radio set
+pub const RADIO_PER_ARTIST: usize = 4;
+
+/// `seed`, then up to `limit - 1` tracks taken round-robin from `pools` of
+/// `(uri, artist)`, skipping repeats and artists already at `RADIO_PER_ARTIST`
+pub fn radio_order(seed: &str, pools: &[Vec<(String, String)>], limit: usize) -> Vec<String> {
+    let mut order = vec![seed.to_string()];
+    let mut per_artist: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    let mut iters: Vec<_> = pools.iter().map(|p| p.iter()).collect();
+    while order.len() < limit {
+        let mut progressed = false;
+        for it in iters.iter_mut() {
+            let Some((uri, artist)) = it.find(|(uri, artist)| {
+                !order.contains(uri) && per_artist.get(artist.as_str()).copied().unwrap_or(0) < RADIO_PER_ARTIST
+            }) else {
+                continue;
+            };
+            progressed = true;
+            *per_artist.entry(artist).or_default() += 1;
+            order.push(uri.clone());
+            if order.len() == limit {
+                break;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+    order
 }
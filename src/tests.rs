@@ -1,8 +1,51 @@
 #[cfg(test)]
 mod tests {
     use crate::app::state::{
-        ActiveScreen, AppState, CurrentTrack, Notification,
+        capped_bpm, counts_as_scrobble, parse_decade, sort_rows, step_filtered, AlbumRow, AlbumSort, SearchState, SortColumn, SortOrder, queue_groups, queue_rows, PrefetchedPage, Prefetched, QueueRow, QueueSource, QueueState, FeatureProfile, HistoryEntry, RadarTrack, RadarView, replay_gain_delta, restore_removed, screen_order, start_offsets, take_matching, ActiveScreen, AppState, Connection, CurrentTrack, LikedTracks, PendingWrite, StatsRange, VisualSelect, Notification, Severity, Prompt, PromptKind,
+        DevicesView, LoadStatus, LogView, Startup, StartupLoad, DEVICE_LOST_MISSES, HISTORY_LIMIT, OFFLINE_AFTER_FAILURES, ROW_JUMP_TIMEOUT_MS, STARTUP_LINGER_MS,
+        REPLAY_GAIN_MAX_DELTA, REPLAY_GAIN_TARGET_DB,
     };
+    use crate::artwork::dominant_color;
+    use crate::cache::{library::Cached, snapshots::{PlaylistDiff, PlaylistSnapshot}, Cache};
+    use crate::collate::{fold, fuzzy_match, Collator};
+    use crate::clipboard::{format_track, web_link, TrackInfo, DEFAULT_TEMPLATE};
+    use crate::config::{profile_env_suffix, take_profile_flag, CacheTtl, Config, NotificationSettings, SidebarPanel, VibesMood, Visualizer, DEFAULT_POLL_MS};
+    use crate::logs::{parse_level, LogBuffer, LogLine, KEPT_LINES};
+    use crate::lyrics::{current_line, parse_lrc};
+    use crate::mpd;
+    use crate::quotes::{self, QuoteCache};
+    use crate::remote::{handle, parse_request, FailedAttempts, RemoteStatus, MAX_FAILED_ATTEMPTS};
+    use crate::jobs::{JobHandle, JobKind, Jobs};
+    use crate::ipc::{self, Command};
+    use crate::hooks::{self, HookEvent, HookTrack, Hooks};
+    use crate::daemon::{self, AttachBackend, Screen};
+    use crate::index::{features::{FeatureIndex, IndexEntry, TrackFeatures}, filter::MoodFilter, IndexedAlbum, LibraryIndex};
+    use crate::connect::DeviceSettings;
+    use crate::features::{apply_env_list, Feature, Features};
+    use crate::events::{map_key_to_action, parse_chord, parse_key, Keymap, SheetFormat, UserAction};
+    use crate::secrets;
+    use crate::session::archive::{self, PlaylistArchive};
+    use crate::session::inbox;
+    use crate::session::searches;
+    use crate::session::trace::{TraceEvent, Tracer};
+    use crate::session::plays::{self, ExportFormat, Play};
+    use crate::session::{write_atomic, NowPlaying, QueueEntry, SessionSummary};
+    use crate::spotify::auth::{parse_port_range, parse_redirect, PkceChallenge, RedirectUri};
+    use crate::spotify::capabilities::Capability;
+    use crate::spotify::queue::restart_order;
+    use crate::spotify::vibes::{radio_order, RADIO_PER_ARTIST};
+    use crate::ui::anim::{fade, slide, Animations};
+    use crate::ui::components::radar::radar_vertices;
+    use crate::ui::page_size;
+    use crate::ui::theme::{reduce, ColorDepth, Theme, PRESETS};
+    use crate::ui::text::{marquee, row_number, truncate, viewport_offset};
+    use crate::widget::{self, Click, WidgetFormat};
+    use proptest::prelude::*;
+    use ratatui::{backend::TestBackend, layout::Rect, style::Color, Terminal};
+    use unicode_width::UnicodeWidthStr;
+    use std::collections::BTreeMap;
+    use tracing::Level;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
     // ── CurrentTrack ─────────────────────────────────────────────────────────
 
@@ -58,6 +101,45 @@ mod tests {
         assert_eq!(state.previous_screen, Some(ActiveScreen::Search));
     }
 
+    #[test]
+    fn test_stats_range_cycles_both_ways() {
+        let start = StatsRange::default();
+        assert_eq!(start, StatsRange::Medium);
+        assert_eq!(start.next().next().next(), start);
+        assert_eq!(start.next().prev(), start);
+        assert_eq!(StatsRange::Short.prev(), StatsRange::Long);
+    }
+
+    #[test]
+    fn test_jump_to_playing_leaves_a_stale_playlist_closed() {
+        use crate::app::state::SearchTab;
+        let item: rspotify::model::PlaylistItem = serde_json::from_value(serde_json::json!({
+            "added_at": null, "added_by": null, "is_local": false,
+            "track": {
+                "album": { "artists": [], "available_markets": [], "external_urls": {}, "images": [], "name": "Discovery" },
+                "artists": [], "available_markets": [], "disc_number": 1, "duration_ms": 320_000, "explicit": false,
+                "external_ids": {}, "external_urls": {}, "href": null, "id": "0DiWol3AO6WpXZgp0goxAV", "is_local": false,
+                "name": "One More Time", "popularity": 0, "preview_url": null, "track_number": 1,
+            },
+        }))
+        .unwrap();
+        let mut state = AppState::default();
+        state.current_track.id = Some("spotify:track:0DiWol3AO6WpXZgp0goxAV".to_string());
+        state.navigate_to(ActiveScreen::Playlists);
+        state.search.tab = SearchTab::Albums;
+        // Left over from the last playlist opened, with the list showing again
+        state.playlists.playlist_tracks = vec![item.clone(), item];
+        state.playlists.selected_track = 1;
+        assert!(!state.select_playing());
+        assert!(!state.playlists.viewing_tracks, "not forced open");
+        assert_eq!(state.playlists.selected_track, 1);
+
+        state.playlists.viewing_tracks = true;
+        assert!(state.select_playing());
+        assert_eq!(state.playlists.selected_track, 0);
+        assert_eq!(state.search.tab, SearchTab::Albums, "another screen's tab is left alone");
+    }
+
     #[test]
     fn test_navigate_to_same_screen_noop() {
         let mut state = AppState::default();
@@ -71,19 +153,55 @@ mod tests {
     fn test_notification_tick_decrements() {
         let mut state = AppState::default();
         state.set_notification(Notification::info("hello"));
-        assert!(state.notification.is_some());
+        assert!(!state.notifications.is_empty());
         // remaining_ticks=30: takes 30 ticks to reach 0, then 1 more tick to clear
         for _ in 0..31 {
             state.tick_notification();
         }
-        assert!(state.notification.is_none());
+        assert!(state.notifications.is_empty());
+    }
+
+    #[test]
+    fn test_notifications_stack_up_to_the_limit() {
+        let mut state = AppState {
+            toast_settings: NotificationSettings { max_stacked: 2, ..Default::default() },
+            toast_ticks: NotificationSettings { duration_ms: Some(1000), ..Default::default() }.ticks(100),
+            ..Default::default()
+        };
+        state.set_notification(Notification::info("one"));
+        state.set_notification(Notification::info("two"));
+        state.set_notification(Notification::failure("three", &anyhow::anyhow!("boom")));
+        let shown: Vec<_> = state.notifications.iter().map(|n| n.message.as_str()).collect();
+        assert_eq!(shown, ["two", "three"]);
+        assert_eq!(state.notifications[0].remaining_ticks, 10);
+        assert_eq!(state.notifications[1].remaining_ticks, 25, "errors stay up longer");
+        assert_eq!(state.notification_history.len(), 3);
+        assert_eq!(state.notification_history[0].1.message, "three");
+        assert_eq!(state.take_error_notification().unwrap().message, "three");
+    }
+
+    #[test]
+    fn test_toasts_expire_per_severity() {
+        let settings = NotificationSettings { duration_ms: Some(1000), error_ms: Some(6000), ..Default::default() };
+        let mut state = AppState { toast_ticks: settings.ticks(100), ..Default::default() };
+        state.set_notification(Notification::info("saved"));
+        state.set_notification(Notification::warning("not available"));
+        state.set_notification(Notification::failure("failed", &anyhow::anyhow!("boom")));
+        let ticks: Vec<u16> = state.notifications.iter().map(|n| n.remaining_ticks).collect();
+        assert_eq!(ticks, [10, 15, 60]);
+        for _ in 0..16 {
+            state.tick_notification();
+        }
+        let left: Vec<Severity> = state.notifications.iter().map(|n| n.severity).collect();
+        assert_eq!(left, [Severity::Error]);
     }
 
     #[test]
     fn test_notification_error_flag() {
-        let n = Notification::error("oops");
-        assert!(n.is_error);
+        let n = Notification::failure("oops", &anyhow::anyhow!("boom"));
+        assert!(n.is_error());
         assert_eq!(n.message, "oops");
+        assert!(!Notification::warning("careful").is_error());
     }
 
     // ── EQ bars ───────────────────────────────────────────────────────────────
@@ -114,6 +232,126 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_eq_bars_kick_with_the_energy() {
+        let mut state = AppState { tempo_bpm: Some(120.0), beat: true, ..Default::default() };
+        state.current_track.is_playing = true;
+        assert_eq!(state.kick_height(), 12, "energy unknown");
+        state.energy = Some(0.1);
+        for _ in 0..20 {
+            state.update_eq_bars();
+            assert!(state.eq_bars.iter().all(|&b| (2..=7).contains(&b)), "{:?}", state.eq_bars);
+        }
+        state.energy = Some(0.95);
+        for _ in 0..20 {
+            state.update_eq_bars();
+            assert!(state.eq_bars.iter().all(|&b| b >= 7), "{:?}", state.eq_bars);
+        }
+        state.beat = false;
+        state.update_eq_bars();
+        assert!(state.eq_bars.iter().all(|&b| b >= 5), "falls off gently between beats");
+    }
+
+    #[test]
+    fn test_eq_bars_follow_captured_audio() {
+        use crate::capture::{bar_levels, BARS};
+        // 2048-point FFT at 48 kHz: 1025 bins, 23.4 Hz apart
+        let mut spectrum = vec![0.0; 1025];
+        assert_eq!(bar_levels(&spectrum, 48_000), [1; BARS], "silence");
+        spectrum[43] = 0.5; // ~1 kHz, -6 dB
+        let levels = bar_levels(&spectrum, 48_000);
+        assert_eq!(levels.iter().filter(|&&l| l == 12).count(), 1);
+        assert_eq!((levels[0], levels[BARS - 1]), (1, 1));
+        spectrum[43] = 0.01; // -40 dB
+        let quiet = bar_levels(&spectrum, 48_000);
+        assert!(quiet.iter().any(|&l| l > 1 && l < 12));
+
+        let mut state = AppState { eq_bars: [6; 24], eq_capture: Some(levels), ..Default::default() };
+        state.update_eq_bars();
+        assert_eq!(state.eq_bars.iter().max(), Some(&12), "up to what's heard at once");
+        assert_eq!(state.eq_bars[0], 5, "down a step a tick");
+    }
+
+    // ── Now Playing ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_focus_mode_shows_the_playing_track() {
+        let mut state = AppState { is_authenticated: true, focus: true, volume: 40, ..Default::default() };
+        state.current_track = CurrentTrack {
+            name: "Roygbiv".into(),
+            artists: vec!["Boards of Canada".into()],
+            album: "Music Has the Right to Children".into(),
+            duration_ms: 151_000,
+            progress_ms: 20_000,
+            is_playing: true,
+            device_name: Some("Kitchen".into()),
+            shuffle: true,
+            repeat: Some(rspotify::model::RepeatState::Track),
+            ..Default::default()
+        };
+        state.lyrics = Some(vec![(0, "first line".into()), (15_000, "sung now".into()), (30_000, "later".into())]);
+        let screen = |state: &AppState, w, h| {
+            let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+            terminal.draw(|f| crate::ui::render(f, state)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+        let big = screen(&state, 100, 40);
+        for expected in ["R O Y G B I V", "Boards of Canada", "Kitchen · 40%", "repeat one", "sung now", "0:20 / 2:31"] {
+            assert!(big.contains(expected), "{expected} missing");
+        }
+        assert!(!big.contains("Library"), "no sidebar");
+        assert!(big.contains("█▀▄ ▄▀▄ █ █"), "no art yet: the title in large type");
+        screen(&state, 12, 4);
+
+        state.cover = Some(crate::artwork::Cover(vec![(200, 30, 30); 24 * 24]));
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let art = terminal.backend().buffer().content.iter()
+            .filter(|c| c.symbol() == "▀" && c.fg == Color::Rgb(200, 30, 30) && c.bg == Color::Rgb(200, 30, 30))
+            .count();
+        assert_eq!(art, 24 * 12, "the cover, two pixels a cell");
+
+        state.navigate_to(ActiveScreen::Queue);
+        assert!(!state.focus, "switching screens leaves focus mode");
+    }
+
+    #[test]
+    fn test_mini_mode_is_a_list_and_the_player_bar() {
+        let mut state = AppState { is_authenticated: true, mini: true, eq_expanded: true, ..Default::default() };
+        state.navigate_to(ActiveScreen::Queue);
+        state.current_track = CurrentTrack { name: "Roygbiv".into(), artists: vec!["Boards of Canada".into()], duration_ms: 151_000, ..Default::default() };
+        let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(!screen.contains("your terminal"), "no sidebar");
+        assert!(screen.contains("Roygbiv"), "player bar");
+        // 5 rows of player bar whatever the EQ, the rest for the list
+        assert_eq!(page_size(Rect::new(0, 0, 60, 12), &state), 4);
+    }
+
+    // ── Animations ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_transition_eases_to_done_and_respects_reduce_motion() {
+        let mut anims = Animations::default();
+        assert_eq!(anims.track_change.progress(), 1.0, "idle transitions draw their end state");
+        anims.track_changed();
+        let mut last = anims.track_change.progress();
+        assert_eq!(last, 0.0);
+        while anims.track_change.is_running() {
+            anims.tick();
+            assert!(anims.track_change.progress() >= last);
+            last = anims.track_change.progress();
+        }
+        assert_eq!(last, 1.0);
+        assert_eq!(fade(Color::Rgb(0, 0, 0), Color::Rgb(200, 100, 0), 0.5), Color::Rgb(100, 50, 0));
+        assert_eq!(slide(4, 0.0), 4);
+
+        let mut still = Animations { reduce_motion: true, ..Default::default() };
+        still.screen_switched();
+        assert!(!still.screen_switch.is_running());
+    }
+
     // ── Ticker ────────────────────────────────────────────────────────────────
 
     #[test]
@@ -131,4 +369,1976 @@ mod tests {
         let title = state.get_display_title(20);
         assert_eq!(title.len(), 20);
     }
+
+    // ── Volume leveling ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_replay_gain_delta_direction_and_cap() {
+        assert_eq!(replay_gain_delta(REPLAY_GAIN_TARGET_DB), 0);
+        assert!(replay_gain_delta(-4.0) < 0, "loud tracks are turned down");
+        assert!(replay_gain_delta(-14.0) > 0, "quiet tracks are turned up");
+        assert_eq!(replay_gain_delta(-60.0), REPLAY_GAIN_MAX_DELTA as i8);
+    }
+
+    #[test]
+    fn test_apply_replay_gain_does_not_drift() {
+        let mut state = AppState { volume: 50, ..Default::default() };
+        assert_eq!(state.apply_replay_gain(5), 55);
+        assert_eq!(state.apply_replay_gain(-3), 47);
+        assert_eq!(state.apply_replay_gain(0), 50);
+        assert_eq!(state.replay_gain_offset, 0);
+    }
+
+    #[test]
+    fn test_apply_replay_gain_clamps_at_max_volume() {
+        let mut state = AppState { volume: 98, ..Default::default() };
+        assert_eq!(state.apply_replay_gain(6), 100);
+        assert_eq!(state.replay_gain_offset, 2);
+        assert_eq!(state.apply_replay_gain(0), 98);
+    }
+
+    #[test]
+    fn test_mute_restores_previous_volume() {
+        let mut state = AppState { volume: 65, ..Default::default() };
+        assert_eq!(state.toggle_mute(), 0);
+        assert_eq!(state.muted_volume, Some(65));
+        state.sync_device_volume(0); // the poll still sees the muted device
+        assert_eq!(state.toggle_mute(), 65);
+        assert_eq!(state.muted_volume, None);
+
+        state.toggle_mute();
+        state.sync_device_volume(30); // turned up from another device
+        assert_eq!(state.muted_volume, None);
+        assert_eq!(state.volume, 30);
+    }
+
+    // ── Audio-features radar ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_radar_vertices_start_up_and_go_clockwise() {
+        let points = radar_vertices(&[1.0, 0.5, 0.0, 0.0, 0.0]);
+        assert!(points[0].0.abs() < 1e-9 && (points[0].1 - 1.0).abs() < 1e-9);
+        assert!(points[1].0 > 0.0, "second axis is to the right");
+        assert!(((points[1].0.powi(2) + points[1].1.powi(2)).sqrt() - 0.5).abs() < 1e-9);
+        assert_eq!(points[2], (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_radar_popup_fills_in_and_renders() {
+        let track = |id: &str| RadarTrack { id: id.into(), name: id.into(), profile: None, loaded: false };
+        let mut radar = RadarView { tracks: vec![track("a"), track("b")] };
+        radar.set_profile("b", Some(FeatureProfile([0.9, 0.8, 0.3, 0.1, 0.0])));
+        radar.set_profile("a", None);
+        assert!(radar.tracks[0].loaded && radar.tracks[0].profile.is_none());
+        assert_eq!(radar.tracks[1].profile.map(|p| p.0[0]), Some(0.9));
+
+        let state = AppState { is_authenticated: true, radar: Some(radar), ..Default::default() };
+        for (w, h) in [(120, 40), (30, 8)] {
+            let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+            terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        }
+    }
+
+    // ── Config ────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_partial_config_file_keeps_defaults() {
+        let config: Config = toml::from_str("tick_ms = 50\n[keybinds]\nnext_track = \"N\"").unwrap();
+        assert_eq!(config.tick_ms, 50);
+        assert_eq!(config.poll_ms, DEFAULT_POLL_MS);
+        assert_eq!(config.redirect_uri, "http://127.0.0.1:8989/login");
+        assert_eq!(config.keybinds.get("next_track").map(String::as_str), Some("N"));
+    }
+
+    #[test]
+    fn test_log_file_setting_and_platform_default() {
+        let config: Config = toml::from_str("log_file = \"/var/tmp/vibes.log\"").unwrap();
+        assert_eq!(config.log_path(), Some(std::path::PathBuf::from("/var/tmp/vibes.log")));
+        let default = Config::default().log_path().expect("a home directory here");
+        assert_eq!(default.file_name().unwrap(), "vibes.log");
+        assert!(default.parent().unwrap().ends_with("vibes"), "in a directory of our own, not /tmp");
+    }
+
+    #[test]
+    fn test_user_moods_extend_and_replace_builtins() {
+        let raw = "[[moods]]\nname = \"Rainy day\"\nemoji = \"🌧\"\ngenres = [\"indie folk\"]\nquery = \"rain\"\n\n[[moods]]\nname = \"focus\"\nquery = \"post-rock\"";
+        let config: Config = toml::from_str(raw).unwrap();
+        let moods = VibesMood::all(&config.moods);
+        assert_eq!(moods.len(), 6);
+        assert_eq!(moods[0].search_query(), "genre:chill lo-fi relaxing");
+        assert_eq!(moods[2].search_query(), "post-rock", "replaced in place");
+        assert_eq!(moods[5].to_string(), "🌧 Rainy day");
+        assert_eq!(moods[5].search_query(), "genre:\"indie folk\" rain");
+    }
+
+    #[test]
+    fn test_config_save_never_writes_credentials() {
+        let config = Config {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+            ..Default::default()
+        };
+        let raw = toml::to_string_pretty(&config).unwrap();
+        assert!(!raw.contains("secret"));
+        assert!(!raw.contains("client_id"));
+    }
+
+    #[test]
+    fn test_saving_a_setting_leaves_the_rest_of_the_file_alone() {
+        use crate::config::with_setting;
+        let raw = "# my setup\nvisualizer = \"cat\" # the cat's the best\ntick_ms = 5\n\n[keybinds]\nnext_track = \"N\"\n";
+        let saved = with_setting(raw, "visualizer", &Visualizer::Off).unwrap();
+        assert_eq!(saved, "# my setup\nvisualizer = \"off\" # the cat's the best\ntick_ms = 5\n\n[keybinds]\nnext_track = \"N\"\n");
+        // Nothing from the environment or the clamps gets written
+        assert!(!saved.contains("redis_url") && !saved.contains("tick_ms = 10"));
+        let added = with_setting(&saved, "replay_gain", &true).unwrap();
+        let config: Config = toml::from_str(&added).unwrap();
+        assert!(config.replay_gain);
+        assert_eq!(config.keybinds.get("next_track").map(String::as_str), Some("N"));
+    }
+
+    #[test]
+    fn test_smart_resume_rewinds_only_after_long_pauses() {
+        let rules: Config = toml::from_str("[smart_resume]\nafter_secs = 60\nepisode_secs = 15").unwrap();
+        let rules = rules.smart_resume;
+        assert_eq!(rules.rewind_ms(59_000, true), 0);
+        assert_eq!(rules.rewind_ms(60_000, true), 15_000);
+        assert_eq!(rules.rewind_ms(600_000, false), 0, "music rewind defaults to off");
+    }
+
+    #[test]
+    fn test_step_sizes_and_shift_keys() {
+        let config: Config = toml::from_str("[steps]\nvolume = 2").unwrap();
+        assert_eq!(config.steps.volume, 2);
+        assert_eq!(config.steps.seek_large_secs, 60);
+
+        let mut keymap = Keymap::default();
+        let mut press = |code: KeyCode, modifiers: KeyModifiers| map_key_to_action(KeyEvent::new(code, modifiers), false, &mut keymap);
+        assert_eq!(press(KeyCode::Char('='), KeyModifiers::NONE), Some(UserAction::VolumeUp));
+        assert_eq!(press(KeyCode::Char('+'), KeyModifiers::SHIFT), Some(UserAction::VolumeUpLarge));
+        assert_eq!(press(KeyCode::Char('_'), KeyModifiers::SHIFT), Some(UserAction::VolumeDownLarge));
+        assert_eq!(press(KeyCode::Right, KeyModifiers::SHIFT), Some(UserAction::SeekForwardLarge));
+        assert_eq!(press(KeyCode::Left, KeyModifiers::NONE), Some(UserAction::NavigateLeft));
+    }
+
+    #[test]
+    fn test_capability_from_status() {
+        assert_eq!(Capability::from_status(200), Capability::Available);
+        assert_eq!(Capability::from_status(403), Capability::Unavailable);
+        assert_eq!(Capability::from_status(429), Capability::Unknown);
+        assert_eq!(Capability::from_queue_status(404), Capability::Unknown, "no active device");
+        assert_eq!(Capability::from_queue_status(501), Capability::Unavailable);
+        assert!(Capability::Unknown.usable() && !Capability::Unavailable.usable());
+    }
+
+    #[test]
+    fn test_copy_template_fills_fields_and_tidies_gaps() {
+        let info = TrackInfo {
+            artist: "Daft Punk".into(),
+            title: "One More Time".into(),
+            album: "Discovery".into(),
+            year: "2001".into(),
+            link: web_link("spotify:track:0DiWol3AO6WpXZgp0goxAV"),
+        };
+        assert_eq!(
+            format_track(DEFAULT_TEMPLATE, &info),
+            "Daft Punk – One More Time (Discovery, 2001) https://open.spotify.com/track/0DiWol3AO6WpXZgp0goxAV"
+        );
+        let no_year = TrackInfo { year: String::new(), link: String::new(), ..info };
+        assert_eq!(format_track(DEFAULT_TEMPLATE, &no_year), "Daft Punk – One More Time (Discovery)");
+        assert_eq!(format_track("{title} by {artist}", &no_year), "One More Time by Daft Punk");
+    }
+
+    #[test]
+    fn test_keybind_names() {
+        assert_eq!(parse_key("N"), Some(KeyCode::Char('N')));
+        assert_eq!(parse_key("space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("nope"), None);
+        assert_eq!(UserAction::from_name("next_track"), Some(UserAction::NextTrack));
+        assert_eq!(UserAction::from_name("explode"), None);
+    }
+
+    #[test]
+    fn test_cheat_sheet_follows_the_keymap() {
+        let binds = BTreeMap::from([("next_track".to_string(), "N".to_string())]);
+        let chords = BTreeMap::from([("go_queue".to_string(), "gq".to_string())]);
+        let keymap = Keymap::from_config(&binds, &chords);
+        let rows = keymap.bindings();
+        let keys_for = |what: &str| rows.iter().find(|(w, _)| *w == what).map(|(_, keys)| keys.clone());
+        // The default key keeps working next to the override
+        assert_eq!(keys_for("Next track"), Some(vec!["N".to_string(), "n".to_string()]));
+        assert_eq!(keys_for("Move down"), Some(vec!["j".to_string(), "↓".to_string()]));
+        assert_eq!(keys_for("Go to Queue"), Some(vec!["gq".to_string()]));
+        assert_eq!(keys_for("Jump to the top"), Some(vec!["Home".to_string(), "gg".to_string()]));
+        assert_eq!(keys_for("Go to Stats"), None); // not bound by default
+
+        let md = keymap.cheat_sheet(SheetFormat::for_path(std::path::Path::new("keys.md")));
+        assert!(md.contains("| `N` `n` | Next track |"), "{md}");
+        let text = keymap.cheat_sheet(SheetFormat::for_path(std::path::Path::new("keys.txt")));
+        assert!(text.lines().any(|l| l.starts_with("N  n ") && l.ends_with("Next track")), "{text}");
+    }
+
+    #[test]
+    fn test_feature_flags_from_config_and_env() {
+        let mut table = BTreeMap::new();
+        table.insert("lyrics".to_string(), false);
+        table.insert("mystery".to_string(), false); // ignored with a warning
+        apply_env_list(&mut table, "lyrics, -session_file");
+        let features = Features::from_config(&table);
+        assert!(features.enabled(Feature::Lyrics)); // env wins over the file
+        assert!(!features.enabled(Feature::SessionFile));
+        assert!(features.enabled(Feature::AudioFeatures));
+        assert_eq!(features.enabled(Feature::AlbumArt), cfg!(feature = "album-art"));
+        assert_eq!(features.enabled(Feature::Keyring), cfg!(feature = "keyring"));
+        apply_env_list(&mut table, "-keyring");
+        assert!(!Features::from_config(&table).enabled(Feature::Keyring), "secrets stay in .env and Redis");
+    }
+
+    // ── Dynamic accents ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_dominant_color_ignores_greys() {
+        let grey = std::iter::repeat_n([128, 128, 128], 100);
+        let red = std::iter::repeat_n([200, 30, 30], 10);
+        let blue = std::iter::repeat_n([20, 40, 220], 5);
+        assert_eq!(dominant_color(grey.chain(red).chain(blue)), Some((200, 30, 30)));
+        assert_eq!(dominant_color(std::iter::repeat_n([0, 0, 0], 10)), None);
+    }
+
+    #[test]
+    fn test_tick_accent_converges_and_falls_back() {
+        let mut state = AppState { accent_target: Some((10, 200, 10)), ..Default::default() };
+        for _ in 0..100 {
+            state.tick_accent();
+        }
+        assert_eq!(state.accent_tint, Some((10, 200, 10)));
+
+        state.accent_target = None;
+        for _ in 0..100 {
+            state.tick_accent();
+        }
+        assert_eq!(state.accent_tint, None);
+    }
+
+    // ── Queue ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_queue_groups_by_source_and_folds() {
+        let mut queue = QueueState::default();
+        queue.manual.insert("t:m".into());
+        queue.set_origin("playlist Road trip", ["t:1".to_string(), "t:2".to_string(), "t:m".to_string()]);
+        assert_eq!(queue.source_of("t:m"), QueueSource::Manual, "manual wins over the context");
+        assert_eq!(queue.source_of("t:2"), QueueSource::From("playlist Road trip".into()));
+        assert_eq!(queue.source_of("t:x"), QueueSource::Autoplay);
+
+        let sources: Vec<QueueSource> = ["t:m", "t:1", "t:2", "t:x"].iter().map(|u| queue.source_of(u)).collect();
+        let groups = queue_groups(&sources);
+        assert_eq!(groups.iter().map(|(_, r)| r.clone()).collect::<Vec<_>>(), vec![0..1, 1..3, 3..4]);
+        assert_eq!(groups[1].0.label(), "From playlist Road trip");
+
+        let mut collapsed = std::collections::HashSet::new();
+        collapsed.insert(QueueSource::From("playlist Road trip".into()));
+        assert_eq!(
+            queue_rows(&groups, &collapsed),
+            vec![QueueRow::Group(0), QueueRow::Track(0), QueueRow::Group(1), QueueRow::Group(2), QueueRow::Track(3)]
+        );
+    }
+
+    #[test]
+    fn test_queue_start_offsets_accumulate_from_current_progress() {
+        assert_eq!(start_offsets(30_000, [180_000, 200_000, 90_000]), vec![30_000, 210_000, 410_000]);
+        assert!(start_offsets(0, []).is_empty());
+    }
+
+    #[test]
+    fn test_clearing_restarts_without_dropped_or_hand_queued_tracks() {
+        let rest: Vec<String> = ["u:1", "u:mine", "u:2", "e:ep", "u:1"].map(String::from).to_vec();
+        let drop = std::collections::HashSet::from(["u:1".to_string()]);
+        let manual = std::collections::HashSet::from(["u:mine".to_string()]);
+        assert_eq!(restart_order("u:cur", &rest, &drop, &manual), vec!["u:cur", "u:2", "e:ep"]);
+        assert_eq!(restart_order("u:cur", &[], &drop, &manual), vec!["u:cur"]);
+    }
+
+    #[test]
+    fn test_radio_interleaves_and_caps_artists() {
+        let pool = |artist: &str, uris: &[&str]| -> Vec<(String, String)> {
+            uris.iter().map(|u| (u.to_string(), artist.to_string())).collect()
+        };
+        let top = pool("Seed", &["t:seed", "t:1", "t:2", "t:3", "t:4", "t:5"]);
+        let genre = vec![("g:1".to_string(), "Other".to_string()), ("t:2".to_string(), "Seed".to_string()), ("g:2".to_string(), "Third".to_string())];
+        let order = radio_order("t:seed", &[top, genre], 50);
+        assert_eq!(order[..4], ["t:seed", "t:1", "g:1", "t:2"]);
+        assert_eq!(order.iter().filter(|u| u.starts_with("t:") && *u != "t:seed").count(), RADIO_PER_ARTIST);
+        assert!(order.contains(&"g:2".to_string()));
+        assert_eq!(radio_order("t:seed", &[pool("A", &["a:1", "a:2"])], 2), vec!["t:seed", "a:1"]);
+    }
+
+    // ── Session summary ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_history_is_most_recent_first_and_capped() {
+        let mut state = AppState::default();
+        state.push_history(&CurrentTrack::default()); // nothing playing yet
+        for i in 0..HISTORY_LIMIT + 5 {
+            let track = CurrentTrack { id: Some(format!("t{i}")), ..Default::default() };
+            state.push_history(&track);
+        }
+        assert_eq!(state.history.len(), HISTORY_LIMIT);
+        assert_eq!(state.history[0].id, format!("t{}", HISTORY_LIMIT + 4));
+    }
+
+    #[test]
+    fn test_session_file_written_atomically() {
+        let state = AppState {
+            current_track: CurrentTrack {
+                name: "Song".to_string(),
+                progress_ms: 1_000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let path = std::env::temp_dir()
+            .join(format!("vibes-test-{}", std::process::id()))
+            .join("session.json");
+        write_atomic(&path, serde_json::to_string_pretty(&SessionSummary::from_state(&state)).unwrap()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json["now_playing"]["name"], "Song");
+        assert_eq!(json["history"].as_array().map(Vec::len), Some(0));
+        assert!(!path.with_extension("tmp").exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_web_remote_needs_pairing_code() {
+        let status = RemoteStatus { volume: 40, ..Default::default() };
+        let req = |line: &str| parse_request(&format!("{line} HTTP/1.1\r\nHost: x\r\n\r\n")).unwrap();
+
+        let (reply, action) = handle(&req("GET /"), "123456", &status);
+        assert!(reply.status.starts_with("200") && reply.content_type.starts_with("text/html"));
+        assert_eq!(action, None);
+
+        let (reply, action) = handle(&req("POST /api/next?code=654321"), "123456", &status);
+        assert!(reply.status.starts_with("401"));
+        assert_eq!(action, None);
+
+        let (reply, action) = handle(&req("POST /api/next?code=123456"), "123456", &status);
+        assert_eq!(action, Some(UserAction::NextTrack));
+        let json: serde_json::Value = serde_json::from_str(&reply.body).unwrap();
+        assert_eq!(json["volume"], 40);
+
+        let (reply, _) = handle(&req("GET /api/toggle?code=123456"), "123456", &status);
+        assert!(reply.status.starts_with("404")); // actions are POST only
+    }
+
+    #[test]
+    fn test_wrong_pairing_codes_lock_out_only_that_address() {
+        let (guesser, phone) = ("192.168.1.66".parse().unwrap(), "192.168.1.20".parse().unwrap());
+        let mut failures = FailedAttempts::default();
+        let locked_at = (1..=MAX_FAILED_ATTEMPTS).find(|_| failures.record(guesser));
+        assert_eq!(locked_at, Some(MAX_FAILED_ATTEMPTS));
+        assert!(failures.locked(guesser));
+        assert!(!failures.locked(phone));
+    }
+
+    #[test]
+    fn test_mpd_commands_map_onto_playback() {
+        let words = mpd::parse_line(r#"find "Artist" "Sigur \"Rós\"" x"#);
+        assert_eq!(words, vec!["find", "Artist", "Sigur \"Rós\"", "x"]);
+        let run = |line: &str, status: &RemoteStatus| mpd::run(&mpd::parse_line(line), status);
+
+        let stopped = RemoteStatus { volume: 40, ..Default::default() };
+        let (out, action) = run("status", &stopped).unwrap();
+        assert!(out.contains("volume: 40\n") && out.contains("state: stop\n") && out.contains("playlistlength: 0\n"));
+        assert_eq!(action, None);
+        assert_eq!(run("currentsong", &stopped).unwrap().0, "");
+
+        let song = |id: &str, name: &str| QueueEntry { id: Some(id.into()), name: name.into(), artists: vec!["A".into()], duration_ms: 200_000 };
+        let paused = RemoteStatus {
+            now_playing: Some(NowPlaying {
+                id: Some("spotify:track:now".into()),
+                name: "Now".into(),
+                artists: vec!["A".into(), "B".into()],
+                album: "Album".into(),
+                album_art_url: None,
+                is_playing: false,
+                progress_ms: 50_000,
+                duration_ms: 200_000,
+            }),
+            volume: 40,
+            queue: vec![song("spotify:track:next", "Next")],
+        };
+        let (out, _) = run("status", &paused).unwrap();
+        assert!(out.contains("state: pause\n") && out.contains("time: 50:200\n") && out.contains("nextsongid: 2\n"));
+        let (out, _) = run("playlistinfo", &paused).unwrap();
+        assert!(out.starts_with("file: spotify:track:now\nArtist: A, B\nTitle: Now\nAlbum: Album\n"));
+        assert!(out.contains("file: spotify:track:next\n") && out.contains("Pos: 1\nId: 2\n"));
+        assert_eq!(run("playlistinfo 5", &paused).unwrap_err().code, 50);
+
+        assert_eq!(run("play", &paused).unwrap().1, Some(UserAction::TogglePlay));
+        assert_eq!(run("pause 1", &paused).unwrap().1, None);
+        assert_eq!(run("pause", &paused).unwrap().1, Some(UserAction::TogglePlay));
+        assert_eq!(run("next", &paused).unwrap().1, Some(UserAction::NextTrack));
+        assert_eq!(run("seekcur 100", &paused).unwrap().1, Some(UserAction::SeekPercent(50)));
+        assert!(run("play 1", &paused).is_err());
+        let ack = run("update", &paused).unwrap_err();
+        assert_eq!(ack.line(0, "update"), "ACK [5@0] {update} unknown command \"update\"\n");
+
+        // Progress ticking by isn't news for idle clients; a new track is
+        let mut later = paused.clone();
+        later.now_playing.as_mut().unwrap().progress_ms += 2_000;
+        assert!(mpd::changed_subsystems(&paused, &later).is_empty());
+        later.now_playing.as_mut().unwrap().id = Some("spotify:track:next".into());
+        later.queue.clear();
+        assert_eq!(mpd::changed_subsystems(&paused, &later), vec!["player", "playlist"]);
+    }
+
+    #[test]
+    fn test_status_line_template_and_file() {
+        let now = NowPlaying {
+            id: Some("spotify:track:now".into()),
+            name: "Windowlicker".into(),
+            artists: vec!["Aphex Twin".into()],
+            album: "Windowlicker".into(),
+            album_art_url: None,
+            is_playing: false,
+            progress_ms: 65_000,
+            duration_ms: 367_000,
+        };
+        assert_eq!(widget::status_line(widget::DEFAULT_STATUS_FORMAT, Some(&now), 40), "⏸ Aphex Twin – Windowlicker 1:05/6:07");
+        assert_eq!(widget::status_line("{state} {volume}% {album}", Some(&now), 40), "paused 40% Windowlicker");
+        let braces = NowPlaying { name: "Live {album} {x".into(), ..now.clone() };
+        assert_eq!(widget::status_line("{title} {nope}", Some(&braces), 40), "Live {album} {x {nope}");
+        assert_eq!(widget::status_line(widget::DEFAULT_STATUS_FORMAT, None, 40), "", "nothing playing shows nothing");
+
+        let dir = std::env::temp_dir().join(format!("vibes-status-{}", std::process::id()));
+        let path = dir.join("bar").join("status.txt");
+        widget::write_status(&path, "first").unwrap();
+        widget::write_status(&path, "second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second\n", "replaced, not appended");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_bar_widget_lines_and_clicks() {
+        let now = NowPlaying {
+            id: Some("spotify:track:now".into()),
+            name: "Rock & Roll <Live>".into(),
+            artists: vec!["A".into(), "B".into()],
+            album: "100% Album".into(),
+            album_art_url: None,
+            is_playing: true,
+            progress_ms: 50_000,
+            duration_ms: 200_000,
+        };
+        let line: serde_json::Value = serde_json::from_str(&widget::render(WidgetFormat::Waybar, Some(&now))).unwrap();
+        assert_eq!(line["text"], "A, B – Rock &amp; Roll &lt;Live&gt;");
+        assert_eq!((line["class"].as_str(), line["percentage"].as_u64()), (Some("playing"), Some(25)));
+        let stopped: serde_json::Value = serde_json::from_str(&widget::render(WidgetFormat::Waybar, None)).unwrap();
+        assert_eq!((stopped["text"].as_str(), stopped["class"].as_str()), (Some(""), Some("stopped")));
+
+        let paused = NowPlaying { is_playing: false, name: "50% Off".into(), ..now };
+        let line = widget::render(WidgetFormat::Polybar, Some(&paused));
+        assert!(line.starts_with("%{A1:vibes widget click toggle:}"), "{line}");
+        assert!(line.contains("⏸ A, B – 50%% Off%{A}"), "{line}");
+        assert_eq!(widget::render(WidgetFormat::Polybar, None), "");
+        assert!("i3bar".parse::<WidgetFormat>().is_err());
+
+        // A click is one control-socket command to the running vibes
+        assert_eq!("prev".parse::<Click>().unwrap().command(), Command::Prev);
+        assert!("shuffle".parse::<Click>().is_err());
+    }
+
+    #[test]
+    fn test_control_socket_commands_become_actions() {
+        let paused = RemoteStatus {
+            now_playing: Some(NowPlaying {
+                id: Some("spotify:track:now".into()),
+                name: "Now".into(),
+                artists: vec!["A".into()],
+                album: "Album".into(),
+                album_art_url: None,
+                is_playing: false,
+                progress_ms: 0,
+                duration_ms: 200_000,
+            }),
+            ..Default::default()
+        };
+        let run = |line: &str| {
+            let (reply, action) = ipc::run(line, &paused);
+            (serde_json::to_value(&reply).unwrap(), action)
+        };
+        assert_eq!(run(r#"{"command":"play"}"#).1, Some(UserAction::TogglePlay));
+        assert_eq!(run(r#"{"command":"pause"}"#), (serde_json::json!({"ok": true}), None), "already paused");
+        assert_eq!(run(r#"{"command":"search","query":" sigur ros "}"#).1, Some(UserAction::Search("sigur ros".into())));
+        let link = r#"{"command":"queue","uri":"https://open.spotify.com/track/4uLU6hMCjMI75M1A2tKUQC?si=x"}"#;
+        assert_eq!(run(link).1, Some(UserAction::QueueUri("spotify:track:4uLU6hMCjMI75M1A2tKUQC".into())));
+        let (reply, action) = run(r#"{"command":"queue","uri":"spotify:album:4uLU6hMCjMI75M1A2tKUQC"}"#);
+        assert_eq!((reply["ok"].as_bool(), action), (Some(false), None));
+        assert_eq!(run(r#"{"command":"status"}"#).0["status"]["now_playing"]["name"], "Now");
+        assert_eq!(run("shuffle").0["ok"], false);
+        assert_eq!(run(r#"{"command":"quit"}"#).1, Some(UserAction::Shutdown));
+
+        assert_eq!(Command::from_args(&["search", "a", "b"]).unwrap(), Command::Search { query: "a b".into() });
+        assert!(Command::from_args(&["search"]).is_err());
+        assert_eq!(ipc::default_path(Some("family")).file_name().unwrap(), "vibes-family.sock");
+        assert_ne!(ipc::default_path(None).parent(), Some(std::env::temp_dir().as_path()), "never loose in the shared temp directory");
+    }
+
+    #[test]
+    fn test_transfer_finds_the_device() {
+        use crate::spotify::player::find_device;
+        use rspotify::model::{Device, DeviceType};
+        let device = |id: &str, name: &str| Device {
+            id: Some(id.into()),
+            is_active: false,
+            is_private_session: false,
+            is_restricted: false,
+            name: name.into(),
+            _type: DeviceType::Speaker,
+            volume_percent: Some(50),
+        };
+        let devices = vec![device("a1", "Kitchen"), device("b2", "Living Room TV"), device("c3", "Living Room Speaker")];
+        assert_eq!(find_device(&devices, "b2").unwrap().name, "Living Room TV");
+        assert_eq!(find_device(&devices, "kitchen").unwrap().id.as_deref(), Some("a1"));
+        assert_eq!(find_device(&devices, "tv").unwrap().id.as_deref(), Some("b2"), "the only one with that in its name");
+        assert_eq!(find_device(&devices, "living room"), None, "two of them");
+        assert_eq!(find_device(&devices, "bathroom"), None);
+
+        let paused = RemoteStatus::default();
+        let run = |line: &str| ipc::run(line, &paused).1;
+        assert_eq!(
+            run(r#"{"command":"transfer","device":" Kitchen "}"#),
+            Some(UserAction::TransferPlayback { device: "Kitchen".into(), play: true })
+        );
+        assert_eq!(
+            run(r#"{"command":"transfer","device":"Kitchen","play":false}"#),
+            Some(UserAction::TransferPlayback { device: "Kitchen".into(), play: false })
+        );
+        assert_eq!(run(r#"{"command":"transfer","device":""}"#), None);
+        assert_eq!(
+            Command::from_args(&["transfer", "--no-play", "Living", "Room", "TV"]).unwrap(),
+            Command::Transfer { device: "Living Room TV".into(), play: false }
+        );
+        assert!(Command::from_args(&["transfer"]).is_err());
+
+        let state = AppState { devices: Some(DevicesView { devices, selected: 1 }), ..Default::default() };
+        let mut terminal = Terminal::new(TestBackend::new(70, 12)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains("Devices (3)") && screen.contains("Living Room TV"));
+    }
+
+    #[test]
+    fn test_json_output_for_scripts() {
+        let mut args: Vec<String> = ["queue", "--json"].iter().map(|s| s.to_string()).collect();
+        assert!(crate::config::take_flag(&mut args, "--json"));
+        assert_eq!(args, ["queue"]);
+        assert!(!crate::config::take_flag(&mut args, "--json"));
+
+        // Nobody listening: a stopped player, not an error
+        let config = Config { control_socket: Some(std::env::temp_dir().join("vibes-nobody-here.sock")), ..Config::default() };
+        let stopped = crate::running_status(&config);
+        assert_eq!(stopped, serde_json::json!({"now_playing": null, "volume": 0, "queue": []}));
+        assert_eq!(crate::status_line(&stopped, "{title}"), "");
+
+        // The queue reads back from what the socket sends
+        let status = RemoteStatus {
+            queue: vec![QueueEntry { id: Some("spotify:track:a".into()), name: "A".into(), artists: vec!["X".into()], duration_ms: 1_000 }],
+            volume: 70,
+            ..Default::default()
+        };
+        let sent = serde_json::to_value(&status).unwrap();
+        let queue: Vec<QueueEntry> = serde_json::from_value(sent["queue"].clone()).unwrap();
+        assert_eq!(queue, status.queue);
+        assert_eq!(crate::status_line(&sent, "{volume}"), "", "nothing playing");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_control_socket_client_round_trip() {
+        let dir = std::env::temp_dir().join(format!("vibes-ipc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("vibes.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            use std::io::{BufRead, Write};
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            std::io::BufReader::new(stream.try_clone().unwrap()).read_line(&mut line).unwrap();
+            let status = RemoteStatus { volume: 40, ..Default::default() };
+            let (reply, _) = ipc::run(&line, &status);
+            writeln!(stream, "{}", serde_json::to_string(&reply).unwrap()).unwrap();
+            line
+        });
+        let reply = ipc::send(&path, &Command::Status).unwrap();
+        assert_eq!(reply["status"]["volume"], 40);
+        assert_eq!(server.join().unwrap(), "{\"command\":\"status\"}\n");
+        let _ = std::fs::remove_dir_all(dir);
+        assert!(ipc::send(&path, &Command::Next).is_err(), "nobody listening");
+    }
+
+    #[test]
+    fn test_local_device_settings() {
+        let config: Config = toml::from_str("[device]\nname = \"Desk\"\nbitrate = 200\n").unwrap();
+        assert_eq!(config.device.name, "Desk");
+        assert_eq!(config.device.bitrate(), 160, "rounded down to a quality Spotify has");
+        assert_eq!(config.device.initial_volume(), DeviceSettings::default().initial_volume());
+        let loud = DeviceSettings { volume: 150, bitrate: 10, ..Default::default() };
+        assert_eq!((loud.initial_volume(), loud.bitrate()), (u16::MAX, 96));
+        assert_eq!(Features::default().enabled(Feature::LocalDevice), cfg!(feature = "librespot"));
+    }
+
+    #[test]
+    fn test_hooks_get_the_track_in_their_environment() {
+        let config: Config = toml::from_str("[hooks]\non_track_change = \"notify-send \\\"$VIBES_TITLE\\\"\"\non_pause = \" \"\n").unwrap();
+        assert_eq!(config.hooks.command(HookEvent::TrackChange), Some("notify-send \"$VIBES_TITLE\""));
+        assert_eq!(config.hooks.command(HookEvent::Pause), None, "blank means unset");
+        assert_eq!(Hooks::default().command(HookEvent::Like), None);
+
+        let track = CurrentTrack {
+            id: Some("spotify:track:abc".into()),
+            name: "Roygbiv".into(),
+            artists: vec!["Boards of Canada".into(), "Guest".into()],
+            album: "Music Has the Right to Children".into(),
+            duration_ms: 151_000,
+            progress_ms: 42_000,
+            ..Default::default()
+        };
+        let env = hooks::env(HookEvent::Pause, &HookTrack::from(&track));
+        let get = |name: &str| env.iter().find(|(k, _)| *k == name).map(|(_, v)| v.as_str());
+        assert_eq!(get("VIBES_EVENT"), Some("pause"));
+        assert_eq!(get("VIBES_ARTIST"), Some("Boards of Canada, Guest"));
+        assert_eq!(get("VIBES_TRACK_LINK"), Some("https://open.spotify.com/track/abc"));
+        assert_eq!(get("VIBES_PROGRESS_MS"), Some("42000"));
+
+        #[cfg(unix)]
+        {
+            let hook = r#"printf '%s|%s' "$VIBES_EVENT" "$VIBES_TITLE" >&2"#;
+            let out = hooks::command(hook, HookEvent::TrackChange, &HookTrack::from(&track)).output().unwrap();
+            assert_eq!(String::from_utf8_lossy(&out.stderr), "track_change|Roygbiv");
+        }
+    }
+
+    #[test]
+    fn test_daemon_frames_go_to_the_attached_terminal() {
+        use ratatui::{layout::Size, widgets::Paragraph, Terminal};
+
+        assert_eq!(
+            daemon::attach_path(std::path::Path::new("/run/user/1000/vibes-family.sock")),
+            std::path::PathBuf::from("/run/user/1000/vibes-family-attach.sock")
+        );
+
+        let screen = Screen::default();
+        let mut terminal = Terminal::new(AttachBackend::new(screen.clone())).unwrap();
+        let draw = |terminal: &mut Terminal<AttachBackend>| {
+            terminal.draw(|f| f.render_widget(Paragraph::new("vibes"), f.area())).unwrap();
+        };
+        // Detached: drawn into the void
+        draw(&mut terminal);
+        assert!(!screen.is_attached());
+
+        let (first, mut frames) = screen.attach(Size::new(100, 30));
+        terminal.clear().unwrap();
+        draw(&mut terminal);
+        assert_eq!(terminal.get_frame().area().width, 100, "drawn at the attached terminal's size");
+        let mut out = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            out.extend(frame);
+        }
+        assert!(String::from_utf8_lossy(&out).contains("vibes"));
+
+        // Another terminal takes over; the first one's output ends
+        let (second, _frames) = screen.attach(Size::new(80, 24));
+        assert!(frames.try_recv().is_err());
+        assert!(!screen.is_current(first) && screen.is_current(second));
+        assert!(screen.detach());
+        assert!(!screen.detach(), "nothing left to detach");
+    }
+
+    #[test]
+    fn test_play_log_exports_for_backfill() {
+        let mut state = AppState { listened_ms: 150_000, ..Default::default() };
+        let track = CurrentTrack {
+            id: Some("spotify:track:abc".into()),
+            name: "Song, \"Live\"".into(),
+            artists: vec!["A".into(), "B".into()],
+            album: "Album".into(),
+            duration_ms: 200_000,
+            ..Default::default()
+        };
+        let entry = state.push_history(&track).unwrap();
+        assert!(entry.scrobble);
+        assert_eq!((entry.played_at - entry.started_at).num_seconds(), 150);
+        let play = Play::from(entry);
+
+        let dir = std::env::temp_dir().join(format!("vibes-plays-{}", std::process::id()));
+        let log = dir.join("plays.jsonl");
+        plays::append(&log, &play).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&log).map(|mut f| {
+            use std::io::Write;
+            writeln!(f, "not json").unwrap();
+        }).unwrap();
+        assert_eq!(plays::load(&log).unwrap(), vec![play.clone()]);
+
+        let csv = plays::to_csv(std::slice::from_ref(&play));
+        let row = format!("{},\"A, B\",\"Song, \"\"Live\"\"\",Album,200", play.listened_at.timestamp());
+        assert_eq!(csv.lines().collect::<Vec<_>>(), vec!["timestamp,artist,track,album,duration", row.as_str()]);
+
+        let job = JobHandle::default();
+        let (path, n) = plays::export(&log, ExportFormat::ListenBrainz, Some(play.listened_at), &job).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!((job.done(), job.total()), (1, 1));
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(json[0]["track_metadata"]["artist_name"], "A, B");
+        assert_eq!(json[0]["track_metadata"]["additional_info"]["spotify_id"], "https://open.spotify.com/track/abc");
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_recently_played_merges_without_duplicates() {
+        let play = |at: &str, id: &str| Play {
+            listened_at: at.parse().unwrap(),
+            artist: "A".into(),
+            track: id.into(),
+            album: String::new(),
+            duration_ms: 200_000,
+            id: id.into(),
+        };
+        let dir = std::env::temp_dir().join(format!("vibes-merge-{}", std::process::id()));
+        let log = dir.join("plays.jsonl");
+        plays::append(&log, &play("2026-10-16T10:00:00Z", "x")).unwrap();
+        let recent = vec![
+            play("2026-10-16T10:01:10Z", "x"), // logged locally; Spotify's stamp is a bit off
+            play("2026-10-16T10:03:20Z", "x"), // on repeat: the next listen
+            play("2026-10-16T08:00:00Z", "y"), // while vibes was closed
+        ];
+        let added = plays::merge(&log, &recent).unwrap();
+        assert_eq!(added.iter().map(|p| p.listened_at.to_rfc3339()).collect::<Vec<_>>(), vec![
+            "2026-10-16T10:03:20+00:00",
+            "2026-10-16T08:00:00+00:00",
+        ]);
+        let merged = plays::load(&log).unwrap();
+        assert_eq!(merged.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), vec!["y", "x", "x"]);
+        assert!(plays::merge(&log, &recent).unwrap().is_empty()); // the next launch adds nothing
+        let _ = std::fs::remove_dir_all(dir);
+
+        let mut state = AppState { listened_ms: 200_000, ..Default::default() };
+        state.push_history(&CurrentTrack { id: Some("x".into()), duration_ms: 200_000, ..Default::default() });
+        let local = state.history[0].clone();
+        let spotify = HistoryEntry { played_at: local.played_at + chrono::Duration::seconds(5), ..local.clone() };
+        let earlier = HistoryEntry { id: "y".into(), played_at: local.played_at - chrono::Duration::hours(2), ..local };
+        state.merge_history([spotify, earlier]);
+        assert_eq!(state.history.iter().map(|h| h.id.as_str()).collect::<Vec<_>>(), vec!["x", "y"]);
+    }
+
+    #[test]
+    fn test_on_this_day_groups_earlier_years() {
+        let play = |at: &str, id: &str| Play {
+            listened_at: at.parse().unwrap(),
+            artist: "A".into(),
+            track: id.into(),
+            album: String::new(),
+            duration_ms: 0,
+            id: id.into(),
+        };
+        let log = vec![
+            play("2023-10-16T08:00:00Z", "x"),
+            play("2024-10-16T08:00:00Z", "y"),
+            play("2024-10-16T09:00:00Z", "z"),
+            play("2024-10-16T10:00:00Z", "z"),
+            play("2024-10-17T01:00:00Z", "late"), // still the 16th at UTC-5
+            play("2024-10-15T12:00:00Z", "before"),
+            play("2026-10-16T08:00:00Z", "today"),
+        ];
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let utc = chrono::FixedOffset::east_opt(0).unwrap();
+        let recaps = plays::on_this_day(&log, today, utc);
+        assert_eq!(recaps.iter().map(|r| r.year).collect::<Vec<_>>(), vec![2024, 2023]);
+        assert_eq!((recaps[0].plays, recaps[0].top.id.as_str(), recaps[0].top_count), (3, "z", 2));
+
+        let recaps = plays::on_this_day(&log, today, chrono::FixedOffset::west_opt(5 * 3600).unwrap());
+        assert_eq!(recaps[0].plays, 4);
+    }
+
+    #[test]
+    fn test_scrobble_rule() {
+        assert!(!counts_as_scrobble(25_000, 25_000)); // too short to ever count
+        assert!(!counts_as_scrobble(200_000, 99_999));
+        assert!(counts_as_scrobble(200_000, 100_000));
+        assert!(counts_as_scrobble(1_200_000, 240_000)); // 4 minutes of a long mix
+    }
+
+    #[test]
+    fn test_track_end_detected_once_and_recorded_as_completed() {
+        let mut state = AppState {
+            current_track: CurrentTrack {
+                id: Some("spotify:track:a".into()),
+                duration_ms: 1_000,
+                progress_ms: 900,
+                is_playing: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(!state.tick_progress(80));
+        assert!(state.tick_progress(80));
+        assert!(!state.tick_progress(80)); // only fires on the tick it ends
+        let finished = state.current_track.clone();
+        state.push_history(&finished);
+        assert!(state.history[0].completed);
+        assert!(!state.track_ended);
+        assert_eq!(state.listened_ms, 0);
+
+        // Skipped early: neither completed nor a scrobble
+        let skipped = CurrentTrack { progress_ms: 10_000, duration_ms: 200_000, ..finished };
+        state.listened_ms = 10_000;
+        state.push_history(&skipped);
+        assert!(!state.history[0].completed);
+        assert!(!state.history[0].scrobble);
+    }
+
+    // ── Theme ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_every_preset_resolves() {
+        for name in PRESETS {
+            assert!(Theme::preset(name).is_some(), "missing preset {name}");
+        }
+        assert_eq!(Theme::preset("Nord"), Theme::preset("nord"));
+        assert!(Theme::preset("solarized-ultra").is_none());
+    }
+
+    #[test]
+    fn test_theme_color_overrides() {
+        let mut colors = BTreeMap::new();
+        colors.insert("primary".to_string(), "#ff8800".to_string());
+        colors.insert("nonsense".to_string(), "#000000".to_string());
+        colors.insert("accent".to_string(), "not-a-color".to_string());
+        let theme = Theme::from_config("dracula", &colors);
+        let dracula = Theme::preset("dracula").unwrap();
+        assert_eq!(theme.primary, Color::Rgb(255, 136, 0));
+        assert_eq!(theme.accent, dracula.accent);
+        assert_eq!(Theme::from_config("missing", &BTreeMap::new()), Theme::default());
+    }
+
+    #[test]
+    fn test_palette_maps_down_to_what_the_terminal_shows() {
+        use ratatui::style::Modifier;
+        assert_eq!(ColorDepth::detect(None, Some("truecolor"), Some("xterm-256color")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::detect(None, None, Some("xterm-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::detect(None, None, Some("screen")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::detect(None, None, Some("dumb")), ColorDepth::NoColor);
+        assert_eq!(ColorDepth::detect(Some("1"), Some("truecolor"), Some("xterm-direct")), ColorDepth::NoColor);
+        assert_eq!(ColorDepth::detect(Some(""), None, Some("xterm-direct")), ColorDepth::TrueColor, "empty NO_COLOR is unset");
+        assert_eq!(ColorDepth::Ansi16.resolve(), ColorDepth::Ansi16, "config beats the environment");
+        let config: Config = toml::from_str("color_depth = \"256\"").unwrap();
+        assert_eq!(config.color_depth, ColorDepth::Ansi256);
+
+        let pink = Color::Rgb(241, 91, 181);
+        assert_eq!(reduce(pink, ColorDepth::TrueColor), pink);
+        assert_eq!(reduce(Color::Rgb(255, 0, 0), ColorDepth::Ansi256), Color::Indexed(196));
+        // Greys land on the ramp rather than the coarser cube
+        assert_eq!(reduce(Color::Rgb(80, 80, 100), ColorDepth::Ansi256), Color::Indexed(240));
+        assert_eq!(reduce(Color::Rgb(0, 245, 255), ColorDepth::Ansi16), Color::LightCyan);
+        assert_eq!(reduce(Color::Indexed(196), ColorDepth::Ansi16), Color::LightRed);
+        assert_eq!(reduce(Color::Magenta, ColorDepth::Ansi256), Color::Magenta);
+        assert_eq!(reduce(Color::Magenta, ColorDepth::NoColor), Color::Reset);
+
+        let default = Theme::default();
+        assert!(!default.selected_style().add_modifier.contains(Modifier::REVERSED));
+        for depth in [ColorDepth::Ansi16, ColorDepth::NoColor] {
+            let theme = default.for_depth(depth);
+            assert!(theme.selected_style().add_modifier.contains(Modifier::REVERSED), "{depth:?}");
+        }
+        assert!(default.for_depth(ColorDepth::NoColor).header_style().add_modifier.contains(Modifier::REVERSED));
+    }
+
+    // ── Tempo sync ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_capped_bpm_halves_fast_tempos() {
+        assert_eq!(capped_bpm(128.0, 180.0), 128.0);
+        assert_eq!(capped_bpm(200.0, 180.0), 100.0);
+        assert_eq!(capped_bpm(400.0, 90.0), 50.0);
+    }
+
+    #[test]
+    fn test_tick_tempo_lands_beats_at_bpm() {
+        let mut state = AppState {
+            tempo_bpm: Some(120.0),
+            current_track: CurrentTrack { is_playing: true, ..Default::default() },
+            ..Default::default()
+        };
+        // 120 BPM = one beat per 500ms = every 5th 100ms tick
+        let beats = (0..50)
+            .filter(|_| {
+                state.tick_tempo(100);
+                state.beat
+            })
+            .count();
+        assert_eq!(beats, 10);
+    }
+
+    // ── Device disconnect ─────────────────────────────────────────────────────
+
+    fn playing_on(device: &str) -> AppState {
+        let mut state = AppState {
+            current_track: CurrentTrack {
+                id: Some("spotify:track:abc".into()),
+                context_uri: Some("spotify:album:xyz".into()),
+                is_playing: true,
+                device_id: Some(device.into()),
+                device_name: Some("Kitchen".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        state.note_playback_seen(61_000, true);
+        state
+    }
+
+    #[test]
+    fn test_vanished_device_records_resume_point() {
+        let mut state = playing_on("kitchen");
+        assert!(state.note_playback_missing(Some(&["kitchen".to_string()])).is_none(), "device still listed");
+        let point = state.note_playback_missing(Some(&["laptop".to_string()])).cloned().expect("device gone");
+        assert_eq!(point.uri, "spotify:track:abc");
+        assert_eq!(point.context_uri.as_deref(), Some("spotify:album:xyz"));
+        assert_eq!(point.position_ms, 61_000);
+        assert_eq!(point.device_name, "Kitchen");
+        assert!(!state.current_track.is_playing);
+
+        state.note_playback_seen(0, true);
+        assert!(state.resume_point.is_none(), "playing again elsewhere clears it");
+    }
+
+    #[test]
+    fn test_failed_polls_need_several_misses() {
+        let mut state = playing_on("kitchen");
+        for _ in 1..DEVICE_LOST_MISSES {
+            assert!(state.note_playback_missing(None).is_none());
+        }
+        assert!(state.note_playback_missing(None).is_some());
+        // Already paused: later misses don't re-toast
+        assert!(state.note_playback_missing(None).is_none());
+    }
+
+    // ── Liked tracks ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_liked_status_is_requested_once_per_track() {
+        let mut liked = LikedTracks::default();
+        let uris = || ["spotify:track:a", "spotify:track:b", "spotify:episode:c", "spotify:track:a"].map(String::from);
+        assert_eq!(liked.claim_unknown(uris()), vec!["spotify:track:a", "spotify:track:b"]);
+        // Still waiting on the answer: not asked again
+        assert!(liked.claim_unknown(uris()).is_empty());
+        liked.set("spotify:track:a", true);
+        assert!(liked.contains("spotify:track:a"));
+        assert_eq!(liked.get("spotify:track:b"), None);
+    }
+
+    #[test]
+    fn test_note_liked_updates_the_playing_track() {
+        let mut state = playing_on("kitchen");
+        state.note_liked("spotify:track:other", true);
+        assert!(!state.current_track.is_liked);
+        state.note_liked("spotify:track:abc", true);
+        assert!(state.current_track.is_liked);
+        state.note_liked("spotify:track:abc", false);
+        assert!(!state.current_track.is_liked);
+    }
+
+    // ── Visual select ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_visual_select_marks_in_order() {
+        let mut visual = VisualSelect::default();
+        assert!(visual.toggle("spotify:track:b"));
+        assert!(visual.toggle("spotify:track:a"));
+        assert!(visual.toggle("spotify:track:c"));
+        assert!(!visual.toggle("spotify:track:a"));
+        assert_eq!(visual.marked, vec!["spotify:track:b", "spotify:track:c"]);
+        assert!(visual.toggle("spotify:playlist:p"));
+        let state = AppState { visual: Some(visual), ..Default::default() };
+        assert_eq!(state.marked_tracks(), vec!["spotify:track:b", "spotify:track:c"]);
+        assert_eq!(state.marked_playlists(), vec!["spotify:playlist:p"]);
+        assert!(AppState::default().marked_tracks().is_empty());
+    }
+
+    // ── Inbox ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_inbox_takes_shared_links() {
+        let id = "4uLU6hMCjMI75M1A2tKUQC";
+        let uri = format!("spotify:track:{id}");
+        for link in [
+            format!("https://open.spotify.com/track/{id}?si=abc123"),
+            format!("https://open.spotify.com/intl-de/track/{id}"),
+            format!("  {uri}\n"),
+        ] {
+            assert_eq!(inbox::track_uri(&link), Some(uri.clone()), "{link}");
+        }
+        assert_eq!(inbox::track_uri(&format!("https://open.spotify.com/album/{id}")), None);
+        assert_eq!(inbox::track_uri("spotify:track:short"), None);
+
+        let dir = std::env::temp_dir().join(format!("vibes-inbox-{}", std::process::id()));
+        let path = dir.join("inbox.jsonl");
+        assert!(inbox::load(&path).unwrap().is_empty());
+        assert!(inbox::add(&path, "https://example.com", None).is_err());
+        inbox::add(&path, &format!("https://open.spotify.com/track/{id}"), Some("from Sam".into())).unwrap();
+        // Scripts can append bare links; repeats are shown once
+        let other = "spotify:track:0VjIjW4GlUZAMYd2vXMi3b";
+        std::fs::OpenOptions::new().append(true).open(&path).map(|mut f| {
+            use std::io::Write;
+            writeln!(f, "{other}\n{uri}").unwrap();
+        }).unwrap();
+        let items = inbox::load(&path).unwrap();
+        assert_eq!(items.iter().map(|i| i.uri.as_str()).collect::<Vec<_>>(), vec![uri.as_str(), other]);
+        assert_eq!(items[0].note.as_deref(), Some("from Sam"));
+
+        let kept = inbox::remove(&path, std::slice::from_ref(&uri)).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(inbox::load(&path).unwrap(), kept);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    // ── Trace ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_trace_lines_are_flat_json_and_append_across_runs() {
+        let dir = std::env::temp_dir().join(format!("vibes-trace-{}", std::process::id()));
+        let path = dir.join("trace.jsonl");
+        let mut tracer = Tracer::open(&path).unwrap();
+        tracer.record(&TraceEvent::Poll {
+            track: Some("abc".into()),
+            progress_ms: 61_000,
+            duration_ms: 200_000,
+            is_playing: true,
+            took_ms: 180,
+            drift_ms: Some(-450),
+        }).unwrap();
+        drop(tracer);
+        // `:trace off` then on again keeps the earlier lines
+        Tracer::open(&path).unwrap().record(&TraceEvent::PollMissing { took_ms: 90, devices: None }).unwrap();
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path).unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let events: Vec<_> = lines.iter().map(|l| l["event"].as_str().unwrap()).collect();
+        assert_eq!(events, vec!["started", "poll", "started", "poll_missing"]);
+        assert_eq!(lines[1]["drift_ms"], -450);
+        assert_eq!(lines[1]["track"], "abc");
+        assert!(lines[1]["mono_ms"].is_u64() && lines[1]["at"].is_string());
+        assert!(lines[3]["devices"].is_null());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    // ── Search history ────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_search_history_recall() {
+        let mut history = Vec::new();
+        for q in ["daft punk", "  ", "khruangbin", "Daft Punk", "daft punk "] {
+            searches::push(&mut history, q);
+        }
+        // Repeats move to the front instead of being listed twice; blanks are skipped
+        assert_eq!(history, vec!["daft punk", "Daft Punk", "khruangbin"]);
+        for i in 0..searches::LIMIT + 5 {
+            searches::push(&mut history, &format!("q{i}"));
+        }
+        assert_eq!(history.len(), searches::LIMIT);
+        assert_eq!(history[0], format!("q{}", searches::LIMIT + 4));
+
+        let mut search = SearchState {
+            history: vec!["newest".into(), "older".into()],
+            query: "half-ty".into(),
+            ..Default::default()
+        };
+        search.recall(false);
+        assert_eq!(search.query, "half-ty");
+        search.recall(true);
+        assert_eq!(search.query, "newest");
+        search.recall(true);
+        search.recall(true);
+        assert_eq!(search.query, "older");
+        search.recall(false);
+        search.recall(false);
+        // Back past the newest restores what was being typed
+        assert_eq!((search.query.as_str(), search.recall), ("half-ty", None));
+
+        let dir = std::env::temp_dir().join(format!("vibes-searches-{}", std::process::id()));
+        let path = dir.join("searches.txt");
+        assert!(searches::load(&path).unwrap().is_empty());
+        searches::save(&path, &history).unwrap();
+        assert_eq!(searches::load(&path).unwrap(), history);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    // ── Playlist archive ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_playlist_archive_round_trip() {
+        let entry = |id: &str, days_ago: i64| PlaylistArchive {
+            id: id.to_string(),
+            name: format!("Playlist {id}"),
+            owner: "me".to_string(),
+            owned: true,
+            archived_at: chrono::Utc::now() - chrono::Duration::days(days_ago),
+            snapshot: PlaylistSnapshot {
+                snapshot_id: "s1".to_string(),
+                tracks: vec![("spotify:track:a".to_string(), "A — X".to_string())],
+            },
+        };
+        let dir = std::env::temp_dir().join(format!("vibes-archive-{}", std::process::id()));
+        assert!(archive::load_all(&dir).unwrap().is_empty());
+
+        let old = entry("old", 3);
+        archive::save(&dir, &old).unwrap();
+        archive::save(&dir, &entry("new", 1)).unwrap();
+        std::fs::write(dir.join("broken.json"), "not json").unwrap();
+        let loaded = archive::load_all(&dir).unwrap();
+        assert_eq!(loaded.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(), vec!["new", "old"]);
+        assert_eq!(loaded[1], old);
+
+        archive::remove(&dir, "new").unwrap();
+        assert_eq!(archive::load_all(&dir).unwrap().len(), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_row_number_shows_marks() {
+        assert_eq!(row_number(7, 3, false, false), "  7");
+        assert_eq!(row_number(7, 3, false, true), "  ●");
+        assert_eq!(row_number(7, 3, true, false), "▶");
+        assert_eq!(row_number(7, 3, true, true), "▶●");
+    }
+
+    // ── Jobs ──────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_jobs_report_progress_and_cancel_once() {
+        let mut jobs = Jobs::default();
+        let (archive, handle) = jobs.start(JobKind::Archive, "3 playlists");
+        let (export, _) = jobs.start(JobKind::Export, "Csv export");
+        assert_ne!(archive, export);
+        assert_eq!(jobs.running()[0].fraction(), None);
+        handle.set_total(3);
+        handle.advance(1);
+        assert_eq!(jobs.running()[0].progress(), "1 of 3 playlists");
+        assert!((jobs.running()[0].fraction().unwrap() - 1.0 / 3.0).abs() < 1e-9);
+
+        assert!(jobs.cancel(archive));
+        assert!(!jobs.cancel(archive));
+        assert!(handle.is_cancelled() && handle.check().is_err());
+        assert!(jobs.finish(archive).is_some_and(|j| j.handle.is_cancelled()));
+        assert!(!jobs.is_running(JobKind::Archive) && jobs.is_running(JobKind::Export));
+        assert!(jobs.finish(archive).is_none() && !jobs.cancel(archive));
+    }
+
+    // ── Offline mode ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_offline_after_repeated_failures_until_a_call_gets_through() {
+        let mut conn = Connection::default();
+        for _ in 1..OFFLINE_AFTER_FAILURES {
+            assert!(!conn.note_failure());
+        }
+        assert!(conn.note_failure());
+        assert!(conn.offline);
+        // Only the first failure past the threshold reports the outage
+        assert!(!conn.note_failure());
+        assert!(conn.note_ok());
+        assert!(!conn.offline && !conn.note_ok());
+        // A success resets the count
+        assert!(!conn.note_failure());
+    }
+
+    #[test]
+    fn test_outbox_drops_a_like_undone_while_offline() {
+        let mut conn = Connection::default();
+        conn.queue(PendingWrite::Like("spotify:track:a".into()));
+        conn.queue(PendingWrite::Unlike("spotify:track:b".into()));
+        conn.queue(PendingWrite::Unlike("spotify:track:a".into()));
+        assert_eq!(conn.outbox, vec![PendingWrite::Unlike("spotify:track:b".into())]);
+    }
+
+    #[test]
+    fn test_offline_copy_is_flagged_and_shown_in_the_header() {
+        let mut state = AppState::default();
+        let fetched_at = chrono::Utc::now() - chrono::Duration::days(3);
+        let msg = state.connection.use_offline_copy("playlists", fetched_at);
+        assert!(msg.starts_with("Spotify can't be reached — showing playlists as of "));
+        assert!(state.connection.offline_copy && !state.connection.offline);
+        for _ in 1..OFFLINE_AFTER_FAILURES {
+            state.connection.note_failure();
+        }
+        assert!(state.connection.offline);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+        assert!(screen[..screen.find("your terminal").unwrap()].contains("⚠ offline"));
+    }
+
+    // ── Error toasts ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_failure_toast_keeps_error_chain() {
+        let err = anyhow::anyhow!("connection reset").context("Playback failed");
+        let n = Notification::failure(err.to_string(), &err);
+        assert!(n.is_error());
+        assert_eq!(n.message, "Playback failed");
+        let details = n.details.unwrap();
+        assert!(details.contains("Caused by") && details.contains("connection reset"));
+    }
+
+    #[test]
+    fn test_set_notification_bumps_sequence() {
+        let mut state = AppState::default();
+        state.set_notification(Notification::info("a"));
+        state.set_notification(Notification::info("a"));
+        assert_eq!(state.notification_seq, 2);
+    }
+
+    // ── Log viewer ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_log_buffer_keeps_whole_lines_with_their_level() {
+        let mut buffer = LogBuffer::default();
+        buffer.push("2024-05-01T12:00:00.000001Z ERROR vibes::app: Playback failed\n  Caused by: 429");
+        assert_eq!(buffer.written(), 1, "the unfinished line waits");
+        buffer.push("\n2024-05-01T12:00:01.000001Z  INFO vibes::app: ok\n");
+        let lines = buffer.lines();
+        assert_eq!(lines.iter().map(|l| l.level).collect::<Vec<_>>(), [Some(Level::ERROR), Some(Level::ERROR), Some(Level::INFO)]);
+        assert_eq!(lines[1].text, "  Caused by: 429");
+        assert_eq!(parse_level("  INFO not a log line"), None);
+
+        for _ in 0..KEPT_LINES {
+            buffer.push("2024-05-01T12:00:02.000001Z DEBUG vibes: tick\n");
+        }
+        assert_eq!(buffer.lines().len(), KEPT_LINES);
+        assert_eq!(buffer.written(), 3 + KEPT_LINES as u64);
+
+        let line = |level, text: &str| LogLine { level: Some(level), text: text.into() };
+        let state = AppState {
+            log_view: Some(LogView {
+                lines: vec![
+                    line(Level::ERROR, "2024-05-01T12:00:00.000001Z ERROR vibes::app: Playback failed"),
+                    line(Level::INFO, "2024-05-01T12:00:01.000001Z  INFO vibes::app: ok"),
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains("Log · 1 error") && screen.contains("12:00:00 ERROR vibes::app: Playback failed"));
+    }
+
+    // ── Paging ────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_page_size_follows_viewport() {
+        let area = Rect::new(0, 0, 120, 40);
+        let mut state = AppState { active_screen: ActiveScreen::Library, ..Default::default() };
+        assert_eq!(page_size(area, &state), 40 - 5 - 3);
+        state.eq_expanded = true;
+        assert_eq!(page_size(area, &state), 40 - 15 - 3);
+        assert_eq!(page_size(Rect::new(0, 0, 80, 4), &state), 1);
+    }
+
+    #[test]
+    fn test_prefetched_pages_follow_the_snapshot_and_stay_bounded() {
+        let page = |id: usize, snapshot: &str| PrefetchedPage {
+            playlist_id: format!("pl{id}"),
+            snapshot_id: snapshot.to_string(),
+            items: Vec::new(),
+            total: 0,
+        };
+        let mut prefetched = Prefetched::default();
+        assert!(prefetched.wants("pl0", "a"));
+        prefetched.in_flight = Some("pl0".into());
+        // One request at a time, whatever is highlighted meanwhile
+        assert!(!prefetched.wants("pl1", "a"));
+        prefetched.in_flight = None;
+        prefetched.insert(page(0, "a"));
+        assert!(!prefetched.wants("pl0", "a"));
+        assert!(prefetched.get("pl0", "a").is_some_and(PrefetchedPage::is_complete));
+        // An edited playlist has a new snapshot, so the page is stale
+        assert!(prefetched.get("pl0", "b").is_none());
+        assert!(prefetched.wants("pl0", "b"));
+        prefetched.insert(page(0, "b"));
+        assert!(prefetched.get("pl0", "a").is_none());
+
+        for id in 1..=Prefetched::KEEP {
+            prefetched.insert(page(id, "a"));
+        }
+        assert!(prefetched.get("pl0", "b").is_none());
+        assert!(prefetched.get(&format!("pl{}", Prefetched::KEEP), "a").is_some());
+
+        prefetched.fail("pl99".into(), "a".into());
+        assert!(!prefetched.wants("pl99", "a"));
+        assert!(prefetched.wants("pl99", "b"), "edited since: worth another try");
+        prefetched.loaded("pl99");
+        assert!(prefetched.wants("pl99", "a"));
+    }
+
+    // ── Startup ───────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_startup_panel_tracks_each_load_then_goes() {
+        let mut state = AppState { is_authenticated: true, startup: Some(Startup::default()), ..Default::default() };
+        state.note_startup(StartupLoad::Profile, LoadStatus::Done, "me");
+        state.note_startup(StartupLoad::Playlists, LoadStatus::Done, "38 playlists");
+        state.note_startup(StartupLoad::LikedSongs, LoadStatus::Loading, "50 of 1204");
+        state.note_startup(StartupLoad::RecentlyPlayed, LoadStatus::Failed, "403 Forbidden");
+        state.tick_startup(STARTUP_LINGER_MS);
+        assert!(state.startup.is_some(), "still loading Liked Songs");
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains("Loading your library") && screen.contains("50 of 1204") && screen.contains("403 Forbidden"));
+
+        state.note_startup(StartupLoad::LikedSongs, LoadStatus::Done, "1204 songs");
+        state.tick_startup(STARTUP_LINGER_MS / 2);
+        assert!(state.startup.as_ref().is_some_and(Startup::is_done));
+        state.tick_startup(STARTUP_LINGER_MS / 2);
+        assert!(state.startup.is_none());
+    }
+
+    // ── Library cache ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_cached_lists_keep_the_ttl_they_were_fetched_under() {
+        let fetched_at = chrono::DateTime::parse_from_rfc3339("2026-03-01T12:00:00Z").unwrap().to_utc();
+        let list = Cached { fetched_at, items: vec!["a", "b"] };
+        let later = |secs| fetched_at + chrono::Duration::seconds(secs);
+        assert_eq!(list.remaining(3600, fetched_at), Some(3600));
+        // Written back at quit, it only gets what's left
+        assert_eq!(list.remaining(3600, later(3000)), Some(600));
+        assert_eq!(list.remaining(3600, later(3600)), None);
+        assert_eq!(list.remaining(0, fetched_at), None, "0 turns caching off");
+
+        let config: Config = toml::from_str("[cache_ttl]\nliked_songs = 60").unwrap();
+        assert_eq!(config.cache_ttl.liked_songs, 60);
+        assert_eq!(config.cache_ttl.playlists, CacheTtl::default().playlists);
+    }
+
+    // ── Login ─────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_redirect_uri_ports() {
+        let redirect = RedirectUri::parse("http://127.0.0.1:8989/login").unwrap();
+        assert_eq!((redirect.host.as_str(), redirect.port, redirect.path.as_str()), ("127.0.0.1", 8989, "/login"));
+        assert_eq!(redirect.with_port(8990), "http://127.0.0.1:8990/login");
+        assert_eq!(RedirectUri::parse("http://localhost").unwrap().with_port(8990), "http://localhost:8990");
+        assert!(RedirectUri::parse("https://127.0.0.1:8989/login").is_err(), "nothing to serve https with");
+        assert!(RedirectUri::parse("http://127.0.0.1:login/").is_err());
+
+        assert_eq!(parse_port_range("8990-8992").unwrap().collect::<Vec<_>>(), [8990, 8991, 8992]);
+        assert_eq!(parse_port_range(" 8990 ").unwrap().collect::<Vec<_>>(), [8990]);
+        assert_eq!(parse_port_range("").unwrap().count(), 0);
+        assert!(parse_port_range("8990..8999").is_err());
+    }
+
+    #[test]
+    fn test_one_pkce_verifier_and_state_per_login() {
+        use base64::Engine;
+        use sha2::{Digest, Sha256};
+        let pkce = PkceChallenge::new();
+        let digest = Sha256::digest(pkce.verifier.as_bytes());
+        assert_eq!(pkce.challenge, base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(digest));
+        assert!((43..=128).contains(&pkce.verifier.len()), "RFC 7636 length");
+        assert_ne!(pkce.state, PkceChallenge::new().state);
+
+        let url = pkce.authorize_url("abc", "http://127.0.0.1:8990/login", &["user-read-private", "streaming"]);
+        assert!(url.starts_with("https://accounts.spotify.com/authorize?client_id=abc&response_type=code"));
+        assert!(url.contains("redirect_uri=http%3A%2F%2F127.0.0.1%3A8990%2Flogin"));
+        assert!(url.contains(&format!("code_challenge={}&state={}", pkce.challenge, pkce.state)));
+        assert!(url.ends_with("scope=user-read-private%20streaming"));
+
+        let back = parse_redirect(&format!("GET /login?code=xyz&state={} HTTP/1.1\r\nHost: x\r\n", pkce.state)).unwrap();
+        assert_eq!((back.code.as_str(), back.state.as_str()), ("xyz", pkce.state.as_str()));
+        let refused = parse_redirect("GET /login?error=access_denied&state=s HTTP/1.1").unwrap_err();
+        assert_eq!(refused.to_string(), "Spotify answered access_denied");
+        assert!(parse_redirect("GET /favicon.ico HTTP/1.1").is_err());
+    }
+
+    #[test]
+    fn test_auth_screen_offers_retry_after_a_failed_login() {
+        let screen = |state: &AppState| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            terminal.draw(|f| crate::ui::render(f, state)).unwrap();
+            format!("{:?}", terminal.backend().buffer())
+        };
+        let mut state = AppState {
+            auth_url: Some("https://accounts.spotify.com/authorize".into()),
+            auth_redirect: Some("http://127.0.0.1:8990/login".into()),
+            ..Default::default()
+        };
+        let waiting = screen(&state);
+        assert!(waiting.contains("Listening on http://127.0.0.1:8990/login") && waiting.contains("Esc cancel"));
+
+        state.auth_error = Some("No answer from the browser within 300 seconds".into());
+        let failed = screen(&state);
+        assert!(failed.contains("within 300 seconds") && failed.contains("r retry"));
+        assert_eq!(Config::default().auth_timeout_secs, 300);
+    }
+
+    #[test]
+    fn test_a_refused_token_brings_the_login_screen_back() {
+        assert!(crate::spotify::is_unauthorized(&anyhow::Error::new(rspotify::ClientError::InvalidToken).context("playback")));
+        assert!(!crate::spotify::is_unauthorized(&anyhow::anyhow!("connection reset")));
+
+        let state = AppState {
+            auth_url: Some("https://accounts.spotify.com/authorize".into()),
+            auth_expired: true,
+            ..Default::default()
+        };
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains("Spotify signed you out") && screen.contains("Esc cancel"));
+    }
+
+    // ── Rate limiting ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_rate_limit_keeps_the_latest_retry_after() {
+        use crate::spotify::retry::{parse_retry_after, RateLimit};
+        use std::time::{Duration, Instant};
+        assert_eq!(parse_retry_after(Some("7")), Duration::from_secs(7));
+        assert_eq!(parse_retry_after(Some(" 0 ")), Duration::from_secs(1));
+        assert_eq!(parse_retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT")), Duration::from_secs(1));
+        assert_eq!(parse_retry_after(None), Duration::from_secs(1));
+
+        let now = Instant::now();
+        let mut limit = RateLimit::default();
+        assert_eq!(limit.remaining(now), None);
+        limit.hit(Duration::from_secs(10), now);
+        limit.hit(Duration::from_secs(2), now);
+        assert_eq!(limit.remaining(now), Some(Duration::from_secs(10)));
+        assert_eq!(limit.remaining(now + Duration::from_secs(4)), Some(Duration::from_secs(6)));
+        assert_eq!(limit.remaining(now + Duration::from_secs(10)), None);
+        assert_eq!(limit.hits(), 2);
+    }
+
+    #[test]
+    fn test_network_retries_back_off_with_jitter() {
+        use crate::spotify::retry::backoff;
+        use std::time::Duration;
+        assert_eq!(backoff(0, 0.0), Duration::from_millis(250));
+        assert_eq!(backoff(1, 0.0), Duration::from_millis(500));
+        assert_eq!(backoff(2, 1.0), Duration::from_millis(500));
+        assert_eq!(backoff(10, 0.0), Duration::from_secs(4));
+        assert_eq!(backoff(40, 1.0), Duration::from_secs(2));
+        assert!((0..5).all(|retry| backoff(retry, 0.5) < backoff(retry, 0.0)));
+    }
+
+    // ── Profiles ──────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_profiles_keep_tokens_and_caches_apart() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut cmd = args(&["--profile", "family", "inbox"]);
+        assert_eq!(take_profile_flag(&mut cmd).unwrap().as_deref(), Some("family"));
+        assert_eq!(cmd, args(&["inbox"]));
+        let mut cmd = args(&["inbox", "--profile=work"]);
+        assert_eq!(take_profile_flag(&mut cmd).unwrap().as_deref(), Some("work"));
+        assert_eq!(cmd, args(&["inbox"]));
+        assert!(take_profile_flag(&mut args(&["--profile"])).is_err());
+        assert_eq!(take_profile_flag(&mut args(&["inbox"])).unwrap(), None);
+        assert_eq!(profile_env_suffix("family-2"), "FAMILY_2");
+
+        let cache = Cache::new("redis://127.0.0.1:0").unwrap();
+        assert_eq!(cache.key("vibes:spotify_token"), "vibes:spotify_token");
+        let cache = cache.for_profile(Some("family"));
+        assert_eq!(cache.key("vibes:spotify_token"), "vibes:profile:family:spotify_token");
+        assert_eq!(cache.key("vibes:library:playlists"), "vibes:profile:family:library:playlists");
+
+        // A profile on the default app shares its secret but logs in on its own
+        let mut config = Config { profile: Some("family".into()), ..Config::default() };
+        assert_eq!((secrets::client_secret_entry(&config), secrets::token_entry(&config)), ("client_secret".into(), "spotify_token:family".into()));
+        config.profile_app = true;
+        assert_eq!(secrets::client_secret_entry(&config), "client_secret:family");
+        assert_eq!(secrets::token_entry(&Config::default()), "spotify_token");
+    }
+
+    // ── Playlist snapshots ────────────────────────────────────────────────────
+
+    #[test]
+    fn test_playlist_diff_lists_added_and_removed() {
+        let snap = |ids: &[&str]| PlaylistSnapshot {
+            snapshot_id: ids.join(""),
+            tracks: ids.iter().map(|id| (format!("spotify:track:{id}"), id.to_uppercase())).collect(),
+        };
+        let diff = PlaylistDiff::between(&snap(&["a", "b", "c"]), &snap(&["c", "a", "d"]));
+        assert_eq!(diff.added, vec!["D"]);
+        assert_eq!(diff.removed, vec!["B"]);
+        // Reordering alone isn't a content change
+        assert!(PlaylistDiff::between(&snap(&["a", "b"]), &snap(&["b", "a"])).is_empty());
+    }
+
+    #[test]
+    fn test_snapshots_keep_episodes_and_local_files() {
+        let item = |track: serde_json::Value| -> rspotify::model::PlaylistItem {
+            serde_json::from_value(serde_json::json!({ "added_at": null, "added_by": null, "is_local": false, "track": track })).unwrap()
+        };
+        let track = |id: Option<&str>, name: &str| serde_json::json!({
+            "album": { "artists": [], "available_markets": [], "external_urls": {}, "images": [], "name": "Home Tapes" },
+            "artists": [{ "external_urls": {}, "name": "Me & You" }], "available_markets": [], "disc_number": 1,
+            "duration_ms": 185_400, "explicit": false, "external_ids": {}, "external_urls": {}, "href": null, "id": id,
+            "is_local": id.is_none(), "name": name, "popularity": 0, "preview_url": null, "track_number": 1,
+        });
+        let episode = serde_json::json!({
+            "description": "", "duration_ms": 1_800_000, "id": "512ojhOuo1ktJprKbVcKyQ", "images": [],
+            "name": "Pilot", "show": { "name": "The Show", "publisher": "Someone" },
+        });
+        let items = [item(track(Some("0DiWol3AO6WpXZgp0goxAV"), "One")), item(episode), item(track(None, "Demo 1"))];
+        let snapshot = PlaylistSnapshot::from_items("s1", &items);
+        let uris: Vec<&str> = snapshot.tracks.iter().map(|(uri, _)| uri.as_str()).collect();
+        assert_eq!(uris, vec![
+            "spotify:track:0DiWol3AO6WpXZgp0goxAV",
+            "spotify:episode:512ojhOuo1ktJprKbVcKyQ",
+            "spotify:local:Me+%26+You:Home+Tapes:Demo+1:185",
+        ]);
+        assert_eq!(snapshot.tracks[1].1, "Pilot — The Show");
+        assert!(crate::cache::snapshots::is_local(uris[2]) && !crate::cache::snapshots::is_local(uris[0]));
+    }
+
+    #[test]
+    fn test_gg_sequence() {
+        let mut keymap = Keymap::default();
+        let mut press = |c: char| map_key_to_action(KeyEvent::from(KeyCode::Char(c)), false, &mut keymap);
+        assert_eq!(press('g'), None);
+        assert_eq!(press('g'), Some(UserAction::JumpTop));
+        // An unrelated key in between cancels the sequence
+        assert_eq!(press('g'), None);
+        assert_eq!(press('j'), Some(UserAction::NavigateDown));
+        assert_eq!(press('g'), None);
+        assert_eq!(press('G'), Some(UserAction::JumpBottom));
+    }
+
+    #[test]
+    fn test_configured_chords() {
+        let chords = BTreeMap::from([
+            ("go_queue".to_string(), "gq".to_string()),
+            ("go_stats".to_string(), "zs".to_string()),
+            ("go_vibes".to_string(), "toolong".to_string()),
+        ]);
+        let mut keymap = Keymap::from_config(&BTreeMap::new(), &chords);
+        let mut press = |code: KeyCode| map_key_to_action(KeyEvent::from(code), false, &mut keymap);
+        assert_eq!(press(KeyCode::Char('g')), None);
+        assert_eq!(press(KeyCode::Char('q')), Some(UserAction::GoTo(ActiveScreen::Queue)));
+        // Built-ins still there alongside
+        assert_eq!(press(KeyCode::Char('g')), None);
+        assert_eq!(press(KeyCode::Char('p')), Some(UserAction::GoTo(ActiveScreen::Playlists)));
+        assert_eq!(press(KeyCode::Char('g')), None);
+        assert_eq!(press(KeyCode::Char('y')), Some(UserAction::CopyTrackInfo));
+        assert_eq!(press(KeyCode::Char('y')), Some(UserAction::CopyTrackLink));
+        assert_eq!(press(KeyCode::Char('Y')), Some(UserAction::CopyTrackUri));
+        // A new prefix key waits too, and Esc only cancels it
+        assert_eq!(press(KeyCode::Char('z')), None);
+        assert_eq!(press(KeyCode::Esc), None);
+        assert_eq!(press(KeyCode::Esc), Some(UserAction::Back));
+        assert_eq!(parse_chord("toolong"), None);
+    }
+
+    #[test]
+    fn test_screen_order_from_config() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(screen_order(&[]), ActiveScreen::ALL.to_vec());
+        assert_eq!(
+            screen_order(&names(&["playlists", "search", "nope", "playlists", "stats"])),
+            vec![ActiveScreen::Playlists, ActiveScreen::Search, ActiveScreen::Stats]
+        );
+        assert_eq!(screen_order(&names(&["nope"])), ActiveScreen::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_browse_leaves_the_sidebar_when_spotify_refuses_it() {
+        use crate::spotify::capabilities::{Capabilities, Capability};
+        let mut state = AppState { active_screen: ActiveScreen::Browse, ..Default::default() };
+        state.browse.categories = vec![crate::app::state::BrowseState::featured_category()];
+        state.set_capabilities(Capabilities { browse: Capability::Unknown, featured: Capability::Unavailable, ..Default::default() });
+        assert!(state.screens.contains(&ActiveScreen::Browse), "unknown still gets a try");
+        assert!(state.browse.categories.is_empty());
+
+        state.set_capabilities(Capabilities { browse: Capability::Unavailable, ..Default::default() });
+        assert!(!state.screens.contains(&ActiveScreen::Browse));
+        assert_eq!(state.active_screen, ActiveScreen::Search);
+    }
+
+    #[test]
+    fn test_row_jump_digits() {
+        let mut state = AppState::default();
+        state.browse.categories = vec![crate::app::state::BrowseState::featured_category(); 30];
+        state.navigate_to(ActiveScreen::Browse);
+        assert_eq!(state.push_row_digit(2), None);
+        assert_eq!(state.tick_row_jump(100), None);
+        assert_eq!(state.push_row_digit(3), None);
+        assert_eq!(state.row_jump.as_ref().and_then(|j| j.row()), Some(23));
+        // Never confirmed: dropped without switching screens
+        assert_eq!(state.tick_row_jump(ROW_JUMP_TIMEOUT_MS), None);
+        assert!(state.row_jump.is_none());
+
+        assert_eq!(state.push_row_digit(4), None);
+        assert_eq!(state.tick_row_jump(ROW_JUMP_TIMEOUT_MS), Some(4), "a lone digit still switches screens");
+
+        // No row starts with these on a three-row list: no waiting
+        state.browse.categories.truncate(3);
+        assert_eq!(state.push_row_digit(4), Some(4));
+        assert!(state.row_jump.is_none());
+        assert_eq!(state.push_row_digit(0), None);
+        assert!(state.row_jump.is_none());
+        assert_eq!(state.push_row_digit(3), None, "row 3 is there");
+    }
+
+    #[test]
+    fn test_percent_seek_keys() {
+        let mut keymap = Keymap::default();
+        let mut press = |c: char| map_key_to_action(KeyEvent::from(KeyCode::Char(c)), false, &mut keymap);
+        assert_eq!(press('%'), None);
+        assert_eq!(press('5'), Some(UserAction::SeekPercent(50)));
+        // Without the prefix digits start a row number (or a screen switch)
+        assert_eq!(press('5'), Some(UserAction::RowDigit(5)));
+        let alt = KeyEvent::new(KeyCode::Char('0'), KeyModifiers::ALT);
+        assert_eq!(map_key_to_action(alt, false, &mut keymap), Some(UserAction::SeekPercent(0)));
+    }
+
+    #[test]
+    fn test_optimistic_removal_rolls_back_in_place() {
+        let mut tracks = vec!["a", "b", "a", "c", "a"];
+        let removed = take_matching(&mut tracks, |t| *t == "a");
+        assert_eq!(tracks, vec!["b", "c"]);
+        assert_eq!(removed.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 2, 4]);
+        restore_removed(&mut tracks, removed);
+        assert_eq!(tracks, vec!["a", "b", "a", "c", "a"]);
+    }
+
+    // ── Collation ─────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_collation_ignores_case_accents_and_articles() {
+        let en = Collator::for_locale("en_US.UTF-8");
+        assert_eq!(en.key("The Beatles"), "beatles");
+        assert_eq!(en.key("  Beyoncé "), "beyonce");
+        assert_eq!(en.key("The The"), "the");
+        assert_eq!(fold("Straße · Ærø"), "strasse · aero");
+
+        let mut names = vec!["The Beatles", "björk", "ABBA", "Air", "Bon Iver"];
+        names.sort_by(|a, b| en.cmp(a, b));
+        assert_eq!(names, vec!["ABBA", "Air", "The Beatles", "björk", "Bon Iver"]);
+        assert!(fold("Sigur Rós").contains(&fold("ROS")));
+
+        // Locale articles on top of the English ones
+        assert_eq!(en.key("Les Négresses Vertes"), "les negresses vertes");
+        let fr = Collator::for_locale("fr_FR.UTF-8");
+        assert_eq!(fr.key("Les Négresses Vertes"), "negresses vertes");
+        assert_eq!(fr.key("L’Impératrice"), "imperatrice");
+        assert_eq!(fr.key("A Tribe Called Quest"), "tribe called quest");
+    }
+
+    #[test]
+    fn test_list_filter_matches_fuzzily_and_steps_over_hidden_rows() {
+        assert!(fuzzy_match("drk sde", "Dark Side of the Moon Pink Floyd"));
+        assert!(fuzzy_match("sigur ros", "Hoppípolla Sigur Rós Takk..."));
+        assert!(fuzzy_match("  ", "anything"));
+        assert!(!fuzzy_match("floyd dark", "Dark Side Floy"));
+        assert!(!fuzzy_match("xyz", "Dark Side of the Moon"));
+
+        let rows = [2, 5, 9, 14];
+        assert_eq!(step_filtered(&rows, 5, 1, false), Some(9));
+        assert_eq!(step_filtered(&rows, 5, 1, true), Some(2));
+        // From a hidden row, one step reaches the neighbour on that side
+        assert_eq!(step_filtered(&rows, 6, 1, false), Some(9));
+        assert_eq!(step_filtered(&rows, 6, 1, true), Some(5));
+        assert_eq!(step_filtered(&rows, 0, usize::MAX, false), Some(14));
+        assert_eq!(step_filtered(&rows, 14, usize::MAX, true), Some(2));
+        assert_eq!(step_filtered(&[], 3, 1, false), None);
+    }
+
+    #[test]
+    fn test_sort_cycles_columns_and_keeps_the_selection() {
+        let mut order = SortOrder::default();
+        let mut seen = Vec::new();
+        loop {
+            order = order.next();
+            match order.label() {
+                Some(label) => seen.push(label),
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), 10);
+        assert_eq!(seen[..3], ["title ↑", "title ↓", "artist ↑"]);
+        assert_eq!(seen.last().map(String::as_str), Some("date added ↓"));
+        assert_eq!(SortOrder { column: Some(SortColumn::Album), descending: false }.next().label().as_deref(), Some("album ↓"));
+
+        let mut rows = vec![("b", 1), ("a", 2), ("c", 3), ("a", 4)];
+        let mut selected = 2; // ("c", 3)
+        sort_rows(&mut rows, &mut selected, false, |r| r.0);
+        // Ties keep their order
+        assert_eq!(rows, vec![("a", 2), ("a", 4), ("b", 1), ("c", 3)]);
+        assert_eq!(rows[selected], ("c", 3));
+        sort_rows(&mut rows, &mut selected, true, |r| r.1);
+        assert_eq!(rows.iter().map(|r| r.1).collect::<Vec<_>>(), vec![4, 3, 2, 1]);
+        assert_eq!(rows[selected], ("c", 3));
+    }
+
+    #[test]
+    fn test_album_results_sort_by_release_date_under_decade_headers() {
+        let album = |name: &str, date: Option<&str>| -> rspotify::model::SimplifiedAlbum {
+            serde_json::from_value(serde_json::json!({
+                "artists": [], "available_markets": [], "external_urls": {}, "images": [],
+                "name": name, "release_date": date,
+            }))
+            .unwrap()
+        };
+        let mut search = SearchState {
+            albums: vec![
+                album("Nevermind", Some("1991-09-24")),
+                album("Bootleg", None),
+                album("In Rainbows", Some("2007-10-10")),
+                album("OK Computer", Some("1997")),
+            ],
+            selected_album: 2, // In Rainbows, in best-match order
+            ..Default::default()
+        };
+        assert_eq!(search.album_order(), vec![0, 1, 2, 3]);
+        assert!(search.album_rows().iter().all(|r| matches!(r, AlbumRow::Album(_))));
+
+        search.reorder_albums(|s| s.album_sort = AlbumSort::Oldest);
+        assert_eq!(search.album_rows(), vec![
+            AlbumRow::Decade(Some(1990)), AlbumRow::Album(0), AlbumRow::Album(3),
+            AlbumRow::Decade(Some(2000)), AlbumRow::Album(2),
+            AlbumRow::Decade(None), AlbumRow::Album(1),
+        ]);
+        assert_eq!(search.selected_album().map(|a| a.name.as_str()), Some("In Rainbows"));
+        // Newest first still leaves the undated one last
+        search.reorder_albums(|s| s.album_sort = s.album_sort.next());
+        assert_eq!(search.album_order(), vec![2, 3, 0, 1]);
+        assert_eq!(search.selected_album, 0);
+
+        search.reorder_albums(|s| s.decade = parse_decade("90s"));
+        assert_eq!(search.album_order(), vec![3, 0]);
+        // In Rainbows is filtered out, so the selection falls back to the top
+        assert_eq!(search.selected_album, 0);
+
+        assert_eq!(parse_decade("1990s"), Some(1990));
+        assert_eq!(parse_decade("'80s"), Some(1980));
+        assert_eq!(parse_decade("10s"), Some(2010));
+        assert_eq!(parse_decade("1997"), Some(1990));
+        assert_eq!(parse_decade("nineties"), None);
+    }
+
+    // ── Library index ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_library_index_finds_liked_songs_on_saved_albums() {
+        let album = |id: &str, uris: &[&str]| IndexedAlbum {
+            id: id.into(),
+            name: id.into(),
+            artist: "x".into(),
+            track_uris: uris.iter().map(|u| u.to_string()).collect(),
+        };
+        let index = LibraryIndex::new(
+            ["t1", "t2", "t5"].map(String::from),
+            vec![album("one", &["t1", "t3"]), album("two", &["t2", "t5", "t6"]), album("three", &["t9"])],
+        );
+        let overlaps = index.overlaps();
+        assert_eq!(overlaps.len(), 2); // "three" shares nothing
+        assert_eq!(overlaps[0].album_id, "two");
+        assert_eq!(overlaps[0].liked, vec!["t2", "t5"]);
+        assert_eq!(overlaps[0].missing, vec!["t6"]);
+        assert_eq!(index.on_saved_albums().len(), 3);
+    }
+
+    #[test]
+    fn test_feature_index_only_fetches_each_track_once() {
+        let features = TrackFeatures {
+            energy: 0.8,
+            danceability: 0.7,
+            valence: 0.6,
+            acousticness: 0.1,
+            instrumentalness: 0.0,
+            tempo: 124.0,
+            loudness: -6.5,
+        };
+        let dir = std::env::temp_dir().join(format!("vibes-features-{}", std::process::id()));
+        let path = dir.join("features.jsonl");
+        let mut index = FeatureIndex::load(&path).unwrap();
+        assert!(index.is_empty());
+        index
+            .append(&path, vec![
+                IndexEntry { uri: "t1".into(), features: Some(features) },
+                IndexEntry { uri: "t2".into(), features: None },
+            ])
+            .unwrap();
+
+        let library = LibraryIndex::new(["t1", "t2", "t3"].map(String::from), vec![IndexedAlbum {
+            id: "a".into(),
+            name: "a".into(),
+            artist: "x".into(),
+            track_uris: vec!["t3".into(), "t4".into()],
+        }]);
+        let reloaded = FeatureIndex::load(&path).unwrap();
+        assert_eq!(reloaded, index);
+        assert_eq!(reloaded.get("t1"), Some(&features));
+        assert_eq!(reloaded.get("t2"), None);
+        assert_eq!(reloaded.len(), 1);
+        // Tracks Spotify had nothing for aren't asked about again
+        let mut missing = reloaded.missing(library.track_uris());
+        missing.sort();
+        assert_eq!(missing, vec!["t3", "t4"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_mood_filter_expressions() {
+        let track = |energy: f32, tempo: f32, loudness: f32| TrackFeatures {
+            energy,
+            danceability: 0.5,
+            valence: 0.5,
+            acousticness: 0.2,
+            instrumentalness: 0.0,
+            tempo,
+            loudness,
+        };
+        let filter = MoodFilter::parse("energy > 0.7 and tempo 120–130").unwrap();
+        assert!(filter.matches(&track(0.8, 125.0, -5.0)));
+        assert!(filter.matches(&track(0.8, 130.0, -5.0))); // ranges include both ends
+        assert!(!filter.matches(&track(0.7, 125.0, -5.0)));
+        assert!(!filter.matches(&track(0.9, 131.0, -5.0)));
+        assert_eq!(filter.to_string(), "energy > 0.7 and tempo 120–130");
+
+        // Other spellings of the same thing
+        for expr in ["Energy>0.7, bpm 120-130", "energy > .7 AND tempo 120..130", "energy >0.7 and tempo 130 to 120"] {
+            assert_eq!(MoodFilter::parse(expr).unwrap(), filter, "{expr}");
+        }
+        let quiet = MoodFilter::parse("loudness -20--8 and energy <= 0.3").unwrap();
+        assert!(quiet.matches(&track(0.3, 90.0, -12.0)));
+        assert!(!quiet.matches(&track(0.3, 90.0, -6.0)));
+
+        for bad in ["", "and", "vibe > 0.5", "energy 0.5", "tempo > fast"] {
+            assert!(MoodFilter::parse(bad).is_err(), "{bad}");
+        }
+    }
+
+    // ── Sidebar panel ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_sidebar_settings_from_config() {
+        let config: Config =
+            toml::from_str("sidebar_panel = \"lyrics\"\nvisualizer = \"off\"\nquotes = [\"hi\"]").unwrap();
+        assert_eq!(config.sidebar_panel, SidebarPanel::Lyrics);
+        assert_eq!(config.visualizer, Visualizer::Off);
+        assert_eq!(config.quotes, vec!["hi"]);
+        assert_eq!(SidebarPanel::Off.next(), SidebarPanel::Quotes);
+    }
+
+    #[test]
+    fn test_parse_lrc_and_current_line() {
+        let lines = parse_lrc("[ar:Someone]\n[00:12.50]Second\n[00:01.00][00:30.00]Hook\n");
+        assert_eq!(
+            lines,
+            vec![(1_000, "Hook".to_string()), (12_500, "Second".to_string()), (30_000, "Hook".to_string())]
+        );
+        assert_eq!(current_line(&lines, 500), None);
+        assert_eq!(current_line(&lines, 12_500), Some(1));
+        assert_eq!(current_line(&lines, 99_000), Some(2));
+
+        // Stamps that overflow are not lyrics
+        assert!(parse_lrc("[99999:00.00]Late\n[00:-1.00]Early\n[00:1e12]Huge\n").is_empty());
+    }
+
+    #[test]
+    fn test_prompt_captures_typing() {
+        let mut state = AppState::default();
+        assert!(!state.is_typing());
+        state.prompt = Some(Prompt::new(PromptKind::NewPlaylist));
+        assert!(state.is_typing());
+        // `q` must type into the prompt instead of quitting
+        let mut keymap = Keymap::default();
+        let key = KeyEvent::from(KeyCode::Char('q'));
+        assert_eq!(map_key_to_action(key, state.is_typing(), &mut keymap), Some(UserAction::SearchInput('q')));
+    }
+
+    #[test]
+    fn test_quote_sources() {
+        // ZenQuotes, Quotable (wrapped in `results`) and a bare object
+        let zen = r#"[{"q":"Where words fail, music speaks.","a":"Hans Christian Andersen","h":"<b>…</b>"},{"a":"nobody"}]"#;
+        assert_eq!(quotes::parse_api(zen).unwrap(), vec!["\"Where words fail, music speaks.\"\n  – Hans Christian Andersen"]);
+        let quotable = r#"{"count":1,"results":[{"content":"Music is life itself.","author":"Louis Armstrong"}]}"#;
+        assert_eq!(quotes::parse_api(quotable).unwrap(), vec!["\"Music is life itself.\"\n  – Louis Armstrong"]);
+        assert_eq!(quotes::parse_api(r#"{"quote":"Turn it up."}"#).unwrap(), vec!["\"Turn it up.\""]);
+        assert!(quotes::parse_api("<html>").is_err());
+
+        let date = |m, d| chrono::NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        assert_eq!(quotes::history_facts(date(7, 13)), vec!["On this day in 1985: Live Aid is staged in London and Philadelphia."]);
+        let march = quotes::history_facts(date(3, 2));
+        assert!(march.len() >= 2 && march.iter().all(|f| f.starts_with("Mar ")), "{march:?}");
+        for month in 1..=12 {
+            assert!(!quotes::history_facts(date(month, 28)).is_empty(), "no facts for month {month}");
+        }
+
+        let cache = QuoteCache { date: date(10, 16), url: "https://q.example/api".into(), quotes: vec!["\"Hi\"".into()] };
+        assert!(cache.is_fresh("https://q.example/api", date(10, 16)));
+        assert!(!cache.is_fresh("https://q.example/api", date(10, 17)));
+        assert!(!cache.is_fresh("https://other.example/api", date(10, 16)));
+        let dir = std::env::temp_dir().join(format!("vibes-quotes-{}", std::process::id()));
+        let path = dir.join("quotes.json");
+        assert_eq!(quotes::load_cache(&path), None);
+        quotes::save_cache(&path, &cache).unwrap();
+        assert_eq!(quotes::load_cache(&path), Some(cache));
+        let file = dir.join("quotes.txt");
+        std::fs::write(&file, "# mine\nTurn it up.\n\n  Loud is a feeling.  \n").unwrap();
+        assert_eq!(quotes::load_file(&file).unwrap(), vec!["Turn it up.", "Loud is a feeling."]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    // ── Text & viewport properties ────────────────────────────────────────────
+
+    proptest! {
+        #[test]
+        fn prop_truncate_fits_and_keeps_a_prefix(s in "\\PC*", max in 0usize..40) {
+            let out = truncate(&s, max);
+            prop_assert!(out.width() <= max);
+            let kept = out.strip_suffix('…').unwrap_or(&out);
+            prop_assert!(s.starts_with(kept));
+        }
+
+        #[test]
+        fn prop_marquee_fits_and_loops(s in "\\PC{1,60}", offset in any::<usize>(), max in 0usize..40) {
+            let out = marquee(&s, offset, max);
+            prop_assert!(out.width() <= max || s.width() <= max);
+            let period = s.chars().count() + 3;
+            prop_assert_eq!(out, marquee(&s, offset % period + period, max));
+        }
+
+        #[test]
+        fn prop_viewport_keeps_selection_visible(len in 0usize..500, selected in 0usize..600, rows in 0usize..60) {
+            let offset = viewport_offset(selected, len, rows);
+            prop_assert!(offset + rows.min(len) <= len);
+            if rows > 0 && len > 0 {
+                let sel = selected.min(len - 1);
+                prop_assert!(offset <= sel && sel < offset + rows);
+            }
+        }
+
+        #[test]
+        fn prop_render_never_panics(w in 0u16..160, h in 0u16..60, screen in 0usize..ActiveScreen::ALL.len(), sel in 0usize..100) {
+            let mut state = AppState {
+                is_authenticated: true,
+                active_screen: ActiveScreen::ALL[screen].clone(),
+                ..Default::default()
+            };
+            state.search.selected_track = sel;
+            state.current_track.name = "日本語のタイトル 🎵 with a fairly long tail".into();
+            let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+            terminal.draw(|f| crate::ui::render(f, &state)).unwrap();
+        }
+    }
 }
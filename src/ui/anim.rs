@@ -0,0 +1,91 @@
+use ratatui::style::Color;
+
+use crate::artwork::blend;
+
+/// Ticks the player bar takes to fade/slide in a new track
+pub const TRACK_CHANGE_TICKS: u16 = 6;
+/// Ticks the sidebar highlight takes to settle after switching screens
+pub const SCREEN_SWITCH_TICKS: u16 = 4;
+
+/// A short transition counted in UI ticks. Components only read `progress()`,
+/// so anything drawn with it snaps straight to its end state when idle.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Transition {
+    elapsed: u16,
+    duration: u16,
+}
+
+impl Transition {
+    pub fn start(duration: u16) -> Self {
+        Transition { elapsed: 0, duration }
+    }
+
+    pub fn tick(&mut self) {
+        if self.elapsed < self.duration {
+            self.elapsed += 1;
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.elapsed < self.duration
+    }
+
+    /// Eased 0.0 → 1.0; finished (or never started) transitions are at 1.0
+    pub fn progress(&self) -> f32 {
+        if !self.is_running() {
+            return 1.0;
+        }
+        ease_out(self.elapsed as f32 / self.duration as f32)
+    }
+}
+
+/// Cubic ease-out: quick start, gentle landing
+pub fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3)
+}
+
+/// `t` of the way from `from` to `to`. Named/indexed colors can't be mixed,
+/// so those switch at the halfway point.
+pub fn fade(from: Color, to: Color, t: f32) -> Color {
+    match (from, to) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            let (r, g, b) = blend((r1, g1, b1), (r2, g2, b2), t);
+            Color::Rgb(r, g, b)
+        }
+        _ if t < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// Columns still left to travel when sliding in over `distance` columns
+pub fn slide(distance: u16, t: f32) -> u16 {
+    ((1.0 - t.clamp(0.0, 1.0)) * distance as f32).round() as u16
+}
+
+/// The UI's running transitions, ticked together from the main loop
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Animations {
+    pub track_change: Transition,
+    pub screen_switch: Transition,
+    /// `reduce_motion` in config: never start a transition
+    pub reduce_motion: bool,
+}
+
+impl Animations {
+    pub fn tick(&mut self) {
+        self.track_change.tick();
+        self.screen_switch.tick();
+    }
+
+    pub fn track_changed(&mut self) {
+        if !self.reduce_motion {
+            self.track_change = Transition::start(TRACK_CHANGE_TICKS);
+        }
+    }
+
+    pub fn screen_switched(&mut self) {
+        if !self.reduce_motion {
+            self.screen_switch = Transition::start(SCREEN_SWITCH_TICKS);
+        }
+    }
+}
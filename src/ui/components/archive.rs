@@ -0,0 +1,58 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `:archived` popup — playlists archived with X, newest first
+pub fn render_archived(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref archived) = state.archived else { return };
+
+    let width = area.width.saturating_sub(4).min(80);
+    let height = (archived.entries.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = archived
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let is_sel = i == archived.selected;
+            let mut spans = vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(entry.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+            ];
+            if !entry.owned {
+                spans.push(Span::styled(format!(" — {}", entry.owner), theme.dim_style()));
+            }
+            spans.push(Span::styled(format!("  {}", entry.snapshot.tracks.len()), theme.muted_style()));
+            spans.push(Span::styled(
+                format!(" · {}", entry.archived_at.with_timezone(&chrono::Local).format("%Y-%m-%d")),
+                theme.muted_style(),
+            ));
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" ▣ Archived playlists ({}) ", archived.entries.len()), theme.title_style()))
+            .title_bottom(Span::styled(" Enter restore · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(archived.selected, archived.entries.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
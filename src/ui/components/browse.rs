@@ -0,0 +1,132 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::app::state::{AppState, BrowseState};
+use super::super::{text::viewport_offset, theme::Theme};
+
+pub fn render_browse(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    render_categories(f, chunks[0], state, theme);
+    render_category_playlists(f, chunks[1], state, theme);
+}
+
+fn render_categories(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let browse = &state.browse;
+    if browse.categories.is_empty() {
+        let msg = if browse.is_loading { "  ⠋ Loading categories..." } else { "  No categories" };
+        let para = Paragraph::new(Line::from(Span::styled(msg, theme.dim_style())))
+            .block(make_block(theme, " 🧭 Browse ", true));
+        f.render_widget(para, area);
+        return;
+    }
+
+    let selected = browse.selected_category;
+    let items: Vec<ListItem> = browse
+        .categories
+        .iter()
+        .enumerate()
+        .map(|(i, category)| {
+            let is_sel = i == selected;
+            let line = Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Span::styled(category.name.clone(), if is_sel {
+                    theme.selected_style()
+                } else if category.id == BrowseState::FEATURED {
+                    theme.hot_pink_style()
+                } else {
+                    theme.normal_style()
+                }),
+            ]);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(make_block(
+        theme,
+        &format!(" 🧭 Browse ({}) ", browse.categories.len()),
+        !browse.viewing_playlists,
+    ));
+    let offset = viewport_offset(selected, browse.categories.len(), area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
+}
+
+fn render_category_playlists(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let browse = &state.browse;
+    let open = browse
+        .categories
+        .iter()
+        .find(|c| browse.playlists_of.as_deref() == Some(c.id.as_str()))
+        .filter(|_| browse.viewing_playlists);
+    let title = match open {
+        // Spotify's own header, e.g. "Monday morning picks"
+        Some(c) if c.id == BrowseState::FEATURED => {
+            let header = browse.featured_message.as_deref().unwrap_or(&c.name);
+            format!(" ★ {header} ({}) ", browse.playlists.len())
+        }
+        Some(c) => format!(" {} ({}) ", c.name, browse.playlists.len()),
+        None => " Playlists ".to_string(),
+    };
+
+    let Some(_) = open.filter(|_| !browse.playlists.is_empty()) else {
+        let msg = if browse.is_loading {
+            "  ⠋ Loading playlists..."
+        } else if open.is_some() {
+            "  No playlists in this category"
+        } else {
+            "  Press Enter or → to open a category"
+        };
+        let para = Paragraph::new(Line::from(Span::styled(msg, theme.dim_style())))
+            .block(make_block(theme, &title, browse.viewing_playlists));
+        f.render_widget(para, area);
+        return;
+    };
+
+    let selected = browse.selected_playlist;
+    let items: Vec<ListItem> = browse
+        .playlists
+        .iter()
+        .enumerate()
+        .map(|(i, pl)| {
+            let is_sel = i == selected;
+            let mut spans = vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Span::styled(pl.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!("  {}", pl.tracks.total), theme.muted_style()),
+            ];
+            if let Some(ref owner) = pl.owner.display_name {
+                spans.push(Span::styled(format!(" · {owner}"), theme.dim_style()));
+            }
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(make_block(theme, &title, true));
+    let offset = viewport_offset(selected, browse.playlists.len(), area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
+}
+
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
+    Block::default()
+        .title(Span::styled(title.to_string(), theme.title_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
+}
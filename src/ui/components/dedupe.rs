@@ -0,0 +1,52 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `:dedupe` popup — saved albums that share tracks with Liked Songs
+pub fn render_dedupe(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref dedupe) = state.dedupe else { return };
+
+    let width = area.width.saturating_sub(4).min(80);
+    let height = (dedupe.overlaps.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = dedupe
+        .overlaps
+        .iter()
+        .enumerate()
+        .map(|(i, album)| {
+            let is_sel = i == dedupe.selected;
+            let line = Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(album.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!(" — {}", album.artist), theme.dim_style()),
+                Span::styled(format!("  ♥ {} liked", album.liked.len()), theme.hot_pink_style()),
+                Span::styled(format!(" · {} not", album.missing.len()), theme.muted_style()),
+            ]);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(" ◎ Liked songs on saved albums ", theme.title_style()))
+            .title_bottom(Span::styled(" Enter like whole album · x unlike duplicates · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(dedupe.selected, dedupe.overlaps.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
@@ -0,0 +1,65 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `gd` popup — Spotify Connect devices, the one playing marked
+pub fn render_devices(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref view) = state.devices else { return };
+
+    let width = area.width.saturating_sub(4).min(60);
+    let height = (view.devices.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = view
+        .devices
+        .iter()
+        .enumerate()
+        .map(|(i, device)| {
+            let is_sel = i == view.selected;
+            let name_style = match () {
+                _ if is_sel => theme.selected_style(),
+                _ if device.is_restricted => theme.muted_style(),
+                _ => theme.normal_style(),
+            };
+            let mut spans = vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(device.name.clone(), name_style),
+                Span::styled(format!("  {:?}", device._type), theme.dim_style()),
+            ];
+            if let Some(volume) = device.volume_percent {
+                spans.push(Span::styled(format!(" · {volume}%"), theme.muted_style()));
+            }
+            if device.is_active {
+                spans.push(Span::styled(" · playing here", theme.playing_style()));
+            }
+            if device.is_restricted {
+                spans.push(Span::styled(" · can't be controlled", theme.muted_style()));
+            }
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" ◉ Devices ({}) ", view.devices.len()), theme.title_style()))
+            .title_bottom(Span::styled(" Enter play there · Space move as is · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(view.selected, view.devices.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
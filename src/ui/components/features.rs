@@ -0,0 +1,113 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    text::Span,
+    widgets::{Block, BorderType, Borders, Cell, Clear, Row, Table},
+    Frame,
+};
+use strum::IntoEnumIterator;
+
+use crate::app::state::AppState;
+use crate::features::Feature;
+use crate::spotify::capabilities::Capability;
+use super::super::{centered, theme::Theme};
+
+/// `:features` popup — every optional subsystem and whether it's running,
+/// followed by what the startup probe found out about the Spotify API, how
+/// much of the library has audio features indexed and, when they're serving,
+/// where to reach the web remote, the MPD server and the control socket
+pub fn render_features(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let width = area.width.saturating_sub(4).min(64);
+    let api_rows = state.capabilities.rows();
+    let index_rows = if state.feature_index.is_empty() { 0 } else { 2 };
+    let remote_rows = if state.remote.is_some() { 3 } else { 0 };
+    let mpd_rows = if state.mpd.is_some() { 2 } else { 0 };
+    let socket_rows = if state.control_socket.is_some() { 2 } else { 0 };
+    let trace_rows = if state.trace.is_some() { 2 } else { 0 };
+    let extra_rows = index_rows + remote_rows + mpd_rows + socket_rows + trace_rows;
+    let height = (Feature::iter().count() as u16 + api_rows.len() as u16 + extra_rows + 4).min(area.height);
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let mut rows: Vec<Row> = Feature::iter()
+        .map(|feature| {
+            let (status, style) = if !feature.compiled() {
+                ("not built", theme.muted_style())
+            } else if state.features.enabled(feature) {
+                ("on", theme.playing_style())
+            } else {
+                ("off", theme.error_style())
+            };
+            Row::new(vec![
+                Cell::from(feature.name()).style(theme.accent_style()),
+                Cell::from(feature.description()).style(theme.dim_style()),
+                Cell::from(status).style(style),
+            ])
+        })
+        .collect();
+    rows.push(Row::new(vec![Cell::from("Spotify API").style(theme.header_style())]));
+    rows.extend(api_rows.into_iter().map(|(name, what, capability)| {
+        let style = match capability {
+            Capability::Available => theme.playing_style(),
+            Capability::Unavailable => theme.error_style(),
+            Capability::Unknown => theme.muted_style(),
+        };
+        Row::new(vec![
+            Cell::from(name).style(theme.accent_style()),
+            Cell::from(what).style(theme.dim_style()),
+            Cell::from(capability.label()).style(style),
+        ])
+    }));
+    if !state.feature_index.is_empty() {
+        rows.push(Row::new(vec![Cell::from("Library index").style(theme.header_style())]));
+        rows.push(Row::new(vec![
+            Cell::from("audio features").style(theme.accent_style()),
+            Cell::from(format!("{} tracks", state.feature_index.len())).style(theme.dim_style()),
+        ]));
+    }
+    if let Some(ref pairing) = state.remote {
+        rows.push(Row::new(vec![Cell::from("Web remote").style(theme.header_style())]));
+        rows.push(Row::new(vec![
+            Cell::from("address").style(theme.accent_style()),
+            Cell::from(pairing.url.clone()).style(theme.hot_pink_style()),
+        ]));
+        rows.push(Row::new(vec![
+            Cell::from("pairing code").style(theme.accent_style()),
+            Cell::from(pairing.code.clone()).style(theme.title_style()),
+        ]));
+    }
+    if let Some(addr) = state.mpd {
+        rows.push(Row::new(vec![Cell::from("MPD server").style(theme.header_style())]));
+        rows.push(Row::new(vec![
+            Cell::from("address").style(theme.accent_style()),
+            Cell::from(addr.to_string()).style(theme.hot_pink_style()),
+        ]));
+    }
+    if let Some(ref path) = state.control_socket {
+        rows.push(Row::new(vec![Cell::from("Control socket").style(theme.header_style())]));
+        rows.push(Row::new(vec![
+            Cell::from("path").style(theme.accent_style()),
+            Cell::from(path.display().to_string()).style(theme.hot_pink_style()),
+        ]));
+    }
+    if let Some(ref path) = state.trace {
+        rows.push(Row::new(vec![Cell::from("Trace").style(theme.header_style())]));
+        rows.push(Row::new(vec![
+            Cell::from("writing to").style(theme.accent_style()),
+            Cell::from(path.display().to_string()).style(theme.hot_pink_style()),
+            Cell::from(":trace off").style(theme.muted_style()),
+        ]));
+    }
+
+    let table = Table::new(rows, [Constraint::Length(16), Constraint::Min(10), Constraint::Length(11)])
+        .header(Row::new(vec!["Feature", "What", "Status"]).style(theme.header_style()))
+        .block(
+            Block::default()
+                .title(Span::styled(" ⚑ Features ", theme.title_style()))
+                .title_bottom(Span::styled(" [features] in config.toml · Esc to close ", theme.muted_style()))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(theme.border_style(true))
+                .style(theme.normal_style().bg(theme.surface)),
+        );
+    f.render_widget(table, popup);
+}
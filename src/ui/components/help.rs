@@ -5,20 +5,20 @@ use ratatui::{
     Frame,
 };
 
-use crate::app::state::AppState;
-use super::super::theme::*;
+use crate::app::state::{ActiveScreen, AppState};
+use super::super::{centered_rect, theme::Theme};
 
-pub fn render_help(f: &mut Frame, area: Rect, _state: &AppState) {
+pub fn render_help(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     // Center the popup
     let popup_area = centered_rect(60, 80, area);
     f.render_widget(Clear, popup_area);
 
     let block = Block::default()
-        .title(Span::styled(" ❓ Keybindings ", title_style()))
+        .title(Span::styled(" ❓ Keybindings ", theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Double)
-        .border_style(border_style(true))
-        .style(normal_style());
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style());
 
     let inner = block.inner(popup_area);
     f.render_widget(block, popup_area);
@@ -30,72 +30,90 @@ pub fn render_help(f: &mut Frame, area: Rect, _state: &AppState) {
         .split(inner);
 
     let left = vec![
-        Line::from(Span::styled("  Navigation", hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
+        Line::from(Span::styled("  Navigation", theme.hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
         Line::from(Span::raw("")),
-        key_line("↑ / k", "Move up"),
-        key_line("↓ / j", "Move down"),
-        key_line("Enter", "Select / Play"),
-        key_line("Esc / b", "Back"),
-        key_line("1-5", "Switch screen"),
+        key_line(theme, "↑ / k", "Move up"),
+        key_line(theme, "↓ / j", "Move down"),
+        key_line(theme, "/", "Filter the list (Esc clears)"),
+        key_line(theme, "S", "Sort Liked Songs / playlist tracks / albums"),
+        key_line(theme, "U", "Reload playlists & Liked Songs"),
+        key_line(theme, "PgUp/PgDn", "Page up / down"),
+        key_line(theme, "gg / G", "First / last"),
+        key_line(theme, "gp", "Go to Playlists"),
+        key_line(theme, "gn", "Go to where the playing track is from"),
+        key_line(theme, ".", "Jump to playing"),
+        key_line(theme, "23 Enter", "Jump to row 23"),
+        key_line(theme, "Enter", "Select / Play"),
+        key_line(theme, "Esc / b", "Back"),
+        key_line(theme, &format!("1-{}", state.screens.len()), "Switch screen"),
+        key_line(theme, ":", "Command (:features, :dedupe, :archived, …)"),
         Line::from(Span::raw("")),
-        Line::from(Span::styled("  Playback", hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
+        Line::from(Span::styled("  Playback", theme.hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
         Line::from(Span::raw("")),
-        key_line("Space", "Pause / Resume"),
-        key_line("n", "Next track"),
-        key_line("p", "Previous track"),
-        key_line("f / →", "Seek forward"),
-        key_line("r / ←", "Seek backward"),
-        key_line("+ / =", "Volume up"),
-        key_line("-", "Volume down"),
+        key_line(theme, "Space", "Pause / Resume"),
+        key_line(theme, "n", "Next track"),
+        key_line(theme, "p", "Previous track"),
+        key_line(theme, "f / →", "Seek forward"),
+        key_line(theme, "r / ←", "Seek backward"),
+        key_line(theme, "%0-9", "Seek to 0–90% (or Alt+0-9)"),
+        key_line(theme, "S-→ / S-←", "Seek a large step"),
+        key_line(theme, "= / -", "Volume up / down"),
+        key_line(theme, "+ / _", "Volume a large step"),
+        key_line(theme, "m", "Mute / unmute"),
+        key_line(theme, "v", "Volume leveling"),
+        key_line(theme, "L / C", "Sidebar panel / critter"),
     ];
 
-    let right = vec![
-        Line::from(Span::styled("  Library", hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
+    let mut right = vec![
+        Line::from(Span::styled("  Library", theme.hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
         Line::from(Span::raw("")),
-        key_line("l", "Like / Unlike track"),
-        key_line("a", "Add to queue"),
-        key_line("s", "Open search"),
+        key_line(theme, "l", "Like / Unlike highlighted or playing track"),
+        key_line(theme, "a", "Add to queue"),
+        key_line(theme, "A", "Play next"),
+        key_line(theme, "Q", "Queue all Vibes results"),
+        key_line(theme, "V", "Visual select (Space marks)"),
+        key_line(theme, "X", "Archive playlist(s)"),
+        key_line(theme, "y / Y", "Copy track link / URI"),
+        key_line(theme, "gy", "Copy track info"),
+        key_line(theme, "O", "Open in Spotify"),
+        key_line(theme, "gi", "Inbox of shared links"),
+        key_line(theme, "i", "Audio features radar"),
+        key_line(theme, "M", "Mood filter over Liked Songs"),
+        key_line(theme, "R", "Track radio"),
+        key_line(theme, "o", "Replay on this day"),
+        key_line(theme, "c", "New playlist"),
+        key_line(theme, "P", "Add track to playlist"),
+        key_line(theme, "x / Del", "Remove from playlist / unfollow"),
+        key_line(theme, "u", "Playlist changes"),
+        key_line(theme, "s", "Open search"),
+        key_line(theme, "gs", "Past searches (↑/↓ in search)"),
+        key_line(theme, "gb", "Running jobs (x cancels)"),
+        key_line(theme, "Tab", "Switch tab (results, albums, artists)"),
+        key_line(theme, "F", "Follow playlist (search, Browse)"),
         Line::from(Span::raw("")),
-        Line::from(Span::styled("  Screens", hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
+        Line::from(Span::styled("  Screens", theme.hot_pink_style().add_modifier(ratatui::style::Modifier::BOLD))),
         Line::from(Span::raw("")),
-        key_line("[1]", "Search"),
-        key_line("[2]", "Liked Songs"),
-        key_line("[3]", "Playlists"),
-        key_line("[4]", "Queue"),
-        key_line("[5]", "Vibes"),
-        Line::from(Span::raw("")),
-        key_line("?", "Toggle this help"),
-        key_line("q", "Quit"),
     ];
+    // Follows the configured sidebar order
+    right.extend(state.screens.iter().enumerate().map(|(i, screen)| match screen {
+        ActiveScreen::Stats => key_line(theme, &format!("[{}]", i + 1), "Stats (←/→ range)"),
+        _ => key_line(theme, &format!("[{}]", i + 1), screen.label()),
+    }));
+    right.extend([
+        Line::from(Span::raw("")),
+        key_line(theme, "⏎ / d", "Retry / details (error)"),
+        key_line(theme, "?", "Toggle this help"),
+        key_line(theme, "q", "Quit"),
+    ]);
 
     f.render_widget(Paragraph::new(left), cols[0]);
     f.render_widget(Paragraph::new(right), cols[1]);
 }
 
-fn key_line(key: &str, desc: &str) -> Line<'static> {
+fn key_line(theme: &Theme, key: &str, desc: &str) -> Line<'static> {
     Line::from(vec![
-        Span::styled("  ", muted_style()),
-        Span::styled(format!("{key:<10}"), accent_style()),
-        Span::styled(desc.to_string(), normal_style()),
+        Span::styled("  ", theme.muted_style()),
+        Span::styled(format!("{key:<10}"), theme.accent_style()),
+        Span::styled(desc.to_string(), theme.normal_style()),
     ])
 }
-
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
@@ -0,0 +1,62 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `gi` popup — tracks shared with `vibes inbox add`, oldest first
+pub fn render_inbox(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let inbox = &state.inbox;
+
+    let width = area.width.saturating_sub(4).min(80);
+    let height = (inbox.items.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = inbox
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let is_sel = i == inbox.selected;
+            let mut spans = vec![Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style())];
+            match inbox.tracks.get(&item.uri) {
+                Some(track) => {
+                    let artists: Vec<&str> = track.artists.iter().map(|a| a.name.as_str()).collect();
+                    spans.push(Span::styled(track.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }));
+                    spans.push(Span::styled(format!(" — {}", artists.join(", ")), theme.dim_style()));
+                }
+                None => spans.push(Span::styled(item.uri.clone(), theme.muted_style())),
+            }
+            if let Some(ref note) = item.note {
+                spans.push(Span::styled(format!("  “{note}”"), theme.hot_pink_style()));
+            }
+            spans.push(Span::styled(
+                format!(" · {}", item.added_at.with_timezone(&chrono::Local).format("%Y-%m-%d")),
+                theme.muted_style(),
+            ));
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" 📥 Inbox ({}) ", inbox.items.len()), theme.title_style()))
+            .title_bottom(Span::styled(" Enter play · a queue · P add to playlist · x dismiss · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(inbox.selected, inbox.items.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
@@ -0,0 +1,70 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::{format_duration, viewport_offset}, theme::Theme};
+
+/// Cells in a job's progress bar
+const BAR_WIDTH: usize = 24;
+
+/// `gb` popup — long operations still running, two rows each: what it is,
+/// then a progress bar
+pub fn render_jobs(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let jobs = state.jobs.running();
+
+    let width = area.width.saturating_sub(4).min(70);
+    let height = (jobs.len() as u16 * 2 + 2).clamp(4, area.height.saturating_sub(2).max(4));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = jobs
+        .iter()
+        .enumerate()
+        .map(|(i, job)| {
+            let is_sel = i == state.selected_job;
+            let elapsed = format_duration(job.started.elapsed().as_millis().min(u32::MAX as u128) as u32);
+            let title = Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(job.kind.name(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!(" — {}", job.label), theme.dim_style()),
+                Span::styled(format!("  {elapsed}"), theme.muted_style()),
+            ]);
+            let filled = job.fraction().map_or(0, |f| (f * BAR_WIDTH as f64).round() as usize);
+            let mut bar = vec![
+                Span::raw("  "),
+                Span::styled("█".repeat(filled), theme.playing_style()),
+                Span::styled("░".repeat(BAR_WIDTH - filled), theme.muted_style()),
+            ];
+            if let Some(f) = job.fraction() {
+                bar.push(Span::styled(format!(" {:3.0}%", f * 100.0), theme.dim_style()));
+            }
+            bar.push(Span::styled(format!(" · {}", job.progress()), theme.muted_style()));
+            if job.handle.is_cancelled() {
+                bar.push(Span::styled(" · cancelling…", theme.error_style()));
+            }
+            let item = ListItem::new(vec![title, Line::from(bar)]);
+            if is_sel {
+                item.style(theme.selected_style())
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" ⚙ Jobs ({}) ", jobs.len()), theme.title_style()))
+            .title_bottom(Span::styled(" x cancel · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let rows = (popup.height.saturating_sub(2) / 2) as usize;
+    let offset = viewport_offset(state.selected_job, jobs.len(), rows);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
@@ -1,18 +1,37 @@
 use ratatui::{
     layout::{Constraint, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
     Frame,
 };
 
-use crate::app::state::AppState;
-use super::super::theme::*;
+use rspotify::{model::FullTrack, prelude::Id};
+
+use crate::app::state::{AppState, LibraryTab};
+use super::{list_count, sort_label};
+use super::super::{text::{filtered_position, format_duration, row_number, viewport_offset}, theme::Theme};
+
+pub fn render_library(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    if state.library.tab == LibraryTab::Albums {
+        if state.library.viewing_album {
+            render_album_tracks(f, area, state, theme);
+        } else {
+            render_albums(f, area, state, theme);
+        }
+        return;
+    }
+    if state.library.tab == LibraryTab::Artists {
+        match state.library.artist_top_tracks {
+            Some(ref tracks) => render_artist_top_tracks(f, area, tracks, state, theme),
+            None => render_artists(f, area, state, theme),
+        }
+        return;
+    }
 
-pub fn render_library(f: &mut Frame, area: Rect, state: &AppState) {
     if state.library.is_loading {
         let para =
-            Paragraph::new(Line::from(Span::styled("  ⠋ Loading liked songs...", dim_style())))
-                .block(make_block(" ❤  Liked Songs ", true));
+            Paragraph::new(Line::from(Span::styled("  ⠋ Loading liked songs...", theme.dim_style())))
+                .block(make_block(theme, " ❤  Liked Songs ", true));
         f.render_widget(para, area);
         return;
     }
@@ -20,24 +39,28 @@ pub fn render_library(f: &mut Frame, area: Rect, state: &AppState) {
     if state.library.liked_songs.is_empty() {
         let para = Paragraph::new(Line::from(Span::styled(
             "  No liked songs yet. Open Spotify and like some tracks!",
-            muted_style(),
+            theme.muted_style(),
         )))
-        .block(make_block(" ❤  Liked Songs ", false));
+        .block(make_block(theme, " ❤  Liked Songs ", false));
         f.render_widget(para, area);
         return;
     }
 
     let selected = state.library.selected;
+    let visible = state.filtered_rows();
     let rows: Vec<Row> = state
         .library
         .liked_songs
         .iter()
         .enumerate()
+        .filter(|(i, _)| visible.as_ref().is_none_or(|rows| rows.binary_search(i).is_ok()))
         .map(|(i, saved)| {
             let track = &saved.track;
             let is_sel = i == selected;
-            let num = if is_sel { "▶".to_string() } else { format!("{:>3}", i + 1) };
-            let title = track.name.clone();
+            let num = row_number(i + 1, 3, is_sel, state.is_marked(track.id.as_ref()));
+            // ◎ marks songs a saved album already covers (see `:dedupe`)
+            let on_album = track.id.as_ref().is_some_and(|id| state.library.on_saved_album.contains(&id.uri()));
+            let title = if on_album { format!("{} ◎", track.name) } else { track.name.clone() };
             let artist = track
                 .artists
                 .iter()
@@ -45,28 +68,26 @@ pub fn render_library(f: &mut Frame, area: Rect, state: &AppState) {
                 .collect::<Vec<_>>()
                 .join(", ");
             let album = track.album.name.clone();
-            let dur_ms = track.duration.num_milliseconds() as u32;
-            let secs = dur_ms / 1000;
-            let dur = format!("{}:{:02}", secs / 60, secs % 60);
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
 
-            let style = if is_sel { selected_style() } else { normal_style() };
+            let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
             Row::new(vec![
-                Cell::from(num).style(if is_sel { playing_style() } else { muted_style() }),
+                Cell::from(num).style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
                 Cell::from(title).style(style.clone()),
-                Cell::from(artist).style(dim_style()),
-                Cell::from(album).style(muted_style()),
-                Cell::from(dur).style(muted_style()),
+                Cell::from(artist).style(theme.dim_style()),
+                Cell::from(album).style(theme.muted_style()),
+                Cell::from(dur).style(theme.muted_style()),
             ])
             .style(style)
         })
         .collect();
 
     let header = Row::new(vec![
-        Cell::from(" # ").style(header_style()),
-        Cell::from("Title").style(header_style()),
-        Cell::from("Artist").style(header_style()),
-        Cell::from("Album").style(header_style()),
-        Cell::from("Dur").style(header_style()),
+        Cell::from(" # ").style(theme.header_style()),
+        Cell::from("Title").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Album").style(theme.header_style()),
+        Cell::from("Dur").style(theme.header_style()),
     ])
     .height(1);
 
@@ -81,20 +102,250 @@ pub fn render_library(f: &mut Frame, area: Rect, state: &AppState) {
         ],
     )
     .header(header)
-    .block(make_block(
-        &format!(" ❤  Liked Songs ({}) ", state.library.liked_songs.len()),
+    .block(make_block(theme, 
+        &format!(
+            " ❤  Liked Songs ({}){}{} · Tab: albums ",
+            list_count(state, state.library.liked_songs.len(), visible.as_deref()),
+            paging_label(state),
+            sort_label(state.library.sort),
+        ),
         true,
     ))
-    .row_highlight_style(selected_style());
+    .row_highlight_style(theme.selected_style());
+
+    let (pos, len) = filtered_position(selected, state.library.liked_songs.len(), visible.as_deref());
+    let offset = viewport_offset(pos, len, area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
+}
+
+/// ` · ⠋ 200/1432 loaded` while later pages of Liked Songs are still coming in
+fn paging_label(state: &AppState) -> String {
+    let library = &state.library;
+    if !library.paging_liked {
+        return String::new();
+    }
+    format!(" · ⠋ {}/{} loaded", library.liked_songs.len(), library.liked_total)
+}
+
+/// Saved albums tab: Enter plays an album, → opens its tracks
+fn render_albums(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let albums = &state.library.saved_albums;
+    if albums.is_empty() {
+        let para = Paragraph::new(Line::from(Span::styled(
+            "  No saved albums (or still loading).",
+            theme.muted_style(),
+        )))
+        .block(make_block(theme, " 💿 Albums · Tab: artists ", true));
+        f.render_widget(para, area);
+        return;
+    }
+
+    let selected = state.library.selected_album;
+    let rows: Vec<Row> = albums
+        .iter()
+        .enumerate()
+        .map(|(i, saved)| {
+            let album = &saved.album;
+            let is_sel = i == selected;
+            let num = if is_sel { "▶".to_string() } else { format!("{:>3}", i + 1) };
+            let artist = album.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", ");
+            let year = album.release_date.get(..4).unwrap_or_default().to_string();
+            let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
+            Row::new(vec![
+                Cell::from(num).style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Cell::from(album.name.clone()).style(style),
+                Cell::from(artist).style(theme.dim_style()),
+                Cell::from(year).style(theme.muted_style()),
+                Cell::from(album.tracks.total.to_string()).style(theme.muted_style()),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(" # ").style(theme.header_style()),
+        Cell::from("Album").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Year").style(theme.header_style()),
+        Cell::from("Trk").style(theme.header_style()),
+    ])
+    .height(1);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Percentage(45),
+            Constraint::Percentage(35),
+            Constraint::Length(6),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header)
+    .block(make_block(theme, &format!(" 💿 Albums ({}) · Tab: artists ", albums.len()), true))
+    .row_highlight_style(theme.selected_style());
+
+    let offset = viewport_offset(selected, albums.len(), area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
+}
+
+fn render_album_tracks(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(saved) = state.library.open_album() else { return };
+    let tracks = &saved.album.tracks.items;
+    let selected = state.library.selected_album_track;
+    let rows: Vec<Row> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let is_sel = i == selected;
+            let num = row_number(track.track_number as usize, 3, is_sel, state.is_marked(track.id.as_ref()));
+            let artist = track.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", ");
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
+            let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
+            Row::new(vec![
+                Cell::from(num).style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Cell::from(track.name.clone()).style(style),
+                Cell::from(artist).style(theme.dim_style()),
+                Cell::from(dur).style(theme.muted_style()),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(" # ").style(theme.header_style()),
+        Cell::from("Title").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Dur").style(theme.header_style()),
+    ])
+    .height(1);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Percentage(50),
+            Constraint::Percentage(40),
+            Constraint::Length(7),
+        ],
+    )
+    .header(header)
+    .block(make_block(theme, &format!(" 💿 {} · ← back ", saved.album.name), true))
+    .row_highlight_style(theme.selected_style());
+
+    let offset = viewport_offset(selected, tracks.len(), area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
+}
+
+/// Followed artists tab: Enter/→ shows top tracks, x unfollows
+fn render_artists(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let artists = &state.library.followed_artists;
+    if artists.is_empty() {
+        let text = if state.library.is_loading { "  ⠋ Loading followed artists..." } else { "  You don't follow any artists yet." };
+        let para = Paragraph::new(Line::from(Span::styled(text, theme.muted_style())))
+            .block(make_block(theme, " 🎤 Artists · Tab: liked songs ", true));
+        f.render_widget(para, area);
+        return;
+    }
+
+    let selected = state.library.selected_artist;
+    let rows: Vec<Row> = artists
+        .iter()
+        .enumerate()
+        .map(|(i, artist)| {
+            let is_sel = i == selected;
+            let num = if is_sel { "▶".to_string() } else { format!("{:>3}", i + 1) };
+            let genres = artist.genres.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+            let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
+            Row::new(vec![
+                Cell::from(num).style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Cell::from(artist.name.clone()).style(style),
+                Cell::from(genres).style(theme.dim_style()),
+                Cell::from(artist.followers.total.to_string()).style(theme.muted_style()),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(" # ").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Genres").style(theme.header_style()),
+        Cell::from("Followers").style(theme.header_style()),
+    ])
+    .height(1);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Percentage(40),
+            Constraint::Percentage(45),
+            Constraint::Length(11),
+        ],
+    )
+    .header(header)
+    .block(make_block(theme, &format!(" 🎤 Artists ({}) · x: unfollow · Tab: liked songs ", artists.len()), true))
+    .row_highlight_style(theme.selected_style());
+
+    let offset = viewport_offset(selected, artists.len(), area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
+}
+
+fn render_artist_top_tracks(f: &mut Frame, area: Rect, tracks: &[FullTrack], state: &AppState, theme: &Theme) {
+    let artist = state.library.followed_artists.get(state.library.selected_artist).map(|a| a.name.as_str()).unwrap_or_default();
+    let selected = state.library.selected_artist_track;
+    let rows: Vec<Row> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| {
+            let is_sel = i == selected;
+            let num = row_number(i + 1, 3, is_sel, state.is_marked(track.id.as_ref()));
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
+            let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
+            Row::new(vec![
+                Cell::from(num).style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Cell::from(Line::from(vec![
+                    Span::styled(track.name.clone(), style),
+                    Span::styled(if state.is_liked(track) { " ❤" } else { "" }, theme.gold_style()),
+                ])),
+                Cell::from(track.album.name.clone()).style(theme.muted_style()),
+                Cell::from(dur).style(theme.muted_style()),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    let header = Row::new(vec![
+        Cell::from(" # ").style(theme.header_style()),
+        Cell::from("Title").style(theme.header_style()),
+        Cell::from("Album").style(theme.header_style()),
+        Cell::from("Dur").style(theme.header_style()),
+    ])
+    .height(1);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Percentage(50),
+            Constraint::Percentage(40),
+            Constraint::Length(7),
+        ],
+    )
+    .header(header)
+    .block(make_block(theme, &format!(" 🎤 {artist} · top tracks · ← back "), true))
+    .row_highlight_style(theme.selected_style());
 
-    f.render_widget(table, area);
+    let offset = viewport_offset(selected, tracks.len(), area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
 }
 
-fn make_block(title: &str, focused: bool) -> Block<'static> {
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
     Block::default()
-        .title(Span::styled(title.to_string(), title_style()))
+        .title(Span::styled(title.to_string(), theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(focused))
-        .style(normal_style().bg(BG))
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
 }
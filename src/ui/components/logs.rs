@@ -0,0 +1,102 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+use tracing::Level;
+use unicode_width::UnicodeWidthChar;
+
+use crate::app::state::AppState;
+use crate::logs::{parse_level, LogLine};
+use super::super::{centered, theme::Theme};
+
+/// `gl` popup — the newest log lines, wrapped, errors and warnings in color
+pub fn render_logs(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref view) = state.log_view else { return };
+
+    let width = area.width.saturating_sub(4).min(140);
+    let height = (area.height * 4 / 5).max(3).min(area.height);
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let errors = view.lines.iter().filter(|l| parse_level(&l.text) == Some(Level::ERROR)).count();
+    let title = match errors {
+        0 => " ☰ Log ".to_string(),
+        1 => " ☰ Log · 1 error ".to_string(),
+        n => format!(" ☰ Log · {n} errors "),
+    };
+    let hint = if view.scroll > 0 {
+        format!(" {} newer · ↓ G to follow · Esc ", view.scroll)
+    } else {
+        match view.path {
+            Some(ref path) => format!(" ↑↓ scroll · following {} · Esc ", path.display()),
+            None => " ↑↓ scroll · Esc ".to_string(),
+        }
+    };
+    let block = Block::default()
+        .title(Span::styled(title, theme.title_style()))
+        .title_bottom(Span::styled(hint, theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style().bg(theme.surface));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    if view.lines.is_empty() {
+        f.render_widget(Paragraph::new(Span::styled("  Nothing logged yet.", theme.muted_style())), inner);
+        return;
+    }
+    // Fill from the bottom with the lines up to the scroll position
+    let rows = inner.height as usize;
+    let shown = &view.lines[..view.lines.len().saturating_sub(view.scroll)];
+    let mut lines: Vec<Line> = Vec::with_capacity(rows);
+    for line in shown.iter().rev() {
+        let style = level_style(line, theme);
+        let wrapped = wrap(&short_timestamp(&line.text), inner.width as usize);
+        for row in wrapped.into_iter().rev() {
+            lines.push(Line::from(Span::styled(row, style)));
+        }
+        if lines.len() >= rows {
+            break;
+        }
+    }
+    lines.truncate(rows);
+    lines.reverse();
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn level_style(line: &LogLine, theme: &Theme) -> Style {
+    match line.level {
+        Some(Level::ERROR) => theme.error_style(),
+        Some(Level::WARN) => theme.gold_style(),
+        Some(Level::INFO) | None => theme.normal_style(),
+        Some(_) => theme.muted_style(),
+    }
+}
+
+/// `2024-05-01T12:00:00.123456Z  WARN …` as `12:00:00  WARN …`
+fn short_timestamp(text: &str) -> String {
+    match text.split_once(' ') {
+        Some((stamp, rest)) if stamp.len() > 19 && stamp.is_ascii() && stamp.as_bytes()[10] == b'T' => format!("{} {rest}", &stamp[11..19]),
+        _ => text.to_string(),
+    }
+}
+
+/// `text` in rows at most `width` columns wide
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut rows = vec![String::new()];
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width.max(1) {
+            rows.push(String::new());
+            used = 0;
+        }
+        used += w;
+        rows.last_mut().expect("never empty").push(c);
+    }
+    rows
+}
@@ -1,8 +1,40 @@
+pub mod archive;
+pub mod browse;
+pub mod dedupe;
+pub mod devices;
+pub mod features;
 pub mod help;
+pub mod inbox;
+pub mod jobs;
 pub mod library;
+pub mod logs;
+pub mod mood;
+pub mod now_playing;
+pub mod picker;
 pub mod player_bar;
 pub mod playlists;
+pub mod prompt;
 pub mod queue;
+pub mod radar;
 pub mod search;
+pub mod search_history;
 pub mod sidebar;
+pub mod startup;
+pub mod stats;
 pub mod vibes_screen;
+
+use crate::app::state::{AppState, SortOrder};
+
+/// A list's row count for its title: `n`, or `shown/n · /query` while `/`
+/// narrows it
+pub fn list_count(state: &AppState, total: usize, visible: Option<&[usize]>) -> String {
+    let Some(filter) = state.shown_filter() else { return total.to_string() };
+    let shown = visible.map_or(total, <[usize]>::len);
+    let cursor = if filter.typing { "▏" } else { "" };
+    format!("{shown}/{total} · /{}{cursor}", filter.query)
+}
+
+/// ` · by artist ↓` for a sorted track table's title
+pub fn sort_label(order: SortOrder) -> String {
+    order.label().map(|label| format!(" · by {label}")).unwrap_or_default()
+}
@@ -0,0 +1,60 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `M` popup — liked songs passing a mood filter, with the features it tests
+pub fn render_mood(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref mood) = state.mood else { return };
+
+    let width = area.width.saturating_sub(4).min(90);
+    let height = (mood.tracks.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = mood
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(i, (track, features))| {
+            let is_sel = i == mood.selected;
+            let artists: Vec<&str> = track.artists.iter().map(|a| a.name.as_str()).collect();
+            let spans = vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(track.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!(" — {}", artists.join(", ")), theme.dim_style()),
+                Span::styled(
+                    format!("  ⚡{:.2} ☺{:.2} · {:.0} bpm", features.energy, features.valence, features.tempo),
+                    theme.muted_style(),
+                ),
+            ];
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let mut title = format!(" ≈ {} ({}) ", mood.filter, mood.tracks.len());
+    if mood.unindexed > 0 {
+        title.push_str(&format!("· {} not indexed yet ", mood.unindexed));
+    }
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(title, theme.title_style()))
+            .title_bottom(Span::styled(" Enter play · c save as playlist · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(mood.selected, mood.tracks.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
@@ -0,0 +1,305 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Gauge, Paragraph},
+    Frame,
+};
+use rspotify::model::RepeatState;
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::state::AppState;
+use crate::artwork::Cover;
+use crate::collate::fold;
+use crate::lyrics::current_line;
+use super::super::{text::truncate, theme::{reduce, ColorDepth, Theme}};
+use super::player_bar::{bar_block, bar_color};
+
+/// Rows of the EQ under the progress bar
+const EQ_ROWS: u8 = 6;
+
+/// `z` focus mode: the playing track across the whole terminal — the album
+/// art, the title, where and how it's playing, the progress and the lyrics
+pub fn render_now_playing(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let block = Block::default()
+        .title(Span::styled(" ◉ Now Playing ", theme.title_style()))
+        .title_bottom(Span::styled(" z/Esc back · spc play · n/p skip · f/r seek · l like ", theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let track = &state.current_track;
+    if track.name.is_empty() {
+        let idle = Paragraph::new(Line::from(Span::styled("♪ Nothing playing", theme.muted_style()))).alignment(Alignment::Center);
+        f.render_widget(idle, Rect { y: inner.y + inner.height / 2, height: 1, ..inner });
+        return;
+    }
+
+    let art_rows = (inner.height / 3).clamp(5, 12);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),              // breathing room
+            Constraint::Length(art_rows),       // cover + track details
+            Constraint::Length(1),
+            Constraint::Length(1),              // progress gauge
+            Constraint::Length(1),              // times
+            Constraint::Length(EQ_ROWS as u16), // EQ
+            Constraint::Min(0),                 // lyrics
+        ])
+        .split(inner);
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(2), Constraint::Length(art_rows * 2), Constraint::Length(3), Constraint::Min(0)])
+        .split(rows[1]);
+    render_cover(f, top[1], state, theme);
+    render_details(f, top[3], state, theme);
+
+    let bar = Rect { x: inner.x + 2, width: inner.width.saturating_sub(4), ..rows[3] };
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(theme.primary).bg(theme.surface))
+        .ratio(track.progress_percent())
+        .label("");
+    f.render_widget(gauge, bar);
+    let times = Paragraph::new(Line::from(Span::styled(track.progress_formatted(), theme.dim_style()))).alignment(Alignment::Center);
+    f.render_widget(times, rows[4]);
+
+    render_eq(f, rows[5], state, theme);
+    render_lyrics(f, rows[6], state, theme);
+}
+
+/// The album art in half-block cells once it's fetched, else the title in
+/// large type
+fn render_cover(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let lines = match state.cover {
+        Some(ref cover) => cover_lines(cover, area, state.color_depth),
+        None => big_type(&state.current_track.name, area, theme),
+    };
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// Each cell shows two pixels: the top one as `▀`, the bottom one behind it
+fn cover_lines(cover: &Cover, area: Rect, depth: ColorDepth) -> Vec<Line<'static>> {
+    let (w, h) = (area.width as u32, area.height as u32 * 2);
+    let color = |(r, g, b)| reduce(Color::Rgb(r, g, b), depth);
+    (0..h / 2)
+        .map(|row| {
+            let spans = (0..w)
+                .map(|x| {
+                    let (top, bottom) = (cover.sample(x, row * 2, w, h), cover.sample(x, row * 2 + 1, w, h));
+                    Span::styled("▀", Style::default().fg(color(top)).bg(color(bottom)))
+                })
+                .collect::<Vec<_>>();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// `text` in a 3×5 pixel font, wrapped by word and centered in `area`.
+/// Titles the font can't spell at all are written out plainly instead.
+fn big_type(text: &str, area: Rect, theme: &Theme) -> Vec<Line<'static>> {
+    let style = Style::default().fg(theme.primary);
+    let (w, h) = (area.width as usize, area.height as usize);
+    let letters = fold(text).to_uppercase();
+    if !letters.chars().any(|c| c != ' ' && glyph(c).is_some()) {
+        let pad = h.saturating_sub(1) / 2;
+        let mut lines = vec![Line::default(); pad];
+        lines.push(Line::from(Span::styled(truncate(text, w), theme.title_style())).centered());
+        return lines;
+    }
+
+    // Glyphs are 3 cells and a gap wide, 3 rows and a gap tall
+    let per_line = (w + 1) / 4;
+    let mut rows: Vec<String> = Vec::new();
+    for word in letters.split(' ').filter(|w| !w.is_empty()) {
+        let word: Vec<char> = word.chars().filter(|&c| glyph(c).is_some()).collect();
+        for chunk in word.chunks(per_line.max(1)) {
+            match rows.last_mut() {
+                Some(row) if row.chars().count() + 1 + chunk.len() <= per_line => {
+                    row.push(' ');
+                    row.extend(chunk);
+                }
+                _ => rows.push(chunk.iter().collect()),
+            }
+        }
+    }
+    rows.truncate(((h + 1) / 4).max(1));
+
+    let drawn = rows.len() * 4 - 1;
+    let mut lines = vec![Line::default(); h.saturating_sub(drawn) / 2];
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::default());
+        }
+        let glyphs: Vec<[u8; 5]> = row.chars().filter_map(glyph).collect();
+        for cell_row in 0..3 {
+            let text: String = glyphs
+                .iter()
+                .map(|g| {
+                    let bits = |px: usize| g.get(px).copied().unwrap_or(0);
+                    let (top, bottom) = (bits(cell_row * 2), bits(cell_row * 2 + 1));
+                    (0..3)
+                        .rev()
+                        .map(|col| match (top >> col & 1, bottom >> col & 1) {
+                            (1, 1) => '█',
+                            (1, _) => '▀',
+                            (_, 1) => '▄',
+                            _ => ' ',
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(Line::from(Span::styled(text, style)).centered());
+        }
+    }
+    lines
+}
+
+/// Rows of a 3×5 letter, top first, leftmost pixel in the high bit
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b110, 0b001, 0b010, 0b100, 0b111],
+        '3' => [0b110, 0b001, 0b010, 0b001, 0b110],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b110, 0b001, 0b110],
+        '6' => [0b011, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b110],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b110, 0b001, 0b010, 0b000, 0b010],
+        '\'' => [0b010, 0b010, 0b000, 0b000, 0b000],
+        '&' => [0b010, 0b101, 0b010, 0b101, 0b011],
+        _ => return None,
+    })
+}
+
+/// Title (letter-spaced when there's room), artists, album and the state of
+/// playback: playing or paused, shuffle, repeat, device and volume
+fn render_details(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let track = &state.current_track;
+    let width = area.width as usize;
+    let spaced: String = track.name.to_uppercase().chars().flat_map(|c| [c, ' ']).collect();
+    let title = if spaced.width() <= width { spaced.trim_end().to_string() } else { truncate(&track.name, width) };
+    let album = match track.album_year {
+        Some(ref year) => format!("{} · {year}", track.album),
+        None => track.album.clone(),
+    };
+    let heart = if track.is_liked { " ♥" } else { "" };
+
+    let on = |label: &str, active: bool| {
+        Span::styled(label.to_string(), if active { theme.accent_style() } else { theme.muted_style() })
+    };
+    let repeat = match track.repeat {
+        Some(RepeatState::Track) => on("🔂 repeat one", true),
+        Some(RepeatState::Context) => on("🔁 repeat", true),
+        _ => on("🔁 repeat off", false),
+    };
+    let playing = if track.is_playing {
+        Span::styled("▶ playing", theme.playing_style())
+    } else {
+        Span::styled("⏸ paused", theme.dim_style())
+    };
+    let sep = || Span::styled("  ·  ", theme.muted_style());
+    let device = track.device_name.as_deref().unwrap_or("no device");
+
+    // Centered in the cover's height
+    let mut lines = vec![Line::from(""); area.height.saturating_sub(6) as usize / 2];
+    lines.extend([
+        Line::from(vec![Span::styled(title, theme.title_style()), Span::styled(heart, theme.hot_pink_style())]),
+        Line::from(""),
+        Line::from(Span::styled(truncate(&track.artists.join(", "), width), theme.accent_style())),
+        Line::from(Span::styled(truncate(&album, width), theme.dim_style())),
+        Line::from(""),
+        Line::from(vec![
+            playing,
+            sep(),
+            on(if track.shuffle { "🔀 shuffle" } else { "🔀 shuffle off" }, track.shuffle),
+            sep(),
+            repeat,
+            sep(),
+            Span::styled(format!("🔈 {device} · {}%", state.volume), theme.dim_style()),
+        ]),
+    ]);
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// The EQ bars, stretched over the width
+fn render_eq(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let bars = state.eq_bars.len();
+    let step = (area.width as usize / bars).clamp(1, 4);
+    let rows = (area.height as u8).min(EQ_ROWS);
+    for row in 0..rows {
+        let row_from_bottom = rows - 1 - row;
+        let spans: Vec<Span> = state
+            .eq_bars
+            .iter()
+            .map(|&h| {
+                // 12 levels over `rows` rows
+                let h = (u16::from(h) * u16::from(rows)).div_ceil(12) as u8;
+                let glyph = bar_block(h, row_from_bottom).repeat(step.saturating_sub(1).max(1));
+                Span::styled(format!("{glyph} "), Style::default().fg(bar_color(theme, h * 2, row_from_bottom * 2)))
+            })
+            .collect();
+        let line = Rect { y: area.y + row as u16, height: 1, ..area };
+        f.render_widget(Paragraph::new(Line::from(spans)).alignment(Alignment::Center), line);
+    }
+}
+
+/// Synced lyrics around the line being sung, that one highlighted
+fn render_lyrics(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    if area.height < 2 {
+        return;
+    }
+    let lines: Vec<Line> = match state.lyrics.as_deref() {
+        Some(lyrics) if !lyrics.is_empty() => {
+            let now = current_line(lyrics, state.current_track.progress_ms);
+            let rows = area.height.saturating_sub(1) as usize;
+            let first = now.unwrap_or(0).saturating_sub(rows / 3);
+            let mut lines = vec![Line::from("")];
+            lines.extend(lyrics.iter().enumerate().skip(first).take(rows).map(|(i, (_, text))| {
+                let text = if text.is_empty() { "♪" } else { text.as_str() };
+                let style = if Some(i) == now { theme.title_style() } else { theme.muted_style() };
+                Line::from(Span::styled(truncate(text, area.width as usize), style))
+            }));
+            lines
+        }
+        _ => vec![Line::from(""), Line::from(Span::styled("♪ no synced lyrics", theme.muted_style()))],
+    };
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), area);
+}
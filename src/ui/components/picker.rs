@@ -0,0 +1,48 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, theme::Theme};
+
+/// "Add to playlist" popup: the editable playlists, highlighted one first in line for Enter
+pub fn render_playlist_picker(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref picker) = state.picker else { return };
+    let playlists = state.editable_playlists();
+
+    let width = area.width.saturating_sub(4).min(50);
+    let height = (playlists.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = playlists
+        .iter()
+        .enumerate()
+        .map(|(i, pl)| {
+            let is_sel = i == picker.selected;
+            ListItem::new(Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(pl.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!("  {}", pl.tracks.total), theme.muted_style()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(theme.selected_style())
+        .block(
+            Block::default()
+                .title(Span::styled(format!(" ＋ Add “{}” to… ", picker.track_name), theme.title_style()))
+                .title_bottom(Span::styled(" Enter ✓  Esc ✗ ", theme.muted_style()))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(theme.border_style(true))
+                .style(theme.normal_style().bg(theme.surface)),
+        );
+    // Stateful so long playlist collections scroll with the selection
+    let mut list_state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list, popup, &mut list_state);
+}
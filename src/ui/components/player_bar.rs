@@ -6,12 +6,12 @@ use ratatui::{
 };
 
 use crate::app::state::AppState;
-use super::super::theme::*;
+use super::super::{anim, theme::Theme};
 
 /// Block characters for vertical bar heights (8 levels)
 const BAR_BLOCKS: &[&str] = &[" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
-fn bar_block(height: u8, row_from_bottom: u8) -> &'static str {
+pub fn bar_block(height: u8, row_from_bottom: u8) -> &'static str {
     // For a given bar height (1-12) and row (0=bottom),
     // return full block if height > row, else empty
     if height > row_from_bottom {
@@ -21,40 +21,40 @@ fn bar_block(height: u8, row_from_bottom: u8) -> &'static str {
     }
 }
 
-fn bar_color(height: u8, row_from_bottom: u8) -> ratatui::style::Color {
+pub fn bar_color(theme: &Theme, height: u8, row_from_bottom: u8) -> ratatui::style::Color {
     let level = row_from_bottom;
     if height <= row_from_bottom {
-        SURFACE // invisible
+        theme.surface    // invisible
     } else if level >= 9 {
-        ERROR      // red peak
+        theme.error      // red peak
     } else if level >= 6 {
-        HOT_PINK   // hot zone
+        theme.hot_pink   // hot zone
     } else if level >= 3 {
-        PRIMARY    // mid purple
+        theme.primary    // mid accent
     } else {
-        ACCENT     // cyan base
+        theme.accent     // base
     }
 }
 
-pub fn render_player_bar(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render_player_bar(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(true))
-        .style(normal_style());
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style());
 
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    if state.eq_expanded {
-        render_expanded(f, inner, state);
+    if state.eq_expanded && !state.mini {
+        render_expanded(f, inner, state, theme);
     } else {
-        render_compact(f, inner, state);
+        render_compact(f, inner, state, theme);
     }
 }
 
 /// Compact player bar (5 lines) — track info + inline EQ + progress
-fn render_compact(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_compact(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -65,7 +65,7 @@ fn render_compact(f: &mut Frame, area: Rect, state: &AppState) {
         .split(area);
 
     // ── Track info ──────────────────────────────────────────────────
-    render_track_info(f, chunks[0], state);
+    render_track_info(f, chunks[0], state, theme);
 
     // ── Center: EQ + progress ──────────────────────────────────────
     let center_chunks = Layout::default()
@@ -80,7 +80,7 @@ fn render_compact(f: &mut Frame, area: Rect, state: &AppState) {
     // Single-row EQ
     let eq_spans: Vec<Span> = state.eq_bars.iter().map(|&h| {
         let ch = BAR_BLOCKS[(h as usize).clamp(0, 8)];
-        let color = if h >= 9 { ERROR } else if h >= 6 { HOT_PINK } else if h >= 3 { PRIMARY } else { ACCENT };
+        let color = if h >= 9 { theme.error } else if h >= 6 { theme.hot_pink } else if h >= 3 { theme.primary } else { theme.accent };
         Span::styled(ch, ratatui::style::Style::default().fg(color))
     }).collect();
     let eq_line = Line::from(eq_spans);
@@ -89,7 +89,7 @@ fn render_compact(f: &mut Frame, area: Rect, state: &AppState) {
     // Progress gauge
     let progress_pct = (state.current_track.progress_percent() * 100.0) as u16;
     let gauge = Gauge::default()
-        .gauge_style(ratatui::style::Style::default().fg(PRIMARY).bg(SURFACE))
+        .gauge_style(ratatui::style::Style::default().fg(theme.primary).bg(theme.surface))
         .percent(progress_pct)
         .label("");
     f.render_widget(gauge, center_chunks[1]);
@@ -97,20 +97,39 @@ fn render_compact(f: &mut Frame, area: Rect, state: &AppState) {
     // Time label
     let time_label = Paragraph::new(Line::from(Span::styled(
         state.current_track.progress_formatted(),
-        dim_style(),
+        theme.dim_style(),
     ))).alignment(Alignment::Center);
     f.render_widget(time_label, center_chunks[2]);
 
     // ── Controls ───────────────────────────────────────────────────
+    let volume_hint = if let Some(ref jump) = state.row_jump {
+        Span::styled(format!("go to row {} ⏎", jump.digits), theme.accent_style())
+    } else if let Some(key) = state.pending_key {
+        Span::styled(format!("{key}…   Esc cancels"), theme.accent_style())
+    } else if let Some(ref visual) = state.visual {
+        Span::styled(format!("VISUAL · {} marked · spc mark · l a P", visual.marked.len()), theme.accent_style())
+    } else if state.connection.offline {
+        let unsynced = match state.connection.outbox.len() {
+            0 => String::new(),
+            n => format!(" · {n} to sync"),
+        };
+        Span::styled(format!("⚠ offline{unsynced}   ? help"), theme.error_style())
+    } else if state.muted_volume.is_some() {
+        Span::styled("🔇 muted · m   ? help", theme.error_style())
+    } else if !state.jobs.is_empty() {
+        Span::styled(format!("⚙ {} job(s) · gb   ? help", state.jobs.running().len()), theme.accent_style())
+    } else {
+        Span::styled("+ vol -  e EQ  z focus  ? help", theme.muted_style())
+    };
     let controls = Paragraph::new(vec![
-        Line::from(Span::styled("⏮ p  ⏸ spc  ⏭ n", dim_style())),
-        Line::from(Span::styled("+ vol -   e EQ   ? help", muted_style())),
+        Line::from(Span::styled("⏮ p  ⏸ spc  ⏭ n", theme.dim_style())),
+        Line::from(volume_hint),
     ]).alignment(Alignment::Right);
     f.render_widget(controls, chunks[2]);
 }
 
 /// Expanded player bar (12 lines) — big vertical EQ + track + progress
-fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_expanded(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -121,7 +140,7 @@ fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
         .split(area);
 
     // ── Track info (left) ───────────────────────────────────────────
-    render_track_info(f, chunks[0], state);
+    render_track_info(f, chunks[0], state, theme);
 
     // ── Vertical EQ visualization (center) ──────────────────────────
     let eq_area = chunks[1];
@@ -142,7 +161,7 @@ fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
         let mut spans: Vec<Span> = Vec::with_capacity(bar_count * 2);
         for i in 0..bar_count {
             let h = state.eq_bars[i];
-            let color = bar_color(h, row_from_bottom);
+            let color = bar_color(theme, h, row_from_bottom);
             let ch = bar_block(h, row_from_bottom);
             spans.push(Span::styled(ch, ratatui::style::Style::default().fg(color)));
             spans.push(Span::styled(" ", ratatui::style::Style::default())); // spacing
@@ -157,7 +176,7 @@ fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
     // Progress gauge
     let progress_pct = (state.current_track.progress_percent() * 100.0) as u16;
     let gauge = Gauge::default()
-        .gauge_style(ratatui::style::Style::default().fg(PRIMARY).bg(SURFACE))
+        .gauge_style(ratatui::style::Style::default().fg(theme.primary).bg(theme.surface))
         .percent(progress_pct)
         .label("");
     f.render_widget(gauge, center[1]);
@@ -165,21 +184,35 @@ fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
     // Time label
     let time_label = Paragraph::new(Line::from(Span::styled(
         state.current_track.progress_formatted(),
-        dim_style(),
+        theme.dim_style(),
     ))).alignment(Alignment::Center);
     f.render_widget(time_label, center[2]);
 
     // ── Controls (right) ────────────────────────────────────────────
     // Right-aligning with uniform padding so the icons line up cleanly
     let controls = Paragraph::new(vec![
-        Line::from(Span::styled("  ⏮ p", dim_style())),
-        Line::from(Span::styled("⏸ spc", dim_style())),
-        Line::from(Span::styled("  ⏭ n", dim_style())),
+        Line::from(Span::styled("  ⏮ p", theme.dim_style())),
+        Line::from(Span::styled("⏸ spc", theme.dim_style())),
+        Line::from(Span::styled("  ⏭ n", theme.dim_style())),
         Line::from(Span::raw("")),
-        Line::from(Span::styled("+ vol -", muted_style())),
-        Line::from(Span::styled("e min EQ", accent_style())),
-        Line::from(Span::styled(" ? help", muted_style())),
-        Line::from(Span::styled(" q quit", muted_style())),
+        if let Some(ref jump) = state.row_jump {
+            Line::from(Span::styled(format!("row {} ⏎", jump.digits), theme.accent_style()))
+        } else if let Some(key) = state.pending_key {
+            Line::from(Span::styled(format!("{key}…"), theme.accent_style()))
+        } else if let Some(ref visual) = state.visual {
+            Line::from(Span::styled(format!("VISUAL {}", visual.marked.len()), theme.accent_style()))
+        } else if state.connection.offline {
+            Line::from(Span::styled("⚠ offline", theme.error_style()))
+        } else if state.muted_volume.is_some() {
+            Line::from(Span::styled("🔇 m", theme.error_style()))
+        } else if !state.jobs.is_empty() {
+            Line::from(Span::styled(format!("⚙ {} gb", state.jobs.running().len()), theme.accent_style()))
+        } else {
+            Line::from(Span::styled("+ vol -", theme.muted_style()))
+        },
+        Line::from(Span::styled("e min EQ", theme.accent_style())),
+        Line::from(Span::styled(" ? help", theme.muted_style())),
+        Line::from(Span::styled(" q quit", theme.muted_style())),
     ]).alignment(Alignment::Right);
     
     // We render in a vertically centered block within the right chunk
@@ -196,33 +229,52 @@ fn render_expanded(f: &mut Frame, area: Rect, state: &AppState) {
     f.render_widget(controls, right_chunk[1]);
 }
 
-fn render_track_info(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_track_info(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let track = &state.current_track;
     let liked_icon = if track.is_liked { "❤ " } else { "♡ " };
-    let liked_style = if track.is_liked { gold_style() } else { muted_style() };
+    let liked_style = if track.is_liked { theme.gold_style() } else { theme.muted_style() };
     let play_icon = if track.is_playing { "▶" } else { "⏸" };
 
     let title_display = state.get_display_title(area.width.saturating_sub(6) as usize);
     let artist = track.artists.join(", ");
     let album = &track.album;
 
+    // A new track slides in from the left while fading up from the muted color
+    let t = state.anims.track_change.progress();
+    let indent = " ".repeat(anim::slide(3, t) as usize);
+    let title_style = theme.normal_style()
+        .fg(anim::fade(theme.text_muted, theme.text, t))
+        .add_modifier(ratatui::style::Modifier::BOLD);
+
     let mut lines = vec![
         Line::from(vec![
-            Span::styled(format!("{play_icon} "), playing_style()),
-            Span::styled(title_display, normal_style().add_modifier(ratatui::style::Modifier::BOLD)),
+            Span::styled(format!("{play_icon} "), theme.playing_style()),
+            Span::raw(indent.clone()),
+            Span::styled(title_display, title_style),
             Span::styled(format!(" {liked_icon}"), liked_style),
         ]),
-        Line::from(Span::styled(
-            if artist.is_empty() { "—".to_string() } else { artist },
-            dim_style(),
-        )),
+        Line::from(vec![
+            Span::raw(indent),
+            Span::styled(
+                if artist.is_empty() { "—".to_string() } else { artist },
+                theme.dim_style().fg(anim::fade(theme.text_muted, theme.text_dim, t)),
+            ),
+        ]),
     ];
 
     // Show album in expanded mode if there's space
     if area.height >= 4 && !album.is_empty() {
         lines.push(Line::from(Span::styled(
             format!("💿 {album}"),
-            muted_style(),
+            theme.muted_style(),
+        )));
+    }
+
+    // Volume leveling badge, with the nudge currently applied to this track
+    if state.replay_gain {
+        lines.push(Line::from(Span::styled(
+            format!("≈ leveling {:+}%", state.replay_gain_offset),
+            theme.accent_style(),
         )));
     }
 
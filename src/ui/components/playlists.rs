@@ -1,64 +1,86 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, List, ListItem, Paragraph, Row, Table},
+    widgets::{Block, BorderType, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState},
     Frame,
 };
-use rspotify::model::PlayableItem;
+use rspotify::{model::PlayableItem, prelude::Id};
 
 use crate::app::state::AppState;
-use super::super::theme::*;
+use super::{list_count, sort_label};
+use super::super::{centered_rect, text::{filtered_position, format_duration, row_number, viewport_offset}, theme::Theme};
 
-pub fn render_playlists(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render_playlists(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
         .split(area);
 
-    render_playlist_list(f, chunks[0], state);
-    render_playlist_tracks(f, chunks[1], state);
+    render_playlist_list(f, chunks[0], state, theme);
+    render_playlist_tracks(f, chunks[1], state, theme);
 }
 
-fn render_playlist_list(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_playlist_list(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     if state.playlists.is_loading {
-        let para = Paragraph::new(Line::from(Span::styled("  ⠋ Loading playlists...", dim_style())))
-            .block(make_block(" 📋 Playlists ", true));
+        let para = Paragraph::new(Line::from(Span::styled("  ⠋ Loading playlists...", theme.dim_style())))
+            .block(make_block(theme, " 📋 Playlists ", true));
         f.render_widget(para, area);
         return;
     }
 
     let selected = state.playlists.selected_playlist;
+    // The tracks pane takes over `/` once a playlist is open
+    let visible = state.filtered_rows().filter(|_| !state.playlists.viewing_tracks);
     let items: Vec<ListItem> = state
         .playlists
         .playlists
         .iter()
         .enumerate()
+        .filter(|(i, _)| visible.as_ref().is_none_or(|rows| rows.binary_search(i).is_ok()))
         .map(|(i, pl)| {
             let is_sel = i == selected;
             let name = pl.name.clone();
             let count = pl.tracks.total;
-            let icon = if is_sel { "▶" } else { " " };
-            let line = Line::from(vec![
-                Span::styled(format!("{icon} "), if is_sel { playing_style() } else { muted_style() }),
-                Span::styled(name, if is_sel { selected_style() } else { normal_style() }),
-                Span::styled(format!("  {count}"), muted_style()),
-            ]);
+            // Marked in visual select, for X to archive
+            let marked = state.visual.as_ref().is_some_and(|v| v.contains(&pl.id.uri()));
+            let (icon, icon_style) = if marked {
+                ("●", theme.hot_pink_style())
+            } else if is_sel {
+                ("▶", theme.playing_style())
+            } else {
+                (" ", theme.muted_style())
+            };
+            let mut spans = vec![
+                Span::styled(format!("{icon} "), icon_style),
+                Span::styled(name, if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!("  {count}"), theme.muted_style()),
+            ];
+            if state.playlists.updated.contains(pl.id.id()) {
+                spans.push(Span::styled("  ● updated", theme.hot_pink_style()));
+            }
+            let line = Line::from(spans);
             if is_sel {
-                ListItem::new(line).style(selected_style())
+                ListItem::new(line).style(theme.selected_style())
             } else {
                 ListItem::new(line)
             }
         })
         .collect();
 
-    let list = List::new(items).block(make_block(
-        &format!(" 📋 Playlists ({}) ", state.playlists.playlists.len()),
+    let list = List::new(items).block(make_block(theme, 
+        &format!(" 📋 Playlists ({}) ", if state.playlists.viewing_tracks {
+            state.playlists.playlists.len().to_string()
+        } else {
+            list_count(state, state.playlists.playlists.len(), visible.as_deref())
+        }),
         !state.playlists.viewing_tracks,
     ));
-    f.render_widget(list, area);
+    let (pos, len) = filtered_position(selected, state.playlists.playlists.len(), visible.as_deref());
+    let offset = viewport_offset(pos, len, area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
 }
 
-fn render_playlist_tracks(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_playlist_tracks(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let playlist_name = state
         .playlists
         .playlists
@@ -72,39 +94,42 @@ fn render_playlist_tracks(f: &mut Frame, area: Rect, state: &AppState) {
         } else {
             "  Select a playlist to see its tracks (Enter)"
         };
-        let para = Paragraph::new(Line::from(Span::styled(msg, muted_style())))
-            .block(make_block(&format!(" 🎵 {playlist_name} "), false));
+        let para = Paragraph::new(Line::from(Span::styled(msg, theme.muted_style())))
+            .block(make_block(theme, &format!(" 🎵 {playlist_name} "), false));
         f.render_widget(para, area);
         return;
     }
 
     let selected = state.playlists.selected_track;
+    let visible = state.filtered_rows().filter(|_| state.playlists.viewing_tracks);
     let rows: Vec<Row> = state
         .playlists
         .playlist_tracks
         .iter()
         .enumerate()
+        .filter(|(i, _)| visible.as_ref().is_none_or(|rows| rows.binary_search(i).is_ok()))
         .filter_map(|(i, item)| {
             if let Some(PlayableItem::Track(track)) = &item.track {
                 let is_sel = i == selected;
-                let dur_ms = track.duration.num_milliseconds() as u32;
-                let secs = dur_ms / 1000;
-                let dur = format!("{}:{:02}", secs / 60, secs % 60);
+                let dur = format_duration(track.duration.num_milliseconds() as u32);
                 let artist = track
                     .artists
                     .iter()
                     .map(|a| a.name.clone())
                     .collect::<Vec<_>>()
                     .join(", ");
-                let num = if is_sel { "▶".to_string() } else { format!("{}", i + 1) };
-                let style = if is_sel { selected_style() } else { normal_style() };
+                let num = row_number(i + 1, 0, is_sel, state.is_marked(track.id.as_ref()));
+                let style = if is_sel { theme.selected_style() } else { theme.normal_style() };
                 Some(
                     Row::new(vec![
                         Cell::from(num)
-                            .style(if is_sel { playing_style() } else { muted_style() }),
-                        Cell::from(track.name.clone()).style(style.clone()),
-                        Cell::from(artist).style(dim_style()),
-                        Cell::from(dur).style(muted_style()),
+                            .style(if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                        Cell::from(Line::from(vec![
+                            Span::styled(track.name.clone(), style.clone()),
+                            Span::styled(if state.is_liked(track) { " ❤" } else { "" }, theme.gold_style()),
+                        ])),
+                        Cell::from(artist).style(theme.dim_style()),
+                        Cell::from(dur).style(theme.muted_style()),
                     ])
                     .style(style),
                 )
@@ -115,10 +140,10 @@ fn render_playlist_tracks(f: &mut Frame, area: Rect, state: &AppState) {
         .collect();
 
     let header = Row::new(vec![
-        Cell::from("#").style(header_style()),
-        Cell::from("Title").style(header_style()),
-        Cell::from("Artist").style(header_style()),
-        Cell::from("Dur").style(header_style()),
+        Cell::from("#").style(theme.header_style()),
+        Cell::from("Title").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Dur").style(theme.header_style()),
     ]);
 
     let table = Table::new(
@@ -131,20 +156,67 @@ fn render_playlist_tracks(f: &mut Frame, area: Rect, state: &AppState) {
         ],
     )
     .header(header)
-    .block(make_block(
-        &format!(" 🎵 {} ", playlist_name),
+    .block(make_block(theme, 
+        &match state.shown_filter().filter(|_| state.playlists.viewing_tracks) {
+            Some(_) => format!(
+                " 🎵 {playlist_name} ({}){}{} ",
+                list_count(state, state.playlists.playlist_tracks.len(), visible.as_deref()),
+                sort_label(state.playlists.sort),
+                paging_label(state),
+            ),
+            None => format!(" 🎵 {}{}{} ", playlist_name, sort_label(state.playlists.sort), paging_label(state)),
+        },
         state.playlists.viewing_tracks,
     ))
-    .row_highlight_style(selected_style());
+    .row_highlight_style(theme.selected_style());
 
-    f.render_widget(table, area);
+    let (pos, len) = filtered_position(selected, state.playlists.playlist_tracks.len(), visible.as_deref());
+    let offset = viewport_offset(pos, len, area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
 }
 
-fn make_block(title: &str, focused: bool) -> Block<'static> {
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
     Block::default()
-        .title(Span::styled(title.to_string(), title_style()))
+        .title(Span::styled(title.to_string(), theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(focused))
-        .style(normal_style().bg(BG))
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
+}
+
+/// Popup listing tracks added/removed since the playlist was last opened
+pub fn render_playlist_diff(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some((name, diff)) = &state.playlists.diff else { return };
+    let popup = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup);
+
+    let mut lines = vec![Line::from(Span::raw(""))];
+    if !diff.added.is_empty() {
+        lines.push(Line::from(Span::styled(format!("  Added ({})", diff.added.len()), theme.playing_style())));
+        lines.extend(diff.added.iter().map(|t| Line::from(Span::styled(format!("   + {t}"), theme.normal_style()))));
+        lines.push(Line::from(Span::raw("")));
+    }
+    if !diff.removed.is_empty() {
+        lines.push(Line::from(Span::styled(format!("  Removed ({})", diff.removed.len()), theme.error_style())));
+        lines.extend(diff.removed.iter().map(|t| Line::from(Span::styled(format!("   − {t}"), theme.dim_style()))));
+    }
+
+    let block = Block::default()
+        .title(Span::styled(format!(" 📋 {name} — changes since last visit "), theme.title_style()))
+        .title_bottom(Span::styled(" u / Esc to close ", theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style());
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
+
+/// ` · ⠋ 100/730 loaded` while the rest of a prefetched playlist comes in
+fn paging_label(state: &AppState) -> String {
+    let playlists = &state.playlists;
+    if !playlists.paging_tracks {
+        return String::new();
+    }
+    let total = playlists.playlists.get(playlists.selected_playlist).map_or(0, |p| p.tracks.total);
+    format!(" · ⠋ {}/{total} loaded", playlists.playlist_tracks.len())
 }
@@ -0,0 +1,35 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, theme::Theme};
+
+/// Small centered text-input popup (e.g. new playlist name)
+pub fn render_prompt(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref prompt) = state.prompt else { return };
+
+    let width = area.width.saturating_sub(4).min(60);
+    let popup = centered(area, width, 3);
+    f.render_widget(Clear, popup);
+
+    let cursor = if (state.eq_tick / 5).is_multiple_of(2) { "│" } else { " " };
+    let para = Paragraph::new(Line::from(vec![
+        Span::styled(" ", theme.muted_style()),
+        Span::styled(prompt.input.clone(), theme.accent_style()),
+        Span::styled(cursor, theme.hot_pink_style()),
+    ]))
+    .block(
+        Block::default()
+            .title(Span::styled(prompt.kind.title(), theme.title_style()))
+            .title_bottom(Span::styled(" Enter ✓  Esc ✗ ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    f.render_widget(para, popup);
+}
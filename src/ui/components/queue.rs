@@ -1,17 +1,19 @@
+use chrono::{Local, TimeDelta};
 use ratatui::{
     layout::{Constraint, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
     Frame,
 };
 
-use crate::app::state::AppState;
-use super::super::theme::*;
+use crate::app::state::{queue_rows, AppState, QueueRow};
+use super::list_count;
+use super::super::{text::{filtered_position, format_duration, viewport_offset}, theme::Theme};
 
-pub fn render_queue(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render_queue(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     if state.queue.is_loading {
-        let para = Paragraph::new(Line::from(Span::styled("  ⠋ Loading queue...", dim_style())))
-            .block(make_block(" 🎵 Queue ", true));
+        let para = Paragraph::new(Line::from(Span::styled("  ⠋ Loading queue...", theme.dim_style())))
+            .block(make_block(theme, " 🎵 Queue ", true));
         f.render_widget(para, area);
         return;
     }
@@ -19,64 +21,99 @@ pub fn render_queue(f: &mut Frame, area: Rect, state: &AppState) {
     if state.queue.tracks.is_empty() {
         let para = Paragraph::new(Line::from(Span::styled(
             "  Queue is empty. Press [a] on any track to add it.",
-            muted_style(),
+            theme.muted_style(),
         )))
-        .block(make_block(" 🎵 Queue ", false));
+        .block(make_block(theme, " 🎵 Queue ", false));
         f.render_widget(para, area);
         return;
     }
 
     let selected = state.queue.selected;
-    let rows: Vec<Row> = state
-        .queue
-        .tracks
+    // Recomputed every frame, so the times follow the current track's progress
+    let now = Local::now();
+    let starts = state.queue_start_offsets();
+    let groups = state.queue.groups();
+    let queue_rows = queue_rows(&groups, &state.queue.collapsed);
+    let visible = state.filtered_rows();
+    let rows: Vec<Row> = queue_rows
         .iter()
         .enumerate()
-        .map(|(i, track)| {
-            let is_sel = i == selected;
-            let dur_ms = track.duration.num_milliseconds() as u32;
-            let secs = dur_ms / 1000;
-            let dur = format!("{}:{:02}", secs / 60, secs % 60);
+        .filter(|(row, _)| visible.as_ref().is_none_or(|rows| rows.binary_search(row).is_ok()))
+        .map(|(row, entry)| {
+            let is_sel = row == selected;
+            let i = match *entry {
+                QueueRow::Track(i) => i,
+                QueueRow::Group(g) => {
+                    let (source, range) = &groups[g];
+                    let fold = if state.queue.collapsed.contains(source) { "▸" } else { "▾" };
+                    let total: i64 = state.queue.tracks[range.clone()].iter().map(|t| t.duration.num_milliseconds()).sum();
+                    let style = if is_sel { theme.selected_style() } else { theme.hot_pink_style() };
+                    return Row::new(vec![
+                        Cell::from(fold).style(style),
+                        Cell::from(source.label()).style(style),
+                        Cell::from(format!("{} tracks", range.len())).style(theme.muted_style()),
+                        Cell::from(format_duration(total.max(0) as u32)).style(theme.muted_style()),
+                        Cell::from(""),
+                    ])
+                    .style(if is_sel { theme.selected_style() } else { theme.normal_style() });
+                }
+            };
+            let track = &state.queue.tracks[i];
+            let eta = if state.current_track.is_playing {
+                let at = now + TimeDelta::milliseconds(starts[i] as i64);
+                format!("~{}", at.format("%H:%M"))
+            } else {
+                "paused".to_string()
+            };
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
             let artist = track
                 .artists
                 .iter()
                 .map(|a| a.name.clone())
                 .collect::<Vec<_>>()
                 .join(", ");
+            let marked = if state.is_marked(track.id.as_ref()) { "●" } else { "" };
             let prefix = if i == 0 {
-                "Next ▶".to_string()
-            } else {
+                format!("Next ▶{marked}")
+            } else if marked.is_empty() {
                 format!("{}", i + 1)
+            } else {
+                marked.to_string()
             };
             let style = if is_sel {
-                selected_style()
+                theme.selected_style()
             } else if i == 0 {
-                accent_style()
+                theme.accent_style()
             } else {
-                normal_style()
+                theme.normal_style()
             };
             let num_style = if is_sel {
-                playing_style()
+                theme.playing_style()
             } else if i == 0 {
-                accent_style()
+                theme.accent_style()
             } else {
-                muted_style()
+                theme.muted_style()
             };
             Row::new(vec![
                 Cell::from(prefix).style(num_style),
-                Cell::from(track.name.clone()).style(style.clone()),
-                Cell::from(artist).style(dim_style()),
-                Cell::from(dur).style(muted_style()),
+                Cell::from(Line::from(vec![
+                    Span::styled(track.name.clone(), style.clone()),
+                    Span::styled(if state.is_liked(track) { " ❤" } else { "" }, theme.gold_style()),
+                ])),
+                Cell::from(artist).style(theme.dim_style()),
+                Cell::from(dur).style(theme.muted_style()),
+                Cell::from(eta).style(theme.dim_style()),
             ])
             .style(style)
         })
         .collect();
 
     let header = Row::new(vec![
-        Cell::from("#").style(header_style()),
-        Cell::from("Title").style(header_style()),
-        Cell::from("Artist").style(header_style()),
-        Cell::from("Dur").style(header_style()),
+        Cell::from("#").style(theme.header_style()),
+        Cell::from("Title").style(theme.header_style()),
+        Cell::from("Artist").style(theme.header_style()),
+        Cell::from("Dur").style(theme.header_style()),
+        Cell::from("Plays at").style(theme.header_style()),
     ]);
 
     let table = Table::new(
@@ -84,25 +121,31 @@ pub fn render_queue(f: &mut Frame, area: Rect, state: &AppState) {
         [
             Constraint::Length(7),
             Constraint::Percentage(40),
-            Constraint::Percentage(40),
+            Constraint::Percentage(35),
             Constraint::Length(7),
+            Constraint::Length(9),
         ],
     )
     .header(header)
-    .block(make_block(
-        &format!(" 🎵 Queue ({} tracks) ", state.queue.tracks.len()),
+    .block(make_block(theme, 
+        &match state.shown_filter() {
+            Some(_) => format!(" 🎵 Queue ({} rows) ", list_count(state, queue_rows.len(), visible.as_deref())),
+            None => format!(" 🎵 Queue ({} tracks) · Enter folds a group · x clears it ", state.queue.tracks.len()),
+        },
         true,
     ))
-    .row_highlight_style(selected_style());
+    .row_highlight_style(theme.selected_style());
 
-    f.render_widget(table, area);
+    let (pos, len) = filtered_position(selected, queue_rows.len(), visible.as_deref());
+    let offset = viewport_offset(pos, len, area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
 }
 
-fn make_block(title: &str, focused: bool) -> Block<'static> {
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
     Block::default()
-        .title(Span::styled(title.to_string(), title_style()))
+        .title(Span::styled(title.to_string(), theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(focused))
-        .style(normal_style().bg(BG))
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
 }
@@ -0,0 +1,120 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        canvas::{Canvas, Line as CanvasLine},
+        Block, BorderType, Borders, Clear, Paragraph,
+    },
+    Frame,
+};
+
+use crate::app::state::{AppState, RadarTrack, RADAR_AXES};
+use super::super::{centered, text::truncate, theme::Theme};
+
+/// Corners of a radar polygon: one value per axis, the first pointing up,
+/// the rest going clockwise, at distance `value` from the center
+pub fn radar_vertices(values: &[f64]) -> Vec<(f64, f64)> {
+    let n = values.len().max(1) as f64;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let angle = std::f64::consts::FRAC_PI_2 - i as f64 * std::f64::consts::TAU / n;
+            (v * angle.cos(), v * angle.sin())
+        })
+        .collect()
+}
+
+/// Closed outline through `points`
+fn polygon(points: &[(f64, f64)], color: Color) -> Vec<CanvasLine> {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(&(x1, y1), &(x2, y2))| CanvasLine { x1, y1, x2, y2, color })
+        .collect()
+}
+
+/// `i` popup — one radar per track, highlighted track first
+pub fn render_radar(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref radar) = state.radar else { return };
+
+    let width = area.width.saturating_sub(4).min(44 * radar.tracks.len() as u16 + 2);
+    let height = area.height.saturating_sub(2).min(22);
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(Span::styled(" ✦ Audio features ", theme.title_style()))
+        .title_bottom(Span::styled(" Esc to close ", theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style().bg(theme.surface));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, radar.tracks.len() as u32); radar.tracks.len()])
+        .split(inner);
+    // Highlighted track in the accent color, the playing one in pink
+    let colors = [theme.accent, theme.hot_pink];
+    for (i, (track, col)) in radar.tracks.iter().zip(columns.iter()).enumerate() {
+        let label = if i == 0 && radar.tracks.len() > 1 { "selected" } else if i > 0 { "playing" } else { "" };
+        render_chart(f, *col, track, label, colors[i.min(1)], theme);
+    }
+}
+
+fn render_chart(f: &mut Frame, area: Rect, track: &RadarTrack, label: &str, color: Color, theme: &Theme) {
+    let title = truncate(&track.name, area.width.saturating_sub(label.len() as u16 + 6) as usize);
+    let mut title_spans = vec![Span::styled(format!(" {title} "), Style::default().fg(color))];
+    if !label.is_empty() {
+        title_spans.push(Span::styled(format!("{label} "), theme.muted_style()));
+    }
+    let block = Block::default()
+        .title(Line::from(title_spans))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain)
+        .border_style(theme.border_style(false));
+
+    let Some(profile) = track.profile else {
+        let msg = if track.loaded { "Spotify has no audio features for this track" } else { "Loading…" };
+        let para = Paragraph::new(Span::styled(msg, theme.muted_style()))
+            .alignment(Alignment::Center)
+            .block(block);
+        f.render_widget(para, area);
+        return;
+    };
+
+    let grid = theme.text_muted;
+    let outer = radar_vertices(&[1.0; 5]);
+    let canvas = Canvas::default()
+        .block(block)
+        .marker(Marker::Braille)
+        .x_bounds([-1.6, 1.6])
+        .y_bounds([-1.3, 1.3])
+        .paint(move |ctx| {
+            for line in polygon(&radar_vertices(&[0.5; 5]), grid) {
+                ctx.draw(&line);
+            }
+            for line in polygon(&outer, grid) {
+                ctx.draw(&line);
+            }
+            for &(x, y) in &outer {
+                ctx.draw(&CanvasLine { x1: 0.0, y1: 0.0, x2: x, y2: y, color: grid });
+            }
+            ctx.layer();
+            for line in polygon(&radar_vertices(&profile.0), color) {
+                ctx.draw(&line);
+            }
+            for (axis, (&(x, y), value)) in RADAR_AXES.iter().zip(outer.iter().zip(profile.0)) {
+                let text = format!("{axis} {:.0}", value * 100.0);
+                // Nudge left-side labels so they end at the axis tip
+                let x = if x < -0.1 { x * 1.1 - text.len() as f64 * 0.06 } else { x * 1.1 };
+                ctx.print(x, y * 1.12, Line::from(Span::styled(text, Style::default().fg(color))));
+            }
+        });
+    f.render_widget(canvas, area);
+}
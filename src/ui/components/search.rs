@@ -1,14 +1,16 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
-use crate::app::state::AppState;
-use super::super::theme::*;
+use rspotify::prelude::Id;
 
-pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
+use crate::app::state::{release_year, AlbumRow, AppState, SearchTab};
+use super::super::{text::{format_duration, viewport_offset}, theme::Theme};
+
+pub fn render_search(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -21,21 +23,26 @@ pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
     let input_focused = state.search.is_searching;
     let cursor = if input_focused && (state.eq_tick / 5) % 2 == 0 { "│" } else { "" };
     let input_block = Block::default()
-        .title(Span::styled(" 󰍉 Search Spotify ", title_style()))
+        .title(Span::styled(" 󰍉 Search Spotify ", theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(false))
-        .style(normal_style());
+        .border_style(theme.border_style(false))
+        .style(theme.normal_style());
 
     let input_text = Paragraph::new(Line::from(vec![
-        Span::styled(" ", muted_style()),
-        Span::styled(state.search.query.clone(), accent_style()),
-        Span::styled(cursor, hot_pink_style()),
+        Span::styled(" ", theme.muted_style()),
+        Span::styled(state.search.query.clone(), theme.accent_style()),
+        Span::styled(cursor, theme.hot_pink_style()),
     ]))
     .block(input_block);
     f.render_widget(input_text, chunks[0]);
 
     // ── Results ───────────────────────────────────────────────────────────
+    match state.search.tab {
+        SearchTab::Tracks => {}
+        SearchTab::Playlists => return render_playlist_results(f, chunks[1], state, theme, !input_focused),
+        SearchTab::Albums => return render_album_results(f, chunks[1], state, theme, !input_focused),
+    }
     if state.search.tracks.is_empty() {
         let placeholder = if state.search.query.is_empty() {
             "  Press [s] to search, type a query, then Enter..."
@@ -44,14 +51,14 @@ pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
         } else {
             "  No results found."
         };
-        let para = Paragraph::new(Line::from(Span::styled(placeholder, muted_style())))
+        let para = Paragraph::new(Line::from(Span::styled(placeholder, theme.muted_style())))
             .block(
                 Block::default()
-                    .title(Span::styled(" Results ", dim_style()))
+                    .title(Span::styled(" Results ", theme.dim_style()))
                     .borders(Borders::ALL)
                     .border_type(BorderType::Rounded)
-                    .border_style(border_style(false))
-                    .style(normal_style().bg(BG)),
+                    .border_style(theme.border_style(false))
+                    .style(theme.normal_style().bg(theme.bg)),
             );
         f.render_widget(para, chunks[1]);
         return;
@@ -65,7 +72,8 @@ pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
         .enumerate()
         .map(|(i, track)| {
             let is_sel = i == selected;
-            let num = format!("{:>3}. ", i + 1);
+            let marked = state.is_marked(track.id.as_ref());
+            let num = if marked { "  ●  ".to_string() } else { format!("{:>3}. ", i + 1) };
             let title = track.name.clone();
             let artist = track
                 .artists
@@ -74,30 +82,31 @@ pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
                 .collect::<Vec<_>>()
                 .join(", ");
             let album = track.album.name.clone();
-            let dur_ms = track.duration.num_milliseconds() as u32;
-            let dur_s = dur_ms / 1000;
-            let dur = format!("{}:{:02}", dur_s / 60, dur_s % 60);
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
+            let heart = if state.is_liked(track) { " ❤" } else { "" };
 
             let line = if is_sel {
                 Line::from(vec![
-                    Span::styled("▶ ", playing_style()),
-                    Span::styled(title, selected_style()),
-                    Span::styled(" — ", muted_style()),
-                    Span::styled(artist, dim_style()),
-                    Span::styled(format!("  {dur}"), muted_style()),
+                    Span::styled(if marked { "▶●" } else { "▶ " }, theme.playing_style()),
+                    Span::styled(title, theme.selected_style()),
+                    Span::styled(heart, theme.gold_style()),
+                    Span::styled(" — ", theme.muted_style()),
+                    Span::styled(artist, theme.dim_style()),
+                    Span::styled(format!("  {dur}"), theme.muted_style()),
                 ])
             } else {
                 Line::from(vec![
-                    Span::styled(num, muted_style()),
-                    Span::styled(title, normal_style()),
-                    Span::styled(" — ", muted_style()),
-                    Span::styled(artist, dim_style()),
-                    Span::styled(format!("  {album}  {dur}"), muted_style()),
+                    Span::styled(num, if marked { theme.accent_style() } else { theme.muted_style() }),
+                    Span::styled(title, theme.normal_style()),
+                    Span::styled(heart, theme.gold_style()),
+                    Span::styled(" — ", theme.muted_style()),
+                    Span::styled(artist, theme.dim_style()),
+                    Span::styled(format!("  {album}  {dur}"), theme.muted_style()),
                 ])
             };
 
             if is_sel {
-                ListItem::new(line).style(selected_style())
+                ListItem::new(line).style(theme.selected_style())
             } else {
                 ListItem::new(line)
             }
@@ -109,14 +118,124 @@ pub fn render_search(f: &mut Frame, area: Rect, state: &AppState) {
             Block::default()
                 .title(Span::styled(
                     format!(" Results ({}) ", state.search.tracks.len()),
-                    title_style(),
+                    theme.title_style(),
+                ))
+                .title_bottom(Span::styled(
+                    format!(" Tab: playlists ({}) ", state.search.playlists.len()),
+                    theme.muted_style(),
                 ))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(border_style(!input_focused))
-                .style(normal_style().bg(BG)),
+                .border_style(theme.border_style(!input_focused))
+                .style(theme.normal_style().bg(theme.bg)),
         )
-        .highlight_style(selected_style());
+        .highlight_style(theme.selected_style());
+
+    let offset = viewport_offset(selected, state.search.tracks.len(), chunks[1].height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, chunks[1], &mut ListState::default().with_offset(offset));
+}
+
+/// Playlist hits for the query; F follows the highlighted one
+fn render_playlist_results(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme, focused: bool) {
+    let selected = state.search.selected_playlist;
+    let items: Vec<ListItem> = state
+        .search
+        .playlists
+        .iter()
+        .enumerate()
+        .map(|(i, pl)| {
+            let is_sel = i == selected;
+            let owner = pl.owner.display_name.clone().unwrap_or_else(|| pl.owner.id.id().to_string());
+            let following = state.playlists.playlists.iter().any(|p| p.id == pl.id);
+            let mut spans = vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(pl.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!(" — {owner}"), theme.dim_style()),
+                Span::styled(format!("  {} tracks", pl.tracks.total), theme.muted_style()),
+            ];
+            if following {
+                spans.push(Span::styled("  ✓ following", theme.gold_style()));
+            }
+            let line = Line::from(spans);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(
+                format!(" Playlists ({}) ", state.search.playlists.len()),
+                theme.title_style(),
+            ))
+            .title_bottom(Span::styled(
+                format!(" F follow · Tab: albums ({}) ", state.search.albums.len()),
+                theme.muted_style(),
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(focused))
+            .style(theme.normal_style().bg(theme.bg)),
+    );
+    let offset = viewport_offset(selected, state.search.playlists.len(), area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
+}
+
+/// Album hits, optionally sorted by release date (S) under decade headers
+/// and narrowed to one decade (`:decade 90s`)
+fn render_album_results(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme, focused: bool) {
+    let search = &state.search;
+    let rows = search.album_rows();
+    let selected = search.album_order().get(search.selected_album).copied();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|row| {
+            let i = match *row {
+                AlbumRow::Decade(decade) => {
+                    let label = decade.map_or_else(|| "Unknown date".to_string(), |d| format!("{d}s"));
+                    return ListItem::new(Line::from(Span::styled(format!(" ── {label} "), theme.accent_style())));
+                }
+                AlbumRow::Album(i) => i,
+            };
+            let album = &search.albums[i];
+            let is_sel = Some(i) == selected;
+            let artist = album.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", ");
+            let year = release_year(album).map(|y| y.to_string()).unwrap_or_else(|| "—".to_string());
+            let kind = album.album_type.as_deref().filter(|t| *t != "album").map(|t| format!(" · {t}")).unwrap_or_default();
+            let line = Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(format!("{year}  "), theme.muted_style()),
+                Span::styled(album.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(format!(" — {artist}"), theme.dim_style()),
+                Span::styled(kind, theme.muted_style()),
+            ]);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
 
-    f.render_widget(list, chunks[1]);
+    let shown = rows.iter().filter(|r| matches!(r, AlbumRow::Album(_))).count();
+    let count = match search.decade {
+        Some(d) => format!("{shown}/{} · {d}s", search.albums.len()),
+        None => shown.to_string(),
+    };
+    let sort = search.album_sort.label().map(|label| format!(" · by {label}")).unwrap_or_default();
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" Albums ({count}){sort} "), theme.title_style()))
+            .title_bottom(Span::styled(" S sort · :decade · Tab: tracks ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(focused))
+            .style(theme.normal_style().bg(theme.bg)),
+    );
+    let line = rows.iter().position(|r| Some(*r) == selected.map(AlbumRow::Album)).unwrap_or(0);
+    let offset = viewport_offset(line, rows.len(), area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
 }
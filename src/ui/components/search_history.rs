@@ -0,0 +1,49 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::app::state::AppState;
+use super::super::{centered, text::viewport_offset, theme::Theme};
+
+/// `gs` popup — past searches, newest first
+pub fn render_search_history(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let search = &state.search;
+
+    let width = area.width.saturating_sub(4).min(60);
+    let height = (search.history.len() as u16 + 2).clamp(3, area.height.saturating_sub(2).max(3));
+    let popup = centered(area, width, height);
+    f.render_widget(Clear, popup);
+
+    let items: Vec<ListItem> = search
+        .history
+        .iter()
+        .enumerate()
+        .map(|(i, query)| {
+            let is_sel = i == search.selected_history;
+            let line = Line::from(vec![
+                Span::styled(if is_sel { "▶ " } else { "  " }, theme.playing_style()),
+                Span::styled(query.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+            ]);
+            if is_sel {
+                ListItem::new(line).style(theme.selected_style())
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .title(Span::styled(format!(" ⌕ Past searches ({}) ", search.history.len()), theme.title_style()))
+            .title_bottom(Span::styled(" Enter search again · x forget · Esc ", theme.muted_style()))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style().bg(theme.surface)),
+    );
+    let offset = viewport_offset(search.selected_history, search.history.len(), popup.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_offset(offset));
+}
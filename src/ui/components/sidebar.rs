@@ -7,23 +7,34 @@ use ratatui::{
 };
 
 use crate::app::state::{ActiveScreen, AppState};
-use super::super::theme::*;
+use crate::config::{SidebarPanel, Visualizer};
+use crate::lyrics::current_line;
+use crate::quotes::BUILTIN;
+use super::super::{anim, text::truncate, theme::Theme};
 
-const NAV_ITEMS: &[(&str, &str, ActiveScreen)] = &[
-    ("1", "󰍉  Search",      ActiveScreen::Search),
-    ("2", "❤  Liked Songs", ActiveScreen::Library),
-    ("3", "📋  Playlists",   ActiveScreen::Playlists),
-    ("4", "🎵  Queue",       ActiveScreen::Queue),
-    ("5", "🌊  Vibes",       ActiveScreen::Vibes),
-];
+fn nav_label(screen: &ActiveScreen) -> &'static str {
+    match screen {
+        ActiveScreen::Search => "󰍉  Search",
+        ActiveScreen::Library => "❤  Liked Songs",
+        ActiveScreen::Playlists => "📋  Playlists",
+        ActiveScreen::Queue => "🎵  Queue",
+        ActiveScreen::Vibes => "🌊  Vibes",
+        ActiveScreen::Stats => "📊  Stats",
+        ActiveScreen::Browse => "🧭  Browse",
+    }
+}
 
-pub fn render_sidebar(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render_sidebar(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let mut title = vec![Span::styled(" 🎵 vibes ", theme.title_style())];
+    if state.connection.offline {
+        title.push(Span::styled("⚠ offline ", theme.error_style()));
+    }
     let block = Block::default()
-        .title(Span::styled(" 🎵 vibes ", title_style()))
+        .title(Line::from(title))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(false))
-        .style(normal_style());
+        .border_style(theme.border_style(false))
+        .style(theme.normal_style());
 
     let inner = block.inner(area);
     f.render_widget(block, area);
@@ -32,7 +43,7 @@ pub fn render_sidebar(f: &mut Frame, area: Rect, state: &AppState) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(2),  // tagline
-            Constraint::Length(7),  // nav items (5 items + 2 padding)
+            Constraint::Length(state.screens.len() as u16 + 2), // nav items + 2 padding
             Constraint::Length(1),  // separator
             Constraint::Min(0),    // now playing + animation
             Constraint::Length(3), // volume
@@ -41,27 +52,32 @@ pub fn render_sidebar(f: &mut Frame, area: Rect, state: &AppState) {
 
     // ── Tagline ────────────────────────────────────────
     let tagline = Paragraph::new(Line::from(vec![
-        Span::styled(" your terminal, your ", dim_style()),
-        Span::styled("vibe", hot_pink_style()),
+        Span::styled(" your terminal, your ", theme.dim_style()),
+        Span::styled("vibe", theme.hot_pink_style()),
     ]));
     f.render_widget(tagline, chunks[0]);
 
     // ── Nav items ──────────────────────────────────────
-    let items: Vec<ListItem> = NAV_ITEMS
+    let items: Vec<ListItem> = state
+        .screens
         .iter()
-        .map(|(key, label, screen)| {
+        .enumerate()
+        .map(|(i, screen)| {
+            let (key, label) = (i + 1, nav_label(screen));
             let is_active = &state.active_screen == screen;
             let prefix = if is_active { " ▶ " } else { "   " };
             
-            // Clearer focus indicator with background color
+            // Clearer focus indicator with background color, which fades in
+            // after a screen switch
+            let highlight = anim::fade(theme.surface_sel, theme.hot_pink, state.anims.screen_switch.progress());
             let style = if is_active { 
-                Style::default().fg(BG).bg(HOT_PINK).add_modifier(Modifier::BOLD)
+                Style::default().fg(theme.bg).bg(highlight).add_modifier(Modifier::BOLD)
             } else { 
-                normal_style()
+                theme.normal_style()
             };
             
             let line = Line::from(vec![
-                Span::styled(prefix, if is_active { Style::default().fg(BG).bg(HOT_PINK) } else { muted_style() }),
+                Span::styled(prefix, if is_active { Style::default().fg(theme.bg).bg(highlight) } else { theme.muted_style() }),
                 Span::styled(format!("[{key}] {label}"), style),
                 // Padding to fill the background block to the edge
                 Span::styled(" ".repeat(area.width.saturating_sub(15) as usize), style)
@@ -77,43 +93,31 @@ pub fn render_sidebar(f: &mut Frame, area: Rect, state: &AppState) {
     let sep_width = chunks[2].width.saturating_sub(4) as usize;
     let sep = Paragraph::new(Line::from(Span::styled(
         format!("  {}", "─".repeat(sep_width)),
-        muted_style(),
+        theme.muted_style(),
     )));
     f.render_widget(sep, chunks[2]);
 
     // ── Now Playing + Animation area ───────────────────
-    render_now_playing_area(f, chunks[3], state);
+    render_now_playing_area(f, chunks[3], state, theme);
 
     // ── Volume bar (bottom) ────────────────────────────
-    render_volume(f, chunks[4], state);
+    render_volume(f, chunks[4], state, theme);
 }
 
-const QUOTES: &[&str] = &[
-    "\"Music is the universal language of mankind.\"\n  – Henry Wadsworth Longfellow",
-    "\"Where words fail, music speaks.\"\n  – Hans Christian Andersen",
-    "\"Without music, life would be a mistake.\"\n  – Friedrich Nietzsche",
-    "\"Music gives a soul to the universe.\"\n  – Plato",
-    "\"One good thing about music, when it hits you, you feel no pain.\"\n  – Bob Marley",
-    "\"Music can change the world because it can change people.\"\n  – Bono",
-    "\"I don't sing because I'm happy; I'm happy because I sing.\"\n  – William James",
-    "\"Music is life itself.\"\n  – Louis Armstrong",
-    "\"Life is like a beautiful melody, only the lyrics are messed up.\"\n  – Hans Christian Andersen",
-    "\"No matter what you're going through, there's a song for that.\"",
-];
-
-fn render_now_playing_area(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_now_playing_area(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     if area.height < 3 {
         return;
     }
 
     let track = &state.current_track;
 
+    let critter_height = if state.visualizer == Visualizer::Off { 0 } else { 7 };
     let sections = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4), // now playing info
-            Constraint::Length(7), // animated animal
-            Constraint::Min(0),    // quote
+            Constraint::Length(4),              // now playing info
+            Constraint::Length(critter_height), // animated animal
+            Constraint::Min(0),                 // quote / lyrics
         ])
         .split(area);
 
@@ -121,70 +125,105 @@ fn render_now_playing_area(f: &mut Frame, area: Rect, state: &AppState) {
     if !track.name.is_empty() {
         let play_icon = if track.is_playing { "▶" } else { "⏸" };
         let liked = if track.is_liked { " ❤" } else { "" };
-        let title = truncate_str(&track.name, area.width.saturating_sub(6) as usize);
-        let artist = truncate_str(
+        let title = truncate(&track.name, area.width.saturating_sub(6) as usize);
+        let artist = truncate(
             &track.artists.join(", "),
             area.width.saturating_sub(4) as usize,
         );
 
         let info = Paragraph::new(vec![
-            Line::from(Span::styled("  ♪ Now Playing", accent_style())),
+            Line::from(Span::styled("  ♪ Now Playing", theme.accent_style())),
             Line::from(vec![
-                Span::styled(format!("  {play_icon} "), playing_style()),
-                Span::styled(title, normal_style().add_modifier(ratatui::style::Modifier::BOLD)),
-                Span::styled(liked, gold_style()),
+                Span::styled(format!("  {play_icon} "), theme.playing_style()),
+                Span::styled(title, theme.normal_style().add_modifier(ratatui::style::Modifier::BOLD)),
+                Span::styled(liked, theme.gold_style()),
             ]),
-            Line::from(Span::styled(format!("    {artist}"), dim_style())),
+            Line::from(Span::styled(format!("    {artist}"), theme.dim_style())),
             Line::from(Span::raw("")),
         ]);
         f.render_widget(info, sections[0]);
     } else {
         let empty = Paragraph::new(vec![
-            Line::from(Span::styled("  ♪ Now Playing", accent_style())),
-            Line::from(Span::styled("    No track", muted_style())),
+            Line::from(Span::styled("  ♪ Now Playing", theme.accent_style())),
+            Line::from(Span::styled("    No track", theme.muted_style())),
             Line::from(Span::raw("")),
         ]);
         f.render_widget(empty, sections[0]);
     }
 
     // ── Animated Visualizer ────────────────────────────
-    render_animal_visualizer(f, sections[1], state);
+    render_animal_visualizer(f, sections[1], state, theme);
 
-    // ── Quote ──────────────────────────────────────────
+    // ── Quote / lyrics ─────────────────────────────────
     if sections[2].height >= 2 {
-        // Pick a stable quote based on tick / 100 so it changes every 8 seconds
-        let quote_idx = ((state.eq_tick / 200) as usize) % QUOTES.len();
-        let quote_text = QUOTES[quote_idx];
-        
-        // Format quote on a single line so Ratatui's Wrap can split it properly
-        let mut lines = vec![Line::from(Span::raw(""))]; // top padding
-        let formatted_quote = format!("   {}", quote_text.replace("\n", " "));
-        lines.push(Line::from(Span::styled(
-            formatted_quote,
-            muted_style().add_modifier(ratatui::style::Modifier::ITALIC),
-        )));
-        
-        // Apply text wrap so it doesn't get cut off
-        let quote_para = Paragraph::new(lines)
-            .alignment(Alignment::Left)
-            .wrap(ratatui::widgets::Wrap { trim: true });
-            
-        f.render_widget(quote_para, sections[2]);
+        match state.sidebar_panel {
+            SidebarPanel::Quotes => render_quote(f, sections[2], state, theme),
+            SidebarPanel::Lyrics => render_lyrics(f, sections[2], state, theme),
+            SidebarPanel::Off => {}
+        }
     }
 }
 
-fn render_animal_visualizer(f: &mut Frame, area: Rect, state: &AppState) {
-    if area.height < 6 || area.width < 15 {
+fn render_quote(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    // Pick a stable quote based on tick / 100 so it changes every 8 seconds
+    let quote_text = if state.quotes.is_empty() {
+        BUILTIN[((state.eq_tick / 200) as usize) % BUILTIN.len()]
+    } else {
+        state.quotes[((state.eq_tick / 200) as usize) % state.quotes.len()].as_str()
+    };
+
+    // Format quote on a single line so Ratatui's Wrap can split it properly
+    let mut lines = vec![Line::from(Span::raw(""))]; // top padding
+    let formatted_quote = format!("   {}", quote_text.replace("\n", " "));
+    lines.push(Line::from(Span::styled(
+        formatted_quote,
+        theme.muted_style().add_modifier(ratatui::style::Modifier::ITALIC),
+    )));
+
+    // Apply text wrap so it doesn't get cut off
+    let quote_para = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+
+    f.render_widget(quote_para, area);
+}
+
+/// Mini lyrics ticker: the line being sung, with the next one dimmed below
+fn render_lyrics(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let mut lines = vec![Line::from(Span::raw(""))];
+    match state.lyrics.as_deref() {
+        Some(lyrics) => {
+            let idx = current_line(lyrics, state.current_track.progress_ms);
+            let now = idx.map(|i| lyrics[i].1.as_str()).filter(|l| !l.is_empty()).unwrap_or("♪");
+            let next = lyrics.get(idx.map_or(0, |i| i + 1)).map(|(_, l)| l.as_str()).unwrap_or("");
+            lines.push(Line::from(Span::styled(format!("   {now}"), theme.accent_style())));
+            lines.push(Line::from(Span::styled(format!("   {next}"), theme.muted_style())));
+        }
+        None if state.current_track.name.is_empty() => {}
+        None => lines.push(Line::from(Span::styled("   ♪ no synced lyrics", theme.muted_style()))),
+    }
+    f.render_widget(
+        Paragraph::new(lines).wrap(ratatui::widgets::Wrap { trim: true }),
+        area,
+    );
+}
+
+fn render_animal_visualizer(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    if area.height < 6 || area.width < 15 || state.visualizer == Visualizer::Off {
         return; // Need space for the animal
     }
 
     let is_playing = state.current_track.is_playing;
     
-    // Animate based on eq_tick
-    let frame = if is_playing { (state.eq_tick / 3) % 4 } else { 0 };
+    // Animate on the beat when tempo sync is on, otherwise every few ticks
+    let frame = if is_playing { state.anim_frame % 4 } else { 0 };
 
-    // Switch between cat and monkey every 15 seconds
-    let show_monkey = (state.eq_tick / 400) % 2 != 0;
+    // Switch between cat and monkey every 15 seconds, unless one is picked
+    let show_monkey = match state.visualizer {
+        Visualizer::Monkey => true,
+        Visualizer::Cat => false,
+        _ => (state.eq_tick / 400) % 2 != 0,
+    };
 
     let animal_art = if show_monkey {
         if !is_playing && state.current_track.name.is_empty() { // Sleeping
@@ -297,9 +336,9 @@ fn render_animal_visualizer(f: &mut Frame, area: Rect, state: &AppState) {
     for (i, line) in animal_art.iter().enumerate() {
         let color = if is_playing && i == 0 { 
             // Color the music notes
-            if frame % 2 == 0 { HOT_PINK } else { ACCENT }
+            if frame % 2 == 0 { theme.hot_pink } else { theme.accent }
         } else {
-            PRIMARY
+            theme.primary
         };
         lines.push(Line::from(Span::styled(format!("  {line}"), Style::default().fg(color))));
     }
@@ -307,7 +346,7 @@ fn render_animal_visualizer(f: &mut Frame, area: Rect, state: &AppState) {
     f.render_widget(Paragraph::new(lines), area);
 }
 
-fn render_volume(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_volume(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let vol = state.volume;
     let bar_width = area.width.saturating_sub(12) as usize; // Extra space for alignment
     let filled = ((vol as f64 / 100.0) * bar_width as f64).round() as usize;
@@ -323,18 +362,18 @@ fn render_volume(f: &mut Frame, area: Rect, state: &AppState) {
         ])
         .split(area);
 
-    let vol_content = chunks_for_volume(vol, filled, empty);
+    let vol_content = chunks_for_volume(theme, vol, filled, empty, state.muted_volume.is_some());
     
     let para = Paragraph::new(vol_content).alignment(Alignment::Left);
     f.render_widget(para, layout[1]);
 }
 
-fn chunks_for_volume(vol: u8, filled: usize, empty: usize) -> Vec<Line<'static>> {
+fn chunks_for_volume(theme: &Theme, vol: u8, filled: usize, empty: usize, muted: bool) -> Vec<Line<'static>> {
     let vol_line = Line::from(vec![
-        Span::styled(" 🔊 ", accent_style()),
-        Span::styled("█".repeat(filled), playing_style()),
-        Span::styled("░".repeat(empty), muted_style()),
-        Span::styled(format!(" {:3}%", vol), dim_style()),
+        if muted { Span::styled(" 🔇 ", theme.error_style()) } else { Span::styled(" 🔊 ", theme.accent_style()) },
+        Span::styled("█".repeat(filled), theme.playing_style()),
+        Span::styled("░".repeat(empty), theme.muted_style()),
+        Span::styled(format!(" {:3}%", vol), theme.dim_style()),
     ]);
 
     vec![
@@ -343,10 +382,3 @@ fn chunks_for_volume(vol: u8, filled: usize, empty: usize) -> Vec<Line<'static>>
     ]
 }
 
-fn truncate_str(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_string()
-    } else {
-        format!("{}…", &s[..max.saturating_sub(1)])
-    }
-}
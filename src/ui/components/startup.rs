@@ -0,0 +1,55 @@
+use ratatui::{
+    layout::Rect,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::app::state::{AppState, LoadStatus};
+use super::super::{text::truncate, theme::Theme};
+
+const WIDTH: u16 = 44;
+
+/// Top-right panel while the library loads after login: one row per load,
+/// its state and a word on it. It doesn't take keys, so the app is usable
+/// underneath
+pub fn render_startup(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let Some(ref startup) = state.startup else { return };
+    let width = WIDTH.min(area.width);
+    let height = (startup.loads.len() as u16 + 2).min(area.height);
+    let popup = Rect { x: area.x + area.width - width, y: area.y, width, height };
+    f.render_widget(Clear, popup);
+
+    let title = if startup.is_done() { " ✓ Loaded " } else { " ⟳ Loading your library… " };
+    let block = Block::default()
+        .title(Span::styled(title, theme.title_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(false))
+        .style(theme.normal_style().bg(theme.surface));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let lines: Vec<Line> = startup
+        .loads
+        .iter()
+        .map(|(load, status, detail)| {
+            let (mark, style) = match status {
+                LoadStatus::Loading => ("… ", theme.accent_style()),
+                LoadStatus::Done => ("✓ ", theme.playing_style()),
+                LoadStatus::Failed => ("✗ ", theme.error_style()),
+            };
+            let label = format!("{:<16}", load.label());
+            let room = (inner.width as usize).saturating_sub(label.width() + 3);
+            let detail_style = if *status == LoadStatus::Failed { theme.error_style() } else { theme.dim_style() };
+            Line::from(vec![
+                Span::raw(" "),
+                Span::styled(mark, style),
+                Span::styled(label, theme.normal_style()),
+                Span::styled(truncate(detail, room), detail_style),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+}
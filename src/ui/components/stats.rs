@@ -0,0 +1,124 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::app::state::{AppState, StatsState, StatsView};
+use crate::session::plays::DayRecap;
+use super::super::{text::{format_duration, row_number, viewport_offset}, theme::Theme};
+
+/// Top tracks or artists for the selected time range, ranked
+pub fn render_stats(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let stats = &state.stats;
+    let card_height = on_this_day_height(stats);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(card_height)])
+        .split(area);
+    let area = chunks[0];
+    if card_height > 0 {
+        render_on_this_day(f, chunks[1], &stats.on_this_day, theme);
+    }
+    let kind = match stats.view {
+        StatsView::Tracks => "Top Tracks",
+        StatsView::Artists => "Top Artists",
+    };
+    let title = format!(" 📊 {kind} · {} · ←/→ range · Tab: {} ", stats.range.label(), match stats.view {
+        StatsView::Tracks => "artists",
+        StatsView::Artists => "tracks",
+    });
+
+    if stats.is_loading || stats.row_count() == 0 {
+        let text = if stats.is_loading { "  ⠋ Loading your stats..." } else { "  Nothing here yet — listen a little more!" };
+        let para = Paragraph::new(Line::from(Span::styled(text, theme.muted_style())))
+            .block(make_block(theme, &title, true));
+        f.render_widget(para, area);
+        return;
+    }
+
+    let selected = stats.selected;
+    let rank = |i: usize, marked: bool| row_number(i + 1, 3, i == selected, marked);
+    let rank_style = |i: usize| if i == selected { theme.playing_style() } else { theme.muted_style() };
+    let row_style = |i: usize| if i == selected { theme.selected_style() } else { theme.normal_style() };
+
+    let (header, rows, widths): (Vec<&str>, Vec<Row>, Vec<Constraint>) = match stats.view {
+        StatsView::Tracks => (
+            vec![" # ", "Title", "Artist", "Dur"],
+            stats.top_tracks.iter().enumerate().map(|(i, track)| {
+                let artist = track.artists.iter().map(|a| a.name.clone()).collect::<Vec<_>>().join(", ");
+                Row::new(vec![
+                    Cell::from(rank(i, state.is_marked(track.id.as_ref()))).style(rank_style(i)),
+                    Cell::from(Line::from(vec![
+                        Span::styled(track.name.clone(), row_style(i)),
+                        Span::styled(if state.is_liked(track) { " ❤" } else { "" }, theme.gold_style()),
+                    ])),
+                    Cell::from(artist).style(theme.dim_style()),
+                    Cell::from(format_duration(track.duration.num_milliseconds() as u32)).style(theme.muted_style()),
+                ])
+                .style(row_style(i))
+            }).collect(),
+            vec![Constraint::Length(4), Constraint::Percentage(50), Constraint::Percentage(40), Constraint::Length(7)],
+        ),
+        StatsView::Artists => (
+            vec![" # ", "Artist", "Genres", "Pop"],
+            stats.top_artists.iter().enumerate().map(|(i, artist)| {
+                let genres = artist.genres.iter().take(3).cloned().collect::<Vec<_>>().join(", ");
+                Row::new(vec![
+                    Cell::from(rank(i, false)).style(rank_style(i)),
+                    Cell::from(artist.name.clone()).style(row_style(i)),
+                    Cell::from(genres).style(theme.dim_style()),
+                    Cell::from(artist.popularity.to_string()).style(theme.muted_style()),
+                ])
+                .style(row_style(i))
+            }).collect(),
+            vec![Constraint::Length(4), Constraint::Percentage(40), Constraint::Percentage(50), Constraint::Length(5)],
+        ),
+    };
+
+    let header = Row::new(header.into_iter().map(|h| Cell::from(h).style(theme.header_style()))).height(1);
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(make_block(theme, &title, true))
+        .row_highlight_style(theme.selected_style());
+
+    let offset = viewport_offset(selected, stats.row_count(), area.height.saturating_sub(3) as usize);
+    f.render_stateful_widget(table, area, &mut TableState::default().with_offset(offset));
+}
+
+/// Rows the "On this day" card takes under the table; 0 hides it
+pub fn on_this_day_height(stats: &StatsState) -> u16 {
+    match stats.on_this_day.len() {
+        0 => 0,
+        n => n.min(3) as u16 + 2,
+    }
+}
+
+/// One line per earlier year with plays on today's date, newest first
+fn render_on_this_day(f: &mut Frame, area: Rect, recaps: &[DayRecap], theme: &Theme) {
+    let lines: Vec<Line> = recaps
+        .iter()
+        .take(3)
+        .map(|r| {
+            let times = if r.top_count > 1 { format!(" ×{}", r.top_count) } else { String::new() };
+            Line::from(vec![
+                Span::styled(format!(" {} ", r.year), theme.accent_style()),
+                Span::styled(format!("{} plays · top: ", r.plays), theme.muted_style()),
+                Span::styled(r.top.track.clone(), theme.normal_style()),
+                Span::styled(format!(" — {}{times}", r.top.artist), theme.dim_style()),
+            ])
+        })
+        .collect();
+    let title = format!(" 📅 On this day · o replays {}'s top track ", recaps[0].year);
+    f.render_widget(Paragraph::new(lines).block(make_block(theme, &title, false)), area);
+}
+
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
+    Block::default()
+        .title(Span::styled(title.to_string(), theme.title_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
+}
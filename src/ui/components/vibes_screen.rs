@@ -1,21 +1,12 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph},
     Frame,
 };
-use strum::IntoEnumIterator;
 
-use crate::app::state::{AppState, VibesMood};
-use super::super::theme::*;
-
-const MOOD_DESCS: &[&str] = &[
-    "Lo-fi beats, ambient sounds, slow tempo",
-    "High energy, bass drops, dance tracks",
-    "Instrumental, minimal vocals, concentration",
-    "Uplifting, positive vibes, sing-along",
-    "Deep, moody, atmospheric sounds",
-];
+use crate::app::state::AppState;
+use super::super::{text::{format_duration, viewport_offset}, theme::Theme};
 
 const EQ_CHARS: &[&str] = &["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
 
@@ -24,7 +15,7 @@ fn bar_char(height: u8) -> &'static str {
     EQ_CHARS[idx]
 }
 
-pub fn render_vibes(f: &mut Frame, area: Rect, state: &AppState) {
+pub fn render_vibes(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -33,56 +24,61 @@ pub fn render_vibes(f: &mut Frame, area: Rect, state: &AppState) {
         ])
         .split(area);
 
-    render_mood_panel(f, chunks[0], state);
-    render_recommendations(f, chunks[1], state);
+    render_mood_panel(f, chunks[0], state, theme);
+    render_recommendations(f, chunks[1], state, theme);
 }
 
-fn render_mood_panel(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_mood_panel(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let cols = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(area);
 
     // Mood selector
-    let moods: Vec<ListItem> = VibesMood::iter()
+    let moods: Vec<ListItem> = state.vibes.moods
+        .iter()
         .enumerate()
         .map(|(i, mood)| {
             let is_sel = i == state.vibes.selected_mood;
-            let desc = MOOD_DESCS[i];
+            let desc = &mood.description;
             let line = Line::from(vec![
-                Span::styled(if is_sel { "▶ " } else { "  " }, if is_sel { playing_style() } else { muted_style() }),
-                Span::styled(format!("[{}] ", i + 1), muted_style()),
-                Span::styled(mood.to_string(), if is_sel { hot_pink_style() } else { normal_style() }),
+                Span::styled(if is_sel { "▶ " } else { "  " }, if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Span::styled(format!("[{}] ", i + 1), theme.muted_style()),
+                Span::styled(mood.to_string(), if is_sel { theme.hot_pink_style() } else { theme.normal_style() }),
             ]);
             if is_sel {
                 ListItem::new(vec![
                     line,
                     Line::from(vec![
-                        Span::styled("    ", dim_style()),
-                        Span::styled(desc.to_string(), dim_style()),
+                        Span::styled("    ", theme.dim_style()),
+                        Span::styled(desc.to_string(), theme.dim_style()),
                     ]),
                 ])
-                .style(selected_style())
+                .style(theme.selected_style())
             } else {
                 ListItem::new(line)
             }
         })
         .collect();
 
+    let mood_count = moods.len();
     let mood_list = List::new(moods).block(
         Block::default()
-            .title(Span::styled(" 🌊 Select Your Vibe ", title_style()))
+            .title(Span::styled(" 🌊 Select Your Vibe ", theme.title_style()))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(border_style(true))
-            .style(normal_style()),
+            .border_style(theme.border_style(true))
+            .style(theme.normal_style()),
     );
-    f.render_widget(mood_list, cols[0]);
+    // The selected mood takes a second row for its description
+    let visible = cols[0].height.saturating_sub(3) as usize;
+    let offset = viewport_offset(state.vibes.selected_mood, mood_count, visible);
+    f.render_stateful_widget(mood_list, cols[0], &mut ListState::default().with_offset(offset));
 
     // EQ Visualization
     let colors = [
-        ACCENT, PRIMARY, HOT_PINK, NEON_GREEN, ACCENT, PRIMARY,
-        HOT_PINK, NEON_GREEN, ACCENT, PRIMARY, HOT_PINK, NEON_GREEN,
+        theme.accent, theme.primary, theme.hot_pink, theme.neon_green, theme.accent, theme.primary,
+        theme.hot_pink, theme.neon_green, theme.accent, theme.primary, theme.hot_pink, theme.neon_green,
     ];
     let eq_spans: Vec<Span> = state
         .eq_bars
@@ -97,41 +93,41 @@ fn render_mood_panel(f: &mut Frame, area: Rect, state: &AppState) {
         .collect();
 
     let eq_lines = vec![
-        Line::from(Span::styled("  Equalizer", dim_style())),
+        Line::from(Span::styled("  Equalizer", theme.dim_style())),
         Line::from(eq_spans),
-        Line::from(Span::styled("  ▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔", muted_style())),
+        Line::from(Span::styled("  ▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔", theme.muted_style())),
     ];
 
     let eq_block = Paragraph::new(eq_lines)
         .block(
             Block::default()
-                .title(Span::styled(" ≋ Live Equalizer ", title_style()))
+                .title(Span::styled(" ≋ Live Equalizer ", theme.title_style()))
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(border_style(false))
-                .style(normal_style()),
+                .border_style(theme.border_style(false))
+                .style(theme.normal_style()),
         )
         .alignment(Alignment::Center);
     f.render_widget(eq_block, cols[1]);
 }
 
-fn render_recommendations(f: &mut Frame, area: Rect, state: &AppState) {
+fn render_recommendations(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     if state.vibes.is_loading {
         let para = Paragraph::new(Line::from(Span::styled(
             "  ✨ Generating your vibe recommendations...",
-            dim_style(),
+            theme.dim_style(),
         )))
-        .block(make_block(" ✨ Recommendations ", true));
+        .block(make_block(theme, " ✨ Recommendations ", true));
         f.render_widget(para, area);
         return;
     }
 
     if state.vibes.recommendations.is_empty() {
         let para = Paragraph::new(vec![
-            Line::from(Span::styled("  Select a mood above and press", muted_style())),
-            Line::from(Span::styled("  Enter to generate recommendations!", accent_style())),
+            Line::from(Span::styled("  Select a mood above and press", theme.muted_style())),
+            Line::from(Span::styled("  Enter to generate recommendations!", theme.accent_style())),
         ])
-        .block(make_block(" ✨ Recommendations ", false));
+        .block(make_block(theme, " ✨ Recommendations ", false));
         f.render_widget(para, area);
         return;
     }
@@ -150,41 +146,47 @@ fn render_recommendations(f: &mut Frame, area: Rect, state: &AppState) {
                 .map(|a| a.name.clone())
                 .collect::<Vec<_>>()
                 .join(", ");
-            let dur_ms = track.duration.num_milliseconds() as u32;
-            let secs = dur_ms / 1000;
-            let dur = format!("{}:{:02}", secs / 60, secs % 60);
-            let prefix = if is_sel {
-                "▶ ".to_string()
-            } else {
-                format!("{:>2}. ", i + 1)
+            let dur = format_duration(track.duration.num_milliseconds() as u32);
+            let marked = state.is_marked(track.id.as_ref());
+            let prefix = match (is_sel, marked) {
+                (true, true) => "▶●".to_string(),
+                (true, false) => "▶ ".to_string(),
+                (false, true) => " ●  ".to_string(),
+                (false, false) => format!("{:>2}. ", i + 1),
             };
             let line = Line::from(vec![
-                Span::styled(prefix, if is_sel { playing_style() } else { muted_style() }),
-                Span::styled(track.name.clone(), if is_sel { selected_style() } else { normal_style() }),
-                Span::styled(" — ", muted_style()),
-                Span::styled(artist, dim_style()),
-                Span::styled(format!("  {dur}"), muted_style()),
+                Span::styled(prefix, if is_sel { theme.playing_style() } else { theme.muted_style() }),
+                Span::styled(track.name.clone(), if is_sel { theme.selected_style() } else { theme.normal_style() }),
+                Span::styled(if state.is_liked(track) { " ❤" } else { "" }, theme.gold_style()),
+                Span::styled(" — ", theme.muted_style()),
+                Span::styled(artist, theme.dim_style()),
+                Span::styled(format!("  {dur}"), theme.muted_style()),
             ]);
             if is_sel {
-                ListItem::new(line).style(selected_style())
+                ListItem::new(line).style(theme.selected_style())
             } else {
                 ListItem::new(line)
             }
         })
         .collect();
 
-    let list = List::new(items).block(make_block(
-        &format!(" ✨ Recommendations ({}) ", state.vibes.recommendations.len()),
+    let list = List::new(items).block(make_block(theme, 
+        &format!(
+            " ✨ Recommendations ({}) · {} ",
+            state.vibes.recommendations.len(),
+            if state.vibes.enqueueing { "queueing…" } else { "Q queues all" },
+        ),
         true,
     ));
-    f.render_widget(list, area);
+    let offset = viewport_offset(selected, state.vibes.recommendations.len(), area.height.saturating_sub(2) as usize);
+    f.render_stateful_widget(list, area, &mut ListState::default().with_offset(offset));
 }
 
-fn make_block(title: &str, focused: bool) -> Block<'static> {
+fn make_block(theme: &Theme, title: &str, focused: bool) -> Block<'static> {
     Block::default()
-        .title(Span::styled(title.to_string(), title_style()))
+        .title(Span::styled(title.to_string(), theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(border_style(focused))
-        .style(normal_style().bg(BG))
+        .border_style(theme.border_style(focused))
+        .style(theme.normal_style().bg(theme.bg))
 }
@@ -1,32 +1,63 @@
+pub mod anim;
 pub mod components;
+pub mod text;
 pub mod theme;
 
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
-use crate::app::state::{ActiveScreen, AppState};
-use self::theme::*;
+use crate::app::state::{ActiveScreen, AppState, Notification, Severity};
+use crate::config::{NotificationSettings, ToastCorner};
+use self::theme::Theme;
 use self::components::{
+    archive::render_archived,
+    browse::render_browse,
+    dedupe::render_dedupe,
+    devices::render_devices,
+    features::render_features,
     help::render_help,
+    inbox::render_inbox,
+    jobs::render_jobs,
     library::render_library,
+    logs::render_logs,
+    mood::render_mood,
+    now_playing::render_now_playing,
+    picker::render_playlist_picker,
     player_bar::render_player_bar,
-    playlists::render_playlists,
+    playlists::{render_playlist_diff, render_playlists},
+    prompt::render_prompt,
     queue::render_queue,
+    radar::render_radar,
     search::render_search,
+    search_history::render_search_history,
     sidebar::render_sidebar,
+    startup::render_startup,
+    stats::render_stats,
     vibes_screen::render_vibes,
 };
 
 /// Root render function — called every frame
 pub fn render(f: &mut Frame, state: &AppState) {
     let size = f.area();
+    let theme = &state.theme.tinted(state.accent_tint).for_depth(state.color_depth);
 
+    // ── Focus mode: Now Playing takes the whole screen ──────────────────
+    if state.focus {
+        render_now_playing(f, size, state, theme);
+    } else {
+        render_screens(f, size, state, theme);
+    }
+    render_overlays(f, size, state, theme);
+}
+
+/// Sidebar, the active screen and the player bar
+fn render_screens(f: &mut Frame, size: Rect, state: &AppState, theme: &Theme) {
     // ── Outer layout: content + player bar ──────────────────────────────
-    let player_height = if state.eq_expanded { 15 } else { 5 };
+    let player_height = player_height(state);
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -35,103 +66,312 @@ pub fn render(f: &mut Frame, state: &AppState) {
         ])
         .split(size);
 
-    // ── Top: sidebar + content ───────────────────────────────────────────
+    // ── Top: sidebar + content (mini mode: just the content) ─────────────
+    let sidebar_width = if state.mini { 0 } else { 24 };
     let top_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(24), // sidebar
-            Constraint::Min(0),     // main content
+            Constraint::Length(sidebar_width), // sidebar
+            Constraint::Min(0),                // main content
         ])
         .split(main_chunks[0]);
 
     // Render sidebar
-    render_sidebar(f, top_chunks[0], state);
+    if !state.mini {
+        render_sidebar(f, top_chunks[0], state, theme);
+    }
 
     // Render main content based on active screen
+    let content = top_chunks[1];
     match &state.active_screen {
-        ActiveScreen::Search    => render_search(f, top_chunks[1], state),
-        ActiveScreen::Library   => render_library(f, top_chunks[1], state),
-        ActiveScreen::Playlists => render_playlists(f, top_chunks[1], state),
-        ActiveScreen::Queue     => render_queue(f, top_chunks[1], state),
-        ActiveScreen::Vibes     => render_vibes(f, top_chunks[1], state),
+        ActiveScreen::Search    => render_search(f, content, state, theme),
+        ActiveScreen::Library   => render_library(f, content, state, theme),
+        ActiveScreen::Playlists => render_playlists(f, content, state, theme),
+        ActiveScreen::Queue     => render_queue(f, content, state, theme),
+        ActiveScreen::Vibes     => render_vibes(f, content, state, theme),
+        ActiveScreen::Stats     => render_stats(f, content, state, theme),
+        ActiveScreen::Browse    => render_browse(f, content, state, theme),
     }
 
     // Render player bar
-    render_player_bar(f, main_chunks[1], state);
+    render_player_bar(f, main_chunks[1], state, theme);
+}
+
+/// Popups and toasts, over whichever view is showing
+fn render_overlays(f: &mut Frame, size: Rect, state: &AppState, theme: &Theme) {
 
     // ── Auth screen overlay (if not authenticated) ────────────────────────
     if !state.is_authenticated {
-        render_auth_overlay(f, size, state);
+        render_auth_overlay(f, size, state, theme);
+    }
+
+    // ── Startup progress, under any popup ────────────────────────────────
+    if state.startup.is_some() && state.is_authenticated && !state.focus {
+        render_startup(f, size, state, theme);
     }
 
     // ── Help overlay ─────────────────────────────────────────────────────
     if state.show_help {
-        render_help(f, size, state);
+        render_help(f, size, state, theme);
+    }
+
+    // ── Feature flags popup ──────────────────────────────────────────────
+    if state.show_features {
+        render_features(f, size, state, theme);
+    }
+
+    // ── Playlist changes popup ────────────────────────────────────────────
+    if state.playlists.show_diff {
+        render_playlist_diff(f, size, state, theme);
+    }
+
+    // ── Inbox popup ───────────────────────────────────────────────────────
+    if state.inbox.open {
+        render_inbox(f, size, state, theme);
+    }
+
+    // ── Add-to-playlist picker ────────────────────────────────────────────
+    if state.picker.is_some() {
+        render_playlist_picker(f, size, state, theme);
+    }
+
+    // ── Library dedupe popup ──────────────────────────────────────────────
+    if state.dedupe.is_some() {
+        render_dedupe(f, size, state, theme);
+    }
+
+    // ── Archived playlists popup ──────────────────────────────────────────
+    if state.archived.is_some() {
+        render_archived(f, size, state, theme);
+    }
+
+    // ── Devices popup ─────────────────────────────────────────────────────
+    if state.devices.is_some() {
+        render_devices(f, size, state, theme);
+    }
+
+    // ── Log viewer ────────────────────────────────────────────────────────
+    if state.log_view.is_some() {
+        render_logs(f, size, state, theme);
+    }
+
+    // ── Search history popup ──────────────────────────────────────────────
+    if state.search.show_history {
+        render_search_history(f, size, state, theme);
+    }
+
+    // ── Jobs panel ────────────────────────────────────────────────────────
+    if state.show_jobs {
+        render_jobs(f, size, state, theme);
+    }
+
+    // ── Mood filter popup ─────────────────────────────────────────────────
+    if state.mood.is_some() {
+        render_mood(f, size, state, theme);
+    }
+
+    // ── Audio-features radar ──────────────────────────────────────────────
+    if state.radar.is_some() {
+        render_radar(f, size, state, theme);
+    }
+
+    // ── Text prompt popup ─────────────────────────────────────────────────
+    if state.prompt.is_some() {
+        render_prompt(f, size, state, theme);
+    }
+
+    // ── Error details popup ───────────────────────────────────────────────
+    if let Some(ref details) = state.error_details {
+        render_error_details(f, size, details, theme);
+    }
+
+    // ── Notification history (:messages) ─────────────────────────────────
+    if state.show_messages {
+        render_messages(f, size, state, theme);
     }
 
-    // ── Notification toast ────────────────────────────────────────────────
-    if let Some(ref notif) = state.notification {
-        render_notification(f, size, notif.is_error, &notif.message);
+    // ── Notification toasts, newest nearest the corner ───────────────────
+    for (slot, notif) in state.notifications.iter().rev().enumerate() {
+        render_notification(f, size, notif, slot as u16, &state.toast_settings, theme);
     }
 }
 
-fn render_auth_overlay(f: &mut Frame, area: Rect, state: &AppState) {
+fn player_height(state: &AppState) -> u16 {
+    if state.eq_expanded && !state.mini { 15 } else { 5 }
+}
+
+/// Rows of the main list that fit on screen for the active view, mirroring
+/// the layout in `render`. Used as the PageUp/PageDown step.
+pub fn page_size(area: Rect, state: &AppState) -> usize {
+    let content = area.height.saturating_sub(player_height(state));
+    let chrome = match state.active_screen {
+        ActiveScreen::Search => 3 + 2,  // input box + list borders
+        ActiveScreen::Vibes => 10 + 2,  // mood panel + list borders
+        ActiveScreen::Stats => 2 + 1 + components::stats::on_this_day_height(&state.stats),
+        ActiveScreen::Browse => 2,      // list borders
+        _ => 2 + 1,                     // table borders + header row
+    };
+    content.saturating_sub(chrome).max(1) as usize
+}
+
+fn render_auth_overlay(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
     let popup = centered_rect(70, 50, area);
     f.render_widget(Clear, popup);
 
     let block = Block::default()
-        .title(Span::styled(" 🎵 vibes — Spotify Auth ", title_style()))
+        .title(Span::styled(" 🎵 vibes — Spotify Auth ", theme.title_style()))
         .borders(Borders::ALL)
         .border_type(BorderType::Double)
-        .border_style(border_style(true))
-        .style(normal_style());
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style());
 
     let inner = block.inner(popup);
     f.render_widget(block, popup);
 
-    let lines = if let Some(ref url) = state.auth_url {
+    let lines = if let Some(ref error) = state.auth_error {
         vec![
             Line::from(Span::raw("")),
-            Line::from(Span::styled("  Opening browser for Spotify login...", accent_style())),
+            Line::from(Span::styled("  Not logged in", theme.error_style())),
+            Line::from(Span::raw("")),
+            Line::from(Span::styled(format!("  {error}"), theme.dim_style())),
+            Line::from(Span::raw("")),
+            Line::from(Span::styled("  r retry · c copy the login link · q quit", theme.accent_style())),
+        ]
+    } else if let Some(ref url) = state.auth_url {
+        let headline = if state.auth_expired {
+            Span::styled("  Spotify signed you out — opening the browser to log in again...", theme.gold_style())
+        } else {
+            Span::styled("  Opening browser for Spotify login...", theme.accent_style())
+        };
+        vec![
             Line::from(Span::raw("")),
-            Line::from(Span::styled("  If the browser didn't open, visit:", dim_style())),
+            Line::from(headline),
             Line::from(Span::raw("")),
-            Line::from(Span::styled(format!("  {url}"), hot_pink_style())),
+            Line::from(Span::styled("  If the browser didn't open, visit:", theme.dim_style())),
             Line::from(Span::raw("")),
-            Line::from(Span::styled("  Waiting for authorization...", dim_style())),
+            Line::from(Span::styled(format!("  {url}"), theme.hot_pink_style())),
             Line::from(Span::raw("")),
-            Line::from(Span::styled("  ⠋ Listening on http://127.0.0.1:8989/login", muted_style())),
+            Line::from(Span::styled("  Waiting for authorization...", theme.dim_style())),
+            Line::from(Span::raw("")),
+            Line::from(Span::styled(format!("  ⠋ Listening on {}", state.auth_redirect.as_deref().unwrap_or_default()), theme.muted_style())),
+            Line::from(Span::raw("")),
+            Line::from(Span::styled("  c copy the link · Esc cancel", theme.muted_style())),
         ]
     } else {
         vec![
             Line::from(Span::raw("")),
-            Line::from(Span::styled("  Connecting to Spotify...", accent_style())),
+            Line::from(Span::styled("  Connecting to Spotify...", theme.accent_style())),
         ]
     };
 
     f.render_widget(
-        Paragraph::new(lines).alignment(Alignment::Left),
+        Paragraph::new(lines).alignment(Alignment::Left).wrap(Wrap { trim: false }),
         inner,
     );
 }
 
-fn render_notification(f: &mut Frame, area: Rect, is_error: bool, message: &str) {
-    let toast_width = message.len().min(60) as u16 + 4;
-    let toast_area = Rect {
-        x: area.width.saturating_sub(toast_width + 2),
-        y: area.height.saturating_sub(8),
-        width: toast_width,
-        height: 3,
+fn render_error_details(f: &mut Frame, area: Rect, details: &str, theme: &Theme) {
+    let popup = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(Span::styled(" ✖ Error details ", theme.error_style()))
+        .title_bottom(Span::styled(" any key to close ", theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.error_style())
+        .style(theme.normal_style());
+
+    let lines: Vec<Line> = details
+        .lines()
+        .map(|l| Line::from(Span::styled(format!(" {l}"), theme.normal_style())))
+        .collect();
+    f.render_widget(
+        Paragraph::new(lines).block(block).wrap(Wrap { trim: false }),
+        popup,
+    );
+}
+
+/// `:messages` — the session's toasts, most recent first
+fn render_messages(f: &mut Frame, area: Rect, state: &AppState, theme: &Theme) {
+    let popup = centered_rect(70, 60, area);
+    f.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(Span::styled(" ✉ Messages ", theme.title_style()))
+        .title_bottom(Span::styled(" Esc to close ", theme.muted_style()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.border_style(true))
+        .style(theme.normal_style().bg(theme.surface));
+
+    let lines: Vec<Line> = if state.notification_history.is_empty() {
+        vec![Line::from(Span::styled("  Nothing yet.", theme.muted_style()))]
+    } else {
+        state
+            .notification_history
+            .iter()
+            .map(|(at, n)| {
+                let style = match n.severity {
+                    Severity::Info => theme.normal_style(),
+                    Severity::Warning => theme.gold_style(),
+                    Severity::Error => theme.error_style(),
+                };
+                Line::from(vec![
+                    Span::styled(format!(" {} ", at.format("%H:%M:%S")), theme.muted_style()),
+                    Span::styled(n.severity.icon(), style),
+                    Span::styled(n.message.clone(), style),
+                ])
+            })
+            .collect()
     };
+    f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: true }), popup);
+}
+
+/// One toast; `slot` 0 sits in the configured corner and later ones stack away from it
+fn render_notification(
+    f: &mut Frame,
+    area: Rect,
+    notif: &Notification,
+    slot: u16,
+    settings: &NotificationSettings,
+    theme: &Theme,
+) {
+    let mut hints = Vec::new();
+    if notif.retry.is_some() {
+        hints.push("⏎ retry");
+    }
+    if notif.details.is_some() {
+        hints.push("d details");
+    }
+    let hint = if hints.is_empty() { String::new() } else { format!("  [{}]", hints.join(" · ")) };
+    let message = notif.message.as_str();
+    let max_width = settings.width.max(10).min(area.width.saturating_sub(4));
+    let toast_width = ((message.chars().count() + hint.chars().count()) as u16 + 4).min(max_width);
+    let x = match settings.position {
+        ToastCorner::TopLeft | ToastCorner::BottomLeft => area.x + 2,
+        ToastCorner::TopRight | ToastCorner::BottomRight => area.width.saturating_sub(toast_width + 2),
+    };
+    let y = match settings.position {
+        ToastCorner::TopLeft | ToastCorner::TopRight => area.y + 1 + slot * 3,
+        // Bottom toasts sit above the player bar
+        ToastCorner::BottomLeft | ToastCorner::BottomRight => area.height.saturating_sub(8 + slot * 3),
+    };
+    let toast_area = Rect { x, y, width: toast_width, height: 3 }.intersection(area);
 
     f.render_widget(Clear, toast_area);
 
-    let style = if is_error { error_style() } else { playing_style() };
-    let icon = if is_error { "✖ " } else { "✔ " };
+    let style = match notif.severity {
+        Severity::Info => theme.playing_style(),
+        Severity::Warning => theme.gold_style(),
+        Severity::Error => theme.error_style(),
+    };
+    let icon = notif.severity.icon();
 
     let para = Paragraph::new(Line::from(vec![
         Span::styled(icon, style.clone()),
         Span::styled(message.to_string(), style),
+        Span::styled(hint, theme.muted_style()),
     ]))
     .block(
         Block::default()
@@ -144,7 +384,19 @@ fn render_notification(f: &mut Frame, area: Rect, is_error: bool, message: &str)
     f.render_widget(para, toast_area);
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+/// A `width` × `height` box in the middle of `area`, cut to fit
+pub(crate) fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+pub(crate) fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let vert = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
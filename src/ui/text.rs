@@ -0,0 +1,81 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// `m:ss` for a track length or position
+pub fn format_duration(ms: u32) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Cuts `s` to at most `max` terminal columns, ending in `…` when shortened.
+/// Counts display width, so CJK and emoji never overflow or split mid-char.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.width() <= max {
+        return s.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut out = String::new();
+    let mut width = 0;
+    for c in s.chars() {
+        let w = c.width().unwrap_or(0);
+        if width + w > max - 1 {
+            break;
+        }
+        out.push(c);
+        width += w;
+    }
+    out.push('…');
+    out
+}
+
+/// `max` columns of a looping ticker over `title`, starting `offset` chars in.
+/// Titles that already fit are returned unchanged.
+pub fn marquee(title: &str, offset: usize, max: usize) -> String {
+    if title.width() <= max {
+        return title.to_string();
+    }
+    let chars: Vec<char> = format!("{title}   ").chars().collect();
+    let start = offset % chars.len();
+    let mut out = String::new();
+    let mut width = 0;
+    for &c in chars[start..].iter().chain(&chars[..start]) {
+        let w = c.width().unwrap_or(0);
+        if width + w > max {
+            break;
+        }
+        out.push(c);
+        width += w;
+    }
+    out
+}
+
+/// First item to draw so `selected` stays on screen in a list showing `rows`
+/// items. The view only scrolls once the selection runs past the bottom.
+pub fn viewport_offset(selected: usize, len: usize, rows: usize) -> usize {
+    if rows == 0 || len <= rows {
+        return 0;
+    }
+    let selected = selected.min(len - 1);
+    (selected + 1).saturating_sub(rows).min(len - rows)
+}
+
+/// The `#` column of a track table: the row number right-aligned to `width`,
+/// `▶` on the highlighted row and `●` on rows marked in visual select
+pub fn row_number(n: usize, width: usize, selected: bool, marked: bool) -> String {
+    match (selected, marked) {
+        (true, true) => "▶●".to_string(),
+        (true, false) => "▶".to_string(),
+        (false, true) => format!("{:>width$}", "●"),
+        (false, false) => format!("{n:>width$}"),
+    }
+}
+
+/// Where `selected` sits among the rows a `/` filter leaves and how many
+/// there are, for `viewport_offset`; the whole list when nothing is hidden
+pub fn filtered_position(selected: usize, len: usize, visible: Option<&[usize]>) -> (usize, usize) {
+    match visible {
+        Some(rows) => (rows.partition_point(|&r| r < selected), rows.len()),
+        None => (selected, len),
+    }
+}
@@ -1,80 +1,382 @@
 use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::warn;
 
-// ─── Color Palette ───────────────────────────────────────────────────────────
-pub const BG:          Color = Color::Rgb(13,  13,  17);
-pub const SURFACE:     Color = Color::Rgb(28,  28,  40);
-pub const SURFACE_SEL: Color = Color::Rgb(40,  35,  65);
-
-pub const PRIMARY:     Color = Color::Rgb(155, 93,  229); // electric violet
-pub const ACCENT:      Color = Color::Rgb(0,   245, 255); // neon cyan
-pub const HOT_PINK:    Color = Color::Rgb(241, 91,  181); // hot pink
-pub const NEON_GREEN:  Color = Color::Rgb(0,   187, 249); // neon blue-green
-pub const GOLD:        Color = Color::Rgb(255, 210, 63);  // gold/liked
+use crate::artwork::Rgb;
 
-pub const TEXT:        Color = Color::Rgb(220, 220, 235);
-pub const TEXT_DIM:    Color = Color::Rgb(140, 140, 160);
-pub const TEXT_MUTED:  Color = Color::Rgb(80,  80,  100);
+/// Built-in presets selectable with `theme = "..."` in config.toml
+pub const PRESETS: &[&str] = &["default", "dracula", "gruvbox", "catppuccin", "nord"];
 
-pub const BORDER:      Color = Color::Rgb(50,  45,  80);
-pub const BORDER_FOCUSED: Color = PRIMARY;
+/// How many colors the terminal can show; `color_depth` in config.toml
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorDepth {
+    /// Worked out from `NO_COLOR`, `COLORTERM` and `TERM` at startup
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    /// The xterm 256-color palette
+    #[serde(rename = "256")]
+    Ansi256,
+    /// The 16 basic colors, as the terminal's own scheme draws them
+    #[serde(rename = "16")]
+    Ansi16,
+    /// Default foreground and background only; selection shows as reverse video
+    #[serde(rename = "none")]
+    NoColor,
+}
 
-pub const ERROR:       Color = Color::Rgb(255, 90,  90);
+impl ColorDepth {
+    /// `Auto` resolved against the environment; anything else as configured,
+    /// so an explicit setting wins over `NO_COLOR`
+    pub fn resolve(self) -> ColorDepth {
+        if self != ColorDepth::Auto {
+            return self;
+        }
+        let var = |name| std::env::var(name).ok();
+        ColorDepth::detect(var("NO_COLOR").as_deref(), var("COLORTERM").as_deref(), var("TERM").as_deref())
+    }
 
-// ─── Styles ──────────────────────────────────────────────────────────────────
-pub fn title_style() -> Style {
-    Style::default().fg(PRIMARY).add_modifier(Modifier::BOLD)
+    /// What the terminal supports, going by its environment variables
+    pub fn detect(no_color: Option<&str>, colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+        if no_color.is_some_and(|v| !v.is_empty()) {
+            return ColorDepth::NoColor;
+        }
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorDepth::TrueColor;
+        }
+        match term {
+            // Windows consoles don't set TERM but take RGB
+            None if cfg!(windows) => ColorDepth::TrueColor,
+            None | Some("" | "dumb") => ColorDepth::NoColor,
+            Some(t) if t.ends_with("-direct") || t.contains("truecolor") => ColorDepth::TrueColor,
+            Some(t) if t.contains("256") => ColorDepth::Ansi256,
+            Some(_) => ColorDepth::Ansi16,
+        }
+    }
 }
 
-pub fn accent_style() -> Style {
-    Style::default().fg(ACCENT)
-}
+/// The 16 basic colors as xterm draws them by default, to match RGB against
+const ANSI16: [(Color, Rgb); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
 
-pub fn selected_style() -> Style {
-    Style::default()
-        .bg(SURFACE_SEL)
-        .fg(ACCENT)
-        .add_modifier(Modifier::BOLD)
+/// Channel levels of the 6×6×6 cube at indexes 16–231
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): Rgb, (r2, g2, b2): Rgb) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
 }
 
-pub fn normal_style() -> Style {
-    Style::default().fg(TEXT)
+/// RGB of a 256-palette index; the first 16 are the basic colors
+fn indexed_rgb(i: u8) -> Rgb {
+    match i {
+        0..=15 => ANSI16[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[(i / 6 % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let v = 8 + 10 * (i - 232);
+            (v, v, v)
+        }
+    }
 }
 
-pub fn dim_style() -> Style {
-    Style::default().fg(TEXT_DIM)
+/// Nearest entry of the cube or the grey ramp. The first 16 are left out:
+/// terminal schemes redefine them, so they can't be relied on for a shade.
+fn nearest_256(rgb: Rgb) -> u8 {
+    let level = |c: u8| {
+        (0..6).min_by_key(|&i| (CUBE_LEVELS[i] as i32 - c as i32).abs()).unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = 232 + (avg.saturating_sub(3) / 10).min(23) as u8;
+    [cube, grey].into_iter().min_by_key(|&i| distance(rgb, indexed_rgb(i))).unwrap_or(cube)
 }
 
-pub fn muted_style() -> Style {
-    Style::default().fg(TEXT_MUTED)
+fn nearest_16(rgb: Rgb) -> Color {
+    ANSI16.iter().min_by_key(|(_, c)| distance(rgb, *c)).map_or(Color::Reset, |(color, _)| *color)
 }
 
-pub fn border_style(focused: bool) -> Style {
-    if focused {
-        Style::default().fg(BORDER_FOCUSED)
-    } else {
-        Style::default().fg(BORDER)
+/// `color` as the terminal can show it
+pub fn reduce(color: Color, depth: ColorDepth) -> Color {
+    match (depth, color) {
+        (ColorDepth::Auto | ColorDepth::TrueColor, _) => color,
+        (ColorDepth::NoColor, _) => Color::Reset,
+        (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256((r, g, b))),
+        (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_16((r, g, b)),
+        (ColorDepth::Ansi16, Color::Indexed(i)) => nearest_16(indexed_rgb(i)),
+        // Named colors are already in both palettes
+        _ => color,
     }
 }
 
-pub fn playing_style() -> Style {
-    Style::default().fg(NEON_GREEN).add_modifier(Modifier::BOLD)
+// ─── Color Palette ───────────────────────────────────────────────────────────
+/// Runtime palette, resolved once from config and passed to every render function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub bg: Color,
+    pub surface: Color,
+    pub surface_sel: Color,
+    pub primary: Color,
+    pub accent: Color,
+    pub hot_pink: Color,
+    pub neon_green: Color,
+    pub gold: Color,
+    pub text: Color,
+    pub text_dim: Color,
+    pub text_muted: Color,
+    pub border: Color,
+    pub error: Color,
 }
 
-pub fn hot_pink_style() -> Style {
-    Style::default().fg(HOT_PINK).add_modifier(Modifier::BOLD)
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            bg:          Color::Rgb(13,  13,  17),
+            surface:     Color::Rgb(28,  28,  40),
+            surface_sel: Color::Rgb(40,  35,  65),
+            primary:     Color::Rgb(155, 93,  229), // electric violet
+            accent:      Color::Rgb(0,   245, 255), // neon cyan
+            hot_pink:    Color::Rgb(241, 91,  181), // hot pink
+            neon_green:  Color::Rgb(0,   187, 249), // neon blue-green
+            gold:        Color::Rgb(255, 210, 63),  // gold/liked
+            text:        Color::Rgb(220, 220, 235),
+            text_dim:    Color::Rgb(140, 140, 160),
+            text_muted:  Color::Rgb(80,  80,  100),
+            border:      Color::Rgb(50,  45,  80),
+            error:       Color::Rgb(255, 90,  90),
+        }
+    }
 }
 
-pub fn gold_style() -> Style {
-    Style::default().fg(GOLD)
-}
+impl Theme {
+    pub fn preset(name: &str) -> Option<Theme> {
+        let theme = match name.to_ascii_lowercase().as_str() {
+            "default" | "vibes" => Theme::default(),
+            "dracula" => Theme {
+                bg:          Color::Rgb(40,  42,  54),
+                surface:     Color::Rgb(52,  55,  70),
+                surface_sel: Color::Rgb(68,  71,  90),
+                primary:     Color::Rgb(189, 147, 249),
+                accent:      Color::Rgb(139, 233, 253),
+                hot_pink:    Color::Rgb(255, 121, 198),
+                neon_green:  Color::Rgb(80,  250, 123),
+                gold:        Color::Rgb(241, 250, 140),
+                text:        Color::Rgb(248, 248, 242),
+                text_dim:    Color::Rgb(180, 182, 200),
+                text_muted:  Color::Rgb(98,  114, 164),
+                border:      Color::Rgb(68,  71,  90),
+                error:       Color::Rgb(255, 85,  85),
+            },
+            "gruvbox" => Theme {
+                bg:          Color::Rgb(40,  40,  40),
+                surface:     Color::Rgb(60,  56,  54),
+                surface_sel: Color::Rgb(80,  73,  69),
+                primary:     Color::Rgb(254, 128, 25),
+                accent:      Color::Rgb(142, 192, 124),
+                hot_pink:    Color::Rgb(211, 134, 155),
+                neon_green:  Color::Rgb(184, 187, 38),
+                gold:        Color::Rgb(250, 189, 47),
+                text:        Color::Rgb(235, 219, 178),
+                text_dim:    Color::Rgb(168, 153, 132),
+                text_muted:  Color::Rgb(124, 111, 100),
+                border:      Color::Rgb(80,  73,  69),
+                error:       Color::Rgb(251, 73,  52),
+            },
+            "catppuccin" => Theme {
+                bg:          Color::Rgb(30,  30,  46),
+                surface:     Color::Rgb(49,  50,  68),
+                surface_sel: Color::Rgb(69,  71,  90),
+                primary:     Color::Rgb(203, 166, 247),
+                accent:      Color::Rgb(137, 220, 235),
+                hot_pink:    Color::Rgb(245, 194, 231),
+                neon_green:  Color::Rgb(166, 227, 161),
+                gold:        Color::Rgb(249, 226, 175),
+                text:        Color::Rgb(205, 214, 244),
+                text_dim:    Color::Rgb(166, 173, 200),
+                text_muted:  Color::Rgb(108, 112, 134),
+                border:      Color::Rgb(88,  91,  112),
+                error:       Color::Rgb(243, 139, 168),
+            },
+            "nord" => Theme {
+                bg:          Color::Rgb(46,  52,  64),
+                surface:     Color::Rgb(59,  66,  82),
+                surface_sel: Color::Rgb(67,  76,  94),
+                primary:     Color::Rgb(136, 192, 208),
+                accent:      Color::Rgb(143, 188, 187),
+                hot_pink:    Color::Rgb(180, 142, 173),
+                neon_green:  Color::Rgb(163, 190, 140),
+                gold:        Color::Rgb(235, 203, 139),
+                text:        Color::Rgb(236, 239, 244),
+                text_dim:    Color::Rgb(216, 222, 233),
+                text_muted:  Color::Rgb(97,  110, 136),
+                border:      Color::Rgb(76,  86,  106),
+                error:       Color::Rgb(191, 97,  106),
+            },
+            _ => return None,
+        };
+        Some(theme)
+    }
 
-pub fn error_style() -> Style {
-    Style::default().fg(ERROR).add_modifier(Modifier::BOLD)
-}
+    /// Preset by name plus per-color overrides from the `[colors]` table.
+    /// Unknown presets fall back to the default palette; bad entries are skipped.
+    pub fn from_config(name: &str, colors: &BTreeMap<String, String>) -> Theme {
+        let mut theme = Theme::preset(name).unwrap_or_else(|| {
+            warn!("Unknown theme {name:?}, expected one of {PRESETS:?}");
+            Theme::default()
+        });
+        for (key, value) in colors {
+            match value.parse::<Color>() {
+                Ok(color) => {
+                    if !theme.set(key, color) {
+                        warn!("Unknown color in [colors]: {key}");
+                    }
+                }
+                Err(_) => warn!("Invalid color for {key} in [colors]: {value}"),
+            }
+        }
+        theme
+    }
+
+    /// Sets a palette slot by its config name; false if there's no such slot
+    pub fn set(&mut self, key: &str, color: Color) -> bool {
+        let slot = match key {
+            "bg" => &mut self.bg,
+            "surface" => &mut self.surface,
+            "surface_sel" => &mut self.surface_sel,
+            "primary" => &mut self.primary,
+            "accent" => &mut self.accent,
+            "hot_pink" => &mut self.hot_pink,
+            "neon_green" => &mut self.neon_green,
+            "gold" => &mut self.gold,
+            "text" => &mut self.text,
+            "text_dim" => &mut self.text_dim,
+            "text_muted" => &mut self.text_muted,
+            "border" => &mut self.border,
+            "error" => &mut self.error,
+            _ => return false,
+        };
+        *slot = color;
+        true
+    }
+
+    /// This palette with `primary` replaced by the album-art accent, if any
+    pub fn tinted(&self, tint: Option<Rgb>) -> Theme {
+        let mut theme = *self;
+        if let Some((r, g, b)) = tint {
+            theme.primary = Color::Rgb(r, g, b);
+        }
+        theme
+    }
+
+    /// Every slot brought down to what the terminal can show
+    pub fn for_depth(&self, depth: ColorDepth) -> Theme {
+        let r = |color| reduce(color, depth);
+        Theme {
+            bg: r(self.bg),
+            surface: r(self.surface),
+            surface_sel: r(self.surface_sel),
+            primary: r(self.primary),
+            accent: r(self.accent),
+            hot_pink: r(self.hot_pink),
+            neon_green: r(self.neon_green),
+            gold: r(self.gold),
+            text: r(self.text),
+            text_dim: r(self.text_dim),
+            text_muted: r(self.text_muted),
+            border: r(self.border),
+            error: r(self.error),
+        }
+    }
+
+    // ─── Styles ──────────────────────────────────────────────────────────────
+    pub fn title_style(&self) -> Style {
+        Style::default().fg(self.primary).add_modifier(Modifier::BOLD)
+    }
 
-pub fn header_style() -> Style {
-    Style::default()
-        .fg(BG)
-        .bg(PRIMARY)
-        .add_modifier(Modifier::BOLD)
+    pub fn accent_style(&self) -> Style {
+        Style::default().fg(self.accent)
+    }
+
+    /// Reverse video when the selection background came out the same as the
+    /// surface, as it does with 16 colors or none
+    pub fn selected_style(&self) -> Style {
+        let style = Style::default()
+            .bg(self.surface_sel)
+            .fg(self.accent)
+            .add_modifier(Modifier::BOLD);
+        if self.surface_sel == self.surface {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
+
+    pub fn normal_style(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    pub fn dim_style(&self) -> Style {
+        Style::default().fg(self.text_dim)
+    }
+
+    pub fn muted_style(&self) -> Style {
+        Style::default().fg(self.text_muted)
+    }
+
+    pub fn border_style(&self, focused: bool) -> Style {
+        if focused {
+            Style::default().fg(self.primary)
+        } else {
+            Style::default().fg(self.border)
+        }
+    }
+
+    pub fn playing_style(&self) -> Style {
+        Style::default().fg(self.neon_green).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn hot_pink_style(&self) -> Style {
+        Style::default().fg(self.hot_pink).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn gold_style(&self) -> Style {
+        Style::default().fg(self.gold)
+    }
+
+    pub fn error_style(&self) -> Style {
+        Style::default().fg(self.error).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn header_style(&self) -> Style {
+        let style = Style::default()
+            .fg(self.bg)
+            .bg(self.primary)
+            .add_modifier(Modifier::BOLD);
+        if self.bg == self.primary {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        }
+    }
 }
@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    io::Write,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use crate::{ipc::Command, session::{write_atomic, NowPlaying}, ui::text::format_duration};
+
+/// How often the session file is checked for a newer version
+const POLL: Duration = Duration::from_millis(500);
+
+/// What `vibes status` and `status_file` show unless `status_format` says otherwise
+pub const DEFAULT_STATUS_FORMAT: &str = "{icon} {artist} – {title} {progress}/{duration}";
+
+/// What `vibes widget --format` prints for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetFormat {
+    /// One JSON object per line, for a `custom` module with `return-type: json`
+    Waybar,
+    /// Plain text with click actions, for a `custom/script` module with `tail = true`
+    Polybar,
+}
+
+impl FromStr for WidgetFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "waybar" => Ok(WidgetFormat::Waybar),
+            "polybar" => Ok(WidgetFormat::Polybar),
+            other => bail!("Unknown widget format {other:?}, expected waybar or polybar"),
+        }
+    }
+}
+
+/// A bar click, sent on to the running vibes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Click {
+    Toggle,
+    Next,
+    Prev,
+}
+
+impl Click {
+    /// The control-socket command it's sent as
+    pub fn command(self) -> Command {
+        match self {
+            Click::Toggle => Command::Toggle,
+            Click::Next => Command::Next,
+            Click::Prev => Command::Prev,
+        }
+    }
+}
+
+impl FromStr for Click {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "toggle" => Ok(Click::Toggle),
+            "next" => Ok(Click::Next),
+            "prev" | "previous" => Ok(Click::Prev),
+            other => bail!("Unknown click action {other:?}, expected toggle, next or prev"),
+        }
+    }
+}
+
+/// Only the part of the session file a bar shows
+#[derive(Deserialize)]
+struct SessionFile {
+    now_playing: Option<NowPlaying>,
+}
+
+fn state(now: Option<&NowPlaying>) -> &'static str {
+    match now {
+        Some(np) if np.is_playing => "playing",
+        Some(_) => "paused",
+        None => "stopped",
+    }
+}
+
+/// Waybar reads the text as Pango markup
+fn escape_markup(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// The bar's line for what's playing; empty text when nothing is
+pub fn render(format: WidgetFormat, now: Option<&NowPlaying>) -> String {
+    let label = now.map(|np| format!("{} – {}", np.artists.join(", "), np.name));
+    match format {
+        WidgetFormat::Waybar => {
+            let percentage = now.filter(|np| np.duration_ms > 0).map_or(0, |np| {
+                (np.progress_ms as u64 * 100 / np.duration_ms as u64).min(100)
+            });
+            let tooltip = now.map(|np| format!("{}\n{}\n{}", np.name, np.artists.join(", "), np.album));
+            json!({
+                "text": escape_markup(&label.unwrap_or_default()),
+                "alt": state(now),
+                "class": state(now),
+                "tooltip": escape_markup(&tooltip.unwrap_or_default()),
+                "percentage": percentage,
+            })
+            .to_string()
+        }
+        WidgetFormat::Polybar => {
+            let Some(label) = label else { return String::new() };
+            let icon = if now.is_some_and(|np| np.is_playing) { "▶" } else { "⏸" };
+            // Left click toggles, scrolling skips; `%` would start a format tag
+            format!(
+                "%{{A1:vibes widget click toggle:}}%{{A4:vibes widget click prev:}}%{{A5:vibes widget click next:}}{icon} {}%{{A}}%{{A}}%{{A}}",
+                label.replace('%', "%%")
+            )
+        }
+    }
+}
+
+/// Fills in a status line: `{artist}`, `{title}`, `{album}`, `{progress}`,
+/// `{duration}`, `{state}` (`playing`, `paused` or `stopped`), `{icon}` and
+/// `{volume}`. Empty when nothing is playing, so a bar shows nothing. The
+/// template is read once, so a title with braces in it is left as it is;
+/// unknown `{…}` stay too.
+pub fn status_line(template: &str, now: Option<&NowPlaying>, volume: u8) -> String {
+    let Some(np) = now else { return String::new() };
+    let field = |name: &str| match name {
+        "artist" => Some(np.artists.join(", ")),
+        "title" => Some(np.name.clone()),
+        "album" => Some(np.album.clone()),
+        "progress" => Some(format_duration(np.progress_ms)),
+        "duration" => Some(format_duration(np.duration_ms)),
+        "state" => Some(state(now).to_string()),
+        "icon" => Some(if np.is_playing { "▶" } else { "⏸" }.to_string()),
+        "volume" => Some(volume.to_string()),
+        _ => None,
+    };
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+        match rest.find('}').and_then(|close| Some((close, field(&rest[1..close])?))) {
+            Some((close, value)) => {
+                out.push_str(&value);
+                rest = &rest[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out.trim().to_string()
+}
+
+/// Puts `line` in `status_file`. A regular file is replaced whole, so a
+/// reader never sees half a line. A FIFO gets the line down the pipe while
+/// a bar is reading it; with none reading, or one that's fallen behind, the
+/// line is dropped rather than waited on.
+pub fn write_status(path: &Path, line: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo()) {
+            if let Ok(pipe) = tokio::net::unix::pipe::OpenOptions::new().open_sender(path) {
+                let _ = pipe.try_write(format!("{line}\n").as_bytes());
+            }
+            return Ok(());
+        }
+    }
+    write_atomic(path, format!("{line}\n"))
+}
+
+fn read_now_playing(path: &Path) -> Option<NowPlaying> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str::<SessionFile>(&raw).ok()?.now_playing
+}
+
+/// Prints a line whenever the running vibes rewrites the session file with
+/// something that shows differently, until the bar stops reading
+pub fn follow(path: &Path, format: WidgetFormat) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    let mut seen: Option<SystemTime> = None;
+    let mut last: Option<String> = None;
+    loop {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        if last.is_none() || modified != seen {
+            seen = modified;
+            let now = modified.and_then(|_| read_now_playing(path));
+            let line = render(format, now.as_ref());
+            if last.as_ref() != Some(&line) {
+                writeln!(stdout, "{line}").and_then(|_| stdout.flush()).context("Bar stopped reading")?;
+                last = Some(line);
+            }
+        }
+        std::thread::sleep(POLL);
+    }
+}